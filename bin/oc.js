@@ -9,7 +9,7 @@ const config = require('../src/core/config');
 const { syncAgentsArtifacts } = require('../src/core/agents');
 const { startServer: startMcpServer } = require('../src/mcp/server');
 const { createUiServer } = require('../src/ui/server');
-const { Indexer, Searcher } = require('../src/core/search');
+const { Indexer, Searcher, FederatedSearcher, RemoteSearcher, createSearcher } = require('../src/core/search');
 
 const program = new Command();
 program.name('oc').description('OpenContext CLI').showHelpAfterError();
@@ -139,6 +139,7 @@ function handle(action) {
       await action(...args);
     } catch (err) {
       console.error(`Error: ${err.message}`);
+      if (err.hint) console.error(`Hint: ${err.hint}`);
       process.exitCode = 1;
     }
   };
@@ -332,15 +333,37 @@ folderCmd
     })
   );
 
+function siblingPath(relPath, newName) {
+  const trimmed = relPath.replace(/^\/+|\/+$/g, '');
+  const idx = trimmed.lastIndexOf('/');
+  return idx === -1 ? newName : `${trimmed.slice(0, idx)}/${newName}`;
+}
+
+function printLinkRewrites(rewrites, dryRun) {
+  if (!rewrites || rewrites.length === 0) {
+    if (dryRun) console.log('No links need rewriting.');
+    return;
+  }
+  const docs = new Set(rewrites.map((r) => r.rel_path));
+  console.log(`${dryRun ? 'Would rewrite' : 'Rewrote'} ${rewrites.length} link(s) in ${docs.size} document(s):`);
+  rewrites.forEach((r) => console.log(`  ${r.rel_path}:${r.line}  ${r.old_target} → ${r.new_target}`));
+}
+
 folderCmd
   .command('rename')
   .argument('<old_path>', 'Existing folder path')
   .argument('<new_name>', 'New folder name (single segment)')
-  .description('Rename a folder')
+  .option('--dry-run', 'Only show the links that would be rewritten')
+  .description('Rename a folder, rewriting links to the documents inside it')
   .action(
-    handle((oldPath, newName) => {
+    handle((oldPath, newName, options) => {
+      if (options.dryRun) {
+        printLinkRewrites(store.previewLinkRewrites({ oldPath, newPath: siblingPath(oldPath, newName) }), true);
+        return;
+      }
       const result = store.renameFolder({ path: oldPath, newName });
       console.log(`Renamed folder "${result.old_path}" → "${result.new_path}".`);
+      printLinkRewrites(result.links_rewritten, false);
     })
   );
 
@@ -397,11 +420,20 @@ docCmd
   .command('mv')
   .argument('<doc_path>', 'Existing document path')
   .argument('<dest_folder_path>', 'Destination folder path')
-  .description('Move a document to another folder')
+  .option('--dry-run', 'Only show the links that would be rewritten')
+  .description('Move a document to another folder, rewriting links to it')
   .action(
-    handle((docPath, destFolderPath) => {
+    handle((docPath, destFolderPath, options) => {
+      if (options.dryRun) {
+        const name = docPath.replace(/\/+$/, '').split('/').pop();
+        const dest = destFolderPath.replace(/^\/+|\/+$/g, '');
+        const newPath = dest ? `${dest}/${name}` : name;
+        printLinkRewrites(store.previewLinkRewrites({ oldPath: docPath, newPath }), true);
+        return;
+      }
       const result = store.moveDoc({ docPath, destFolderPath });
       console.log(`Moved doc "${result.old_path}" → "${result.new_path}".`);
+      printLinkRewrites(result.links_rewritten, false);
     })
   );
 
@@ -409,11 +441,17 @@ docCmd
   .command('rename')
   .argument('<doc_path>', 'Existing document path')
   .argument('<new_name>', 'New document name')
-  .description('Rename a document')
+  .option('--dry-run', 'Only show the links that would be rewritten')
+  .description('Rename a document, rewriting links to it')
   .action(
-    handle((docPath, newName) => {
+    handle((docPath, newName, options) => {
+      if (options.dryRun) {
+        printLinkRewrites(store.previewLinkRewrites({ oldPath: docPath, newPath: siblingPath(docPath, newName) }), true);
+        return;
+      }
       const result = store.renameDoc({ docPath, newName });
       console.log(`Renamed doc "${result.old_path}" → "${result.new_path}".`);
+      printLinkRewrites(result.links_rewritten, false);
     })
   );
 
@@ -440,6 +478,33 @@ docCmd
     })
   );
 
+docCmd
+  .command('field')
+  .argument('<doc_path>', 'Document path')
+  .argument('[key]', 'Frontmatter field (omit to print all fields)')
+  .argument('[value]', 'New value; JSON literals like true, 3 or ["a","b"] keep their type')
+  .description('Read or set frontmatter fields of a document')
+  .action(
+    handle((docPath, key, value) => {
+      if (!key) {
+        console.log(JSON.stringify(store.getDocFields(docPath), null, 2));
+        return;
+      }
+      if (value === undefined) {
+        console.log(JSON.stringify(store.getDocField(docPath, key)));
+        return;
+      }
+      let parsed = value;
+      try {
+        parsed = JSON.parse(value);
+      } catch {
+        // plain string
+      }
+      const result = store.setDocField({ docPath, key, value: parsed });
+      console.log(`Set ${key} on "${result.rel_path}".`);
+    })
+  );
+
 docCmd
   .command('id')
   .argument('<doc_path>', 'Existing document path')
@@ -509,6 +574,45 @@ docCmd
     })
   );
 
+program
+  .command('open')
+  .argument('<name...>', 'Title, alias or file name of the note (close spellings work)')
+  .option('--print', 'Print the document path instead of opening it')
+  .option('--list', 'List every matching note')
+  .option('--json', 'Print matches as JSON')
+  .description('Open a note by its human-readable name in $EDITOR')
+  .action(
+    handle((words, options) => {
+      const name = words.join(' ');
+      const matches = store.resolveNote(name, options.list || options.json ? 20 : 5);
+      if (options.json) {
+        console.log(JSON.stringify(matches, null, 2));
+        return;
+      }
+      if (matches.length === 0) {
+        const err = new Error(`No note called "${name}"`);
+        err.hint = `Search the contents instead: oc search "${name}"`;
+        throw err;
+      }
+      const [best, next] = matches;
+      const tied = next && next.score === best.score && best.score < 1;
+      if (options.list || tied) {
+        if (tied) console.log(`Several notes match "${name}":`);
+        for (const m of matches) {
+          console.log(`  ${m.rel_path}  (${m.kind}: ${m.name})`);
+        }
+        if (tied) process.exitCode = 1;
+        return;
+      }
+      const doc = store.getDocMeta({ docPath: best.rel_path });
+      if (options.print) {
+        console.log(doc.rel_path);
+        return;
+      }
+      openInEditor(doc.abs_path);
+    })
+  );
+
 program
   .command('context')
   .description('Context utilities')
@@ -638,20 +742,66 @@ configCmd
 // ===== Index commands =====
 const indexCmd = program.command('index').description('Search index operations');
 
+function printIndexPlan(plan) {
+  const label = plan.mode === 'full' ? `Full rebuild (${plan.reason})` : plan.mode === 'dirty' ? 'Dirty update' : 'Incremental update';
+  console.log(`🔎 Dry run: ${label}, nothing will be written`);
+  const marks = { add: '+', modify: '~', delete: '-' };
+  for (const f of plan.files) {
+    const detail = f.action === 'delete' ? '' : `  (${f.chunks} chunks, ~${f.tokens} tokens)`;
+    console.log(`   ${marks[f.action]} ${f.relPath}${detail}`);
+  }
+  const c = plan.changes;
+  console.log(`\n📊 ${c.added} to add, ${c.modified} to re-embed, ${c.deleted} to delete, ${c.unchanged} unchanged`);
+  printCostEstimate(plan.estimate);
+}
+
+function printCostEstimate(estimate) {
+  console.log(`   Chunks: ${estimate.chunks}, embedding inputs: ${estimate.embeddingInputs}`);
+  const cost = estimate.estimatedCostUsd == null
+    ? 'cost unknown (set embedding.price_per_million_tokens)'
+    : `~$${estimate.estimatedCostUsd.toFixed(4)}`;
+  console.log(`   Tokens: ~${estimate.estimatedTokens} with ${estimate.model} (${estimate.tokenizer}), ${cost}`);
+  const others = estimate.tokens.filter((t) => t.tokenizer !== estimate.tokenizer);
+  if (others.length > 0) {
+    console.log(`   Other tokenizers: ${others.map((t) => `${t.tokenizer} ~${t.tokens}`).join(', ')}`);
+  }
+  if (estimate.llmCalls > 0) {
+    console.log(`   Contextual retrieval: ${estimate.llmCalls} LLM calls`);
+  }
+}
+
 indexCmd
   .command('build')
   .option('-f, --folder <folder>', 'Limit indexing to a specific folder')
   .option('--force', 'Force full rebuild (ignore incremental)')
+  .option('--dry-run', 'Show what would be chunked, embedded and deleted without writing anything')
   .description('Build search index for semantic search (supports incremental updates)')
   .action(
     handle(async (options) => {
       const indexer = new Indexer();
-      
+      if (options.dryRun) {
+        printIndexPlan(indexer.planIndex({ folder: options.folder, force: options.force }));
+        return;
+      }
+
+      // First Ctrl-C stops after the current batch and keeps what was
+      // written; a second one exits immediately
+      let cancelled = false;
+      const onSignal = () => {
+        if (cancelled) process.exit(130);
+        cancelled = true;
+        process.stdout.write('\n⏸  Stopping after the current batch (Ctrl-C again to force)...\n');
+        indexer.cancel();
+      };
+      process.on('SIGINT', onSignal);
+      process.on('SIGTERM', onSignal);
+
       const stats = await indexer.buildIndex({
         folder: options.folder,
         force: options.force,
         onProgress: (() => {
           let embStart = 0;
+          let embFirst = 0;
           let lastEta = '?';
           let spinner = 0;
           const SPIN = ['⠋','⠙','⠹','⠸','⠼','⠴','⠦','⠧','⠇','⠏'];
@@ -674,17 +824,25 @@ indexCmd
               process.stdout.write('\r✂️  Chunking done        \n');
             }
           } else if (progress.phase === 'embedding') {
+            // current/total count documents written; the first report
+            // starts the clock
             const cur = progress.current, tot = progress.total;
-            if (cur === 1) embStart = Date.now();
+            if (!embStart) {
+              embStart = Date.now();
+              embFirst = cur;
+            }
             const pct = Math.round((cur / tot) * 100);
             const elapsed = (Date.now() - embStart) / 1000;
-            const rate = cur > 1 ? ((cur - 1) / elapsed).toFixed(1) : null;
-            const eta = cur > 1 ? Math.round((elapsed / (cur - 1)) * (tot - cur)) : null;
+            const done = cur - embFirst;
+            const rate = done > 0 ? (done / elapsed).toFixed(1) : null;
+            const eta = done > 0 ? Math.round((elapsed / done) * (tot - cur)) : null;
             lastEta = eta !== null ? `${eta}s` : '?';
             const rateStr = rate ? ` ${rate}/s` : '';
             const s = SPIN[spinner++ % SPIN.length];
             process.stdout.write(`\r🧠 ${s} [${bar(cur, tot)}] ${cur}/${tot} (${pct}%)${rateStr} ETA ${lastEta}   `);
             if (cur === tot) process.stdout.write('\r🧠 ✓ Embeddings done                                          \n');
+          } else if (progress.phase === 'interrupted') {
+            console.log(`\n⏸  ${progress.message}`);
           } else if (progress.phase === 'done') {
             if (progress.noChanges) {
               console.log('✅ No changes, index is up to date!');
@@ -695,6 +853,14 @@ indexCmd
         };
         })()
       });
+      process.off('SIGINT', onSignal);
+      process.off('SIGTERM', onSignal);
+
+      if (stats.interrupted) {
+        console.log('\n⏸  Index build interrupted. Run `oc index build` again to resume.');
+        process.exitCode = 130;
+        return;
+      }
 
       const modeLabel = stats.mode === 'full' ? 'Full' : 'Incremental';
       console.log(`\n📊 ${modeLabel} stats: ${stats.fileCount} files, ${stats.chunkCount} chunks`);
@@ -702,6 +868,82 @@ indexCmd
         const c = stats.changes;
         console.log(`   Changes: +${c.added} added, ~${c.modified} modified, -${c.deleted} deleted`);
       }
+      console.log(`   Tasks: ${store.reindexTasks()} checkbox items`);
+      console.log(`   Dates: ${store.reindexDates()} mentions`);
+    })
+  );
+
+indexCmd
+  .command('estimate')
+  .argument('[paths...]', 'Files or folders to estimate (default: everything)')
+  .option('--json', 'Print JSON')
+  .description('Estimate chunks, tokens and embedding cost for indexing files, without indexing them')
+  .action(
+    handle((paths, options) => {
+      const estimate = new Indexer().estimateIndexCost(paths.length > 0 ? paths : undefined);
+      if (options.json) {
+        console.log(JSON.stringify(estimate, null, 2));
+        return;
+      }
+      console.log(`📄 ${estimate.files} file(s)`);
+      printCostEstimate(estimate);
+    })
+  );
+
+indexCmd
+  .command('update')
+  .option('--dirty', 'Re-index only the files `oc status` lists')
+  .option('--dry-run', 'Show what would be re-embedded and deleted without writing anything')
+  .description('Bring the search index up to date with files changed since the last build')
+  .action(
+    handle(async (options) => {
+      if (!options.dirty) {
+        console.log('Only `oc index update --dirty` is supported; use `oc index build` for a full pass.');
+        return;
+      }
+      if (options.dryRun) {
+        printIndexPlan(new Indexer().planIndex({ dirty: true }));
+        return;
+      }
+      const status = store.indexStatus();
+      if (status.recorded && status.new.length + status.modified.length + status.deleted.length + status.renamed.length === 0) {
+        console.log('✅ Nothing to update, index is up to date.');
+        return;
+      }
+      const indexer = new Indexer();
+      const result = await indexer.updateDirty();
+      console.log(`✅ Index updated: ${result.indexed} indexed, ${result.removed} removed, ${result.renamed} renamed (${result.chunks} chunks)`);
+    })
+  );
+
+program
+  .command('status')
+  .option('--json', 'Print JSON')
+  .description('Show files that are new, modified or deleted relative to the search index')
+  .action(
+    handle((options) => {
+      const status = store.indexStatus();
+      if (options.json) {
+        console.log(JSON.stringify(status, null, 2));
+        return;
+      }
+      if (!status.recorded) {
+        console.log('ℹ️  No index manifest yet. Run `oc index build` to record one.');
+        return;
+      }
+      const lines = [
+        ...status.new.map((p) => `   new:      ${p}`),
+        ...status.modified.map((p) => `   modified: ${p}`),
+        ...status.deleted.map((p) => `   deleted:  ${p}`),
+        ...status.renamed.map((r) => `   renamed:  ${r.old_path} → ${r.new_path}`),
+      ];
+      if (lines.length === 0) {
+        console.log(`✅ Index is up to date (${status.tracked} files)`);
+        return;
+      }
+      console.log(`${lines.length} file(s) changed since the last index update:`);
+      for (const line of lines) console.log(line);
+      console.log('\nRun `oc index update --dirty` to re-index them.');
     })
   );
 
@@ -744,9 +986,15 @@ indexCmd
 
       let total = 0;
       for (const f of folders) {
+        // An earlier pass may have picked up this folder being moved on disk
+        if (!folder && !store.listFolders({ all: false }).some((x) => x.rel_path === f)) continue;
         const result = store.reconcileFolder({ folderPath: f });
         const added = result.added ?? result ?? [];
         const removed = result.removed ?? [];
+        for (const m of result.folders_moved ?? []) {
+          console.log(`📦 Folder moved on disk: ${m.old_path} → ${m.new_path}`);
+          printLinkRewrites(m.links_rewritten, false);
+        }
         if (added.length === 0 && removed.length === 0) {
           console.log(`✅ ${f}: nothing to reconcile`);
         } else {
@@ -822,15 +1070,787 @@ indexCmd
     })
   );
 
+// ===== Workspace archives =====
+const workspaceCmd = program.command('workspace').description('Move a whole workspace between machines');
+
+workspaceCmd
+  .command('export')
+  .argument('<file>', 'Archive to write, e.g. workspace.tar.zst')
+  .option('--with-index', 'Bundle the search index so the target does not have to re-embed')
+  .description('Export docs, descriptions, stable ids and config (API keys removed) to a .tar.zst archive')
+  .action(
+    handle((file, options) => {
+      const native = require('../src/core/native');
+      const dest = path.resolve(file);
+      const manifest = native.get().exportWorkspace({ path: dest, includeIndex: Boolean(options.withIndex) });
+      console.log(`📦 Exported ${manifest.docs} doc(s) in ${manifest.folders} folder(s) to ${dest}`);
+      if (manifest.index.length > 0) {
+        console.log(`   Index included: ${manifest.index.join(', ')}`);
+      }
+    })
+  );
+
+workspaceCmd
+  .command('import')
+  .argument('<file>', 'Archive written by `oc workspace export`')
+  .option('--overwrite', 'Replace local files and index data that the archive also contains')
+  .option('--no-index', 'Ignore a bundled search index')
+  .description('Import a workspace archive; existing docs, config values and indexes are kept unless --overwrite')
+  .action(
+    handle((file, options) => {
+      const native = require('../src/core/native');
+      const report = native.get().importWorkspace({
+        path: path.resolve(file),
+        overwrite: Boolean(options.overwrite),
+        includeIndex: options.index,
+      });
+      console.log(`📥 Imported ${report.files} file(s), registered ${report.registered_docs} new doc(s)`);
+      if (report.skipped_files.length > 0) {
+        console.log(`   Kept ${report.skipped_files.length} existing file(s) (use --overwrite to replace)`);
+      }
+      if (report.index_restored.length > 0) {
+        console.log(`   Index restored: ${report.index_restored.join(', ')}`);
+      } else if (report.registered_docs > 0) {
+        console.log('   Run `oc index build` to compute embeddings for the imported docs.');
+      }
+      if (report.index_skipped.length > 0) {
+        console.log(`   Kept local index: ${report.index_skipped.join(', ')}`);
+      }
+    })
+  );
+
+// ===== Shell sessions =====
+const sessionsCmd = program.command('sessions').description('Shell history and terminal recordings as searchable notes');
+
+/** History file of the current shell */
+function defaultHistoryFile() {
+  if (process.env.HISTFILE) return process.env.HISTFILE;
+  const home = require('os').homedir();
+  const shell = path.basename(process.env.SHELL || '');
+  if (shell === 'fish') return path.join(home, '.local', 'share', 'fish', 'fish_history');
+  if (shell === 'bash') return path.join(home, '.bash_history');
+  return path.join(home, '.zsh_history');
+}
+
+sessionsCmd
+  .command('import')
+  .argument('[file]', 'History file or asciinema .cast recording (default: your shell\'s history)')
+  .option('--format <format>', 'zsh | bash | fish | asciinema | plain (detected by default)')
+  .option('--json', 'Print JSON')
+  .description('Import commands into .sessions/ (one note per day, secrets redacted); re-run to pick up new history')
+  .action(
+    handle((file, options) => {
+      const native = require('../src/core/native');
+      const source = path.resolve(file || defaultHistoryFile());
+      const report = native.get().importSessions({ path: source, format: options.format });
+      if (options.json) {
+        console.log(JSON.stringify(report, null, 2));
+        return;
+      }
+      console.log(`🐚 Imported ${source} (${report.format})`);
+      console.log(`   ${report.created.length} new and ${report.updated.length} updated note(s), ${report.unchanged} unchanged`);
+      if (report.redactions > 0) {
+        console.log(`   Redacted ${report.redactions} secret(s)`);
+      }
+      if (report.created.length + report.updated.length > 0) {
+        console.log('   Run `oc index update --dirty` to make them searchable (filter with "type:session").');
+      }
+    })
+  );
+
+// ===== Import =====
+const importCmd = program.command('import').description('Bring outside history into the workspace as searchable notes');
+
+importCmd
+  .command('git')
+  .argument('[repo]', 'Path inside the git repository (default: current directory)')
+  .option('--what <kinds>', 'Comma-separated: commits, prs', 'commits')
+  .option('--since <date>', 'Only commits after this (e.g. 2024-01-01, "3 months ago")')
+  .option('--limit <n>', 'Most pull requests to fetch', (v) => parseInt(v, 10))
+  .option('--token <token>', 'GitHub token for pull requests (default: $GITHUB_TOKEN)')
+  .option('--json', 'Print JSON')
+  .description('Import commit messages (one note per day) and GitHub pull requests into .repos/<repo>/')
+  .action(
+    handle(async (repo, options) => {
+      const native = require('../src/core/native');
+      const report = await native.get().importGit({
+        repo: path.resolve(repo || '.'),
+        what: options.what.split(',').map((w) => w.trim()).filter(Boolean),
+        since: options.since,
+        limit: options.limit,
+        token: options.token,
+      });
+      if (options.json) {
+        console.log(JSON.stringify(report, null, 2));
+        return;
+      }
+      console.log(`📜 Imported ${report.commits} commit(s) and ${report.pull_requests} pull request(s) from ${report.repo}`);
+      console.log(`   ${report.created.length} new and ${report.updated.length} updated note(s) in ${report.folder}, ${report.unchanged} unchanged`);
+      if (report.created.length + report.updated.length > 0) {
+        console.log('   Run `oc index update --dirty` to make them searchable (filter with "type:commit" or "type:pull_request").');
+      }
+    })
+  );
+
+function printWikiImport(report, json) {
+  if (json) {
+    console.log(JSON.stringify(report, null, 2));
+    return;
+  }
+  console.log(`📚 Imported ${report.pages} page(s) into ${report.folder}`);
+  console.log(`   ${report.created.length} new, ${report.updated.length} updated, ${report.unchanged} unchanged`);
+  if (report.created.length + report.updated.length > 0) {
+    console.log('   Run `oc index update --dirty` to make them searchable.');
+  }
+}
+
+importCmd
+  .command('bibtex')
+  .argument('<file>', 'BibTeX file, e.g. a Zotero Better BibTeX auto-export')
+  .option('--folder <path>', 'Where the reference notes go', 'references')
+  .option('--watch', 'Keep running and re-import whenever the file changes')
+  .option('--json', 'Print JSON')
+  .description('Import one reference note per entry, named by citation key ([[citekey]] links to it)')
+  .action(
+    handle((file, options) => {
+      const native = require('../src/core/native');
+      const bibFile = path.resolve(file);
+      const run = () => {
+        const report = native.get().importBibtex(bibFile, options.folder);
+        if (options.json) {
+          console.log(JSON.stringify(report, null, 2));
+          return;
+        }
+        console.log(`📖 ${report.entries} reference(s) in ${report.folder}: ${report.created.length} new, ${report.updated.length} updated, ${report.unchanged} unchanged`);
+      };
+      run();
+      if (!options.watch) return;
+      console.log(`👀 Watching ${bibFile} (Ctrl+C to stop)`);
+      // Better BibTeX rewrites the whole file on every change; polling also
+      // survives the file being replaced rather than edited in place
+      fs.watchFile(bibFile, { interval: 2000 }, (curr, prev) => {
+        if (curr.mtimeMs === prev.mtimeMs || curr.size === 0) return;
+        try {
+          run();
+        } catch (error) {
+          console.error(`❌ ${error.message}`);
+        }
+      });
+    })
+  );
+
+function printNotesImport(report, json) {
+  if (json) {
+    console.log(JSON.stringify(report, null, 2));
+    return;
+  }
+  console.log(`🗒️  Imported ${report.notes} note(s) into ${report.folder} with ${report.attachments} new attachment file(s)`);
+  console.log(`   ${report.created.length} new, ${report.updated.length} updated, ${report.unchanged} unchanged`);
+  if (report.created.length + report.updated.length > 0) {
+    console.log('   Run `oc index update --dirty` to make them searchable.');
+  }
+}
+
+importCmd
+  .command('enex')
+  .argument('<files...>', 'Evernote .enex exports (the file name becomes the notebook folder)')
+  .option('--folder <path>', 'Where the notebooks go', 'evernote')
+  .option('--json', 'Print JSON')
+  .description('Import Evernote notebooks with attachments, tags and creation dates')
+  .action(
+    handle((files, options) => {
+      const native = require('../src/core/native');
+      const report = native.get().importEnex(files.map((f) => path.resolve(f)), options.folder);
+      printNotesImport(report, options.json);
+    })
+  );
+
+importCmd
+  .command('apple-notes')
+  .option('--folder <path>', 'Where the Notes folders go', 'apple-notes')
+  .option('--json', 'Print JSON')
+  .description('Import every note of Apple Notes (macOS; attachments are not exported)')
+  .action(
+    handle((options) => {
+      const native = require('../src/core/native');
+      printNotesImport(native.get().importAppleNotes(options.folder), options.json);
+    })
+  );
+
+importCmd
+  .command('confluence')
+  .argument('<dir>', 'Unzipped Confluence HTML export of a space')
+  .option('--folder <path>', 'Where the pages go (default: confluence/<space>)')
+  .option('--json', 'Print JSON')
+  .description('Import a Confluence space, one doc per page nested like the page tree')
+  .action(
+    handle((dir, options) => {
+      const native = require('../src/core/native');
+      const report = native.get().importConfluence(path.resolve(dir), options.folder ?? null);
+      printWikiImport(report, options.json);
+    })
+  );
+
+importCmd
+  .command('gdocs')
+  .option('--drive-folder <id>', 'Drive folder id to import with its subfolders (default: every doc you can read)')
+  .option('--folder <path>', 'Where the docs go', 'google-docs')
+  .option('--limit <n>', 'Most docs to import, most recently modified first', (v) => parseInt(v, 10))
+  .option('--token <token>', 'OAuth access token with a Drive read scope (default: $GOOGLE_ACCESS_TOKEN or the google keyring entry)')
+  .option('--json', 'Print JSON')
+  .description('Import Google Docs exported as markdown through the Drive API')
+  .action(
+    handle(async (options) => {
+      const native = require('../src/core/native');
+      const report = await native.get().importGoogleDocs({
+        driveFolder: options.driveFolder,
+        folder: options.folder,
+        limit: options.limit,
+        token: options.token,
+      });
+      printWikiImport(report, options.json);
+    })
+  );
+
+// ===== Sources =====
+const sourcesCmd = program.command('sources').description('Outside systems mirrored into the workspace ([sources] in config.toml)');
+
+sourcesCmd
+  .command('sync')
+  .argument('[source]', 'Only sources whose id starts with this, e.g. github:owner/name')
+  .option('--json', 'Print JSON')
+  .description('Pull what changed since the last sync of each configured source')
+  .action(
+    handle(async (source, options) => {
+      const native = require('../src/core/native');
+      const reports = await native.get().syncSources(source ?? null);
+      if (options.json) {
+        console.log(JSON.stringify(reports, null, 2));
+        return;
+      }
+      if (reports.length === 0) {
+        console.log('No sources configured. Add e.g. [[sources.github]], [[sources.jira]] or [[sources.linear]] to config.toml.');
+        return;
+      }
+      for (const report of reports) {
+        if (report.error) {
+          console.log(`❌ ${report.source}: ${report.error}`);
+        } else {
+          console.log(`🔄 ${report.source}: ${report.created.length} new, ${report.updated.length} updated, ${report.unchanged} unchanged`);
+        }
+      }
+      if (reports.some((r) => r.error)) process.exitCode = 1;
+    })
+  );
+
+sourcesCmd
+  .command('status')
+  .option('--json', 'Print JSON')
+  .description('Show when each source last synced and whether it failed')
+  .action(
+    handle((options) => {
+      const native = require('../src/core/native');
+      const states = native.get().sourceStates();
+      if (options.json) {
+        console.log(JSON.stringify(states, null, 2));
+        return;
+      }
+      if (states.length === 0) {
+        console.log('No source has been synced yet.');
+        return;
+      }
+      for (const state of states) {
+        const status = state.error ? `failed: ${state.error}` : `${state.docs} doc(s) written`;
+        console.log(`${state.source}  ${state.synced_at}  ${status}`);
+      }
+    })
+  );
+
+sourcesCmd
+  .command('token')
+  .argument('<account>', 'What the token is for: jira, linear or google')
+  .description('Store an API token in the system keyring (read from stdin when piped)')
+  .action(
+    handle(async (account) => {
+      let token;
+      if (!process.stdin.isTTY) {
+        token = fs.readFileSync(0, 'utf8');
+      } else {
+        const readline = require('readline');
+        const rl = readline.createInterface({ input: process.stdin, output: process.stdout });
+        token = await new Promise((resolve) => {
+          rl.question(`${account} token: `, resolve);
+        });
+        rl.close();
+      }
+      const native = require('../src/core/native');
+      native.get().setKeyringToken(account, token.trim());
+      console.log(`🔑 Stored the ${account} token in the system keyring`);
+    })
+  );
+
+// ===== Tasks =====
+const tasksCmd = program.command('tasks').description('Checkbox tasks (- [ ] / - [x]) collected from documents');
+
+tasksCmd
+  .command('list')
+  .option('--open', 'Only open tasks')
+  .option('--done', 'Only completed tasks')
+  .option('--folder <path>', 'Only tasks in documents under this folder')
+  .option('--due <date>', 'Only tasks due on or before this date (YYYY-MM-DD)')
+  .option('-l, --limit <number>', 'Maximum number of tasks', (v) => Number(v))
+  .option('--refresh', 'Re-read all documents first (after editing files outside oc)')
+  .option('--json', 'Print JSON')
+  .description('List tasks, soonest due first')
+  .action(
+    handle((options) => {
+      if (options.open && options.done) {
+        throw new Error('Use either --open or --done, not both');
+      }
+      if (options.refresh) store.reindexTasks();
+      const tasks = store.listTasks({
+        folder: options.folder,
+        done: options.open ? false : options.done ? true : undefined,
+        dueBy: options.due,
+        limit: options.limit,
+      });
+      if (options.json) {
+        console.log(JSON.stringify(tasks, null, 2));
+        return;
+      }
+      if (tasks.length === 0) {
+        console.log('No tasks found.');
+        return;
+      }
+      tasks.forEach((t) => {
+        const due = t.due ? ` (due ${t.due})` : '';
+        console.log(`[${t.done ? 'x' : ' '}] ${t.text}${due}  — ${t.rel_path}:${t.line}`);
+      });
+    })
+  );
+
+tasksCmd
+  .command('done')
+  .argument('<location>', 'Task location as printed by `oc tasks list`, e.g. projects/plan.md:12')
+  .option('--undo', 'Mark the task open again')
+  .description('Tick (or untick) a task checkbox')
+  .action(
+    handle((location, options) => {
+      const match = String(location).match(/^(.+):(\d+)$/);
+      if (!match) throw new Error('Expected <doc_path>:<line>');
+      const result = store.setTaskDone({ docPath: match[1], line: Number(match[2]), done: !options.undo });
+      console.log(`${options.undo ? 'Reopened' : 'Completed'} task at ${result.rel_path}:${match[2]}.`);
+    })
+  );
+
+// ===== Capture =====
+/** Clipboard text via the platform's paste command */
+function readClipboard() {
+  const commands = process.platform === 'darwin'
+    ? [['pbpaste', []]]
+    : process.platform === 'win32'
+      ? [['powershell', ['-NoProfile', '-Command', 'Get-Clipboard']]]
+      : [['wl-paste', ['--no-newline']], ['xclip', ['-selection', 'clipboard', '-o']], ['xsel', ['--clipboard', '--output']]];
+  for (const [cmd, args] of commands) {
+    const result = spawnSync(cmd, args, { encoding: 'utf8' });
+    if (!result.error && result.status === 0) return result.stdout;
+  }
+  throw new Error('Could not read the clipboard (install wl-clipboard, xclip or xsel)');
+}
+
+program
+  .command('capture')
+  .argument('[text...]', 'Text to capture; read from stdin when omitted and input is piped')
+  .option('--clipboard', 'Capture the clipboard contents')
+  .option('--stdin', 'Read the text from stdin')
+  .option('--url <url>', 'Page the text came from')
+  .option('--json', 'Print JSON')
+  .description('Append a note to today\'s inbox (inbox/YYYY-MM-DD.md)')
+  .action(
+    handle(async (words, options) => {
+      let text;
+      let source = 'text';
+      if (options.clipboard) {
+        text = readClipboard();
+        source = 'clipboard';
+      } else if (options.stdin || (words.length === 0 && !process.stdin.isTTY)) {
+        text = fs.readFileSync(0, 'utf8');
+        source = 'stdin';
+      } else {
+        text = words.join(' ');
+        if (options.url) source = 'url';
+      }
+      const captured = store.capture({ text, source, url: options.url });
+
+      // No sync service runs in this process; index the note directly so
+      // the capture is searchable right away
+      try {
+        const indexer = new Indexer();
+        await indexer.initialize();
+        if (await indexer.indexExists()) await indexer.indexFile(captured.rel_path);
+      } catch {
+        // The next index update picks it up
+      }
+
+      if (options.json) {
+        console.log(JSON.stringify(captured, null, 2));
+        return;
+      }
+      console.log(`📥 Captured to ${captured.rel_path}`);
+    })
+  );
+
+// ===== Review =====
+const reviewCmd = program.command('review').description('Find material to archive or refresh, or study flashcards');
+
+reviewCmd
+  .command('stale')
+  .option('--folder <path>', 'Only documents under this folder')
+  .option('--min-score <score>', 'Only documents scoring at least this (0-1)', (v) => Number(v))
+  .option('-l, --limit <number>', 'Number of documents to show', (v) => Number(v), 20)
+  .option('--json', 'Print JSON')
+  .description('Rank documents by staleness: age, no recent hits, broken links, superseded-by links')
+  .action(
+    handle((options) => {
+      const docs = store.staleDocs({
+        folder: options.folder,
+        minScore: options.minScore,
+        limit: options.limit,
+      });
+      if (options.json) {
+        console.log(JSON.stringify(docs, null, 2));
+        return;
+      }
+      if (docs.length === 0) {
+        console.log('No documents found.');
+        return;
+      }
+      docs.forEach((d) => {
+        console.log(`${d.score.toFixed(2)}  ${d.rel_path}`);
+        if (d.reasons.length > 0) console.log(`      ${d.reasons.join(', ')}`);
+        if (d.superseded_by) console.log(`      superseded by ${d.superseded_by}`);
+        d.broken.forEach((target) => console.log(`      broken link: ${target}`));
+      });
+    })
+  );
+
+const CARD_GRADES = { again: 1, hard: 3, good: 4, easy: 5 };
+
+reviewCmd
+  .command('cards')
+  .option('--folder <path>', 'Only cards from documents under this folder')
+  .option('-l, --limit <number>', 'Number of cards to study', (v) => Number(v))
+  .option('--list', 'List due cards instead of studying them')
+  .option('--all', 'With --list, every card rather than only due ones')
+  .option('--reindex', 'Re-read cards from files edited outside OpenContext first')
+  .option('--json', 'Print JSON (implies --list)')
+  .description('Study flashcards (Q :: A, Q:/A: and {{c1::cloze}} lines in notes) with spaced repetition')
+  .action(
+    handle(async (options) => {
+      if (options.reindex) store.reindexCards();
+      const cards = store.listCards({
+        folder: options.folder,
+        all: Boolean(options.all),
+        limit: options.limit,
+      });
+      if (options.json) {
+        console.log(JSON.stringify(cards, null, 2));
+        return;
+      }
+      if (options.list || options.all) {
+        cards.forEach((c) => console.log(`${c.due}  ${c.rel_path}:${c.line}  ${c.front}`));
+        if (cards.length === 0) console.log('No cards due.');
+        return;
+      }
+      if (cards.length === 0) {
+        const stats = store.cardStats();
+        console.log(`No cards due. ${stats.total} card(s), ${stats.reviewed_today} reviewed today.`);
+        return;
+      }
+
+      const readline = require('readline');
+      const rl = readline.createInterface({ input: process.stdin, output: process.stdout });
+      const ask = (prompt) => new Promise((resolve) => rl.question(prompt, resolve));
+      let reviewed = 0;
+      try {
+        for (const card of cards) {
+          console.log(`\n[${reviewed + 1}/${cards.length}] ${card.rel_path}:${card.line}`);
+          console.log(card.front);
+          const shown = await ask('(Enter to show the answer, q to stop) ');
+          if (shown.trim().toLowerCase() === 'q') break;
+          console.log(`\n${card.back}\n`);
+          let grade;
+          while (grade === undefined) {
+            const answer = (await ask('Grade: again, hard, good, easy or 0-5 (q to stop) ')).trim().toLowerCase();
+            if (answer === 'q') break;
+            if (answer in CARD_GRADES) grade = CARD_GRADES[answer];
+            else if (/^[0-5]$/.test(answer)) grade = Number(answer);
+          }
+          if (grade === undefined) break;
+          const next = store.reviewCard(card.id, grade);
+          console.log(`Next review ${next.due} (in ${next.interval_days} day(s))`);
+          reviewed += 1;
+        }
+      } finally {
+        rl.close();
+      }
+      console.log(`\nReviewed ${reviewed} of ${cards.length} due card(s).`);
+    })
+  );
+
+const exportCmd = program.command('export').description('Export notes for use in other tools');
+
+exportCmd
+  .command('anki')
+  .option('--tag <tag>', 'Only cards from documents with this tag')
+  .option('--folder <path>', 'Only cards from documents under this folder')
+  .option('--deck <name>', 'Deck name (Parent::Child for a subdeck)', 'OpenContext')
+  .option('-o, --output <file>', 'Package to write', 'opencontext.apkg')
+  .description('Export flashcards to an Anki .apkg; importing a newer export updates the same notes')
+  .action(
+    handle((options) => {
+      const native = require('../src/core/native');
+      const dest = path.resolve(options.output);
+      const result = native.get().exportAnki({
+        path: dest,
+        tag: options.tag,
+        folder: options.folder,
+        deck: options.deck,
+      });
+      console.log(`🗂️  Exported ${result.cards} card(s) to deck "${result.deck}" in ${result.path}`);
+    })
+  );
+
+const printPicked = (docs) => {
+  docs.forEach((d) => {
+    const desc = d.description ? ` — ${d.description}` : '';
+    console.log(`${d.rel_path}${desc}`);
+    const excerpt = d.excerpt.replace(/\s+/g, ' ').trim();
+    if (excerpt) console.log(`      ${excerpt.length > 160 ? `${excerpt.slice(0, 160)}…` : excerpt}`);
+  });
+};
+
+program
+  .command('random')
+  .option('--folder <path>', 'Only documents under this folder')
+  .option('--tag <tag>', 'Only documents with this tag (frontmatter tags or inline #tag)')
+  .option('-n, --count <number>', 'Number of documents to pick', (v) => Number(v), 1)
+  .option('--open', 'Open the (first) pick in $EDITOR')
+  .option('--json', 'Print JSON')
+  .description('Pick documents at random, for rediscovering old notes')
+  .action(
+    handle((options) => {
+      const docs = store.randomDocs({ folder: options.folder, tag: options.tag, count: options.count });
+      if (options.json) {
+        console.log(JSON.stringify(docs, null, 2));
+        return;
+      }
+      if (docs.length === 0) {
+        console.log('No documents found.');
+        return;
+      }
+      if (options.open) {
+        openInEditor(store.getDocMeta({ docPath: docs[0].rel_path }).abs_path);
+        return;
+      }
+      printPicked(docs);
+    })
+  );
+
+program
+  .command('resurface')
+  .option('-n, --count <number>', 'Number of documents to bring back', (v) => Number(v), 3)
+  .option('--days <number>', 'Only documents neither edited nor opened for this many days', (v) => Number(v), 90)
+  .option('--folder <path>', 'Only documents under this folder')
+  .option('--tag <tag>', 'Only documents with this tag')
+  .option('--dry-run', 'Show the picks without recording them as shown')
+  .option('--json', 'Print JSON')
+  .description('Bring back old notes; each shown note returns after a doubling interval (see also oc digest --resurface)')
+  .action(
+    handle((options) => {
+      const docs = store.resurface({
+        count: options.count,
+        minAgeDays: options.days,
+        folder: options.folder,
+        tag: options.tag,
+        dryRun: Boolean(options.dryRun),
+      });
+      if (options.json) {
+        console.log(JSON.stringify(docs, null, 2));
+        return;
+      }
+      if (docs.length === 0) {
+        console.log(`Nothing to resurface: no document has been left alone for ${options.days} days.`);
+        return;
+      }
+      docs.forEach((d) => {
+        console.log(`${d.rel_path}  (untouched for ${d.idle_days} days)`);
+        if (d.description) console.log(`      ${d.description}`);
+      });
+    })
+  );
+
+// ===== Checks =====
+const checkCmd = program.command('check').description('Workspace health checks');
+
+checkCmd
+  .command('links')
+  .option('--folder <path>', 'Only check documents under this folder')
+  .option('--json', 'Print JSON')
+  .description('Report dead wiki/Markdown/oc:// links (with fixes from rename history) and orphan documents')
+  .action(
+    handle((options) => {
+      const report = store.checkLinks({ folder: options.folder });
+      if (options.json) {
+        console.log(JSON.stringify(report, null, 2));
+        return;
+      }
+      console.log(`🔗 Checked ${report.links_checked} link(s) in ${report.docs_checked} document(s)`);
+      if (report.broken.length > 0) {
+        console.log(`\nDead links (${report.broken.length}):`);
+        report.broken.forEach((b) => {
+          const fix = b.suggestion ? `  → ${b.suggestion}` : '';
+          console.log(`  ${b.rel_path}:${b.line}  ${b.target}${fix}`);
+        });
+      }
+      if (report.orphans.length > 0) {
+        console.log(`\nOrphans, no links in or out (${report.orphans.length}):`);
+        report.orphans.forEach((p) => console.log(`  ${p}`));
+      }
+      if (report.broken.length === 0 && report.orphans.length === 0) {
+        console.log('No problems found.');
+      }
+      if (report.broken.length > 0) process.exitCode = 1;
+    })
+  );
+
+// ===== Timeline =====
+program
+  .command('timeline')
+  .argument('<range>', 'Period: 2024, 2024-Q3, 2024-07, 2024-07-15, or <from>..<to>')
+  .option('--folder <path>', 'Only documents under this folder')
+  .option('--source <kind>', 'Only dates from: frontmatter | entry | inline')
+  .option('-l, --limit <number>', 'Maximum number of dates', (v) => Number(v))
+  .option('--refresh', 'Re-read all documents first (after editing files outside oc)')
+  .option('--json', 'Print JSON')
+  .description('Everything that mentions a date in the given period, in date order')
+  .action(
+    handle((range, options) => {
+      if (options.refresh) store.reindexDates();
+      const dates = store.listDates({
+        range,
+        folder: options.folder,
+        source: options.source,
+        limit: options.limit,
+      });
+      if (options.json) {
+        console.log(JSON.stringify(dates, null, 2));
+        return;
+      }
+      if (dates.length === 0) {
+        console.log(`Nothing dated in ${range}.`);
+        return;
+      }
+      let current = null;
+      dates.forEach((d) => {
+        if (d.date !== current) {
+          current = d.date;
+          console.log(`\n${d.date}`);
+        }
+        const what = d.source === 'frontmatter' ? `${d.context}:` : d.source === 'entry' ? 'idea entry' : d.context;
+        console.log(`  ${d.rel_path}:${d.line}  ${what}`);
+      });
+    })
+  );
+
+// ===== Digest =====
+program
+  .command('digest')
+  .option('-s, --since <window>', 'How far back: 7d, 24h, 2w or YYYY-MM-DD', '7d')
+  .option('--summarize', 'Add a summary written by the [llm] model from the search config')
+  .option('--folder <path>', 'Folder the digest note is saved in', 'digests')
+  .option('--print', 'Print the digest instead of saving it')
+  .option('--json', 'Print JSON instead of saving it')
+  .option('--resurface <number>', 'Also bring back this many old notes (run daily from cron for a morning review)', (v) => Number(v))
+  .option('--resurface-days <number>', 'With --resurface, only notes untouched for this many days', (v) => Number(v), 90)
+  .description('Collect docs created/updated, idea entries and removals in a window into a digest note')
+  .action(
+    handle(async (options) => {
+      const write = !options.print && !options.json;
+      const result = await store.createDigest({
+        since: options.since,
+        folder: options.folder,
+        summarize: Boolean(options.summarize),
+        write,
+        resurface: options.resurface,
+        resurfaceDays: options.resurfaceDays,
+      });
+      if (options.json) {
+        console.log(JSON.stringify(result, null, 2));
+        return;
+      }
+      if (!write) {
+        console.log(result.markdown);
+        return;
+      }
+      const d = result.digest;
+      console.log(`📰 Digest ${d.since.slice(0, 10)} – ${d.until.slice(0, 10)} saved to ${result.saved.rel_path}`);
+      console.log(`   ${d.created.length} new, ${d.updated.length} updated, ${d.ideas.length} idea(s), ${d.removed.length} removed`);
+      if (d.resurfaced.length > 0) console.log(`   ${d.resurfaced.length} old note(s) resurfaced`);
+    })
+  );
+
+// ===== Publish =====
+program
+  .command('publish')
+  .argument('[folders...]', 'Folders to publish whole (docs with publish: false stay out)')
+  .option('-o, --out <dir>', 'Output directory', 'site')
+  .option('--title <title>', 'Site name', 'Notes')
+  .option('--json', 'Print JSON')
+  .description('Render docs with publish: true and the given folders to a static HTML site with search')
+  .action(
+    handle((folders, options) => {
+      const native = require('../src/core/native');
+      const outDir = path.resolve(options.out);
+      const report = native.get().publishSite(outDir, { folders, title: options.title });
+      if (options.json) {
+        console.log(JSON.stringify(report, null, 2));
+        return;
+      }
+      if (report.pages.length === 0) {
+        console.log('Nothing to publish: add publish: true to a doc or name a folder.');
+        return;
+      }
+      console.log(`🌱 Published ${report.pages.length} page(s) and ${report.assets.length} file(s) to ${report.out_dir}`);
+      if (report.hidden_links > 0) {
+        console.log(`   ${report.hidden_links} link(s) to unpublished docs shown as plain text`);
+      }
+      if (report.removed > 0) console.log(`   ${report.removed} file(s) from the last publish removed`);
+      console.log('   Serve the directory over HTTP for search to work (e.g. npx serve)');
+    })
+  );
+
 // ===== Search command =====
 program
   .command('search')
   .argument('<query>', 'Search query')
   .option('-l, --limit <number>', 'Number of results to return', (v) => Number(v), 5)
   .option('-t, --type <type>', 'Aggregation type: content (default) | doc | folder', 'content')
-  .option('-m, --mode <mode>', 'Search mode: hybrid (default) | vector | keyword', 'hybrid')
+  .option('-m, --mode <mode>', 'Search mode: hybrid (default) | vector | keyword | auto', 'hybrid')
   .option('-d, --doc-type <type>', 'Document type filter: doc | idea', undefined)
+  .option('-F, --filter <expr>', 'Filter expression, e.g. "tag:rust path:projects/ created:>2024-01-01"')
+  .option('--include-sources <list>', 'Only results from these comma-separated folders, doc types or sources (e.g. markdown,projects)')
+  .option('--exclude-sources <list>', 'No results from these comma-separated folders, doc types or sources (e.g. web_clip,inbox)')
   .option('-f, --format <format>', 'Output format: plain (default) | json', 'plain')
+  .option('-x, --cross-language', 'Also match keywords in the workspace\'s other languages')
+  .option('-c, --collection <name>', 'Collection to search (default: OPENCONTEXT_COLLECTION or "default")')
+  .option('-w, --all-workspaces', 'Also search the workspaces listed under [federation] in config.toml')
+  .option('--local', 'Search the local index even when REMOTE_URL is set')
+  .option('-s, --summary', 'With --type folder, list each folder\'s documents and matched headings')
+  .option('--sort <field>', 'Order by: score (default) | modified | created | path | entry_date')
+  .option('--order <dir>', 'Sort direction: asc | desc (default: A to Z for path, newest first for dates)')
+  .option('--cite [style]', 'Print results as ready-to-paste citations: link (default) | footnote')
+  .option('--facets', 'Also count matches by folder, doc type, tag and month')
   .description('Search content with optional aggregation by document or folder')
   .action(
     handle(async (query, options) => {
@@ -839,25 +1859,56 @@ program
         throw new Error(`Invalid type "${options.type}". Valid types: ${validTypes.join(', ')}`);
       }
       
-      const validModes = ['hybrid', 'vector', 'keyword'];
+      const validModes = ['hybrid', 'vector', 'keyword', 'auto'];
       if (!validModes.includes(options.mode)) {
         throw new Error(`Invalid mode "${options.mode}". Valid modes: ${validModes.join(', ')}`);
       }
       if (options.docType && !['doc', 'idea'].includes(options.docType)) {
         throw new Error(`Invalid doc type "${options.docType}". Valid types: doc, idea`);
       }
+      const validSorts = ['score', 'modified', 'created', 'path', 'entry_date'];
+      if (options.sort && !validSorts.includes(options.sort)) {
+        throw new Error(`Invalid sort "${options.sort}". Valid fields: ${validSorts.join(', ')}`);
+      }
+      if (options.order && !['asc', 'desc'].includes(options.order)) {
+        throw new Error(`Invalid order "${options.order}". Valid orders: asc, desc`);
+      }
+      const sourceList = (list) => list?.split(',').map((s) => s.trim()).filter(Boolean);
+      const citation = options.cite === true ? 'link' : options.cite;
+      if (citation && !['link', 'footnote'].includes(citation)) {
+        throw new Error(`Invalid citation style "${citation}". Valid styles: link, footnote`);
+      }
 
       // Use Searcher with aggregation
-      const searcher = new Searcher();
+      let searcher;
+      if (options.allWorkspaces) {
+        searcher = new FederatedSearcher();
+      } else {
+        searcher = options.local ? new Searcher() : createSearcher();
+      }
       const results = await searcher.search(query, { 
         limit: options.limit,
         mode: options.mode,
         aggregateBy: options.type,
-        docType: options.docType
+        docType: options.docType,
+        filter: options.filter,
+        includeSources: sourceList(options.includeSources),
+        excludeSources: sourceList(options.excludeSources),
+        crossLanguage: options.crossLanguage,
+        collection: options.collection,
+        folderSummary: options.summary,
+        sortBy: options.sort,
+        sortOrder: options.order,
+        citation,
+        facets: options.facets
       });
 
       // Format output
-      if (options.format === 'json') {
+      if (citation && options.format !== 'json') {
+        results.forEach((r) => {
+          if (r.citation) console.log(r.citation);
+        });
+      } else if (options.format === 'json') {
         const jsonOutput = searcher.formatResultsJson(query, results, {
           mode: options.mode,
           aggregateBy: options.type,
@@ -874,6 +1925,84 @@ program
   );
 
 
+program
+  .command('suggest')
+  .argument('<prefix>', 'Partial query')
+  .option('-l, --limit <number>', 'Number of suggestions', (v) => Number(v), 10)
+  .description('Complete a partial query from document titles, headings and #tags')
+  .action(
+    handle(async (prefix, options) => {
+      const searcher = createSearcher();
+      const suggestions = await searcher.suggest(prefix, options.limit);
+      if (suggestions.length === 0) {
+        console.log(`No suggestions for "${prefix}".`);
+        return;
+      }
+      for (const s of suggestions) {
+        console.log(`${s.text}  (${s.kind}, ${s.count})`);
+      }
+    })
+  );
+
+// ===== Slow-query log =====
+program
+  .command('slowlog')
+  .option('-n, --limit <number>', 'Number of entries to show', (v) => Number(v), 20)
+  .option('-f, --format <format>', 'Output format: plain (default) | json', 'plain')
+  .description('Show recent slow searches (threshold: search.slow_query_ms in config.toml)')
+  .action(
+    handle((options) => {
+      const native = require('../src/core/native');
+      if (!native.isAvailable()) {
+        console.log('❌ Native bindings not available.');
+        return;
+      }
+      const entries = native.get().readSlowQueryLog(options.limit);
+      if (options.format === 'json') {
+        console.log(JSON.stringify(entries, null, 2));
+        return;
+      }
+      if (entries.length === 0) {
+        console.log('✅ No slow queries recorded.');
+        return;
+      }
+      for (const e of entries) {
+        const stages = Object.entries(e.stages)
+          .map(([stage, ms]) => `${stage} ${ms.toFixed(0)}ms`)
+          .join(', ');
+        console.log(`${new Date(e.timestamp).toLocaleString()}  ${e.total_ms.toFixed(0)}ms  [${e.mode}] "${e.query}"`);
+        console.log(`   ${stages || 'no stage timings'} · ${e.result_count} results`);
+      }
+    })
+  );
+
+// ===== Debug commands =====
+const debugCmd = program.command('debug').description('Inspect a running search engine');
+
+debugCmd
+  .command('memory')
+  .option('--url <url>', 'Server to inspect, e.g. http://127.0.0.1:3222 for `oc serve` (default: REMOTE_URL, else this process)')
+  .option('-f, --format <format>', 'Output format: plain (default) | json', 'plain')
+  .description('Show memory used by search caches against the [memory] budget in config.toml')
+  .action(
+    handle(async (options) => {
+      const searcher = options.url
+        ? new RemoteSearcher({ url: options.url, token: config.get('REMOTE_TOKEN') })
+        : createSearcher();
+      const report = await searcher.memoryUsage();
+      if (options.format === 'json') {
+        console.log(JSON.stringify(report, null, 2));
+        return;
+      }
+      const mb = (bytes) => `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+      console.log(`Memory: ${mb(report.used_bytes)} of ${mb(report.budget_bytes)} budget`);
+      for (const c of report.components) {
+        console.log(`  ${c.component.padEnd(16)} ${mb(c.bytes).padStart(10)} / ${mb(c.limit_bytes)}`);
+        console.log(`    ${c.entries} entries · ${c.hits} hits · ${c.misses} misses · ${c.evictions} evictions`);
+      }
+    })
+  );
+
 // ===== MCP command =====
 program
   .command('mcp')
@@ -900,16 +2029,51 @@ program
       if (options.open !== false) {
         await launchBrowser(url);
       }
-      const shutdown = () => {
-        server.close(() => process.exit(0));
-      };
-      process.on('SIGINT', shutdown);
-      process.on('SIGTERM', shutdown);
+      closeOnSignal(server);
+    })
+  );
+
+program
+  .command('serve')
+  .description('Run the HTTP API (/api/...) for scripts and tools; --ui adds a built-in web UI')
+  .option('-p, --port <port>', 'Port to run on', (value) => Number(value), 3222)
+  .option('--host <host>', 'Host to bind', '127.0.0.1')
+  .option('--ui', 'Serve the built-in web UI at / (no UI build needed)')
+  .option('--open', 'Open the web UI in the browser (with --ui)')
+  .action(
+    handle(async (options) => {
+      const server = await createUiServer({
+        host: options.host,
+        port: options.port,
+        ui: options.ui ? 'embedded' : false,
+      });
+      const url = `http://${options.host}:${options.port}`;
+      console.log(`OpenContext API is running at ${url}/api`);
+      if (options.ui) {
+        console.log(`Web UI at ${url}`);
+        if (options.open) await launchBrowser(url);
+      }
+      closeOnSignal(server);
     })
   );
 
 program.parse(process.argv);
 
+function closeOnSignal(server) {
+  let stopping = false;
+  const shutdown = () => {
+    if (stopping) process.exit(1);
+    stopping = true;
+    // Flush (or journal) pending index updates before closing
+    const { indexSync } = require('../src/core/search/indexSync');
+    indexSync.stop()
+      .catch(() => {})
+      .finally(() => server.close(() => process.exit(0)));
+  };
+  process.on('SIGINT', shutdown);
+  process.on('SIGTERM', shutdown);
+}
+
 async function launchBrowser(url) {
   const mod = await import('open');
   return mod.default(url);
@@ -0,0 +1,206 @@
+//! Folder access control
+//!
+//! A shared instance can hand out API tokens that only see part of the
+//! workspace. Rules are read from `acl.json` in the OpenContext root (or
+//! `OPENCONTEXT_ACL_PATH`):
+//!
+//! ```json
+//! { "rules": [
+//!     { "token": "team-secret", "name": "team", "folders": ["projects", "team"] },
+//!     { "token": "*", "folders": ["public"] }
+//! ] }
+//! ```
+//!
+//! Without a rules file every caller sees everything. Once rules exist, a
+//! caller whose token matches no rule falls back to the `"*"` rule, and is
+//! refused if there is none.
+
+use crate::relpath::RelPath;
+use crate::{CoreError, CoreResult};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Token that matches callers without a known token
+pub const ANONYMOUS_TOKEN: &str = "*";
+
+/// Access rules for a shared workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AclConfig {
+    #[serde(default)]
+    pub rules: Vec<AclRule>,
+}
+
+/// Folders one API token may read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AclRule {
+    pub token: String,
+    /// Label for logs and `oc` output
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Folder prefixes relative to contexts/ ("" grants the whole workspace)
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+/// What a caller may see
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "folders", rename_all = "snake_case")]
+pub enum Access {
+    All,
+    Folders(Vec<String>),
+}
+
+impl AclConfig {
+    /// Load rules from `path`; a missing file disables access control
+    pub fn load(path: &Path) -> CoreResult<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(|e| {
+                CoreError::Message(format!("invalid ACL file {}: {e}", path.display()))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Access granted to `token`, or `None` if the caller is refused
+    pub fn access_for(&self, token: Option<&str>) -> Option<Access> {
+        if !self.is_enabled() {
+            return Some(Access::All);
+        }
+        let token = token.map(str::trim).filter(|t| !t.is_empty());
+        let rule = token
+            .and_then(|t| {
+                self.rules
+                    .iter()
+                    .find(|r| r.token != ANONYMOUS_TOKEN && r.token == t)
+            })
+            .or_else(|| self.rules.iter().find(|r| r.token == ANONYMOUS_TOKEN))?;
+        Some(Access::from_folders(&rule.folders))
+    }
+}
+
+impl Access {
+    fn from_folders(folders: &[String]) -> Self {
+        let folders: Vec<String> = folders
+            .iter()
+            .map(|f| f.trim_matches('/').to_string())
+            .collect();
+        if folders.iter().any(|f| f.is_empty()) {
+            Access::All
+        } else {
+            Access::Folders(folders)
+        }
+    }
+
+    /// Allowed folder prefixes; `None` means unrestricted
+    pub fn folders(&self) -> Option<&[String]> {
+        match self {
+            Access::All => None,
+            Access::Folders(folders) => Some(folders),
+        }
+    }
+
+    /// Whether a document or folder at `path` is readable. Paths with a
+    /// `..` segment are refused outright.
+    pub fn allows(&self, path: &str) -> bool {
+        let Some(path) = checked(path) else {
+            return false;
+        };
+        match self {
+            Access::All => true,
+            Access::Folders(folders) => folders.iter().any(|f| within(path.as_str(), f)),
+        }
+    }
+
+    /// Whether `folder` should appear in listings: readable itself, or on
+    /// the way to a readable folder
+    pub fn shows_folder(&self, folder: &str) -> bool {
+        let Some(folder) = checked(folder) else {
+            return false;
+        };
+        let folder = folder.as_str();
+        match self {
+            Access::All => true,
+            Access::Folders(folders) => folders
+                .iter()
+                .any(|f| within(folder, f) || folder.is_empty() || within(f, folder)),
+        }
+    }
+}
+
+/// Normalize `path`, or `None` if it has a `..` segment that could climb
+/// out of an allowed folder
+fn checked(path: &str) -> Option<RelPath> {
+    let path = RelPath::new(path);
+    let escapes = path.segments().any(|s| s == "..");
+    (!escapes).then_some(path)
+}
+
+fn within(path: &str, folder: &str) -> bool {
+    path == folder
+        || path
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AclConfig {
+        serde_json::from_str(
+            r#"{ "rules": [
+                { "token": "team", "folders": ["projects/", "team"] },
+                { "token": "admin", "folders": [""] },
+                { "token": "*", "folders": ["public"] }
+            ] }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_access_for_token() {
+        let acl = config();
+        let team = acl.access_for(Some("team")).unwrap();
+        assert!(team.allows("projects/a.md"));
+        assert!(team.allows("team"));
+        assert!(!team.allows("personal/diary.md"));
+        assert!(!team.allows("projects-old/a.md"));
+
+        assert_eq!(acl.access_for(Some("admin")), Some(Access::All));
+        assert_eq!(
+            acl.access_for(None),
+            Some(Access::Folders(vec!["public".into()]))
+        );
+        assert_eq!(acl.access_for(Some("*")), acl.access_for(None));
+
+        assert_eq!(AclConfig::default().access_for(None), Some(Access::All));
+        let strict = AclConfig {
+            rules: vec![acl.rules[0].clone()],
+        };
+        assert_eq!(strict.access_for(Some("unknown")), None);
+    }
+
+    #[test]
+    fn test_shows_parent_folders() {
+        let access = Access::Folders(vec!["projects/acme".into()]);
+        assert!(access.shows_folder("projects"));
+        assert!(access.shows_folder("projects/acme/design"));
+        assert!(!access.shows_folder("personal"));
+        assert!(!access.allows("projects/other.md"));
+    }
+
+    #[test]
+    fn test_refuses_parent_segments() {
+        let access = Access::Folders(vec!["allowed".into()]);
+        assert!(access.allows("allowed/./x"));
+        assert!(!access.allows("allowed/../denied/x"));
+        assert!(!access.allows("allowed\\..\\denied\\x"));
+        assert!(!access.shows_folder("allowed/../denied"));
+        assert!(!Access::All.allows("../outside"));
+    }
+}
@@ -0,0 +1,368 @@
+//! Anki deck export
+//!
+//! Writes the flashcards found in notes (see [`crate::flashcards`]) to an
+//! `.apkg` file Anki can import: a zip holding a `collection.anki2` SQLite
+//! database in Anki's legacy (schema 11) layout and an empty `media` map.
+//!
+//! Every id in the package is derived from stable inputs: a note's GUID is
+//! the card id, and the note type and deck ids come from fixed names. Anki
+//! matches imported notes by GUID and updates those whose modification time
+//! is newer, so exporting again after editing answers updates the cards in
+//! Anki (keeping their Anki review history) instead of adding duplicates.
+//! Cards go out as new; OpenContext's own schedule stays local. Clozes are
+//! exported as the front and back shown by `oc review cards`.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::json;
+use sha1::{Digest, Sha1};
+
+use crate::flashcards::{Card, CardQuery};
+use crate::{content_revision, CoreError, CoreResult, OpenContext};
+
+/// Deck the cards go into unless another is named
+pub const DEFAULT_DECK: &str = "OpenContext";
+
+/// Name of the note type; its id is derived from it, so renaming it in Anki
+/// is fine but changing it here would create a second note type
+const MODEL_NAME: &str = "OpenContext Card";
+
+const COLLECTION_SCHEMA: &str = "
+    CREATE TABLE col (
+        id INTEGER PRIMARY KEY, crt INTEGER NOT NULL, mod INTEGER NOT NULL,
+        scm INTEGER NOT NULL, ver INTEGER NOT NULL, dty INTEGER NOT NULL,
+        usn INTEGER NOT NULL, ls INTEGER NOT NULL, conf TEXT NOT NULL,
+        models TEXT NOT NULL, decks TEXT NOT NULL, dconf TEXT NOT NULL,
+        tags TEXT NOT NULL
+    );
+    CREATE TABLE notes (
+        id INTEGER PRIMARY KEY, guid TEXT NOT NULL, mid INTEGER NOT NULL,
+        mod INTEGER NOT NULL, usn INTEGER NOT NULL, tags TEXT NOT NULL,
+        flds TEXT NOT NULL, sfld INTEGER NOT NULL, csum INTEGER NOT NULL,
+        flags INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE TABLE cards (
+        id INTEGER PRIMARY KEY, nid INTEGER NOT NULL, did INTEGER NOT NULL,
+        ord INTEGER NOT NULL, mod INTEGER NOT NULL, usn INTEGER NOT NULL,
+        type INTEGER NOT NULL, queue INTEGER NOT NULL, due INTEGER NOT NULL,
+        ivl INTEGER NOT NULL, factor INTEGER NOT NULL, reps INTEGER NOT NULL,
+        lapses INTEGER NOT NULL, left INTEGER NOT NULL, odue INTEGER NOT NULL,
+        odid INTEGER NOT NULL, flags INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE TABLE revlog (
+        id INTEGER PRIMARY KEY, cid INTEGER NOT NULL, usn INTEGER NOT NULL,
+        ease INTEGER NOT NULL, ivl INTEGER NOT NULL, lastIvl INTEGER NOT NULL,
+        factor INTEGER NOT NULL, time INTEGER NOT NULL, type INTEGER NOT NULL
+    );
+    CREATE TABLE graves (
+        usn INTEGER NOT NULL, oid INTEGER NOT NULL, type INTEGER NOT NULL
+    );
+    CREATE INDEX ix_notes_usn ON notes (usn);
+    CREATE INDEX ix_cards_usn ON cards (usn);
+    CREATE INDEX ix_revlog_usn ON revlog (usn);
+    CREATE INDEX ix_cards_nid ON cards (nid);
+    CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+    CREATE INDEX ix_revlog_cid ON revlog (cid);
+    CREATE INDEX ix_notes_csum ON notes (csum);
+";
+
+#[derive(Debug, Clone, Default)]
+pub struct AnkiExportOptions {
+    /// Only cards from notes with this tag (frontmatter `tags` or `#tag`)
+    pub tag: Option<String>,
+    /// Only cards from notes under this folder
+    pub folder: Option<String>,
+    /// Deck name (default [`DEFAULT_DECK`]); `::` makes subdecks
+    pub deck: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnkiExport {
+    pub path: String,
+    pub deck: String,
+    pub cards: usize,
+}
+
+/// A positive id below 2^53 (safe for Anki's JSON) derived from `key`
+fn stable_id(key: &str) -> i64 {
+    let hash = u64::from_str_radix(&content_revision(key.as_bytes()), 16).unwrap_or_default();
+    ((hash & ((1 << 52) - 1)) | (1 << 40)) as i64
+}
+
+/// Field text as Anki's HTML
+fn field_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+/// Anki's duplicate check: the first 8 hex digits of the SHA-1 of the sort
+/// field's text
+fn field_checksum(text: &str) -> i64 {
+    let digest = Sha1::digest(text.as_bytes());
+    i64::from(u32::from_be_bytes([
+        digest[0], digest[1], digest[2], digest[3],
+    ]))
+}
+
+/// Hierarchical Anki tag for the folder a card comes from
+fn folder_tag(rel_path: &str) -> String {
+    match rel_path.rsplit_once('/') {
+        Some((folder, _)) => format!(
+            "opencontext::{}",
+            folder.replace('/', "::").replace(' ', "_")
+        ),
+        None => "opencontext".to_string(),
+    }
+}
+
+fn write_collection(conn: &Connection, deck: &str, cards: &[Card]) -> CoreResult<()> {
+    let now = Utc::now();
+    let (secs, millis) = (now.timestamp(), now.timestamp_millis());
+    let deck_id = stable_id(&format!("deck\n{deck}"));
+    let model_id = stable_id(&format!("model\n{MODEL_NAME}"));
+
+    let fields: Vec<_> = ["Front", "Back", "Source"]
+        .iter()
+        .enumerate()
+        .map(|(ord, name)| {
+            json!({
+                "name": name, "ord": ord, "sticky": false, "rtl": false,
+                "font": "Arial", "size": 20, "media": [],
+            })
+        })
+        .collect();
+    let model = json!({
+        "id": model_id, "name": MODEL_NAME, "type": 0, "mod": secs, "usn": -1,
+        "sortf": 0, "did": deck_id, "tags": [], "vers": [], "flds": fields,
+        "tmpls": [{
+            "name": "Card 1", "ord": 0, "did": null, "bqfmt": "", "bafmt": "",
+            "qfmt": "{{Front}}",
+            "afmt": "{{FrontSide}}<hr id=answer>{{Back}}<div class=source>{{Source}}</div>",
+        }],
+        "css": ".card { font-family: arial; font-size: 20px; text-align: center; }\n\
+                .source { margin-top: 1em; font-size: 12px; color: #888; }",
+        "latexPre": "\\documentclass[12pt]{article}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "req": [[0, "any", [0]]],
+    });
+    let new_deck = |id: i64, name: &str| {
+        json!({
+            "id": id, "name": name, "mod": secs, "usn": -1, "desc": "", "dyn": 0,
+            "conf": 1, "collapsed": false, "extendNew": 10, "extendRev": 50,
+            "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0], "timeToday": [0, 0],
+        })
+    };
+    let decks = json!({
+        "1": new_deck(1, "Default"),
+        deck_id.to_string(): new_deck(deck_id, deck),
+    });
+    let dconf = json!({"1": {
+        "id": 1, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60, "timer": 0,
+        "autoplay": true, "replayq": true,
+        "new": {"perDay": 20, "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500,
+                "separate": true, "order": 1, "bury": true},
+        "rev": {"perDay": 200, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1, "maxIvl": 36500,
+                "minSpace": 1, "bury": true},
+        "lapse": {"delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0},
+    }});
+    let conf = json!({
+        "activeDecks": [deck_id], "curDeck": deck_id, "curModel": model_id, "nextPos": cards.len() + 1,
+        "newSpread": 0, "collapseTime": 1200, "timeLim": 0, "estTimes": true,
+        "dueCounts": true, "sortType": "noteFld", "sortBackwards": false, "addToCur": true,
+    });
+
+    conn.execute_batch(COLLECTION_SCHEMA)?;
+    conn.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+        params![
+            secs,
+            millis,
+            conf.to_string(),
+            json!({ model_id.to_string(): model }).to_string(),
+            decks.to_string(),
+            dconf.to_string()
+        ],
+    )?;
+    let mut note =
+        conn.prepare("INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')")?;
+    let mut card_row = conn.prepare(
+        "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+    )?;
+    for (position, card) in cards.iter().enumerate() {
+        let note_id = stable_id(&format!("note\n{}", card.id));
+        let source = format!("{}:{}", card.rel_path, card.line);
+        let fields = [&card.front, &card.back, &source]
+            .map(|text| field_html(text))
+            .join("\x1f");
+        note.execute(params![
+            note_id,
+            card.id,
+            model_id,
+            secs,
+            format!(" {} ", folder_tag(&card.rel_path)),
+            fields,
+            card.front,
+            field_checksum(&card.front)
+        ])?;
+        card_row.execute(params![
+            stable_id(&format!("card\n{}", card.id)),
+            note_id,
+            deck_id,
+            secs,
+            position as i64 + 1
+        ])?;
+    }
+    Ok(())
+}
+
+impl OpenContext {
+    /// Write flashcards to an Anki package at `dest`
+    pub fn export_anki(&self, dest: &Path, options: &AnkiExportOptions) -> CoreResult<AnkiExport> {
+        let mut cards = self.list_cards(&CardQuery {
+            folder: options.folder.clone(),
+            all: true,
+            limit: None,
+        })?;
+        if let Some(tag) = options.tag.as_deref() {
+            let mut tagged: HashMap<String, bool> = HashMap::new();
+            for card in &cards {
+                if !tagged.contains_key(&card.rel_path) {
+                    let content = self.get_doc_content(&card.rel_path).unwrap_or_default();
+                    let has = crate::resurface::has_tag(&content, tag);
+                    tagged.insert(card.rel_path.clone(), has);
+                }
+            }
+            cards.retain(|card| tagged[&card.rel_path]);
+        }
+        let deck = options
+            .deck
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .unwrap_or(DEFAULT_DECK);
+
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let name = dest.file_name().and_then(|n| n.to_str()).unwrap_or("deck");
+        let collection_path = dest.with_file_name(format!(".{name}.anki2"));
+        let _ = fs::remove_file(&collection_path);
+        let written = Connection::open(&collection_path)
+            .map_err(CoreError::from)
+            .and_then(|conn| write_collection(&conn, deck, &cards));
+        let collection = written.and_then(|()| Ok(fs::read(&collection_path)?));
+        let _ = fs::remove_file(&collection_path);
+        let collection = collection?;
+
+        let zip_error = |e: zip::result::ZipError| {
+            CoreError::Message(format!("writing {}: {e}", dest.display()))
+        };
+        let mut zip = zip::ZipWriter::new(File::create(dest)?);
+        let deflated = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("collection.anki2", deflated)
+            .map_err(zip_error)?;
+        zip.write_all(&collection)?;
+        zip.start_file("media", deflated).map_err(zip_error)?;
+        zip.write_all(b"{}")?;
+        zip.finish().map_err(zip_error)?;
+
+        Ok(AnkiExport {
+            path: dest.display().to_string(),
+            deck: deck.to_string(),
+            cards: cards.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnvOverrides;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn notes_in(apkg: &Path, dir: &Path) -> Vec<(i64, String, String, String)> {
+        let mut archive = zip::ZipArchive::new(File::open(apkg).unwrap()).unwrap();
+        let mut collection = Vec::new();
+        archive
+            .by_name("collection.anki2")
+            .unwrap()
+            .read_to_end(&mut collection)
+            .unwrap();
+        let path = dir.join("check.anki2");
+        fs::write(&path, collection).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT id, guid, flds, tags FROM notes ORDER BY flds")
+            .unwrap();
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        rows
+    }
+
+    #[test]
+    fn test_export_anki_keeps_guids() {
+        let dir = TempDir::new().unwrap();
+        let ctx = OpenContext::initialize(EnvOverrides {
+            base_root: Some(dir.path().to_path_buf()),
+            contexts_root: Some(dir.path().join("contexts")),
+            db_path: Some(dir.path().join("opencontext.db")),
+            ..Default::default()
+        })
+        .unwrap();
+        ctx.create_folder("study/geo", None).unwrap();
+        ctx.create_doc("study/geo", "capitals.md", None).unwrap();
+        ctx.create_doc("study", "other.md", None).unwrap();
+        ctx.save_doc_content(
+            "study/geo/capitals.md",
+            "#flashcards\nCapital of France :: Paris\n{{c1::Canberra}} is in Australia.\n",
+            None,
+        )
+        .unwrap();
+        ctx.save_doc_content("study/other.md", "Untagged :: card\n", None)
+            .unwrap();
+
+        let apkg = dir.path().join("out/deck.apkg");
+        let options = AnkiExportOptions {
+            tag: Some("flashcards".into()),
+            ..Default::default()
+        };
+        let export = ctx.export_anki(&apkg, &options).unwrap();
+        assert_eq!((export.cards, export.deck.as_str()), (2, DEFAULT_DECK));
+        let first = notes_in(&apkg, dir.path());
+        assert_eq!(first.len(), 2);
+        assert_eq!(
+            first[0].2,
+            "Capital of France\x1fParis\x1fstudy/geo/capitals.md:2"
+        );
+        assert_eq!(first[0].3, " opencontext::study::geo ");
+
+        ctx.save_doc_content(
+            "study/geo/capitals.md",
+            "#flashcards\nCapital of France :: Paris & Île-de-France\n{{c1::Canberra}} is in Australia.\n",
+            None,
+        )
+        .unwrap();
+        ctx.export_anki(&apkg, &options).unwrap();
+        let second = notes_in(&apkg, dir.path());
+        let ids = |notes: &[(i64, String, String, String)]| -> Vec<(i64, String)> {
+            notes.iter().map(|n| (n.0, n.1.clone())).collect()
+        };
+        assert_eq!(ids(&first), ids(&second));
+        assert!(second[0].2.contains("Paris &amp; Île-de-France"));
+    }
+}
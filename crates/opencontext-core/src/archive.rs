@@ -0,0 +1,724 @@
+//! Workspace export / import
+//!
+//! A workspace archive is a zstd-compressed tarball holding everything needed
+//! to move a corpus to another machine:
+//!
+//! - `manifest.json`: format version and counts, always the first entry
+//! - `registry.json`: folder / doc descriptions, stable ids and timestamps
+//!   (the SQLite file itself is machine-specific: it stores absolute paths)
+//! - `config/`: `config.json` / `config.toml` with API keys and tokens removed
+//! - `contexts/`: the documents
+//! - `index/<name>`: optionally, the search index files, so the target does
+//!   not have to re-embed everything
+//!
+//! Importing never clobbers local state unless asked to: existing files and
+//! index directories are kept, and local config values win over archived ones.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    generate_stable_id, now_iso, parent_rel_path, wal, CoreError, CoreResult, OpenContext, RelPath,
+};
+
+#[cfg(feature = "search")]
+use crate::events::DocEvent;
+
+/// Bumped when the archive layout changes incompatibly
+pub const ARCHIVE_FORMAT: u32 = 1;
+
+const ZSTD_LEVEL: i32 = 9;
+
+/// Index files and directories to bundle or restore, keyed by a stable name
+/// (e.g. `("lancedb", ~/.opencontext/lancedb)`)
+pub type IndexFiles = Vec<(String, PathBuf)>;
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Index artifacts to include; empty exports documents and config only
+    pub index_files: IndexFiles,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// Replace local files and index data that also exist in the archive
+    pub overwrite: bool,
+    /// Where to restore archived index artifacts; names not listed are ignored
+    pub index_files: IndexFiles,
+}
+
+/// First entry of every archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format: u32,
+    pub created_at: String,
+    pub folders: usize,
+    pub docs: usize,
+    /// Names of the bundled index artifacts
+    pub index: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub manifest: ArchiveManifest,
+    /// Document files written
+    pub files: usize,
+    /// Files kept because they already exist locally
+    pub skipped_files: Vec<String>,
+    /// Docs newly registered in SQLite
+    pub registered_docs: usize,
+    pub index_restored: Vec<String>,
+    /// Index artifacts kept because they already exist locally
+    pub index_skipped: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Registry {
+    folders: Vec<FolderRecord>,
+    docs: Vec<DocRecord>,
+}
+
+impl Registry {
+    /// Normalize every path, refusing any that leaves the contexts root
+    fn normalize_paths(&mut self) -> CoreResult<()> {
+        for folder in &mut self.folders {
+            folder.rel_path = workspace_path(&folder.rel_path)?;
+        }
+        for doc in &mut self.docs {
+            doc.rel_path = workspace_path(&doc.rel_path)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FolderRecord {
+    rel_path: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DocRecord {
+    rel_path: String,
+    description: String,
+    stable_id: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl OpenContext {
+    /// Write the workspace to a `.tar.zst` archive at `dest`
+    pub fn export_workspace(
+        &self,
+        dest: &Path,
+        options: &ExportOptions,
+    ) -> CoreResult<ArchiveManifest> {
+        let registry = self.registry()?;
+        let index_files: Vec<&(String, PathBuf)> = options
+            .index_files
+            .iter()
+            .filter(|(_, path)| path.exists())
+            .collect();
+        let manifest = ArchiveManifest {
+            format: ARCHIVE_FORMAT,
+            created_at: now_iso(),
+            folders: registry.folders.len(),
+            docs: registry.docs.len(),
+            index: index_files.iter().map(|(name, _)| name.clone()).collect(),
+        };
+
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let encoder = zstd::Encoder::new(File::create(dest)?, ZSTD_LEVEL)?;
+        let mut tar = tar::Builder::new(encoder);
+        tar.follow_symlinks(false);
+
+        append_json(&mut tar, "manifest.json", &manifest)?;
+        append_json(&mut tar, "registry.json", &registry)?;
+
+        let json_config = self.base_root.join("config.json");
+        if let Ok(raw) = fs::read_to_string(&json_config) {
+            let mut value: serde_json::Value = serde_json::from_str(&raw).map_err(|e| {
+                CoreError::Message(format!("invalid {}: {e}", json_config.display()))
+            })?;
+            strip_secrets_json(&mut value);
+            append_json(&mut tar, "config/config.json", &value)?;
+        }
+        let toml_config = self.base_root.join("config.toml");
+        if let Ok(raw) = fs::read_to_string(&toml_config) {
+            let mut value: toml::Value = toml::from_str(&raw).map_err(|e| {
+                CoreError::Message(format!("invalid {}: {e}", toml_config.display()))
+            })?;
+            strip_secrets_toml(&mut value);
+            let raw = toml::to_string_pretty(&value)
+                .map_err(|e| CoreError::Message(format!("failed to write config.toml: {e}")))?;
+            append_bytes(&mut tar, "config/config.toml", raw.as_bytes())?;
+        }
+
+        tar.append_dir_all("contexts", &self.contexts_root)?;
+        for (name, path) in index_files {
+            let archived = format!("index/{name}");
+            if path.is_dir() {
+                tar.append_dir_all(&archived, path)?;
+            } else {
+                tar.append_path_with_name(path, &archived)?;
+            }
+        }
+
+        tar.into_inner()?.finish()?;
+        Ok(manifest)
+    }
+
+    /// Restore an archive written by [`export_workspace`](Self::export_workspace)
+    pub fn import_workspace(
+        &self,
+        src: &Path,
+        options: &ImportOptions,
+    ) -> CoreResult<ImportReport> {
+        self.ensure_writable("import workspaces")?;
+        let mut tar = tar::Archive::new(zstd::Decoder::new(File::open(src)?)?);
+
+        let mut manifest: Option<ArchiveManifest> = None;
+        let mut registry = Registry::default();
+        let mut files = 0;
+        let mut skipped_files = Vec::new();
+        // Per index artifact: restore it, or keep the local copy
+        let mut restore: HashMap<String, bool> = HashMap::new();
+
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let parts = archive_path(&entry.path()?)?;
+            // A link would let later entries "under" it land outside the workspace
+            let kind = entry.header().entry_type();
+            if kind.is_symlink() || kind.is_hard_link() {
+                return Err(CoreError::Message(format!(
+                    "refusing link {} in archive",
+                    parts.join("/")
+                )));
+            }
+            let Some((head, rest)) = parts.split_first() else {
+                continue;
+            };
+            if manifest.is_none() && head != "manifest.json" {
+                return Err(CoreError::Message(format!(
+                    "{} is not an OpenContext workspace archive",
+                    src.display()
+                )));
+            }
+
+            match head.as_str() {
+                "manifest.json" => {
+                    let parsed: ArchiveManifest = read_json(&mut entry)?;
+                    if parsed.format > ARCHIVE_FORMAT {
+                        return Err(CoreError::Message(format!(
+                            "archive format {} is newer than supported ({ARCHIVE_FORMAT}); upgrade OpenContext",
+                            parsed.format
+                        )));
+                    }
+                    manifest = Some(parsed);
+                }
+                "registry.json" => {
+                    registry = read_json(&mut entry)?;
+                    registry.normalize_paths()?;
+                }
+                "config" => self.merge_config(rest, &mut entry)?,
+                "contexts" if !rest.is_empty() => {
+                    let rel = workspace_path(&rest.join("/"))?;
+                    let target = self.abs_path(&rel);
+                    if entry.header().entry_type().is_dir() {
+                        fs::create_dir_all(&target)?;
+                    } else if target.exists() && !options.overwrite {
+                        skipped_files.push(rel);
+                    } else {
+                        if let Some(parent) = target.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        entry.unpack(&target)?;
+                        files += 1;
+                    }
+                }
+                "index" => {
+                    let Some((name, rest)) = rest.split_first() else {
+                        continue;
+                    };
+                    let Some((_, dest)) = options.index_files.iter().find(|(n, _)| n == name)
+                    else {
+                        continue;
+                    };
+                    let restoring = match restore.get(name) {
+                        Some(restoring) => *restoring,
+                        None => {
+                            let restoring = !dest.exists() || options.overwrite;
+                            if restoring {
+                                remove_path(dest)?;
+                            }
+                            restore.insert(name.clone(), restoring);
+                            restoring
+                        }
+                    };
+                    if !restoring {
+                        continue;
+                    }
+                    let target = rest.iter().fold(dest.clone(), |p, part| p.join(part));
+                    if entry.header().entry_type().is_dir() {
+                        fs::create_dir_all(&target)?;
+                    } else {
+                        if let Some(parent) = target.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        entry.unpack(&target)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let manifest = manifest
+            .ok_or_else(|| CoreError::Message(format!("{} is an empty archive", src.display())))?;
+        let mut index_restored: Vec<String> = restore
+            .iter()
+            .filter(|(_, restored)| **restored)
+            .map(|(name, _)| name.clone())
+            .collect();
+        let mut index_skipped: Vec<String> = restore
+            .into_iter()
+            .filter(|(_, restored)| !restored)
+            .map(|(name, _)| name)
+            .collect();
+        index_restored.sort();
+        index_skipped.sort();
+
+        // Without the archived vectors, new docs need embedding like any new doc
+        let reindex = index_restored.is_empty();
+        let registered_docs = self.apply_registry(registry, reindex)?;
+        // Unpacked (and possibly overwritten) files carry their own checkboxes
+        // and dates
+        self.reindex_tasks()?;
+        self.reindex_dates()?;
+
+        Ok(ImportReport {
+            manifest,
+            files,
+            skipped_files,
+            registered_docs,
+            index_restored,
+            index_skipped,
+        })
+    }
+
+    fn registry(&self) -> CoreResult<Registry> {
+        self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT rel_path, description FROM folders ORDER BY rel_path")?;
+            let folders = stmt
+                .query_map([], |row| {
+                    Ok(FolderRecord {
+                        rel_path: row.get(0)?,
+                        description: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut stmt = conn.prepare(
+                "SELECT rel_path, description, stable_id, created_at, updated_at FROM docs ORDER BY rel_path",
+            )?;
+            let docs = stmt
+                .query_map([], |row| {
+                    Ok(DocRecord {
+                        rel_path: row.get(0)?,
+                        description: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                        stable_id: row.get(2)?,
+                        created_at: row.get(3)?,
+                        updated_at: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Registry { folders, docs })
+        })
+    }
+
+    /// Register archived folders and docs; local descriptions and stable ids
+    /// are kept. Returns the number of newly registered docs.
+    fn apply_registry(&self, registry: Registry, reindex: bool) -> CoreResult<usize> {
+        for folder in &registry.folders {
+            if self.ensure_folder_record(&folder.rel_path)?.is_some() {
+                self.with_conn(|conn| {
+                    conn.execute(
+                        "UPDATE folders SET description = ?1 WHERE rel_path = ?2 AND COALESCE(description, '') = ''",
+                        params![folder.description, folder.rel_path],
+                    )?;
+                    Ok(())
+                })?;
+            }
+        }
+
+        let mut registered = 0;
+        for doc in registry.docs {
+            let abs_path = self.abs_path(&doc.rel_path);
+            let parent = parent_rel_path(&doc.rel_path).unwrap_or_default();
+            // Root-level docs have no folder row to hang off
+            if parent.is_empty() || !abs_path.is_file() {
+                continue;
+            }
+            if self.find_doc(&doc.rel_path)?.is_some() {
+                self.with_conn(|conn| {
+                    conn.execute(
+                        "UPDATE docs SET description = ?1 WHERE rel_path = ?2 AND COALESCE(description, '') = ''",
+                        params![doc.description, doc.rel_path],
+                    )?;
+                    Ok(())
+                })?;
+                continue;
+            }
+            let folder = self
+                .ensure_folder_record(&parent)?
+                .ok_or_else(|| crate::folder_not_found(&parent))?;
+            let name = crate::relpath::file_name(&doc.rel_path).to_string();
+            self.with_conn(|conn| {
+                let taken = match doc.stable_id.as_deref() {
+                    Some(sid) => conn
+                        .query_row("SELECT 1 FROM docs WHERE stable_id = ?1", [sid], |_| Ok(()))
+                        .optional()?
+                        .is_some(),
+                    None => true,
+                };
+                let stable_id = match doc.stable_id.as_deref() {
+                    Some(sid) if !taken => sid.to_string(),
+                    _ => generate_stable_id(conn)?,
+                };
+                conn.execute(
+                    "INSERT INTO docs (folder_id, name, rel_path, abs_path, description, stable_id, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        folder.id,
+                        name,
+                        doc.rel_path,
+                        abs_path.to_string_lossy(),
+                        doc.description,
+                        stable_id,
+                        doc.created_at,
+                        doc.updated_at
+                    ],
+                )?;
+                if reindex {
+                    wal::append(
+                        conn,
+                        &wal::WalOp::Update {
+                            rel_path: doc.rel_path.clone(),
+                        },
+                        &now_iso(),
+                    )?;
+                }
+                Ok(())
+            })?;
+
+            #[cfg(feature = "search")]
+            if reindex {
+                self.emit_doc_event(DocEvent::Created {
+                    rel_path: doc.rel_path.as_str().into(),
+                });
+            }
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
+    /// Fill in config values missing locally; local values always win
+    fn merge_config(&self, rest: &[String], entry: &mut impl Read) -> CoreResult<()> {
+        let [name] = rest else {
+            return Ok(());
+        };
+        let local = self.base_root.join(name);
+        match name.as_str() {
+            "config.json" => {
+                let archived: serde_json::Map<String, serde_json::Value> = read_json(entry)?;
+                let mut merged: serde_json::Map<String, serde_json::Value> =
+                    fs::read_to_string(&local)
+                        .ok()
+                        .and_then(|raw| serde_json::from_str(&raw).ok())
+                        .unwrap_or_default();
+                for (key, value) in archived {
+                    merged.entry(key).or_insert(value);
+                }
+                fs::create_dir_all(&self.base_root)?;
+                fs::write(
+                    &local,
+                    serde_json::to_string_pretty(&merged).unwrap_or_default(),
+                )?;
+            }
+            "config.toml" if !local.exists() => {
+                let mut raw = Vec::new();
+                entry.read_to_end(&mut raw)?;
+                fs::create_dir_all(&self.base_root)?;
+                fs::write(&local, raw)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Archive path split into components, rejecting anything that could
+/// escape the target directory
+fn archive_path(path: &Path) -> CoreResult<Vec<String>> {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| match c {
+            Component::Normal(part) => Ok(part.to_string_lossy().into_owned()),
+            _ => Err(CoreError::Message(format!(
+                "refusing unsafe archive path {}",
+                path.display()
+            ))),
+        })
+        .collect()
+}
+
+/// `rel_path` from an archive as a workspace path. Backslashes count as
+/// separators there, so `..` and drive prefixes are checked after
+/// normalizing.
+fn workspace_path(rel_path: &str) -> CoreResult<String> {
+    let normalized = RelPath::new(rel_path);
+    if normalized
+        .segments()
+        .any(|segment| segment == ".." || segment.contains(':'))
+    {
+        return Err(CoreError::Message(format!(
+            "refusing unsafe archive path {rel_path}"
+        )));
+    }
+    Ok(normalized.into_string())
+}
+
+fn remove_path(path: &Path) -> CoreResult<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn append_json<W: Write, T: Serialize>(
+    tar: &mut tar::Builder<W>,
+    name: &str,
+    value: &T,
+) -> CoreResult<()> {
+    let raw = serde_json::to_vec_pretty(value)
+        .map_err(|e| CoreError::Message(format!("failed to serialize {name}: {e}")))?;
+    append_bytes(tar, name, &raw)
+}
+
+fn append_bytes<W: Write>(tar: &mut tar::Builder<W>, name: &str, data: &[u8]) -> CoreResult<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(entry: &mut impl Read) -> CoreResult<T> {
+    let mut raw = String::new();
+    entry.read_to_string(&mut raw)?;
+    serde_json::from_str(&raw).map_err(|e| CoreError::Message(format!("corrupt archive: {e}")))
+}
+
+/// API keys, tokens and passwords stay on the exporting machine
+fn is_secret(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    ["key", "token", "secret", "password"]
+        .iter()
+        .any(|suffix| key.ends_with(suffix))
+}
+
+fn strip_secrets_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| !is_secret(key));
+            map.values_mut().for_each(strip_secrets_json);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_secrets_json),
+        _ => {}
+    }
+}
+
+fn strip_secrets_toml(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            table.retain(|key, _| !is_secret(key));
+            table.iter_mut().for_each(|(_, v)| strip_secrets_toml(v));
+        }
+        // Arrays of tables, e.g. `[[sources.github]]`
+        toml::Value::Array(items) => items.iter_mut().for_each(strip_secrets_toml),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnvOverrides;
+    use tempfile::TempDir;
+
+    fn workspace(root: &Path) -> OpenContext {
+        OpenContext::initialize(EnvOverrides {
+            base_root: Some(root.to_path_buf()),
+            contexts_root: Some(root.join("contexts")),
+            db_path: Some(root.join("opencontext.db")),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let source_dir = TempDir::new().unwrap();
+        let source = workspace(source_dir.path());
+        source.create_folder("notes", Some("Team notes")).unwrap();
+        let created = source.create_doc("notes", "a.md", Some("First")).unwrap();
+        source
+            .save_doc_content("notes/a.md", "# A\n", None)
+            .unwrap();
+        fs::write(
+            source_dir.path().join("config.json"),
+            r#"{ "EMBEDDING_MODEL": "m", "EMBEDDING_API_KEY": "sk-secret" }"#,
+        )
+        .unwrap();
+        let index = source_dir.path().join("bm25-index");
+        fs::create_dir_all(&index).unwrap();
+        fs::write(index.join("meta.json"), "{}").unwrap();
+
+        let archive = source_dir.path().join("out/workspace.tar.zst");
+        let manifest = source
+            .export_workspace(
+                &archive,
+                &ExportOptions {
+                    index_files: vec![("bm25-index".into(), index)],
+                },
+            )
+            .unwrap();
+        assert_eq!((manifest.folders, manifest.docs), (1, 1));
+        assert_eq!(manifest.index, vec!["bm25-index"]);
+
+        let target_dir = TempDir::new().unwrap();
+        let target = workspace(target_dir.path());
+        let report = target
+            .import_workspace(
+                &archive,
+                &ImportOptions {
+                    overwrite: false,
+                    index_files: vec![("bm25-index".into(), target_dir.path().join("bm25"))],
+                },
+            )
+            .unwrap();
+        assert_eq!(report.registered_docs, 1);
+        assert_eq!(report.index_restored, vec!["bm25-index"]);
+        assert!(target_dir.path().join("bm25/meta.json").exists());
+
+        let doc = target.get_doc_meta("notes/a.md").unwrap();
+        assert_eq!(doc.stable_id, created.stable_id);
+        assert_eq!(doc.description, "First");
+        assert_eq!(target.get_doc_content("notes/a.md").unwrap(), "# A\n");
+
+        let config = fs::read_to_string(target_dir.path().join("config.json")).unwrap();
+        assert!(config.contains("EMBEDDING_MODEL"));
+        assert!(!config.contains("sk-secret"));
+
+        // A second import keeps everything that is already there
+        let again = target
+            .import_workspace(&archive, &ImportOptions::default())
+            .unwrap();
+        assert_eq!(again.registered_docs, 0);
+        assert_eq!(again.skipped_files, vec!["notes/a.md"]);
+    }
+
+    /// Archive holding a manifest and whatever `fill` appends
+    fn craft_archive(path: &Path, fill: impl FnOnce(&mut tar::Builder<Vec<u8>>)) {
+        let mut tar = tar::Builder::new(Vec::new());
+        let manifest = ArchiveManifest {
+            format: ARCHIVE_FORMAT,
+            created_at: now_iso(),
+            folders: 0,
+            docs: 0,
+            index: vec![],
+        };
+        append_json(&mut tar, "manifest.json", &manifest).unwrap();
+        fill(&mut tar);
+        let raw = tar.into_inner().unwrap();
+        fs::write(path, zstd::encode_all(raw.as_slice(), 0).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_import_refuses_paths_outside_workspace() {
+        let dir = TempDir::new().unwrap();
+        let target = workspace(&dir.path().join("target"));
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        let archive = dir.path().join("link.tar.zst");
+        craft_archive(&archive, |tar| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            header.set_mode(0o777);
+            header.set_cksum();
+            tar.append_link(&mut header, "contexts/link", &outside)
+                .unwrap();
+            append_bytes(tar, "contexts/link/evil.md", b"pwned").unwrap();
+        });
+        let err = target
+            .import_workspace(&archive, &ImportOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("refusing link"), "{err}");
+        assert!(!outside.join("evil.md").exists());
+
+        let archive = dir.path().join("registry.tar.zst");
+        craft_archive(&archive, |tar| {
+            let registry = serde_json::json!({
+                "folders": [],
+                "docs": [{
+                    "rel_path": "notes/../../escape.md",
+                    "description": "",
+                    "stable_id": null,
+                    "created_at": "",
+                    "updated_at": "",
+                }],
+            });
+            append_json(tar, "registry.json", &registry).unwrap();
+        });
+        let err = target
+            .import_workspace(&archive, &ImportOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("unsafe archive path"), "{err}");
+
+        let archive = dir.path().join("backslash.tar.zst");
+        craft_archive(&archive, |tar| {
+            append_bytes(tar, "contexts/a\\..\\..\\..\\evil.md", b"pwned").unwrap();
+        });
+        assert!(target
+            .import_workspace(&archive, &ImportOptions::default())
+            .is_err());
+        assert!(!dir.path().join("evil.md").exists());
+    }
+
+    #[test]
+    fn test_is_secret() {
+        assert!(is_secret("EMBEDDING_API_KEY"));
+        assert!(is_secret("api_key"));
+        assert!(!is_secret("keyword_weight"));
+        assert!(!is_secret("EMBEDDING_MODEL"));
+
+        let mut config: toml::Value =
+            toml::from_str("[[sources.github]]\nrepos = [\"a/b\"]\ntoken = \"ghp_x\"\n").unwrap();
+        strip_secrets_toml(&mut config);
+        let github = &config["sources"]["github"][0];
+        assert!(github.get("token").is_none());
+        assert!(github.get("repos").is_some());
+    }
+}
@@ -0,0 +1,491 @@
+//! BibTeX references
+//!
+//! `oc import bibtex` turns every entry of a `.bib` file (hand written, or
+//! kept up to date by Zotero's Better BibTeX auto-export) into a reference
+//! note at `<folder>/<citekey>.md`, so `[[smith2020]]` links to it and
+//! authors, venue, abstract and keywords are searchable. Frontmatter keeps
+//! the citation key, `@key` as an alias and the bibliographic fields;
+//! keywords become `tags`.
+//!
+//! The note body above `## Notes` is regenerated on every import; what is
+//! written under `## Notes` is the reader's and survives re-imports.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::memory::clean_tags;
+use crate::{frontmatter, CoreError, CoreResult, OpenContext};
+
+/// Heading of the section that re-imports leave alone
+const NOTES_HEADING: &str = "## Notes";
+
+/// One `@type{key, ...}` entry
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BibEntry {
+    /// Lowercase entry type, e.g. `article`
+    pub kind: String,
+    pub key: String,
+    /// Lowercase field names to values with braces and LaTeX escapes removed
+    pub fields: HashMap<String, String>,
+}
+
+impl BibEntry {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Authors (editors if there are none) as `First Last`
+    pub fn authors(&self) -> Vec<String> {
+        let names = self.field("author").or_else(|| self.field("editor"));
+        names
+            .map(|names| {
+                names
+                    .split(" and ")
+                    .map(|name| match name.split_once(',') {
+                        Some((last, first)) => format!("{} {}", first.trim(), last.trim()),
+                        None => name.trim().to_string(),
+                    })
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// `year`, or the year of a biblatex `date`
+    pub fn year(&self) -> Option<&str> {
+        self.field("year")
+            .or_else(|| self.field("date").and_then(|d| d.get(0..4)))
+    }
+
+    /// Journal, proceedings or publisher
+    pub fn venue(&self) -> Option<&str> {
+        [
+            "journal",
+            "journaltitle",
+            "booktitle",
+            "publisher",
+            "school",
+            "institution",
+        ]
+        .iter()
+        .find_map(|name| self.field(name))
+    }
+}
+
+/// What [`OpenContext::import_bibtex`] wrote
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BibtexImport {
+    /// Folder the notes were written to
+    pub folder: String,
+    /// Documents created
+    pub created: Vec<String>,
+    /// Existing documents that changed
+    pub updated: Vec<String>,
+    /// Documents that were already up to date
+    pub unchanged: usize,
+    pub entries: usize,
+}
+
+/// Entries of a `.bib` file. `@string` abbreviations are expanded;
+/// `@comment`, `@preamble` and entries without a key are skipped.
+pub fn parse_bibtex(source: &str) -> Vec<BibEntry> {
+    let mut strings: HashMap<String, String> = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, m)| (m.to_string(), (i + 1).to_string()))
+    .collect();
+    let mut entries = Vec::new();
+    let mut rest = source;
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let kind_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let kind = rest[..kind_len].to_ascii_lowercase();
+        let body_start = rest[kind_len..].trim_start();
+        let Some(open) = body_start.chars().next().filter(|c| matches!(c, '{' | '(')) else {
+            continue;
+        };
+        let close = if open == '{' { '}' } else { ')' };
+        let body = &body_start[1..];
+        let end = matching_end(body, open, close);
+        rest = &body[(end + 1).min(body.len())..];
+        let body = &body[..end];
+        match kind.as_str() {
+            "comment" | "preamble" => {}
+            "string" => {
+                for (name, value) in parse_fields(body, &strings) {
+                    strings.insert(name, value);
+                }
+            }
+            _ => {
+                let (key, fields) = body.split_once(',').unwrap_or((body, ""));
+                let key = key.trim();
+                if key.is_empty() || key.contains(char::is_whitespace) {
+                    continue;
+                }
+                entries.push(BibEntry {
+                    kind,
+                    key: key.to_string(),
+                    fields: parse_fields(fields, &strings).into_iter().collect(),
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Index of the `close` matching an already consumed `open`
+fn matching_end(s: &str, open: char, close: char) -> usize {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        if c == open || (open == '(' && c == '{') {
+            depth += 1;
+        } else if c == close || (open == '(' && c == '}') {
+            if depth == 0 && c == close {
+                return i;
+            }
+            depth = depth.saturating_sub(1);
+        }
+    }
+    s.len()
+}
+
+/// `name = value, ...` pairs, values concatenated with `#` and cleaned
+fn parse_fields(s: &str, strings: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        let Some(eq) = rest.find('=') else {
+            break;
+        };
+        let name = rest[..eq].trim().to_ascii_lowercase();
+        rest = &rest[eq + 1..];
+        let mut value = String::new();
+        loop {
+            rest = rest.trim_start();
+            let (part, len) = match rest.chars().next() {
+                Some('{') => {
+                    let end = matching_end(&rest[1..], '{', '}');
+                    (rest[1..1 + end].to_string(), end + 2)
+                }
+                Some('"') => {
+                    let end = quoted_end(&rest[1..]);
+                    (rest[1..1 + end].to_string(), end + 2)
+                }
+                Some(_) => {
+                    let end = rest.find([',', '#', '}']).unwrap_or(rest.len());
+                    let word = rest[..end].trim();
+                    let expanded = strings
+                        .get(&word.to_ascii_lowercase())
+                        .cloned()
+                        .unwrap_or_else(|| word.to_string());
+                    (expanded, end)
+                }
+                None => break,
+            };
+            value.push_str(&part);
+            rest = &rest[len.min(rest.len())..];
+            match rest.trim_start().strip_prefix('#') {
+                Some(after) => rest = after,
+                None => break,
+            }
+        }
+        if !name.is_empty() {
+            fields.push((name, clean_value(&value)));
+        }
+    }
+    fields
+}
+
+/// Index of the `"` ending a quoted value, skipping braced groups
+fn quoted_end(s: &str) -> usize {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '"' if depth == 0 => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Drop braces, decode the common LaTeX accents and escapes and collapse
+/// whitespace
+fn clean_value(value: &str) -> String {
+    const ACCENTS: &[(char, &str, &str)] = &[
+        ('"', "aeiouAEIOU", "äëïöüÄËÏÖÜ"),
+        ('\'', "aeiouyAEIOUYcnsz", "áéíóúýÁÉÍÓÚÝćńśź"),
+        ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        ('~', "anoANO", "ãñõÃÑÕ"),
+        ('c', "cC", "çÇ"),
+        ('v', "csznrCSZNR", "čšžňřČŠŽŇŘ"),
+    ];
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => {}
+            '~' => out.push(' '),
+            '\\' => {
+                let Some(&next) = chars.peek() else {
+                    continue;
+                };
+                if let Some((_, from, to)) = ACCENTS.iter().find(|(accent, _, _)| *accent == next) {
+                    chars.next();
+                    while chars.peek().is_some_and(|c| *c == '{' || *c == ' ') {
+                        chars.next();
+                    }
+                    if let Some(letter) = chars.next() {
+                        match from.chars().position(|f| f == letter) {
+                            Some(i) => out.extend(to.chars().nth(i)),
+                            None => out.push(letter),
+                        }
+                    }
+                } else if next.is_ascii_alphabetic() {
+                    let command: String =
+                        std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_alphabetic())).collect();
+                    out.push_str(match command.as_str() {
+                        "ss" => "ß",
+                        "o" => "ø",
+                        "O" => "Ø",
+                        "aa" => "å",
+                        "AA" => "Å",
+                        "ae" => "æ",
+                        "l" => "ł",
+                        "TeX" => "TeX",
+                        "LaTeX" => "LaTeX",
+                        // Formatting commands like \emph keep only their argument
+                        _ => "",
+                    });
+                } else {
+                    out.push(next);
+                    chars.next();
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out.replace("---", "—")
+        .replace("--", "–")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// File name for a citation key: characters that are unsafe in paths
+/// become `-`
+pub fn note_name(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Generated part of the note for `entry`, ending with the notes heading
+fn render_reference(entry: &BibEntry) -> CoreResult<String> {
+    let title = entry.field("title").unwrap_or(&entry.key);
+    let authors = entry.authors();
+    let mut citation = authors.join(", ");
+    if let Some(year) = entry.year() {
+        citation.push_str(&format!(" ({year})"));
+    }
+    let mut details: Vec<String> = Vec::new();
+    if let Some(venue) = entry.venue() {
+        details.push(format!("*{venue}*"));
+    }
+    if let Some(volume) = entry.field("volume") {
+        match entry.field("number") {
+            Some(number) => details.push(format!("{volume}({number})")),
+            None => details.push(volume.to_string()),
+        }
+    }
+    if let Some(pages) = entry.field("pages") {
+        details.push(format!("pp. {pages}"));
+    }
+    let mut body = format!("\n# {title}\n\n");
+    let citation = citation.trim();
+    match (citation.is_empty(), details.is_empty()) {
+        (false, false) => body.push_str(&format!("{citation}. {}.\n", details.join(", "))),
+        (false, true) => body.push_str(&format!("{citation}.\n")),
+        (true, false) => body.push_str(&format!("{}.\n", details.join(", "))),
+        (true, true) => body.push_str(&format!("`@{}`\n", entry.key)),
+    }
+    if let Some(doi) = entry.field("doi") {
+        body.push_str(&format!("\nhttps://doi.org/{doi}\n"));
+    }
+    if let Some(abstract_) = entry.field("abstract") {
+        body.push_str(&format!("\n## Abstract\n\n{abstract_}\n"));
+    }
+    body.push_str(&format!("\n{NOTES_HEADING}\n"));
+
+    let keywords: Vec<String> = entry
+        .field("keywords")
+        .map(|k| k.split([',', ';']).map(|k| k.trim().to_string()).collect())
+        .unwrap_or_default();
+    let mut fields: Vec<(&str, Value)> = vec![
+        ("citekey", Value::from(entry.key.as_str())),
+        ("aliases", Value::from(vec![format!("@{}", entry.key)])),
+        ("entry_type", Value::from(entry.kind.as_str())),
+        ("title", Value::from(title)),
+    ];
+    if !authors.is_empty() {
+        fields.push(("authors", Value::from(authors)));
+    }
+    for (key, value) in [
+        ("year", entry.year()),
+        ("venue", entry.venue()),
+        ("doi", entry.field("doi")),
+        ("url", entry.field("url")),
+    ] {
+        if let Some(value) = value {
+            fields.push((key, Value::from(value)));
+        }
+    }
+    let tags = clean_tags(&keywords);
+    if !tags.is_empty() {
+        fields.push(("tags", Value::from(tags)));
+    }
+    fields.iter().try_fold(body, |content, (key, value)| {
+        frontmatter::set_field(&content, key, value)
+    })
+}
+
+/// `generated` followed by what the reader wrote under the notes heading
+/// of `existing`
+fn keep_notes(generated: &str, existing: Option<&str>) -> String {
+    let notes = existing
+        .and_then(|existing| {
+            let at = existing.find(&format!("\n{NOTES_HEADING}\n"))?;
+            Some(&existing[at + NOTES_HEADING.len() + 2..])
+        })
+        .unwrap_or("");
+    format!("{generated}{notes}")
+}
+
+impl OpenContext {
+    /// Write one reference note per entry under `folder`, keeping the notes
+    /// section of existing ones
+    pub fn import_bibtex(&self, folder: &str, entries: &[BibEntry]) -> CoreResult<BibtexImport> {
+        let folder = folder.trim_matches('/').to_string();
+        if folder.is_empty() {
+            return Err(CoreError::Message(
+                "references need a folder to go into".into(),
+            ));
+        }
+        let mut import = BibtexImport {
+            folder: folder.clone(),
+            entries: entries.len(),
+            ..Default::default()
+        };
+        for entry in entries {
+            let rel_path = format!("{folder}/{}.md", note_name(&entry.key));
+            let existing = match self.find_doc(&rel_path)? {
+                Some(doc) => Some(std::fs::read_to_string(&doc.abs_path)?),
+                None => None,
+            };
+            let content = keep_notes(&render_reference(entry)?, existing.as_deref());
+            match self.put_generated_doc(&rel_path, &content)? {
+                Some(true) => import.created.push(rel_path),
+                Some(false) => import.updated.push(rel_path),
+                None => import.unchanged += 1,
+            }
+        }
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIB: &str = r#"
+% Exported by Better BibTeX
+@string{ pami = "IEEE Trans. Pattern Anal." }
+@comment{ jabref-meta: databaseType:bibtex; }
+
+@article{smith:2020,
+  author   = {Smith, Jane and M{\"u}ller, J{\"o}rg and Bob Lee},
+  title    = {{Attention} Is Not {All} You Need},
+  journal  = pami # " 42",
+  year     = 2020,
+  month    = mar,
+  volume   = {42},
+  number   = {3},
+  pages    = {1--12},
+  doi      = {10.1000/xyz},
+  keywords = {transformers, deep learning},
+  abstract = "We show that {attention}
+              is overrated.",
+}
+
+@book(knuth84, title = "The {\TeX}book", publisher = {Addison--Wesley}, date = {1984-01-01})
+"#;
+
+    #[test]
+    fn test_parse_bibtex() {
+        let entries = parse_bibtex(BIB);
+        assert_eq!(entries.len(), 2);
+        let smith = &entries[0];
+        assert_eq!(smith.kind, "article");
+        assert_eq!(smith.key, "smith:2020");
+        assert_eq!(smith.fields["title"], "Attention Is Not All You Need");
+        assert_eq!(smith.fields["journal"], "IEEE Trans. Pattern Anal. 42");
+        assert_eq!(smith.fields["month"], "3");
+        assert_eq!(smith.fields["pages"], "1–12");
+        assert_eq!(
+            smith.fields["abstract"],
+            "We show that attention is overrated."
+        );
+        assert_eq!(
+            smith.authors(),
+            vec!["Jane Smith", "Jörg Müller", "Bob Lee"]
+        );
+        let knuth = &entries[1];
+        assert_eq!(knuth.fields["title"], "The TeXbook");
+        assert_eq!(knuth.year(), Some("1984"));
+        assert_eq!(knuth.venue(), Some("Addison–Wesley"));
+        assert_eq!(note_name(&smith.key), "smith-2020");
+    }
+
+    #[test]
+    fn test_render_reference_keeps_notes() {
+        let entry = &parse_bibtex(BIB)[0];
+        let generated = render_reference(entry).unwrap();
+        let fields = frontmatter::fields(&generated);
+        assert_eq!(fields["citekey"], "smith:2020");
+        assert_eq!(fields["aliases"], serde_json::json!(["@smith:2020"]));
+        assert_eq!(fields["year"], "2020");
+        assert_eq!(
+            fields["tags"],
+            serde_json::json!(["transformers", "deep-learning"])
+        );
+        assert_eq!(
+            frontmatter::body(&generated),
+            "\n# Attention Is Not All You Need\n\nJane Smith, Jörg Müller, Bob Lee (2020). *IEEE Trans. Pattern Anal. 42*, 42(3), pp. 1–12.\n\nhttps://doi.org/10.1000/xyz\n\n## Abstract\n\nWe show that attention is overrated.\n\n## Notes\n"
+        );
+
+        let edited = format!("{generated}\nSection 3 contradicts [[knuth84]].\n");
+        let regenerated = generated.replace("(2020)", "(2021)");
+        assert_eq!(
+            keep_notes(&regenerated, Some(&edited)),
+            format!("{regenerated}\nSection 3 contradicts [[knuth84]].\n")
+        );
+        assert_eq!(keep_notes(&generated, None), generated);
+    }
+}
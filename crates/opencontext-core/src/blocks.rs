@@ -0,0 +1,169 @@
+//! Block references
+//!
+//! A paragraph or list item ending in `^block-id` can be linked, embedded
+//! (`![[note#^block-id]]`) and fetched on its own, as in Obsidian. Unlike a
+//! line range, the id stays with its text while the note around it is
+//! edited. A `^block-id` alone on a line names the block just above it,
+//! which is how tables and quotes get one.
+
+use serde::Serialize;
+
+use crate::embeds::heading_level;
+use crate::{CoreError, CoreResult, OpenContext};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Block {
+    /// Without the `^`
+    pub id: String,
+    /// Text of the block, `^block-id` marker removed
+    pub text: String,
+    /// 1-based, inclusive
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+/// The block id `line` ends with, and the line without it
+fn marker(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_end();
+    let at = trimmed.rfind('^')?;
+    let id = &trimmed[at + 1..];
+    let before = &trimmed[..at];
+    let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    (valid && (before.is_empty() || before.ends_with([' ', '\t']))).then(|| (id, before.trim_end()))
+}
+
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") "))
+}
+
+/// Blocks with a `^block-id` in `content`, skipping frontmatter and code
+/// fences. A marked heading or list item is a block by itself; otherwise
+/// the block is the run of non-blank lines the marker ends.
+pub fn parse_blocks(content: &str) -> Vec<Block> {
+    let header = &content[..content.len() - crate::frontmatter::body(content).len()];
+    let skip = header.matches('\n').count();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut blocks = Vec::new();
+    let mut fence: Option<&str> = None;
+    // First line of the current run of non-blank lines, and of the last
+    // finished one
+    let mut run_start: Option<usize> = None;
+    let mut previous: Option<(usize, usize)> = None;
+    for (idx, line) in lines.iter().enumerate().skip(skip) {
+        let trimmed = line.trim_start();
+        if let Some(open) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(current) if current == open => None,
+                None => Some(open),
+                other => other,
+            };
+            run_start.get_or_insert(idx);
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        if trimmed.is_empty() {
+            if let Some(start) = run_start.take() {
+                previous = Some((start, idx - 1));
+            }
+            continue;
+        }
+        // A heading stands alone, whatever is around it
+        let heading = heading_level(trimmed) > 0;
+        if heading {
+            if let Some(start) = run_start.take() {
+                previous = Some((start, idx - 1));
+            }
+        }
+        let Some((id, text)) = marker(line) else {
+            if heading {
+                previous = Some((idx, idx));
+            } else {
+                run_start.get_or_insert(idx);
+            }
+            continue;
+        };
+        let range = if !text.is_empty() && (heading || is_list_item(line)) {
+            Some((idx, idx))
+        } else if !text.is_empty() {
+            Some((run_start.unwrap_or(idx), idx))
+        } else {
+            // A marker alone names the lines above it
+            match run_start {
+                Some(start) => Some((start, idx - 1)),
+                None => previous,
+            }
+        };
+        if let Some((start, end)) = range {
+            let text = (start..=end)
+                .map(|i| if i == idx { text } else { lines[i] })
+                .collect::<Vec<_>>()
+                .join("\n");
+            blocks.push(Block {
+                id: id.to_string(),
+                text: text.trim().to_string(),
+                line_start: start + 1,
+                line_end: end + 1,
+            });
+        }
+        // The marker ends its block
+        run_start = None;
+        previous = range;
+    }
+    blocks
+}
+
+/// The block `^id` (given with or without the `^`) of `content`
+pub fn find_block(content: &str, id: &str) -> Option<Block> {
+    let id = id.strip_prefix('^').unwrap_or(id);
+    parse_blocks(content).into_iter().find(|b| b.id == id)
+}
+
+impl OpenContext {
+    /// The block `^block_id` of the document at `doc_path`
+    pub fn get_block(&self, doc_path: &str, block_id: &str) -> CoreResult<Block> {
+        let content = self.get_doc_content(doc_path)?;
+        find_block(&content, block_id).ok_or_else(|| {
+            CoreError::Message(format!(
+                "Block \"^{}\" not found in \"{doc_path}\".",
+                block_id.trim_start_matches('^')
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks() {
+        let content = "---\nnote: x ^front\n---\n# Notes\n\
+                       First line\nof a paragraph. ^para\n\
+                       \n- one\n- two ^item\n\
+                       \n| a | b |\n|---|---|\n\n^table\n\
+                       \n```\ncode ^fenced\n```\n\
+                       not^marker and x ^bad_id\n";
+        let blocks = parse_blocks(content);
+        let summary: Vec<(&str, &str, usize, usize)> = blocks
+            .iter()
+            .map(|b| (b.id.as_str(), b.text.as_str(), b.line_start, b.line_end))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("para", "First line\nof a paragraph.", 5, 6),
+                ("item", "- two", 9, 9),
+                ("table", "| a | b |\n|---|---|", 11, 12),
+            ]
+        );
+        assert_eq!(find_block(content, "^item").unwrap().line_start, 9);
+        assert!(find_block(content, "front").is_none());
+    }
+}
@@ -0,0 +1,251 @@
+//! Quick capture
+//!
+//! [`OpenContext::capture`] appends a snippet to the inbox note for the
+//! current day (`inbox/YYYY-MM-DD.md`), creating folder and note as needed.
+//! Each capture becomes a section headed by its local time and source:
+//!
+//! ```text
+//! ## 14:32 · clipboard
+//!
+//! <captured text>
+//!
+//! Source: <https://example.com/page>
+//! ```
+//!
+//! Besides the usual `updated` event the write emits a `captured` event,
+//! which the index sync service applies right away instead of waiting for
+//! its next batch.
+//!
+//! [`ClipboardFilter`] holds the rules the desktop app's clipboard watcher
+//! applies before capturing a copy.
+
+use chrono::Local;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+#[cfg(feature = "keyword")]
+use crate::events::DocEvent;
+use crate::{content_revision, now_iso, CoreError, CoreResult, OpenContext};
+
+/// Folder that holds the dated inbox notes
+pub const INBOX_DIR: &str = "inbox";
+
+/// Where captured text came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureSource {
+    /// Typed or passed as an argument
+    #[default]
+    Text,
+    Clipboard,
+    Stdin,
+    /// Clipped from a web page
+    Url,
+    /// Saved by an agent through MCP
+    Agent,
+}
+
+impl CaptureSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CaptureSource::Text => "text",
+            CaptureSource::Clipboard => "clipboard",
+            CaptureSource::Stdin => "stdin",
+            CaptureSource::Url => "url",
+            CaptureSource::Agent => "agent",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(CaptureSource::Text),
+            "clipboard" => Some(CaptureSource::Clipboard),
+            "stdin" => Some(CaptureSource::Stdin),
+            "url" => Some(CaptureSource::Url),
+            "agent" => Some(CaptureSource::Agent),
+            _ => None,
+        }
+    }
+}
+
+/// Result of [`OpenContext::capture`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Captured {
+    /// Inbox note the text was appended to
+    pub rel_path: String,
+    pub created_at: String,
+    pub source: CaptureSource,
+    pub url: Option<String>,
+}
+
+/// How many captured clipboard texts are remembered for deduplication
+const SEEN_CLIPBOARD_TEXTS: usize = 256;
+
+/// Decides which clipboard contents get captured: long enough but not too
+/// long, copied in an allowed app, and not captured before
+#[derive(Debug, Clone)]
+pub struct ClipboardFilter {
+    min_length: usize,
+    max_length: usize,
+    apps: Vec<String>,
+    /// Hash of the clipboard text last looked at
+    current: Option<String>,
+    /// Hashes of captured texts, oldest first
+    seen: VecDeque<String>,
+}
+
+impl ClipboardFilter {
+    /// `apps` empty allows every app
+    pub fn new(min_length: usize, max_length: usize, apps: &[String]) -> Self {
+        Self {
+            min_length,
+            max_length,
+            apps: apps.to_vec(),
+            current: None,
+            seen: VecDeque::new(),
+        }
+    }
+
+    /// Whether the filter needs to know which app the text was copied in
+    pub fn checks_app(&self) -> bool {
+        !self.apps.is_empty()
+    }
+
+    /// Treat `text` as already looked at, e.g. what is on the clipboard when
+    /// the watcher starts
+    pub fn skip(&mut self, text: &str) {
+        self.current = Some(content_revision(text.trim().as_bytes()));
+    }
+
+    /// Whether `text` differs from the clipboard text last looked at; cheap
+    /// enough to call before finding out which app it was copied in
+    pub fn is_new(&self, text: &str) -> bool {
+        self.current.as_deref() != Some(content_revision(text.trim().as_bytes()).as_str())
+    }
+
+    /// Whether clipboard `text`, copied in `app`, should be captured. A
+    /// clipboard that did not change since the last call is never accepted
+    /// twice, and neither is text captured earlier.
+    pub fn accept(&mut self, text: &str, app: Option<&str>) -> bool {
+        let text = text.trim();
+        let hash = content_revision(text.as_bytes());
+        if self.current.as_ref() == Some(&hash) {
+            return false;
+        }
+        self.current = Some(hash.clone());
+
+        let length = text.chars().count();
+        if length < self.min_length || length > self.max_length {
+            return false;
+        }
+        if self.checks_app()
+            && !app.is_some_and(|app| self.apps.iter().any(|a| a.eq_ignore_ascii_case(app)))
+        {
+            return false;
+        }
+        if self.seen.contains(&hash) {
+            return false;
+        }
+        if self.seen.len() == SEEN_CLIPBOARD_TEXTS {
+            self.seen.pop_front();
+        }
+        self.seen.push_back(hash);
+        true
+    }
+}
+
+/// Inbox section for one capture
+fn render_capture(time: &str, text: &str, source: CaptureSource, url: Option<&str>) -> String {
+    let mut section = format!("## {time} · {}\n\n{text}\n", source.as_str());
+    if let Some(url) = url {
+        section.push_str(&format!("\nSource: <{url}>\n"));
+    }
+    section
+}
+
+impl OpenContext {
+    /// Append `text` to today's inbox note. `url` records the page the text
+    /// came from and is required for [`CaptureSource::Url`].
+    pub fn capture(
+        &self,
+        text: &str,
+        source: CaptureSource,
+        url: Option<&str>,
+    ) -> CoreResult<Captured> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(CoreError::Message("Nothing to capture.".into()));
+        }
+        let url = url.map(str::trim).filter(|u| !u.is_empty());
+        if url.is_some_and(|u| u.chars().any(char::is_whitespace)) {
+            return Err(CoreError::Message(
+                "Capture URL must not contain spaces.".into(),
+            ));
+        }
+        if source == CaptureSource::Url && url.is_none() {
+            return Err(CoreError::Message("URL captures need the page URL.".into()));
+        }
+
+        let now = Local::now();
+        let day = now.format("%Y-%m-%d").to_string();
+        let name = format!("{day}.md");
+        let rel_path = format!("{INBOX_DIR}/{name}");
+        if self.find_doc(&rel_path)?.is_none() {
+            if self.find_folder(INBOX_DIR)?.is_none() {
+                self.create_folder(INBOX_DIR, Some("Quick captures, one note per day"))?;
+            }
+            self.create_doc(INBOX_DIR, &name, Some(&format!("Captures of {day}")))?;
+        }
+
+        let section = render_capture(&now.format("%H:%M").to_string(), text, source, url);
+        self.edit_doc_content(&rel_path, |content| {
+            Ok(if content.trim().is_empty() {
+                format!("# Inbox {day}\n\n{section}")
+            } else {
+                format!("{}\n\n{section}", content.trim_end())
+            })
+        })?;
+
+        #[cfg(feature = "keyword")]
+        self.emit_doc_event(DocEvent::Captured {
+            rel_path: rel_path.as_str().into(),
+            source: source.as_str().to_string(),
+        });
+
+        Ok(Captured {
+            rel_path,
+            created_at: now_iso(),
+            source,
+            url: url.map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_filter_rules_and_dedup() {
+        let quote = "Premature optimization is the root of all evil";
+        let mut filter = ClipboardFilter::new(20, 100, &[]);
+        filter.skip("on the clipboard at startup, long enough to capture");
+        assert!(!filter.accept("on the clipboard at startup, long enough to capture", None));
+        assert!(!filter.accept("hunter2", None));
+        assert!(!filter.accept(&"x".repeat(101), None));
+        assert!(filter.accept(quote, None));
+        // Unchanged clipboard, then the same text copied again later
+        assert!(!filter.accept(quote, None));
+        assert!(filter.accept("Something else entirely, copied in between", None));
+        assert!(!filter.accept(&format!("  {quote}\n"), None));
+
+        let mut filter = ClipboardFilter::new(20, 100, &["Safari".to_string()]);
+        assert!(filter.checks_app());
+        assert!(filter.is_new(quote));
+        filter.skip(quote);
+        assert!(!filter.is_new(&format!("{quote}\n")));
+        assert!(!filter.accept(quote, Some("Terminal")));
+        assert!(!filter.accept("Copied while the frontmost app is unknown", None));
+        assert!(filter.accept("Copied from a page in the browser", Some("safari")));
+    }
+}
@@ -0,0 +1,408 @@
+//! Date index
+//!
+//! Every date a document mentions is recorded in the `doc_dates` table: date
+//! valued frontmatter fields (`due: 2024-08-01`), the `created_at` of idea
+//! entries and `YYYY-MM-DD` dates written inline in the body. Like tasks, the
+//! rows are rebuilt whenever a doc's content is written or reconciled, which
+//! makes "everything touching 2024-Q3" a single range query.
+
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+
+use crate::ideas::parse_idea_marker;
+use crate::{like_children, normalize_folder_path, CoreError, CoreResult, OpenContext};
+
+const CONTEXT_MAX_CHARS: usize = 160;
+
+/// Where in a document a date was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateSource {
+    Frontmatter,
+    Entry,
+    Inline,
+}
+
+impl DateSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DateSource::Frontmatter => "frontmatter",
+            DateSource::Entry => "entry",
+            DateSource::Inline => "inline",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "frontmatter" => Some(DateSource::Frontmatter),
+            "entry" => Some(DateSource::Entry),
+            "inline" => Some(DateSource::Inline),
+            _ => None,
+        }
+    }
+}
+
+/// A date mentioned in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedDate {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub source: DateSource,
+    /// 1-based line number
+    pub line: usize,
+    /// Field name, idea id or the surrounding line
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DateMention {
+    pub rel_path: String,
+    pub date: String,
+    pub source: DateSource,
+    pub line: usize,
+    pub context: String,
+}
+
+/// Inclusive range of days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    /// Parse `2024`, `2024-Q3`, `2024-07`, `2024-07-15` or `<from>..<to>`
+    /// where either side may be any of those forms
+    pub fn parse(s: &str) -> CoreResult<Self> {
+        let s = s.trim();
+        if let Some((from, to)) = s.split_once("..") {
+            let start = Self::parse_period(from.trim())?.start;
+            let end = Self::parse_period(to.trim())?.end;
+            if end < start {
+                return Err(CoreError::Message(format!(
+                    "date range {s:?} ends before it starts"
+                )));
+            }
+            return Ok(DateRange { start, end });
+        }
+        Self::parse_period(s)
+    }
+
+    fn parse_period(s: &str) -> CoreResult<Self> {
+        let invalid = || {
+            CoreError::Message(format!(
+                "invalid date {s:?} (expected YYYY, YYYY-QN, YYYY-MM or YYYY-MM-DD)"
+            ))
+        };
+        let mut parts = s.split('-');
+        let year: i32 = parts
+            .next()
+            .filter(|y| y.len() == 4)
+            .and_then(|y| y.parse().ok())
+            .ok_or_else(invalid)?;
+        let (start, end) = match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => (ymd(year, 1, 1), ymd(year, 12, 31)),
+            (Some(q), None, _) if q.starts_with(['q', 'Q']) => {
+                let quarter: u32 = q[1..]
+                    .parse()
+                    .ok()
+                    .filter(|q| (1..=4).contains(q))
+                    .ok_or_else(invalid)?;
+                let first = (quarter - 1) * 3 + 1;
+                (ymd(year, first, 1), month_end(year, first + 2))
+            }
+            (Some(m), None, _) if m.len() == 2 => {
+                let month: u32 = m.parse().map_err(|_| invalid())?;
+                (ymd(year, month, 1), month_end(year, month))
+            }
+            (Some(_), Some(_), None) => {
+                let day = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+                (day, day)
+            }
+            _ => (None, None),
+        };
+        match (start, end) {
+            (Some(start), Some(end)) => Ok(DateRange { start, end }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+fn month_end(year: i32, month: u32) -> Option<NaiveDate> {
+    let next = if month == 12 {
+        ymd(year + 1, 1, 1)
+    } else {
+        ymd(year, month + 1, 1)
+    };
+    next?.pred_opt().filter(|d| d.month() == month)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DateQuery {
+    pub range: Option<DateRange>,
+    /// Only dates in docs under this folder
+    pub folder: Option<String>,
+    pub source: Option<DateSource>,
+    pub limit: Option<usize>,
+}
+
+/// Leading `YYYY-MM-DD` of a value such as `2024-07-01` or `2024-07-01T09:30:00Z`
+fn leading_date(s: &str) -> Option<String> {
+    let date = s.get(..10)?;
+    if s[10..].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.to_string())
+}
+
+/// Valid `YYYY-MM-DD` dates in a line that are not part of a longer number
+fn inline_dates(line: &str) -> Vec<String> {
+    let bytes = line.as_bytes();
+    let mut dates = Vec::new();
+    let mut i = 0;
+    while i + 10 <= bytes.len() {
+        let window = &bytes[i..i + 10];
+        let shaped = window.iter().enumerate().all(|(j, b)| {
+            if j == 4 || j == 7 {
+                *b == b'-'
+            } else {
+                b.is_ascii_digit()
+            }
+        });
+        let bounded = (i == 0 || !bytes[i - 1].is_ascii_alphanumeric())
+            && !bytes.get(i + 10).is_some_and(u8::is_ascii_digit);
+        if shaped && bounded {
+            if let Some(date) = leading_date(&line[i..]) {
+                if !dates.contains(&date) {
+                    dates.push(date);
+                }
+                i += 10;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    dates
+}
+
+fn truncate(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(CONTEXT_MAX_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Every date mentioned in `content`; inline dates inside code fences are
+/// ignored
+pub fn parse_dates(content: &str) -> Vec<ParsedDate> {
+    let header = &content[..content.len() - crate::frontmatter::body(content).len()];
+    let mut dates = Vec::new();
+
+    for (key, value) in crate::frontmatter::fields(content) {
+        let values = match value {
+            Value::Array(items) => items,
+            other => vec![other],
+        };
+        let line = header
+            .lines()
+            .position(|l| {
+                l.trim_start_matches(['"', '\''])
+                    .strip_prefix(key.as_str())
+                    .is_some_and(|rest| rest.starts_with([':', '"', '\'']))
+            })
+            .map_or(1, |idx| idx + 1);
+        for date in values.iter().filter_map(|v| leading_date(v.as_str()?)) {
+            dates.push(ParsedDate {
+                date,
+                source: DateSource::Frontmatter,
+                line,
+                context: key.clone(),
+            });
+        }
+    }
+
+    let skip = header.matches('\n').count();
+    let mut fence: Option<&str> = None;
+    for (idx, line) in content.lines().enumerate().skip(skip) {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        if let Some((id, created_at)) = parse_idea_marker(line) {
+            if let Some(date) = leading_date(&created_at) {
+                dates.push(ParsedDate {
+                    date,
+                    source: DateSource::Entry,
+                    line: idx + 1,
+                    context: format!("idea:{id}"),
+                });
+            }
+            continue;
+        }
+        for date in inline_dates(line) {
+            dates.push(ParsedDate {
+                date,
+                source: DateSource::Inline,
+                line: idx + 1,
+                context: truncate(line),
+            });
+        }
+    }
+    dates
+}
+
+/// Replace the indexed dates of one doc
+pub(crate) fn sync_doc_dates(conn: &Connection, doc_id: i64, content: &str) -> CoreResult<()> {
+    conn.execute("DELETE FROM doc_dates WHERE doc_id = ?1", [doc_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO doc_dates (doc_id, date, source, line, context) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for d in parse_dates(content) {
+        stmt.execute(params![
+            doc_id,
+            d.date,
+            d.source.as_str(),
+            d.line as i64,
+            d.context
+        ])?;
+    }
+    Ok(())
+}
+
+/// Re-read every registered doc from disk; returns the number of dates found
+pub(crate) fn reindex_all(conn: &Connection) -> CoreResult<usize> {
+    let docs: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, abs_path FROM docs")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        rows
+    };
+    for (doc_id, abs_path) in docs {
+        let content = fs::read_to_string(&abs_path).unwrap_or_default();
+        sync_doc_dates(conn, doc_id, &content)?;
+    }
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM doc_dates", [], |row| row.get(0))?;
+    Ok(count as usize)
+}
+
+impl OpenContext {
+    /// Dates matching `query` in chronological order
+    pub fn list_dates(&self, query: &DateQuery) -> CoreResult<Vec<DateMention>> {
+        let pattern = match query.folder.as_deref() {
+            Some(folder) => {
+                let folder = normalize_folder_path(Some(folder))?;
+                (!folder.is_empty()).then(|| like_children(&folder))
+            }
+            None => None,
+        };
+        let (start, end) = match query.range {
+            Some(r) => (Some(r.start.to_string()), Some(r.end.to_string())),
+            None => (None, None),
+        };
+        let source = query.source.map(DateSource::as_str);
+        let limit = query.limit.map_or(-1, |l| l as i64);
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT d.rel_path, t.date, t.source, t.line, t.context
+                 FROM doc_dates t JOIN docs d ON d.id = t.doc_id
+                 WHERE (?1 IS NULL OR d.rel_path LIKE ?1 ESCAPE '\\')
+                   AND (?2 IS NULL OR t.date >= ?2)
+                   AND (?3 IS NULL OR t.date <= ?3)
+                   AND (?4 IS NULL OR t.source = ?4)
+                 ORDER BY t.date, d.rel_path, t.line
+                 LIMIT ?5",
+            )?;
+            let rows = stmt
+                .query_map(params![pattern, start, end, source, limit], |row| {
+                    let source: String = row.get(2)?;
+                    Ok(DateMention {
+                        rel_path: row.get(0)?,
+                        date: row.get(1)?,
+                        source: DateSource::parse(&source).unwrap_or(DateSource::Inline),
+                        line: row.get::<_, i64>(3)? as usize,
+                        context: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+    }
+
+    /// Rebuild the date index from the files on disk
+    pub fn reindex_dates(&self) -> CoreResult<usize> {
+        self.with_conn(reindex_all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> (String, String) {
+        let r = DateRange::parse(s).unwrap();
+        (r.start.to_string(), r.end.to_string())
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(range("2024"), ("2024-01-01".into(), "2024-12-31".into()));
+        assert_eq!(range("2024-q3"), ("2024-07-01".into(), "2024-09-30".into()));
+        assert_eq!(range("2024-02"), ("2024-02-01".into(), "2024-02-29".into()));
+        assert_eq!(range("2024-12"), ("2024-12-01".into(), "2024-12-31".into()));
+        assert_eq!(
+            range("2024-07-15"),
+            ("2024-07-15".into(), "2024-07-15".into())
+        );
+        assert_eq!(
+            range("2024-Q3..2025-01"),
+            ("2024-07-01".into(), "2025-01-31".into())
+        );
+        for bad in [
+            "24",
+            "2024-Q5",
+            "2024-13",
+            "2024-02-30",
+            "2025..2024",
+            "soon",
+        ] {
+            assert!(DateRange::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let content = "---\ntitle: Launch\ndue: 2024-08-01\nmilestones: [2024-07-15, later]\n---\n# Launch\n\nKickoff on 2024-07-02, review 2024-07-02 and 2024-09-30.\nBuild 20240701-1 and v2024-07-01 are not dates; 2024-02-30 neither.\n```\n2024-07-03\n```\n[//]: # (idea:id=abc created_at=2024-07-04T08:00:00.000Z)\n";
+        let dates: Vec<_> = parse_dates(content)
+            .into_iter()
+            .map(|d| (d.date, d.source, d.line))
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                ("2024-08-01".into(), DateSource::Frontmatter, 3),
+                ("2024-07-15".into(), DateSource::Frontmatter, 4),
+                ("2024-07-02".into(), DateSource::Inline, 8),
+                ("2024-09-30".into(), DateSource::Inline, 8),
+                ("2024-07-04".into(), DateSource::Entry, 13),
+            ]
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Activity digests
+//!
+//! A digest lists what changed in the workspace during a window: documents
+//! created or updated (from their timestamps, plus file mtimes for edits
+//! made outside OpenContext), idea entries captured, and documents removed or
+//! renamed according to the index journal. It renders to a Markdown note
+//! that can be saved into the workspace, optionally headed by an LLM summary
+//! and followed by a few old notes brought back by [`crate::resurface`].
+
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, Utc};
+use rusqlite::params;
+use serde::Serialize;
+use std::fs;
+
+use crate::ideas::{parse_idea_entries, IDEAS_DIR};
+use crate::memory::MEMORY_DIR;
+use crate::resurface::PickedDoc;
+use crate::{normalize_folder_path, CoreError, CoreResult, DocSaved, OpenContext};
+
+/// Characters of each document's body passed along for summarizing
+const EXCERPT_CHARS: usize = 600;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestDoc {
+    pub rel_path: String,
+    pub description: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Start of the body, without frontmatter
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestIdea {
+    pub rel_path: String,
+    pub id: String,
+    pub created_at: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestRename {
+    pub old_path: String,
+    pub new_path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub since: String,
+    pub until: String,
+    pub created: Vec<DigestDoc>,
+    pub updated: Vec<DigestDoc>,
+    pub ideas: Vec<DigestIdea>,
+    pub removed: Vec<String>,
+    pub renamed: Vec<DigestRename>,
+    /// Old notes resurfaced with the digest
+    pub resurfaced: Vec<PickedDoc>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.updated.is_empty()
+            && self.ideas.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.resurfaced.is_empty()
+    }
+
+    /// File name the digest is saved under, e.g. `digest-2026-10-16.md`
+    pub fn file_name(&self) -> String {
+        format!("digest-{}.md", &self.until[..10])
+    }
+
+    /// Markdown note for the digest, with `summary` as its first section
+    pub fn to_markdown(&self, summary: Option<&str>) -> String {
+        let (since, until) = (&self.since[..10], &self.until[..10]);
+        let mut out = format!(
+            "---\ndigest_since: {}\ndigest_until: {}\n---\n# Digest {since} – {until}\n",
+            self.since, self.until
+        );
+        if let Some(summary) = summary.map(str::trim).filter(|s| !s.is_empty()) {
+            out.push_str(&format!("\n## Summary\n\n{summary}\n"));
+        }
+        if self.is_empty() {
+            out.push_str("\nNo activity.\n");
+            return out;
+        }
+
+        let doc_line = |d: &DigestDoc| {
+            let link = format!("[{}](/{})", d.rel_path, d.rel_path.replace(' ', "%20"));
+            match d.description.trim() {
+                "" => format!("- {link}\n"),
+                desc => format!("- {link} — {desc}\n"),
+            }
+        };
+        for (title, docs) in [
+            ("New documents", &self.created),
+            ("Updated documents", &self.updated),
+        ] {
+            if !docs.is_empty() {
+                out.push_str(&format!("\n## {title} ({})\n\n", docs.len()));
+                docs.iter().for_each(|d| out.push_str(&doc_line(d)));
+            }
+        }
+        if !self.ideas.is_empty() {
+            out.push_str(&format!("\n## Ideas ({})\n\n", self.ideas.len()));
+            for idea in &self.ideas {
+                let first = idea
+                    .content
+                    .lines()
+                    .find(|l| !l.trim().is_empty())
+                    .unwrap_or("");
+                out.push_str(&format!(
+                    "- {} {} ({})\n",
+                    idea.created_at.get(..10).unwrap_or(""),
+                    first.trim(),
+                    idea.rel_path
+                ));
+            }
+        }
+        if !self.removed.is_empty() || !self.renamed.is_empty() {
+            out.push_str("\n## Removed and renamed\n\n");
+            for path in &self.removed {
+                out.push_str(&format!("- removed {path}\n"));
+            }
+            for r in &self.renamed {
+                out.push_str(&format!("- {} → {}\n", r.old_path, r.new_path));
+            }
+        }
+        if !self.resurfaced.is_empty() {
+            out.push_str("\n## From the archive\n\n");
+            for d in &self.resurfaced {
+                let link = format!("[{}](/{})", d.rel_path, d.rel_path.replace(' ', "%20"));
+                out.push_str(&format!("- {link} — untouched for {} days\n", d.idle_days));
+            }
+        }
+        out
+    }
+}
+
+/// Start of a digest window: `7d`, `12h`, `2w`, a date (`2026-10-01`) or an
+/// RFC 3339 timestamp
+pub fn parse_since(s: &str, now: DateTime<Utc>) -> CoreResult<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    let invalid = || {
+        CoreError::Message(format!(
+            "invalid window {s:?} (expected e.g. 7d, 12h, 2w or YYYY-MM-DD)"
+        ))
+    };
+    parse_span(s)
+        .and_then(|span| now.checked_sub_signed(span))
+        .ok_or_else(invalid)
+}
+
+/// Length of a `12h`, `7d` or `2w` span; `None` when malformed or not positive
+pub(crate) fn parse_span(s: &str) -> Option<Duration> {
+    let unit = s.chars().last()?;
+    let n: i64 = s[..s.len() - unit.len_utf8()].parse().ok()?;
+    let span = match unit {
+        'h' => Duration::try_hours(n),
+        'd' => Duration::try_days(n),
+        'w' => Duration::try_weeks(n),
+        _ => None,
+    };
+    span.filter(|_| n > 0)
+}
+
+fn timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+fn excerpt(content: &str) -> String {
+    let body = crate::frontmatter::body(content).trim();
+    match body.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+impl OpenContext {
+    /// Activity in the window starting at `since` (see [`parse_since`]),
+    /// leaving out docs under `exclude_folder` (where earlier digests are
+    /// kept). Removals and renames come from the index journal, which keeps
+    /// about a week of history.
+    pub fn collect_digest(&self, since: &str, exclude_folder: Option<&str>) -> CoreResult<Digest> {
+        let until = Utc::now();
+        let since = parse_since(since, until)?;
+        let since_iso = since.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let exclude = match exclude_folder {
+            Some(folder) => {
+                let folder = normalize_folder_path(Some(folder))?;
+                (!folder.is_empty()).then(|| format!("{folder}/"))
+            }
+            None => None,
+        };
+        let excluded = |rel_path: &str| exclude.as_deref().is_some_and(|p| rel_path.starts_with(p));
+
+        let (docs, journal) = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT rel_path, abs_path, description, created_at, updated_at
+                 FROM docs ORDER BY rel_path",
+            )?;
+            let docs = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut stmt = conn.prepare(
+                "SELECT op, rel_path, old_path FROM index_wal
+                 WHERE op IN ('remove', 'rename') AND created_at >= ?1 ORDER BY id",
+            )?;
+            let journal = stmt
+                .query_map(params![since_iso], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((docs, journal))
+        })?;
+
+        let mut digest = Digest {
+            since: since_iso,
+            until: until.to_rfc3339_opts(SecondsFormat::Millis, true),
+            created: Vec::new(),
+            updated: Vec::new(),
+            ideas: Vec::new(),
+            removed: Vec::new(),
+            renamed: Vec::new(),
+            resurfaced: Vec::new(),
+        };
+        let in_window = |ts: &str| timestamp(ts).is_some_and(|ts| ts >= since);
+
+        for (rel_path, abs_path, description, created_at, updated_at) in docs {
+            // Agent memories are bookkeeping, not activity
+            if excluded(&rel_path) || rel_path.starts_with(MEMORY_DIR) {
+                continue;
+            }
+            if rel_path.starts_with(IDEAS_DIR) {
+                let content = fs::read_to_string(&abs_path).unwrap_or_default();
+                digest.ideas.extend(
+                    parse_idea_entries(&content)
+                        .into_iter()
+                        .filter(|e| in_window(&e.created_at))
+                        .map(|e| DigestIdea {
+                            rel_path: rel_path.clone(),
+                            id: e.id,
+                            created_at: e.created_at,
+                            content: e.content,
+                        }),
+                );
+                continue;
+            }
+            // Files edited directly on disk only show up in their mtime
+            let modified = fs::metadata(&abs_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(DateTime::<Utc>::from);
+            let touched = in_window(&updated_at) || modified.is_some_and(|m| m >= since);
+            let created = in_window(&created_at);
+            if !(created || touched) {
+                continue;
+            }
+            let doc = DigestDoc {
+                excerpt: excerpt(&fs::read_to_string(&abs_path).unwrap_or_default()),
+                rel_path,
+                description,
+                created_at,
+                updated_at,
+            };
+            if created {
+                digest.created.push(doc);
+            } else {
+                digest.updated.push(doc);
+            }
+        }
+        digest.ideas.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        for (op, rel_path, old_path) in journal {
+            match (op.as_str(), old_path) {
+                ("remove", _) if !excluded(&rel_path) => digest.removed.push(rel_path),
+                ("rename", Some(old_path)) if !excluded(&rel_path) => {
+                    digest.renamed.push(DigestRename {
+                        old_path,
+                        new_path: rel_path,
+                    })
+                }
+                _ => {}
+            }
+        }
+        digest.removed.sort();
+        digest.removed.dedup();
+        Ok(digest)
+    }
+
+    /// Write the digest note into `folder` (created if missing), replacing a
+    /// digest saved there earlier the same day
+    pub fn save_digest(
+        &self,
+        digest: &Digest,
+        summary: Option<&str>,
+        folder: &str,
+    ) -> CoreResult<DocSaved> {
+        let folder = normalize_folder_path(Some(folder))?;
+        if self.find_folder(&folder)?.is_none() {
+            self.create_folder(&folder, Some("Activity digests"))?;
+        }
+        let name = digest.file_name();
+        let rel_path = format!("{folder}/{name}");
+        if self.find_doc(&rel_path)?.is_none() {
+            self.create_doc(&folder, &name, None)?;
+        }
+        let description = format!("Activity {} – {}", &digest.since[..10], &digest.until[..10]);
+        self.save_doc_content(&rel_path, &digest.to_markdown(summary), Some(&description))
+    }
+}
+
+/// Ask the configured `[llm]` model for a short prose summary of the digest
+#[cfg(feature = "search")]
+pub async fn summarize(
+    digest: &Digest,
+    config: &crate::search::SearchConfig,
+) -> crate::search::SearchResult<String> {
+    const PROMPT: &str = "You write the summary section of a personal knowledge base activity digest. \
+Given the documents created and updated and the ideas captured in the period, write 3-6 short bullet points \
+on the main themes, decisions and open threads. Refer to documents by path. Answer with the bullets only, \
+in the language of the notes.";
+
+    let llm = crate::search::LlmClient::new(&config.llm, &config.embedding)?;
+    let mut input = format!("Period: {} to {}\n", digest.since, digest.until);
+    for (label, docs) in [("Created", &digest.created), ("Updated", &digest.updated)] {
+        for d in docs {
+            input.push_str(&format!(
+                "\n## {label}: {}\n{}\n{}\n",
+                d.rel_path, d.description, d.excerpt
+            ));
+        }
+    }
+    for idea in &digest.ideas {
+        input.push_str(&format!(
+            "\n## Idea ({})\n{}\n",
+            idea.created_at, idea.content
+        ));
+    }
+    llm.complete(PROMPT, &input).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        let now = timestamp("2026-10-16T12:00:00Z").unwrap();
+        let at = |s: &str| parse_since(s, now).unwrap().to_rfc3339();
+        assert_eq!(at("7d"), "2026-10-09T12:00:00+00:00");
+        assert_eq!(at("12h"), "2026-10-16T00:00:00+00:00");
+        assert_eq!(at("2w"), "2026-10-02T12:00:00+00:00");
+        assert_eq!(at("2026-10-01"), "2026-10-01T00:00:00+00:00");
+        for bad in ["", "d", "0d", "7m", "-1d", "last week"] {
+            assert!(parse_since(bad, now).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! In-place document edits
+//!
+//! Agents usually want to add a bullet under a heading or change a few
+//! lines, not resend the whole file. These are the pure text operations
+//! behind [`OpenContext::append_to_section`](crate::OpenContext::append_to_section)
+//! and [`OpenContext::apply_patch`](crate::OpenContext::apply_patch).
+
+use crate::{CoreError, CoreResult};
+
+/// Insert `text` at the end of the section under `heading_path`
+/// (`"Plan > Steps"`, the format search hits use; a trailing part such as
+/// `"Steps"` is enough when it is unambiguous). Headings are matched
+/// case-insensitively and headings inside code fences are ignored.
+pub fn append_to_section(content: &str, heading_path: &str, text: &str) -> CoreResult<String> {
+    let wanted: Vec<String> = heading_path
+        .split('>')
+        .map(|part| part.trim().to_lowercase())
+        .filter(|part| !part.is_empty())
+        .collect();
+    if wanted.is_empty() {
+        return Err(CoreError::Message("heading_path is required".into()));
+    }
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let headings = headings(&lines);
+
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut found = None;
+    for (idx, &(line, level, ref title)) in headings.iter().enumerate() {
+        while stack.last().is_some_and(|(l, _)| *l >= level) {
+            stack.pop();
+        }
+        stack.push((level, title.to_lowercase()));
+        if stack.len() >= wanted.len()
+            && stack[stack.len() - wanted.len()..]
+                .iter()
+                .map(|(_, t)| t)
+                .eq(wanted.iter())
+        {
+            let end = headings[idx + 1..]
+                .iter()
+                .find(|(_, l, _)| *l <= level)
+                .map_or(lines.len(), |(next, _, _)| *next);
+            found = Some((line, end));
+            break;
+        }
+    }
+    let Some((heading_line, end)) = found else {
+        let available: Vec<&str> = headings.iter().map(|(_, _, t)| t.as_str()).collect();
+        return Err(CoreError::Message(format!(
+            "heading \"{heading_path}\" not found (headings: {})",
+            available.join(", ")
+        )));
+    };
+
+    // Insert after the section's last non-blank line
+    let last = (heading_line..end)
+        .rev()
+        .find(|&i| !lines[i].trim().is_empty())
+        .unwrap_or(heading_line);
+    let text = text.trim_end_matches('\n');
+    let mut insert = String::new();
+    if !lines[last].ends_with('\n') {
+        insert.push('\n');
+    }
+    // A paragraph directly after other text would merge into it
+    let is_list_item = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ")
+    };
+    if last != heading_line && !(is_list_item(text) && is_list_item(lines[last])) {
+        insert.push('\n');
+    }
+    insert.push_str(text);
+    insert.push('\n');
+
+    let mut out: String = lines[..=last].concat();
+    out.push_str(&insert);
+    out.push_str(&lines[last + 1..].concat());
+    Ok(out)
+}
+
+/// ATX headings outside code fences: (line index, level, title)
+fn headings(lines: &[&str]) -> Vec<(usize, usize, String)> {
+    let mut fence: Option<&str> = None;
+    let mut out = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let rest = &trimmed[level..];
+        if (1..=6).contains(&level) && (rest.starts_with(' ') || rest.trim().is_empty()) {
+            let title = rest.trim().trim_end_matches('#').trim().to_string();
+            out.push((idx, level, title));
+        }
+    }
+    out
+}
+
+/// Apply a unified diff (as produced by `diff -u` / `git diff`) to `content`.
+/// Hunks whose context moved are located by searching for their original
+/// lines; a hunk that matches nowhere fails the whole patch. The document
+/// keeps its line ending (`\r\n` or `\n`), whichever the diff uses.
+pub fn apply_patch(content: &str, diff: &str) -> CoreResult<String> {
+    let hunks = parse_hunks(diff)?;
+    if hunks.is_empty() {
+        return Err(CoreError::Message("patch contains no hunks".into()));
+    }
+
+    let eol = line_ending(content);
+    let mut trailing_newline = content.is_empty() || content.ends_with('\n');
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    // Line shift caused by earlier hunks
+    let mut offset: isize = 0;
+
+    for (n, hunk) in hunks.iter().enumerate() {
+        let old: Vec<&str> = hunk.old_lines().collect();
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        let start = locate(&lines, &old, expected).ok_or_else(|| {
+            CoreError::Message(format!(
+                "patch hunk {} (@@ -{} @@) does not apply: the document has changed",
+                n + 1,
+                hunk.old_start
+            ))
+        })?;
+        let new: Vec<String> = hunk
+            .new_lines()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect();
+        offset += new.len() as isize - old.len() as isize + start as isize - expected as isize;
+        lines.splice(start..start + old.len(), new);
+        if let Some(newline) = hunk.trailing_newline {
+            trailing_newline = newline;
+        }
+    }
+
+    let mut out = lines.join(eol);
+    if trailing_newline && !out.is_empty() {
+        out.push_str(eol);
+    }
+    Ok(out)
+}
+
+/// `"\r\n"` if the first line of `content` ends that way, else `"\n"`
+fn line_ending(content: &str) -> &'static str {
+    match content.find('\n') {
+        Some(idx) if content[..idx].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+struct Hunk {
+    old_start: usize,
+    /// (' ' | '-' | '+', text)
+    lines: Vec<(char, String)>,
+    /// Set when the hunk ends the new file with or without a newline
+    trailing_newline: Option<bool>,
+}
+
+impl Hunk {
+    fn old_lines(&self) -> impl Iterator<Item = &str> {
+        self.lines
+            .iter()
+            .filter(|(op, _)| *op != '+')
+            .map(|(_, text)| text.as_str())
+    }
+
+    fn new_lines(&self) -> impl Iterator<Item = &str> {
+        self.lines
+            .iter()
+            .filter(|(op, _)| *op != '-')
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+/// Hunks of a single-file unified diff. Each hunk ends once the line counts
+/// in its `@@ -a,b +c,d @@` header are used up; lines between hunks (file
+/// headers, trailing blank lines) are skipped.
+fn parse_hunks(diff: &str) -> CoreResult<Vec<Hunk>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    // Old and new lines the current hunk still has to contain
+    let mut remaining = (0usize, 0usize);
+    let mut files = 0;
+    for line in diff.lines() {
+        if remaining == (0, 0) {
+            if let Some(header) = line.strip_prefix("@@ ") {
+                let (old_start, old_count, new_count) = parse_hunk_header(header)
+                    .ok_or_else(|| CoreError::Message(format!("invalid hunk header: {line}")))?;
+                // A hunk removing nothing inserts after line `old_start`
+                let old_start = if old_count == 0 {
+                    old_start + 1
+                } else {
+                    old_start
+                };
+                hunks.push(Hunk {
+                    old_start,
+                    lines: Vec::new(),
+                    trailing_newline: None,
+                });
+                remaining = (old_count, new_count);
+            } else if line.starts_with("--- ") {
+                files += 1;
+                if files > 1 {
+                    return Err(CoreError::Message(
+                        "patch touches more than one file".into(),
+                    ));
+                }
+            } else if line.starts_with("\\ ") {
+                mark_no_newline(hunks.last_mut());
+            }
+            // Other file headers (diff --git, +++, index) and blank lines
+            continue;
+        }
+        let hunk = hunks
+            .last_mut()
+            .expect("counts are only set by a hunk header");
+        if line.starts_with("\\ ") {
+            mark_no_newline(Some(hunk));
+            continue;
+        }
+        let (op, text) = match line.chars().next() {
+            Some(op @ (' ' | '-' | '+')) => (op, &line[1..]),
+            // Some tools drop the space on blank context lines
+            None => (' ', ""),
+            Some(_) => {
+                return Err(CoreError::Message(format!(
+                    "hunk {} is shorter than its header says (at \"{line}\")",
+                    hunks.len()
+                )))
+            }
+        };
+        let (old, new) = &mut remaining;
+        let counted = match op {
+            ' ' => old.checked_sub(1).zip(new.checked_sub(1)),
+            '-' => old.checked_sub(1).map(|o| (o, *new)),
+            _ => new.checked_sub(1).map(|n| (*old, n)),
+        };
+        let Some(counted) = counted else {
+            return Err(CoreError::Message(format!(
+                "hunk {} has more lines than its header says",
+                hunks.len()
+            )));
+        };
+        remaining = counted;
+        hunk.lines.push((op, text.to_string()));
+    }
+    if remaining != (0, 0) {
+        return Err(CoreError::Message(format!(
+            "hunk {} is shorter than its header says",
+            hunks.len()
+        )));
+    }
+    Ok(hunks)
+}
+
+/// (old start, old count, new count) of `-a,b +c,d @@`; counts default to 1
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let mut ranges = header.split_whitespace();
+    let range = |r: Option<&str>, sign: char| -> Option<(usize, usize)> {
+        let r = r?.strip_prefix(sign)?;
+        match r.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(ranges.next(), '-')?;
+    let (_, new_count) = range(ranges.next(), '+')?;
+    Some((old_start, old_count, new_count))
+}
+
+/// "\ No newline at end of file" refers to the line above it
+fn mark_no_newline(hunk: Option<&mut Hunk>) {
+    if let Some(hunk) = hunk {
+        if hunk.lines.last().is_some_and(|(op, _)| *op != '-') {
+            hunk.trailing_newline = Some(false);
+        }
+    }
+}
+
+/// Position of `needle` in `lines` closest to `expected`
+fn locate(lines: &[String], needle: &[&str], expected: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(expected.min(lines.len()));
+    }
+    let matches_at = |start: usize| {
+        start + needle.len() <= lines.len()
+            && lines[start..start + needle.len()]
+                .iter()
+                .zip(needle)
+                .all(|(have, want)| have.trim_end() == want.trim_end())
+    };
+    (0..=lines.len())
+        .flat_map(|d| [expected.checked_add(d), expected.checked_sub(d)])
+        .flatten()
+        .take(2 * (lines.len() + 1))
+        .find(|&start| matches_at(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str =
+        "# Project\n\nIntro.\n\n## Tasks\n\n- one\n- two\n\n## Notes\n\nSome notes.\n";
+
+    #[test]
+    fn test_append_to_section() {
+        let out = append_to_section(DOC, "Tasks", "- three").unwrap();
+        assert!(out.contains("- one\n- two\n- three\n\n## Notes"), "{out}");
+
+        let out = append_to_section(DOC, "project > notes", "More notes.").unwrap();
+        assert!(out.ends_with("Some notes.\n\nMore notes.\n"), "{out}");
+
+        let fenced = "# A\n\n```\n# not a heading\n```\n";
+        assert!(append_to_section(fenced, "not a heading", "x").is_err());
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let diff =
+            "--- a/doc.md\n+++ b/doc.md\n@@ -6,3 +6,3 @@\n \n-- one\n+- one (done)\n - two\n";
+        let out = apply_patch(DOC, diff).unwrap();
+        assert!(out.contains("- one (done)\n- two\n"), "{out}");
+
+        // Context shifted by an edit made after the diff was produced
+        let shifted = DOC.replace("Intro.\n", "Intro.\n\nMore intro.\n");
+        let out = apply_patch(&shifted, diff).unwrap();
+        assert!(out.contains("- one (done)\n"), "{out}");
+
+        let stale = "@@ -7,1 +7,1 @@\n-- missing\n+- new\n";
+        assert!(apply_patch(DOC, stale).is_err());
+
+        // Pure insertion after line 8
+        let insert = "@@ -8,0 +9 @@\n+- three\n";
+        let out = apply_patch(DOC, insert).unwrap();
+        assert!(out.contains("- two\n- three\n\n## Notes"), "{out}");
+    }
+
+    #[test]
+    fn test_apply_patch_follows_hunk_counts() {
+        // Trailing blank lines after the last hunk are not context
+        let diff = "@@ -7,1 +7,1 @@\n-- one\n+- one (done)\n\n\n";
+        let out = apply_patch(DOC, diff).unwrap();
+        assert!(out.contains("- one (done)\n- two\n"), "{out}");
+
+        let two_files = "--- a/doc.md\n+++ b/doc.md\n@@ -7 +7 @@\n-- one\n+- uno\n\
+                         --- a/other.md\n+++ b/other.md\n@@ -1 +1 @@\n-x\n+y\n";
+        assert!(apply_patch(DOC, two_files).is_err());
+
+        let short = "@@ -7,2 +7,2 @@\n-- one\n+- uno\n";
+        assert!(apply_patch(DOC, short).is_err());
+        let garbled = "@@ -7,2 +7,2 @@\n-- one\n+- uno\n?? two\n";
+        assert!(apply_patch(DOC, garbled).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_keeps_crlf() {
+        let crlf = DOC.replace('\n', "\r\n");
+        let diff = "@@ -7,2 +7,2 @@\n-- one\n+- one (done)\n - two\n";
+        let out = apply_patch(&crlf, diff).unwrap();
+        assert_eq!(out, crlf.replace("- one\r\n", "- one (done)\r\n"));
+    }
+}
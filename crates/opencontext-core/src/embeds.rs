@@ -0,0 +1,325 @@
+//! Transclusion
+//!
+//! `![[note]]` shows a whole note inside the one that embeds it and
+//! `![[note#Section]]` one section of it, as in Obsidian. Rendering expands
+//! embeds as HTML; the search index expands them as text through
+//! [`Transcluder`], so a note assembled from embeds is found by what it
+//! shows, and its checksum changes when an embedded section does.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::blocks::find_block;
+use crate::links::{Link, LinkKind, Resolution, Resolver};
+use crate::{frontmatter, relpath, scan_md_files, CoreResult, RelPath, SymlinkPolicy};
+
+/// Embeds inside embeds are expanded this deep
+pub(crate) const MAX_EMBED_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Embed {
+    /// Note name or path, as written
+    pub target: String,
+    /// Heading, or `^block-id`, the embed is limited to
+    pub anchor: Option<String>,
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl Embed {
+    /// The wiki-link the embed resolves like
+    pub fn link(&self) -> Link {
+        Link {
+            target: self.target.clone(),
+            line: self.line,
+            kind: LinkKind::Wiki,
+        }
+    }
+}
+
+/// `![[…]]` embeds of notes in `content`, skipping frontmatter, code fences
+/// and inline code. Embeds of files other than notes (`![[diagram.png]]`)
+/// are left out.
+pub fn parse_embeds(content: &str) -> Vec<Embed> {
+    let header = &content[..content.len() - frontmatter::body(content).len()];
+    spans(content)
+        .into_iter()
+        .filter(|(range, _)| range.start >= header.len())
+        .filter_map(|(range, inner)| {
+            let (target, anchor) = split_target(inner)?;
+            Some(Embed {
+                target: target.to_string(),
+                anchor: anchor.map(str::to_string),
+                line: content[..range.start].matches('\n').count() + 1,
+            })
+        })
+        .collect()
+}
+
+/// `![[…]]` spans in `content` outside code, with the text between the
+/// brackets
+fn spans(content: &str) -> Vec<(Range<usize>, &str)> {
+    let mut out = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let mut from = 0;
+        while let Some(at) = line[from..].find("![[") {
+            let at = from + at;
+            let Some(end) = line[at + 3..].find("]]") else {
+                break;
+            };
+            let end = at + 3 + end + 2;
+            // An odd number of backticks before it opens an inline code span
+            if line[..at].matches('`').count() % 2 == 0 {
+                out.push((start + at..start + end, &line[at + 3..end - 2]));
+            }
+            from = end;
+        }
+    }
+    out
+}
+
+/// `note#Section|label` as the note and the heading; `None` for files
+/// that are not notes
+fn split_target(inner: &str) -> Option<(&str, Option<&str>)> {
+    let inner = inner.split('|').next().unwrap_or_default();
+    let (target, anchor) = match inner.split_once('#') {
+        Some((target, anchor)) => (target.trim(), Some(anchor.trim())),
+        None => (inner.trim(), None),
+    };
+    let extension = relpath::file_name(target)
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    if target.is_empty() || extension.is_some_and(|ext| ext != "md") {
+        return None;
+    }
+    Some((target, anchor.filter(|a| !a.is_empty())))
+}
+
+/// The part of `body` an embed anchor names: the block `^block-id`, or the
+/// section under a heading
+pub fn excerpt<'a>(body: &'a str, anchor: &str) -> Option<Cow<'a, str>> {
+    match anchor.strip_prefix('^') {
+        Some(id) => find_block(body, id).map(|block| Cow::Owned(block.text)),
+        None => section(body, anchor).map(Cow::Borrowed),
+    }
+}
+
+/// The section of `body` under the heading whose slug is `anchor`, heading
+/// included, up to the next heading of the same or a higher level
+pub fn section<'a>(body: &'a str, anchor: &str) -> Option<&'a str> {
+    let anchor = relpath::slug(anchor);
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    let mut fence = false;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = !fence;
+        }
+        let level = heading_level(trimmed);
+        if !fence && level > 0 {
+            match start {
+                Some((at, open)) if level <= open => return Some(&body[at..offset]),
+                None if relpath::slug(&trimmed[level..]) == anchor => start = Some((offset, level)),
+                _ => {}
+            }
+        }
+        offset += line.len();
+    }
+    start.map(|(at, _)| &body[at..])
+}
+
+/// Level of the ATX heading `line` starts with, 0 if it is not one
+pub(crate) fn heading_level(line: &str) -> usize {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) && line[level..].starts_with([' ', '\t']) {
+        level
+    } else {
+        0
+    }
+}
+
+/// Expands embeds into the text of the notes that embed them
+pub struct Transcluder {
+    resolver: Resolver,
+    /// Doc path -> file on disk
+    files: HashMap<String, PathBuf>,
+}
+
+impl Transcluder {
+    /// Over the documents `(rel_path, abs_path)`
+    pub fn new(docs: impl IntoIterator<Item = (String, PathBuf)>) -> Self {
+        let files: HashMap<String, PathBuf> = docs.into_iter().collect();
+        Transcluder {
+            resolver: Resolver::new(files.keys().map(|path| (path.clone(), None))),
+            files,
+        }
+    }
+
+    /// Over every note under `contexts_root`
+    pub fn scan(contexts_root: &Path, policy: SymlinkPolicy) -> CoreResult<Self> {
+        let mut paths = Vec::new();
+        scan_md_files(contexts_root, contexts_root, policy, &mut paths)?;
+        Ok(Self::new(paths.into_iter().map(|path| {
+            let abs_path = RelPath::new(&path).to_path(contexts_root);
+            (path, abs_path)
+        })))
+    }
+
+    /// `content` of the note at `rel_path` with each `![[note#Section]]`
+    /// replaced by the embedded text. Embedded headings become plain lines,
+    /// so the embed stays part of the section it sits in; embeds that do not
+    /// resolve are left as written.
+    pub fn expand<'a>(&self, rel_path: &str, content: &'a str) -> Cow<'a, str> {
+        if !content.contains("![[") {
+            return Cow::Borrowed(content);
+        }
+        let mut stack = vec![rel_path.to_string()];
+        match self.expand_in(rel_path, content, &mut stack) {
+            Some(expanded) => Cow::Owned(expanded),
+            None => Cow::Borrowed(content),
+        }
+    }
+
+    /// `None` when nothing was expanded
+    fn expand_in(&self, from: &str, content: &str, stack: &mut Vec<String>) -> Option<String> {
+        let mut out = String::with_capacity(content.len());
+        let mut last = 0;
+        for (range, inner) in spans(content) {
+            let Some(text) = self.embedded(from, inner, stack) else {
+                continue;
+            };
+            out.push_str(&content[last..range.start]);
+            out.push_str(&text);
+            last = range.end;
+        }
+        if last == 0 {
+            return None;
+        }
+        out.push_str(&content[last..]);
+        Some(out)
+    }
+
+    fn embedded(&self, from: &str, inner: &str, stack: &mut Vec<String>) -> Option<String> {
+        let (target, anchor) = split_target(inner)?;
+        let link = Link {
+            target: target.to_string(),
+            line: 0,
+            kind: LinkKind::Wiki,
+        };
+        let Resolution::Doc(path) = self.resolver.resolve(from, &link) else {
+            return None;
+        };
+        if stack.contains(&path) || stack.len() > MAX_EMBED_DEPTH {
+            return None;
+        }
+        let content = fs::read_to_string(self.files.get(&path)?).ok()?;
+        let body = frontmatter::body(&content);
+        let body = match anchor {
+            Some(anchor) => excerpt(body, anchor)?,
+            None => Cow::Borrowed(body),
+        };
+        stack.push(path.clone());
+        let expanded = self.expand_in(&path, &body, stack);
+        stack.pop();
+        let text = expanded.as_deref().unwrap_or(&body);
+
+        let mut out = String::with_capacity(text.len());
+        let mut fence = false;
+        for line in text.trim().split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fence = !fence;
+            }
+            let level = heading_level(trimmed);
+            if !fence && level > 0 {
+                out.push_str(trimmed[level..].trim_start());
+            } else {
+                out.push_str(line);
+            }
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_embeds() {
+        let content = "---\nsee: ![[front]]\n---\n![[Plan#Goals|goals]] and ![[ideas]]\n\
+                       `![[code]]` ![[diagram.png]]\n```\n![[fenced]]\n```\n![[a/b.md]]\n";
+        let embeds: Vec<(String, Option<String>, usize)> = parse_embeds(content)
+            .into_iter()
+            .map(|e| (e.target, e.anchor, e.line))
+            .collect();
+        assert_eq!(
+            embeds,
+            vec![
+                ("Plan".into(), Some("Goals".into()), 4),
+                ("ideas".into(), None, 4),
+                ("a/b.md".into(), None, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_section() {
+        let body = "# Plan\nintro\n## Goals\nship it\n### Detail\nfast\n## Risks\nnone\n";
+        assert_eq!(
+            section(body, "goals"),
+            Some("## Goals\nship it\n### Detail\nfast\n")
+        );
+        assert_eq!(section(body, "Risks"), Some("## Risks\nnone\n"));
+        assert_eq!(section(body, "missing"), None);
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("plan.md"),
+            "---\ntitle: Plan\n---\n# Plan\n## Goals\nShip ![[loop]] by May\n## Risks\nnone\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("loop.md"),
+            "Loop back to ![[host]]\n\nA quoted line ^quote\n",
+        )
+        .unwrap();
+        let host = "# Host\n![[plan#Goals]]\n![[missing]] `![[plan]]` ![[loop#^quote]]\n";
+        fs::write(root.join("host.md"), host).unwrap();
+
+        let transcluder = Transcluder::scan(root, SymlinkPolicy::default()).unwrap();
+        assert_eq!(
+            transcluder.expand("host.md", host),
+            "# Host\nGoals\nShip Loop back to ![[host]]\n\nA quoted line ^quote by May\n\
+             ![[missing]] `![[plan]]` A quoted line\n"
+        );
+        assert!(matches!(
+            transcluder.expand("loop.md", "no embeds"),
+            Cow::Borrowed(_)
+        ));
+    }
+}
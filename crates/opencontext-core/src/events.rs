@@ -2,57 +2,123 @@
 //!
 //! This module provides an event bus for document lifecycle events,
 //! enabling decoupled index synchronization and other reactive features.
+//! The search engine also reports its own health on the bus, so a daemon
+//! or UI can tell when results are degraded.
 
+use serde::Serialize;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 
-/// Document lifecycle events
-#[derive(Debug, Clone)]
+use crate::search::API_SCHEMA_VERSION;
+use crate::RelPath;
+
+/// Document lifecycle events. Paths are workspace-relative and
+/// `/`-separated on every platform.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum DocEvent {
     /// A new document was created
-    Created { rel_path: String },
+    Created { rel_path: RelPath },
     /// Document content was updated
-    Updated { rel_path: String },
+    Updated { rel_path: RelPath },
     /// A document was deleted
-    Deleted { rel_path: String },
+    Deleted { rel_path: RelPath },
     /// A document was renamed
-    Renamed { old_path: String, new_path: String },
+    Renamed {
+        old_path: RelPath,
+        new_path: RelPath,
+    },
     /// A document was moved to another folder
-    Moved { old_path: String, new_path: String },
+    Moved {
+        old_path: RelPath,
+        new_path: RelPath,
+    },
+    /// Text was captured into an inbox note (`source` as in
+    /// [`CaptureSource`](crate::capture::CaptureSource)); follows the
+    /// note's `updated` event and asks for it to be indexed right away
+    Captured { rel_path: RelPath, source: String },
 }
 
 /// Folder lifecycle events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum FolderEvent {
     /// A folder was created
-    Created { rel_path: String },
+    Created { rel_path: RelPath },
     /// A folder was renamed (affects all docs inside)
     Renamed {
-        old_path: String,
-        new_path: String,
+        old_path: RelPath,
+        new_path: RelPath,
         /// Affected documents with their old and new paths
-        affected_docs: Vec<(String, String)>,
+        affected_docs: Vec<(RelPath, RelPath)>,
     },
     /// A folder was moved (affects all docs inside)
     Moved {
-        old_path: String,
-        new_path: String,
+        old_path: RelPath,
+        new_path: RelPath,
         /// Affected documents with their old and new paths
-        affected_docs: Vec<(String, String)>,
+        affected_docs: Vec<(RelPath, RelPath)>,
     },
     /// A folder was deleted (all docs inside removed)
     Deleted {
-        rel_path: String,
+        rel_path: RelPath,
         /// Documents that were removed
-        removed_docs: Vec<String>,
+        removed_docs: Vec<RelPath>,
     },
 }
 
-/// Combined event type
-#[derive(Debug, Clone)]
+/// Search engine health. Paths of broken indexes are absolute.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthEvent {
+    /// Embedding requests fail; searches return keyword-only results and
+    /// retry the provider now and then until it answers again
+    EmbeddingProviderDown { provider: String, error: String },
+    /// The embedding provider answers again after being down
+    EmbeddingProviderRecovered { provider: String },
+    /// An index could not be read; `oc index build --force` rebuilds it
+    IndexCorruptionDetected {
+        /// `vector` or `keyword`
+        index: String,
+        path: String,
+        error: String,
+    },
+    /// A bus subscriber fell behind and missed `missed` events; it rescans
+    /// the workspace to catch up
+    WatcherOverflow { subscriber: String, missed: u64 },
+}
+
+/// Combined event type. Serializes as
+/// `{"scope": "doc", "event": {"type": "renamed", "old_path": ..., "new_path": ...}}`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "scope", content = "event", rename_all = "snake_case")]
 pub enum Event {
     Doc(DocEvent),
     Folder(FolderEvent),
+    Health(HealthEvent),
+}
+
+/// An [`Event`] as sent to other processes, tagged with the
+/// [`API_SCHEMA_VERSION`] it follows
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EventMessage {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl From<Event> for EventMessage {
+    fn from(event: Event) -> Self {
+        Self {
+            schema_version: API_SCHEMA_VERSION,
+            event,
+        }
+    }
 }
 
 /// Event bus for broadcasting document events
@@ -94,6 +160,11 @@ impl EventBus {
         let _ = self.sender.send(Event::Folder(event));
     }
 
+    /// Emit a health event
+    pub fn emit_health(&self, event: HealthEvent) {
+        let _ = self.sender.send(Event::Health(event));
+    }
+
     /// Get the number of active subscribers
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
@@ -118,7 +189,7 @@ mod tests {
         let mut rx = bus.subscribe();
 
         bus.emit_doc(DocEvent::Created {
-            rel_path: "test/doc.md".to_string(),
+            rel_path: RelPath::new("test/doc.md"),
         });
 
         let event = rx.recv().await.unwrap();
@@ -129,4 +200,33 @@ mod tests {
             _ => panic!("Unexpected event type"),
         }
     }
+
+    #[test]
+    fn test_health_event_json() {
+        let message = EventMessage::from(Event::Health(HealthEvent::WatcherOverflow {
+            subscriber: "index_sync".to_string(),
+            missed: 12,
+        }));
+        assert_eq!(
+            serde_json::to_value(&message).unwrap()["event"],
+            serde_json::json!({"type": "watcher_overflow", "subscriber": "index_sync", "missed": 12})
+        );
+        assert_eq!(serde_json::to_value(&message).unwrap()["scope"], "health");
+    }
+
+    #[test]
+    fn test_event_message_json() {
+        let message = EventMessage::from(Event::Doc(DocEvent::Renamed {
+            old_path: RelPath::new("a.md"),
+            new_path: RelPath::new("notes/a.md"),
+        }));
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({
+                "schema_version": API_SCHEMA_VERSION,
+                "scope": "doc",
+                "event": {"type": "renamed", "old_path": "a.md", "new_path": "notes/a.md"}
+            })
+        );
+    }
 }
@@ -0,0 +1,545 @@
+//! Flashcards
+//!
+//! Notes can carry flashcards in the syntax of Anki and Obsidian's
+//! spaced-repetition plugin:
+//!
+//! - `Question :: Answer` on one line
+//! - a `Q:` line followed by an `A:` line; the answer runs to the next
+//!   blank line
+//! - clozes, `The capital of France is {{c1::Paris}}`, one card per `cN`
+//!   (`{{c1::Paris::city}}` shows `[city]` as a hint)
+//!
+//! Cards are collected into the `cards` table whenever a doc's content is
+//! written, like tasks. A card is identified by its kind and front, so it
+//! keeps its schedule while the note around it (or its answer) is edited.
+//! Reviews follow SM-2: each answer is graded 0–5, a grade below 3 starts
+//! the card over, and the card's ease grows or shrinks with the grade.
+//! Every answer is kept in `card_reviews`.
+
+use chrono::{Days, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+
+use crate::{
+    content_revision, like_children, normalize_folder_path, now_iso, CoreError, CoreResult,
+    OpenContext,
+};
+
+/// Ease a new card starts with, and the floor SM-2 keeps it above
+const START_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardKind {
+    Basic,
+    Cloze,
+}
+
+impl CardKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            CardKind::Basic => "basic",
+            CardKind::Cloze => "cloze",
+        }
+    }
+}
+
+/// A flashcard found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCard {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: CardKind,
+    pub front: String,
+    pub back: String,
+}
+
+impl ParsedCard {
+    /// Stable id of the card: its kind and front, not its answer or place
+    pub fn id(&self) -> String {
+        content_revision(format!("{}\n{}", self.kind.as_str(), self.front).as_bytes())
+    }
+}
+
+/// SM-2 state of a card
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Schedule {
+    pub ease: f64,
+    pub interval_days: i64,
+    /// Correct answers in a row
+    pub repetitions: i64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            ease: START_EASE,
+            interval_days: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+impl Schedule {
+    /// The schedule after an answer graded `grade` (0 = blackout, 3 = correct
+    /// with effort, 5 = perfect)
+    pub fn answer(self, grade: u8) -> Schedule {
+        let q = f64::from(grade.min(5));
+        let ease = (self.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+        if grade < 3 {
+            return Schedule {
+                ease,
+                interval_days: 1,
+                repetitions: 0,
+            };
+        }
+        let interval_days = match self.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (self.interval_days as f64 * self.ease).round() as i64,
+        };
+        Schedule {
+            ease,
+            interval_days,
+            repetitions: self.repetitions + 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Card {
+    pub id: String,
+    pub rel_path: String,
+    pub line: usize,
+    pub kind: CardKind,
+    pub front: String,
+    pub back: String,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// `YYYY-MM-DD`
+    pub due: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CardQuery {
+    /// Only cards in docs under this folder
+    pub folder: Option<String>,
+    /// Every card, not only those due today
+    pub all: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CardStats {
+    pub total: usize,
+    pub due: usize,
+    /// Never reviewed
+    pub new: usize,
+    pub reviewed_today: usize,
+}
+
+/// List marker (`- `, `* `, `1. `) at the start of `line`, removed
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim_start();
+    if let Some(rest) = line.strip_prefix(['-', '*', '+']) {
+        if rest.starts_with(' ') {
+            return rest.trim_start();
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(['.', ')']) {
+            if rest.starts_with(' ') {
+                return rest.trim_start();
+            }
+        }
+    }
+    line
+}
+
+/// `{{cN::answer}}` / `{{cN::answer::hint}}` spans in `line`:
+/// (byte range, N, answer, hint)
+fn clozes(line: &str) -> Vec<(std::ops::Range<usize>, u32, &str, Option<&str>)> {
+    let mut out = Vec::new();
+    let mut from = 0;
+    while let Some(at) = line[from..].find("{{c") {
+        let start = from + at;
+        let rest = &line[start + 3..];
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let inner = rest[digits..].strip_prefix("::");
+        let (Some(inner), Ok(n)) = (inner, rest[..digits].parse::<u32>()) else {
+            from = start + 3;
+            continue;
+        };
+        let Some(len) = inner.find("}}") else {
+            break;
+        };
+        let (answer, hint) = match inner[..len].split_once("::") {
+            Some((answer, hint)) => (answer, Some(hint)),
+            None => (&inner[..len], None),
+        };
+        let end = line.len() - inner.len() + len + 2;
+        out.push((start..end, n, answer, hint));
+        from = end;
+    }
+    out
+}
+
+/// One card per cloze number in `line`
+fn cloze_cards(line: &str, number: usize, out: &mut Vec<ParsedCard>) {
+    let spans = clozes(line);
+    let mut numbers: Vec<u32> = spans.iter().map(|(_, n, _, _)| *n).collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    let render = |hide: Option<u32>| {
+        let mut text = String::with_capacity(line.len());
+        let mut last = 0;
+        for (range, n, answer, hint) in &spans {
+            text.push_str(&line[last..range.start]);
+            if Some(*n) == hide {
+                text.push_str(&format!("[{}]", hint.unwrap_or("...")));
+            } else {
+                text.push_str(answer);
+            }
+            last = range.end;
+        }
+        text.push_str(&line[last..]);
+        text.trim().to_string()
+    };
+    let back = render(None);
+    for n in numbers {
+        out.push(ParsedCard {
+            line: number,
+            kind: CardKind::Cloze,
+            front: render(Some(n)),
+            back: back.clone(),
+        });
+    }
+}
+
+/// Flashcards in `content`, skipping frontmatter and code fences
+pub fn parse_cards(content: &str) -> Vec<ParsedCard> {
+    let header = &content[..content.len() - crate::frontmatter::body(content).len()];
+    let skip = header.matches('\n').count();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut cards = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut idx = skip;
+    while idx < lines.len() {
+        let line = lines[idx];
+        idx += 1;
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let text = strip_list_marker(line);
+        if text.contains("{{c") && !clozes(text).is_empty() {
+            cloze_cards(text, idx, &mut cards);
+        } else if let Some((front, back)) = text.split_once(" :: ") {
+            if !front.trim().is_empty() && !back.trim().is_empty() {
+                cards.push(ParsedCard {
+                    line: idx,
+                    kind: CardKind::Basic,
+                    front: front.trim().to_string(),
+                    back: back.trim().to_string(),
+                });
+            }
+        } else if let Some(front) = text.strip_prefix("Q:") {
+            let Some(first) = lines
+                .get(idx)
+                .and_then(|l| l.trim_start().strip_prefix("A:"))
+            else {
+                continue;
+            };
+            let mut back = vec![first.trim()];
+            let line = idx;
+            idx += 1;
+            while let Some(more) = lines.get(idx).filter(|l| !l.trim().is_empty()) {
+                back.push(more.trim_end());
+                idx += 1;
+            }
+            if !front.trim().is_empty() {
+                cards.push(ParsedCard {
+                    line,
+                    kind: CardKind::Basic,
+                    front: front.trim().to_string(),
+                    back: back.join("\n").trim().to_string(),
+                });
+            }
+        }
+    }
+    cards
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Insert the cards of one doc and drop those no longer in it; cards that
+/// were there before keep their schedule
+pub(crate) fn sync_doc_cards(conn: &Connection, doc_id: i64, content: &str) -> CoreResult<()> {
+    let today = today().to_string();
+    let mut kept = HashSet::new();
+    let mut upsert = conn.prepare(
+        "INSERT INTO cards (id, doc_id, line, kind, front, back, due)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET doc_id = ?2, line = ?3, back = ?6",
+    )?;
+    for card in parse_cards(content) {
+        let id = card.id();
+        if kept.insert(id.clone()) {
+            upsert.execute(params![
+                id,
+                doc_id,
+                card.line as i64,
+                card.kind.as_str(),
+                card.front,
+                card.back,
+                today
+            ])?;
+        }
+    }
+    let existing: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM cards WHERE doc_id = ?1")?;
+        let rows = stmt
+            .query_map([doc_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        rows
+    };
+    for id in existing.iter().filter(|id| !kept.contains(*id)) {
+        conn.execute("DELETE FROM cards WHERE id = ?1", [id])?;
+    }
+    Ok(())
+}
+
+/// Re-read every registered doc from disk; returns the number of cards found
+pub(crate) fn reindex_all(conn: &Connection) -> CoreResult<usize> {
+    let docs: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, abs_path FROM docs")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        rows
+    };
+    for (doc_id, abs_path) in docs {
+        let content = fs::read_to_string(&abs_path).unwrap_or_default();
+        sync_doc_cards(conn, doc_id, &content)?;
+    }
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM cards", [], |row| row.get(0))?;
+    Ok(count as usize)
+}
+
+const CARD_COLUMNS: &str = "c.id, d.rel_path, c.line, c.kind, c.front, c.back, c.ease,
+                            c.interval_days, c.repetitions, c.due";
+
+fn card_from_row(row: &rusqlite::Row) -> rusqlite::Result<Card> {
+    Ok(Card {
+        id: row.get(0)?,
+        rel_path: row.get(1)?,
+        line: row.get::<_, i64>(2)? as usize,
+        kind: match row.get::<_, String>(3)?.as_str() {
+            "cloze" => CardKind::Cloze,
+            _ => CardKind::Basic,
+        },
+        front: row.get(4)?,
+        back: row.get(5)?,
+        schedule: Schedule {
+            ease: row.get(6)?,
+            interval_days: row.get(7)?,
+            repetitions: row.get(8)?,
+        },
+        due: row.get(9)?,
+    })
+}
+
+impl OpenContext {
+    /// Cards due today or earlier (or every card with `all`), most overdue
+    /// first
+    pub fn list_cards(&self, query: &CardQuery) -> CoreResult<Vec<Card>> {
+        let pattern = match query.folder.as_deref() {
+            Some(folder) => {
+                let folder = normalize_folder_path(Some(folder))?;
+                (!folder.is_empty()).then(|| like_children(&folder))
+            }
+            None => None,
+        };
+        let due_by = (!query.all).then(|| today().to_string());
+        let limit = query.limit.map_or(-1, |l| l as i64);
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {CARD_COLUMNS}
+                 FROM cards c JOIN docs d ON d.id = c.doc_id
+                 WHERE (?1 IS NULL OR d.rel_path LIKE ?1 ESCAPE '\\')
+                   AND (?2 IS NULL OR c.due <= ?2)
+                 ORDER BY c.due, d.rel_path, c.line
+                 LIMIT ?3"
+            ))?;
+            let rows = stmt
+                .query_map(params![pattern, due_by, limit], card_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+    }
+
+    /// Record an answer graded `grade` (0–5) and schedule the card's next
+    /// review
+    pub fn review_card(&self, card_id: &str, grade: u8) -> CoreResult<Card> {
+        if grade > 5 {
+            return Err(CoreError::Message(format!(
+                "Grade {grade} is out of range (0-5)."
+            )));
+        }
+        let not_found = || CoreError::Message(format!("Card \"{card_id}\" not found."));
+        self.with_conn(|conn| {
+            let card = conn
+                .query_row(
+                    &format!(
+                        "SELECT {CARD_COLUMNS} FROM cards c JOIN docs d ON d.id = c.doc_id
+                         WHERE c.id = ?1"
+                    ),
+                    [card_id],
+                    card_from_row,
+                )
+                .optional()?
+                .ok_or_else(not_found)?;
+            let schedule = card.schedule.answer(grade);
+            let due = today()
+                .checked_add_days(Days::new(schedule.interval_days.max(0) as u64))
+                .unwrap_or(NaiveDate::MAX)
+                .to_string();
+            conn.execute(
+                "UPDATE cards SET ease = ?2, interval_days = ?3, repetitions = ?4, due = ?5
+                 WHERE id = ?1",
+                params![
+                    card_id,
+                    schedule.ease,
+                    schedule.interval_days,
+                    schedule.repetitions,
+                    due
+                ],
+            )?;
+            conn.execute(
+                "INSERT INTO card_reviews (card_id, reviewed_at, grade, interval_days, ease)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    card_id,
+                    now_iso(),
+                    grade,
+                    schedule.interval_days,
+                    schedule.ease
+                ],
+            )?;
+            Ok(Card {
+                schedule,
+                due,
+                ..card
+            })
+        })
+    }
+
+    /// Card counts for the review queue
+    pub fn card_stats(&self) -> CoreResult<CardStats> {
+        let today = today().to_string();
+        self.with_conn(|conn| {
+            let (total, due, new): (i64, i64, i64) = conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(due <= ?1), 0),
+                        COALESCE(SUM(repetitions = 0 AND interval_days = 0), 0)
+                 FROM cards",
+                [&today],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+            let reviewed_today: i64 = conn.query_row(
+                "SELECT COUNT(DISTINCT card_id) FROM card_reviews WHERE reviewed_at >= ?1",
+                [&today],
+                |row| row.get(0),
+            )?;
+            Ok(CardStats {
+                total: total as usize,
+                due: due as usize,
+                new: new as usize,
+                reviewed_today: reviewed_today as usize,
+            })
+        })
+    }
+
+    /// Rebuild the card table from the files on disk, picking up edits made
+    /// outside OpenContext. Schedules of cards still present are kept.
+    pub fn reindex_cards(&self) -> CoreResult<usize> {
+        self.with_conn(reindex_all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cards() {
+        let content = "---\nq: a :: b\n---\n# Geography\n\
+                       - Capital of France :: Paris\n\
+                       Q: Largest ocean?\nA: The Pacific,\nby far.\n\n\
+                       {{c1::Canberra}} is the capital of {{c2::Australia::country}}.\n\
+                       ```\nx :: y\n```\n\
+                       use std::fs and a lone Q: line\n";
+        let parsed = parse_cards(content);
+        let cards: Vec<(usize, CardKind, &str, &str)> = parsed
+            .iter()
+            .map(|c| (c.line, c.kind, c.front.as_str(), c.back.as_str()))
+            .collect();
+        let back = "Canberra is the capital of Australia.";
+        assert_eq!(
+            cards,
+            vec![
+                (5, CardKind::Basic, "Capital of France", "Paris"),
+                (
+                    6,
+                    CardKind::Basic,
+                    "Largest ocean?",
+                    "The Pacific,\nby far."
+                ),
+                (
+                    10,
+                    CardKind::Cloze,
+                    "[...] is the capital of Australia.",
+                    back
+                ),
+                (
+                    10,
+                    CardKind::Cloze,
+                    "Canberra is the capital of [country].",
+                    back
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sm2_schedule() {
+        let good = Schedule::default().answer(4);
+        assert_eq!((good.interval_days, good.repetitions), (1, 1));
+        let good = good.answer(4);
+        assert_eq!(good.interval_days, 6);
+        let easy = good.answer(5);
+        assert_eq!(easy.interval_days, 15);
+        assert!((easy.ease - 2.6).abs() < 1e-9);
+        let forgot = easy.answer(1);
+        assert_eq!((forgot.interval_days, forgot.repetitions), (1, 0));
+        assert!(forgot.ease < easy.ease);
+        let mut worst = Schedule::default();
+        for _ in 0..10 {
+            worst = worst.answer(0);
+        }
+        assert_eq!(worst.ease, MIN_EASE);
+    }
+}
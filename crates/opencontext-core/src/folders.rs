@@ -0,0 +1,329 @@
+//! Folder rename, move and delete
+//!
+//! Renaming or moving a folder changes the path of every document under it,
+//! and deleting one removes them all. [`FolderManager`] does the filesystem
+//! work, re-keys or drops the records in one transaction and lists each
+//! affected document twice: as an index WAL entry written in that
+//! transaction, for a sync service in another process or the next run, and in
+//! the [`FolderEvent`](crate::events::FolderEvent) it emits, for the one
+//! listening on this workspace's event bus.
+
+use std::fs;
+
+use rusqlite::params;
+
+use crate::links;
+use crate::wal::{self, WalOp};
+#[cfg(feature = "keyword")]
+use crate::{events::FolderEvent, RelPath};
+use crate::{
+    folder_not_found, like_children, normalize_folder_path, now_iso, parent_rel_path, relpath,
+    CoreError, CoreResult, Folder, OpenContext, Removed, RenameResult,
+};
+
+/// Folder operations on one workspace, from [`OpenContext::folders`]
+pub struct FolderManager<'a> {
+    ctx: &'a OpenContext,
+}
+
+impl OpenContext {
+    /// Rename, move and delete folders
+    pub fn folders(&self) -> FolderManager<'_> {
+        FolderManager { ctx: self }
+    }
+}
+
+impl<'a> FolderManager<'a> {
+    /// Give the folder at `path` a new last segment
+    pub fn rename(&self, path: &str, new_name: &str) -> CoreResult<RenameResult> {
+        self.ctx.ensure_writable("rename folders")?;
+        let rel_path = normalize_folder_path(Some(path))?;
+        if rel_path.is_empty() {
+            return Err(CoreError::Message(
+                "Cannot rename the root contexts directory.".into(),
+            ));
+        }
+        if new_name.is_empty() || new_name.contains('/') {
+            return Err(CoreError::Message(
+                "New name must be a single path segment.".into(),
+            ));
+        }
+        let folder = self
+            .ctx
+            .find_folder(&rel_path)?
+            .ok_or_else(|| folder_not_found(&rel_path))?;
+        let new_rel_path = match parent_rel_path(&rel_path).as_deref() {
+            Some(parent) if !parent.is_empty() => format!("{parent}/{new_name}"),
+            _ => new_name.to_string(),
+        };
+        if self.ctx.find_folder(&new_rel_path)?.is_some() {
+            return Err(CoreError::Message(format!(
+                "Target folder \"{new_rel_path}\" already exists."
+            )));
+        }
+        self.relocate(&folder, &new_rel_path, folder.parent_id, false)?;
+        let links_rewritten = self
+            .ctx
+            .rewrite_links_after_move(&rel_path, &new_rel_path)?;
+        Ok(RenameResult {
+            old_path: rel_path,
+            new_path: new_rel_path,
+            links_rewritten,
+        })
+    }
+
+    /// Move the folder at `path` into the folder at `dest_folder_path`
+    pub fn move_to(&self, path: &str, dest_folder_path: &str) -> CoreResult<RenameResult> {
+        self.ctx.ensure_writable("move folders")?;
+        let rel_path = normalize_folder_path(Some(path))?;
+        if rel_path.is_empty() {
+            return Err(CoreError::Message(
+                "Cannot move the root contexts directory.".into(),
+            ));
+        }
+        let dest_rel_folder = normalize_folder_path(Some(dest_folder_path))?;
+        if dest_rel_folder.is_empty() {
+            return Err(CoreError::Message(
+                "Root is not supported. Please move into a folder under contexts/.".into(),
+            ));
+        }
+        if dest_rel_folder == rel_path || dest_rel_folder.starts_with(&format!("{rel_path}/")) {
+            return Err(CoreError::Message(
+                "Cannot move a folder into itself or its descendants.".into(),
+            ));
+        }
+
+        let folder = self
+            .ctx
+            .find_folder(&rel_path)?
+            .ok_or_else(|| folder_not_found(&rel_path))?;
+        let dest_folder = self
+            .ctx
+            .find_folder(&dest_rel_folder)?
+            .ok_or_else(|| folder_not_found(&dest_rel_folder))?;
+
+        let new_rel_path = if dest_folder.rel_path.is_empty() {
+            folder.name.clone()
+        } else {
+            format!("{}/{}", dest_folder.rel_path, folder.name)
+        };
+        if self.ctx.find_folder(&new_rel_path)?.is_some() {
+            return Err(CoreError::Message(format!(
+                "Target folder \"{new_rel_path}\" already exists."
+            )));
+        }
+
+        self.relocate(&folder, &new_rel_path, Some(dest_folder.id), true)?;
+        let links_rewritten = self
+            .ctx
+            .rewrite_links_after_move(&rel_path, &new_rel_path)?;
+        Ok(RenameResult {
+            old_path: rel_path,
+            new_path: new_rel_path,
+            links_rewritten,
+        })
+    }
+
+    /// Delete the folder at `path`; unless `force`, only when it is empty
+    pub fn delete(&self, path: &str, force: bool) -> CoreResult<Removed> {
+        self.ctx.ensure_writable("remove folders")?;
+        let rel_path = normalize_folder_path(Some(path))?;
+        if rel_path.is_empty() {
+            return Err(CoreError::Message(
+                "Cannot remove the root contexts directory.".into(),
+            ));
+        }
+        let folder = self
+            .ctx
+            .find_folder(&rel_path)?
+            .ok_or_else(|| folder_not_found(&rel_path))?;
+
+        let removed_docs = self.ctx.with_conn(|conn| {
+            let child_count: i64 = conn.query_row(
+                "SELECT COUNT(1) FROM folders WHERE parent_id = ?1",
+                params![folder.id],
+                |row| row.get(0),
+            )?;
+            let doc_count: i64 = conn.query_row(
+                "SELECT COUNT(1) FROM docs WHERE folder_id = ?1",
+                params![folder.id],
+                |row| row.get(0),
+            )?;
+            if !force && (child_count > 0 || doc_count > 0) {
+                return Err(CoreError::Message(format!(
+                    "Folder \"{rel_path}\" is not empty. Use --force to delete recursively."
+                )));
+            }
+            let like_pattern = like_children(&rel_path);
+            let ts = now_iso();
+            let tx = conn.unchecked_transaction()?;
+            let removed_docs = tx
+                .prepare(
+                    "SELECT rel_path FROM docs WHERE rel_path LIKE ?1 ESCAPE '\\' OR folder_id = ?2 ORDER BY rel_path",
+                )?
+                .query_map(params![like_pattern, folder.id], |row| {
+                    row.get::<_, String>(0)
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for doc_rel in &removed_docs {
+                wal::append(
+                    &tx,
+                    &WalOp::Remove {
+                        rel_path: doc_rel.clone(),
+                    },
+                    &ts,
+                )?;
+            }
+            tx.execute(
+                "DELETE FROM docs WHERE rel_path LIKE ?1 ESCAPE '\\'",
+                params![like_pattern],
+            )?;
+            tx.execute(
+                "DELETE FROM folders WHERE rel_path LIKE ?1 ESCAPE '\\'",
+                params![like_pattern],
+            )?;
+            tx.execute("DELETE FROM docs WHERE folder_id = ?1", params![folder.id])?;
+            tx.execute("DELETE FROM folders WHERE id = ?1", params![folder.id])?;
+            tx.commit()?;
+            Ok(removed_docs)
+        })?;
+        if folder.abs_path.exists() {
+            if force {
+                fs::remove_dir_all(&folder.abs_path)?;
+            } else {
+                fs::remove_dir(&folder.abs_path)?;
+            }
+        }
+
+        #[cfg(feature = "keyword")]
+        self.ctx.emit_folder_event(FolderEvent::Deleted {
+            rel_path: rel_path.as_str().into(),
+            removed_docs: removed_docs.into_iter().map(RelPath::from).collect(),
+        });
+        #[cfg(not(feature = "keyword"))]
+        let _ = removed_docs;
+
+        Ok(Removed { rel_path })
+    }
+
+    /// Move a folder's directory to `new_rel_path` and re-key its records
+    fn relocate(
+        &self,
+        folder: &Folder,
+        new_rel_path: &str,
+        parent_id: Option<i64>,
+        moved: bool,
+    ) -> CoreResult<()> {
+        let new_abs_path = self.ctx.abs_path(new_rel_path);
+        if let Some(parent) = new_abs_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&folder.abs_path, &new_abs_path)?;
+        // Put the directory back if the records could not follow it, so disk
+        // and index keep agreeing
+        self.rekey(folder, new_rel_path, parent_id, moved)
+            .inspect_err(|_| {
+                let _ = fs::rename(&new_abs_path, &folder.abs_path);
+            })
+    }
+
+    /// Point a folder whose directory is already at `new_rel_path`, its
+    /// subfolders and its docs at the new path in one transaction, then emit
+    /// the folder event (`Moved` when `moved`, else `Renamed`) listing every
+    /// doc inside with its old and new path
+    pub(crate) fn rekey(
+        &self,
+        folder: &Folder,
+        new_rel_path: &str,
+        parent_id: Option<i64>,
+        moved: bool,
+    ) -> CoreResult<()> {
+        let new_abs_path = self.ctx.abs_path(new_rel_path);
+        let ts = now_iso();
+        let name = relpath::file_name(new_rel_path);
+        let rekey =
+            |child_rel: &str| format!("{new_rel_path}{}", &child_rel[folder.rel_path.len()..]);
+
+        let affected_docs = self.ctx.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let mut affected_docs = Vec::new();
+            {
+                tx.execute(
+                    "UPDATE folders SET parent_id = ?1, name = ?2, rel_path = ?3, abs_path = ?4, updated_at = ?5 WHERE id = ?6",
+                    params![parent_id, name, new_rel_path, new_abs_path.to_string_lossy(), ts, folder.id],
+                )?;
+                let like_pattern = like_children(&folder.rel_path);
+                let mut stmt = tx.prepare(
+                    "SELECT id, rel_path FROM folders WHERE rel_path LIKE ?1 ESCAPE '\\'",
+                )?;
+                let folder_rows = stmt
+                    .query_map([&like_pattern], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (id, child_rel) in folder_rows {
+                    let updated_rel = rekey(&child_rel);
+                    let updated_abs = self.ctx.abs_path(&updated_rel);
+                    tx.execute(
+                        "UPDATE folders SET rel_path = ?1, abs_path = ?2, updated_at = ?3 WHERE id = ?4",
+                        params![updated_rel, updated_abs.to_string_lossy(), ts, id],
+                    )?;
+                }
+                let mut doc_stmt = tx.prepare(
+                    "SELECT id, rel_path FROM docs WHERE rel_path LIKE ?1 ESCAPE '\\' ORDER BY rel_path",
+                )?;
+                let doc_rows = doc_stmt
+                    .query_map([&like_pattern], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (id, doc_rel) in doc_rows {
+                    let updated_rel = rekey(&doc_rel);
+                    let updated_abs = self.ctx.abs_path(&updated_rel);
+                    tx.execute(
+                        "UPDATE docs SET rel_path = ?1, abs_path = ?2, updated_at = ?3 WHERE id = ?4",
+                        params![updated_rel, updated_abs.to_string_lossy(), ts, id],
+                    )?;
+                    wal::append(
+                        &tx,
+                        &WalOp::Rename {
+                            old_path: doc_rel.clone(),
+                            new_path: updated_rel.clone(),
+                        },
+                        &ts,
+                    )?;
+                    affected_docs.push((doc_rel, updated_rel));
+                }
+                links::record_move(&tx, &folder.rel_path, new_rel_path, &ts)?;
+            }
+            tx.commit()?;
+            Ok(affected_docs)
+        })?;
+
+        #[cfg(feature = "keyword")]
+        {
+            let old_path = RelPath::new(&folder.rel_path);
+            let new_path = RelPath::new(new_rel_path);
+            let affected_docs = affected_docs
+                .into_iter()
+                .map(|(old, new)| (old.into(), new.into()))
+                .collect();
+            self.ctx.emit_folder_event(if moved {
+                FolderEvent::Moved {
+                    old_path,
+                    new_path,
+                    affected_docs,
+                }
+            } else {
+                FolderEvent::Renamed {
+                    old_path,
+                    new_path,
+                    affected_docs,
+                }
+            });
+        }
+        #[cfg(not(feature = "keyword"))]
+        let _ = (affected_docs, moved);
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+//! Frontmatter fields
+//!
+//! Workflows keep things like `status: done` or `owner: alice` in a YAML
+//! block at the top of a document. [`set_field`] rewrites one field of that
+//! block in place; every other line, including comments and the body, is
+//! left byte-for-byte as it was. Only the YAML people write by hand in
+//! frontmatter is understood: scalars, flow lists, block lists, one-level
+//! nested maps and `|` / `>` block scalars.
+
+use serde_json::{Map, Value};
+
+use crate::{CoreError, CoreResult};
+
+const DELIMITER: &str = "---";
+
+/// Byte offsets of a frontmatter block within a document
+struct Block {
+    /// First line after the opening `---`
+    start: usize,
+    /// Closing `---` line
+    end: usize,
+    /// First byte of the body
+    body: usize,
+}
+
+/// A top-level `key: value` entry and its continuation lines
+struct Entry<'a> {
+    key: &'a str,
+    lines: Vec<&'a str>,
+    start: usize,
+    end: usize,
+}
+
+fn block(content: &str) -> Option<Block> {
+    let first = content.split_inclusive('\n').next()?;
+    if first.trim_end() != DELIMITER {
+        return None;
+    }
+    let mut offset = first.len();
+    for line in content[first.len()..].split_inclusive('\n') {
+        let text = line.trim_end();
+        if text == DELIMITER || text == "..." {
+            return Some(Block {
+                start: first.len(),
+                end: offset,
+                body: offset + line.len(),
+            });
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The document without its frontmatter block
+pub fn body(content: &str) -> &str {
+    block(content).map_or(content, |b| &content[b.body..])
+}
+
+/// All top-level frontmatter fields (empty if there is no frontmatter)
+pub fn fields(content: &str) -> Map<String, Value> {
+    let Some(b) = block(content) else {
+        return Map::new();
+    };
+    entries(content, &b)
+        .iter()
+        .map(|e| (e.key.to_string(), parse_entry(e)))
+        .collect()
+}
+
+/// Value of one frontmatter field
+pub fn get_field(content: &str, key: &str) -> Option<Value> {
+    let b = block(content)?;
+    entries(content, &b)
+        .iter()
+        .find(|e| e.key == key)
+        .map(parse_entry)
+}
+
+/// Set `key` to `value`, replacing the existing entry (keeping its trailing
+/// comment and block-list style), adding it at the end of the block, or
+/// creating the block if the document has none
+pub fn set_field(content: &str, key: &str, value: &Value) -> CoreResult<String> {
+    let key = key.trim();
+    if key.is_empty() || key.contains([':', '\n', '\r']) || key.starts_with(['#', '-', '"', '\'']) {
+        return Err(CoreError::Message(format!(
+            "invalid frontmatter field name: {key:?}"
+        )));
+    }
+    let eol = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let Some(b) = block(content) else {
+        let entry = render_entry(key, value, None, eol);
+        return Ok(format!("{DELIMITER}{eol}{entry}{DELIMITER}{eol}{content}"));
+    };
+    let entries = entries(content, &b);
+    let (start, end, old) = match entries.iter().find(|e| e.key == key) {
+        Some(e) => (e.start, e.end, Some(e)),
+        None => (b.end, b.end, None),
+    };
+    Ok(format!(
+        "{}{}{}",
+        &content[..start],
+        render_entry(key, value, old, eol),
+        &content[end..]
+    ))
+}
+
+fn entries<'a>(content: &'a str, b: &Block) -> Vec<Entry<'a>> {
+    let mut out: Vec<Entry> = Vec::new();
+    let mut offset = b.start;
+    for line in content[b.start..b.end].split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let continues = !text.trim().is_empty() && text.starts_with([' ', '\t', '-']);
+        match out.last_mut() {
+            Some(last) if continues && last.end == offset => {
+                last.lines.push(text);
+                last.end += line.len();
+            }
+            _ => {
+                if let Some(key) = entry_key(text) {
+                    out.push(Entry {
+                        key,
+                        lines: vec![text],
+                        start: offset,
+                        end: offset + line.len(),
+                    });
+                }
+            }
+        }
+        offset += line.len();
+    }
+    out
+}
+
+fn entry_key(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '#', '-']) {
+        return None;
+    }
+    let (key, rest) = line.split_once(':')?;
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let key = key.trim().trim_matches(['"', '\'']);
+    (!key.is_empty()).then_some(key)
+}
+
+fn parse_entry(entry: &Entry) -> Value {
+    let rest = entry.lines[0].split_once(':').map_or("", |(_, r)| r);
+    let value = split_comment(rest).0.trim();
+    let nested = &entry.lines[1..];
+
+    if value.starts_with(['|', '>']) {
+        let indent = nested
+            .iter()
+            .map(|l| l.len() - l.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let lines: Vec<&str> = nested.iter().map(|l| &l[indent..]).collect();
+        let mut text = lines.join(if value.starts_with('|') { "\n" } else { " " });
+        if !value.contains('-') {
+            text.push('\n');
+        }
+        return Value::String(text);
+    }
+    if !value.is_empty() || nested.is_empty() {
+        return parse_scalar(value);
+    }
+    if nested.iter().all(|l| l.trim_start().starts_with('-')) {
+        return Value::Array(
+            nested
+                .iter()
+                .map(|l| parse_scalar(split_comment(&l.trim_start()[1..]).0.trim()))
+                .collect(),
+        );
+    }
+    Value::Object(
+        nested
+            .iter()
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| {
+                let k = k.trim().trim_matches(['"', '\'']).to_string();
+                (k, parse_scalar(split_comment(v).0.trim()))
+            })
+            .collect(),
+    )
+}
+
+/// Split a value from its `# comment` (the comment keeps its leading space)
+fn split_comment(s: &str) -> (&str, &str) {
+    let mut quote = None;
+    let mut prev_space = true;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if prev_space => {
+                let value = s[..i].trim_end();
+                return (value, &s[value.len()..]);
+            }
+            _ => {}
+        }
+        prev_space = c.is_whitespace();
+    }
+    (s, "")
+}
+
+fn parse_scalar(s: &str) -> Value {
+    match s {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') => {
+            serde_json::from_str(s).unwrap_or_else(|_| Value::String(s[1..s.len() - 1].into()))
+        }
+        _ if s.len() >= 2 && s.starts_with('\'') && s.ends_with('\'') => {
+            Value::String(s[1..s.len() - 1].replace("''", "'"))
+        }
+        _ if s.starts_with('[') && s.ends_with(']') => {
+            Value::Array(split_flow(&s[1..s.len() - 1]).map(parse_scalar).collect())
+        }
+        _ if s.starts_with('{') => {
+            serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.into()))
+        }
+        _ => s
+            .parse::<i64>()
+            .ok()
+            .map(Value::from)
+            .or_else(|| {
+                s.parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+            })
+            .unwrap_or_else(|| Value::String(s.into())),
+    }
+}
+
+/// Items of a flow list body, splitting on commas outside quotes
+fn split_flow(s: &str) -> impl Iterator<Item = &str> {
+    let mut items = Vec::new();
+    let (mut quote, mut start) = (None, 0);
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ',') => {
+                items.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(s[start..].trim());
+    items.into_iter().filter(|item| !item.is_empty())
+}
+
+fn render_entry(key: &str, value: &Value, old: Option<&Entry>, eol: &str) -> String {
+    let (prefix, comment) = match old {
+        Some(e) => {
+            let (prefix, rest) = e.lines[0].split_once(':').unwrap_or((key, ""));
+            (prefix, split_comment(rest).1)
+        }
+        None => (key, ""),
+    };
+
+    // Keep a block list a block list
+    let block_indent = old
+        .filter(|e| {
+            e.lines.len() > 1 && e.lines[1..].iter().all(|l| l.trim_start().starts_with('-'))
+        })
+        .map(|e| &e.lines[1][..e.lines[1].len() - e.lines[1].trim_start().len()]);
+    if let (Value::Array(items), Some(indent)) = (value, block_indent) {
+        let mut out = format!("{prefix}:{comment}{eol}");
+        for item in items {
+            out.push_str(&format!("{indent}- {}{eol}", render_value(item, false)));
+        }
+        return out;
+    }
+    format!("{prefix}: {}{comment}{eol}", render_value(value, false))
+}
+
+fn render_value(value: &Value, in_flow: bool) -> String {
+    match value {
+        Value::String(s) if needs_quotes(s, in_flow) => Value::String(s.clone()).to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(|item| render_value(item, true))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => other.to_string(),
+    }
+}
+
+fn needs_quotes(s: &str, in_flow: bool) -> bool {
+    s.is_empty()
+        || s != s.trim()
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.ends_with(':')
+        || s.chars().any(char::is_control)
+        || s.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        || (in_flow && s.contains([',', '[', ']', '{', '}']))
+        || !parse_scalar(s).is_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DOC: &str = "---\ntitle: \"Launch plan\"\nstatus: todo  # todo | doing | done\npriority: 2\ntags:\n  - launch\n  - q4\nowner:\n  name: alice\n---\n# Launch\n";
+
+    #[test]
+    fn test_get_fields() {
+        assert_eq!(get_field(DOC, "title"), Some(json!("Launch plan")));
+        assert_eq!(get_field(DOC, "status"), Some(json!("todo")));
+        assert_eq!(get_field(DOC, "priority"), Some(json!(2)));
+        assert_eq!(get_field(DOC, "tags"), Some(json!(["launch", "q4"])));
+        assert_eq!(get_field(DOC, "owner"), Some(json!({ "name": "alice" })));
+        assert_eq!(get_field(DOC, "missing"), None);
+        assert_eq!(fields(DOC).len(), 5);
+        assert_eq!(body(DOC), "# Launch\n");
+        assert_eq!(get_field("# No frontmatter\n", "title"), None);
+    }
+
+    #[test]
+    fn test_set_field_preserves_formatting() {
+        let out = set_field(DOC, "status", &json!("done")).unwrap();
+        assert_eq!(out, DOC.replace("status: todo ", "status: done "));
+
+        let out = set_field(DOC, "tags", &json!(["launch", "shipped"])).unwrap();
+        assert!(
+            out.contains("tags:\n  - launch\n  - shipped\nowner:"),
+            "{out}"
+        );
+
+        let out = set_field(DOC, "done", &json!(true)).unwrap();
+        assert!(
+            out.ends_with("  name: alice\ndone: true\n---\n# Launch\n"),
+            "{out}"
+        );
+        // A string that would read back as another type is quoted
+        let out = set_field(&out, "done", &json!("yes: really")).unwrap();
+        assert_eq!(get_field(&out, "done"), Some(json!("yes: really")));
+
+        let out = set_field("# Title\n", "status", &json!("done")).unwrap();
+        assert_eq!(out, "---\nstatus: done\n---\n# Title\n");
+
+        assert!(set_field(DOC, "a: b", &json!(1)).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Git history
+//!
+//! [`OpenContext::import_git`] turns a repository's history into documents
+//! under `.repos/<repo>/`: one note per day of commits (`commits/<date>.md`,
+//! a section per commit with its message) and, when pull requests are given,
+//! one note per pull request (`prs/<number>.md`). Every note carries the
+//! repository path and its date in frontmatter, so it shows up in the date
+//! index and can be traced back to the checkout; the search index tags the
+//! notes with doc type `commit` or `pull_request`.
+//!
+//! Commits are read with the `git` binary ([`read_commits`]); pull requests
+//! come from the GitHub API ([`fetch_pull_requests`], `search` builds only).
+//! Importing again only rewrites the notes whose content changed.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{frontmatter, relpath, CoreError, CoreResult, OpenContext};
+
+/// Folder that holds imported repository history
+pub const REPOS_DIR: &str = ".repos/";
+
+/// Field and record separators of the `git log` format below
+const FIELD_SEP: char = '\x1f';
+const RECORD_SEP: char = '\x1e';
+const LOG_FORMAT: &str = "--format=%H%x1f%an%x1f%aI%x1f%s%x1f%b%x1e";
+
+/// One commit as read from `git log`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub author: String,
+    /// Author date, ISO 8601 in the author's time zone
+    pub date: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl GitCommit {
+    /// `YYYY-MM-DD` of the author date
+    fn day(&self) -> &str {
+        self.date.get(0..10).unwrap_or("undated")
+    }
+}
+
+/// A GitHub pull request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub author: String,
+    /// `open`, `closed` or `merged`
+    pub state: String,
+    pub created_at: String,
+    pub merged_at: Option<String>,
+    pub url: String,
+}
+
+/// Owner and name of a repository on GitHub
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GithubRepo {
+    pub owner: String,
+    pub name: String,
+}
+
+/// What [`OpenContext::import_git`] wrote
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GitImport {
+    /// Repository the history came from
+    pub repo: String,
+    /// Folder the notes were written to
+    pub folder: String,
+    /// Documents created
+    pub created: Vec<String>,
+    /// Existing documents that changed
+    pub updated: Vec<String>,
+    /// Documents that were already up to date
+    pub unchanged: usize,
+    pub commits: usize,
+    pub pull_requests: usize,
+}
+
+/// Run `git` in `repo` and return its standard output
+fn git(repo: &Path, args: &[&str]) -> CoreResult<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| CoreError::Message(format!("could not run git: {e}")))?;
+    if !output.status.success() {
+        return Err(CoreError::Message(format!(
+            "git {} failed in {}: {}",
+            args.first().copied().unwrap_or_default(),
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Top-level directory of the repository containing `path`
+pub fn repo_root(path: &Path) -> CoreResult<PathBuf> {
+    let root = git(path, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(root.trim()))
+}
+
+/// Commits of `repo`'s current branch, newest first. Merge commits are left
+/// out; `since` takes anything `git log --since` does (`2024-01-01`,
+/// `3 months ago`).
+pub fn read_commits(repo: &Path, since: Option<&str>) -> CoreResult<Vec<GitCommit>> {
+    let since = since.map(|s| format!("--since={s}"));
+    let mut args = vec!["log", "--no-merges", LOG_FORMAT];
+    args.extend(since.as_deref());
+    Ok(parse_git_log(&git(repo, &args)?))
+}
+
+/// Commits in the output of `git log` run with [`LOG_FORMAT`]
+pub fn parse_git_log(output: &str) -> Vec<GitCommit> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches(['\n', '\r']).splitn(5, FIELD_SEP);
+            let hash = fields.next()?.trim();
+            if hash.is_empty() {
+                return None;
+            }
+            Some(GitCommit {
+                hash: hash.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                body: fields.next().unwrap_or("").trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// GitHub repository of a remote URL (`git@github.com:owner/name.git`,
+/// `https://github.com/owner/name`, `ssh://git@github.com/owner/name.git`)
+pub fn parse_github_remote(url: &str) -> Option<GithubRepo> {
+    let url = url.trim();
+    let path = url.strip_prefix("git@github.com:").or_else(|| {
+        let rest = url.split_once("://")?.1;
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+        rest.strip_prefix("github.com/")
+    })?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, name) = path.split_once('/')?;
+    (!owner.is_empty() && !name.is_empty() && !name.contains('/')).then(|| GithubRepo {
+        owner: owner.to_string(),
+        name: name.to_string(),
+    })
+}
+
+/// GitHub repository behind `repo`'s `origin` remote, if it is on GitHub
+pub fn github_repo(repo: &Path) -> CoreResult<Option<GithubRepo>> {
+    let url = git(repo, &["remote", "get-url", "origin"])?;
+    Ok(parse_github_remote(&url))
+}
+
+/// Up to `limit` pull requests of `repo`, most recently updated first.
+/// `token` is needed for private repositories and raises the rate limit.
+#[cfg(feature = "search")]
+pub async fn fetch_pull_requests(
+    repo: &GithubRepo,
+    token: Option<&str>,
+    limit: usize,
+) -> CoreResult<Vec<PullRequest>> {
+    #[derive(serde::Deserialize)]
+    struct User {
+        login: String,
+    }
+    #[derive(serde::Deserialize)]
+    struct ApiPull {
+        number: u64,
+        title: String,
+        body: Option<String>,
+        user: Option<User>,
+        state: String,
+        created_at: String,
+        merged_at: Option<String>,
+        html_url: String,
+    }
+
+    let client = crate::sources::github::GithubClient::new(token.map(str::to_string))?;
+    let path = format!("/repos/{}/{}/pulls", repo.owner, repo.name);
+    let per_page = limit.clamp(1, 100);
+    let mut pulls: Vec<PullRequest> = Vec::new();
+    for page in 1.. {
+        let query = [
+            ("state", "all".to_string()),
+            ("sort", "updated".to_string()),
+            ("direction", "desc".to_string()),
+            ("per_page", per_page.to_string()),
+            ("page", page.to_string()),
+        ];
+        let batch: Vec<ApiPull> = client.get(&path, &query).await?;
+        let last_page = batch.len() < per_page;
+        pulls.extend(batch.into_iter().map(|p| PullRequest {
+            number: p.number,
+            title: p.title,
+            body: p.body.unwrap_or_default().trim().to_string(),
+            author: p.user.map(|u| u.login).unwrap_or_default(),
+            state: if p.merged_at.is_some() {
+                "merged".to_string()
+            } else {
+                p.state
+            },
+            created_at: p.created_at,
+            merged_at: p.merged_at,
+            url: p.html_url,
+        }));
+        if last_page || pulls.len() >= limit {
+            break;
+        }
+    }
+    pulls.truncate(limit);
+    Ok(pulls)
+}
+
+/// `body` with a frontmatter block holding `fields`
+fn with_frontmatter(body: &str, fields: &[(&str, Value)]) -> CoreResult<String> {
+    fields
+        .iter()
+        .try_fold(body.to_string(), |content, (key, value)| {
+            frontmatter::set_field(&content, key, value)
+        })
+}
+
+/// Note for the commits of one day, oldest first
+fn render_commit_day(repo: &str, day: &str, commits: &[&GitCommit]) -> CoreResult<String> {
+    let mut body = format!("\n# {repo} commits on {day}\n");
+    for commit in commits {
+        let time = commit.date.get(11..16).unwrap_or("");
+        let short = commit.hash.get(0..7).unwrap_or(&commit.hash);
+        body.push_str(&format!(
+            "\n## {}\n\n`{short}` · {} · {time}\n",
+            commit.subject, commit.author
+        ));
+        if !commit.body.is_empty() {
+            body.push_str(&format!("\n{}\n", commit.body));
+        }
+    }
+    with_frontmatter(
+        &body,
+        &[("repo", Value::from(repo)), ("date", Value::from(day))],
+    )
+}
+
+/// Note for one pull request
+fn render_pull_request(repo: &str, pull: &PullRequest) -> CoreResult<String> {
+    let opened = pull.created_at.get(0..10).unwrap_or(&pull.created_at);
+    let mut status = format!("Opened by {} on {opened}", pull.author);
+    if let Some(merged) = &pull.merged_at {
+        status.push_str(&format!(
+            " · merged {}",
+            merged.get(0..10).unwrap_or(merged)
+        ));
+    } else if pull.state != "open" {
+        status.push_str(" · closed");
+    }
+    let mut body = format!("\n# #{} {}\n\n{status}\n", pull.number, pull.title);
+    if !pull.body.is_empty() {
+        body.push_str(&format!("\n{}\n", pull.body));
+    }
+    with_frontmatter(
+        &body,
+        &[
+            ("repo", Value::from(repo)),
+            ("date", Value::from(opened)),
+            ("pr", Value::from(pull.number)),
+            ("state", Value::from(pull.state.as_str())),
+            ("url", Value::from(pull.url.as_str())),
+        ],
+    )
+}
+
+impl OpenContext {
+    /// Write `commits` and `pulls` of the repository at `repo` as notes
+    /// under `.repos/<repo name>/`
+    pub fn import_git(
+        &self,
+        repo: &Path,
+        commits: &[GitCommit],
+        pulls: &[PullRequest],
+    ) -> CoreResult<GitImport> {
+        let repo_path = repo.to_string_lossy().into_owned();
+        let name = repo
+            .file_name()
+            .map(|n| relpath::slug(&n.to_string_lossy()))
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| CoreError::Message(format!("not a repository path: {repo_path}")))?;
+        let folder = format!("{REPOS_DIR}{name}");
+        let mut import = GitImport {
+            repo: repo_path.clone(),
+            folder: folder.clone(),
+            commits: commits.len(),
+            pull_requests: pulls.len(),
+            ..Default::default()
+        };
+        if self.find_folder(&folder)?.is_none() {
+            self.create_folder(&folder, Some(&format!("Git history of {repo_path}")))?;
+        }
+
+        let mut days: BTreeMap<&str, Vec<&GitCommit>> = BTreeMap::new();
+        for commit in commits {
+            days.entry(commit.day()).or_default().push(commit);
+        }
+        let mut docs: Vec<(String, String)> = Vec::new();
+        for (day, mut commits) in days {
+            commits.sort_by(|a, b| a.date.cmp(&b.date));
+            docs.push((
+                format!("{folder}/commits/{day}.md"),
+                render_commit_day(&repo_path, day, &commits)?,
+            ));
+        }
+        for pull in pulls {
+            docs.push((
+                format!("{folder}/prs/{}.md", pull.number),
+                render_pull_request(&repo_path, pull)?,
+            ));
+        }
+
+        for (rel_path, content) in docs {
+            match self.put_generated_doc(&rel_path, &content)? {
+                Some(true) => import.created.push(rel_path),
+                Some(false) => import.updated.push(rel_path),
+                None => import.unchanged += 1,
+            }
+        }
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = "a1b2c3d4e5\x1fAda\x1f2026-10-14T14:32:01+02:00\x1fFix watcher race\x1fThe watcher could miss\nthe first event.\n\x1e\n\
+                      f6e5d4c3b2\x1fGrace\x1f2026-10-13T09:05:00-07:00\x1fInitial commit\x1f\x1e\n";
+        let commits = parse_git_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].subject, "Fix watcher race");
+        assert_eq!(commits[0].body, "The watcher could miss\nthe first event.");
+        assert_eq!(commits[0].day(), "2026-10-14");
+        assert_eq!(commits[1].hash, "f6e5d4c3b2");
+        assert_eq!(commits[1].body, "");
+        assert!(parse_git_log("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_github_remote() {
+        let repo = |owner: &str, name: &str| {
+            Some(GithubRepo {
+                owner: owner.into(),
+                name: name.into(),
+            })
+        };
+        assert_eq!(
+            parse_github_remote("git@github.com:wormuz/OpenContext.git\n"),
+            repo("wormuz", "OpenContext")
+        );
+        assert_eq!(
+            parse_github_remote("https://github.com/wormuz/OpenContext"),
+            repo("wormuz", "OpenContext")
+        );
+        assert_eq!(
+            parse_github_remote("https://token@github.com/a/b.git/"),
+            repo("a", "b")
+        );
+        assert_eq!(
+            parse_github_remote("ssh://git@github.com/a/b.git"),
+            repo("a", "b")
+        );
+        assert_eq!(parse_github_remote("https://gitlab.com/a/b.git"), None);
+        assert_eq!(parse_github_remote("https://github.com/a"), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Code highlighting
+//!
+//! Marks up the code of fenced blocks with `<span class="hl-…">` around
+//! comments, strings, numbers and keywords, so rendered notes read like an
+//! editor shows them. It is a lexer per language family, not a parser:
+//! good enough for notes, and free of a grammar bundle. Unknown languages
+//! come back escaped and unmarked.
+
+use crate::html;
+
+/// How one family of languages writes comments and strings
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comment: &'static [&'static str],
+    /// Opening and closing delimiters
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+}
+
+const C_LIKE: &[&str] = &[
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "fn",
+    "for",
+    "func",
+    "function",
+    "go",
+    "if",
+    "impl",
+    "implements",
+    "import",
+    "in",
+    "interface",
+    "let",
+    "loop",
+    "match",
+    "mod",
+    "module",
+    "mut",
+    "namespace",
+    "new",
+    "nil",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "use",
+    "var",
+    "void",
+    "where",
+    "while",
+    "yield",
+];
+
+const PYTHON: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "self", "True", "try",
+    "while", "with", "yield",
+];
+
+const RUBY: &[&str] = &[
+    "begin", "class", "def", "do", "else", "elsif", "end", "ensure", "false", "if", "module",
+    "next", "nil", "require", "rescue", "return", "self", "then", "true", "unless", "until",
+    "when", "while", "yield",
+];
+
+const SHELL: &[&str] = &[
+    "case", "do", "done", "echo", "elif", "else", "esac", "exit", "export", "fi", "for",
+    "function", "if", "in", "local", "return", "then", "until", "while",
+];
+
+const SQL: &[&str] = &[
+    "and", "as", "asc", "by", "create", "delete", "desc", "distinct", "drop", "from", "group",
+    "having", "insert", "into", "join", "left", "limit", "not", "null", "on", "or", "order",
+    "outer", "right", "select", "set", "table", "union", "update", "values", "where", "with",
+];
+
+fn syntax(lang: &str) -> Option<Syntax> {
+    let lang = lang.to_ascii_lowercase();
+    let syntax = match lang.as_str() {
+        // `'` also starts lifetimes, so only `"` strings
+        "rust" | "rs" => Syntax {
+            keywords: C_LIKE,
+            line_comment: &["//"],
+            block_comment: Some(("/*", "*/")),
+            quotes: &['"'],
+        },
+        "js" | "javascript" | "jsx" | "ts" | "typescript" | "tsx" | "go" | "java" | "kotlin"
+        | "kt" | "swift" | "c" | "h" | "cpp" | "c++" | "cc" | "cs" | "csharp" | "scala"
+        | "dart" | "php" => Syntax {
+            keywords: C_LIKE,
+            line_comment: &["//"],
+            block_comment: Some(("/*", "*/")),
+            quotes: &['"', '\'', '`'],
+        },
+        "python" | "py" => Syntax {
+            keywords: PYTHON,
+            line_comment: &["#"],
+            block_comment: None,
+            quotes: &['"', '\''],
+        },
+        "ruby" | "rb" => Syntax {
+            keywords: RUBY,
+            line_comment: &["#"],
+            block_comment: None,
+            quotes: &['"', '\''],
+        },
+        "sh" | "bash" | "zsh" | "shell" | "console" => Syntax {
+            keywords: SHELL,
+            line_comment: &["#"],
+            block_comment: None,
+            quotes: &['"', '\''],
+        },
+        "sql" => Syntax {
+            keywords: SQL,
+            line_comment: &["--"],
+            block_comment: Some(("/*", "*/")),
+            quotes: &['\''],
+        },
+        "toml" | "yaml" | "yml" | "ini" => Syntax {
+            keywords: &["true", "false", "null"],
+            line_comment: &["#"],
+            block_comment: None,
+            quotes: &['"', '\''],
+        },
+        "json" => Syntax {
+            keywords: &["true", "false", "null"],
+            line_comment: &[],
+            block_comment: None,
+            quotes: &['"'],
+        },
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// `code` as HTML, marked up for `lang` (a fence info string such as
+/// `rust` or `ts`)
+pub fn highlight(code: &str, lang: &str) -> String {
+    let Some(syntax) = syntax(lang) else {
+        return html::escape(code);
+    };
+    // SQL keywords are case-insensitive
+    let fold_case = lang.eq_ignore_ascii_case("sql");
+    let mut out = String::with_capacity(code.len() * 2);
+    let mut rest = code;
+    let span = |out: &mut String, class: &str, text: &str| {
+        out.push_str(&format!(
+            "<span class=\"hl-{class}\">{}</span>",
+            html::escape(text)
+        ));
+    };
+    while let Some(c) = rest.chars().next() {
+        if syntax.line_comment.iter().any(|m| rest.starts_with(m)) {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            span(&mut out, "comment", &rest[..end]);
+            rest = &rest[end..];
+        } else if let Some((open, close)) =
+            syntax.block_comment.filter(|(o, _)| rest.starts_with(o))
+        {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |at| open.len() + at + close.len());
+            span(&mut out, "comment", &rest[..end]);
+            rest = &rest[end..];
+        } else if syntax.quotes.contains(&c) {
+            let mut end = rest.len();
+            let mut escaped = false;
+            for (i, ch) in rest.char_indices().skip(1) {
+                match ch {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '\n' if c != '`' => {
+                        end = i;
+                        break;
+                    }
+                    ch if ch == c => {
+                        end = i + 1;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            span(&mut out, "string", &rest[..end]);
+            rest = &rest[end..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'))
+                .unwrap_or(rest.len());
+            span(&mut out, "number", &rest[..end]);
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let keyword = if fold_case {
+                syntax
+                    .keywords
+                    .contains(&word.to_ascii_lowercase().as_str())
+            } else {
+                syntax.keywords.contains(&word)
+            };
+            if keyword {
+                span(&mut out, "keyword", word);
+            } else {
+                out.push_str(&html::escape(word));
+            }
+            rest = &rest[end..];
+        } else {
+            out.push_str(&html::escape(&rest[..c.len_utf8()]));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(
+            highlight("let n = 42; // \"answer\"", "rust"),
+            "<span class=\"hl-keyword\">let</span> n = <span class=\"hl-number\">42</span>; \
+             <span class=\"hl-comment\">// &quot;answer&quot;</span>"
+        );
+        assert_eq!(
+            highlight("print('a\\'b') # <done>", "py"),
+            "print(<span class=\"hl-string\">'a\\'b'</span>) \
+             <span class=\"hl-comment\"># &lt;done&gt;</span>"
+        );
+        assert_eq!(
+            highlight("SELECT x FROM t", "sql"),
+            "<span class=\"hl-keyword\">SELECT</span> x <span class=\"hl-keyword\">FROM</span> t"
+        );
+        assert_eq!(highlight("a < b", "brainfuck"), "a &lt; b");
+    }
+}
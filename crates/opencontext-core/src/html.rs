@@ -0,0 +1,728 @@
+//! HTML to Markdown
+//!
+//! Enough of a converter for exported wiki pages: headings, paragraphs,
+//! emphasis, links, images, lists, quotes, code blocks and simple tables
+//! survive; other tags are dropped and their text kept. It does not build a
+//! DOM, so badly nested markup comes out as well as it went in.
+//!
+//! The same tag scanner backs [`sanitize`], which cleans the raw HTML of
+//! notes before it is rendered.
+
+/// Markdown for `html`
+pub fn to_markdown(html: &str) -> String {
+    let mut converter = Converter::default();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        converter.text(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let end = tag_end(rest);
+        let tag = Tag::parse(&rest[..end]);
+        rest = &rest[end..];
+        match tag {
+            Some(tag) if !tag.closing && SKIPPED.contains(&tag.name.as_str()) => {
+                let close = format!("</{}", tag.name);
+                rest = find_ignore_case(rest, &close)
+                    .map_or("", |at| &rest[at + tag_end(&rest[at..])..]);
+            }
+            Some(tag) => converter.tag(&tag),
+            None => {}
+        }
+    }
+    converter.text(rest);
+    converter.finish()
+}
+
+/// Inner HTML of the first element whose start tag contains `marker`
+/// (e.g. `id="main-content"`), up to its matching end tag
+pub fn element<'a>(html: &'a str, marker: &str) -> Option<&'a str> {
+    let at = html.find(marker)?;
+    let open = html[..at + 1].rfind('<')?;
+    let tag = Tag::parse(&html[open..open + tag_end(&html[open..])])?;
+    let inner_start = open + tag_end(&html[open..]);
+    let mut depth = 1;
+    let mut pos = inner_start;
+    while let Some(next) = html[pos..].find('<') {
+        let tag_start = pos + next;
+        let tag_len = tag_end(&html[tag_start..]);
+        if let Some(inner) = Tag::parse(&html[tag_start..tag_start + tag_len]) {
+            if inner.name == tag.name && !inner.self_closing {
+                depth += if inner.closing { -1 } else { 1 };
+                if depth == 0 {
+                    return Some(&html[inner_start..tag_start]);
+                }
+            }
+        }
+        pos = tag_start + tag_len.max(1);
+    }
+    Some(&html[inner_start..])
+}
+
+/// `(href, text)` of every link in `html`
+pub fn links(html: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut rest = html;
+    while let Some(start) = find_ignore_case(rest, "<a ") {
+        rest = &rest[start..];
+        let end = tag_end(rest);
+        let href = Tag::parse(&rest[..end]).and_then(|t| t.attr("href"));
+        rest = &rest[end..];
+        let text_end = find_ignore_case(rest, "</a").unwrap_or(rest.len());
+        if let Some(href) = href {
+            out.push((href, to_markdown(&rest[..text_end]).trim().to_string()));
+        }
+        rest = &rest[text_end..];
+    }
+    out
+}
+
+/// `html` with every `name` element tag replaced by what `replace` returns
+/// for it; end tags are passed as well, with [`Tag::closing`] set
+pub fn replace_tags(html: &str, name: &str, mut replace: impl FnMut(&Tag) -> String) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    let open = format!("<{name}");
+    let close = format!("</{name}");
+    while let Some(start) = [&open, &close]
+        .iter()
+        .filter_map(|needle| find_ignore_case(rest, needle))
+        .min()
+    {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = tag_end(rest);
+        match Tag::parse(&rest[..end]).filter(|tag| tag.name == name) {
+            Some(tag) => out.push_str(&replace(&tag)),
+            None => out.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Text of `html` with character references decoded and whitespace
+/// collapsed
+pub fn to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        rest = &rest[start + tag_end(&rest[start..])..];
+    }
+    text.push_str(rest);
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decode `&amp;`-style and numeric character references
+pub fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| {
+                let name = &rest[1..end + 1];
+                let c = match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "ndash" => Some('–'),
+                    "mdash" => Some('—'),
+                    "hellip" => Some('…'),
+                    "rsquo" => Some('’'),
+                    "lsquo" => Some('‘'),
+                    "rdquo" => Some('”'),
+                    "ldquo" => Some('“'),
+                    _ => {
+                        let code = match name.strip_prefix('#') {
+                            Some(hex) if hex.starts_with(['x', 'X']) => {
+                                u32::from_str_radix(&hex[1..], 16).ok()
+                            }
+                            Some(dec) => dec.parse().ok(),
+                            None => None,
+                        };
+                        code.and_then(char::from_u32)
+                    }
+                }?;
+                Some((c, end + 2))
+            });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `s` with `& < > "` escaped, for text and attribute values
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Elements [`sanitize`] keeps, with the attributes it keeps on them
+const ALLOWED: &[(&str, &[&str])] = &[
+    ("a", &["href", "title"]),
+    ("abbr", &["title"]),
+    ("b", &[]),
+    ("blockquote", &[]),
+    ("br", &[]),
+    ("code", &[]),
+    ("dd", &[]),
+    ("del", &[]),
+    ("details", &[]),
+    ("div", &[]),
+    ("dl", &[]),
+    ("dt", &[]),
+    ("em", &[]),
+    ("figcaption", &[]),
+    ("figure", &[]),
+    ("h1", &[]),
+    ("h2", &[]),
+    ("h3", &[]),
+    ("h4", &[]),
+    ("h5", &[]),
+    ("h6", &[]),
+    ("hr", &[]),
+    ("i", &[]),
+    ("img", &["src", "alt", "title", "width", "height"]),
+    ("ins", &[]),
+    ("kbd", &[]),
+    ("li", &[]),
+    ("mark", &[]),
+    ("ol", &["start"]),
+    ("p", &[]),
+    ("pre", &[]),
+    ("s", &[]),
+    ("small", &[]),
+    ("span", &[]),
+    ("strong", &[]),
+    ("sub", &[]),
+    ("summary", &[]),
+    ("sup", &[]),
+    ("table", &[]),
+    ("tbody", &[]),
+    ("td", &["colspan", "rowspan", "align"]),
+    ("th", &["colspan", "rowspan", "align"]),
+    ("thead", &[]),
+    ("tr", &[]),
+    ("u", &[]),
+    ("ul", &[]),
+];
+
+/// Whether `url` is safe in an `href` or `src`: no `javascript:` and the
+/// like, and `data:` only for images
+pub fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            matches!(scheme, "http" | "https" | "mailto" | "oc") || url.starts_with("data:image/")
+        }
+        _ => true,
+    }
+}
+
+/// `html` with only common formatting elements left: scripts, styles,
+/// forms, frames, event handlers and `javascript:` URLs are removed, and
+/// the content of `<script>`-like elements with them. For raw HTML written
+/// inside Markdown notes.
+pub fn sanitize(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+        let end = tag_end(rest);
+        let Some(tag) = Tag::parse(&rest[..end]) else {
+            out.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[end..];
+        if !tag.closing && SKIPPED.contains(&tag.name.as_str()) {
+            let close = format!("</{}", tag.name);
+            rest =
+                find_ignore_case(rest, &close).map_or("", |at| &rest[at + tag_end(&rest[at..])..]);
+            continue;
+        }
+        let Some((name, attrs)) = ALLOWED.iter().find(|(name, _)| *name == tag.name) else {
+            continue;
+        };
+        if tag.closing {
+            out.push_str(&format!("</{name}>"));
+            continue;
+        }
+        out.push('<');
+        out.push_str(name);
+        for attr in attrs.iter() {
+            let Some(value) = tag.attr(attr) else {
+                continue;
+            };
+            if matches!(*attr, "href" | "src") && !is_safe_url(&value) {
+                continue;
+            }
+            out.push_str(&format!(" {attr}=\"{}\"", escape(&value)));
+        }
+        out.push('>');
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Elements whose content is not text of the page
+const SKIPPED: &[&str] = &["head", "script", "style", "noscript", "template"];
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Length of the tag starting at `s[0] == '<'`, honouring quoted `>`
+fn tag_end(s: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// A start or end tag
+pub struct Tag {
+    /// Lowercase element name
+    pub name: String,
+    pub closing: bool,
+    self_closing: bool,
+    source: String,
+}
+
+impl Tag {
+    /// `None` for declarations and anything else that is not an element tag
+    fn parse(source: &str) -> Option<Tag> {
+        let inner = source.strip_prefix('<')?.trim_end_matches('>');
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, inner),
+        };
+        let name: String = inner
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | ':'))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        Some(Tag {
+            self_closing: inner.ends_with('/')
+                || matches!(name.as_str(), "br" | "hr" | "img" | "input"),
+            name,
+            closing,
+            source: inner.to_string(),
+        })
+    }
+
+    /// Decoded value of attribute `name`
+    pub fn attr(&self, name: &str) -> Option<String> {
+        let mut rest = self.source.as_str();
+        while let Some(at) = find_ignore_case(rest, name) {
+            let before = rest[..at].chars().last();
+            let after = rest[at + name.len()..].trim_start();
+            rest = &rest[at + name.len()..];
+            if !before.is_some_and(char::is_whitespace) {
+                continue;
+            }
+            let Some(value) = after.strip_prefix('=') else {
+                continue;
+            };
+            let value = value.trim_start();
+            let raw = match value.chars().next() {
+                Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or(""),
+                _ => value
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or(""),
+            };
+            return Some(decode_entities(raw));
+        }
+        None
+    }
+}
+
+#[derive(Default)]
+struct Table {
+    rows: usize,
+    cells: Vec<String>,
+    cell_start: Option<usize>,
+}
+
+#[derive(Default)]
+struct Converter {
+    out: String,
+    /// Open lists: `None` for bullets, `Some(n)` for the next number
+    lists: Vec<Option<usize>>,
+    /// Hrefs of open links
+    links: Vec<Option<String>>,
+    /// Output positions where open blockquotes began
+    quotes: Vec<usize>,
+    pre: bool,
+    table: Option<Table>,
+}
+
+impl Converter {
+    fn text(&mut self, raw: &str) {
+        if raw.is_empty() {
+            return;
+        }
+        let text = decode_entities(raw);
+        if self.pre {
+            self.out.push_str(&text);
+            return;
+        }
+        let starts_with_space = text.starts_with(char::is_whitespace);
+        let ends_with_space = text.ends_with(char::is_whitespace);
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() {
+            if starts_with_space {
+                self.space();
+            }
+            return;
+        }
+        if starts_with_space {
+            self.space();
+        }
+        self.out.push_str(&words.join(" "));
+        if ends_with_space {
+            self.out.push(' ');
+        }
+    }
+
+    fn space(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with([' ', '\n', '(', '[']) {
+            self.out.push(' ');
+        }
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+    }
+
+    /// Start a new paragraph-level block
+    fn block(&mut self) {
+        if self.table.as_ref().is_some_and(|t| t.cell_start.is_some()) {
+            self.space();
+            return;
+        }
+        if !self.lists.is_empty() {
+            self.trim_trailing_spaces();
+            return;
+        }
+        self.trim_trailing_spaces();
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn newline(&mut self) {
+        self.trim_trailing_spaces();
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        let name = tag.name.as_str();
+        match (name, tag.closing) {
+            ("p" | "div" | "section" | "article" | "header" | "footer" | "main", _) => self.block(),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.block();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => self.block(),
+            ("br", _) => {
+                if self.table.as_ref().is_some_and(|t| t.cell_start.is_some()) {
+                    self.space();
+                } else if self.pre {
+                    self.out.push('\n');
+                } else {
+                    self.trim_trailing_spaces();
+                    self.out.push_str("  \n");
+                }
+            }
+            ("hr", _) => {
+                self.block();
+                self.out.push_str("---");
+                self.block();
+            }
+            ("strong" | "b", _) => self.out.push_str("**"),
+            ("em" | "i", _) => self.out.push('*'),
+            ("del" | "s" | "strike", _) => self.out.push_str("~~"),
+            ("code" | "tt", _) if !self.pre => self.out.push('`'),
+            ("pre", false) => {
+                self.block();
+                self.out.push_str("```\n");
+                self.pre = true;
+            }
+            ("pre", true) => {
+                self.pre = false;
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str("```");
+                self.block();
+            }
+            ("a", false) => {
+                let href = tag.attr("href").filter(|h| !h.is_empty());
+                if href.is_some() {
+                    self.out.push('[');
+                }
+                self.links.push(href);
+            }
+            ("a", true) => {
+                if let Some(href) = self.links.pop().flatten() {
+                    self.trim_trailing_spaces();
+                    self.out.push_str(&format!("]({href})"));
+                }
+            }
+            ("input", false) if tag.attr("type").as_deref() == Some("checkbox") => {
+                let mark = if tag.attr("checked").is_some() || tag.source.contains(" checked") {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                if self.out.is_empty() || self.out.ends_with('\n') {
+                    self.out.push_str("- ");
+                }
+                self.out.push_str(mark);
+            }
+            ("img", _) => {
+                if let Some(src) = tag.attr("src") {
+                    let alt = tag.attr("alt").unwrap_or_default();
+                    self.out.push_str(&format!("![{alt}]({src})"));
+                }
+            }
+            ("ul", false) => {
+                self.newline();
+                self.lists.push(None);
+            }
+            ("ol", false) => {
+                self.newline();
+                self.lists.push(Some(1));
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                }
+            }
+            ("li", false) => {
+                self.newline();
+                let depth = self.lists.len().max(1);
+                self.out.push_str(&"  ".repeat(depth - 1));
+                match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        self.out.push_str(&format!("{n}. "));
+                        *n += 1;
+                    }
+                    _ => self.out.push_str("- "),
+                }
+            }
+            ("li", true) => self.trim_trailing_spaces(),
+            ("blockquote", false) => {
+                self.block();
+                self.quotes.push(self.out.len());
+            }
+            ("blockquote", true) => {
+                if let Some(start) = self.quotes.pop() {
+                    let quoted: Vec<String> = self.out[start..]
+                        .trim_end()
+                        .lines()
+                        .map(|line| format!("> {line}").trim_end().to_string())
+                        .collect();
+                    self.out.truncate(start);
+                    self.out.push_str(&quoted.join("\n"));
+                    self.block();
+                }
+            }
+            ("table", false) => {
+                self.block();
+                self.table = Some(Table::default());
+            }
+            ("table", true) => {
+                self.table = None;
+                self.block();
+            }
+            ("td" | "th", false) => {
+                let start = self.out.len();
+                if let Some(table) = &mut self.table {
+                    table.cell_start = Some(start);
+                }
+            }
+            ("td" | "th", true) => {
+                let start = self.table.as_mut().and_then(|t| t.cell_start.take());
+                if let (Some(start), Some(table)) = (start, &mut self.table) {
+                    let cell = self.out[start..].trim().replace('|', "\\|");
+                    self.out.truncate(start);
+                    table.cells.push(cell);
+                }
+            }
+            ("tr", true) => {
+                if let Some(table) = &mut self.table {
+                    if table.cells.is_empty() {
+                        return;
+                    }
+                    let cells = std::mem::take(&mut table.cells);
+                    let mut row = format!("| {} |\n", cells.join(" | "));
+                    if table.rows == 0 {
+                        row.push_str(&format!("|{}\n", " --- |".repeat(cells.len())));
+                    }
+                    table.rows += 1;
+                    let trimmed = self.out.trim_end().len();
+                    self.out.truncate(trimmed);
+                    if !self.out.is_empty() {
+                        self.out
+                            .push_str(if table.rows == 1 { "\n\n" } else { "\n" });
+                    }
+                    self.out.push_str(row.trim_end());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::with_capacity(self.out.len());
+        let mut blank_lines = 0;
+        for line in self.out.trim().lines() {
+            let line = if line.trim().is_empty() { "" } else { line };
+            if line.is_empty() {
+                blank_lines += 1;
+                if blank_lines > 1 {
+                    continue;
+                }
+            } else {
+                blank_lines = 0;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_markdown() {
+        let html = r#"<html><head><title>x</title><style>p{}</style></head><body>
+            <h2>Release   plan</h2>
+            <p>Ship <strong>2.3</strong> once <a href="https://ci/123">CI</a> is &amp; stays green.</p>
+            <ul><li>Sign <em>binaries</em></li><li>Notes<ol><li>Draft</li><li>Review</li></ol></li></ul>
+            <!-- hidden -->
+            <blockquote><p>Don't ship on Fridays.</p></blockquote>
+            <pre>fn main() {
+    run();
+}</pre>
+            <table><tr><th>Task</th><th>Owner</th></tr><tr><td>Cert</td><td>Ada</td></tr></table>
+            <img src="diagram.png" alt="Flow">
+        </body></html>"#;
+        assert_eq!(
+            to_markdown(html),
+            "## Release plan\n\nShip **2.3** once [CI](https://ci/123) is & stays green.\n\n- Sign *binaries*\n- Notes\n  1. Draft\n  2. Review\n\n> Don't ship on Fridays.\n\n```\nfn main() {\n    run();\n}\n```\n\n| Task | Owner |\n| --- | --- |\n| Cert | Ada |\n\n![Flow](diagram.png)\n"
+        );
+    }
+
+    #[test]
+    fn test_element_and_links() {
+        let html = r#"<div id="outer"><div id="main-content"><div>a</div><a href='b.html'>B &amp; C</a></div><div>after</div></div>"#;
+        let main = element(html, r#"id="main-content""#).unwrap();
+        assert_eq!(main, r#"<div>a</div><a href='b.html'>B &amp; C</a>"#);
+        assert_eq!(
+            links(main),
+            vec![("b.html".to_string(), "B & C".to_string())]
+        );
+        assert_eq!(to_text(main), "a B & C");
+        assert_eq!(
+            replace_tags(
+                "<p><en-todo checked=\"true\"/>Call <EN-TODO/></p>",
+                "en-todo",
+                |tag| { format!("[{}]", tag.attr("checked").unwrap_or_default()) }
+            ),
+            "<p>[true]Call []</p>"
+        );
+        assert_eq!(
+            to_markdown(
+                r#"<div><input type="checkbox" checked>Pack</div><div><input type="checkbox"> Ship</div>"#
+            ),
+            "- [x] Pack\n\n- [ ] Ship\n"
+        );
+        assert_eq!(decode_entities("&#x41;&#66;&bogus; &"), "AB&bogus; &");
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            sanitize(
+                "<p onclick=\"x()\">Hi <a href=\"javascript:alert(1)\">there</a>\
+                 <script>alert(1)</script><!-- note --><img src=\"a.png\" onerror=\"x\"></p>"
+            ),
+            "<p>Hi <a>there</a><img src=\"a.png\"></p>"
+        );
+        assert_eq!(sanitize("1 < 2 <iframe src=x></iframe>"), "1 &lt; 2 ");
+        assert!(is_safe_url("notes/a b.md#x"));
+        assert!(!is_safe_url(" JavaScript:alert(1)"));
+        assert!(is_safe_url("data:image/png;base64,AAAA"));
+    }
+}
@@ -0,0 +1,80 @@
+//! Idea entries
+//!
+//! Idea boxes are documents under `.ideas/` holding many short entries, each
+//! introduced by an invisible marker line:
+//!
+//! ```text
+//! [//]: # (idea:id=<id> created_at=<timestamp>)
+//! ```
+
+use serde::Serialize;
+
+/// Folder that holds idea boxes
+pub const IDEAS_DIR: &str = ".ideas/";
+
+/// One entry of an idea box
+#[derive(Debug, Clone, Serialize)]
+pub struct IdeaEntry {
+    pub id: String,
+    pub created_at: String,
+    pub content: String,
+}
+
+/// `(id, created_at)` of an entry marker line
+pub fn parse_idea_marker(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim();
+    if !trimmed.starts_with("[//]: # (") || !trimmed.ends_with(')') {
+        return None;
+    }
+    let inner = trimmed.strip_prefix("[//]: # (")?.strip_suffix(')')?;
+    let mut id = None;
+    let mut created_at = None;
+    for part in inner.split_whitespace() {
+        if let Some(value) = part.strip_prefix("idea:id=") {
+            id = Some(value.to_string());
+        } else if let Some(value) = part.strip_prefix("created_at=") {
+            created_at = Some(value.to_string());
+        }
+    }
+    match (id, created_at) {
+        (Some(id), Some(created_at)) => Some((id, created_at)),
+        _ => None,
+    }
+}
+
+/// Entries of an idea box document, in file order
+pub fn parse_idea_entries(content: &str) -> Vec<IdeaEntry> {
+    let mut entries = Vec::new();
+    let mut current_id: Option<String> = None;
+    let mut current_created: Option<String> = None;
+    let mut buffer: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        if let Some((id, created_at)) = parse_idea_marker(line) {
+            if let (Some(id), Some(created_at)) = (current_id.take(), current_created.take()) {
+                entries.push(IdeaEntry {
+                    id,
+                    created_at,
+                    content: buffer.join("\n").trim().to_string(),
+                });
+            }
+            buffer.clear();
+            current_id = Some(id);
+            current_created = Some(created_at);
+            continue;
+        }
+        if current_id.is_some() {
+            buffer.push(line.to_string());
+        }
+    }
+
+    if let (Some(id), Some(created_at)) = (current_id.take(), current_created.take()) {
+        entries.push(IdeaEntry {
+            id,
+            created_at,
+            content: buffer.join("\n").trim().to_string(),
+        });
+    }
+
+    entries
+}
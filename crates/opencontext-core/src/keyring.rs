@@ -0,0 +1,90 @@
+//! System keyring
+//!
+//! API tokens of sources can be kept in the operating system's credential
+//! store instead of config.toml: the login keychain on macOS (through
+//! `security`) and the Secret Service on Linux (through libsecret's
+//! `secret-tool`). Entries are stored under service [`SERVICE`] with an
+//! account naming what the secret is for, e.g. `jira` or `linear`. Other
+//! platforms have no keyring support; tokens go into config or environment
+//! there.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::{CoreError, CoreResult};
+
+/// Service name of every OpenContext keyring entry
+pub const SERVICE: &str = "opencontext";
+
+/// Secret stored for `account`, if there is a keyring and it has one
+pub fn get(account: &str) -> Option<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"])
+            .stderr(Stdio::null())
+            .output()
+    } else if cfg!(target_os = "linux") {
+        Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "account", account])
+            .stderr(Stdio::null())
+            .output()
+    } else {
+        return None;
+    };
+    let output = output.ok().filter(|o| o.status.success())?;
+    let secret = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!secret.is_empty()).then_some(secret)
+}
+
+/// Store `secret` for `account`, replacing an existing entry
+pub fn set(account: &str, secret: &str) -> CoreResult<()> {
+    let secret = secret.trim();
+    if account.trim().is_empty() || secret.is_empty() {
+        return Err(CoreError::Message(
+            "keyring account and secret must not be empty".into(),
+        ));
+    }
+    let (tool, status) = if cfg!(target_os = "macos") {
+        let status = Command::new("security")
+            .args([
+                "add-generic-password",
+                "-U",
+                "-s",
+                SERVICE,
+                "-a",
+                account,
+                "-w",
+                secret,
+            ])
+            .stdout(Stdio::null())
+            .status();
+        ("security", status)
+    } else if cfg!(target_os = "linux") {
+        // The secret goes over stdin so it never shows up in `ps`
+        let label = format!("OpenContext {account}");
+        let status = Command::new("secret-tool")
+            .args([
+                "store", "--label", &label, "service", SERVICE, "account", account,
+            ])
+            .stdin(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(secret.as_bytes())?;
+                }
+                child.wait()
+            });
+        ("secret-tool", status)
+    } else {
+        return Err(CoreError::Message(
+            "no system keyring on this platform; put the token in config.toml".into(),
+        ));
+    };
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(CoreError::Message(format!(
+            "{tool} could not store the secret ({status})"
+        ))),
+        Err(e) => Err(CoreError::Message(format!("could not run {tool}: {e}"))),
+    }
+}
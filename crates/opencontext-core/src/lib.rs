@@ -3,8 +3,36 @@
 #[cfg(test)]
 mod tests;
 
+pub mod acl;
+pub mod bibtex;
+pub mod blocks;
+pub mod capture;
+pub mod dates;
+pub mod digest;
+pub mod edit;
+pub mod embeds;
+pub mod flashcards;
+pub mod folders;
+pub mod frontmatter;
+pub mod git_history;
+pub mod highlight;
+pub mod html;
+pub mod ideas;
+pub mod keyring;
+pub mod links;
+pub mod manifest;
+pub mod memory;
+pub mod merge;
 pub mod migrations;
+pub mod names;
+pub mod neighborhood;
+pub mod relpath;
+pub mod resurface;
+pub mod scan;
+pub mod staleness;
+pub mod tasks;
 pub mod wal;
+pub mod wiki_import;
 
 use chrono::{SecondsFormat, Utc};
 use parking_lot::Mutex;
@@ -16,15 +44,49 @@ use std::{
 };
 use thiserror::Error;
 
-// Events module (enabled with "search" feature)
-#[cfg(feature = "search")]
+use acl::{Access, AclConfig};
+pub use folders::FolderManager;
+pub use relpath::RelPath;
+pub(crate) use scan::scan_md_files;
+pub use scan::SymlinkPolicy;
+
+// Events module (enabled with "keyword" feature)
+#[cfg(feature = "keyword")]
 pub mod events;
 
-// Search module (enabled with "search" feature)
-#[cfg(feature = "search")]
+// Search module ("keyword" for BM25 only, "search" for the full engine)
+#[cfg(feature = "keyword")]
 pub mod search;
 
+// Shell history import (needs the regex engine)
+#[cfg(feature = "keyword")]
+pub mod sessions;
+
+// Markdown to HTML for previews and static sites (pulldown-cmark)
+#[cfg(feature = "keyword")]
+pub mod render;
+
+// Static site publishing
+#[cfg(feature = "keyword")]
+pub mod publish;
+
+// Workspace archives (enabled with "search" feature)
+#[cfg(feature = "search")]
+pub mod archive;
+
+// Anki deck export (zip and SHA-1 for the .apkg format)
+#[cfg(feature = "search")]
+pub mod anki;
+
+// Evernote / Apple Notes import (base64 and MD5 for attachments)
+#[cfg(feature = "search")]
+pub mod notes_import;
+
+// Outside systems synced into the workspace (HTTP clients)
 #[cfg(feature = "search")]
+pub mod sources;
+
+#[cfg(feature = "keyword")]
 use events::{DocEvent, FolderEvent, SharedEventBus};
 
 #[derive(Debug, Error)]
@@ -35,16 +97,30 @@ pub enum CoreError {
     Db(#[from] rusqlite::Error),
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
+    /// A mutation was attempted on a read-only workspace
+    #[error("workspace is read-only: cannot {0}")]
+    ReadOnly(&'static str),
+    /// The document changed on disk since the revision the caller edited
+    #[error("conflict: {rel_path} changed since revision {expected} (now {actual})")]
+    Conflict {
+        rel_path: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 pub type CoreResult<T> = Result<T, CoreError>;
 
 #[derive(Clone)]
 pub struct OpenContext {
+    base_root: PathBuf,
     contexts_root: PathBuf,
     db_path: PathBuf,
     conn: Arc<Mutex<Connection>>,
-    #[cfg(feature = "search")]
+    read_only: bool,
+    acl: Arc<AclConfig>,
+    symlinks: SymlinkPolicy,
+    #[cfg(feature = "keyword")]
     event_bus: Option<SharedEventBus>,
 }
 
@@ -53,6 +129,12 @@ pub struct EnvOverrides {
     pub base_root: Option<PathBuf>,
     pub contexts_root: Option<PathBuf>,
     pub db_path: Option<PathBuf>,
+    /// Reject every folder / document mutation (defaults to `OPENCONTEXT_READ_ONLY`)
+    pub read_only: Option<bool>,
+    /// Access rules file (defaults to `OPENCONTEXT_ACL_PATH`, then `<root>/acl.json`)
+    pub acl_path: Option<PathBuf>,
+    /// How scans treat symlinked files and folders (defaults to `OPENCONTEXT_SYMLINKS`)
+    pub symlinks: Option<SymlinkPolicy>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -106,6 +188,26 @@ pub struct ManifestResult {
     pub unindexed_files: Vec<String>,
 }
 
+/// Contents returned by one [`get_documents`](OpenContext::get_documents)
+/// call unless the caller asks for another limit
+pub const DEFAULT_BATCH_BYTES: usize = 256 * 1024;
+
+/// One document of a [`get_documents`](OpenContext::get_documents) batch
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FetchedDoc {
+    /// Path as requested
+    pub doc_path: String,
+    /// Metadata, when the document was found
+    pub doc: Option<Doc>,
+    pub content: Option<String>,
+    /// Revision of `content`
+    pub revision: Option<String>,
+    /// Why `content` is missing
+    pub error: Option<String>,
+    /// Left out because it did not fit in the batch's size limit
+    pub over_limit: bool,
+}
+
 impl OpenContext {
     pub fn initialize(overrides: EnvOverrides) -> CoreResult<Self> {
         let base_root = overrides
@@ -121,6 +223,22 @@ impl OpenContext {
             .db_path
             .or_else(|| env_path("OPENCONTEXT_DB_PATH"))
             .unwrap_or_else(|| base_root.join("opencontext.db"));
+        let read_only = overrides.read_only.unwrap_or_else(|| {
+            env::var("OPENCONTEXT_READ_ONLY")
+                .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false)
+        });
+        let acl_path = overrides
+            .acl_path
+            .or_else(|| env_path("OPENCONTEXT_ACL_PATH"))
+            .unwrap_or_else(|| base_root.join("acl.json"));
+        let acl = AclConfig::load(&acl_path)?;
+        let symlinks = match overrides.symlinks {
+            Some(policy) => policy,
+            None => env::var("OPENCONTEXT_SYMLINKS")
+                .map(|v| v.parse())
+                .unwrap_or(Ok(SymlinkPolicy::default()))?,
+        };
 
         fs::create_dir_all(&contexts_root)?;
         if let Some(parent) = db_path.parent() {
@@ -132,29 +250,55 @@ impl OpenContext {
         migrations::run(&conn)?;
 
         Ok(Self {
+            base_root,
             contexts_root,
             db_path,
             conn: Arc::new(Mutex::new(conn)),
-            #[cfg(feature = "search")]
+            read_only,
+            acl: Arc::new(acl),
+            symlinks,
+            #[cfg(feature = "keyword")]
             event_bus: None,
         })
     }
 
+    /// Whether folder and document mutations are rejected
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// How scans of the contexts root treat symlinks
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+
+    /// What a caller presenting `token` may read; `None` if it is refused
+    pub fn access_for(&self, token: Option<&str>) -> Option<Access> {
+        self.acl.access_for(token)
+    }
+
+    fn ensure_writable(&self, action: &'static str) -> CoreResult<()> {
+        if self.read_only {
+            return Err(CoreError::ReadOnly(action));
+        }
+        Ok(())
+    }
+
     /// Set the event bus for this context
-    #[cfg(feature = "search")]
+    #[cfg(feature = "keyword")]
     pub fn with_event_bus(mut self, event_bus: SharedEventBus) -> Self {
         self.event_bus = Some(event_bus);
         self
     }
 
     /// Get the event bus
-    #[cfg(feature = "search")]
+    #[cfg(feature = "keyword")]
     pub fn event_bus(&self) -> Option<&SharedEventBus> {
         self.event_bus.as_ref()
     }
 
     /// Emit a document event
-    #[cfg(feature = "search")]
+    #[cfg(feature = "keyword")]
     fn emit_doc_event(&self, event: DocEvent) {
         if let Some(ref bus) = self.event_bus {
             bus.emit_doc(event);
@@ -162,7 +306,7 @@ impl OpenContext {
     }
 
     /// Emit a folder event
-    #[cfg(feature = "search")]
+    #[cfg(feature = "keyword")]
     fn emit_folder_event(&self, event: FolderEvent) {
         if let Some(ref bus) = self.event_bus {
             bus.emit_folder(event);
@@ -216,6 +360,11 @@ impl OpenContext {
         }
     }
 
+    /// Native filesystem path of a workspace-relative path
+    pub(crate) fn abs_path(&self, rel_path: &str) -> PathBuf {
+        RelPath::new(rel_path).to_path(&self.contexts_root)
+    }
+
     pub fn list_folders(&self, all: bool) -> CoreResult<Vec<Folder>> {
         self.with_conn(|conn| {
             let query = if all {
@@ -236,6 +385,7 @@ impl OpenContext {
         path: &str,
         description: Option<&str>,
     ) -> CoreResult<FolderSummary> {
+        self.ensure_writable("create folders")?;
         let rel_path = normalize_folder_path(Some(path))?;
         if rel_path.is_empty() {
             return Err(CoreError::Message(
@@ -251,17 +401,13 @@ impl OpenContext {
             self.update_folder_description(&rel_path, description.unwrap_or(""))?;
             return Ok(FolderSummary {
                 rel_path: rel_path.clone(),
-                abs_path: self.contexts_root.join(&rel_path),
+                abs_path: self.abs_path(&rel_path),
                 description: description.unwrap_or("").to_string(),
             });
         }
         let ts = now_iso();
-        let name = rel_path
-            .split('/')
-            .next_back()
-            .unwrap_or(&rel_path)
-            .to_string();
-        let abs_path = self.contexts_root.join(&rel_path);
+        let name = relpath::file_name(&rel_path).to_string();
+        let abs_path = self.abs_path(&rel_path);
         fs::create_dir_all(&abs_path)?;
         self.with_conn(|conn| {
             conn.execute(
@@ -280,6 +426,10 @@ impl OpenContext {
             )?;
             Ok(())
         })?;
+        #[cfg(feature = "keyword")]
+        self.emit_folder_event(FolderEvent::Created {
+            rel_path: rel_path.as_str().into(),
+        });
         Ok(FolderSummary {
             rel_path,
             abs_path,
@@ -288,325 +438,15 @@ impl OpenContext {
     }
 
     pub fn rename_folder(&self, path: &str, new_name: &str) -> CoreResult<RenameResult> {
-        let rel_path = normalize_folder_path(Some(path))?;
-        if rel_path.is_empty() {
-            return Err(CoreError::Message(
-                "Cannot rename the root contexts directory.".into(),
-            ));
-        }
-        if new_name.is_empty() || new_name.contains('/') {
-            return Err(CoreError::Message(
-                "New name must be a single path segment.".into(),
-            ));
-        }
-        let folder = self
-            .find_folder(&rel_path)?
-            .ok_or_else(|| folder_not_found(&rel_path))?;
-        let parent_path = parent_rel_path(&rel_path);
-        let new_rel_path = if let Some(parent) = parent_path.as_deref() {
-            if parent.is_empty() {
-                new_name.to_string()
-            } else {
-                format!("{parent}/{new_name}")
-            }
-        } else {
-            new_name.to_string()
-        };
-        if self.find_folder(&new_rel_path)?.is_some() {
-            return Err(CoreError::Message(format!(
-                "Target folder \"{new_rel_path}\" already exists."
-            )));
-        }
-        let new_abs_path = self.contexts_root.join(&new_rel_path);
-        if let Some(parent) = new_abs_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::rename(&folder.abs_path, &new_abs_path)?;
-        let ts = now_iso();
-
-        // Collect affected doc paths before the transaction (for event emission)
-        #[cfg(feature = "search")]
-        let affected_doc_paths: Vec<String> = self.with_conn(|conn| {
-            let like_pattern = format!("{}/%", folder.rel_path);
-            let mut stmt = conn.prepare("SELECT rel_path FROM docs WHERE rel_path LIKE ?1")?;
-            let paths = stmt
-                .query_map([like_pattern], |row| row.get::<_, String>(0))?
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(paths)
-        })?;
-
-        self.with_conn(|conn| {
-            let tx = conn.unchecked_transaction()?;
-            {
-                tx.execute(
-                    "UPDATE folders SET name = ?1, rel_path = ?2, abs_path = ?3, updated_at = ?4 WHERE id = ?5",
-                    params![new_name, new_rel_path, new_abs_path.to_string_lossy(), ts, folder.id],
-                )?;
-                let like_pattern = format!("{}/%", folder.rel_path);
-                let mut stmt = tx.prepare("SELECT id, rel_path FROM folders WHERE rel_path LIKE ?1")?;
-                let folder_rows = stmt
-                    .query_map([like_pattern.clone()], |row| {
-                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-                    })?
-                    .collect::<Result<Vec<_>, _>>()?;
-                for (id, child_rel) in folder_rows {
-                    let suffix = &child_rel[folder.rel_path.len() + 1..];
-                    let updated_rel = format!("{new_rel_path}/{suffix}");
-                    let updated_abs = self.contexts_root.join(&updated_rel);
-                    tx.execute(
-                        "UPDATE folders SET rel_path = ?1, abs_path = ?2, updated_at = ?3 WHERE id = ?4",
-                        params![updated_rel, updated_abs.to_string_lossy(), ts, id],
-                    )?;
-                }
-                let mut doc_stmt =
-                    tx.prepare("SELECT id, rel_path FROM docs WHERE rel_path LIKE ?1")?;
-                let doc_rows = doc_stmt
-                    .query_map([like_pattern], |row| {
-                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-                    })?
-                    .collect::<Result<Vec<_>, _>>()?;
-                for (id, doc_rel) in doc_rows {
-                    let suffix = &doc_rel[folder.rel_path.len() + 1..];
-                    let updated_rel = format!("{new_rel_path}/{suffix}");
-                    let updated_abs = self.contexts_root.join(&updated_rel);
-                    tx.execute(
-                        "UPDATE docs SET rel_path = ?1, abs_path = ?2, updated_at = ?3 WHERE id = ?4",
-                        params![updated_rel, updated_abs.to_string_lossy(), ts, id],
-                    )?;
-                }
-            }
-            tx.commit()?;
-            Ok(())
-        })?;
-
-        // Emit folder event with affected docs
-        #[cfg(feature = "search")]
-        {
-            let affected_docs: Vec<(String, String)> = affected_doc_paths
-                .into_iter()
-                .map(|old_path| {
-                    let suffix = &old_path[folder.rel_path.len() + 1..];
-                    let new_path = format!("{}/{}", new_rel_path, suffix);
-                    (old_path, new_path)
-                })
-                .collect();
-            self.emit_folder_event(FolderEvent::Renamed {
-                old_path: rel_path.clone(),
-                new_path: new_rel_path.clone(),
-                affected_docs,
-            });
-        }
-
-        Ok(RenameResult {
-            old_path: rel_path,
-            new_path: new_rel_path,
-        })
+        self.folders().rename(path, new_name)
     }
 
     pub fn move_folder(&self, path: &str, dest_folder_path: &str) -> CoreResult<RenameResult> {
-        let rel_path = normalize_folder_path(Some(path))?;
-        if rel_path.is_empty() {
-            return Err(CoreError::Message(
-                "Cannot move the root contexts directory.".into(),
-            ));
-        }
-        let dest_rel_folder = normalize_folder_path(Some(dest_folder_path))?;
-        if dest_rel_folder.is_empty() {
-            return Err(CoreError::Message(
-                "Root is not supported. Please move into a folder under contexts/.".into(),
-            ));
-        }
-        if dest_rel_folder == rel_path || dest_rel_folder.starts_with(&format!("{rel_path}/")) {
-            return Err(CoreError::Message(
-                "Cannot move a folder into itself or its descendants.".into(),
-            ));
-        }
-
-        let folder = self
-            .find_folder(&rel_path)?
-            .ok_or_else(|| folder_not_found(&rel_path))?;
-        let dest_folder = self
-            .find_folder(&dest_rel_folder)?
-            .ok_or_else(|| folder_not_found(&dest_rel_folder))?;
-
-        let new_rel_path = if dest_folder.rel_path.is_empty() {
-            folder.name.clone()
-        } else {
-            format!("{}/{}", dest_folder.rel_path, folder.name)
-        };
-        if self.find_folder(&new_rel_path)?.is_some() {
-            return Err(CoreError::Message(format!(
-                "Target folder \"{new_rel_path}\" already exists."
-            )));
-        }
-
-        let new_abs_path = self.contexts_root.join(&new_rel_path);
-        if let Some(parent) = new_abs_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::rename(&folder.abs_path, &new_abs_path)?;
-
-        let ts = now_iso();
-
-        // Collect affected doc paths before the transaction (for event emission)
-        #[cfg(feature = "search")]
-        let affected_doc_paths: Vec<String> = self.with_conn(|conn| {
-            let like_pattern = format!("{}/%", folder.rel_path);
-            let mut stmt = conn.prepare("SELECT rel_path FROM docs WHERE rel_path LIKE ?1")?;
-            let paths = stmt
-                .query_map([like_pattern], |row| row.get::<_, String>(0))?
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(paths)
-        })?;
-
-        self.with_conn(|conn| {
-            let tx = conn.unchecked_transaction()?;
-            {
-                tx.execute(
-                    "UPDATE folders SET parent_id = ?1, rel_path = ?2, abs_path = ?3, updated_at = ?4 WHERE id = ?5",
-                    params![
-                        dest_folder.id,
-                        new_rel_path,
-                        new_abs_path.to_string_lossy(),
-                        ts,
-                        folder.id
-                    ],
-                )?;
-
-                let like_pattern = format!("{}/%", folder.rel_path);
-                let mut stmt = tx.prepare("SELECT id, rel_path FROM folders WHERE rel_path LIKE ?1")?;
-                let folder_rows = stmt
-                    .query_map([like_pattern.clone()], |row| {
-                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-                    })?
-                    .collect::<Result<Vec<_>, _>>()?;
-                for (id, child_rel) in folder_rows {
-                    let suffix = &child_rel[folder.rel_path.len() + 1..];
-                    let updated_rel = format!("{new_rel_path}/{suffix}");
-                    let updated_abs = self.contexts_root.join(&updated_rel);
-                    tx.execute(
-                        "UPDATE folders SET rel_path = ?1, abs_path = ?2, updated_at = ?3 WHERE id = ?4",
-                        params![updated_rel, updated_abs.to_string_lossy(), ts, id],
-                    )?;
-                }
-
-                let mut doc_stmt =
-                    tx.prepare("SELECT id, rel_path FROM docs WHERE rel_path LIKE ?1")?;
-                let doc_rows = doc_stmt
-                    .query_map([like_pattern], |row| {
-                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-                    })?
-                    .collect::<Result<Vec<_>, _>>()?;
-                for (id, doc_rel) in doc_rows {
-                    let suffix = &doc_rel[folder.rel_path.len() + 1..];
-                    let updated_rel = format!("{new_rel_path}/{suffix}");
-                    let updated_abs = self.contexts_root.join(&updated_rel);
-                    tx.execute(
-                        "UPDATE docs SET rel_path = ?1, abs_path = ?2, updated_at = ?3 WHERE id = ?4",
-                        params![updated_rel, updated_abs.to_string_lossy(), ts, id],
-                    )?;
-                }
-            }
-            tx.commit()?;
-            Ok(())
-        })?;
-
-        // Emit folder event with affected docs
-        #[cfg(feature = "search")]
-        {
-            let affected_docs: Vec<(String, String)> = affected_doc_paths
-                .into_iter()
-                .map(|old_path| {
-                    let suffix = &old_path[folder.rel_path.len() + 1..];
-                    let new_path = format!("{}/{}", new_rel_path, suffix);
-                    (old_path, new_path)
-                })
-                .collect();
-            self.emit_folder_event(FolderEvent::Moved {
-                old_path: rel_path.clone(),
-                new_path: new_rel_path.clone(),
-                affected_docs,
-            });
-        }
-
-        Ok(RenameResult {
-            old_path: rel_path,
-            new_path: new_rel_path,
-        })
+        self.folders().move_to(path, dest_folder_path)
     }
 
     pub fn remove_folder(&self, path: &str, force: bool) -> CoreResult<Removed> {
-        let rel_path = normalize_folder_path(Some(path))?;
-        if rel_path.is_empty() {
-            return Err(CoreError::Message(
-                "Cannot remove the root contexts directory.".into(),
-            ));
-        }
-        let folder = self
-            .find_folder(&rel_path)?
-            .ok_or_else(|| folder_not_found(&rel_path))?;
-
-        // Collect documents to be removed (for event emission)
-        #[cfg(feature = "search")]
-        let removed_docs: Vec<String> = self.with_conn(|conn| {
-            let like_pattern = format!("{}/%", rel_path);
-            let mut stmt =
-                conn.prepare("SELECT rel_path FROM docs WHERE rel_path LIKE ?1 OR folder_id = ?2")?;
-            let paths = stmt
-                .query_map(params![like_pattern, folder.id], |row| {
-                    row.get::<_, String>(0)
-                })?
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(paths)
-        })?;
-
-        self.with_conn(|conn| {
-            let child_count: i64 = conn.query_row(
-                "SELECT COUNT(1) FROM folders WHERE parent_id = ?1",
-                params![folder.id],
-                |row| row.get(0),
-            )?;
-            let doc_count: i64 = conn.query_row(
-                "SELECT COUNT(1) FROM docs WHERE folder_id = ?1",
-                params![folder.id],
-                |row| row.get(0),
-            )?;
-            if !force && (child_count > 0 || doc_count > 0) {
-                return Err(CoreError::Message(format!(
-                    "Folder \"{rel_path}\" is not empty. Use --force to delete recursively."
-                )));
-            }
-            let like_pattern = format!("{rel_path}/%");
-            let tx = conn.unchecked_transaction()?;
-            tx.execute(
-                "DELETE FROM docs WHERE rel_path LIKE ?1",
-                params![like_pattern.clone()],
-            )?;
-            tx.execute(
-                "DELETE FROM folders WHERE rel_path LIKE ?1",
-                params![like_pattern.clone()],
-            )?;
-            tx.execute("DELETE FROM docs WHERE folder_id = ?1", params![folder.id])?;
-            tx.execute("DELETE FROM folders WHERE id = ?1", params![folder.id])?;
-            tx.commit()?;
-            Ok(())
-        })?;
-        if folder.abs_path.exists() {
-            if force {
-                fs::remove_dir_all(&folder.abs_path)?;
-            } else {
-                fs::remove_dir(&folder.abs_path)?;
-            }
-        }
-
-        // Emit folder deleted event
-        #[cfg(feature = "search")]
-        self.emit_folder_event(FolderEvent::Deleted {
-            rel_path: rel_path.clone(),
-            removed_docs,
-        });
-
-        Ok(Removed { rel_path })
+        self.folders().delete(path, force)
     }
 
     pub fn list_docs(&self, folder_path: &str, recursive: bool) -> CoreResult<Vec<Doc>> {
@@ -619,11 +459,11 @@ impl OpenContext {
                 let pattern = if folder.rel_path.is_empty() {
                     "%".to_string()
                 } else {
-                    format!("{}/%", folder.rel_path)
+                    like_children(&folder.rel_path)
                 };
                 let mut stmt = conn.prepare(
                     "SELECT id, folder_id, name, rel_path, abs_path, description, stable_id, created_at, updated_at
-                     FROM docs WHERE rel_path LIKE ?1 ORDER BY rel_path",
+                     FROM docs WHERE rel_path LIKE ?1 ESCAPE '\\' ORDER BY rel_path",
                 )?;
                 let rows = stmt
                     .query_map([pattern], row_to_doc)?
@@ -657,6 +497,7 @@ impl OpenContext {
         name: &str,
         description: Option<&str>,
     ) -> CoreResult<DocCreated> {
+        self.ensure_writable("create documents")?;
         if name.is_empty() {
             return Err(CoreError::Message("Document name is required.".into()));
         }
@@ -679,7 +520,7 @@ impl OpenContext {
                 "File \"{rel_path}\" already exists."
             )));
         }
-        let abs_path = self.contexts_root.join(&rel_path);
+        let abs_path = self.abs_path(&rel_path);
         if let Some(parent) = abs_path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -707,9 +548,9 @@ impl OpenContext {
         })?;
 
         // Emit event
-        #[cfg(feature = "search")]
+        #[cfg(feature = "keyword")]
         self.emit_doc_event(DocEvent::Created {
-            rel_path: rel_path.clone(),
+            rel_path: rel_path.as_str().into(),
         });
 
         Ok(DocCreated {
@@ -721,6 +562,7 @@ impl OpenContext {
     }
 
     pub fn move_doc(&self, doc_path: &str, dest_folder_path: &str) -> CoreResult<RenameResult> {
+        self.ensure_writable("move documents")?;
         let rel_doc_path = normalize_doc_path(Some(doc_path))?;
         let doc = self
             .find_doc(&rel_doc_path)?
@@ -739,7 +581,7 @@ impl OpenContext {
                 "Document \"{new_rel_path}\" already exists."
             )));
         }
-        let new_abs_path = self.contexts_root.join(&new_rel_path);
+        let new_abs_path = self.abs_path(&new_rel_path);
         if let Some(parent) = new_abs_path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -760,23 +602,27 @@ impl OpenContext {
                 old_path: rel_doc_path.clone(),
                 new_path: new_rel_path.clone(),
             }, &ts)?;
+            links::record_move(conn, &rel_doc_path, &new_rel_path, &ts)?;
             Ok(())
         })?;
 
         // Emit event
-        #[cfg(feature = "search")]
+        #[cfg(feature = "keyword")]
         self.emit_doc_event(DocEvent::Moved {
-            old_path: rel_doc_path.clone(),
-            new_path: new_rel_path.clone(),
+            old_path: rel_doc_path.as_str().into(),
+            new_path: new_rel_path.as_str().into(),
         });
 
+        let links_rewritten = self.rewrite_links_after_move(&rel_doc_path, &new_rel_path)?;
         Ok(RenameResult {
             old_path: rel_doc_path,
             new_path: new_rel_path,
+            links_rewritten,
         })
     }
 
     pub fn rename_doc(&self, doc_path: &str, new_name: &str) -> CoreResult<RenameResult> {
+        self.ensure_writable("rename documents")?;
         if new_name.is_empty() || new_name.contains('/') {
             return Err(CoreError::Message(
                 "New name must be a single file name without \"/\".".into(),
@@ -796,7 +642,7 @@ impl OpenContext {
                 "Document \"{new_rel_path}\" already exists."
             )));
         }
-        let new_abs_path = self.contexts_root.join(&new_rel_path);
+        let new_abs_path = self.abs_path(&new_rel_path);
         if let Some(parent) = new_abs_path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -811,23 +657,27 @@ impl OpenContext {
                 old_path: rel_doc_path.clone(),
                 new_path: new_rel_path.clone(),
             }, &ts)?;
+            links::record_move(conn, &rel_doc_path, &new_rel_path, &ts)?;
             Ok(())
         })?;
 
         // Emit event
-        #[cfg(feature = "search")]
+        #[cfg(feature = "keyword")]
         self.emit_doc_event(DocEvent::Renamed {
-            old_path: rel_doc_path.clone(),
-            new_path: new_rel_path.clone(),
+            old_path: rel_doc_path.as_str().into(),
+            new_path: new_rel_path.as_str().into(),
         });
 
+        let links_rewritten = self.rewrite_links_after_move(&rel_doc_path, &new_rel_path)?;
         Ok(RenameResult {
             old_path: rel_doc_path,
             new_path: new_rel_path,
+            links_rewritten,
         })
     }
 
     pub fn remove_doc(&self, doc_path: &str) -> CoreResult<Removed> {
+        self.ensure_writable("remove documents")?;
         let rel_doc_path = normalize_doc_path(Some(doc_path))?;
         let doc = self
             .find_doc(&rel_doc_path)?
@@ -848,9 +698,9 @@ impl OpenContext {
         })?;
 
         // Emit event
-        #[cfg(feature = "search")]
+        #[cfg(feature = "keyword")]
         self.emit_doc_event(DocEvent::Deleted {
-            rel_path: rel_doc_path.clone(),
+            rel_path: rel_doc_path.as_str().into(),
         });
         Ok(Removed {
             rel_path: rel_doc_path,
@@ -858,6 +708,7 @@ impl OpenContext {
     }
 
     pub fn set_doc_description(&self, doc_path: &str, description: &str) -> CoreResult<DocSummary> {
+        self.ensure_writable("edit document descriptions")?;
         let rel_doc_path = normalize_doc_path(Some(doc_path))?;
         let doc = self
             .find_doc(&rel_doc_path)?
@@ -898,16 +749,203 @@ impl OpenContext {
         Ok(content)
     }
 
+    /// Documents at `doc_paths` in one call, in order and without
+    /// duplicates. A path that can't be read comes back with its error
+    /// instead of failing the batch. Contents add up to at most `max_bytes`:
+    /// a document that would go over is returned without content, marked
+    /// `over_limit`, and smaller ones after it still fill the batch.
+    pub fn get_documents(&self, doc_paths: &[String], max_bytes: usize) -> Vec<FetchedDoc> {
+        let mut seen = std::collections::HashSet::new();
+        let mut budget = max_bytes;
+        doc_paths
+            .iter()
+            .filter(|doc_path| seen.insert(doc_path.as_str()))
+            .map(|doc_path| {
+                let read = self.get_doc_meta(doc_path).and_then(|doc| {
+                    let content = fs::read_to_string(&doc.abs_path)?;
+                    Ok((doc, content))
+                });
+                let mut fetched = FetchedDoc {
+                    doc_path: doc_path.clone(),
+                    doc: None,
+                    content: None,
+                    revision: None,
+                    error: None,
+                    over_limit: false,
+                };
+                match read {
+                    Ok((doc, content)) if content.len() <= budget => {
+                        budget -= content.len();
+                        fetched.doc = Some(doc);
+                        fetched.revision = Some(content_revision(content.as_bytes()));
+                        fetched.content = Some(content);
+                    }
+                    Ok((doc, content)) => {
+                        fetched.doc = Some(doc);
+                        fetched.error = Some(format!(
+                            "{} bytes would exceed the batch limit of {max_bytes} bytes; fetch it on its own.",
+                            content.len()
+                        ));
+                        fetched.over_limit = true;
+                    }
+                    Err(err) => fetched.error = Some(err.to_string()),
+                }
+                fetched
+            })
+            .collect()
+    }
+
     pub fn save_doc_content(
         &self,
         doc_path: &str,
         content: &str,
         description: Option<&str>,
     ) -> CoreResult<DocSaved> {
+        self.write_doc_content(doc_path, content, description, None)
+    }
+
+    /// [`save_doc_content`](Self::save_doc_content) that refuses with
+    /// [`CoreError::Conflict`] unless the file is still at
+    /// `expected_revision` (from [`doc_revision`](Self::doc_revision) or a
+    /// previous save), so edits made in the meantime are not overwritten.
+    /// Use [`merge::merge3`] to combine both versions and retry.
+    pub fn save_doc_content_if_unchanged(
+        &self,
+        doc_path: &str,
+        content: &str,
+        description: Option<&str>,
+        expected_revision: &str,
+    ) -> CoreResult<DocSaved> {
+        self.write_doc_content(doc_path, content, description, Some(expected_revision))
+    }
+
+    /// Revision of the doc's current content on disk
+    pub fn doc_revision(&self, doc_path: &str) -> CoreResult<String> {
         let rel_doc_path = normalize_doc_path(Some(doc_path))?;
         let doc = self
             .find_doc(&rel_doc_path)?
             .ok_or_else(|| doc_not_found(&rel_doc_path))?;
+        Ok(content_revision(&fs::read(&doc.abs_path)?))
+    }
+
+    /// Frontmatter fields of a doc (empty when it has no frontmatter block)
+    pub fn get_doc_fields(
+        &self,
+        doc_path: &str,
+    ) -> CoreResult<serde_json::Map<String, serde_json::Value>> {
+        Ok(frontmatter::fields(&self.get_doc_content(doc_path)?))
+    }
+
+    /// One frontmatter field of a doc, `None` if it is not set
+    pub fn get_doc_field(
+        &self,
+        doc_path: &str,
+        key: &str,
+    ) -> CoreResult<Option<serde_json::Value>> {
+        Ok(frontmatter::get_field(
+            &self.get_doc_content(doc_path)?,
+            key,
+        ))
+    }
+
+    /// Set a frontmatter field in place (`set_doc_field("plan.md", "status",
+    /// &json!("done"))`). The body is untouched, so index sync sees an
+    /// unchanged checksum and does not re-embed the doc.
+    pub fn set_doc_field(
+        &self,
+        doc_path: &str,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> CoreResult<DocSaved> {
+        self.edit_doc_content(doc_path, |content| {
+            frontmatter::set_field(content, key, value)
+        })
+    }
+
+    /// Add `text` at the end of the section under `heading_path`
+    /// (`"Plan > Steps"`); see [`edit::append_to_section`]
+    pub fn append_to_section(
+        &self,
+        doc_path: &str,
+        heading_path: &str,
+        text: &str,
+    ) -> CoreResult<DocSaved> {
+        self.edit_doc_content(doc_path, |content| {
+            edit::append_to_section(content, heading_path, text)
+        })
+    }
+
+    /// Apply a unified diff to the doc; see [`edit::apply_patch`]
+    pub fn apply_patch(&self, doc_path: &str, unified_diff: &str) -> CoreResult<DocSaved> {
+        self.edit_doc_content(doc_path, |content| edit::apply_patch(content, unified_diff))
+    }
+
+    /// Read-modify-write that fails with [`CoreError::Conflict`] if the file
+    /// changes between the read and the write
+    fn edit_doc_content(
+        &self,
+        doc_path: &str,
+        edit: impl FnOnce(&str) -> CoreResult<String>,
+    ) -> CoreResult<DocSaved> {
+        self.ensure_writable("save documents")?;
+        let rel_doc_path = normalize_doc_path(Some(doc_path))?;
+        let doc = self
+            .find_doc(&rel_doc_path)?
+            .ok_or_else(|| doc_not_found(&rel_doc_path))?;
+        let raw = fs::read(&doc.abs_path)?;
+        let content = String::from_utf8(raw.clone())
+            .map_err(|_| CoreError::Message(format!("not a UTF-8 document: {rel_doc_path}")))?;
+        let updated = edit(&content)?;
+        self.write_doc_content(&rel_doc_path, &updated, None, Some(&content_revision(&raw)))
+    }
+
+    /// Write generated `content` (imports, synced sources) to `doc_path`,
+    /// creating the doc and its folder as needed: `Some(true)` if the doc
+    /// was created, `Some(false)` if it changed, `None` if it already held
+    /// exactly `content`
+    fn put_generated_doc(&self, doc_path: &str, content: &str) -> CoreResult<Option<bool>> {
+        let created = match self.find_doc(doc_path)? {
+            Some(doc) => {
+                if fs::read_to_string(&doc.abs_path)? == content {
+                    return Ok(None);
+                }
+                false
+            }
+            None => {
+                let (folder, name) = doc_path.rsplit_once('/').unwrap_or(("", doc_path));
+                if self.find_folder(folder)?.is_none() {
+                    self.create_folder(folder, None)?;
+                }
+                self.create_doc(folder, name, None)?;
+                true
+            }
+        };
+        self.save_doc_content(doc_path, content, None)?;
+        Ok(Some(created))
+    }
+
+    fn write_doc_content(
+        &self,
+        doc_path: &str,
+        content: &str,
+        description: Option<&str>,
+        expected_revision: Option<&str>,
+    ) -> CoreResult<DocSaved> {
+        self.ensure_writable("save documents")?;
+        let rel_doc_path = normalize_doc_path(Some(doc_path))?;
+        let doc = self
+            .find_doc(&rel_doc_path)?
+            .ok_or_else(|| doc_not_found(&rel_doc_path))?;
+        if let Some(expected) = expected_revision {
+            let actual = content_revision(&fs::read(&doc.abs_path)?);
+            if actual != expected {
+                return Err(CoreError::Conflict {
+                    rel_path: rel_doc_path,
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
         fs::write(&doc.abs_path, content)?;
         let ts = now_iso();
         self.with_conn(|conn| {
@@ -922,6 +960,9 @@ impl OpenContext {
                     params![ts, doc.id],
                 )?;
             }
+            tasks::sync_doc_tasks(conn, doc.id, content)?;
+            dates::sync_doc_dates(conn, doc.id, content)?;
+            flashcards::sync_doc_cards(conn, doc.id, content)?;
             wal::append(
                 conn,
                 &wal::WalOp::Update {
@@ -933,14 +974,15 @@ impl OpenContext {
         })?;
 
         // Emit event
-        #[cfg(feature = "search")]
+        #[cfg(feature = "keyword")]
         self.emit_doc_event(DocEvent::Updated {
-            rel_path: rel_doc_path.clone(),
+            rel_path: rel_doc_path.as_str().into(),
         });
 
         Ok(DocSaved {
             rel_path: rel_doc_path,
             abs_path: doc.abs_path,
+            revision: content_revision(content.as_bytes()),
         })
     }
 
@@ -955,6 +997,7 @@ impl OpenContext {
     /// output-token ceiling). The content is read implicitly from
     /// disk by downstream consumers of the Updated event.
     pub fn reconcile_doc(&self, doc_path: &str, description: Option<&str>) -> CoreResult<DocSaved> {
+        self.ensure_writable("reconcile documents")?;
         let rel_doc_path = normalize_doc_path(Some(doc_path))?;
         let doc = self
             .find_doc(&rel_doc_path)?
@@ -965,6 +1008,7 @@ impl OpenContext {
                 doc.abs_path.display()
             )));
         }
+        let content = fs::read(&doc.abs_path)?;
         let ts = now_iso();
         self.with_conn(|conn| {
             if let Some(desc) = description {
@@ -978,16 +1022,21 @@ impl OpenContext {
                     params![ts, doc.id],
                 )?;
             }
+            let content = String::from_utf8_lossy(&content);
+            tasks::sync_doc_tasks(conn, doc.id, &content)?;
+            dates::sync_doc_dates(conn, doc.id, &content)?;
+            flashcards::sync_doc_cards(conn, doc.id, &content)?;
             Ok(())
         })?;
 
-        #[cfg(feature = "search")]
+        #[cfg(feature = "keyword")]
         self.emit_doc_event(DocEvent::Updated {
-            rel_path: rel_doc_path.clone(),
+            rel_path: rel_doc_path.as_str().into(),
         });
 
         Ok(DocSaved {
             rel_path: rel_doc_path,
+            revision: content_revision(&content),
             abs_path: doc.abs_path,
         })
     }
@@ -1054,7 +1103,12 @@ impl OpenContext {
         // Collect rel_paths of every *.md file under the folder.
         let mut on_disk: Vec<String> = Vec::new();
         if folder.abs_path.is_dir() {
-            scan_md_files(&folder.abs_path, &self.contexts_root, &mut on_disk)?;
+            scan_md_files(
+                &folder.abs_path,
+                &self.contexts_root,
+                self.symlinks,
+                &mut on_disk,
+            )?;
         }
 
         // Subtract everything that is already in the DB for this folder
@@ -1087,14 +1141,27 @@ impl OpenContext {
     /// embeddings / LanceDB — that's a separate (slow) step via
     /// `oc index build`. Returns the list of newly registered rel_paths.
     pub fn reconcile_folder(&self, folder_path: &str) -> CoreResult<ReconcileReport> {
+        self.ensure_writable("reconcile folders")?;
+        let folders_moved = self.reconcile_folder_moves()?;
         let rel_path = normalize_folder_path(Some(folder_path))?;
+        // The folder being reconciled may itself be one that moved
+        let moves: Vec<(String, String)> = folders_moved
+            .iter()
+            .map(|m| (m.old_path.clone(), m.new_path.clone()))
+            .collect();
+        let rel_path = links::follow_moves(&rel_path, &moves).unwrap_or(rel_path);
         let folder = self
             .find_folder(&rel_path)?
             .ok_or_else(|| folder_not_found(&rel_path))?;
 
         let mut on_disk_vec: Vec<String> = Vec::new();
         if folder.abs_path.is_dir() {
-            scan_md_files(&folder.abs_path, &self.contexts_root, &mut on_disk_vec)?;
+            scan_md_files(
+                &folder.abs_path,
+                &self.contexts_root,
+                self.symlinks,
+                &mut on_disk_vec,
+            )?;
         }
         let on_disk: std::collections::HashSet<String> = on_disk_vec.iter().cloned().collect();
 
@@ -1102,9 +1169,10 @@ impl OpenContext {
             let pattern = if folder.rel_path.is_empty() {
                 "%".to_string()
             } else {
-                format!("{}/%", folder.rel_path)
+                like_children(&folder.rel_path)
             };
-            let mut stmt = conn.prepare("SELECT rel_path FROM docs WHERE rel_path LIKE ?1")?;
+            let mut stmt =
+                conn.prepare("SELECT rel_path FROM docs WHERE rel_path LIKE ?1 ESCAPE '\\'")?;
             let rows = stmt
                 .query_map([pattern], |row| row.get::<_, String>(0))?
                 .collect::<Result<Vec<_>, _>>()?;
@@ -1125,12 +1193,8 @@ impl OpenContext {
             let parent_folder = self
                 .ensure_folder_record(&parent_rel)?
                 .ok_or_else(|| folder_not_found(&parent_rel))?;
-            let name = doc_rel
-                .split('/')
-                .next_back()
-                .unwrap_or(&doc_rel)
-                .to_string();
-            let abs_path = self.contexts_root.join(&doc_rel);
+            let name = relpath::file_name(&doc_rel).to_string();
+            let abs_path = self.abs_path(&doc_rel);
             let ts = now_iso();
             self.with_conn(|conn| {
                 let sid = generate_stable_id(conn)?;
@@ -1146,12 +1210,17 @@ impl OpenContext {
                         ts
                     ],
                 )?;
+                let content = fs::read_to_string(&abs_path).unwrap_or_default();
+                let doc_id = conn.last_insert_rowid();
+                tasks::sync_doc_tasks(conn, doc_id, &content)?;
+                dates::sync_doc_dates(conn, doc_id, &content)?;
+                flashcards::sync_doc_cards(conn, doc_id, &content)?;
                 Ok(())
             })?;
 
-            #[cfg(feature = "search")]
+            #[cfg(feature = "keyword")]
             self.emit_doc_event(DocEvent::Created {
-                rel_path: doc_rel.clone(),
+                rel_path: doc_rel.as_str().into(),
             });
 
             added.push(doc_rel);
@@ -1164,7 +1233,7 @@ impl OpenContext {
                 continue;
             }
             // Confirm file is truly missing on disk (defense against scan_md_files quirks).
-            let abs = self.contexts_root.join(known_rel);
+            let abs = self.abs_path(known_rel);
             if abs.exists() {
                 continue;
             }
@@ -1173,9 +1242,9 @@ impl OpenContext {
                 Ok(())
             })?;
 
-            #[cfg(feature = "search")]
+            #[cfg(feature = "keyword")]
             self.emit_doc_event(DocEvent::Deleted {
-                rel_path: known_rel.clone(),
+                rel_path: known_rel.as_str().into(),
             });
 
             removed.push(known_rel.clone());
@@ -1183,7 +1252,80 @@ impl OpenContext {
 
         added.sort();
         removed.sort();
-        Ok(ReconcileReport { added, removed })
+        Ok(ReconcileReport {
+            added,
+            removed,
+            folders_moved,
+        })
+    }
+
+    /// Re-key folders whose directory was moved or renamed outside
+    /// OpenContext (one filesystem operation, so no per-file history). Left
+    /// alone, each of their docs would be dropped and re-added with a new
+    /// stable id, losing tasks, hits and search index entries.
+    fn reconcile_folder_moves(&self) -> CoreResult<Vec<RenameResult>> {
+        let gone: Vec<String> = self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT rel_path, abs_path FROM folders ORDER BY rel_path")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows
+                .into_iter()
+                .filter(|(rel, abs)| !rel.is_empty() && !Path::new(abs).exists())
+                .map(|(rel, _)| rel)
+                .collect())
+        })?;
+        if gone.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let known: std::collections::HashSet<String> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT rel_path FROM docs")?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows.into_iter().collect())
+        })?;
+        let mut on_disk = Vec::new();
+        scan_md_files(
+            &self.contexts_root,
+            &self.contexts_root,
+            self.symlinks,
+            &mut on_disk,
+        )?;
+        let on_disk_set: std::collections::HashSet<&String> = on_disk.iter().collect();
+        let mut missing: Vec<String> = known
+            .iter()
+            .filter(|p| !on_disk_set.contains(p))
+            .cloned()
+            .collect();
+        missing.sort();
+        let found: Vec<String> = on_disk.into_iter().filter(|p| !known.contains(p)).collect();
+
+        let mut folders_moved = Vec::new();
+        for (old_rel, new_rel) in detect_folder_moves(&gone, &missing, &found) {
+            let Some(moved_folder) = self.find_folder(&old_rel)? else {
+                continue;
+            };
+            if self.find_folder(&new_rel)?.is_some() {
+                continue;
+            }
+            let old_parent = parent_rel_path(&old_rel).unwrap_or_default();
+            let new_parent = parent_rel_path(&new_rel).unwrap_or_default();
+            let parent_id = self.ensure_folder_record(&new_parent)?.map(|f| f.id);
+            self.folders()
+                .rekey(&moved_folder, &new_rel, parent_id, old_parent != new_parent)?;
+            let links_rewritten = self.rewrite_links_after_move(&old_rel, &new_rel)?;
+            folders_moved.push(RenameResult {
+                old_path: old_rel,
+                new_path: new_rel,
+                links_rewritten,
+            });
+        }
+        Ok(folders_moved)
     }
 
     fn find_folder(&self, rel_path: &str) -> CoreResult<Option<Folder>> {
@@ -1206,7 +1348,7 @@ impl OpenContext {
             };
 
             // Partial match on the last path segment
-            let leaf = query.split('/').next_back().unwrap_or(query);
+            let leaf = relpath::file_name(query);
             let pattern = format!("%{}%", leaf);
 
             let mut candidates: Vec<String> = Vec::new();
@@ -1263,10 +1405,10 @@ impl OpenContext {
                 self.ensure_folder_record(parent_rel)?;
             }
         }
-        let abs_path = self.contexts_root.join(rel_path);
+        let abs_path = self.abs_path(rel_path);
         fs::create_dir_all(&abs_path)?;
         let ts = now_iso();
-        let name = rel_path.split('/').next_back().unwrap_or(rel_path);
+        let name = relpath::file_name(rel_path);
         self.with_conn(|conn| {
             conn.execute(
                 "INSERT INTO folders (parent_id, name, rel_path, abs_path, description, created_at, updated_at)
@@ -1312,6 +1454,8 @@ pub struct FolderSummary {
 pub struct RenameResult {
     pub old_path: String,
     pub new_path: String,
+    /// Links in other docs updated to follow the move
+    pub links_rewritten: Vec<links::LinkRewrite>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -1323,6 +1467,8 @@ pub struct Removed {
 pub struct ReconcileReport {
     pub added: Vec<String>,
     pub removed: Vec<String>,
+    /// Folders whose directory was moved or renamed on disk
+    pub folders_moved: Vec<RenameResult>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -1343,6 +1489,18 @@ pub struct DocSummary {
 pub struct DocSaved {
     pub rel_path: String,
     pub abs_path: PathBuf,
+    /// Revision of the saved content, for the next conditional save
+    pub revision: String,
+}
+
+/// Short, stable fingerprint of a document's content (64-bit FNV-1a, hex)
+pub fn content_revision(content: &[u8]) -> String {
+    let hash = content
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
 }
 
 fn now_iso() -> String {
@@ -1353,85 +1511,78 @@ fn normalize_folder_path(input: Option<&str>) -> CoreResult<String> {
     let Some(value) = input else {
         return Err(CoreError::Message("Folder path is required".into()));
     };
-    let trimmed = value.trim();
-    if trimmed.is_empty() || trimmed == "." || trimmed == "/" {
-        return Ok(String::new());
-    }
-    let normalized = trimmed
-        .replace('\\', "/")
-        .split('/')
-        .filter(|segment| !segment.is_empty())
-        .collect::<Vec<_>>()
-        .join("/");
-    Ok(normalized)
+    Ok(RelPath::new(value).into_string())
 }
 
 fn normalize_doc_path(input: Option<&str>) -> CoreResult<String> {
     let Some(value) = input else {
         return Err(CoreError::Message("Document path is required".into()));
     };
-    let cleaned = value
-        .trim()
-        .replace('\\', "/")
-        .split('/')
-        .filter(|segment| !segment.is_empty())
-        .collect::<Vec<_>>()
-        .join("/");
-    if cleaned.is_empty() {
+    let cleaned = RelPath::new(value);
+    if cleaned.is_root() {
         return Err(CoreError::Message("Document path cannot be root".into()));
     }
-    Ok(cleaned)
+    Ok(cleaned.into_string())
+}
+
+/// `LIKE ... ESCAPE '\'` pattern for everything under `rel_path`, so `_` and
+/// `%` in folder names match only themselves
+fn like_children(rel_path: &str) -> String {
+    let escaped = rel_path
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{escaped}/%")
 }
 
 fn parent_rel_path(rel_path: &str) -> Option<String> {
-    if rel_path.is_empty() {
-        return None;
-    }
-    let mut parts: Vec<&str> = rel_path.split('/').collect();
-    if parts.is_empty() {
-        return None;
-    }
-    parts.pop();
-    let joined = parts.join("/");
-    if joined.is_empty() {
-        None
-    } else {
-        Some(joined)
-    }
+    RelPath::new(rel_path).parent().map(RelPath::into_string)
 }
 
-/// Recursively walk `dir` and append rel_paths (relative to `contexts_root`)
-/// of every `*.md` file. Hidden directories (starting with `.`) and any
-/// non-utf8 paths are skipped.
-fn scan_md_files(dir: &Path, contexts_root: &Path, out: &mut Vec<String>) -> CoreResult<()> {
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        let file_name = match entry.file_name().into_string() {
-            Ok(n) => n,
-            Err(_) => continue,
-        };
-        if file_name.starts_with('.') {
+/// Folders whose directory was moved or renamed outside OpenContext: every
+/// indexed doc under a folder in `gone` (directories no longer on disk,
+/// parents first) reappears in `found` (unindexed files) under exactly one
+/// other directory with the same relative path. `missing` holds the indexed
+/// docs whose files are gone.
+fn detect_folder_moves(
+    gone: &[String],
+    missing: &[String],
+    found: &[String],
+) -> Vec<(String, String)> {
+    let mut moves: Vec<(String, String)> = Vec::new();
+    for folder in gone {
+        if moves
+            .iter()
+            .any(|(old, _)| folder.starts_with(&format!("{old}/")))
+        {
             continue;
         }
-        let file_type = entry.file_type()?;
-        if file_type.is_dir() {
-            scan_md_files(&path, contexts_root, out)?;
-        } else if file_type.is_file()
-            && path
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|e| e.eq_ignore_ascii_case("md"))
-                .unwrap_or(false)
-        {
-            if let Ok(rel) = path.strip_prefix(contexts_root) {
-                if let Some(s) = rel.to_str() {
-                    out.push(s.replace('\\', "/"));
+        let prefix = format!("{folder}/");
+        let docs: Vec<&str> = missing
+            .iter()
+            .filter_map(|p| p.strip_prefix(&prefix))
+            .collect();
+        if docs.is_empty() {
+            continue;
+        }
+        let mut votes: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        for suffix in &docs {
+            let tail = format!("/{suffix}");
+            for path in found {
+                if let Some(dir) = path.strip_suffix(&tail) {
+                    *votes.entry(dir).or_default() += 1;
                 }
             }
         }
+        let mut targets = votes
+            .into_iter()
+            .filter(|(_, n)| *n == docs.len())
+            .map(|(dir, _)| dir);
+        if let (Some(target), None) = (targets.next(), targets.next()) {
+            moves.push((folder.clone(), target.to_string()));
+        }
     }
-    Ok(())
+    moves
 }
 
 fn folder_not_found(rel_path: &str) -> CoreError {
@@ -0,0 +1,871 @@
+//! Link graph
+//!
+//! Documents reach each other through `[[wiki-links]]`, Markdown links to
+//! `.md` files (relative to the linking doc, or to the workspace root when
+//! they start with `/`) and `oc://doc/<stable_id>` links inserted by the
+//! editor. [`Resolver`] maps a link to the document it reaches; renames and
+//! moves are kept in `path_moves` so a dead link can be pointed at where its
+//! target went.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use crate::names::{name_key, NameIndex};
+use crate::{normalize_folder_path, CoreResult, OpenContext};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Wiki,
+    Markdown,
+    DocId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub target: String,
+    /// 1-based line number
+    pub line: usize,
+    pub kind: LinkKind,
+}
+
+/// Links in `content`, skipping frontmatter, code fences and inline code
+pub fn parse_links(content: &str) -> Vec<Link> {
+    let header = &content[..content.len() - crate::frontmatter::body(content).len()];
+    let skip = header.matches('\n').count();
+    let mut out = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (idx, line) in content.lines().enumerate().skip(skip) {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let mut push = |target: &str| {
+            let target = target.trim().trim_matches(['<', '>']);
+            if target.is_empty() {
+                return;
+            }
+            let kind = if target.starts_with("oc://doc/") {
+                LinkKind::DocId
+            } else {
+                LinkKind::Markdown
+            };
+            out.push(Link {
+                target: target.to_string(),
+                line: idx + 1,
+                kind,
+            });
+        };
+
+        // `[label]: target` reference definitions
+        if let Some((label, target)) = trimmed.split_once("]: ") {
+            if label.starts_with('[') && !label.contains(']') {
+                if let Some(target) = target.split_whitespace().next() {
+                    push(target);
+                }
+                continue;
+            }
+        }
+
+        // Drop inline code spans so `[a](b)` examples are not links
+        let text = line.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+        let mut rest = text.as_str();
+        while let Some(idx) = rest.find("](") {
+            rest = &rest[idx + 2..];
+            let mut depth = 0;
+            let end = rest.char_indices().find_map(|(i, c)| match c {
+                '(' => {
+                    depth += 1;
+                    None
+                }
+                ')' if depth == 0 => Some(i),
+                ')' => {
+                    depth -= 1;
+                    None
+                }
+                _ => None,
+            });
+            let Some(end) = end else { break };
+            // `(target "title")`
+            if let Some(target) = rest[..end].split_whitespace().next() {
+                push(target);
+            }
+            rest = &rest[end..];
+        }
+
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find("[[") {
+            rest = &rest[start + 2..];
+            let Some(end) = rest.find("]]") else { break };
+            let target = rest[..end]
+                .split(['|', '#'])
+                .next()
+                .unwrap_or_default()
+                .trim();
+            if !target.is_empty() {
+                out.push(Link {
+                    target: target.to_string(),
+                    line: idx + 1,
+                    kind: LinkKind::Wiki,
+                });
+            }
+            rest = &rest[end + 2..];
+        }
+    }
+    out
+}
+
+/// Where a link leads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Doc(String),
+    /// Not a link to a document (web, images, anchors, idea refs)
+    External,
+    /// Meant for a document that does not exist; carries the workspace path
+    /// it pointed at when that is known
+    Broken(Option<String>),
+}
+
+pub(crate) fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Resolve `target` relative to `dir`; `None` if it leaves the workspace.
+/// Links written on Windows may use `\` as the separator.
+pub(crate) fn join_rel(dir: &str, target: &str) -> Option<String> {
+    let mut parts: Vec<&str> = if target.starts_with(['/', '\\']) {
+        Vec::new()
+    } else {
+        dir.split('/').filter(|p| !p.is_empty()).collect()
+    };
+    for part in target.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            p => parts.push(p),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn stem(rel_path: &str) -> String {
+    let name = crate::relpath::file_name(rel_path);
+    name.strip_suffix(".md").unwrap_or(name).to_lowercase()
+}
+
+/// Maps links to the documents registered in the workspace
+pub struct Resolver {
+    paths: HashSet<String>,
+    stable_ids: HashMap<String, String>,
+    /// Lowercased file name without `.md` -> doc paths
+    by_name: HashMap<String, Vec<String>>,
+    /// Titles and aliases by [`name_key`](crate::names::name_key)
+    titles: HashMap<String, String>,
+}
+
+impl Resolver {
+    /// Build from `(rel_path, stable_id)` pairs
+    pub fn new(docs: impl IntoIterator<Item = (String, Option<String>)>) -> Self {
+        let mut resolver = Resolver {
+            paths: HashSet::new(),
+            stable_ids: HashMap::new(),
+            by_name: HashMap::new(),
+            titles: HashMap::new(),
+        };
+        for (rel_path, stable_id) in docs {
+            if let Some(id) = stable_id {
+                resolver.stable_ids.insert(id, rel_path.clone());
+            }
+            resolver
+                .by_name
+                .entry(stem(&rel_path))
+                .or_default()
+                .push(rel_path.clone());
+            resolver.paths.insert(rel_path);
+        }
+        for paths in resolver.by_name.values_mut() {
+            // Shortest path wins when a wiki-link name is ambiguous
+            paths.sort_by_key(|p| (p.matches('/').count(), p.clone()));
+        }
+        resolver
+    }
+
+    /// Also resolve `[[wiki-links]]` by document title and alias, when no
+    /// file has the name
+    pub fn with_names(mut self, names: &NameIndex) -> Self {
+        self.titles = names.exact();
+        self
+    }
+
+    pub fn contains(&self, rel_path: &str) -> bool {
+        self.paths.contains(rel_path)
+    }
+
+    /// Docs whose file name (without `.md`) matches `rel_path`'s
+    pub fn same_name(&self, rel_path: &str) -> &[String] {
+        self.by_name.get(&stem(rel_path)).map_or(&[], Vec::as_slice)
+    }
+
+    /// The document `link` in the doc at `from` leads to
+    pub fn resolve(&self, from: &str, link: &Link) -> Resolution {
+        let dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+        match link.kind {
+            LinkKind::DocId => {
+                let rest = &link.target["oc://doc/".len()..];
+                let (id, query) = rest.split_once('?').unwrap_or((rest, ""));
+                if let Some(path) = self.stable_ids.get(id.trim_matches('/')) {
+                    return Resolution::Doc(path.clone());
+                }
+                let fallback = query
+                    .split('&')
+                    .find_map(|kv| kv.strip_prefix("path="))
+                    .map(percent_decode);
+                match fallback {
+                    Some(path) if self.paths.contains(&path) => Resolution::Doc(path),
+                    other => Resolution::Broken(other),
+                }
+            }
+            LinkKind::Markdown => {
+                let target = &link.target;
+                if target.contains("://")
+                    || target.starts_with(['#', '?'])
+                    || target.starts_with("mailto:")
+                {
+                    return Resolution::External;
+                }
+                let path = target.split(['#', '?']).next().unwrap_or_default();
+                if !path.to_ascii_lowercase().ends_with(".md") {
+                    return Resolution::External;
+                }
+                match join_rel(dir, &percent_decode(path)) {
+                    Some(resolved) if self.paths.contains(&resolved) => Resolution::Doc(resolved),
+                    resolved => Resolution::Broken(resolved),
+                }
+            }
+            LinkKind::Wiki => {
+                let target = link.target.trim_start_matches('/');
+                let with_ext = if target.to_ascii_lowercase().ends_with(".md") {
+                    target.to_string()
+                } else {
+                    format!("{target}.md")
+                };
+                let candidates = [join_rel("", &with_ext), join_rel(dir, &with_ext)];
+                if let Some(path) = candidates
+                    .iter()
+                    .flatten()
+                    .find(|p| self.paths.contains(*p))
+                {
+                    return Resolution::Doc(path.clone());
+                }
+                if !target.contains('/') {
+                    if let Some(path) = self.same_name(&with_ext).first() {
+                        return Resolution::Doc(path.clone());
+                    }
+                }
+                if let Some(path) = self.titles.get(&name_key(target)) {
+                    return Resolution::Doc(path.clone());
+                }
+                Resolution::Broken(join_rel("", &with_ext))
+            }
+        }
+    }
+}
+
+/// Remember that a doc or folder moved, for fixing links to its old path
+pub(crate) fn record_move(
+    conn: &Connection,
+    old_path: &str,
+    new_path: &str,
+    ts: &str,
+) -> CoreResult<()> {
+    conn.execute(
+        "INSERT INTO path_moves (old_path, new_path, moved_at) VALUES (?1, ?2, ?3)",
+        params![old_path, new_path, ts],
+    )?;
+    Ok(())
+}
+
+/// Where `rel_path` lives now according to `moves` (oldest first), if it
+/// was renamed or sits in a folder that was
+pub(crate) fn follow_moves(rel_path: &str, moves: &[(String, String)]) -> Option<String> {
+    let mut current = rel_path.to_string();
+    for (old, new) in moves {
+        if current == *old {
+            current = new.clone();
+        } else if let Some(rest) = current
+            .strip_prefix(old.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            current = format!("{new}/{rest}");
+        }
+    }
+    (current != rel_path).then_some(current)
+}
+
+/// Path of `target` as written from a doc in `dir`
+fn relative_path(dir: &str, target: &str) -> String {
+    let from: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    let to: Vec<&str> = target.split('/').collect();
+    let common = from
+        .iter()
+        .zip(&to[..to.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+/// Percent-encode like JavaScript's `encodeURIComponent`
+fn encode_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// New text for `link`, found in the doc now at `from`, so that it reaches
+/// `target` in `resolver`'s workspace; `None` if the current text will do
+fn retarget(link: &Link, from: &str, target: &str, resolver: &Resolver) -> Option<String> {
+    let dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let reaches = |text: &str| {
+        let candidate = Link {
+            target: text.to_string(),
+            ..link.clone()
+        };
+        resolver.resolve(from, &candidate) == Resolution::Doc(target.to_string())
+    };
+    if reaches(&link.target) {
+        return None;
+    }
+    let text = match link.kind {
+        LinkKind::Markdown => {
+            let split = link.target.find(['#', '?']).unwrap_or(link.target.len());
+            let (path, suffix) = link.target.split_at(split);
+            let mut new_path = if path.starts_with('/') {
+                format!("/{target}")
+            } else {
+                let rel = relative_path(dir, target);
+                if path.starts_with("./") && !rel.starts_with("../") {
+                    format!("./{rel}")
+                } else {
+                    rel
+                }
+            };
+            if path.contains("%20") {
+                new_path = new_path.replace(' ', "%20");
+            }
+            format!("{new_path}{suffix}")
+        }
+        LinkKind::Wiki => {
+            let with_ext = link.target.to_ascii_lowercase().ends_with(".md");
+            let written = |path: &str| {
+                let path = if with_ext {
+                    path
+                } else {
+                    path.strip_suffix(".md").unwrap_or(path)
+                };
+                if link.target.starts_with('/') {
+                    format!("/{path}")
+                } else {
+                    path.to_string()
+                }
+            };
+            // Keep bare names bare while the name still finds the doc
+            let name = target.rsplit('/').next().unwrap_or(target);
+            if !link.target.contains('/') && reaches(&written(name)) {
+                written(name)
+            } else {
+                written(target)
+            }
+        }
+        LinkKind::DocId => {
+            // The stable id survives moves; only the `path=` fallback ages
+            let (base, query) = link.target.split_once('?')?;
+            let query = query
+                .split('&')
+                .map(|kv| match kv.strip_prefix("path=") {
+                    Some(_) => format!("path={}", encode_component(target)),
+                    None => kv.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+            format!("{base}?{query}")
+        }
+    };
+    (text != link.target).then_some(text)
+}
+
+/// Replace `old` with `new` in `line` wherever it appears as a whole link
+/// target of the given kind
+fn replace_target(line: &str, kind: LinkKind, old: &str, new: &str) -> String {
+    let (openers, closers): (&[&str], &[char]) = match kind {
+        LinkKind::Wiki => (&["[["], &[']', '|', '#']),
+        _ => (
+            &["](", "](<", "]: ", "]: <"],
+            &[')', '>', ' ', '\t', '\r', '\n'],
+        ),
+    };
+    let mut out = line.to_string();
+    for opener in openers {
+        let pattern = format!("{opener}{old}");
+        let mut replaced = String::with_capacity(out.len());
+        let mut rest = out.as_str();
+        while let Some(idx) = rest.find(&pattern) {
+            let after = &rest[idx + pattern.len()..];
+            let whole = after.chars().next().is_none_or(|c| closers.contains(&c));
+            replaced.push_str(&rest[..idx]);
+            replaced.push_str(opener);
+            replaced.push_str(if whole { new } else { old });
+            rest = after;
+        }
+        replaced.push_str(rest);
+        out = replaced;
+    }
+    out
+}
+
+/// A link rewritten, or to be rewritten, after a rename or move
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkRewrite {
+    /// Doc containing the link, at its path after the move
+    pub rel_path: String,
+    pub line: usize,
+    pub kind: LinkKind,
+    pub old_target: String,
+    pub new_target: String,
+}
+
+/// Rewrite the links in `content` that `before` resolved for the doc at
+/// `from_old` but that would miss once the doc is at `from_new` and paths
+/// have moved as in `after`
+fn rewrite_links(
+    content: &str,
+    from_old: &str,
+    from_new: &str,
+    moves: &[(String, String)],
+    before: &Resolver,
+    after: &Resolver,
+) -> (String, Vec<LinkRewrite>) {
+    let mut rewrites = Vec::new();
+    for link in parse_links(content) {
+        let Resolution::Doc(old_target) = before.resolve(from_old, &link) else {
+            continue;
+        };
+        let new_target = follow_moves(&old_target, moves).unwrap_or(old_target.clone());
+        if new_target == old_target && from_new == from_old {
+            continue;
+        }
+        if let Some(text) = retarget(&link, from_new, &new_target, after) {
+            rewrites.push(LinkRewrite {
+                rel_path: from_new.to_string(),
+                line: link.line,
+                kind: link.kind,
+                old_target: link.target,
+                new_target: text,
+            });
+        }
+    }
+    if rewrites.is_empty() {
+        return (content.to_string(), rewrites);
+    }
+    let mut out = String::with_capacity(content.len());
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let mut line = line.to_string();
+        for rewrite in rewrites.iter().filter(|r| r.line == idx + 1) {
+            line = replace_target(
+                &line,
+                rewrite.kind,
+                &rewrite.old_target,
+                &rewrite.new_target,
+            );
+        }
+        out.push_str(&line);
+    }
+    (out, rewrites)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    /// Doc containing the link
+    pub rel_path: String,
+    pub line: usize,
+    pub target: String,
+    pub kind: LinkKind,
+    /// Doc the link should point at: where the target was renamed or moved
+    /// to, or the only doc with the same file name
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkReport {
+    pub docs_checked: usize,
+    pub links_checked: usize,
+    pub broken: Vec<BrokenLink>,
+    /// Docs with no links in either direction
+    pub orphans: Vec<String>,
+}
+
+impl OpenContext {
+    /// Dead links and orphan documents, optionally only for docs under
+    /// `folder` (links from the rest of the workspace still count as inbound)
+    pub fn check_links(&self, folder: Option<&str>) -> CoreResult<LinkReport> {
+        let prefix = match folder {
+            Some(folder) => {
+                let folder = normalize_folder_path(Some(folder))?;
+                (!folder.is_empty()).then(|| format!("{folder}/"))
+            }
+            None => None,
+        };
+        let (docs, moves) = self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT rel_path, abs_path, stable_id FROM docs ORDER BY rel_path")?;
+            let docs = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut stmt = conn.prepare("SELECT old_path, new_path FROM path_moves ORDER BY id")?;
+            let moves = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<(String, String)>, _>>()?;
+            Ok((docs, moves))
+        })?;
+
+        let resolver = Resolver::new(docs.iter().map(|(p, _, id)| (p.clone(), id.clone())))
+            .with_names(&self.name_index()?);
+        let in_scope = |p: &str| prefix.as_deref().is_none_or(|pre| p.starts_with(pre));
+        let mut report = LinkReport {
+            docs_checked: 0,
+            links_checked: 0,
+            broken: Vec::new(),
+            orphans: Vec::new(),
+        };
+        let mut linked: HashSet<String> = HashSet::new();
+        for (rel_path, abs_path, _) in &docs {
+            let content = fs::read_to_string(abs_path).unwrap_or_default();
+            let scoped = in_scope(rel_path);
+            report.docs_checked += usize::from(scoped);
+            for link in parse_links(&content) {
+                match resolver.resolve(rel_path, &link) {
+                    Resolution::Doc(target) if target != *rel_path => {
+                        linked.insert(rel_path.clone());
+                        linked.insert(target);
+                    }
+                    Resolution::Broken(path) if scoped => {
+                        let suggestion = path
+                            .as_deref()
+                            .and_then(|p| follow_moves(p, &moves))
+                            .filter(|p| resolver.contains(p))
+                            .or_else(|| {
+                                let name_matches = resolver.same_name(path.as_deref()?);
+                                (name_matches.len() == 1).then(|| name_matches[0].clone())
+                            });
+                        report.broken.push(BrokenLink {
+                            rel_path: rel_path.clone(),
+                            line: link.line,
+                            target: link.target,
+                            kind: link.kind,
+                            suggestion,
+                        });
+                    }
+                    _ => {}
+                }
+                report.links_checked += usize::from(scoped);
+            }
+        }
+        report.orphans = docs
+            .into_iter()
+            .map(|(rel_path, _, _)| rel_path)
+            .filter(|p| {
+                in_scope(p) && !p.starts_with(crate::ideas::IDEAS_DIR) && !linked.contains(p)
+            })
+            .collect();
+        Ok(report)
+    }
+
+    /// Links that moving the doc or folder at `old_path` to `new_path` would
+    /// rewrite, without moving or editing anything
+    pub fn preview_link_rewrites(
+        &self,
+        old_path: &str,
+        new_path: &str,
+    ) -> CoreResult<Vec<LinkRewrite>> {
+        let old_path = old_path.trim_matches('/');
+        if self.find_doc(old_path)?.is_none() && self.find_folder(old_path)?.is_none() {
+            return Err(crate::CoreError::Message(format!(
+                "No document or folder at \"{old_path}\"."
+            )));
+        }
+        self.rewrite_links_for_move(old_path, new_path.trim_matches('/'), false, true)
+    }
+
+    /// Point links in every doc at `new_path` after the doc or folder at
+    /// `old_path` has moved there. Edits go through the normal save path, so
+    /// each changed doc emits an update event.
+    pub(crate) fn rewrite_links_after_move(
+        &self,
+        old_path: &str,
+        new_path: &str,
+    ) -> CoreResult<Vec<LinkRewrite>> {
+        self.rewrite_links_for_move(old_path, new_path, true, false)
+    }
+
+    fn rewrite_links_for_move(
+        &self,
+        old_path: &str,
+        new_path: &str,
+        moved: bool,
+        dry_run: bool,
+    ) -> CoreResult<Vec<LinkRewrite>> {
+        let docs = self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT rel_path, abs_path, stable_id FROM docs ORDER BY rel_path")?;
+            let docs = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(docs)
+        })?;
+        let forward = [(old_path.to_string(), new_path.to_string())];
+        let backward = [(new_path.to_string(), old_path.to_string())];
+        // (path before the move, path after, file, stable id)
+        let docs: Vec<_> = docs
+            .into_iter()
+            .map(|(rel_path, abs_path, id)| {
+                if moved {
+                    let before = follow_moves(&rel_path, &backward).unwrap_or(rel_path.clone());
+                    (before, rel_path, abs_path, id)
+                } else {
+                    let after = follow_moves(&rel_path, &forward).unwrap_or(rel_path.clone());
+                    (rel_path, after, abs_path, id)
+                }
+            })
+            .collect();
+        let before = Resolver::new(docs.iter().map(|d| (d.0.clone(), d.3.clone())));
+        let after = Resolver::new(docs.iter().map(|d| (d.1.clone(), d.3.clone())));
+
+        let mut rewrites = Vec::new();
+        for (from_old, from_new, abs_path, _) in &docs {
+            let Ok(content) = fs::read_to_string(abs_path) else {
+                continue;
+            };
+            let (_, edits) = rewrite_links(&content, from_old, from_new, &forward, &before, &after);
+            if edits.is_empty() {
+                continue;
+            }
+            if !dry_run {
+                let current = if moved { from_new } else { from_old };
+                self.edit_doc_content(current, |content| {
+                    Ok(rewrite_links(content, from_old, from_new, &forward, &before, &after).0)
+                })?;
+            }
+            rewrites.extend(edits);
+        }
+        Ok(rewrites)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(target: &str, kind: LinkKind) -> Link {
+        Link {
+            target: target.into(),
+            line: 1,
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_parse_links() {
+        let content = "---\nsee: \"[[not-a-link]]\"\n---\nSee [plan](../b/plan.md \"Plan\") and [site](https://x.io/a(b)).\n`[not](a.md)` [[Roadmap|the roadmap]] [[ideas#later]]\n```\n[code](gone.md) [[Gone]]\n```\n[def]: <./notes%20v2.md>\n[id](oc://doc/abc123)\n";
+        let links: Vec<_> = parse_links(content)
+            .into_iter()
+            .map(|l| (l.target, l.line, l.kind))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("../b/plan.md".into(), 4, LinkKind::Markdown),
+                ("https://x.io/a(b)".into(), 4, LinkKind::Markdown),
+                ("Roadmap".into(), 5, LinkKind::Wiki),
+                ("ideas".into(), 5, LinkKind::Wiki),
+                ("./notes%20v2.md".into(), 9, LinkKind::Markdown),
+                ("oc://doc/abc123".into(), 10, LinkKind::DocId),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let resolver = Resolver::new([
+            ("b/plan.md".to_string(), None),
+            ("a/notes v2.md".to_string(), None),
+            ("a/roadmap.md".to_string(), Some("abc123".to_string())),
+            ("archive/2023/roadmap.md".to_string(), None),
+        ]);
+        let md = |t| resolver.resolve("a/x.md", &link(t, LinkKind::Markdown));
+        let wiki = |t| resolver.resolve("a/x.md", &link(t, LinkKind::Wiki));
+        let doc = |p: &str| Resolution::Doc(p.to_string());
+
+        assert_eq!(md("../b/plan.md"), doc("b/plan.md"));
+        assert_eq!(md("./notes%20v2.md#top"), doc("a/notes v2.md"));
+        assert_eq!(md("/b/plan.md"), doc("b/plan.md"));
+        assert_eq!(md("plan.md"), Resolution::Broken(Some("a/plan.md".into())));
+        assert_eq!(md("../../escape.md"), Resolution::Broken(None));
+        assert_eq!(md("image.png"), Resolution::External);
+        assert_eq!(md("https://example.com/x.md"), Resolution::External);
+
+        assert_eq!(wiki("Roadmap"), doc("a/roadmap.md"));
+        assert_eq!(wiki("b/plan"), doc("b/plan.md"));
+        assert_eq!(
+            wiki("b/missing"),
+            Resolution::Broken(Some("b/missing.md".into()))
+        );
+
+        let id = |t| resolver.resolve("a/x.md", &link(t, LinkKind::DocId));
+        assert_eq!(id("oc://doc/abc123"), doc("a/roadmap.md"));
+        assert_eq!(id("oc://doc/zzz?path=b%2Fplan.md"), doc("b/plan.md"));
+        assert_eq!(id("oc://doc/zzz"), Resolution::Broken(None));
+
+        let mut names = NameIndex::default();
+        names.add("b/plan.md", "---\naliases: [Launch Plan]\n---\n# Q4 Plan\n");
+        let resolver = Resolver::new([("b/plan.md".to_string(), None)]).with_names(&names);
+        let wiki = |t| resolver.resolve("a/x.md", &link(t, LinkKind::Wiki));
+        assert_eq!(wiki("launch  plan"), doc("b/plan.md"));
+        assert_eq!(wiki("Q4 Plan"), doc("b/plan.md"));
+        assert!(matches!(wiki("Q4"), Resolution::Broken(_)));
+    }
+
+    #[test]
+    fn test_follow_moves() {
+        let moves = vec![
+            ("drafts/plan.md".to_string(), "drafts/launch.md".to_string()),
+            ("drafts".to_string(), "projects/q4".to_string()),
+        ];
+        assert_eq!(
+            follow_moves("drafts/plan.md", &moves).as_deref(),
+            Some("projects/q4/launch.md")
+        );
+        assert_eq!(
+            follow_moves("drafts/other.md", &moves).as_deref(),
+            Some("projects/q4/other.md")
+        );
+        assert_eq!(follow_moves("elsewhere.md", &moves), None);
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let docs = [
+            "a/x.md",
+            "drafts/plan.md",
+            "drafts/notes v2.md",
+            "roadmap.md",
+        ];
+        let moves = vec![("drafts".to_string(), "projects/q4".to_string())];
+        let before = Resolver::new(docs.iter().map(|p| (p.to_string(), None)));
+        let after = Resolver::new(
+            docs.iter()
+                .map(|p| (follow_moves(p, &moves).unwrap_or(p.to_string()), None)),
+        );
+
+        let content = "See [plan](../drafts/plan.md#goals) and [[drafts/plan|the plan]].\n[[roadmap]] [[plan]] [[drafts/planning]]\n[v2]: <../drafts/notes%20v2.md>\n";
+        let (updated, rewrites) =
+            rewrite_links(content, "a/x.md", "a/x.md", &moves, &before, &after);
+        assert_eq!(
+            updated,
+            "See [plan](../projects/q4/plan.md#goals) and [[projects/q4/plan|the plan]].\n[[roadmap]] [[plan]] [[drafts/planning]]\n[v2]: <../projects/q4/notes%20v2.md>\n"
+        );
+        assert_eq!(rewrites.len(), 3);
+        assert_eq!(rewrites[1].old_target, "drafts/plan");
+        assert_eq!(rewrites[1].new_target, "projects/q4/plan");
+
+        // The moved doc's own relative links follow it
+        let content = "Back to [roadmap](../roadmap.md), [notes](./notes%20v2.md)\n";
+        let (updated, _) = rewrite_links(
+            content,
+            "drafts/plan.md",
+            "projects/q4/plan.md",
+            &moves,
+            &before,
+            &after,
+        );
+        assert_eq!(
+            updated,
+            "Back to [roadmap](../../roadmap.md), [notes](./notes%20v2.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_retarget_wiki_rename() {
+        let resolver = Resolver::new([
+            ("drafts/launch.md".to_string(), None),
+            ("launch.md".to_string(), None),
+            ("ideas.md".to_string(), Some("abc".to_string())),
+        ]);
+        let wiki = |t: &str| link(t, LinkKind::Wiki);
+        // A bare name that became ambiguous falls back to the full path
+        assert_eq!(
+            retarget(&wiki("Plan"), "a/x.md", "drafts/launch.md", &resolver).as_deref(),
+            Some("drafts/launch")
+        );
+        assert_eq!(
+            retarget(&wiki("old.md"), "a/x.md", "ideas.md", &resolver).as_deref(),
+            Some("ideas.md")
+        );
+        let id = link("oc://doc/abc?path=old%2Fideas.md", LinkKind::DocId);
+        assert_eq!(retarget(&id, "a/x.md", "ideas.md", &resolver), None);
+        let id = link("oc://doc/zzz?path=old%2Fideas.md&v=1", LinkKind::DocId);
+        assert_eq!(
+            retarget(&id, "a/x.md", "ideas.md", &resolver).as_deref(),
+            Some("oc://doc/zzz?path=ideas.md&v=1")
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! File manifest for startup reconciliation
+//!
+//! `file_manifest` holds the mtime, size and content hash of every Markdown
+//! file as of the last time the search index caught up with it. Edits made
+//! while no index sync was running (an editor, `git pull`, another machine)
+//! never produce events; comparing the manifest with the filesystem when
+//! sync starts recovers them as [`FileChange`]s. Unchanged mtime and size
+//! skip hashing, so the pass costs one `stat` per file.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::{content_revision, scan_md_files, CoreResult, OpenContext, RelPath, SymlinkPolicy};
+
+/// A difference between the manifest and the filesystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    Created(String),
+    Updated(String),
+    Deleted(String),
+    /// A file gone from one path with identical content appearing at another
+    Renamed {
+        old_path: String,
+        new_path: String,
+    },
+}
+
+struct Entry {
+    mtime_ms: i64,
+    size: i64,
+    hash: String,
+}
+
+fn stat(path: &Path) -> Option<(i64, i64)> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime_ms = meta
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis() as i64;
+    Some((mtime_ms, meta.len() as i64))
+}
+
+fn load(conn: &Connection) -> CoreResult<HashMap<String, Entry>> {
+    let mut stmt = conn.prepare("SELECT rel_path, mtime_ms, size, hash FROM file_manifest")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                Entry {
+                    mtime_ms: row.get(1)?,
+                    size: row.get(2)?,
+                    hash: row.get(3)?,
+                },
+            ))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(rows)
+}
+
+struct Comparison {
+    changes: Vec<FileChange>,
+    /// State of every file now on disk
+    current: Vec<(String, Entry)>,
+    /// Entries in the manifest before the comparison
+    tracked: usize,
+}
+
+fn compare(
+    conn: &Connection,
+    contexts_root: &Path,
+    symlinks: SymlinkPolicy,
+) -> CoreResult<Comparison> {
+    let mut previous = load(conn)?;
+    let tracked = previous.len();
+    let mut files = Vec::new();
+    if contexts_root.is_dir() {
+        scan_md_files(contexts_root, contexts_root, symlinks, &mut files)?;
+    }
+    files.sort();
+
+    let mut current: Vec<(String, Entry)> = Vec::with_capacity(files.len());
+    let mut changes = Vec::new();
+    let mut added: Vec<(String, String)> = Vec::new();
+    for rel_path in files {
+        let abs_path = RelPath::new(&rel_path).to_path(contexts_root);
+        let Some((mtime_ms, size)) = stat(&abs_path) else {
+            continue;
+        };
+        let before = previous.remove(&rel_path);
+        if let Some(entry) = before
+            .as_ref()
+            .filter(|e| e.mtime_ms == mtime_ms && e.size == size)
+        {
+            let hash = entry.hash.clone();
+            current.push((
+                rel_path,
+                Entry {
+                    mtime_ms,
+                    size,
+                    hash,
+                },
+            ));
+            continue;
+        }
+        let Ok(bytes) = fs::read(&abs_path) else {
+            continue;
+        };
+        let hash = content_revision(&bytes);
+        match before {
+            Some(entry) if entry.hash != hash => {
+                changes.push(FileChange::Updated(rel_path.clone()))
+            }
+            Some(_) => {}
+            None => added.push((rel_path.clone(), hash.clone())),
+        }
+        current.push((
+            rel_path,
+            Entry {
+                mtime_ms,
+                size,
+                hash,
+            },
+        ));
+    }
+
+    // What is left of `previous` is gone from disk; new files with the same
+    // content are taken to be those files moved
+    let mut gone_by_hash: HashMap<String, Vec<String>> = HashMap::new();
+    let mut gone: Vec<(String, Entry)> = previous.into_iter().collect();
+    gone.sort_by(|a, b| b.0.cmp(&a.0));
+    for (rel_path, entry) in gone {
+        gone_by_hash.entry(entry.hash).or_default().push(rel_path);
+    }
+    for (rel_path, hash) in added {
+        match gone_by_hash.get_mut(&hash).and_then(Vec::pop) {
+            Some(old_path) => changes.push(FileChange::Renamed {
+                old_path,
+                new_path: rel_path,
+            }),
+            None => changes.push(FileChange::Created(rel_path)),
+        }
+    }
+    let mut deleted: Vec<String> = gone_by_hash.into_values().flatten().collect();
+    deleted.sort();
+    changes.extend(deleted.into_iter().map(FileChange::Deleted));
+    Ok(Comparison {
+        changes,
+        current,
+        tracked,
+    })
+}
+
+fn store(conn: &Connection, current: &[(String, Entry)]) -> CoreResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM file_manifest", [])?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO file_manifest (rel_path, mtime_ms, size, hash) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (rel_path, entry) in current {
+            stmt.execute(params![rel_path, entry.mtime_ms, entry.size, entry.hash])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Compare the manifest with the files under `contexts_root`, store the
+/// current state and return what changed. The first run only records a
+/// baseline: with no manifest there is nothing to compare against, and
+/// reporting every file as created would rebuild the whole index.
+pub fn reconcile(
+    conn: &Connection,
+    contexts_root: &Path,
+    symlinks: SymlinkPolicy,
+) -> CoreResult<Vec<FileChange>> {
+    let comparison = compare(conn, contexts_root, symlinks)?;
+    store(conn, &comparison.current)?;
+    if comparison.tracked == 0 {
+        return Ok(Vec::new());
+    }
+    Ok(comparison.changes)
+}
+
+/// Record the current state of `rel_paths` once the index has processed
+/// them, dropping the ones no longer on disk
+pub fn refresh(conn: &Connection, contexts_root: &Path, rel_paths: &[String]) -> CoreResult<()> {
+    for rel_path in rel_paths {
+        let abs_path = RelPath::new(rel_path).to_path(contexts_root);
+        let state = stat(&abs_path).zip(fs::read(&abs_path).ok());
+        match state {
+            Some(((mtime_ms, size), bytes)) => {
+                conn.execute(
+                    "INSERT INTO file_manifest (rel_path, mtime_ms, size, hash) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(rel_path) DO UPDATE SET mtime_ms = ?2, size = ?3, hash = ?4",
+                    params![rel_path, mtime_ms, size, content_revision(&bytes)],
+                )?;
+            }
+            None => {
+                conn.execute(
+                    "DELETE FROM file_manifest WHERE rel_path = ?1",
+                    params![rel_path],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamedFile {
+    pub old_path: String,
+    pub new_path: String,
+}
+
+/// Files that differ from what the search index last saw
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexStatus {
+    /// False until the first index build or sync run records a manifest;
+    /// until then every file counts as new
+    pub recorded: bool,
+    /// Files in the manifest
+    pub tracked: usize,
+    pub new: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+    pub renamed: Vec<RenamedFile>,
+}
+
+impl IndexStatus {
+    pub fn is_clean(&self) -> bool {
+        self.new.is_empty()
+            && self.modified.is_empty()
+            && self.deleted.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+impl OpenContext {
+    /// New, modified, deleted and renamed files relative to the search index
+    pub fn index_status(&self) -> CoreResult<IndexStatus> {
+        let comparison =
+            self.with_conn(|conn| compare(conn, &self.contexts_root, self.symlinks))?;
+        let mut status = IndexStatus {
+            recorded: comparison.tracked > 0,
+            tracked: comparison.tracked,
+            ..Default::default()
+        };
+        for change in comparison.changes {
+            match change {
+                FileChange::Created(p) => status.new.push(p),
+                FileChange::Updated(p) => status.modified.push(p),
+                FileChange::Deleted(p) => status.deleted.push(p),
+                FileChange::Renamed { old_path, new_path } => {
+                    status.renamed.push(RenamedFile { old_path, new_path })
+                }
+            }
+        }
+        Ok(status)
+    }
+
+    /// Record files as seen by the search index: the given paths, or every
+    /// file after a full build
+    pub fn mark_indexed(&self, rel_paths: Option<&[String]>) -> CoreResult<()> {
+        self.with_conn(|conn| match rel_paths {
+            Some(paths) => refresh(conn, &self.contexts_root, paths),
+            None => store(
+                conn,
+                &compare(conn, &self.contexts_root, self.symlinks)?.current,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (Connection, TempDir) {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::migrations::run(&conn).unwrap();
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+        (conn, dir)
+    }
+
+    #[test]
+    fn test_reconcile_reports_missed_changes() {
+        let (conn, dir) = setup();
+        let root = dir.path();
+        fs::write(root.join("notes/keep.md"), "keep").unwrap();
+        fs::write(root.join("notes/edit.md"), "v1").unwrap();
+        fs::write(root.join("notes/move.md"), "moving").unwrap();
+        fs::write(root.join("notes/drop.md"), "drop").unwrap();
+        assert!(reconcile(&conn, root, SymlinkPolicy::Ignore)
+            .unwrap()
+            .is_empty());
+
+        fs::write(root.join("notes/edit.md"), "v2, longer").unwrap();
+        fs::rename(root.join("notes/move.md"), root.join("notes/moved.md")).unwrap();
+        fs::remove_file(root.join("notes/drop.md")).unwrap();
+        fs::write(root.join("notes/new.md"), "new").unwrap();
+
+        let mut changes = reconcile(&conn, root, SymlinkPolicy::Ignore).unwrap();
+        changes.sort_by_key(|c| format!("{c:?}"));
+        assert_eq!(
+            changes,
+            vec![
+                FileChange::Created("notes/new.md".into()),
+                FileChange::Deleted("notes/drop.md".into()),
+                FileChange::Renamed {
+                    old_path: "notes/move.md".into(),
+                    new_path: "notes/moved.md".into(),
+                },
+                FileChange::Updated("notes/edit.md".into()),
+            ]
+        );
+        assert!(reconcile(&conn, root, SymlinkPolicy::Ignore)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_refresh_tracks_processed_paths() {
+        let (conn, dir) = setup();
+        let root = dir.path();
+        fs::write(root.join("notes/a.md"), "a").unwrap();
+        reconcile(&conn, root, SymlinkPolicy::Ignore).unwrap();
+
+        fs::write(root.join("notes/a.md"), "a, edited").unwrap();
+        fs::write(root.join("notes/b.md"), "b").unwrap();
+        refresh(&conn, root, &["notes/a.md".into(), "notes/b.md".into()]).unwrap();
+        assert!(reconcile(&conn, root, SymlinkPolicy::Ignore)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_file(root.join("notes/b.md")).unwrap();
+        refresh(&conn, root, &["notes/b.md".into()]).unwrap();
+        assert!(reconcile(&conn, root, SymlinkPolicy::Ignore)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+//! Agent memory
+//!
+//! Facts and preferences an agent wants to keep between conversations ("the
+//! user writes in British English", "staging deploys need the VPN") are
+//! saved with [`OpenContext::remember`] as entries of a memory document under
+//! `.memory/`. Entries use the idea-box layout, with tags and an optional
+//! expiry in the marker line:
+//!
+//! ```text
+//! [//]: # (memory:id=<id> created_at=<timestamp> expires_at=<timestamp> tags=a,b)
+//! ```
+//!
+//! The search index turns each live entry into one chunk of doc type
+//! `memory` with its tags appended as `#tags`, so memories are found by
+//! normal search (`type:memory tag:deploy`) as well as by `recall`. Expired
+//! entries are never listed and are dropped from the file on the next write.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::fs;
+
+use crate::digest::parse_span;
+use crate::{content_revision, now_iso, CoreError, CoreResult, OpenContext};
+
+/// Folder that holds memory documents
+pub const MEMORY_DIR: &str = ".memory/";
+
+/// Document new memories are written to
+pub const MEMORY_DOC: &str = ".memory/memories.md";
+
+/// One remembered fact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub created_at: String,
+    /// RFC 3339 timestamp after which the entry is forgotten
+    pub expires_at: Option<String>,
+    pub tags: Vec<String>,
+    pub content: String,
+}
+
+impl MemoryEntry {
+    /// Whether the entry expired before `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .is_some_and(|ts| ts <= now)
+    }
+
+    fn marker(&self) -> String {
+        let mut marker = format!(
+            "[//]: # (memory:id={} created_at={}",
+            self.id, self.created_at
+        );
+        if let Some(expires_at) = &self.expires_at {
+            marker.push_str(&format!(" expires_at={expires_at}"));
+        }
+        if !self.tags.is_empty() {
+            marker.push_str(&format!(" tags={}", self.tags.join(",")));
+        }
+        marker.push(')');
+        marker
+    }
+}
+
+/// Entry fields of a memory marker line; `content` is left empty
+fn parse_memory_marker(line: &str) -> Option<MemoryEntry> {
+    let inner = line.trim().strip_prefix("[//]: # (")?.strip_suffix(')')?;
+    let mut entry = MemoryEntry {
+        id: String::new(),
+        created_at: String::new(),
+        expires_at: None,
+        tags: Vec::new(),
+        content: String::new(),
+    };
+    for part in inner.split_whitespace() {
+        if let Some(value) = part.strip_prefix("memory:id=") {
+            entry.id = value.to_string();
+        } else if let Some(value) = part.strip_prefix("created_at=") {
+            entry.created_at = value.to_string();
+        } else if let Some(value) = part.strip_prefix("expires_at=") {
+            entry.expires_at = Some(value.to_string());
+        } else if let Some(value) = part.strip_prefix("tags=") {
+            entry.tags = value
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+    }
+    (!entry.id.is_empty() && !entry.created_at.is_empty()).then_some(entry)
+}
+
+/// Entries of a memory document, in file order
+pub fn parse_memory_entries(content: &str) -> Vec<MemoryEntry> {
+    let mut entries: Vec<MemoryEntry> = Vec::new();
+    let mut buffer: Vec<&str> = Vec::new();
+    let flush = |entries: &mut Vec<MemoryEntry>, buffer: &mut Vec<&str>| {
+        if let Some(last) = entries.last_mut() {
+            last.content = buffer.join("\n").trim().to_string();
+        }
+        buffer.clear();
+    };
+    for line in content.lines() {
+        if let Some(entry) = parse_memory_marker(line) {
+            flush(&mut entries, &mut buffer);
+            entries.push(entry);
+        } else if !entries.is_empty() {
+            buffer.push(line);
+        }
+    }
+    flush(&mut entries, &mut buffer);
+    entries
+}
+
+/// Memory document holding `entries`
+pub fn render_memory_entries(entries: &[MemoryEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{}\n{}\n", e.marker(), e.content))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tags reduced to what fits a marker line and a `#tag`: no whitespace,
+/// commas or leading `#`, duplicates dropped
+pub(crate) fn clean_tags(tags: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag: String = tag
+            .trim()
+            .trim_start_matches('#')
+            .chars()
+            .map(|c| {
+                if c.is_whitespace() || c == ',' {
+                    '-'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        if !tag.is_empty() && !cleaned.contains(&tag) {
+            cleaned.push(tag);
+        }
+    }
+    cleaned
+}
+
+impl OpenContext {
+    /// Save `text` as a memory. `ttl` (`12h`, `30d`, `2w`) makes it expire;
+    /// expired memories are dropped from the file in the same write.
+    pub fn remember(
+        &self,
+        text: &str,
+        tags: &[String],
+        ttl: Option<&str>,
+    ) -> CoreResult<MemoryEntry> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(CoreError::Message("Memory text is required.".into()));
+        }
+        let now = Utc::now();
+        let expires_at = match ttl.map(str::trim).filter(|t| !t.is_empty()) {
+            Some(ttl) => {
+                let span = parse_span(ttl).ok_or_else(|| {
+                    CoreError::Message(format!(
+                        "invalid ttl {ttl:?} (expected e.g. 12h, 30d or 2w)"
+                    ))
+                })?;
+                Some((now + span).to_rfc3339_opts(SecondsFormat::Millis, true))
+            }
+            None => None,
+        };
+        let created_at = now_iso();
+        let entry = MemoryEntry {
+            id: content_revision(format!("{created_at}\n{text}").as_bytes())[..12].to_string(),
+            created_at,
+            expires_at,
+            tags: clean_tags(tags),
+            content: text.to_string(),
+        };
+
+        self.ensure_memory_doc()?;
+        self.edit_doc_content(MEMORY_DOC, |content| {
+            let mut entries = parse_memory_entries(content);
+            entries.retain(|e| !e.is_expired(now));
+            entries.push(entry.clone());
+            Ok(render_memory_entries(&entries))
+        })?;
+        Ok(entry)
+    }
+
+    /// Live memories, oldest first, optionally only those tagged `tag`
+    pub fn list_memories(&self, tag: Option<&str>) -> CoreResult<Vec<MemoryEntry>> {
+        let Some(doc) = self.find_doc(MEMORY_DOC)? else {
+            return Ok(Vec::new());
+        };
+        let now = Utc::now();
+        let tag = tag.map(|t| t.trim_start_matches('#'));
+        Ok(parse_memory_entries(&fs::read_to_string(&doc.abs_path)?)
+            .into_iter()
+            .filter(|e| !e.is_expired(now))
+            .filter(|e| tag.is_none_or(|tag| e.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))))
+            .collect())
+    }
+
+    /// Delete one memory; `false` if there was none with that id
+    pub fn forget(&self, id: &str) -> CoreResult<bool> {
+        let mut found = false;
+        self.rewrite_memories(|entries| {
+            let before = entries.len();
+            entries.retain(|e| e.id != id);
+            found = entries.len() < before;
+        })?;
+        Ok(found)
+    }
+
+    /// Drop expired memories from the file; returns how many were removed
+    pub fn purge_expired_memories(&self) -> CoreResult<usize> {
+        let now = Utc::now();
+        let mut removed = 0;
+        self.rewrite_memories(|entries| {
+            let before = entries.len();
+            entries.retain(|e| !e.is_expired(now));
+            removed = before - entries.len();
+        })?;
+        Ok(removed)
+    }
+
+    /// Apply `edit` to the stored memories, writing only if it changed them.
+    /// The write fails with [`CoreError::Conflict`] rather than drop a memory
+    /// saved in between.
+    fn rewrite_memories(&self, mut edit: impl FnMut(&mut Vec<MemoryEntry>)) -> CoreResult<()> {
+        let Some(doc) = self.find_doc(MEMORY_DOC)? else {
+            return Ok(());
+        };
+        let mut entries = parse_memory_entries(&fs::read_to_string(&doc.abs_path)?);
+        let before = entries.clone();
+        edit(&mut entries);
+        if entries == before {
+            return Ok(());
+        }
+        self.edit_doc_content(MEMORY_DOC, |content| {
+            let mut entries = parse_memory_entries(content);
+            edit(&mut entries);
+            Ok(render_memory_entries(&entries))
+        })?;
+        Ok(())
+    }
+
+    fn ensure_memory_doc(&self) -> CoreResult<()> {
+        if self.find_doc(MEMORY_DOC)?.is_some() {
+            return Ok(());
+        }
+        let folder = MEMORY_DIR.trim_end_matches('/');
+        if self.find_folder(folder)?.is_none() {
+            self.create_folder(folder, Some("Agent memory"))?;
+        }
+        let name = &MEMORY_DOC[MEMORY_DIR.len()..];
+        self.create_doc(folder, name, Some("Facts and preferences saved by agents"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_entries_round_trip() {
+        let content = "[//]: # (memory:id=a1 created_at=2026-10-01T00:00:00.000Z tags=prefs,style)\nPrefers tabs\nover spaces\n\n[//]: # (memory:id=b2 created_at=2026-10-02T00:00:00.000Z expires_at=2026-10-03T00:00:00.000Z)\nOn call this week\n";
+        let entries = parse_memory_entries(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tags, vec!["prefs", "style"]);
+        assert_eq!(entries[0].content, "Prefers tabs\nover spaces");
+        assert_eq!(
+            entries[1].expires_at.as_deref(),
+            Some("2026-10-03T00:00:00.000Z")
+        );
+        assert_eq!(
+            parse_memory_entries(&render_memory_entries(&entries)),
+            entries
+        );
+
+        let now = DateTime::parse_from_rfc3339("2026-10-03T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(!entries[0].is_expired(now));
+        assert!(entries[1].is_expired(now));
+
+        // Idea markers and stray text are not memories
+        assert!(parse_memory_entries("intro\n[//]: # (idea:id=x created_at=y)\ntext").is_empty());
+        assert_eq!(
+            clean_tags(&[
+                "#deploy".into(),
+                "on call".into(),
+                "a,b".into(),
+                "deploy".into()
+            ]),
+            vec!["deploy", "on-call", "a-b"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Three-way merge for markdown documents
+//!
+//! When a save is rejected because the file changed on disk, the caller
+//! still has the content it started from (`base`), its own edit (`ours`) and
+//! the current file (`theirs`). [`merge3`] combines the two edits line by
+//! line; regions both sides changed differently are kept with git-style
+//! conflict markers so nothing is lost.
+
+use serde::Serialize;
+
+/// Above this many DP cells the changed region is treated as one block
+/// instead of being diffed line by line
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergeResult {
+    pub merged: String,
+    /// Number of conflict blocks left in `merged`
+    pub conflicts: usize,
+}
+
+/// Merge `ours` and `theirs`, both derived from `base`
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> MergeResult {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+
+    // base index → matching line in ours / theirs
+    let mut in_ours = vec![None; base.len()];
+    for (b, o) in common_lines(&base, &ours) {
+        in_ours[b] = Some(o);
+    }
+    let mut in_theirs = vec![None; base.len()];
+    for (b, t) in common_lines(&base, &theirs) {
+        in_theirs[b] = Some(t);
+    }
+
+    let mut out = String::new();
+    let mut conflicts = 0;
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    loop {
+        // Next base line kept by both sides, or the end of all three
+        let stable = (i..base.len()).find_map(|b| Some((b, in_ours[b]?, in_theirs[b]?)));
+        let (b_end, o_end, t_end) = stable.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        let (b, o, t) = (&base[i..b_end], &ours[j..o_end], &theirs[k..t_end]);
+        if o == b || o == t {
+            push_lines(&mut out, t);
+        } else if t == b {
+            push_lines(&mut out, o);
+        } else {
+            conflicts += 1;
+            out.push_str("<<<<<<< ours\n");
+            push_block(&mut out, o);
+            out.push_str("=======\n");
+            push_block(&mut out, t);
+            out.push_str(">>>>>>> theirs\n");
+        }
+
+        if stable.is_none() {
+            break;
+        }
+        out.push_str(base[b_end]);
+        (i, j, k) = (b_end + 1, o_end + 1, t_end + 1);
+    }
+    MergeResult {
+        merged: out,
+        conflicts,
+    }
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    lines.iter().for_each(|line| out.push_str(line));
+}
+
+/// Lines inside conflict markers must end with a newline
+fn push_block(out: &mut String, lines: &[&str]) {
+    push_lines(out, lines);
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Index pairs of a longest common subsequence of `a` and `b`
+fn common_lines(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    if !a_mid.is_empty()
+        && !b_mid.is_empty()
+        && (a_mid.len() + 1) * (b_mid.len() + 1) <= MAX_DIFF_CELLS
+    {
+        let (n, m) = (a_mid.len(), b_mid.len());
+        // lcs[x][y] = LCS length of a_mid[x..] and b_mid[y..]
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        for x in (0..n).rev() {
+            for y in (0..m).rev() {
+                lcs[x * (m + 1) + y] = if a_mid[x] == b_mid[y] {
+                    lcs[(x + 1) * (m + 1) + y + 1] + 1
+                } else {
+                    lcs[(x + 1) * (m + 1) + y].max(lcs[x * (m + 1) + y + 1])
+                };
+            }
+        }
+        let (mut x, mut y) = (0, 0);
+        while x < n && y < m {
+            if a_mid[x] == b_mid[y] {
+                pairs.push((prefix + x, prefix + y));
+                x += 1;
+                y += 1;
+            } else if lcs[(x + 1) * (m + 1) + y] >= lcs[x * (m + 1) + y + 1] {
+                x += 1;
+            } else {
+                y += 1;
+            }
+        }
+    }
+    pairs.extend((0..suffix).map(|s| (a.len() - suffix + s, b.len() - suffix + s)));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "# Plan\n\nIntro\n\n## Steps\n\n- one\n- two\n";
+
+    #[test]
+    fn test_non_overlapping_edits_merge_cleanly() {
+        let ours = "# Plan\n\nIntro, revised\n\n## Steps\n\n- one\n- two\n";
+        let theirs = "# Plan\n\nIntro\n\n## Steps\n\n- one\n- two\n- three\n";
+        let result = merge3(BASE, ours, theirs);
+        assert_eq!(result.conflicts, 0);
+        assert_eq!(
+            result.merged,
+            "# Plan\n\nIntro, revised\n\n## Steps\n\n- one\n- two\n- three\n"
+        );
+    }
+
+    #[test]
+    fn test_overlapping_edits_conflict() {
+        let ours = "# Plan\n\nOur intro\n\n## Steps\n\n- one\n- two\n";
+        let theirs = "# Plan\n\nTheir intro\n\n## Steps\n\n- one\n- two";
+        let result = merge3(BASE, ours, theirs);
+        assert_eq!(result.conflicts, 1);
+        assert!(result
+            .merged
+            .contains("<<<<<<< ours\nOur intro\n=======\nTheir intro\n>>>>>>> theirs\n"));
+        assert!(result.merged.ends_with("- two"));
+
+        let same = merge3(BASE, ours, ours);
+        assert_eq!((same.merged.as_str(), same.conflicts), (ours, 0));
+    }
+}
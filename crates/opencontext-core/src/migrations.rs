@@ -13,6 +13,29 @@ const MIGRATIONS: &[(&str, &str)] = &[
         include_str!("migrations/002_stable_id.sql"),
     ),
     ("003_wal", include_str!("migrations/003_wal.sql")),
+    ("004_tasks", include_str!("migrations/004_tasks.sql")),
+    ("005_dates", include_str!("migrations/005_dates.sql")),
+    ("006_doc_hits", include_str!("migrations/006_doc_hits.sql")),
+    (
+        "007_path_moves",
+        include_str!("migrations/007_path_moves.sql"),
+    ),
+    (
+        "008_file_manifest",
+        include_str!("migrations/008_file_manifest.sql"),
+    ),
+    (
+        "009_source_state",
+        include_str!("migrations/009_source_state.sql"),
+    ),
+    (
+        "010_resurfaced",
+        include_str!("migrations/010_resurfaced.sql"),
+    ),
+    (
+        "011_flashcards",
+        include_str!("migrations/011_flashcards.sql"),
+    ),
 ];
 
 /// Apply all pending migrations in order.
@@ -47,6 +70,17 @@ pub fn run(conn: &Connection) -> CoreResult<()> {
 
         conn.execute_batch(sql)?;
 
+        // 004_tasks: existing docs already have checkboxes on disk
+        if *name == "004_tasks" {
+            crate::tasks::reindex_all(conn)?;
+        }
+        if *name == "005_dates" {
+            crate::dates::reindex_all(conn)?;
+        }
+        if *name == "011_flashcards" {
+            crate::flashcards::reindex_all(conn)?;
+        }
+
         conn.execute(
             "INSERT INTO schema_migrations (version, applied_at) VALUES (?1, ?2)",
             rusqlite::params![name, crate::now_iso()],
@@ -99,7 +133,10 @@ mod tests {
             })
             .expect("count rows");
 
-        assert_eq!(count, 3, "expected 3 migrations in schema_migrations table");
+        assert_eq!(
+            count, 11,
+            "expected 10 migrations in schema_migrations table"
+        );
     }
 
     #[test]
@@ -114,7 +151,10 @@ mod tests {
             })
             .expect("count rows");
 
-        assert_eq!(count, 3, "idempotency check: still 3 rows after second run");
+        assert_eq!(
+            count, 11,
+            "idempotency check: still 10 rows after second run"
+        );
     }
 
     #[test]
@@ -0,0 +1,13 @@
+-- Checkbox items parsed from doc content (see tasks.rs).
+-- Rebuilt per doc whenever its content is written through OpenContext.
+CREATE TABLE IF NOT EXISTS tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doc_id INTEGER NOT NULL REFERENCES docs(id) ON DELETE CASCADE,
+    line INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    done INTEGER NOT NULL DEFAULT 0,
+    due TEXT                -- YYYY-MM-DD, from due:/@due()/📅 in the text
+);
+
+CREATE INDEX IF NOT EXISTS idx_tasks_doc ON tasks(doc_id);
+CREATE INDEX IF NOT EXISTS idx_tasks_open_due ON tasks(done, due);
@@ -0,0 +1,13 @@
+-- Dates mentioned in doc content (see dates.rs).
+-- Rebuilt per doc whenever its content is written through OpenContext.
+CREATE TABLE IF NOT EXISTS doc_dates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doc_id INTEGER NOT NULL REFERENCES docs(id) ON DELETE CASCADE,
+    date TEXT NOT NULL,     -- YYYY-MM-DD
+    source TEXT NOT NULL,   -- frontmatter | entry | inline
+    line INTEGER NOT NULL,
+    context TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_doc_dates_doc ON doc_dates(doc_id);
+CREATE INDEX IF NOT EXISTS idx_doc_dates_date ON doc_dates(date);
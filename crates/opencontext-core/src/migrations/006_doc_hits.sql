@@ -0,0 +1,6 @@
+-- When docs were last opened or returned by a search (see staleness.rs).
+CREATE TABLE IF NOT EXISTS doc_hits (
+    doc_id INTEGER PRIMARY KEY REFERENCES docs(id) ON DELETE CASCADE,
+    hit_count INTEGER NOT NULL DEFAULT 0,
+    last_hit_at TEXT NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Doc and folder renames/moves, oldest first (see links.rs).
+-- Used to suggest where a dead link's target went.
+CREATE TABLE IF NOT EXISTS path_moves (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    old_path TEXT NOT NULL,
+    new_path TEXT NOT NULL,
+    moved_at TEXT NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Last-seen state of every Markdown file (see manifest.rs). Compared with
+-- the filesystem when index sync starts, to catch up on missed changes.
+CREATE TABLE IF NOT EXISTS file_manifest (
+    rel_path TEXT PRIMARY KEY,
+    mtime_ms INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    hash TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Sync progress of every configured source (see sources/mod.rs). `cursor`
+-- is the source's own position, e.g. the newest `updated_at` seen.
+CREATE TABLE IF NOT EXISTS source_state (
+    source TEXT PRIMARY KEY,
+    cursor TEXT,
+    synced_at TEXT NOT NULL,
+    docs INTEGER NOT NULL DEFAULT 0,
+    error TEXT
+);
@@ -0,0 +1,7 @@
+-- Notes brought back by resurfacing (see resurface.rs): how often each was
+-- shown and when last.
+CREATE TABLE IF NOT EXISTS resurfaced (
+    doc_id INTEGER PRIMARY KEY REFERENCES docs(id) ON DELETE CASCADE,
+    times INTEGER NOT NULL DEFAULT 0,
+    last_shown_at TEXT NOT NULL
+);
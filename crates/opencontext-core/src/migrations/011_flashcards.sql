@@ -0,0 +1,30 @@
+-- Flashcards parsed from doc content, with their SM-2 schedule (see
+-- flashcards.rs). Cards are upserted per doc on every write, so a card keeps
+-- its schedule while its note is edited around it.
+CREATE TABLE IF NOT EXISTS cards (
+    id TEXT PRIMARY KEY,            -- fingerprint of the card's kind and front
+    doc_id INTEGER NOT NULL REFERENCES docs(id) ON DELETE CASCADE,
+    line INTEGER NOT NULL,
+    kind TEXT NOT NULL,             -- basic | cloze
+    front TEXT NOT NULL,
+    back TEXT NOT NULL,
+    ease REAL NOT NULL DEFAULT 2.5,
+    interval_days INTEGER NOT NULL DEFAULT 0,
+    repetitions INTEGER NOT NULL DEFAULT 0,
+    due TEXT NOT NULL               -- YYYY-MM-DD
+);
+
+CREATE INDEX IF NOT EXISTS idx_cards_doc ON cards(doc_id);
+CREATE INDEX IF NOT EXISTS idx_cards_due ON cards(due);
+
+-- Every answer given in review. Kept when a card is deleted from its note.
+CREATE TABLE IF NOT EXISTS card_reviews (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    card_id TEXT NOT NULL,
+    reviewed_at TEXT NOT NULL,
+    grade INTEGER NOT NULL,         -- 0-5
+    interval_days INTEGER NOT NULL, -- interval scheduled by this answer
+    ease REAL NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_card_reviews_card ON card_reviews(card_id);
@@ -0,0 +1,351 @@
+//! Note names
+//!
+//! People refer to a note by what it is called rather than where it is
+//! filed: its `title` frontmatter field or `# heading`, or one of the
+//! `aliases` listed in its frontmatter. [`NameIndex`] maps those names back
+//! to document paths, exactly for `[[wiki-links]]` and fuzzily for
+//! `oc open <name>` and the `oc_resolve_note` MCP tool. [`display_names`]
+//! picks the names search results are shown under.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::embeds::heading_level;
+use crate::{frontmatter, relpath, CoreResult, OpenContext};
+
+/// Matches scoring below this are not returned by [`NameIndex::search`]
+const MIN_SCORE: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameKind {
+    Title,
+    Alias,
+    /// File name without `.md`
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoteMatch {
+    pub rel_path: String,
+    /// The name that matched, as written
+    pub name: String,
+    pub kind: NameKind,
+    /// 1 for an exact match, down to 0.4
+    pub score: f32,
+}
+
+/// `title` frontmatter, else the first `# heading`, and the `aliases` (or
+/// `alias`) listed in frontmatter, as a list or comma-separated
+pub fn note_names(content: &str) -> (Option<String>, Vec<String>) {
+    let fields = frontmatter::fields(content);
+    let title = match fields.get("title") {
+        Some(Value::String(title)) if !title.trim().is_empty() => Some(title.trim().to_string()),
+        _ => first_h1(frontmatter::body(content)),
+    };
+    let aliases = match fields.get("aliases").or_else(|| fields.get("alias")) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        Some(Value::String(list)) => list.split(',').map(str::to_string).collect(),
+        _ => Vec::new(),
+    };
+    let aliases = aliases
+        .into_iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    (title, aliases)
+}
+
+fn first_h1(body: &str) -> Option<String> {
+    let mut fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = !fence;
+        } else if !fence && heading_level(trimmed) == 1 {
+            let text = trimmed[1..].trim();
+            return (!text.is_empty()).then(|| text.to_string());
+        }
+    }
+    None
+}
+
+/// Lowercased, whitespace collapsed, so `Design  Notes` finds `design notes`
+pub(crate) fn name_key(name: &str) -> String {
+    let name = name.trim();
+    let name = name.strip_suffix(".md").unwrap_or(name);
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// How well `query` matches `name`, both as [`name_key`]s, from 0 to 1
+fn similarity(query: &str, name: &str) -> f32 {
+    if query == name {
+        return 1.0;
+    }
+    if name.starts_with(query) {
+        return 0.9;
+    }
+    if name.contains(query) {
+        return 0.8;
+    }
+    let words: Vec<&str> = name.split(' ').collect();
+    if query
+        .split(' ')
+        .all(|q| words.iter().any(|w| w.starts_with(q)))
+    {
+        return 0.75;
+    }
+    // Dice coefficient of character bigrams, for typos and word order
+    let bigrams = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().filter(|c| *c != ' ').collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (a, mut b) = (bigrams(query), bigrams(name));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let total = a.len() + b.len();
+    let mut shared = 0;
+    for pair in a {
+        if let Some(at) = b.iter().position(|p| *p == pair) {
+            b.swap_remove(at);
+            shared += 1;
+        }
+    }
+    0.7 * (2 * shared) as f32 / total as f32
+}
+
+/// Names that tell apart the documents or folders at `paths`, each given
+/// with its frontmatter title if it has one. A path is called by its title,
+/// else by its file name without `.md`; paths left sharing a name are
+/// called by as many trailing path segments as it takes to tell them apart,
+/// so `alpha/notes.md` and `beta/notes.md` show as `alpha/notes` and
+/// `beta/notes`.
+pub fn display_names<'a>(
+    paths: impl IntoIterator<Item = (&'a str, Option<&'a str>)>,
+) -> HashMap<&'a str, String> {
+    let mut names: HashMap<&str, String> = HashMap::new();
+    for (path, title) in paths {
+        let name = match title.map(str::trim) {
+            Some(title) if !title.is_empty() => title.to_string(),
+            _ => relpath::display_name(path).to_string(),
+        };
+        names.insert(path, name);
+    }
+    let mut by_key: HashMap<String, Vec<&str>> = HashMap::new();
+    for (path, name) in &names {
+        by_key.entry(name_key(name)).or_default().push(path);
+    }
+    for paths in by_key.into_values().filter(|paths| paths.len() > 1) {
+        let segments: Vec<Vec<&str>> = paths
+            .iter()
+            .map(|path| path.trim_end_matches(".md").split('/').collect())
+            .collect();
+        let suffix = |segs: &[&str], n: usize| segs[segs.len().saturating_sub(n)..].join("/");
+        for (path, segs) in paths.iter().zip(&segments) {
+            let unique = (1..segs.len()).find(|&n| {
+                let own = suffix(segs, n).to_lowercase();
+                segments
+                    .iter()
+                    .filter(|other| *other != segs)
+                    .all(|other| suffix(other, n).to_lowercase() != own)
+            });
+            names.insert(path, suffix(segs, unique.unwrap_or(segs.len())));
+        }
+    }
+    names
+}
+
+/// Titles, aliases and file names of a set of documents
+#[derive(Debug, Default)]
+pub struct NameIndex {
+    /// `(name, key, rel_path, kind)`
+    names: Vec<(String, String, String, NameKind)>,
+}
+
+impl NameIndex {
+    /// Add the names of the document at `rel_path` with text `content`
+    pub fn add(&mut self, rel_path: &str, content: &str) {
+        let (title, aliases) = note_names(content);
+        let file = relpath::display_name(rel_path);
+        let file = file.strip_suffix(".md").unwrap_or(file).to_string();
+        let names = title
+            .map(|t| (t, NameKind::Title))
+            .into_iter()
+            .chain(aliases.into_iter().map(|a| (a, NameKind::Alias)))
+            .chain([(file, NameKind::File)]);
+        for (name, kind) in names {
+            let key = name_key(&name);
+            if !key.is_empty() {
+                self.names.push((name, key, rel_path.to_string(), kind));
+            }
+        }
+    }
+
+    /// Titles and aliases by [`name_key`], for resolving wiki-links. A name
+    /// several documents share goes to the one with the shortest path.
+    pub fn exact(&self) -> HashMap<String, String> {
+        let mut out: HashMap<String, String> = HashMap::new();
+        for (_, key, rel_path, kind) in &self.names {
+            if *kind == NameKind::File {
+                continue;
+            }
+            let entry = out.entry(key.clone()).or_insert_with(|| rel_path.clone());
+            if (rel_path.matches('/').count(), rel_path) < (entry.matches('/').count(), &*entry) {
+                *entry = rel_path.clone();
+            }
+        }
+        out
+    }
+
+    /// Documents whose names match `query`, best first, one match (the
+    /// best-scoring name) per document
+    pub fn search(&self, query: &str, limit: usize) -> Vec<NoteMatch> {
+        let query = name_key(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut best: HashMap<&str, NoteMatch> = HashMap::new();
+        for (name, key, rel_path, kind) in &self.names {
+            let score = similarity(&query, key);
+            if score < MIN_SCORE {
+                continue;
+            }
+            let better = best.get(rel_path.as_str()).is_none_or(|m| score > m.score);
+            if better {
+                best.insert(
+                    rel_path,
+                    NoteMatch {
+                        rel_path: rel_path.clone(),
+                        name: name.clone(),
+                        kind: *kind,
+                        score,
+                    },
+                );
+            }
+        }
+        let mut matches: Vec<NoteMatch> = best.into_values().collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.rel_path.len().cmp(&b.rel_path.len()))
+                .then_with(|| a.rel_path.cmp(&b.rel_path))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+impl OpenContext {
+    /// Names of every document in the workspace
+    pub fn name_index(&self) -> CoreResult<NameIndex> {
+        let docs = self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT rel_path, abs_path FROM docs ORDER BY rel_path")?;
+            let docs = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<(String, String)>, _>>()?;
+            Ok(docs)
+        })?;
+        let mut index = NameIndex::default();
+        for (rel_path, abs_path) in docs {
+            index.add(&rel_path, &fs::read_to_string(abs_path).unwrap_or_default());
+        }
+        Ok(index)
+    }
+
+    /// Documents called `query` (by title, alias or file name), best first
+    pub fn resolve_note(&self, query: &str, limit: usize) -> CoreResult<Vec<NoteMatch>> {
+        Ok(self.name_index()?.search(query, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_names() {
+        let content = "---\ntitle: Q3 Roadmap\naliases: [roadmap, \"plan 2026\"]\n---\n# Heading\n";
+        assert_eq!(
+            note_names(content),
+            (
+                Some("Q3 Roadmap".into()),
+                vec!["roadmap".into(), "plan 2026".into()]
+            )
+        );
+        let content = "```\n# not a title\n```\n# Meeting Notes\n";
+        assert_eq!(note_names(content), (Some("Meeting Notes".into()), vec![]));
+        assert_eq!(
+            note_names("---\nalias: a, b\n---\ntext\n"),
+            (None, vec!["a".into(), "b".into()])
+        );
+    }
+
+    #[test]
+    fn test_name_index() {
+        let mut index = NameIndex::default();
+        index.add(
+            "work/q3.md",
+            "---\naliases: [roadmap]\n---\n# Q3 Product Roadmap\n",
+        );
+        index.add("roadmap-archive.md", "# Old plans\n");
+        index.add("people/alice.md", "# Alice Liddell\n");
+
+        let exact = index.exact();
+        assert_eq!(exact["q3 product roadmap"], "work/q3.md");
+        assert_eq!(exact["roadmap"], "work/q3.md");
+        assert!(!exact.contains_key("q3"));
+
+        let found = |q: &str| -> Vec<(String, NameKind)> {
+            index
+                .search(q, 5)
+                .into_iter()
+                .map(|m| (m.rel_path, m.kind))
+                .collect()
+        };
+        assert_eq!(
+            found("Roadmap"),
+            vec![
+                ("work/q3.md".into(), NameKind::Alias),
+                ("roadmap-archive.md".into(), NameKind::File),
+            ]
+        );
+        assert_eq!(
+            found("product road"),
+            vec![("work/q3.md".into(), NameKind::Title)]
+        );
+        assert_eq!(
+            found("alice liddel"),
+            vec![("people/alice.md".into(), NameKind::Title)]
+        );
+        assert_eq!(found("zzz"), vec![]);
+    }
+
+    #[test]
+    fn test_display_names() {
+        let names = display_names([
+            ("work/alpha/notes.md", None),
+            ("work/beta/notes.md", None),
+            ("archive/beta/notes.md", None),
+            ("notes.md", None),
+            ("work/q3.md", Some("Roadmap")),
+            ("plans/roadmap.md", Some(" ")),
+            ("people/alice.md", Some("Alice Liddell")),
+        ]);
+        assert_eq!(names["work/alpha/notes.md"], "alpha/notes");
+        assert_eq!(names["work/beta/notes.md"], "work/beta/notes");
+        assert_eq!(names["archive/beta/notes.md"], "archive/beta/notes");
+        assert_eq!(names["notes.md"], "notes");
+        assert_eq!(names["work/q3.md"], "q3");
+        assert_eq!(names["plans/roadmap.md"], "roadmap");
+        assert_eq!(names["people/alice.md"], "Alice Liddell");
+    }
+}
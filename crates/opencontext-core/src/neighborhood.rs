@@ -0,0 +1,202 @@
+//! Line neighborhoods
+//!
+//! A search hit points at a few lines of a note. To read around it, an
+//! agent asks for the lines `before` and `after` the hit
+//! ([`OpenContext::get_context`]) and gets them with the headings they sit
+//! under and the text right above and below the window, each cut at a
+//! heading. Asking again with wider bounds grows the window by whole
+//! sections, without reading the whole note into context.
+
+use serde::Serialize;
+
+use crate::embeds::heading_level;
+use crate::{CoreError, CoreResult, OpenContext};
+
+/// Part of a section next to a window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Section {
+    /// Headings the text sits under, outermost first, joined by " > "
+    pub heading_path: String,
+    pub text: String,
+    /// 1-based, inclusive
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineContext {
+    /// The requested lines
+    pub text: String,
+    /// 1-based, inclusive, clamped to the document
+    pub line_start: usize,
+    pub line_end: usize,
+    /// Headings enclosing the requested line, joined by " > "; empty above
+    /// the first heading
+    pub heading_path: String,
+    /// From the start of the section holding the text just above the
+    /// window to the last non-blank line before it
+    pub previous: Option<Section>,
+    /// From the first non-blank line after the window to the end of its
+    /// section
+    pub next: Option<Section>,
+    /// Lines in the document
+    pub line_count: usize,
+}
+
+/// Lines `line - before ..= line + after` (1-based) of `content` and what
+/// surrounds them, or `None` when `line` is not in `content`. Headings in
+/// frontmatter and code fences don't count.
+pub fn line_context(
+    content: &str,
+    line: usize,
+    before: usize,
+    after: usize,
+) -> Option<LineContext> {
+    let lines: Vec<&str> = content.lines().collect();
+    if line == 0 || line > lines.len() {
+        return None;
+    }
+    let header = &content[..content.len() - crate::frontmatter::body(content).len()];
+    let skip = header.matches('\n').count();
+
+    // Heading path at every line, and the first line of its section
+    let mut paths: Vec<String> = Vec::with_capacity(lines.len());
+    let mut starts: Vec<usize> = Vec::with_capacity(lines.len());
+    let mut headings: Vec<(usize, &str)> = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut start = 0;
+    for (idx, text) in lines.iter().enumerate() {
+        let trimmed = text.trim_start();
+        if idx == skip {
+            start = skip;
+        }
+        if idx >= skip {
+            if let Some(open) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+                fence = match fence {
+                    Some(current) if current == open => None,
+                    None => Some(open),
+                    other => other,
+                };
+            } else if fence.is_none() {
+                let level = heading_level(trimmed);
+                if level > 0 {
+                    headings.retain(|(outer, _)| *outer < level);
+                    headings.push((
+                        level,
+                        trimmed[level..].trim().trim_end_matches('#').trim_end(),
+                    ));
+                    start = idx;
+                }
+            }
+        }
+        paths.push(
+            headings
+                .iter()
+                .map(|(_, title)| *title)
+                .collect::<Vec<_>>()
+                .join(" > "),
+        );
+        starts.push(start);
+    }
+
+    let blank = |idx: usize| lines[idx].trim().is_empty();
+    let section = |first: usize, last: usize| Section {
+        heading_path: paths[first].clone(),
+        text: lines[first..=last].join("\n"),
+        line_start: first + 1,
+        line_end: last + 1,
+    };
+    let first = (line - 1).saturating_sub(before);
+    let last = (line - 1).saturating_add(after).min(lines.len() - 1);
+
+    let previous = (skip..first).rev().find(|&idx| !blank(idx)).map(|end| {
+        let begin = (starts[end]..=end).find(|&idx| !blank(idx)).unwrap_or(end);
+        section(begin, end)
+    });
+    let next = (last + 1..lines.len())
+        .find(|&idx| !blank(idx))
+        .map(|begin| {
+            let end = (begin..lines.len())
+                .take_while(|&idx| starts[idx] == starts[begin])
+                .filter(|&idx| !blank(idx))
+                .last()
+                .unwrap_or(begin);
+            section(begin, end)
+        });
+
+    Some(LineContext {
+        text: lines[first..=last].join("\n"),
+        line_start: first + 1,
+        line_end: last + 1,
+        heading_path: paths[line - 1].clone(),
+        previous,
+        next,
+        line_count: lines.len(),
+    })
+}
+
+impl OpenContext {
+    /// Lines `line - before ..= line + after` of the document at
+    /// `doc_path`, with their heading path and the sections around them
+    pub fn get_context(
+        &self,
+        doc_path: &str,
+        line: usize,
+        before: usize,
+        after: usize,
+    ) -> CoreResult<LineContext> {
+        let content = self.get_doc_content(doc_path)?;
+        line_context(&content, line, before, after).ok_or_else(|| {
+            CoreError::Message(format!(
+                "Line {line} is out of range in \"{doc_path}\" ({} lines).",
+                content.lines().count()
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_context() {
+        let content = "---\ntitle: Plan\n---\nIntro\n\n# Plan\n\
+                       ## Goals\nShip it\nin May\n\n\
+                       ```\n# not a heading\n```\n\
+                       ## Risks\nScope\n\n## Notes ##\nNone\n";
+        let context = line_context(content, 9, 1, 0).unwrap();
+        assert_eq!(context.text, "Ship it\nin May");
+        assert_eq!((context.line_start, context.line_end), (8, 9));
+        assert_eq!(context.heading_path, "Plan > Goals");
+        assert_eq!(
+            context.previous,
+            Some(Section {
+                heading_path: "Plan > Goals".into(),
+                text: "## Goals".into(),
+                line_start: 7,
+                line_end: 7,
+            })
+        );
+        let next = context.next.unwrap();
+        assert_eq!(next.text, "```\n# not a heading\n```");
+        assert_eq!((next.line_start, next.line_end), (11, 13));
+        assert_eq!(context.line_count, 18);
+
+        // The window stops at the document's ends
+        let context = line_context(content, 15, 2, 10).unwrap();
+        assert_eq!((context.line_start, context.line_end), (13, 18));
+        assert_eq!(context.heading_path, "Plan > Risks");
+        assert_eq!(context.previous.unwrap().line_start, 7);
+        assert_eq!(context.next, None);
+
+        let context = line_context(content, 4, 0, 0).unwrap();
+        assert_eq!(
+            (context.heading_path.as_str(), context.previous),
+            ("", None)
+        );
+        assert_eq!(context.next.unwrap().text, "# Plan");
+        assert!(line_context(content, 19, 0, 0).is_none());
+        assert!(line_context(content, 0, 0, 0).is_none());
+    }
+}
@@ -0,0 +1,494 @@
+//! Note app imports
+//!
+//! Moves notes out of Evernote (`.enex` exports, one per notebook) and Apple
+//! Notes (read through `osascript`, macOS only) into the docs tree. Each
+//! notebook or Apple Notes folder becomes a folder and each note a doc with
+//! its creation and update dates, tags and source URL in frontmatter.
+//! Evernote attachments are written next to the notes under
+//! `_attachments/<note>/` and linked from where they appeared; checklists
+//! become tasks.
+//!
+//! Notes are matched by title, so importing an export again updates the
+//! notes written the first time instead of adding copies.
+
+use base64::Engine;
+use md5::{Digest, Md5};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::memory::clean_tags;
+use crate::{frontmatter, html, relpath, CoreError, CoreResult, OpenContext};
+
+/// Folder, next to the notes of a notebook, holding their attachments
+const ATTACHMENTS_DIR: &str = "_attachments";
+
+/// A file embedded in a note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub file_name: String,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+/// One note read from a note app
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedNote {
+    /// Evernote notebook or Apple Notes folder
+    pub notebook: String,
+    pub title: String,
+    /// Note content as HTML (ENML for Evernote)
+    pub html: String,
+    /// RFC 3339 timestamps
+    pub created: Option<String>,
+    pub updated: Option<String>,
+    pub tags: Vec<String>,
+    pub author: Option<String>,
+    pub source_url: Option<String>,
+    /// Referenced from `html` as `<en-media hash="<md5>">`
+    pub attachments: Vec<Attachment>,
+}
+
+/// What [`OpenContext::import_notes`] wrote
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotesImport {
+    /// `evernote` or `apple-notes`
+    pub source: String,
+    /// Folder the notebooks were written to
+    pub folder: String,
+    /// Documents created
+    pub created: Vec<String>,
+    /// Existing documents that changed
+    pub updated: Vec<String>,
+    /// Documents that were already up to date
+    pub unchanged: usize,
+    pub notes: usize,
+    /// Attachment files written
+    pub attachments: usize,
+}
+
+/// Text of the first `<name>` element in `xml`
+fn xml_child<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = xml[start..].find(&format!("</{name}>"))?;
+    Some(&xml[start..start + end])
+}
+
+/// Every `<name>...</name>` (or `<name attr="...">`) element in `xml`
+fn xml_children<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut out = Vec::new();
+    let mut rest = xml;
+    let close = format!("</{name}>");
+    while let Some(at) = rest.find(&format!("<{name}")) {
+        let after = &rest[at + name.len() + 1..];
+        if !after.starts_with(['>', ' ']) {
+            rest = after;
+            continue;
+        }
+        let Some(open_end) = after.find('>') else {
+            break;
+        };
+        let body = &after[open_end + 1..];
+        let Some(end) = body.find(&close) else {
+            break;
+        };
+        out.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    out
+}
+
+/// Decoded text of an element that may be a CDATA section
+fn xml_text(raw: &str) -> String {
+    let raw = raw.trim();
+    match raw
+        .strip_prefix("<![CDATA[")
+        .and_then(|r| r.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_string(),
+        None => html::decode_entities(raw),
+    }
+}
+
+/// `20130730T205204Z` as `2013-07-30T20:52:04Z`
+fn enex_time(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.len() < 15 || !raw.is_char_boundary(15) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        &raw[0..4],
+        &raw[4..6],
+        &raw[6..8],
+        &raw[9..11],
+        &raw[11..13],
+        &raw[13..15]
+    ))
+}
+
+/// Notes of an Evernote export; `notebook` names the notebook, usually the
+/// file name, as exports do not record it
+pub fn parse_enex(xml: &str, notebook: &str) -> CoreResult<Vec<ImportedNote>> {
+    if !xml.contains("<en-export") {
+        return Err(CoreError::Message(
+            "not an Evernote export (no <en-export> element)".into(),
+        ));
+    }
+    let mut notes = Vec::new();
+    for note in xml_children(xml, "note") {
+        let title = xml_child(note, "title").map(xml_text).unwrap_or_default();
+        let content = xml_child(note, "content").map(xml_text).unwrap_or_default();
+        let html = html::element(&content, "<en-note")
+            .unwrap_or(&content)
+            .to_string();
+        let attributes = xml_child(note, "note-attributes").unwrap_or("");
+        let mut attachments = Vec::new();
+        for resource in xml_children(note, "resource") {
+            let Some(data) = xml_children(resource, "data")
+                .first()
+                .map(|d| d.chars().filter(|c| !c.is_whitespace()).collect::<String>())
+            else {
+                continue;
+            };
+            let Ok(data) = base64::engine::general_purpose::STANDARD.decode(data) else {
+                continue;
+            };
+            let mime = xml_child(resource, "mime")
+                .map(xml_text)
+                .unwrap_or_default();
+            let file_name = xml_child(resource, "file-name").map(xml_text);
+            attachments.push(Attachment {
+                file_name: file_name.unwrap_or_default(),
+                mime,
+                data,
+            });
+        }
+        notes.push(ImportedNote {
+            notebook: notebook.to_string(),
+            title: title.trim().to_string(),
+            html,
+            created: xml_child(note, "created").and_then(enex_time),
+            updated: xml_child(note, "updated").and_then(enex_time),
+            tags: xml_children(note, "tag")
+                .into_iter()
+                .map(xml_text)
+                .collect(),
+            author: xml_child(attributes, "author").map(xml_text),
+            source_url: xml_child(attributes, "source-url").map(xml_text),
+            attachments,
+        });
+    }
+    Ok(notes)
+}
+
+/// Notes of an `.enex` file, the notebook named after the file
+pub fn read_enex(path: &Path) -> CoreResult<Vec<ImportedNote>> {
+    let notebook = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    parse_enex(&fs::read_to_string(path)?, &notebook)
+}
+
+/// JavaScript for Automation printing every Apple Note as JSON
+const APPLE_NOTES_SCRIPT: &str = r#"
+const notes = [];
+for (const folder of Application('Notes').folders()) {
+  if (folder.name() === 'Recently Deleted') continue;
+  for (const note of folder.notes()) {
+    notes.push({
+      notebook: folder.name(),
+      title: note.name(),
+      html: note.body(),
+      created: note.creationDate().toISOString(),
+      updated: note.modificationDate().toISOString(),
+    });
+  }
+}
+JSON.stringify(notes);
+"#;
+
+/// Every note in Apple Notes, attachments left out (macOS only; the first
+/// run asks for permission to control Notes)
+pub fn read_apple_notes() -> CoreResult<Vec<ImportedNote>> {
+    if !cfg!(target_os = "macos") {
+        return Err(CoreError::Message(
+            "Apple Notes can only be read on macOS".into(),
+        ));
+    }
+    #[derive(serde::Deserialize)]
+    struct AppleNote {
+        notebook: String,
+        title: String,
+        html: String,
+        created: Option<String>,
+        updated: Option<String>,
+    }
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", APPLE_NOTES_SCRIPT])
+        .output()
+        .map_err(|e| CoreError::Message(format!("could not run osascript: {e}")))?;
+    if !output.status.success() {
+        return Err(CoreError::Message(format!(
+            "reading Apple Notes failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let notes: Vec<AppleNote> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| CoreError::Message(format!("unexpected Apple Notes output: {e}")))?;
+    Ok(notes
+        .into_iter()
+        .map(|note| ImportedNote {
+            notebook: note.notebook,
+            title: note.title,
+            html: note.html,
+            created: note.created,
+            updated: note.updated,
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// Extension for an attachment without a file name
+fn extension(mime: &str) -> &str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "application/pdf" => "pdf",
+        "audio/mpeg" => "mp3",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "text/plain" => "txt",
+        _ => "bin",
+    }
+}
+
+/// File name for `attachment`: safe characters only, unique within `taken`
+fn attachment_name(attachment: &Attachment, taken: &mut Vec<String>) -> String {
+    let name = attachment.file_name.trim();
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (relpath::slug(stem), relpath::slug(ext)),
+        _ => (relpath::slug(name), extension(&attachment.mime).to_string()),
+    };
+    let stem = if stem.is_empty() {
+        "attachment".to_string()
+    } else {
+        stem
+    };
+    let mut candidate = format!("{stem}.{ext}");
+    let mut n = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{stem}-{n}.{ext}");
+        n += 1;
+    }
+    taken.push(candidate.clone());
+    candidate
+}
+
+/// Markdown of `note`, `<en-media>` pointing at `links` (by MD5 hash) and
+/// Evernote checkboxes turned into task items
+fn note_markdown(note: &ImportedNote, links: &HashMap<String, (String, bool)>) -> String {
+    let html = html::replace_tags(&note.html, "en-media", |tag| {
+        if tag.closing {
+            return String::new();
+        }
+        let hash = tag.attr("hash").unwrap_or_default().to_lowercase();
+        match links.get(&hash) {
+            Some((path, true)) => format!(r#"<img src="{path}" alt="">"#),
+            Some((path, false)) => {
+                let name = path.rsplit('/').next().unwrap_or(path);
+                format!(r#"<a href="{path}">{name}</a>"#)
+            }
+            None => String::new(),
+        }
+    });
+    let html = html::replace_tags(&html, "en-todo", |tag| {
+        if tag.closing {
+            String::new()
+        } else if tag.attr("checked").as_deref() == Some("true") {
+            r#"<input type="checkbox" checked>"#.to_string()
+        } else {
+            r#"<input type="checkbox">"#.to_string()
+        }
+    });
+    html::to_markdown(&html)
+}
+
+fn render_note(source: &str, note: &ImportedNote, markdown: &str) -> CoreResult<String> {
+    let title = if note.title.is_empty() {
+        "Untitled"
+    } else {
+        &note.title
+    };
+    let content = if markdown.trim().is_empty() {
+        format!("\n# {title}\n")
+    } else {
+        format!("\n# {title}\n\n{}", markdown.trim_start())
+    };
+    let day = |t: &Option<String>| t.as_deref().map(|t| Value::from(t.get(0..10).unwrap_or(t)));
+    let tags = clean_tags(&note.tags);
+    let fields = [
+        ("title", Some(Value::from(title))),
+        ("source", Some(Value::from(source))),
+        ("notebook", Some(Value::from(note.notebook.as_str()))),
+        ("created", day(&note.created)),
+        ("updated", day(&note.updated)),
+        ("author", note.author.as_deref().map(Value::from)),
+        ("url", note.source_url.as_deref().map(Value::from)),
+        ("tags", (!tags.is_empty()).then(|| Value::from(tags))),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .try_fold(content, |content, (key, value)| {
+            frontmatter::set_field(&content, key, &value)
+        })
+}
+
+impl OpenContext {
+    /// Write `notes` read from `source` (`evernote`, `apple-notes`) under
+    /// `folder`, one folder per notebook, attachments included
+    pub fn import_notes(
+        &self,
+        source: &str,
+        folder: &str,
+        notes: &[ImportedNote],
+    ) -> CoreResult<NotesImport> {
+        let folder = folder.trim_matches('/').to_string();
+        let mut import = NotesImport {
+            source: source.to_string(),
+            folder: folder.clone(),
+            notes: notes.len(),
+            ..Default::default()
+        };
+        // Titles already used per notebook folder in this import
+        let mut taken: HashMap<String, Vec<String>> = HashMap::new();
+        for note in notes {
+            let notebook = match relpath::slug(&note.notebook) {
+                slug if slug.is_empty() => "notes".to_string(),
+                slug => slug,
+            };
+            let notebook_folder = if folder.is_empty() {
+                notebook
+            } else {
+                format!("{folder}/{notebook}")
+            };
+            let names = taken.entry(notebook_folder.clone()).or_default();
+            let base = match relpath::slug(&note.title) {
+                slug if slug.is_empty() => "untitled".to_string(),
+                slug => slug,
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while names.contains(&name) {
+                name = format!("{base}-{n}");
+                n += 1;
+            }
+            names.push(name.clone());
+
+            let mut links = HashMap::new();
+            if !note.attachments.is_empty() {
+                if self.find_folder(&notebook_folder)?.is_none() {
+                    self.create_folder(&notebook_folder, None)?;
+                }
+                let folder_abs = self
+                    .find_folder(&notebook_folder)?
+                    .map(|f| f.abs_path)
+                    .ok_or_else(|| {
+                        CoreError::Message(format!("cannot create {notebook_folder}"))
+                    })?;
+                let dir = folder_abs.join(ATTACHMENTS_DIR).join(&name);
+                fs::create_dir_all(&dir)?;
+                let mut files = Vec::new();
+                for attachment in &note.attachments {
+                    let file = attachment_name(attachment, &mut files);
+                    let path = dir.join(&file);
+                    if fs::read(&path).ok().as_deref() != Some(attachment.data.as_slice()) {
+                        fs::write(&path, &attachment.data)?;
+                        import.attachments += 1;
+                    }
+                    let hash = format!("{:x}", Md5::digest(&attachment.data));
+                    let is_image = attachment.mime.starts_with("image/");
+                    links.insert(hash, (format!("{ATTACHMENTS_DIR}/{name}/{file}"), is_image));
+                }
+            }
+
+            let rel_path = format!("{notebook_folder}/{name}.md");
+            let content = render_note(source, note, &note_markdown(note, &links))?;
+            match self.put_generated_doc(&rel_path, &content)? {
+                Some(true) => import.created.push(rel_path),
+                Some(false) => import.updated.push(rel_path),
+                None => import.unchanged += 1,
+            }
+        }
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENEX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export3.dtd">
+<en-export export-date="20240101T000000Z" application="Evernote">
+  <note>
+    <title>Trip &amp; packing</title>
+    <content><![CDATA[<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note><div><en-todo checked="true"/>Passport</div><div><en-todo/>Charger</div><div><en-media hash="5d41402abc4b2a76b9719d911017c592" type="image/png"/></div></en-note>]]></content>
+    <created>20130730T205204Z</created>
+    <updated>20130801T101010Z</updated>
+    <tag>travel</tag>
+    <tag>to do</tag>
+    <note-attributes><author>Ada</author><source-url>https://example.com/trip</source-url></note-attributes>
+    <resource>
+      <data encoding="base64">aGVs
+bG8=</data>
+      <mime>image/png</mime>
+      <resource-attributes><file-name>Map View.PNG</file-name></resource-attributes>
+    </resource>
+  </note>
+</en-export>"#;
+
+    #[test]
+    fn test_parse_enex() {
+        let notes = parse_enex(ENEX, "Personal").unwrap();
+        assert_eq!(notes.len(), 1);
+        let note = &notes[0];
+        assert_eq!(note.title, "Trip & packing");
+        assert_eq!(note.created.as_deref(), Some("2013-07-30T20:52:04Z"));
+        assert_eq!(note.tags, vec!["travel", "to do"]);
+        assert_eq!(note.author.as_deref(), Some("Ada"));
+        assert_eq!(note.attachments[0].data, b"hello");
+        assert_eq!(note.attachments[0].file_name, "Map View.PNG");
+        assert!(parse_enex("<html/>", "x").is_err());
+
+        let mut taken = Vec::new();
+        assert_eq!(
+            attachment_name(&note.attachments[0], &mut taken),
+            "map-view.png"
+        );
+        assert_eq!(
+            attachment_name(&note.attachments[0], &mut taken),
+            "map-view-2.png"
+        );
+    }
+
+    #[test]
+    fn test_note_markdown_links_attachments() {
+        let note = &parse_enex(ENEX, "Personal").unwrap()[0];
+        let links = HashMap::from([(
+            "5d41402abc4b2a76b9719d911017c592".to_string(),
+            ("_attachments/trip-packing/map-view.png".to_string(), true),
+        )]);
+        assert_eq!(
+            note_markdown(note, &links),
+            "- [x] Passport\n\n- [ ] Charger\n\n![](_attachments/trip-packing/map-view.png)\n"
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Static site publishing
+//!
+//! `oc publish` renders part of the workspace to plain HTML that any static
+//! host can serve: one page per document at the document's path, an index
+//! grouped by folder, and a search box that runs in the browser over
+//! `search-index.json`, so the site needs no server. A document is published
+//! when its frontmatter says `publish: true`, or when it lies in one of the
+//! selected folders and does not say `publish: false`.
+//!
+//! Pages go through the same renderer as previews (see [`crate::render`]),
+//! so embeds are expanded and code is highlighted; frontmatter is never
+//! rendered. Links to documents that are not published
+//! become plain text, so the site neither dead-ends nor reveals the paths of
+//! private notes; images and attachments next to published documents are
+//! copied along. A published `index.md` at the workspace root becomes the
+//! top of the home page.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::links::Resolver;
+use crate::render::{encode_path, relative, RenderedDoc, Renderer};
+use crate::{
+    folder_not_found, frontmatter, html, normalize_folder_path, relpath, CoreError, CoreResult,
+    OpenContext,
+};
+
+/// Files the previous run wrote, one per line, so publishing again removes
+/// pages that are no longer published and leaves everything else in the
+/// output directory (`.git`, `CNAME`) alone
+const MANIFEST: &str = ".opencontext-publish";
+
+/// Characters of a page's text kept in the search index
+const MAX_INDEXED_TEXT: usize = 20_000;
+
+const STYLE: &str = include_str!("publish/style.css");
+const SEARCH_SCRIPT: &str = include_str!("publish/search.js");
+
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// Folders published as a whole, except docs with `publish: false`
+    pub folders: Vec<String>,
+    /// Site name shown in the header and page titles
+    pub title: String,
+}
+
+/// What [`OpenContext::publish_site`] wrote
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublishReport {
+    pub out_dir: PathBuf,
+    /// Published documents
+    pub pages: Vec<String>,
+    /// Images and attachments copied next to the pages
+    pub assets: Vec<String>,
+    /// Links to unpublished or missing documents, rendered as plain text
+    pub hidden_links: usize,
+    /// Files of the previous publish that are gone from this one
+    pub removed: usize,
+}
+
+/// One entry of `search-index.json`
+#[derive(Debug, Clone, Serialize)]
+struct IndexEntry<'a> {
+    url: String,
+    title: &'a str,
+    headings: &'a [String],
+    tags: &'a [String],
+    text: &'a str,
+}
+
+struct Page {
+    doc: RenderedDoc,
+    /// Output file, relative to the site root
+    file: String,
+    description: Option<String>,
+    tags: Vec<String>,
+    /// Heading texts, for the search index
+    headings: Vec<String>,
+}
+
+impl OpenContext {
+    /// Render the published documents to a static site in `out_dir`
+    pub fn publish_site(
+        &self,
+        out_dir: &Path,
+        options: &PublishOptions,
+    ) -> CoreResult<PublishReport> {
+        let mut folders = Vec::new();
+        for folder in &options.folders {
+            let folder = normalize_folder_path(Some(folder))?;
+            if self.find_folder(&folder)?.is_none() {
+                return Err(folder_not_found(&folder));
+            }
+            folders.push(folder);
+        }
+        let docs = self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT rel_path, abs_path, stable_id FROM docs ORDER BY rel_path")?;
+            let docs = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(docs)
+        })?;
+
+        let mut sources = Vec::new();
+        for (rel_path, abs_path, _) in &docs {
+            let Ok(content) = fs::read_to_string(abs_path) else {
+                continue;
+            };
+            if is_published(&content, rel_path, &folders) {
+                sources.push((rel_path.clone(), content));
+            }
+        }
+        let published: HashSet<String> = sources.iter().map(|(p, _)| p.clone()).collect();
+        let route = |from: &str, path: &str| {
+            published
+                .contains(path)
+                .then(|| relative(from, &page_file(path)))
+        };
+        let resolver = Resolver::new(docs.into_iter().map(|(p, _, id)| (p, id)))
+            .with_names(&self.name_index()?);
+        let mut renderer = Renderer::new(resolver, &self.contexts_root, &route);
+        let pages: Vec<Page> = sources
+            .iter()
+            .map(|(rel_path, content)| {
+                let doc = renderer.render(rel_path, content);
+                Page {
+                    file: page_file(rel_path),
+                    description: match frontmatter::get_field(content, "description") {
+                        Some(Value::String(d)) if !d.trim().is_empty() => {
+                            Some(d.trim().to_string())
+                        }
+                        _ => None,
+                    },
+                    tags: string_list(frontmatter::get_field(content, "tags")),
+                    headings: doc.headings.iter().map(|h| h.text.clone()).collect(),
+                    doc,
+                }
+            })
+            .collect();
+
+        let previous = prepare_out_dir(out_dir)?;
+        let mut site = Site {
+            out_dir,
+            title: &options.title,
+            written: BTreeSet::new(),
+        };
+        site.write("style.css", STYLE)?;
+        site.write("search.js", SEARCH_SCRIPT)?;
+        for asset in &renderer.files {
+            let dest = out_dir.join(asset);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(self.contexts_root.join(asset), dest)?;
+            site.written.insert(asset.clone());
+        }
+
+        let mut backlinks: HashMap<&str, Vec<&Page>> = HashMap::new();
+        for page in &pages {
+            for target in &page.doc.links {
+                if target != &page.doc.rel_path {
+                    backlinks.entry(target.as_str()).or_default().push(page);
+                }
+            }
+        }
+        let home = pages.iter().find(|p| p.file == "index.html");
+        for page in pages.iter().filter(|p| p.file != "index.html") {
+            let from = backlinks
+                .get(page.doc.rel_path.as_str())
+                .map_or(&[][..], Vec::as_slice);
+            site.write(&page.file, &site.page_html(page, from))?;
+        }
+        site.write("index.html", &site.index_html(home, &pages))?;
+
+        let index: Vec<IndexEntry> = pages
+            .iter()
+            .map(|page| IndexEntry {
+                url: encode_path(&page.file),
+                title: &page.doc.title,
+                headings: &page.headings,
+                tags: &page.tags,
+                text: truncate(&page.doc.text, MAX_INDEXED_TEXT),
+            })
+            .collect();
+        let index = serde_json::json!({ "pages": index });
+        site.write("search-index.json", &index.to_string())?;
+
+        let removed = remove_stale(out_dir, &previous, &site.written)?;
+        let manifest: String = site.written.iter().map(|f| format!("{f}\n")).collect();
+        fs::write(out_dir.join(MANIFEST), manifest)?;
+
+        Ok(PublishReport {
+            out_dir: out_dir.to_path_buf(),
+            hidden_links: pages.iter().map(|p| p.doc.broken_links.len()).sum(),
+            pages: pages.into_iter().map(|p| p.doc.rel_path).collect(),
+            assets: renderer.files.into_iter().collect(),
+            removed,
+        })
+    }
+}
+
+fn is_published(content: &str, rel_path: &str, folders: &[String]) -> bool {
+    match frontmatter::get_field(content, "publish") {
+        Some(Value::Bool(flag)) => flag,
+        _ => folders.iter().any(|f| relpath::is_within(rel_path, f)),
+    }
+}
+
+/// Output file of the document at `rel_path`
+fn page_file(rel_path: &str) -> String {
+    let stem = match rel_path.len().checked_sub(3) {
+        Some(at) if rel_path[at..].eq_ignore_ascii_case(".md") => &rel_path[..at],
+        _ => rel_path,
+    };
+    format!("{stem}.html")
+}
+
+fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((at, _)) => &text[..at],
+        None => text,
+    }
+}
+
+fn string_list(value: Option<Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(str::to_string)
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+struct Site<'a> {
+    out_dir: &'a Path,
+    title: &'a str,
+    /// Files written, relative to `out_dir`
+    written: BTreeSet<String>,
+}
+
+impl Site<'_> {
+    fn write(&mut self, file: &str, content: &str) -> CoreResult<()> {
+        let dest = self.out_dir.join(file);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(dest, content)?;
+        self.written.insert(file.to_string());
+        Ok(())
+    }
+
+    /// Page shell around `main`, for the site file `file`
+    fn layout(&self, file: &str, title: &str, main: &str) -> String {
+        let root = "../".repeat(file.matches('/').count());
+        let site = html::escape(self.title);
+        let heading = if title == self.title {
+            site.clone()
+        } else {
+            format!("{} · {site}", html::escape(title))
+        };
+        format!(
+            "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{heading}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n\
+             </head>\n<body>\n<header>\n<a class=\"site\" href=\"{root}index.html\">{site}</a>\n\
+             <div class=\"search\">\n<input id=\"oc-search\" type=\"search\" placeholder=\"Search\" \
+             autocomplete=\"off\" data-root=\"{root}\">\n<ol id=\"oc-results\" hidden></ol>\n</div>\n\
+             </header>\n<main>\n{main}</main>\n<script src=\"{root}search.js\"></script>\n\
+             </body>\n</html>\n"
+        )
+    }
+
+    fn page_html(&self, page: &Page, backlinks: &[&Page]) -> String {
+        let mut main = String::from("<article>\n");
+        if !page.tags.is_empty() {
+            main.push_str("<p class=\"tags\">");
+            for tag in &page.tags {
+                main.push_str(&format!("<span>#{}</span> ", html::escape(tag)));
+            }
+            main.push_str("</p>\n");
+        }
+        main.push_str(&page.doc.html);
+        main.push_str("</article>\n");
+        if !backlinks.is_empty() {
+            main.push_str("<aside class=\"backlinks\">\n<h2>Linked from</h2>\n<ul>\n");
+            for from in backlinks {
+                main.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a></li>\n",
+                    html::escape(&relative(&page.file, &from.file)),
+                    html::escape(&from.doc.title)
+                ));
+            }
+            main.push_str("</ul>\n</aside>\n");
+        }
+        self.layout(&page.file, &page.doc.title, &main)
+    }
+
+    fn index_html(&self, home: Option<&Page>, pages: &[Page]) -> String {
+        let mut main = String::new();
+        match home {
+            Some(home) => main.push_str(&format!("<article>\n{}</article>\n", home.doc.html)),
+            None => main.push_str(&format!("<h1>{}</h1>\n", html::escape(self.title))),
+        }
+        let mut by_folder: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+        for page in pages.iter().filter(|p| p.file != "index.html") {
+            let folder = page
+                .doc
+                .rel_path
+                .rsplit_once('/')
+                .map_or("", |(dir, _)| dir);
+            by_folder.entry(folder).or_default().push(page);
+        }
+        for (folder, pages) in by_folder {
+            main.push_str("<section>\n");
+            if !folder.is_empty() {
+                main.push_str(&format!("<h2>{}</h2>\n", html::escape(folder)));
+            }
+            main.push_str("<ul class=\"pages\">\n");
+            for page in pages {
+                main.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a>",
+                    html::escape(&encode_path(&page.file)),
+                    html::escape(&page.doc.title)
+                ));
+                if let Some(description) = &page.description {
+                    main.push_str(&format!(" <span>{}</span>", html::escape(description)));
+                }
+                main.push_str("</li>\n");
+            }
+            main.push_str("</ul>\n</section>\n");
+        }
+        self.layout("index.html", self.title, &main)
+    }
+}
+
+/// Create `out_dir` or check it holds an earlier publish; the files that
+/// publish wrote
+fn prepare_out_dir(out_dir: &Path) -> CoreResult<Vec<String>> {
+    if !out_dir.exists() {
+        fs::create_dir_all(out_dir)?;
+        return Ok(Vec::new());
+    }
+    match fs::read_to_string(out_dir.join(MANIFEST)) {
+        Ok(manifest) => Ok(manifest.lines().map(str::to_string).collect()),
+        Err(_) if fs::read_dir(out_dir)?.next().is_none() => Ok(Vec::new()),
+        Err(_) => Err(CoreError::Message(format!(
+            "{} is not empty and was not written by oc publish",
+            out_dir.display()
+        ))),
+    }
+}
+
+/// Delete the files of the previous publish that this one did not write,
+/// and the directories that leaves empty
+fn remove_stale(
+    out_dir: &Path,
+    previous: &[String],
+    written: &BTreeSet<String>,
+) -> CoreResult<usize> {
+    let mut removed = 0;
+    for file in previous {
+        // Never follow a tampered manifest out of the output directory
+        if written.contains(file) || file.split('/').any(|p| p == ".." || p.is_empty()) {
+            continue;
+        }
+        let path = out_dir.join(file);
+        if path.is_file() {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+        let mut dir = path.parent();
+        while let Some(d) = dir.filter(|d| *d != out_dir) {
+            if fs::remove_dir(d).is_err() {
+                break;
+            }
+            dir = d.parent();
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_paths() {
+        assert_eq!(page_file("notes/Read Me.MD"), "notes/Read Me.html");
+        assert_eq!(page_file("index.md"), "index.html");
+    }
+}
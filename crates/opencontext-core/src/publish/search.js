@@ -0,0 +1,99 @@
+// Search box of sites written by `oc publish`: ranks the pages of
+// search-index.json in the browser, so the site needs no server
+(function () {
+  const input = document.getElementById('oc-search');
+  const results = document.getElementById('oc-results');
+  if (!input || !results) return;
+  const root = input.dataset.root || '';
+  let pages = null;
+  let latest = 0;
+
+  function load() {
+    if (!pages) {
+      pages = fetch(`${root}search-index.json`)
+        .then((response) => response.json())
+        .then((index) =>
+          index.pages.map((page) => ({
+            ...page,
+            lower: {
+              title: page.title.toLowerCase(),
+              headings: page.headings.join(' ').toLowerCase(),
+              tags: page.tags.join(' ').toLowerCase(),
+              text: page.text.toLowerCase(),
+            },
+          }))
+        );
+    }
+    return pages;
+  }
+
+  function count(haystack, term) {
+    let n = 0;
+    for (let at = haystack.indexOf(term); at !== -1 && n < 20; at = haystack.indexOf(term, at + term.length)) {
+      n += 1;
+    }
+    return n;
+  }
+
+  // Every term must match; title and heading hits outweigh body text
+  function score(page, terms) {
+    let total = 0;
+    for (const term of terms) {
+      const hits =
+        count(page.lower.title, term) * 10 +
+        count(page.lower.headings, term) * 4 +
+        count(page.lower.tags, term) * 4 +
+        count(page.lower.text, term);
+      if (hits === 0) return 0;
+      total += hits;
+    }
+    return total;
+  }
+
+  function snippet(page, terms) {
+    const hits = terms.map((term) => page.lower.text.indexOf(term)).filter((at) => at >= 0);
+    if (hits.length === 0) return page.text.slice(0, 160);
+    const start = Math.max(0, Math.min(...hits) - 60);
+    return `${start > 0 ? '…' : ''}${page.text.slice(start, start + 160)}…`;
+  }
+
+  function escape(s) {
+    return s.replace(/[&<>"]/g, (c) => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;' })[c]);
+  }
+
+  async function run() {
+    const seq = ++latest;
+    const terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    if (terms.length === 0) {
+      results.hidden = true;
+      results.innerHTML = '';
+      return;
+    }
+    const ranked = (await load())
+      .map((page) => [score(page, terms), page])
+      .filter(([s]) => s > 0)
+      .sort((a, b) => b[0] - a[0])
+      .slice(0, 20);
+    if (seq !== latest) return;
+    results.innerHTML = ranked.length
+      ? ranked
+          .map(
+            ([, page]) =>
+              `<li><a href="${escape(root + page.url)}">${escape(page.title)}</a><p>${escape(snippet(page, terms))}</p></li>`
+          )
+          .join('')
+      : '<li>No matches</li>';
+    results.hidden = false;
+  }
+
+  input.addEventListener('input', run);
+  input.addEventListener('keydown', (event) => {
+    if (event.key === 'Escape') {
+      input.value = '';
+      run();
+    } else if (event.key === 'Enter') {
+      const first = results.querySelector('a');
+      if (first) window.location.href = first.href;
+    }
+  });
+})();
@@ -0,0 +1,285 @@
+//! Workspace-relative paths.
+//!
+//! Documents and folders are identified by their path under the contexts
+//! root, always `/`-separated regardless of platform. `RelPath` is that
+//! normalized form: paths coming from the filesystem, the CLI or a Windows
+//! vault (`notes\2024\a.md`, `C:\Vault\notes\a.md`) go through it before
+//! they are stored, indexed or compared, and get turned back into native
+//! paths only at the filesystem boundary.
+
+use std::fmt;
+use std::ops::Deref;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct RelPath(String);
+
+impl RelPath {
+    /// Normalize a relative path: `\` becomes `/`, and empty and `.`
+    /// segments are dropped. The workspace root is the empty path.
+    pub fn new(path: &str) -> Self {
+        let normalized = path
+            .trim()
+            .replace('\\', "/")
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .collect::<Vec<_>>()
+            .join("/");
+        Self(normalized)
+    }
+
+    /// Build from a relative filesystem path. `None` if it has a root,
+    /// prefix or `..` component, or is not valid UTF-8.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let mut segments = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => segments.push(part.to_str()?),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        Some(Self::new(&segments.join("/")))
+    }
+
+    /// The path of `abs` under `root`, or `None` if it lies elsewhere.
+    ///
+    /// Falls back to a textual comparison so that spellings of the same
+    /// Windows location still match: `\\?\C:\Vault` against `c:/Vault`.
+    pub fn from_abs(root: &Path, abs: &Path) -> Option<Self> {
+        if let Ok(rel) = abs.strip_prefix(root) {
+            if let Some(rel) = Self::from_path(rel) {
+                return Some(rel);
+            }
+        }
+        let root = comparable(root.to_str()?);
+        let abs = comparable(abs.to_str()?);
+        let rest = abs.strip_prefix(root.trim_end_matches('/'))?;
+        if !(rest.is_empty() || rest.starts_with('/')) {
+            return None;
+        }
+        let rel = Self::new(rest);
+        let escapes = rel.segments().any(|s| s == "..");
+        (!escapes).then_some(rel)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Whether this is the workspace root
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split('/').filter(|s| !s.is_empty())
+    }
+
+    /// Last segment: the file or folder name
+    pub fn file_name(&self) -> &str {
+        file_name(&self.0)
+    }
+
+    /// File name without the `.md` extension, as shown in results
+    pub fn display_name(&self) -> &str {
+        display_name(&self.0)
+    }
+
+    /// The containing folder; `None` for top-level entries and the root
+    pub fn parent(&self) -> Option<RelPath> {
+        let (parent, _) = self.0.rsplit_once('/')?;
+        Some(Self(parent.to_string()))
+    }
+
+    /// Append a child path
+    pub fn join(&self, child: &str) -> RelPath {
+        Self::new(&format!("{}/{}", self.0, child))
+    }
+
+    /// Whether this path is `folder` or lies below it
+    pub fn is_within(&self, folder: &str) -> bool {
+        is_within(&self.0, folder)
+    }
+
+    /// The native filesystem path under `root`
+    pub fn to_path(&self, root: &Path) -> PathBuf {
+        let mut path = root.to_path_buf();
+        path.extend(self.segments());
+        path
+    }
+}
+
+/// Last segment of a `/`-separated path
+pub fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// [`file_name`] without the `.md` extension
+pub fn display_name(path: &str) -> &str {
+    file_name(path).trim_end_matches(".md")
+}
+
+/// Path segment made of `s`: lowercase letters, digits and dashes
+pub fn slug(s: &str) -> String {
+    let slug: String = s
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Whether `path` is `folder` itself or lies below it. The empty folder
+/// (the root) contains everything.
+pub fn is_within(path: &str, folder: &str) -> bool {
+    let folder = folder.trim_end_matches('/');
+    folder.is_empty()
+        || path
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// `/`-separated absolute path with any `\\?\` verbatim prefix removed and
+/// the drive letter lowercased
+fn comparable(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path
+        .strip_prefix("//?/UNC/")
+        .map(|rest| format!("//{rest}"))
+        .or_else(|| path.strip_prefix("//?/").map(str::to_string))
+        .unwrap_or(path);
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[1] == b':' && bytes[0].is_ascii_alphabetic() {
+        format!("{}{}", path[..1].to_ascii_lowercase(), &path[1..])
+    } else {
+        path
+    }
+}
+
+impl Deref for RelPath {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for RelPath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RelPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for RelPath {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<String> for RelPath {
+    fn from(path: String) -> Self {
+        Self::new(&path)
+    }
+}
+
+impl From<&String> for RelPath {
+    fn from(path: &String) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<RelPath> for String {
+    fn from(path: RelPath) -> Self {
+        path.0
+    }
+}
+
+impl PartialEq<str> for RelPath {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for RelPath {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_normalizes_windows_separators() {
+        assert_eq!(RelPath::new(r"notes\2024\a.md"), "notes/2024/a.md");
+        assert_eq!(RelPath::new(r".\notes\\a.md"), "notes/a.md");
+        assert_eq!(RelPath::new("/notes/./a.md/"), "notes/a.md");
+        assert!(RelPath::new(" . ").is_root());
+    }
+
+    #[test]
+    fn test_names_and_parent() {
+        let path = RelPath::new(r"projects\alpha\Plan.md");
+        assert_eq!(path.file_name(), "Plan.md");
+        assert_eq!(path.display_name(), "Plan");
+        assert_eq!(path.parent().unwrap(), "projects/alpha");
+        assert_eq!(RelPath::new("Plan.md").parent(), None);
+        assert_eq!(path.parent().unwrap().join("b.md"), "projects/alpha/b.md");
+    }
+
+    #[test]
+    fn test_is_within_respects_segment_boundaries() {
+        let path = RelPath::new("projects/alpha/a.md");
+        assert!(path.is_within("projects"));
+        assert!(path.is_within("projects/alpha/"));
+        assert!(path.is_within(""));
+        assert!(!path.is_within("proj"));
+        assert!(!path.is_within("projects/alph"));
+    }
+
+    #[test]
+    fn test_from_abs_matches_windows_spellings() {
+        let rel = RelPath::from_abs(
+            Path::new(r"C:\Users\me\Vault"),
+            Path::new(r"\\?\c:\Users\me\Vault\notes\a.md"),
+        );
+        assert_eq!(rel.unwrap(), "notes/a.md");
+        assert_eq!(
+            RelPath::from_abs(Path::new("/vault"), Path::new("/vault/notes/a.md")).unwrap(),
+            "notes/a.md"
+        );
+        assert_eq!(
+            RelPath::from_abs(Path::new(r"C:\Vault"), Path::new(r"C:\Vault2\a.md")),
+            None
+        );
+        assert_eq!(
+            RelPath::from_abs(Path::new(r"C:\Vault"), Path::new(r"C:\Vault\..\a.md")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_to_path_uses_native_separators() {
+        let path = RelPath::new("notes/a.md").to_path(Path::new("/vault"));
+        assert_eq!(path, Path::new("/vault").join("notes").join("a.md"));
+    }
+}
@@ -0,0 +1,586 @@
+//! Markdown rendering
+//!
+//! [`OpenContext::render`] turns a document into HTML for the web UI and
+//! editor previews. `[[wiki-links]]`, relative Markdown links and
+//! `oc://doc/` links lead to workspace routes (`?doc=<path>`); `![[note]]`
+//! and `![[note#Section]]` embeds are expanded in place; fenced code is
+//! highlighted; raw HTML written in the note is sanitized. Links the reader
+//! cannot follow (missing documents, or folders outside their access) are
+//! shown as plain text. `oc publish` renders with the same code and routes
+//! of its own.
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::acl::Access;
+use crate::embeds::{excerpt, MAX_EMBED_DEPTH};
+use crate::highlight::highlight;
+use crate::links::{self, Link, LinkKind, Resolution, Resolver};
+use crate::{frontmatter, html, relpath, CoreResult, OpenContext};
+
+/// Destination `[[wiki-links]]` are rewritten to before Markdown parsing
+const WIKI_SCHEME: &str = "oc-wiki:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenderedHeading {
+    pub level: u8,
+    pub text: String,
+    /// `id` attribute of the heading, for `#anchor` links
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenderedDoc {
+    pub rel_path: String,
+    /// `title` frontmatter, else the first `# heading`, else the file name
+    pub title: String,
+    pub html: String,
+    /// Headings of the document itself, not of what it embeds
+    pub headings: Vec<RenderedHeading>,
+    /// Documents linked to, in order of first appearance
+    pub links: Vec<String>,
+    /// Documents embedded, including embeds of embeds
+    pub embeds: Vec<String>,
+    /// Link targets the reader cannot open, rendered as plain text
+    pub broken_links: Vec<String>,
+    /// Text content with whitespace collapsed, for search indexes
+    #[serde(skip)]
+    pub(crate) text: String,
+}
+
+/// `href` of the document `rel_path` linked from the document at `from`;
+/// `None` shows the link as text and leaves the document out of embeds
+pub(crate) type DocRoute<'a> = dyn Fn(&str, &str) -> Option<String> + 'a;
+
+impl OpenContext {
+    /// `rel_path` as HTML, with links and embeds limited to what `access`
+    /// can read
+    pub fn render(&self, rel_path: &str, access: &Access) -> CoreResult<RenderedDoc> {
+        let content = self.get_doc_content(rel_path)?;
+        let docs = self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT rel_path, stable_id FROM docs")?;
+            let docs = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<(String, Option<String>)>, _>>()?;
+            Ok(docs)
+        })?;
+        let mut resolver = Resolver::new(docs);
+        if content.contains("[[") {
+            resolver = resolver.with_names(&self.name_index()?);
+        }
+        let route = |_: &str, path: &str| access.allows(path).then(|| workspace_route(path));
+        let mut renderer = Renderer::new(resolver, &self.contexts_root, &route);
+        Ok(renderer.render(rel_path, &content))
+    }
+}
+
+/// UI route of a document: `?doc=<path>`, as the web UI writes it
+fn workspace_route(rel_path: &str) -> String {
+    let mut out = String::from("?doc=");
+    for b in rel_path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(b as char)
+            }
+            b => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// `path` with the characters that would end or break a URL path escaped
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            '%' => out.push_str("%25"),
+            ' ' => out.push_str("%20"),
+            '#' => out.push_str("%23"),
+            '?' => out.push_str("%3F"),
+            '"' => out.push_str("%22"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// URL of the workspace path `to` from the document (or site page) at
+/// `from`, when both sit in the same tree
+pub(crate) fn relative(from: &str, to: &str) -> String {
+    format!(
+        "{}{}",
+        "../".repeat(from.matches('/').count()),
+        encode_path(to)
+    )
+}
+
+/// `[[target|label]]` as a Markdown link to `oc-wiki:target`, outside code
+/// fences and inline code. `![[note]]` embeds become images.
+fn wiki_links_to_markdown(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut fence: Option<&str> = None;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            out.push_str(line);
+            continue;
+        }
+        if fence.is_some() || !line.contains("[[") {
+            out.push_str(line);
+            continue;
+        }
+        for (i, part) in line.split('`').enumerate() {
+            if i > 0 {
+                out.push('`');
+            }
+            if i % 2 == 1 {
+                out.push_str(part);
+                continue;
+            }
+            let mut rest = part;
+            while let Some(start) = rest.find("[[") {
+                let Some(end) = rest[start + 2..].find("]]") else {
+                    break;
+                };
+                let inner = &rest[start + 2..start + 2 + end];
+                let (target, label) = inner.split_once('|').unwrap_or((inner, inner));
+                let label = label.trim();
+                let label = label.strip_suffix(".md").unwrap_or(label);
+                out.push_str(&rest[..start]);
+                out.push('[');
+                for c in label.chars() {
+                    if matches!(c, '[' | ']' | '\\') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+                out.push_str(&format!("](<{WIKI_SCHEME}{}>)", target.trim()));
+                rest = &rest[start + 2 + end + 2..];
+            }
+            out.push_str(rest);
+        }
+    }
+    out
+}
+
+fn title_of(content: &str, first_heading: Option<String>, rel_path: &str) -> String {
+    match frontmatter::get_field(content, "title") {
+        Some(Value::String(title)) if !title.trim().is_empty() => title.trim().to_string(),
+        _ => first_heading.unwrap_or_else(|| relpath::display_name(rel_path).to_string()),
+    }
+}
+
+/// Where a link in a rendered document leads
+enum Target {
+    /// Left as written (web links, anchors)
+    Keep,
+    Url(String),
+    /// Shown as its text only
+    Text,
+}
+
+pub(crate) struct Renderer<'a> {
+    resolver: Resolver,
+    root: &'a Path,
+    route: &'a DocRoute<'a>,
+    /// Workspace paths of the local files linked or embedded
+    pub(crate) files: BTreeSet<String>,
+    /// Heading ids handed out in the current document
+    ids: HashSet<String>,
+    /// Documents being rendered, outermost first, against embed cycles
+    stack: Vec<String>,
+}
+
+impl<'a> Renderer<'a> {
+    pub(crate) fn new(resolver: Resolver, root: &'a Path, route: &'a DocRoute<'a>) -> Self {
+        Renderer {
+            resolver,
+            root,
+            route,
+            files: BTreeSet::new(),
+            ids: HashSet::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// The document at `rel_path`, whose text is `content`
+    pub(crate) fn render(&mut self, rel_path: &str, content: &str) -> RenderedDoc {
+        let mut doc = RenderedDoc {
+            rel_path: rel_path.to_string(),
+            ..Default::default()
+        };
+        self.ids.clear();
+        self.stack = vec![rel_path.to_string()];
+        let mut first_heading = None;
+        doc.html = self.html(
+            rel_path,
+            frontmatter::body(content),
+            &mut doc,
+            Some(&mut first_heading),
+        );
+        doc.title = title_of(content, first_heading, rel_path);
+        doc.text = doc.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        doc
+    }
+
+    /// HTML of `markdown` (the body of the document at `from`). Headings
+    /// are recorded in `doc` only for the document itself, when
+    /// `first_heading` is given.
+    fn html(
+        &mut self,
+        from: &str,
+        markdown: &str,
+        doc: &mut RenderedDoc,
+        mut first_heading: Option<&mut Option<String>>,
+    ) -> String {
+        let mut events: Vec<Event> = Vec::new();
+        // Start event index, level and text of the heading being read
+        let mut heading: Option<(usize, HeadingLevel, String)> = None;
+        // Language and code of the fenced block being read
+        let mut code: Option<(String, String)> = None;
+        // Consecutive raw HTML lines, sanitized together
+        let mut raw = String::new();
+        // End of the link shown as its text only
+        let mut unlinking: Option<TagEnd> = None;
+        // End of the image whose alt text an embed replaced
+        let mut skipping = false;
+        // An embed closed the paragraph it stood in
+        let mut reopen = false;
+
+        let source = wiki_links_to_markdown(markdown);
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_FOOTNOTES;
+        for event in Parser::new_ext(&source, options) {
+            if skipping {
+                skipping = !matches!(event, Event::End(TagEnd::Image));
+                continue;
+            }
+            if !raw.is_empty() && !matches!(event, Event::Html(_)) {
+                events.push(Event::Html(
+                    html::sanitize(&std::mem::take(&mut raw)).into(),
+                ));
+            }
+            if reopen {
+                reopen = false;
+                if matches!(event, Event::End(TagEnd::Paragraph)) {
+                    continue;
+                }
+                events.push(Event::Start(Tag::Paragraph));
+            }
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    heading = Some((events.len(), level, String::new()));
+                    events.push(event);
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    if let Some((start, level, text)) = heading.take() {
+                        let base = relpath::slug(&text);
+                        let mut id = base.clone();
+                        let mut n = 1;
+                        while !self.ids.insert(id.clone()) {
+                            id = format!("{base}-{n}");
+                            n += 1;
+                        }
+                        if let Event::Start(Tag::Heading { id: slot, .. }) = &mut events[start] {
+                            *slot = Some(id.clone().into());
+                        }
+                        if let Some(first) = first_heading.as_deref_mut() {
+                            if level == HeadingLevel::H1 && first.is_none() {
+                                *first = Some(text.clone());
+                            }
+                            doc.headings.push(RenderedHeading {
+                                level: level as u8,
+                                text,
+                                id,
+                            });
+                        }
+                    }
+                    events.push(event);
+                }
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(info) => {
+                            info.split_whitespace().next().unwrap_or("").to_string()
+                        }
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code = Some((lang, String::new()));
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((lang, text)) = code.take() {
+                        let class = if lang.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" class=\"language-{}\"", html::escape(&lang))
+                        };
+                        events.push(Event::Html(
+                            format!(
+                                "<pre><code{class}>{}</code></pre>\n",
+                                highlight(&text, &lang)
+                            )
+                            .into(),
+                        ));
+                        doc.text.push_str(&text);
+                        doc.text.push(' ');
+                    }
+                }
+                Event::Text(t) if code.is_some() => {
+                    if let Some((_, text)) = &mut code {
+                        text.push_str(&t);
+                    }
+                }
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => match self.link(from, &dest_url, doc) {
+                    Target::Keep => events.push(Event::Start(Tag::Link {
+                        link_type,
+                        dest_url,
+                        title,
+                        id,
+                    })),
+                    Target::Url(url) => events.push(Event::Start(Tag::Link {
+                        link_type,
+                        dest_url: url.into(),
+                        title,
+                        id,
+                    })),
+                    Target::Text => unlinking = Some(TagEnd::Link),
+                },
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => {
+                    if let Some(target) = dest_url.strip_prefix(WIKI_SCHEME) {
+                        // Embeds name a file next to the note or anywhere
+                        if let Some(path) =
+                            self.file(from, target).or_else(|| self.file("", target))
+                        {
+                            events.push(Event::Start(Tag::Image {
+                                link_type,
+                                dest_url: relative(from, &path).into(),
+                                title,
+                                id,
+                            }));
+                            continue;
+                        }
+                        match self.embed(from, target, doc) {
+                            Some(embed) => {
+                                if matches!(events.last(), Some(Event::Start(Tag::Paragraph))) {
+                                    events.pop();
+                                    reopen = true;
+                                }
+                                events.push(Event::Html(embed.into()));
+                                skipping = true;
+                            }
+                            None => unlinking = Some(TagEnd::Image),
+                        }
+                    } else if !html::is_safe_url(&dest_url) {
+                        skipping = true;
+                    } else {
+                        let dest_url = match self.file(from, &dest_url) {
+                            Some(path) => relative(from, &path).into(),
+                            None => dest_url,
+                        };
+                        events.push(Event::Start(Tag::Image {
+                            link_type,
+                            dest_url,
+                            title,
+                            id,
+                        }));
+                    }
+                }
+                Event::End(end) if unlinking == Some(end) => unlinking = None,
+                Event::Html(s) => raw.push_str(&s),
+                Event::InlineHtml(s) => events.push(Event::InlineHtml(html::sanitize(&s).into())),
+                Event::Text(ref t) | Event::Code(ref t) => {
+                    if let Some((_, _, text)) = &mut heading {
+                        text.push_str(t);
+                    }
+                    doc.text.push_str(t);
+                    events.push(event);
+                }
+                Event::End(_) | Event::SoftBreak | Event::HardBreak => {
+                    doc.text.push(' ');
+                    events.push(event);
+                }
+                event => events.push(event),
+            }
+        }
+        if !raw.is_empty() {
+            events.push(Event::Html(html::sanitize(&raw).into()));
+        }
+        let mut out = String::new();
+        pulldown_cmark::html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    fn link(&mut self, from: &str, dest: &str, doc: &mut RenderedDoc) -> Target {
+        let (link, anchor) = match dest.strip_prefix(WIKI_SCHEME) {
+            Some(wiki) => {
+                let (target, anchor) = wiki.split_once('#').unwrap_or((wiki, ""));
+                let link = Link {
+                    target: target.trim().to_string(),
+                    line: 0,
+                    kind: LinkKind::Wiki,
+                };
+                (link, anchor)
+            }
+            None => {
+                let kind = if dest.starts_with("oc://doc/") {
+                    LinkKind::DocId
+                } else {
+                    LinkKind::Markdown
+                };
+                let anchor = match kind {
+                    LinkKind::Markdown => dest.split_once('#').map_or("", |(_, a)| a),
+                    _ => "",
+                };
+                let link = Link {
+                    target: dest.to_string(),
+                    line: 0,
+                    kind,
+                };
+                (link, anchor)
+            }
+        };
+        match self.resolver.resolve(from, &link) {
+            Resolution::Doc(path) => match (self.route)(from, &path) {
+                Some(mut url) => {
+                    let anchor = relpath::slug(&links::percent_decode(anchor));
+                    if !anchor.is_empty() {
+                        url.push('#');
+                        url.push_str(&anchor);
+                    }
+                    if !doc.links.contains(&path) {
+                        doc.links.push(path);
+                    }
+                    Target::Url(url)
+                }
+                None => {
+                    doc.broken_links.push(path);
+                    Target::Text
+                }
+            },
+            Resolution::External if !html::is_safe_url(dest) => Target::Text,
+            Resolution::External => match self.file(from, dest) {
+                Some(path) => Target::Url(relative(from, &path)),
+                None => Target::Keep,
+            },
+            Resolution::Broken(path) => {
+                doc.broken_links.push(path.unwrap_or(link.target));
+                Target::Text
+            }
+        }
+    }
+
+    /// HTML of the `![[note#Section]]` embed in the document at `from`, or
+    /// `None` if the note cannot be shown there
+    fn embed(&mut self, from: &str, target: &str, doc: &mut RenderedDoc) -> Option<String> {
+        let (name, anchor) = target.split_once('#').unwrap_or((target, ""));
+        let link = Link {
+            target: name.trim().to_string(),
+            line: 0,
+            kind: LinkKind::Wiki,
+        };
+        let path = match self.resolver.resolve(from, &link) {
+            Resolution::Doc(path) => path,
+            Resolution::Broken(path) => {
+                doc.broken_links.push(path.unwrap_or(link.target));
+                return None;
+            }
+            Resolution::External => return None,
+        };
+        let Some(url) = (self.route)(from, &path) else {
+            doc.broken_links.push(path);
+            return None;
+        };
+        if self.stack.contains(&path) || self.stack.len() > MAX_EMBED_DEPTH {
+            return None;
+        }
+        let content = fs::read_to_string(self.root.join(&path)).ok()?;
+        let body = frontmatter::body(&content);
+        let body = if anchor.is_empty() {
+            Cow::Borrowed(body)
+        } else {
+            excerpt(body, anchor)?
+        };
+        if !doc.embeds.contains(&path) {
+            doc.embeds.push(path.clone());
+        }
+        self.stack.push(path.clone());
+        let inner = self.html(&path, &body, doc, None);
+        self.stack.pop();
+        Some(format!(
+            "<div class=\"oc-embed\" data-doc=\"{}\">\n{inner}<a class=\"oc-embed-source\" href=\"{}\">{}</a>\n</div>\n",
+            html::escape(&path),
+            html::escape(&url),
+            html::escape(relpath::display_name(&path)),
+        ))
+    }
+
+    /// Workspace path of the local file `dest` (relative to the doc at
+    /// `from`) names, recorded in [`Renderer::files`]. Documents, hidden
+    /// paths and anything outside the workspace are not files here.
+    fn file(&mut self, from: &str, dest: &str) -> Option<String> {
+        if dest.starts_with(['#', '?']) || dest.contains(':') {
+            return None;
+        }
+        let path = dest.split(['#', '?']).next()?;
+        let dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let rel = links::join_rel(dir, &links::percent_decode(path))?;
+        if rel.is_empty()
+            || rel.to_ascii_lowercase().ends_with(".md")
+            || rel.split('/').any(|part| part.starts_with('.'))
+            || !self.root.join(&rel).is_file()
+        {
+            return None;
+        }
+        self.files.insert(rel.clone());
+        Some(rel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiki_links_to_markdown() {
+        let body = "See [[Design Notes|the design]] and [[plan#Goals]].\n\
+                    `[[not a link]]` ![[diagram.png]]\n```\n[[code]]\n```\n";
+        assert_eq!(
+            wiki_links_to_markdown(body),
+            "See [the design](<oc-wiki:Design Notes>) and [plan#Goals](<oc-wiki:plan#Goals>).\n\
+             `[[not a link]]` ![diagram.png](<oc-wiki:diagram.png>)\n```\n[[code]]\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(
+            relative("notes/a/b.md", "notes/c #1.html"),
+            "../../notes/c%20%231.html"
+        );
+        assert_eq!(
+            workspace_route("notes/Read me.md"),
+            "?doc=notes/Read%20me.md"
+        );
+    }
+}
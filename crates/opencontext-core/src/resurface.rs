@@ -0,0 +1,318 @@
+//! Random notes and resurfacing
+//!
+//! [`OpenContext::random_docs`] picks notes at random, optionally from one
+//! folder or tag, for `oc random`. [`OpenContext::resurface`] brings back
+//! notes nobody has edited or opened in a while: it picks a few that have
+//! sat untouched for `min_age_days`, a different few each day, and records
+//! them in the `resurfaced` table. A note that was shown comes back after
+//! an interval that doubles each time (spaced repetition), so a large
+//! archive is worked through instead of the same notes recurring.
+//! `oc digest --resurface 3` run from cron each morning puts them in the
+//! digest note.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+
+use crate::ideas::IDEAS_DIR;
+use crate::memory::MEMORY_DIR;
+use crate::{
+    content_revision, frontmatter, normalize_folder_path, now_iso, CoreResult, OpenContext,
+};
+
+/// Characters of each picked note's body returned as its excerpt
+const EXCERPT_CHARS: usize = 280;
+
+/// Resurfacing intervals stop doubling after this many showings
+const MAX_DOUBLINGS: u32 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PickedDoc {
+    pub rel_path: String,
+    pub description: String,
+    pub updated_at: String,
+    /// Days since the note was last edited or opened
+    pub idle_days: i64,
+    /// Times it was resurfaced before, this time excluded
+    pub times_shown: i64,
+    pub last_shown_at: Option<String>,
+    /// Start of the body, without frontmatter
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RandomQuery {
+    /// Only notes under this folder
+    pub folder: Option<String>,
+    /// Only notes with this tag, in frontmatter `tags` or as an inline `#tag`
+    pub tag: Option<String>,
+    /// Notes to pick (default 1)
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ResurfaceOptions {
+    /// Notes to pick (default 3)
+    pub count: Option<usize>,
+    /// Only notes neither edited nor opened for this many days (default 90)
+    pub min_age_days: Option<i64>,
+    pub folder: Option<String>,
+    /// Leave out notes under this folder, e.g. where digests are saved
+    pub exclude_folder: Option<String>,
+    pub tag: Option<String>,
+    /// Look without recording the picks, so they stay due
+    pub dry_run: bool,
+}
+
+struct Candidate {
+    doc_id: i64,
+    rel_path: String,
+    abs_path: String,
+    description: String,
+    updated_at: String,
+    last_hit_at: Option<String>,
+    times_shown: i64,
+    last_shown_at: Option<String>,
+}
+
+/// Whether `content` carries `tag`, in frontmatter `tags` or as a `#tag` in
+/// the body. `#project` also matches the nested `#project/alpha`.
+pub(crate) fn has_tag(content: &str, tag: &str) -> bool {
+    let wanted = tag.trim().trim_start_matches('#');
+    let matches = |t: &str| {
+        let t = t.trim().trim_start_matches('#');
+        t.eq_ignore_ascii_case(wanted)
+            || t.get(..wanted.len()).is_some_and(|head| {
+                head.eq_ignore_ascii_case(wanted) && t[wanted.len()..].starts_with('/')
+            })
+    };
+    let listed = match frontmatter::fields(content).get("tags") {
+        Some(Value::Array(items)) => items.iter().any(|v| v.as_str().is_some_and(matches)),
+        Some(Value::String(list)) => list.split(',').any(matches),
+        _ => false,
+    };
+    listed
+        || frontmatter::body(content)
+            .lines()
+            .filter(|line| crate::embeds::heading_level(line.trim_start()) == 0)
+            .flat_map(str::split_whitespace)
+            .filter_map(|word| word.strip_prefix('#'))
+            .any(|word| matches(word.trim_end_matches(|c: char| !c.is_alphanumeric())))
+}
+
+fn excerpt(content: &str) -> String {
+    let body = frontmatter::body(content).trim();
+    match body.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+fn timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+/// Whether a note shown `times` times, last at `last_shown_at`, is due again:
+/// after `min_age_days` (at least a day) the first time, then twice as long
+/// after each showing
+pub fn is_due(
+    times: i64,
+    last_shown_at: Option<&str>,
+    min_age_days: i64,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some(last) = last_shown_at.and_then(timestamp) else {
+        return true;
+    };
+    let doublings = times.saturating_sub(1).clamp(0, i64::from(MAX_DOUBLINGS)) as u32;
+    let interval = min_age_days.max(1).saturating_mul(2_i64.pow(doublings));
+    Duration::try_days(interval).is_some_and(|interval| now - last >= interval)
+}
+
+impl OpenContext {
+    /// Notes with their resurfacing state, leaving out idea boxes and agent
+    /// memories, which are not notes one rereads
+    fn pick_candidates(
+        &self,
+        folder: Option<&str>,
+        exclude_folder: Option<&str>,
+        tag: Option<&str>,
+    ) -> CoreResult<Vec<(Candidate, String)>> {
+        let prefix = |folder: Option<&str>| -> CoreResult<Option<String>> {
+            let Some(folder) = folder else {
+                return Ok(None);
+            };
+            let folder = normalize_folder_path(Some(folder))?;
+            Ok((!folder.is_empty()).then(|| format!("{folder}/")))
+        };
+        let (include, exclude) = (prefix(folder)?, prefix(exclude_folder)?);
+        let rows = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT d.id, d.rel_path, d.abs_path, d.description, d.updated_at, h.last_hit_at,
+                        COALESCE(r.times, 0), r.last_shown_at
+                 FROM docs d
+                 LEFT JOIN doc_hits h ON h.doc_id = d.id
+                 LEFT JOIN resurfaced r ON r.doc_id = d.id
+                 ORDER BY d.rel_path",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(Candidate {
+                        doc_id: row.get(0)?,
+                        rel_path: row.get(1)?,
+                        abs_path: row.get(2)?,
+                        description: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                        updated_at: row.get(4)?,
+                        last_hit_at: row.get(5)?,
+                        times_shown: row.get(6)?,
+                        last_shown_at: row.get(7)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+        let mut out = Vec::new();
+        for candidate in rows {
+            let path = candidate.rel_path.as_str();
+            if path.starts_with(IDEAS_DIR)
+                || path.starts_with(MEMORY_DIR)
+                || include.as_deref().is_some_and(|p| !path.starts_with(p))
+                || exclude.as_deref().is_some_and(|p| path.starts_with(p))
+            {
+                continue;
+            }
+            let content = fs::read_to_string(&candidate.abs_path).unwrap_or_default();
+            if tag.is_some_and(|tag| !has_tag(&content, tag)) {
+                continue;
+            }
+            out.push((candidate, content));
+        }
+        Ok(out)
+    }
+
+    /// Notes picked at random
+    pub fn random_docs(&self, query: &RandomQuery) -> CoreResult<Vec<PickedDoc>> {
+        let now = Utc::now();
+        let random = RandomState::new();
+        let mut candidates =
+            self.pick_candidates(query.folder.as_deref(), None, query.tag.as_deref())?;
+        candidates.sort_by_cached_key(|(c, _)| random.hash_one(&c.rel_path));
+        candidates.truncate(query.count.unwrap_or(1));
+        Ok(candidates
+            .into_iter()
+            .map(|(c, content)| picked(c, &content, now))
+            .collect())
+    }
+
+    /// Old notes due to be shown again (see [`is_due`]), a different set
+    /// each day. Unless `dry_run`, they are recorded as shown.
+    pub fn resurface(&self, options: &ResurfaceOptions) -> CoreResult<Vec<PickedDoc>> {
+        let now = Utc::now();
+        let min_age = options.min_age_days.unwrap_or(90).max(0);
+        let today = now.format("%Y-%m-%d").to_string();
+        let mut due: Vec<(Candidate, String)> = self
+            .pick_candidates(
+                options.folder.as_deref(),
+                options.exclude_folder.as_deref(),
+                options.tag.as_deref(),
+            )?
+            .into_iter()
+            .filter(|(c, _)| {
+                let idle = idle_days(c, now);
+                idle >= min_age && is_due(c.times_shown, c.last_shown_at.as_deref(), min_age, now)
+            })
+            .collect();
+        // Never-shown notes first; within that, an order fixed for the day
+        due.sort_by_cached_key(|(c, _)| {
+            let shuffle = content_revision(format!("{today}\n{}", c.rel_path).as_bytes());
+            (c.times_shown, shuffle)
+        });
+        due.truncate(options.count.unwrap_or(3));
+
+        if !options.dry_run && !due.is_empty() {
+            let ts = now_iso();
+            self.with_conn(|conn| {
+                let mut stmt = conn.prepare(
+                    "INSERT INTO resurfaced (doc_id, times, last_shown_at) VALUES (?1, 1, ?2)
+                     ON CONFLICT(doc_id) DO UPDATE SET times = times + 1, last_shown_at = ?2",
+                )?;
+                for (c, _) in &due {
+                    stmt.execute(params![c.doc_id, ts])?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(due
+            .into_iter()
+            .map(|(c, content)| picked(c, &content, now))
+            .collect())
+    }
+}
+
+/// Days since the note was last edited (in OpenContext or on disk) or opened
+fn idle_days(c: &Candidate, now: DateTime<Utc>) -> i64 {
+    let modified = fs::metadata(&c.abs_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+    let touched = [
+        timestamp(&c.updated_at),
+        modified,
+        c.last_hit_at.as_deref().and_then(timestamp),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+    touched.map_or(i64::MAX, |ts| (now - ts).num_days())
+}
+
+fn picked(c: Candidate, content: &str, now: DateTime<Utc>) -> PickedDoc {
+    PickedDoc {
+        idle_days: idle_days(&c, now),
+        excerpt: excerpt(content),
+        rel_path: c.rel_path,
+        description: c.description,
+        updated_at: c.updated_at,
+        times_shown: c.times_shown,
+        last_shown_at: c.last_shown_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::SecondsFormat;
+
+    #[test]
+    fn test_has_tag() {
+        let content =
+            "---\ntags: [reading, project/alpha]\n---\n# Notes #heading\nSee #Ideas, later.\n";
+        for tag in ["reading", "#project", "project/alpha", "ideas"] {
+            assert!(has_tag(content, tag), "{tag}");
+        }
+        for tag in ["read", "alpha", "heading"] {
+            assert!(!has_tag(content, tag), "{tag}");
+        }
+        assert!(has_tag("---\ntags: a, b\n---\n", "b"));
+    }
+
+    #[test]
+    fn test_is_due() {
+        let now = timestamp("2026-10-16T08:00:00Z").unwrap();
+        let shown =
+            |days: i64| (now - Duration::days(days)).to_rfc3339_opts(SecondsFormat::Millis, true);
+        assert!(is_due(0, None, 90, now));
+        assert!(!is_due(1, Some(&shown(89)), 90, now));
+        assert!(is_due(1, Some(&shown(90)), 90, now));
+        assert!(!is_due(2, Some(&shown(179)), 90, now));
+        assert!(is_due(2, Some(&shown(180)), 90, now));
+        assert!(!is_due(1, Some(&shown(0)), 0, now));
+        assert!(is_due(1, Some(&shown(1)), 0, now));
+    }
+}
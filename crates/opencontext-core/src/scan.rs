@@ -0,0 +1,222 @@
+//! Markdown file discovery under the contexts root.
+//!
+//! Symbolic links (and NTFS junctions, which the standard library reports
+//! as symlinks) are handled according to [`SymlinkPolicy`], set with
+//! `OPENCONTEXT_SYMLINKS` or [`EnvOverrides::symlinks`](crate::EnvOverrides):
+//!
+//! - `ignore` (default): links are skipped, only real files are listed.
+//! - `follow`: links are treated like their targets, so a shared folder
+//!   linked into two places is listed under both.
+//! - `alias`: links are followed, but every real file is listed once. A file
+//!   that also lives in the workspace keeps its own path; one reachable only
+//!   through links is listed under the first link path in sorted order.
+//!
+//! When following links, a directory that resolves to one of its own
+//! ancestors is not entered again, so link cycles end the walk instead of
+//! recursing forever.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CoreError, CoreResult, RelPath};
+
+/// How the scanner treats symbolic links and junctions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    #[default]
+    Ignore,
+    Follow,
+    Alias,
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> CoreResult<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "ignore" => Ok(Self::Ignore),
+            "follow" => Ok(Self::Follow),
+            "alias" => Ok(Self::Alias),
+            other => Err(CoreError::Message(format!(
+                "unknown symlink policy '{other}' (expected ignore, follow or alias)"
+            ))),
+        }
+    }
+}
+
+struct Found {
+    rel_path: String,
+    /// Resolved location, for telling aliases apart (alias policy only)
+    target: Option<PathBuf>,
+    via_link: bool,
+}
+
+struct Walk<'a> {
+    contexts_root: &'a Path,
+    policy: SymlinkPolicy,
+    /// Resolved directories from the scan root down to the current one
+    ancestors: Vec<PathBuf>,
+    found: Vec<Found>,
+}
+
+/// Recursively walk `dir` and append rel_paths (relative to `contexts_root`)
+/// of every `*.md` file. Hidden directories (starting with `.`) and any
+/// non-utf8 paths are skipped.
+pub(crate) fn scan_md_files(
+    dir: &Path,
+    contexts_root: &Path,
+    policy: SymlinkPolicy,
+    out: &mut Vec<String>,
+) -> CoreResult<()> {
+    let mut walk = Walk {
+        contexts_root,
+        policy,
+        ancestors: Vec::new(),
+        found: Vec::new(),
+    };
+    if policy != SymlinkPolicy::Ignore {
+        walk.ancestors.push(fs::canonicalize(dir)?);
+    }
+    walk.dir(dir, false)?;
+
+    if policy != SymlinkPolicy::Alias {
+        out.extend(walk.found.into_iter().map(|f| f.rel_path));
+        return Ok(());
+    }
+    // One entry per real file: its own path if it has one, else the
+    // first link path
+    let mut found = walk.found;
+    found.sort_by(|a, b| (a.via_link, &a.rel_path).cmp(&(b.via_link, &b.rel_path)));
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    for f in found {
+        if f.target.is_none_or(|target| seen.insert(target)) {
+            out.push(f.rel_path);
+        }
+    }
+    Ok(())
+}
+
+impl Walk<'_> {
+    fn dir(&mut self, dir: &Path, via_link: bool) -> CoreResult<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_name = match entry.file_name().into_string() {
+                Ok(n) => n,
+                Err(_) => continue,
+            };
+            if file_name.starts_with('.') {
+                continue;
+            }
+            let mut file_type = entry.file_type()?;
+            let is_link = file_type.is_symlink();
+            if is_link {
+                if self.policy == SymlinkPolicy::Ignore {
+                    continue;
+                }
+                // Dangling links are skipped
+                match fs::metadata(&path) {
+                    Ok(meta) => file_type = meta.file_type(),
+                    Err(_) => continue,
+                }
+            }
+            let via_link = via_link || is_link;
+            if file_type.is_dir() {
+                if self.policy == SymlinkPolicy::Ignore {
+                    self.dir(&path, via_link)?;
+                    continue;
+                }
+                let Ok(resolved) = fs::canonicalize(&path) else {
+                    continue;
+                };
+                if self.ancestors.contains(&resolved) {
+                    continue;
+                }
+                self.ancestors.push(resolved);
+                let result = self.dir(&path, via_link);
+                self.ancestors.pop();
+                result?;
+            } else if file_type.is_file()
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.eq_ignore_ascii_case("md"))
+                    .unwrap_or(false)
+            {
+                let Some(rel) = RelPath::from_abs(self.contexts_root, &path) else {
+                    continue;
+                };
+                let target = match self.policy {
+                    SymlinkPolicy::Alias => fs::canonicalize(&path).ok(),
+                    _ => None,
+                };
+                self.found.push(Found {
+                    rel_path: rel.into_string(),
+                    target,
+                    via_link,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    /// contexts/notes/a.md, contexts/notes/loop -> contexts/notes,
+    /// contexts/shared -> <outside>/shared, contexts/also -> contexts/notes
+    fn vault() -> (TempDir, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("contexts");
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join("notes/a.md"), "a").unwrap();
+        let outside = temp.path().join("other-repo/shared");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("b.md"), "b").unwrap();
+        symlink(root.join("notes"), root.join("notes/loop")).unwrap();
+        symlink(&outside, root.join("shared")).unwrap();
+        symlink(root.join("notes"), root.join("also")).unwrap();
+        symlink(root.join("missing"), root.join("dangling.md")).unwrap();
+        (temp, root)
+    }
+
+    fn scan(root: &Path, policy: SymlinkPolicy) -> Vec<String> {
+        let mut files = Vec::new();
+        scan_md_files(root, root, policy, &mut files).unwrap();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_symlink_policies() {
+        let (_temp, root) = vault();
+        assert_eq!(scan(&root, SymlinkPolicy::Ignore), vec!["notes/a.md"]);
+        assert_eq!(
+            scan(&root, SymlinkPolicy::Follow),
+            vec!["also/a.md", "notes/a.md", "shared/b.md"]
+        );
+        assert_eq!(
+            scan(&root, SymlinkPolicy::Alias),
+            vec!["notes/a.md", "shared/b.md"]
+        );
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(
+            "Follow".parse::<SymlinkPolicy>().unwrap(),
+            SymlinkPolicy::Follow
+        );
+        assert_eq!("".parse::<SymlinkPolicy>().unwrap(), SymlinkPolicy::Ignore);
+        assert!("sometimes".parse::<SymlinkPolicy>().is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Versioned JSON contract
+//!
+//! [`SearchOptions`], [`SearchResults`] (with its [`SearchHit`]s) and
+//! [`Event`] are what the Node.js binding, the HTTP server and MCP clients
+//! exchange with this crate. Their JSON field names are snake_case, and
+//! `SearchResults` and [`EventMessage`] carry `schema_version`. Adding an
+//! optional field keeps the version; renaming or removing a field, or
+//! changing what one means, bumps [`API_SCHEMA_VERSION`].
+//!
+//! With the `schema` feature, [`json_schemas`] describes these types as
+//! JSON Schema.
+//!
+//! [`SearchOptions`]: super::SearchOptions
+//! [`SearchResults`]: super::SearchResults
+//! [`SearchHit`]: super::SearchHit
+//! [`Event`]: crate::events::Event
+//! [`EventMessage`]: crate::events::EventMessage
+
+/// Version of the JSON contract described above
+///
+/// 2: `SearchOptions.doc_type` replaced by `filter`
+pub const API_SCHEMA_VERSION: u32 = 2;
+
+/// JSON Schema of each public API type, by type name
+#[cfg(feature = "schema")]
+pub fn json_schemas() -> std::collections::BTreeMap<&'static str, serde_json::Value> {
+    use super::types::{SearchOptions, SearchResults};
+    use crate::events::EventMessage;
+
+    let schema = |schema: schemars::Schema| {
+        let mut value = schema.to_value();
+        value["x-schema-version"] = API_SCHEMA_VERSION.into();
+        value
+    };
+    [
+        (
+            "SearchOptions",
+            schema(schemars::schema_for!(SearchOptions)),
+        ),
+        (
+            "SearchResults",
+            schema(schemars::schema_for!(SearchResults)),
+        ),
+        ("EventMessage", schema(schemars::schema_for!(EventMessage))),
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[cfg(all(test, feature = "schema"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schemas_use_snake_case() {
+        let schemas = json_schemas();
+        let options = &schemas["SearchOptions"]["properties"];
+        assert!(options.get("aggregate_by").is_some());
+        assert!(options.get("aggregateBy").is_none());
+
+        let results = &schemas["SearchResults"];
+        assert_eq!(results["x-schema-version"], API_SCHEMA_VERSION);
+        assert!(results["required"]
+            .as_array()
+            .unwrap()
+            .contains(&"schema_version".into()));
+        let hit = &results["$defs"]["SearchHit"]["properties"];
+        assert!(hit.get("file_path").is_some());
+        assert!(hit.get("doc_meta").is_some());
+    }
+}
@@ -0,0 +1,261 @@
+//! Blocking wrappers around the async search API
+//!
+//! [`Searcher`], [`Indexer`] and [`FederatedSearcher`] here mirror their
+//! async counterparts method for method, but run each call to completion on
+//! a tokio runtime shared by the whole process and started on first use.
+//! They are meant for callers that have no runtime of their own: FFI
+//! layers, small command-line tools, GUI event loops. The document API
+//! ([`crate::OpenContext`]) is synchronous already and needs no wrapper.
+//!
+//! Calling these from inside an async runtime would deadlock a worker
+//! thread, so they return an error there instead; async code should use
+//! [`crate::search::Searcher`] and friends directly.
+//!
+//! ```rust,ignore
+//! use opencontext_core::search::{blocking, SearchConfig, SearchOptions};
+//!
+//! let searcher = blocking::Searcher::new(SearchConfig::load()?)?;
+//! let results = searcher.search(SearchOptions::builder().query("roadmap").build()?)?;
+//! ```
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tokio::runtime::Runtime;
+use tokio_util::sync::CancellationToken;
+
+use super::config::SearchConfig;
+use super::error::{SearchError, SearchResult};
+use super::federation::FederatedResults;
+use super::indexer::{IndexProgress, IndexStats};
+use super::types::{ContextDoc, Readiness, SearchOptions, SearchResults, Suggestion};
+
+/// Run `future` to completion on the shared runtime
+fn block_on<F: Future>(future: F) -> SearchResult<F::Output> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(SearchError::Search(
+            "blocking API called from within an async runtime; use the async API instead"
+                .to_string(),
+        ));
+    }
+    Ok(runtime()?.block_on(future))
+}
+
+fn runtime() -> SearchResult<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("opencontext-blocking")
+        .enable_all()
+        .build()?;
+    // Another thread may have won the race; its runtime is kept and ours dropped
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Blocking version of [`crate::search::Searcher`]
+pub struct Searcher {
+    inner: super::Searcher,
+}
+
+impl Searcher {
+    /// See [`crate::search::Searcher::new`]
+    pub fn new(config: SearchConfig) -> SearchResult<Self> {
+        let inner = block_on(super::Searcher::new(config))??;
+        Ok(Self { inner })
+    }
+
+    /// The wrapped async searcher
+    pub fn inner(&self) -> &super::Searcher {
+        &self.inner
+    }
+
+    /// See [`crate::search::Searcher::warm_up`]
+    pub fn warm_up(&self, ping_embedding: bool) -> SearchResult<Readiness> {
+        block_on(self.inner.warm_up(ping_embedding))
+    }
+
+    /// See [`crate::search::Searcher::suggest`]
+    pub fn suggest(&self, prefix: &str, limit: usize) -> SearchResult<Vec<Suggestion>> {
+        self.inner.suggest(prefix, limit)
+    }
+
+    /// See [`crate::search::Searcher::search`]
+    pub fn search(&self, options: SearchOptions) -> SearchResult<SearchResults> {
+        block_on(self.inner.search(options))?
+    }
+
+    /// See [`crate::search::Searcher::search_with_cancel`]; `cancel` may be
+    /// fired from another thread while this one waits
+    pub fn search_with_cancel(
+        &self,
+        options: SearchOptions,
+        cancel: &CancellationToken,
+    ) -> SearchResult<SearchResults> {
+        block_on(self.inner.search_with_cancel(options, cancel))?
+    }
+
+    /// See [`crate::search::Searcher::search_with_context`]
+    pub fn search_with_context(
+        &self,
+        options: SearchOptions,
+        extra_docs: Vec<ContextDoc>,
+    ) -> SearchResult<SearchResults> {
+        block_on(self.inner.search_with_context(options, extra_docs))?
+    }
+
+    /// Search only files under `prefix`, see [`crate::search::Searcher::scoped`]
+    pub fn search_scoped(
+        &self,
+        prefix: &str,
+        options: SearchOptions,
+    ) -> SearchResult<SearchResults> {
+        block_on(self.inner.scoped(prefix).search(options))?
+    }
+
+    /// See [`crate::search::Searcher::index_exists`]
+    pub fn index_exists(&self) -> SearchResult<bool> {
+        block_on(self.inner.index_exists())
+    }
+
+    /// See [`crate::search::Searcher::refresh`]
+    pub fn refresh(&self) -> SearchResult<()> {
+        block_on(self.inner.refresh())?
+    }
+}
+
+/// Blocking version of [`crate::search::Indexer`]
+pub struct Indexer {
+    inner: super::Indexer,
+}
+
+impl Indexer {
+    /// See [`crate::search::Indexer::new`]
+    pub fn new(config: SearchConfig, contexts_root: PathBuf) -> SearchResult<Self> {
+        let inner = block_on(super::Indexer::new(config, contexts_root))??;
+        Ok(Self { inner })
+    }
+
+    /// Directory the indexed documents are read from
+    pub fn contexts_root(&self) -> &Path {
+        self.inner.contexts_root()
+    }
+
+    /// See [`crate::search::Indexer::set_cancellation`]
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.inner.set_cancellation(token);
+    }
+
+    /// See [`crate::search::Indexer::build_all`]
+    pub fn build_all(&mut self, docs: Vec<crate::Doc>) -> SearchResult<IndexStats> {
+        block_on(self.inner.build_all(docs))?
+    }
+
+    /// See [`crate::search::Indexer::build_all_with_progress`]
+    pub fn build_all_with_progress<F>(
+        &mut self,
+        docs: Vec<crate::Doc>,
+        on_progress: F,
+    ) -> SearchResult<IndexStats>
+    where
+        F: FnMut(IndexProgress),
+    {
+        block_on(self.inner.build_all_with_progress(docs, on_progress))?
+    }
+
+    /// See [`crate::search::Indexer::build_smart`]
+    pub fn build_smart<F>(
+        &mut self,
+        docs: Vec<crate::Doc>,
+        force: bool,
+        on_progress: F,
+    ) -> SearchResult<IndexStats>
+    where
+        F: FnMut(IndexProgress),
+    {
+        block_on(self.inner.build_smart(docs, force, on_progress))?
+    }
+
+    /// See [`crate::search::Indexer::index_file`]
+    pub fn index_file(&mut self, rel_path: &str) -> SearchResult<usize> {
+        block_on(self.inner.index_file(rel_path))?
+    }
+
+    /// See [`crate::search::Indexer::remove_file`]
+    pub fn remove_file(&mut self, rel_path: &str) -> SearchResult<()> {
+        block_on(self.inner.remove_file(rel_path))?
+    }
+
+    /// See [`crate::search::Indexer::update_file_path`]
+    pub fn update_file_path(&mut self, old_path: &str, new_path: &str) -> SearchResult<()> {
+        block_on(self.inner.update_file_path(old_path, new_path))?
+    }
+
+    /// See [`crate::search::Indexer::index_exists`]
+    pub fn index_exists(&self) -> SearchResult<bool> {
+        block_on(self.inner.index_exists())
+    }
+
+    /// See [`crate::search::Indexer::get_stats`]
+    pub fn get_stats(&self) -> SearchResult<IndexStats> {
+        block_on(self.inner.get_stats())?
+    }
+
+    /// See [`crate::search::Indexer::get_index_info`]
+    pub fn get_index_info(&self) -> SearchResult<serde_json::Value> {
+        block_on(self.inner.get_index_info())?
+    }
+
+    /// See [`crate::search::Indexer::clean`]
+    pub fn clean(&mut self) -> SearchResult<()> {
+        block_on(self.inner.clean())?
+    }
+
+    /// See [`crate::search::Indexer::update_metadata`]
+    pub fn update_metadata(&self) -> SearchResult<()> {
+        block_on(self.inner.update_metadata())?
+    }
+}
+
+/// Blocking version of [`crate::search::FederatedSearcher`]
+pub struct FederatedSearcher {
+    inner: super::FederatedSearcher,
+}
+
+impl FederatedSearcher {
+    /// See [`crate::search::FederatedSearcher::new`]
+    pub fn new(config: SearchConfig) -> SearchResult<Self> {
+        let inner = block_on(super::FederatedSearcher::new(config))??;
+        Ok(Self { inner })
+    }
+
+    /// Names of the federated workspaces, local first
+    pub fn workspaces(&self) -> Vec<String> {
+        self.inner.workspaces()
+    }
+
+    /// See [`crate::search::FederatedSearcher::search`]
+    pub fn search(&self, options: SearchOptions) -> SearchResult<FederatedResults> {
+        block_on(self.inner.search(options))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_on_runs_without_caller_runtime() {
+        assert_eq!(block_on(async { 40 + 2 }).unwrap(), 42);
+        // The runtime is shared, not rebuilt per call
+        assert!(std::ptr::eq(runtime().unwrap(), runtime().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_block_on_refuses_inside_async_runtime() {
+        let err = block_on(async {}).unwrap_err();
+        assert!(matches!(err, SearchError::Search(_)));
+    }
+}
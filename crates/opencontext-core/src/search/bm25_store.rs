@@ -1,5 +1,7 @@
 //! Tantivy-based BM25 full-text index
 
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
 use std::path::PathBuf;
 
 use tantivy::collector::TopDocs;
@@ -9,15 +11,40 @@ use tantivy::schema::{
 };
 use tantivy::{Index, IndexWriter, ReloadPolicy, TantivyDocument};
 
+use super::config::TokenizerConfig;
 use super::error::{SearchError, SearchResult};
-use super::types::{Chunk, MatchType, SearchHit};
+use super::facets::extract_tags;
+use super::tokenizer;
+use super::types::{Chunk, MatchType, SearchHit, Suggestion};
+
+/// Score multiplier for matches in the title field (file name / entry title)
+/// relative to body content
+const TITLE_BOOST: f32 = 3.0;
+
+/// Separates the lowercase match key, kind and display text in `suggest` terms
+const SUGGEST_SEP: char = '\u{1f}';
+
+/// Word-start suffixes indexed per suggestion ("auth design doc" also completes from "design")
+const SUGGEST_MAX_SUFFIXES: usize = 4;
+
+/// Default indexing buffer of a writer
+const WRITER_HEAP: usize = 50_000_000;
+
+/// Upper bound on dictionary terms scanned per `suggest` call
+const SUGGEST_SCAN_LIMIT: usize = 5000;
 
 /// Persistent BM25 index backed by tantivy
+#[derive(Clone)]
 pub struct Bm25Store {
     index: Index,
     schema: Bm25Schema,
+    /// Set when an index with an outdated schema was discarded on open
+    recreated: bool,
+    /// Indexing buffer of writers, see [`Bm25Store::with_writer_heap`]
+    writer_heap: usize,
 }
 
+#[derive(Clone)]
 struct Bm25Schema {
     schema: Schema,
     chunk_id: Field,
@@ -31,20 +58,24 @@ struct Bm25Schema {
     idea_box: Field,
     section_title: Field,
     chunk_index: Field,
+    title: Field,
+    suggest: Field,
+    /// Unstemmed lowercase words, the vocabulary for spelling correction
+    spell: Field,
 }
 
-fn build_schema() -> Bm25Schema {
+fn build_schema(text_tokenizer: &str) -> Bm25Schema {
     let mut builder = SchemaBuilder::new();
 
     let text_indexed = TextOptions::default().set_indexing_options(
         TextFieldIndexing::default()
-            .set_tokenizer("en_stem")
+            .set_tokenizer(text_tokenizer)
             .set_index_option(tantivy::schema::IndexRecordOption::WithFreqsAndPositions),
     );
 
     let chunk_id = builder.add_text_field("chunk_id", STRING | STORED);
     let file_path = builder.add_text_field("file_path", STRING | STORED);
-    let content = builder.add_text_field("content", text_indexed | STORED);
+    let content = builder.add_text_field("content", text_indexed.clone() | STORED);
     let heading_path = builder.add_text_field("heading_path", STRING | STORED);
     let doc_type = builder.add_text_field("doc_type", STRING | STORED);
     let entry_id = builder.add_text_field("entry_id", STRING | STORED);
@@ -53,6 +84,16 @@ fn build_schema() -> Bm25Schema {
     let idea_box = builder.add_text_field("idea_box", STRING | STORED);
     let section_title = builder.add_text_field("section_title", STRING | STORED);
     let chunk_index = builder.add_u64_field("chunk_index", FAST | STORED);
+    let title = builder.add_text_field("title", text_indexed);
+    let suggest = builder.add_text_field("suggest", STRING);
+    let spell = builder.add_text_field(
+        "spell",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("default")
+                .set_index_option(tantivy::schema::IndexRecordOption::Basic),
+        ),
+    );
 
     Bm25Schema {
         schema: builder.build(),
@@ -67,36 +108,80 @@ fn build_schema() -> Bm25Schema {
         idea_box,
         section_title,
         chunk_index,
+        title,
+        suggest,
+        spell,
     }
 }
 
 impl Bm25Store {
-    /// Open or create a tantivy index at `path`
+    /// Open or create a tantivy index at `path` with the default tokenizer settings
     pub fn open(path: PathBuf) -> SearchResult<Self> {
+        Self::open_with(path, &TokenizerConfig::default())
+    }
+
+    /// Open or create a tantivy index at `path`. An index built with
+    /// different tokenizer settings is discarded like any outdated schema.
+    pub fn open_with(path: PathBuf, tokenizer_config: &TokenizerConfig) -> SearchResult<Self> {
         std::fs::create_dir_all(&path)
-            .map_err(|e| SearchError::Index(format!("bm25 mkdir: {e}")))?;
+            .map_err(|e| SearchError::Index(format!("bm25 mkdir: {e}")).with_path(&path))?;
 
-        let schema_def = build_schema();
+        let schema_def = build_schema(&tokenizer::analyzer_name(tokenizer_config));
 
         // Try to open existing index; if meta file absent — create fresh
         let meta_file = path.join("meta.json");
-        let index = if meta_file.exists() {
-            tantivy::Index::open_in_dir(&path)
-                .map_err(|e| SearchError::Index(format!("bm25 open: {e}")))?
+        let mut recreated = false;
+        let existing = if meta_file.exists() {
+            let index = tantivy::Index::open_in_dir(&path)
+                .map_err(|e| SearchError::Index(format!("bm25 open: {e}")).with_path(&path))?;
+            if index.schema() == schema_def.schema {
+                Some(index)
+            } else {
+                // Field ids shift when the schema changes, so an old index can't be reused
+                log::warn!(
+                    "[Bm25Store] Index schema is outdated, recreating {}",
+                    path.display()
+                );
+                drop(index);
+                std::fs::remove_dir_all(&path)
+                    .and_then(|_| std::fs::create_dir_all(&path))
+                    .map_err(|e| SearchError::Index(format!("bm25 reset: {e}")))?;
+                recreated = true;
+                None
+            }
         } else {
-            tantivy::Index::create_in_dir(&path, schema_def.schema.clone())
-                .map_err(|e| SearchError::Index(format!("bm25 create: {e}")))?
+            None
+        };
+        let index = match existing {
+            Some(index) => index,
+            None => tantivy::Index::create_in_dir(&path, schema_def.schema.clone())
+                .map_err(|e| SearchError::Index(format!("bm25 create: {e}")))?,
         };
+        tokenizer::register(&index, tokenizer_config);
 
         Ok(Self {
             index,
             schema: schema_def,
+            recreated,
+            writer_heap: WRITER_HEAP,
         })
     }
 
+    /// Give writers an indexing buffer of `bytes` instead of 50 MB; tantivy
+    /// needs at least 15 MB
+    pub fn with_writer_heap(mut self, bytes: usize) -> Self {
+        self.writer_heap = bytes;
+        self
+    }
+
+    /// Whether `open` discarded an outdated index (callers should rebuild)
+    pub fn was_recreated(&self) -> bool {
+        self.recreated
+    }
+
     fn make_writer(&self) -> SearchResult<IndexWriter> {
         self.index
-            .writer(50_000_000)
+            .writer(self.writer_heap)
             .map_err(|e| SearchError::Index(format!("bm25 writer: {e}")))
     }
 
@@ -120,6 +205,12 @@ impl Bm25Store {
             chunk.section_title.as_deref().unwrap_or(""),
         );
         doc.add_u64(s.chunk_index, chunk.chunk_index as u64);
+        doc.add_text(s.title, title_text(chunk));
+        doc.add_text(s.spell, &chunk.content);
+        doc.add_text(s.spell, basename(&chunk.file_path));
+        for term in suggest_terms(chunk) {
+            doc.add_text(s.suggest, term);
+        }
         doc
     }
 
@@ -174,23 +265,45 @@ impl Bm25Store {
         Ok(reader.searcher().num_docs() as usize)
     }
 
-    /// Search using BM25 — returns ranked `SearchHit`s
-    pub fn search(&self, query_str: &str, limit: usize) -> SearchResult<Vec<SearchHit>> {
+    /// `(chunk_id, file_path, content)` of every indexed chunk
+    pub fn all_contents(&self) -> SearchResult<Vec<(String, String, String)>> {
         let reader = self
             .index
             .reader_builder()
             .reload_policy(ReloadPolicy::OnCommitWithDelay)
             .try_into()
             .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
-
         let searcher = reader.searcher();
 
-        let query_parser = QueryParser::for_index(
+        let addresses = searcher
+            .search(
+                &tantivy::query::AllQuery,
+                &tantivy::collector::DocSetCollector,
+            )
+            .map_err(|e| SearchError::Index(format!("bm25 scan: {e}")))?;
+        Ok(addresses
+            .into_iter()
+            .filter_map(|addr| searcher.doc::<TantivyDocument>(addr).ok())
+            .map(|doc| {
+                let (_, hit) = self.stored_hit(&doc);
+                (hit.chunk_id.unwrap_or_default(), hit.file_path, hit.content)
+            })
+            .collect())
+    }
+
+    /// Parse a user query over content, heading path and (boosted) title
+    fn parse_query(&self, query_str: &str) -> Box<dyn tantivy::query::Query> {
+        let mut query_parser = QueryParser::for_index(
             &self.index,
-            vec![self.schema.content, self.schema.heading_path],
+            vec![
+                self.schema.content,
+                self.schema.heading_path,
+                self.schema.title,
+            ],
         );
+        query_parser.set_field_boost(self.schema.title, TITLE_BOOST);
 
-        let query = query_parser.parse_query(query_str).unwrap_or_else(|_| {
+        query_parser.parse_query(query_str).unwrap_or_else(|_| {
             // Fallback: wrap as literal phrase to avoid parse errors on special chars
             let safe: String = query_str
                 .chars()
@@ -205,7 +318,64 @@ impl Bm25Store {
             query_parser
                 .parse_query(safe.trim())
                 .unwrap_or_else(|_| query_parser.parse_query("").unwrap())
-        });
+        })
+    }
+
+    /// Restrict `query` to chunks of files under `folder` (a path prefix).
+    /// The folder clause only filters; it adds nothing to the score.
+    fn in_folder(
+        &self,
+        query: Box<dyn tantivy::query::Query>,
+        folder: Option<&str>,
+    ) -> Box<dyn tantivy::query::Query> {
+        use tantivy::query::{BooleanQuery, ConstScoreQuery, Occur, RangeQuery, TermQuery};
+
+        let Some(folder) = folder.map(|f| f.trim_end_matches('/')) else {
+            return query;
+        };
+        let exact = TermQuery::new(
+            tantivy::Term::from_field_text(self.schema.file_path, folder),
+            tantivy::schema::IndexRecordOption::Basic,
+        );
+        // Paths below "a/b/" sort before "a/b0" ('0' is the byte after '/')
+        let (lower, upper) = (format!("{folder}/"), format!("{folder}0"));
+        let below = RangeQuery::new_str_bounds(
+            "file_path".to_string(),
+            Bound::Included(&lower),
+            Bound::Excluded(&upper),
+        );
+        let scope = BooleanQuery::union(vec![Box::new(exact), Box::new(below)]);
+        Box::new(BooleanQuery::new(vec![
+            (Occur::Must, query),
+            (
+                Occur::Must,
+                Box::new(ConstScoreQuery::new(Box::new(scope), 0.0)),
+            ),
+        ]))
+    }
+
+    /// Search using BM25 — returns ranked `SearchHit`s
+    pub fn search(&self, query_str: &str, limit: usize) -> SearchResult<Vec<SearchHit>> {
+        self.search_in(query_str, limit, None)
+    }
+
+    /// BM25 search over the chunks of files under `folder`
+    pub fn search_in(
+        &self,
+        query_str: &str,
+        limit: usize,
+        folder: Option<&str>,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+
+        let searcher = reader.searcher();
+
+        let query = self.in_folder(self.parse_query(query_str), folder);
 
         let top_docs = searcher
             .search(&query, &TopDocs::with_limit(limit))
@@ -268,6 +438,7 @@ impl Bm25Store {
             };
 
             hits.push(SearchHit {
+                chunk_id: get_opt(s.chunk_id),
                 file_path,
                 display_name,
                 content: get_str(s.content),
@@ -275,6 +446,7 @@ impl Bm25Store {
                 section_title,
                 line_start: None,
                 line_end: None,
+                block_id: None,
                 score: normalized_score,
                 matched_by: MatchType::Keyword,
                 hit_count: None,
@@ -286,12 +458,38 @@ impl Bm25Store {
                 entry_date: get_opt(s.entry_date),
                 entry_created_at: get_opt(s.entry_created_at),
                 idea_box: get_opt(s.idea_box),
+                snippet: None,
+                children: Vec::new(),
+                also_in: Vec::new(),
+                folder_summary: None,
+                doc_meta: None,
+                citation: None,
+                provenance: None,
+                trust: None,
             });
         }
 
         Ok(hits)
     }
 
+    /// Exact number of chunks matching `query_str`, optionally only under `folder`
+    pub fn count_matches(&self, query_str: &str, folder: Option<&str>) -> SearchResult<usize> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+
+        reader
+            .searcher()
+            .search(
+                &self.in_folder(self.parse_query(query_str), folder),
+                &tantivy::collector::Count,
+            )
+            .map_err(|e| SearchError::Index(format!("bm25 count: {e}")))
+    }
+
     /// Fetch all chunks for a given file_path, sorted by chunk_index.
     /// Used for neighboring chunk context expansion.
     pub fn get_chunks_by_file(&self, file_path: &str) -> SearchResult<Vec<SearchHit>> {
@@ -315,8 +513,217 @@ impl Bm25Store {
             .search(&query, &TopDocs::with_limit(1000))
             .map_err(|e| SearchError::Index(format!("bm25 file chunks: {e}")))?;
 
+        let mut chunks: Vec<(u64, SearchHit)> = top_docs
+            .into_iter()
+            .filter_map(|(_, addr)| searcher.doc(addr).ok().map(|doc| self.stored_hit(&doc)))
+            .collect();
+
+        chunks.sort_by_key(|(idx, _)| *idx);
+        Ok(chunks.into_iter().map(|(_, h)| h).collect())
+    }
+
+    /// List every distinct indexed file path
+    pub fn file_paths(&self) -> SearchResult<Vec<String>> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+        let searcher = reader.searcher();
+
+        let mut paths = std::collections::BTreeSet::new();
+        for segment in searcher.segment_readers() {
+            let inverted = segment
+                .inverted_index(self.schema.file_path)
+                .map_err(|e| SearchError::Index(format!("bm25 paths: {e}")))?;
+            let mut terms = inverted
+                .terms()
+                .stream()
+                .map_err(|e| SearchError::Index(format!("bm25 paths: {e}")))?;
+            while terms.advance() {
+                paths.insert(String::from_utf8_lossy(terms.key()).into_owned());
+            }
+        }
+
+        // The term dictionary keeps paths of deleted docs until segments merge
+        let alive = |path: &String| {
+            let term = tantivy::Term::from_field_text(self.schema.file_path, path);
+            let query =
+                tantivy::query::TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+            searcher
+                .search(&query, &tantivy::collector::Count)
+                .map(|n| n > 0)
+                .unwrap_or(false)
+        };
+        Ok(paths.into_iter().filter(alive).collect())
+    }
+
+    /// Complete `prefix` from indexed titles, headings and tags, most frequent first.
+    /// Counts are chunk frequencies and may include deleted chunks until segments merge.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> SearchResult<Vec<Suggestion>> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+        let searcher = reader.searcher();
+
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for segment in searcher.segment_readers() {
+            let inverted = segment
+                .inverted_index(self.schema.suggest)
+                .map_err(|e| SearchError::Index(format!("bm25 suggest: {e}")))?;
+            let mut terms = inverted
+                .terms()
+                .range()
+                .ge(prefix.as_bytes())
+                .into_stream()
+                .map_err(|e| SearchError::Index(format!("bm25 suggest: {e}")))?;
+            let mut scanned = 0;
+            while terms.advance() && scanned < SUGGEST_SCAN_LIMIT {
+                scanned += 1;
+                if !terms.key().starts_with(prefix.as_bytes()) {
+                    break;
+                }
+                let key = String::from_utf8_lossy(terms.key());
+                let mut parts = key.splitn(3, SUGGEST_SEP).skip(1);
+                let (Some(kind), Some(text)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                *counts
+                    .entry((kind.to_string(), text.to_string()))
+                    .or_default() += terms.value().doc_freq as usize;
+            }
+        }
+
+        let mut suggestions: Vec<Suggestion> = counts
+            .into_iter()
+            .map(|((kind, text), count)| Suggestion { text, kind, count })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.text.len().cmp(&b.text.len()))
+                .then_with(|| a.text.cmp(&b.text))
+        });
+        let mut seen = BTreeSet::new();
+        suggestions.retain(|s| seen.insert(s.text.to_lowercase()));
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+
+    /// Index version that changes on every commit (for caching derived data)
+    pub fn opstamp(&self) -> SearchResult<u64> {
+        self.index
+            .load_metas()
+            .map(|m| m.opstamp)
+            .map_err(|e| SearchError::Index(format!("bm25 metas: {e}")))
+    }
+
+    /// Every unstemmed word in the corpus with its document frequency
+    pub fn vocabulary(&self) -> SearchResult<Vec<(String, u64)>> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+
+        let mut words: HashMap<String, u64> = HashMap::new();
+        for segment in reader.searcher().segment_readers() {
+            let inverted = segment
+                .inverted_index(self.schema.spell)
+                .map_err(|e| SearchError::Index(format!("bm25 vocabulary: {e}")))?;
+            let mut terms = inverted
+                .terms()
+                .stream()
+                .map_err(|e| SearchError::Index(format!("bm25 vocabulary: {e}")))?;
+            while terms.advance() {
+                let word = String::from_utf8_lossy(terms.key()).into_owned();
+                *words.entry(word).or_default() += u64::from(terms.value().doc_freq);
+            }
+        }
+        Ok(words.into_iter().collect())
+    }
+
+    /// Number of indexed chunks and the document frequency of each word
+    /// (as produced by the spelling tokenizer, i.e. lowercase and unstemmed)
+    pub fn doc_freqs(&self, words: &[&str]) -> SearchResult<(u64, Vec<u64>)> {
+        use tantivy::Term;
+
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+        let searcher = reader.searcher();
+
+        let freqs = words
+            .iter()
+            .map(|w| {
+                searcher
+                    .doc_freq(&Term::from_field_text(self.schema.spell, w))
+                    .map_err(|e| SearchError::Index(format!("bm25 doc freq: {e}")))
+            })
+            .collect::<SearchResult<Vec<u64>>>()?;
+        Ok((searcher.num_docs(), freqs))
+    }
+
+    /// Fetch stored chunks by chunk id, in the order of `chunk_ids`.
+    /// Ids missing from the index are skipped.
+    pub fn get_chunks_by_ids(&self, chunk_ids: &[String]) -> SearchResult<Vec<SearchHit>> {
+        use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
+        use tantivy::schema::IndexRecordOption;
+        use tantivy::Term;
+
+        if chunk_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| SearchError::Index(format!("bm25 reader: {e}")))?;
+        let searcher = reader.searcher();
+
+        let clauses: Vec<(Occur, Box<dyn Query>)> = chunk_ids
+            .iter()
+            .map(|id| {
+                let term = Term::from_field_text(self.schema.chunk_id, id);
+                let query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                (Occur::Should, query)
+            })
+            .collect();
+        let query = BooleanQuery::new(clauses);
+
+        let top_docs = searcher
+            .search(&query, &TopDocs::with_limit(chunk_ids.len()))
+            .map_err(|e| SearchError::Index(format!("bm25 chunks by id: {e}")))?;
+
+        let mut by_id: std::collections::HashMap<String, SearchHit> = top_docs
+            .into_iter()
+            .filter_map(|(_, addr)| searcher.doc(addr).ok().map(|doc| self.stored_hit(&doc).1))
+            .filter_map(|hit| hit.chunk_id.clone().map(|id| (id, hit)))
+            .collect();
+
+        Ok(chunk_ids.iter().filter_map(|id| by_id.remove(id)).collect())
+    }
+
+    /// Convert a stored document back into `(chunk_index, SearchHit)`
+    fn stored_hit(&self, doc: &TantivyDocument) -> (u64, SearchHit) {
         let s = &self.schema;
-        let get_str = |doc: &TantivyDocument, field: tantivy::schema::Field| -> String {
+        let get_str = |field: Field| -> String {
             doc.get_first(field)
                 .and_then(|v| {
                     if let tantivy::schema::OwnedValue::Str(s) = v {
@@ -328,73 +735,111 @@ impl Bm25Store {
                 .unwrap_or("")
                 .to_string()
         };
-        let get_opt = |doc: &TantivyDocument, field: tantivy::schema::Field| -> Option<String> {
-            let v = get_str(doc, field);
+        let get_opt = |field: Field| -> Option<String> {
+            let v = get_str(field);
             if v.is_empty() {
                 None
             } else {
                 Some(v)
             }
         };
-        let get_u64 = |doc: &TantivyDocument, field: tantivy::schema::Field| -> u64 {
-            doc.get_first(field)
-                .and_then(|v| {
-                    if let tantivy::schema::OwnedValue::U64(n) = v {
-                        Some(*n)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(0)
+        let chunk_index = doc
+            .get_first(s.chunk_index)
+            .and_then(|v| {
+                if let tantivy::schema::OwnedValue::U64(n) = v {
+                    Some(*n)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0);
+
+        let fp = get_str(s.file_path);
+        let display_name = basename(&fp);
+        let hit = SearchHit {
+            chunk_id: get_opt(s.chunk_id),
+            file_path: fp,
+            display_name,
+            content: get_str(s.content),
+            heading_path: get_opt(s.heading_path),
+            section_title: get_opt(s.section_title),
+            line_start: None,
+            line_end: None,
+            block_id: None,
+            score: 0.0,
+            matched_by: MatchType::Keyword,
+            hit_count: None,
+            doc_count: None,
+            folder_path: None,
+            aggregate_type: None,
+            doc_type: get_opt(s.doc_type),
+            entry_id: get_opt(s.entry_id),
+            entry_date: get_opt(s.entry_date),
+            entry_created_at: get_opt(s.entry_created_at),
+            idea_box: get_opt(s.idea_box),
+            snippet: None,
+            children: Vec::new(),
+            also_in: Vec::new(),
+            folder_summary: None,
+            doc_meta: None,
+            citation: None,
+            provenance: None,
+            trust: None,
         };
+        (chunk_index, hit)
+    }
+}
 
-        let mut chunks: Vec<(u64, SearchHit)> = top_docs
+/// Searchable title of a chunk: the file name with separators as spaces,
+/// plus the entry title for idea chunks
+fn title_text(chunk: &Chunk) -> String {
+    let stem = basename(&chunk.file_path).replace(['-', '_', '.'], " ");
+    match chunk.section_title.as_deref() {
+        Some(title) if chunk.doc_type.as_deref() == Some("idea") => format!("{stem} {title}"),
+        _ => stem,
+    }
+}
+
+/// Terms for the `suggest` field: document title, heading segments and tags,
+/// each keyed by its lowercase text and by a few word-start suffixes
+fn suggest_terms(chunk: &Chunk) -> BTreeSet<String> {
+    let mut entries: Vec<(&str, String)> = vec![("title", basename(&chunk.file_path))];
+    entries.extend(
+        chunk
+            .heading_path
+            .split(" > ")
+            .chain(chunk.section_title.as_deref())
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .map(|h| ("heading", h.to_string())),
+    );
+    entries.extend(
+        extract_tags(&chunk.content)
             .into_iter()
-            .filter_map(|(_, addr)| {
-                searcher.doc(addr).ok().map(|doc| {
-                    let chunk_index = get_u64(&doc, s.chunk_index);
-                    let fp = get_str(&doc, s.file_path);
-                    let doc_type = get_opt(&doc, s.doc_type);
-                    let section_title = get_opt(&doc, s.section_title);
-                    let heading_path = get_opt(&doc, s.heading_path);
-                    let display_name = basename(&fp);
-                    let hit = SearchHit {
-                        file_path: fp,
-                        display_name,
-                        content: get_str(&doc, s.content),
-                        heading_path,
-                        section_title,
-                        line_start: None,
-                        line_end: None,
-                        score: 0.0,
-                        matched_by: MatchType::Keyword,
-                        hit_count: None,
-                        doc_count: None,
-                        folder_path: None,
-                        aggregate_type: None,
-                        doc_type,
-                        entry_id: get_opt(&doc, s.entry_id),
-                        entry_date: get_opt(&doc, s.entry_date),
-                        entry_created_at: get_opt(&doc, s.entry_created_at),
-                        idea_box: get_opt(&doc, s.idea_box),
-                    };
-                    (chunk_index, hit)
-                })
-            })
-            .collect();
+            .map(|t| ("tag", format!("#{t}"))),
+    );
 
-        chunks.sort_by_key(|(idx, _)| *idx);
-        Ok(chunks.into_iter().map(|(_, h)| h).collect())
+    let mut terms = BTreeSet::new();
+    for (kind, text) in entries {
+        let lower = text.to_lowercase();
+        let starts = std::iter::once(0).chain(
+            lower
+                .char_indices()
+                .filter(|(_, c)| matches!(c, ' ' | '-' | '_' | '/' | '#'))
+                .map(|(i, c)| i + c.len_utf8()),
+        );
+        for start in starts.take(SUGGEST_MAX_SUFFIXES) {
+            let key = lower[start..].trim_start();
+            if !key.is_empty() {
+                terms.insert(format!("{key}{SUGGEST_SEP}{kind}{SUGGEST_SEP}{text}"));
+            }
+        }
     }
+    terms
 }
 
 fn basename(file_path: &str) -> String {
-    file_path
-        .split('/')
-        .next_back()
-        .unwrap_or(file_path)
-        .trim_end_matches(".md")
-        .to_string()
+    crate::relpath::display_name(file_path).to_string()
 }
 
 #[cfg(all(test, feature = "search"))]
@@ -446,6 +891,48 @@ mod tests {
         assert!(found, "expected doc2.md in search results");
     }
 
+    #[test]
+    fn test_identifier_parts_match_words() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        store
+            .index_all(&[
+                make_chunk(
+                    "c1",
+                    "code.md",
+                    "the searcher owns a vector_store and an EmbeddingClient",
+                ),
+                make_chunk("c2", "prose.md", "a store of vector data"),
+            ])
+            .expect("index chunks");
+
+        for query in ["vector store", "embedding client"] {
+            let results = store.search(query, 5).expect("search");
+            assert!(results.iter().any(|h| h.file_path == "code.md"), "{query}");
+        }
+        for query in ["\"vector store\"", "vector_store"] {
+            let results = store.search(query, 5).expect("search");
+            assert_eq!(results.len(), 1, "{query}");
+            assert_eq!(results[0].file_path, "code.md");
+        }
+    }
+
+    #[test]
+    fn test_dates_match_across_formats() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        store
+            .index_all(&[
+                make_chunk("c1", "retro.md", "Sprint review held 2024-03-05"),
+                make_chunk("c2", "other.md", "Sprint review held 2023-11-20"),
+            ])
+            .expect("index chunks");
+
+        let results = store.search("march 2024", 5).expect("search");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file_path, "retro.md");
+    }
+
     #[test]
     fn test_update_removes_and_adds() {
         let tmpdir = tempfile::tempdir().expect("create tempdir");
@@ -462,4 +949,150 @@ mod tests {
         let results = store.search("original", 5).expect("search");
         assert_eq!(results.len(), 0, "expected no results for removed content");
     }
+
+    #[test]
+    fn test_title_match_ranks_first() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+
+        store
+            .index_all(&[
+                make_chunk(
+                    "c1",
+                    "notes/planning.md",
+                    "the roadmap roadmap is discussed here",
+                ),
+                make_chunk("c2", "product/roadmap.md", "Q3 goals and milestones"),
+                make_chunk("c3", "notes/misc.md", "see the roadmap"),
+            ])
+            .expect("index chunks");
+
+        let results = store.search("roadmap", 5).expect("search");
+        assert_eq!(results[0].file_path, "product/roadmap.md");
+    }
+
+    #[test]
+    fn test_outdated_schema_is_recreated() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("chunk_id", STRING | STORED);
+        tantivy::Index::create_in_dir(tmpdir.path(), builder.build()).expect("old index");
+
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        assert!(store.was_recreated());
+        store
+            .index_all(&[make_chunk("c1", "a.md", "hello")])
+            .expect("index chunks");
+        assert_eq!(store.count().expect("count"), 1);
+        drop(store);
+
+        let reopened = Bm25Store::open(tmpdir.path().to_path_buf()).expect("reopen index");
+        assert!(!reopened.was_recreated());
+        assert_eq!(reopened.count().expect("count"), 1);
+    }
+
+    #[test]
+    fn test_file_paths_skip_deleted() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        store
+            .index_all(&[
+                make_chunk("c1", "plans/roadmap.md", "next quarter"),
+                make_chunk("c2", "notes/auth.md", "oauth flow"),
+            ])
+            .expect("index chunks");
+        store
+            .update(&["notes/auth.md".to_string()], &[])
+            .expect("update");
+
+        assert_eq!(store.file_paths().expect("paths"), vec!["plans/roadmap.md"]);
+    }
+
+    #[test]
+    fn test_get_chunks_by_ids_keeps_order() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        store
+            .index_all(&[
+                make_chunk("a.md#0", "a.md", "first"),
+                make_chunk("a.md#1", "a.md", "second"),
+            ])
+            .expect("index chunks");
+
+        let ids = vec![
+            "a.md#1".to_string(),
+            "missing#0".to_string(),
+            "a.md#0".to_string(),
+        ];
+        let hits = store.get_chunks_by_ids(&ids).expect("by ids");
+        let contents: Vec<&str> = hits.iter().map(|h| h.content.as_str()).collect();
+        assert_eq!(contents, vec!["second", "first"]);
+    }
+
+    #[test]
+    fn test_count_matches_beyond_limit() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        let chunks: Vec<Chunk> = (0..5)
+            .map(|i| make_chunk(&format!("n{i}.md#0"), &format!("n{i}.md"), "rust notes"))
+            .chain([make_chunk("other.md#0", "other.md", "unrelated")])
+            .collect();
+        store.index_all(&chunks).expect("index chunks");
+
+        assert_eq!(store.search("rust", 2).expect("search").len(), 2);
+        assert_eq!(store.count_matches("rust", None).expect("count"), 5);
+    }
+
+    #[test]
+    fn test_search_in_folder() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        let chunks = vec![
+            make_chunk("a1#0", "proj/a.md", "rust notes"),
+            make_chunk("a2#0", "proj/sub/b.md", "rust notes"),
+            make_chunk("a3#0", "project/c.md", "rust notes"),
+            make_chunk("a4#0", "other/d.md", "rust notes"),
+        ];
+        store.index_all(&chunks).expect("index chunks");
+
+        let mut paths: Vec<String> = store
+            .search_in("rust", 10, Some("proj/"))
+            .expect("search")
+            .into_iter()
+            .map(|h| h.file_path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["proj/a.md", "proj/sub/b.md"]);
+        assert_eq!(store.count_matches("rust", Some("proj")).expect("count"), 2);
+        assert_eq!(
+            store
+                .search_in("rust", 10, Some("other/d.md"))
+                .expect("search")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_suggest_titles_headings_tags() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let store = Bm25Store::open(tmpdir.path().to_path_buf()).expect("open index");
+        let mut heading = make_chunk("b.md#0", "notes/b.md", "body #design-review");
+        heading.heading_path = "Overview > Design goals".to_string();
+        store
+            .index_all(&[
+                make_chunk("a.md#0", "specs/auth-design.md", "text"),
+                make_chunk("a.md#1", "specs/auth-design.md", "more"),
+                heading,
+            ])
+            .expect("index chunks");
+
+        let suggestions = store.suggest("Desi", 10).expect("suggest");
+        let texts: Vec<&str> = suggestions.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts[0], "auth-design");
+        assert_eq!(suggestions[0].count, 2);
+        assert!(texts.contains(&"Design goals"));
+        assert!(texts.contains(&"#design-review"));
+        assert!(store.suggest("zzz", 10).expect("suggest").is_empty());
+    }
 }
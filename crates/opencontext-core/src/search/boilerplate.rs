@@ -0,0 +1,128 @@
+//! Boilerplate detection
+//!
+//! License headers, note templates and navigation footers repeat across many
+//! documents and crowd out real content in search results. Each chunk is cut
+//! into overlapping word shingles; a shingle seen in many distinct documents
+//! is boilerplate, and a chunk's `boilerplate_score` is the fraction of its
+//! shingles that are. Scores are recomputed after every index update, saved
+//! next to the index and applied as a ranking penalty by the searcher.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+/// Words per shingle
+const SHINGLE_WORDS: usize = 5;
+
+/// A shingle is boilerplate once it occurs in this many documents
+/// (or in 2% of all documents, whichever is larger)
+const MIN_DOCS: usize = 3;
+
+/// Chunks scoring below this are not stored
+const MIN_STORED_SCORE: f32 = 0.05;
+
+/// Boilerplate score (0–1) by chunk id
+pub type Scores = HashMap<String, f32>;
+
+/// Boilerplate score per chunk id for `(chunk_id, file_path, content)` triples.
+/// Only chunks with a non-negligible score are returned.
+pub fn scores<'a>(chunks: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>) -> Scores {
+    let chunks: Vec<(&str, &str, HashSet<u64>)> = chunks
+        .into_iter()
+        .map(|(id, file, content)| (id, file, shingles(content)))
+        .collect();
+
+    let mut files_per_shingle: HashMap<u64, HashSet<&str>> = HashMap::new();
+    for (_, file, hashes) in &chunks {
+        for hash in hashes {
+            files_per_shingle.entry(*hash).or_default().insert(file);
+        }
+    }
+
+    let file_count = chunks
+        .iter()
+        .map(|(_, file, _)| *file)
+        .collect::<HashSet<_>>()
+        .len();
+    let threshold = MIN_DOCS.max(file_count / 50);
+
+    chunks
+        .iter()
+        .filter(|(_, _, hashes)| !hashes.is_empty())
+        .filter_map(|(id, _, hashes)| {
+            let repeated = hashes
+                .iter()
+                .filter(|h| files_per_shingle[h].len() >= threshold)
+                .count();
+            let score = repeated as f32 / hashes.len() as f32;
+            (score >= MIN_STORED_SCORE).then(|| (id.to_string(), score))
+        })
+        .collect()
+}
+
+/// Hashes of the chunk's word shingles; short chunks are a single shingle
+fn shingles(content: &str) -> HashSet<u64> {
+    let words: Vec<String> = content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return HashSet::new();
+    }
+    let hash = |window: &[String]| {
+        let mut hasher = DefaultHasher::new();
+        window.hash(&mut hasher);
+        hasher.finish()
+    };
+    if words.len() < SHINGLE_WORDS {
+        return HashSet::from([hash(&words)]);
+    }
+    words.windows(SHINGLE_WORDS).map(hash).collect()
+}
+
+/// Persist scores as JSON
+pub fn save(path: &Path, scores: &Scores) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(scores)?)
+}
+
+/// Load saved scores; missing or unreadable files mean no penalties
+pub fn load(path: &Path) -> Scores {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_footer_scores_high() {
+        let footer = "Copyright ACME Corp. All rights reserved. See LICENSE for details.";
+        let docs: Vec<(String, String, String)> = (0..4)
+            .flat_map(|i| {
+                [
+                    (
+                        format!("d{i}#0"),
+                        format!("d{i}.md"),
+                        format!("Unique note number {i} about topic {i} with its own words"),
+                    ),
+                    (format!("d{i}#1"), format!("d{i}.md"), footer.to_string()),
+                ]
+            })
+            .collect();
+
+        let scores = scores(
+            docs.iter()
+                .map(|(id, file, content)| (id.as_str(), file.as_str(), content.as_str())),
+        );
+        assert_eq!(scores.get("d0#1"), Some(&1.0));
+        assert!(!scores.contains_key("d0#0"), "{scores:?}");
+    }
+}
@@ -0,0 +1,173 @@
+//! Canonical date and number tokens
+//!
+//! Dates and numbers are written many ways ("2024-03-05", "Mar 5, 2024",
+//! "2024年3月5日"; "1,000" / "1000"; "3.50" / "3.5"). Alongside the words the
+//! tokenizer produces, the keyword analyzer emits one canonical token per
+//! date granularity (`2024m03`, `2024m03d05`) and per number, at both index
+//! and query time, so different spellings of the same value match.
+
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use super::tokenizer::Word;
+
+const MONTHS: &str = "jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?\
+|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?";
+
+struct Patterns {
+    numeric_date: Regex,
+    month_day_year: Regex,
+    day_month_year: Regex,
+    month_year: Regex,
+    year_month: Regex,
+    grouped_number: Regex,
+    decimal: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let re = |p: &str| Regex::new(p).expect("valid date pattern");
+        Patterns {
+            numeric_date: re(r"\b([0-9]{4})[-/.年]([0-9]{1,2})[-/.月]([0-9]{1,2})日?"),
+            month_day_year: re(&format!(
+                r"(?i)\b({MONTHS})\.?\s+([0-9]{{1,2}})(?:st|nd|rd|th)?,?\s+([0-9]{{4}})\b"
+            )),
+            day_month_year: re(&format!(
+                r"(?i)\b([0-9]{{1,2}})(?:st|nd|rd|th)?\s+({MONTHS})\.?,?\s+([0-9]{{4}})\b"
+            )),
+            month_year: re(&format!(r"(?i)\b({MONTHS})\.?,?\s+([0-9]{{4}})\b")),
+            year_month: re(r"\b([0-9]{4})(?:[-/]|年)([0-9]{1,2})(?:月|\b)"),
+            grouped_number: re(r"\b[0-9]{1,3}(?:,[0-9]{3})+(?:\.[0-9]+)?\b"),
+            decimal: re(r"\b[0-9]+\.[0-9]+\b"),
+        }
+    })
+}
+
+/// Canonical tokens for the dates and numbers in `text`, with the byte
+/// range of the expression each one stands for
+pub fn canonical_tokens(text: &str) -> Vec<Word> {
+    let p = patterns();
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut tokens = Vec::new();
+
+    let mut add = |m: &Captures, canonical: Vec<String>, spans: &mut Vec<(usize, usize)>| {
+        let whole = m.get(0).expect("match");
+        let (from, to) = (whole.start(), whole.end());
+        if canonical.is_empty() || spans.iter().any(|&(a, b)| from < b && a < to) {
+            return;
+        }
+        spans.push((from, to));
+        tokens.extend(canonical.into_iter().map(|text| Word {
+            text,
+            offset_from: from,
+            offset_to: to,
+        }));
+    };
+
+    for m in p.numeric_date.captures_iter(text) {
+        add(&m, date(&m[1], &m[2], Some(&m[3])), &mut spans);
+    }
+    for m in p.month_day_year.captures_iter(text) {
+        let month = month_number(&m[1]).to_string();
+        add(&m, date(&m[3], &month, Some(&m[2])), &mut spans);
+    }
+    for m in p.day_month_year.captures_iter(text) {
+        let month = month_number(&m[2]).to_string();
+        add(&m, date(&m[3], &month, Some(&m[1])), &mut spans);
+    }
+    for m in p.month_year.captures_iter(text) {
+        let month = month_number(&m[1]).to_string();
+        add(&m, date(&m[2], &month, None), &mut spans);
+    }
+    for m in p.year_month.captures_iter(text) {
+        add(&m, date(&m[1], &m[2], None), &mut spans);
+    }
+    for m in p.grouped_number.captures_iter(text) {
+        add(&m, vec![decimal(&m[0].replace(',', ""))], &mut spans);
+    }
+    for m in p.decimal.captures_iter(text) {
+        let whole = m.get(0).expect("match");
+        // Skip version numbers like 1.2.3
+        let dotted = |c: Option<char>| c == Some('.');
+        if dotted(text[..whole.start()].chars().last())
+            || dotted(text[whole.end()..].chars().next())
+        {
+            continue;
+        }
+        add(&m, vec![decimal(whole.as_str())], &mut spans);
+    }
+
+    tokens.sort_by_key(|w| w.offset_from);
+    tokens
+}
+
+/// `["2024m03", "2024m03d05"]` for a valid date, empty otherwise
+fn date(year: &str, month: &str, day: Option<&str>) -> Vec<String> {
+    let (Ok(y), Ok(m)) = (year.parse::<u32>(), month.parse::<u32>()) else {
+        return vec![];
+    };
+    if !(1900..=2100).contains(&y) || !(1..=12).contains(&m) {
+        return vec![];
+    }
+    let year_month = format!("{y}m{m:02}");
+    match day.map(str::parse::<u32>) {
+        None => vec![year_month],
+        Some(Ok(d)) if (1..=31).contains(&d) => {
+            let full = format!("{year_month}d{d:02}");
+            vec![year_month, full]
+        }
+        Some(_) => vec![],
+    }
+}
+
+fn month_number(name: &str) -> u32 {
+    let name = name.to_lowercase();
+    [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ]
+    .iter()
+    .position(|m| name.starts_with(m))
+    .map_or(0, |i| i as u32 + 1)
+}
+
+/// Decimal without trailing zeros: "3.50" → "3.5", "2.0" → "2"
+fn decimal(number: &str) -> String {
+    if !number.contains('.') {
+        return number.to_string();
+    }
+    number
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(text: &str) -> Vec<String> {
+        canonical_tokens(text).into_iter().map(|w| w.text).collect()
+    }
+
+    #[test]
+    fn test_dates_share_tokens() {
+        let full = vec!["2024m03", "2024m03d05"];
+        assert_eq!(texts("retro on 2024-03-05"), full);
+        assert_eq!(texts("Retro, Mar 5th, 2024"), full);
+        assert_eq!(texts("5 March 2024"), full);
+        assert_eq!(texts("2024年3月5日 复盘"), full);
+        assert_eq!(texts("march 2024 retro"), vec!["2024m03"]);
+        assert_eq!(texts("planned for 2024-03"), vec!["2024m03"]);
+        assert!(texts("2024-13-05 or 1234").is_empty());
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(texts("cost 1,000 dollars"), vec!["1000"]);
+        assert_eq!(texts("ratio 3.50"), vec!["3.5"]);
+        assert_eq!(texts("12,345.60"), vec!["12345.6"]);
+        assert!(texts("version 1.2.3").is_empty());
+    }
+}
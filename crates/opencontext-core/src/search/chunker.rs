@@ -4,9 +4,14 @@ use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
 
 use super::types::TextChunk;
 
+/// Version of the Markdown chunking rules, recorded in chunk provenance.
+/// Bump it when a change to the rules moves chunk boundaries.
+pub(super) const CHUNKER_VERSION: u32 = 1;
+
 /// Markdown chunker that splits documents into semantic chunks
 /// All size calculations are based on **character count**, not byte count,
 /// ensuring proper handling of Unicode (CJK, emoji, etc.)
+#[derive(Debug, Clone)]
 pub struct Chunker {
     /// Maximum chunk size in characters (not bytes)
     max_chunk_chars: usize,
@@ -34,6 +39,9 @@ impl Chunker {
 
     /// Chunk a markdown document into semantic pieces
     pub fn chunk(&self, content: &str, _file_path: &str) -> Vec<TextChunk> {
+        // Frontmatter is metadata, not content
+        let content = crate::frontmatter::body(content);
+
         let mut chunks = Vec::new();
         let mut current_heading_path: Vec<(HeadingLevel, String)> = Vec::new();
         let mut current_text = String::new();
@@ -285,6 +293,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_frontmatter_not_chunked() {
+        let chunker = Chunker::default();
+        let content = "---\nstatus: done\n---\n# Title\n\nBody text";
+        let chunks = chunker.chunk(content, "test.md");
+
+        assert!(!chunks.is_empty());
+        assert!(chunks.iter().all(|c| !c.content.contains("status")));
+    }
+
     #[test]
     fn test_heading_path() {
         let chunker = Chunker::default();
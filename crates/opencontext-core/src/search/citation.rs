@@ -0,0 +1,117 @@
+//! Citations for search results
+//!
+//! Agents writing documents from retrieved context need to say where a
+//! statement came from. [`format_citation`] renders a hit as a markdown
+//! link to the matched lines, `[Title](notes/plan.md#L12-L30)`, or as the
+//! definition of a numbered footnote, `[^1]: [Title](notes/plan.md#L12-L30)`,
+//! so every caller cites the same way. The `#Lstart-Lend` fragment is the
+//! one GitHub and most editors' preview links understand.
+
+use super::types::{CitationStyle, SearchHit};
+
+/// `hit` as a citation; `number` is the footnote number (1-based rank)
+pub fn format_citation(hit: &SearchHit, style: CitationStyle, number: usize) -> String {
+    let link = link(hit);
+    match style {
+        CitationStyle::Link => link,
+        CitationStyle::Footnote => format!("[^{number}]: {link}"),
+    }
+}
+
+/// Fill `citation` on every hit, numbering footnotes by rank. Grouped
+/// chunks are cited as plain links under their document's footnote.
+pub(super) fn attach(hits: &mut [SearchHit], style: CitationStyle) {
+    for (rank, hit) in hits.iter_mut().enumerate() {
+        hit.citation = Some(format_citation(hit, style, rank + 1));
+        for child in &mut hit.children {
+            child.citation = Some(format_citation(child, CitationStyle::Link, rank + 1));
+        }
+    }
+}
+
+fn link(hit: &SearchHit) -> String {
+    let is_folder = hit.aggregate_type.as_deref() == Some("folder");
+    let path = match hit.folder_path.as_deref() {
+        Some(folder) if is_folder => folder,
+        _ => hit.file_path.as_str(),
+    };
+    let title = if hit.display_name.is_empty() {
+        path.rsplit('/')
+            .next()
+            .unwrap_or(path)
+            .trim_end_matches(".md")
+    } else {
+        hit.display_name.as_str()
+    };
+    let fragment = match (hit.line_start, hit.line_end) {
+        _ if is_folder => String::new(),
+        (Some(start), Some(end)) if end > start => format!("#L{start}-L{end}"),
+        (Some(start), _) => format!("#L{start}"),
+        _ => String::new(),
+    };
+    let target = format!("{path}{fragment}");
+    let target = if target.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        format!("<{target}>")
+    } else {
+        target
+    };
+    format!("[{}]({target})", escape(title))
+}
+
+/// Backslash-escape what would end the link text early
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::MatchType;
+
+    fn hit(path: &str, name: &str, lines: Option<(usize, usize)>) -> SearchHit {
+        SearchHit {
+            display_name: name.to_string(),
+            line_start: lines.map(|l| l.0),
+            line_end: lines.map(|l| l.1),
+            ..SearchHit::fixture(path, MatchType::Keyword)
+        }
+    }
+
+    #[test]
+    fn test_format_citation() {
+        let plan = hit("notes/plan.md", "Q3 [draft] plan", Some((12, 30)));
+        assert_eq!(
+            format_citation(&plan, CitationStyle::Link, 1),
+            r"[Q3 \[draft\] plan](notes/plan.md#L12-L30)"
+        );
+        assert_eq!(
+            format_citation(&plan, CitationStyle::Footnote, 2),
+            r"[^2]: [Q3 \[draft\] plan](notes/plan.md#L12-L30)"
+        );
+        let whole = hit("my notes/idea.md", "", None);
+        assert_eq!(
+            format_citation(&whole, CitationStyle::Link, 1),
+            "[idea](<my notes/idea.md>)"
+        );
+        let line = hit("a.md", "A", Some((4, 4)));
+        assert_eq!(
+            format_citation(&line, CitationStyle::Link, 1),
+            "[A](a.md#L4)"
+        );
+
+        let mut folder = hit("notes/plan.md", "notes", Some((1, 9)));
+        folder.aggregate_type = Some("folder".into());
+        folder.folder_path = Some("notes".into());
+        assert_eq!(
+            format_citation(&folder, CitationStyle::Link, 1),
+            "[notes](notes)"
+        );
+    }
+}
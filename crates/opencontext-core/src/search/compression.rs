@@ -0,0 +1,257 @@
+//! Compressed chunk text
+//!
+//! In a large vault the chunk text, not the vectors, takes most of the
+//! vector table. The store writes it zstd-compressed into `content_zstd`
+//! (leaving `content` empty), with a dictionary trained on the chunks the
+//! table was created from. The dictionary is what makes short chunks
+//! compress well: they share headings, frontmatter keys and phrasing that
+//! it holds once instead of every frame repeating them. Rows are
+//! decompressed when they are turned into hits, never while the table is
+//! searched.
+//!
+//! The dictionary is kept in `content.dict` next to the tables until they
+//! are dropped, as rows compressed with it can't be read without it. Every
+//! frame records the id of its dictionary, so a reader notices one
+//! retrained by another process and reloads it. Rows written before
+//! compression keep their text in `content`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use zstd::bulk::{Compressor, Decompressor};
+use zstd::zstd_safe;
+
+use super::error::{SearchError, SearchResult};
+
+const DICTIONARY_FILE: &str = "content.dict";
+const LEVEL: i32 = 3;
+const DICTIONARY_BYTES: usize = 64 * 1024;
+/// A dictionary trained on fewer chunks fits them and little else
+const MIN_SAMPLES: usize = 100;
+/// Largest chunk text a frame may decode to; also the output buffer for a
+/// frame that doesn't record its size
+const MAX_CHUNK_BYTES: usize = 1 << 20;
+
+#[derive(Clone)]
+struct Dictionary {
+    id: u32,
+    bytes: Arc<Vec<u8>>,
+}
+
+impl Dictionary {
+    fn load(path: &Path) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        Some(Dictionary {
+            id: zstd_safe::get_dict_id_from_dict(&bytes)?.get(),
+            bytes: Arc::new(bytes),
+        })
+    }
+}
+
+/// Compresses the chunk text of one vector database
+pub(super) struct ContentCodec {
+    path: PathBuf,
+    dictionary: Mutex<Option<Dictionary>>,
+}
+
+impl ContentCodec {
+    /// Codec of the database at `db_path`, with its dictionary if it has one
+    pub fn new(db_path: &Path) -> Self {
+        let path = db_path.join(DICTIONARY_FILE);
+        let dictionary = Dictionary::load(&path);
+        Self {
+            path,
+            dictionary: Mutex::new(dictionary),
+        }
+    }
+
+    /// Train the dictionary for a new table on its first chunks. With too
+    /// few of them the table is compressed without one.
+    pub fn train(&self, samples: &[&str]) -> SearchResult<()> {
+        let trained = if samples.len() >= MIN_SAMPLES {
+            zstd::dict::from_samples(samples, DICTIONARY_BYTES)
+                .inspect_err(|e| log::warn!("[VectorStore] Could not train a dictionary: {e}"))
+                .ok()
+        } else {
+            None
+        };
+        let dictionary = trained.and_then(|bytes| {
+            Some(Dictionary {
+                id: zstd_safe::get_dict_id_from_dict(&bytes)?.get(),
+                bytes: Arc::new(bytes),
+            })
+        });
+        let Some(dictionary) = dictionary else {
+            return self.clear();
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, dictionary.bytes.as_slice())?;
+        *self.dictionary.lock() = Some(dictionary);
+        Ok(())
+    }
+
+    /// Forget the dictionary, once no table needs it
+    pub fn clear(&self) -> SearchResult<()> {
+        *self.dictionary.lock() = None;
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// One frame per text. Texts over [`MAX_CHUNK_BYTES`] are refused, since
+    /// the decoder would refuse them on every read.
+    pub fn compress_all(&self, texts: &[&str]) -> SearchResult<Vec<Vec<u8>>> {
+        if let Some(text) = texts.iter().find(|t| t.len() > MAX_CHUNK_BYTES) {
+            let preview: String = text.chars().take(40).collect();
+            return Err(SearchError::Index(format!(
+                "chunk text of {} bytes (\"{preview}...\") is over the {MAX_CHUNK_BYTES} byte limit; \
+                 split it into smaller notes or entries",
+                text.len()
+            )));
+        }
+        let dictionary = self.dictionary.lock().clone();
+        let mut compressor = match &dictionary {
+            Some(dictionary) => Compressor::with_dictionary(LEVEL, &dictionary.bytes)?,
+            None => Compressor::new(LEVEL)?,
+        };
+        texts
+            .iter()
+            .map(|text| Ok(compressor.compress(text.as_bytes())?))
+            .collect()
+    }
+
+    /// Decoder for the rows of one query result
+    pub fn decoder(&self) -> ContentDecoder<'_> {
+        ContentDecoder {
+            codec: self,
+            plain: None,
+            with_dictionary: None,
+        }
+    }
+
+    /// Dictionary `id`, reloaded from disk if it was retrained
+    fn dictionary(&self, id: u32) -> SearchResult<Arc<Vec<u8>>> {
+        let mut current = self.dictionary.lock();
+        if current.as_ref().is_none_or(|d| d.id != id) {
+            *current = Dictionary::load(&self.path);
+        }
+        match current.as_ref() {
+            Some(dictionary) if dictionary.id == id => Ok(dictionary.bytes.clone()),
+            _ => Err(SearchError::VectorStore(format!(
+                "chunk text was compressed with dictionary {id}, which is missing from {}. \
+                 Run `oc index build --force` to rebuild the index.",
+                self.path.display()
+            ))),
+        }
+    }
+}
+
+/// Decompresses chunk texts, setting up each dictionary once
+pub(super) struct ContentDecoder<'a> {
+    codec: &'a ContentCodec,
+    plain: Option<Decompressor<'static>>,
+    with_dictionary: Option<(u32, Decompressor<'static>)>,
+}
+
+impl ContentDecoder<'_> {
+    pub fn decode(&mut self, frame: &[u8]) -> SearchResult<String> {
+        // The header's size comes from disk; don't allocate whatever it claims
+        let capacity = match zstd_safe::get_frame_content_size(frame).ok().flatten() {
+            Some(size) if size > MAX_CHUNK_BYTES as u64 => {
+                return Err(SearchError::VectorStore(format!(
+                    "corrupt chunk text: frame claims {size} bytes"
+                )))
+            }
+            Some(size) => size as usize,
+            None => MAX_CHUNK_BYTES,
+        };
+        let decompressor = match zstd_safe::get_dict_id_from_frame(frame) {
+            None => {
+                if self.plain.is_none() {
+                    self.plain = Some(Decompressor::new()?);
+                }
+                self.plain
+                    .as_mut()
+                    .expect("plain decompressor was just set")
+            }
+            Some(id) => {
+                let id = id.get();
+                if self.with_dictionary.as_ref().map(|(loaded, _)| *loaded) != Some(id) {
+                    let bytes = self.codec.dictionary(id)?;
+                    self.with_dictionary = Some((id, Decompressor::with_dictionary(&bytes)?));
+                }
+                &mut self
+                    .with_dictionary
+                    .as_mut()
+                    .expect("dictionary decompressor was just set")
+                    .1
+            }
+        };
+        let bytes = decompressor.decompress(frame, capacity)?;
+        String::from_utf8(bytes)
+            .map_err(|e| SearchError::VectorStore(format!("corrupt chunk text: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes() -> Vec<String> {
+        (0..200)
+            .map(|i| {
+                format!(
+                    "Meeting notes > Week {i}\n\nAttendees: Alice, Bob. Action items: \
+                     review the deployment checklist, update runbook section {}.",
+                    i % 7
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_with_and_without_dictionary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let codec = ContentCodec::new(dir.path());
+
+        codec.train(&["too few"]).unwrap();
+        let plain = codec.compress_all(&["Short note"]).unwrap();
+        assert_eq!(zstd_safe::get_dict_id_from_frame(&plain[0]), None);
+
+        let notes = notes();
+        let samples: Vec<&str> = notes.iter().map(String::as_str).collect();
+        codec.train(&samples).unwrap();
+        let frames = codec.compress_all(&samples[..2]).unwrap();
+        assert!(zstd_safe::get_dict_id_from_frame(&frames[0]).is_some());
+        let total: usize = frames.iter().map(Vec::len).sum();
+        assert!(total < samples[0].len() + samples[1].len());
+
+        // Another process reads them with the dictionary saved next to the tables
+        let reader = ContentCodec::new(dir.path());
+        let mut decoder = reader.decoder();
+        assert_eq!(decoder.decode(&frames[1]).unwrap(), samples[1]);
+        assert_eq!(decoder.decode(&plain[0]).unwrap(), "Short note");
+
+        codec.clear().unwrap();
+        let reader = ContentCodec::new(dir.path());
+        let mut decoder = reader.decoder();
+        assert!(decoder.decode(&frames[0]).is_err());
+        assert_eq!(decoder.decode(&plain[0]).unwrap(), "Short note");
+    }
+
+    #[test]
+    fn test_rejects_oversized_chunks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let codec = ContentCodec::new(dir.path());
+        let huge = "a".repeat(MAX_CHUNK_BYTES + 1);
+        assert!(codec.compress_all(&["Short note", huge.as_str()]).is_err());
+
+        // Frames written before the limit was enforced fail to decode
+        let frame = zstd::bulk::compress(huge.as_bytes(), LEVEL).unwrap();
+        assert!(codec.decoder().decode(&frame).is_err());
+    }
+}
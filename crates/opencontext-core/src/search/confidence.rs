@@ -0,0 +1,149 @@
+//! Answerability of a query from the results it got
+//!
+//! A search always returns its best matches, even when the workspace has
+//! nothing on the subject, and fused scores are normalized so the top hit
+//! scores 1 either way. [`assess`] estimates whether the results actually
+//! cover the query, so an agent can answer "my notes don't cover this"
+//! instead of building on weak hits. It looks at three things in the top
+//! results:
+//!
+//! - term coverage: the share of the query's content words they contain,
+//!   which dominates the estimate;
+//! - agreement: the share matched by more than one retrieval signal
+//!   (vector and keyword), counted only when the query ran in hybrid mode;
+//! - separation: how far the best score stands above the others', as a
+//!   flat score distribution means no result is a clear answer.
+
+use std::collections::HashSet;
+
+use super::related;
+use super::types::{Confidence, ConfidenceLevel, MatchType, SearchHit};
+
+/// Results looked at, best first
+const TOP: usize = 3;
+
+const COVERAGE_WEIGHT: f32 = 0.6;
+const AGREEMENT_WEIGHT: f32 = 0.25;
+const SEPARATION_WEIGHT: f32 = 0.15;
+
+/// Confidence that `hits` (best first) answer `query`. `hybrid` tells
+/// whether several retrieval signals ran.
+pub(super) fn assess(query: &str, hits: &[SearchHit], hybrid: bool) -> Confidence {
+    let top: Vec<&SearchHit> = hits
+        .iter()
+        .filter(|hit| hit.aggregate_type.as_deref() != Some("folder"))
+        .take(TOP)
+        .collect();
+    if top.is_empty() {
+        return Confidence {
+            score: 0.0,
+            level: ConfidenceLevel::Low,
+            term_coverage: 0.0,
+            missing_terms: related::words(query).collect(),
+        };
+    }
+
+    let text = top
+        .iter()
+        .flat_map(|hit| std::iter::once(*hit).chain(&hit.children))
+        .map(|hit| {
+            format!(
+                "{} {} {}",
+                hit.file_path,
+                hit.heading_path.as_deref().unwrap_or(""),
+                hit.content
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase();
+    let mut seen = HashSet::new();
+    let terms: Vec<String> = related::words(query)
+        .filter(|t| seen.insert(t.clone()))
+        .collect();
+    let missing_terms: Vec<String> = terms
+        .iter()
+        .filter(|t| !text.contains(t.as_str()))
+        .cloned()
+        .collect();
+    // A query of stopwords only says nothing either way
+    let term_coverage = if terms.is_empty() {
+        0.5
+    } else {
+        1.0 - missing_terms.len() as f32 / terms.len() as f32
+    };
+
+    let agreement = if hybrid {
+        let agreeing = top
+            .iter()
+            .filter(|hit| hit.matched_by == MatchType::Hybrid)
+            .count();
+        agreeing as f32 / top.len() as f32
+    } else {
+        0.5
+    };
+
+    let best = top[0].score.max(f32::EPSILON);
+    let rest = &hits[1.min(hits.len())..hits.len().min(TOP * 2)];
+    let separation = if rest.is_empty() {
+        1.0
+    } else {
+        let mean = rest.iter().map(|hit| hit.score).sum::<f32>() / rest.len() as f32;
+        (1.0 - mean / best).clamp(0.0, 1.0)
+    };
+
+    let score = (COVERAGE_WEIGHT * term_coverage
+        + AGREEMENT_WEIGHT * agreement
+        + SEPARATION_WEIGHT * separation)
+        .clamp(0.0, 1.0);
+    Confidence {
+        score,
+        level: ConfidenceLevel::from_score(score),
+        term_coverage,
+        missing_terms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(content: &str, score: f32, matched_by: MatchType) -> SearchHit {
+        SearchHit {
+            content: content.to_string(),
+            score,
+            ..SearchHit::fixture("notes/a.md", matched_by)
+        }
+    }
+
+    #[test]
+    fn test_assess_confidence() {
+        let answered = [
+            hit(
+                "Rotate the signing keys often, every 90 days",
+                1.0,
+                MatchType::Hybrid,
+            ),
+            hit("Key rotation runbook", 0.4, MatchType::Hybrid),
+        ];
+        let confidence = assess("how often do we rotate signing keys", &answered, true);
+        assert_eq!(confidence.level, ConfidenceLevel::High);
+        assert_eq!(confidence.term_coverage, 1.0);
+        assert!(confidence.missing_terms.is_empty());
+
+        let weak = [
+            hit("Lunch menu for the offsite", 1.0, MatchType::Vector),
+            hit("Offsite agenda", 0.97, MatchType::Vector),
+            hit("Travel budget", 0.95, MatchType::Vector),
+        ];
+        let confidence = assess("kubernetes signing keys rotation", &weak, true);
+        assert_eq!(confidence.level, ConfidenceLevel::Low);
+        assert_eq!(
+            confidence.missing_terms,
+            ["kubernetes", "signing", "keys", "rotation"]
+        );
+
+        let none = assess("signing keys", &[], true);
+        assert_eq!((none.score, none.level), (0.0, ConfidenceLevel::Low));
+    }
+}
@@ -1,9 +1,11 @@
 //! Search configuration
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use super::error::{SearchError, SearchResult};
+use super::types::{EmbeddingTier, SearchMode};
 
 /// Main search configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -19,6 +21,66 @@ pub struct SearchConfig {
     /// Paths configuration
     #[serde(default)]
     pub paths: PathsConfig,
+
+    /// Experimental multi-vector (late interaction) retrieval
+    #[serde(default)]
+    pub multi_vector: MultiVectorConfig,
+
+    /// Approximate nearest neighbor search over the chunk vectors
+    #[serde(default)]
+    pub vector_index: VectorIndexConfig,
+
+    /// Sparse embedding (SPLADE) model used as a third hybrid signal
+    #[serde(default)]
+    pub sparse: SparseConfig,
+
+    /// Chat completion model used by optional LLM-assisted features
+    #[serde(default)]
+    pub llm: LlmConfig,
+
+    /// Query routing for `mode = "auto"`
+    #[serde(default)]
+    pub router: RouterConfig,
+
+    /// LLM-generated chunk context prepended before embedding
+    #[serde(default)]
+    pub contextual: ContextualConfig,
+
+    /// Query translation for workspaces whose notes mix languages
+    #[serde(default)]
+    pub cross_language: CrossLanguageConfig,
+
+    /// Keyword index text analysis
+    #[serde(default)]
+    pub tokenizer: TokenizerConfig,
+
+    /// Index build parallelism and buffering
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+
+    /// Other workspaces searched alongside this one
+    #[serde(default)]
+    pub federation: FederationConfig,
+
+    /// Desktop app clipboard watcher
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+
+    /// Shell history and terminal recording import
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+
+    /// Outside systems mirrored into the workspace on a schedule
+    #[serde(default)]
+    pub sources: SourcesConfig,
+
+    /// Ranking priors by folder and source type
+    #[serde(default)]
+    pub trust: TrustConfig,
+
+    /// Memory budget for the caches of a long-running searcher
+    #[serde(default)]
+    pub memory: MemoryConfig,
 }
 
 /// Embedding API configuration
@@ -43,6 +105,20 @@ pub struct EmbeddingConfig {
     /// Batch size for embedding requests
     #[serde(default = "default_batch_size")]
     pub batch_size: usize,
+
+    /// Prefix each chunk with its document title and heading path before embedding.
+    /// Changing this triggers a full rebuild on the next `oc index build`.
+    #[serde(default)]
+    pub include_heading_context: bool,
+
+    /// USD per million input tokens, for dry-run cost estimates. Known
+    /// OpenAI models are priced automatically.
+    #[serde(default)]
+    pub price_per_million_tokens: Option<f64>,
+
+    /// A second, cheaper model embedded alongside this one
+    #[serde(default)]
+    pub tiers: TiersConfig,
 }
 
 impl Default for EmbeddingConfig {
@@ -53,6 +129,9 @@ impl Default for EmbeddingConfig {
             model: default_model(),
             dimensions: default_dimensions(),
             batch_size: default_batch_size(),
+            include_heading_context: false,
+            price_per_million_tokens: None,
+            tiers: TiersConfig::default(),
         }
     }
 }
@@ -70,6 +149,98 @@ impl EmbeddingConfig {
             .or_else(|_| std::env::var("OPENAI_KEY"))
             .map_err(|_| SearchError::ApiKeyMissing)
     }
+
+    /// USD per million input tokens: the configured price, or the list price
+    /// of a known OpenAI model
+    pub fn price_per_million_tokens(&self) -> Option<f64> {
+        self.price_per_million_tokens.or(match self.model.as_str() {
+            "text-embedding-3-small" => Some(0.02),
+            "text-embedding-3-large" => Some(0.13),
+            "text-embedding-ada-002" => Some(0.10),
+            _ => None,
+        })
+    }
+}
+
+/// Fast / accurate embedding tiers
+///
+/// When enabled every chunk is embedded twice: with the model configured in
+/// `[embedding]` (the accurate tier) and with the small model set here (the
+/// fast tier), kept in a LanceDB database of its own. Fast-tier queries only
+/// touch the small model; accurate-tier queries recall `recall` candidates
+/// from the fast index and re-score them against the accurate vectors.
+/// Changing these settings triggers a full rebuild on the next `oc index build`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiersConfig {
+    /// Store and search the second embedding
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Model of the fast tier
+    #[serde(default = "default_model")]
+    pub model: String,
+
+    /// Embedding dimensions of the fast tier
+    #[serde(default = "default_fast_dimensions")]
+    pub dimensions: usize,
+
+    /// API base URL of the fast tier, if it differs from `[embedding]`
+    #[serde(default)]
+    pub api_base: Option<String>,
+
+    /// API key of the fast tier, if it differs from `[embedding]`
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Candidates recalled from the fast index for accurate re-scoring
+    #[serde(default = "default_tier_recall")]
+    pub recall: usize,
+
+    /// Tier of queries that do not ask for one
+    #[serde(default)]
+    pub default: EmbeddingTier,
+}
+
+impl Default for TiersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: default_model(),
+            dimensions: default_fast_dimensions(),
+            api_base: None,
+            api_key: None,
+            recall: default_tier_recall(),
+            default: EmbeddingTier::default(),
+        }
+    }
+}
+
+fn default_fast_dimensions() -> usize {
+    256
+}
+
+fn default_tier_recall() -> usize {
+    100
+}
+
+impl EmbeddingConfig {
+    /// Settings of the fast tier: this configuration with the model,
+    /// dimensions and endpoint overridden by `tiers`
+    pub fn fast_tier(&self) -> EmbeddingConfig {
+        let tiers = &self.tiers;
+        EmbeddingConfig {
+            api_key: tiers.api_key.clone().or_else(|| self.api_key.clone()),
+            api_base: tiers
+                .api_base
+                .clone()
+                .unwrap_or_else(|| self.api_base.clone()),
+            model: tiers.model.clone(),
+            dimensions: tiers.dimensions,
+            price_per_million_tokens: None,
+            tiers: TiersConfig::default(),
+            ..self.clone()
+        }
+    }
 }
 
 fn default_api_base() -> String {
@@ -107,6 +278,59 @@ pub struct SearchBehaviorConfig {
     /// Overlap between chunks in characters
     #[serde(default = "default_chunk_overlap")]
     pub chunk_overlap: usize,
+
+    /// RRF weight of the dense vector signal in hybrid mode
+    #[serde(default = "default_signal_weight")]
+    pub vector_weight: f32,
+
+    /// RRF weight of the BM25 keyword signal in hybrid mode
+    #[serde(default = "default_signal_weight")]
+    pub keyword_weight: f32,
+
+    /// RRF weight of the sparse embedding signal in hybrid mode (only used when `[sparse]` is enabled)
+    #[serde(default = "default_signal_weight")]
+    pub sparse_weight: f32,
+
+    /// RRF weight of the context-document signal in `search_with_context`
+    #[serde(default = "default_signal_weight")]
+    pub context_weight: f32,
+
+    /// Queries slower than this (milliseconds) are written to the slow-query log; 0 disables it
+    #[serde(default = "default_slow_query_ms")]
+    pub slow_query_ms: u64,
+
+    /// Include this session's earlier queries in related-query suggestions
+    #[serde(default = "default_related_from_history")]
+    pub related_from_history: bool,
+
+    /// Score reduction for boilerplate chunks: a chunk's score is multiplied by
+    /// `1 - boilerplate_penalty * boilerplate_score`; 0 disables the penalty
+    #[serde(default = "default_boilerplate_penalty")]
+    pub boilerplate_penalty: f32,
+
+    /// Merge results from different documents holding the same content
+    /// (renamed copies, duplicated files) into one result listing the other
+    /// paths in `also_in`
+    #[serde(default = "default_dedup")]
+    pub dedup: bool,
+
+    /// Cosine similarity of two results' chunk vectors above which they count
+    /// as the same content even if the text differs slightly; above 1 only
+    /// identical text is merged
+    #[serde(default = "default_dedup_similarity")]
+    pub dedup_similarity: f32,
+
+    /// Have the `[llm]` model describe each summarized folder result. Replies
+    /// are cached until a document in the folder changes.
+    #[serde(default)]
+    pub folder_summary_llm: bool,
+
+    /// Modes to try in turn when a search fails because the embedding
+    /// provider, the vector index or another provider does; the requested
+    /// mode and anything listed before it are skipped. Results found this
+    /// way are marked `degraded`. Empty lets the error reach the caller.
+    #[serde(default = "default_fallback")]
+    pub fallback: Vec<SearchMode>,
 }
 
 impl Default for SearchBehaviorConfig {
@@ -115,6 +339,17 @@ impl Default for SearchBehaviorConfig {
             default_limit: default_limit(),
             chunk_size: default_chunk_size(),
             chunk_overlap: default_chunk_overlap(),
+            vector_weight: default_signal_weight(),
+            keyword_weight: default_signal_weight(),
+            sparse_weight: default_signal_weight(),
+            context_weight: default_signal_weight(),
+            slow_query_ms: default_slow_query_ms(),
+            related_from_history: default_related_from_history(),
+            boilerplate_penalty: default_boilerplate_penalty(),
+            dedup: default_dedup(),
+            dedup_similarity: default_dedup_similarity(),
+            folder_summary_llm: false,
+            fallback: default_fallback(),
         }
     }
 }
@@ -131,6 +366,772 @@ fn default_chunk_overlap() -> usize {
     200
 }
 
+fn default_signal_weight() -> f32 {
+    1.0
+}
+
+fn default_slow_query_ms() -> u64 {
+    1000
+}
+
+fn default_related_from_history() -> bool {
+    true
+}
+
+fn default_boilerplate_penalty() -> f32 {
+    0.5
+}
+
+fn default_dedup() -> bool {
+    true
+}
+
+fn default_dedup_similarity() -> f32 {
+    0.98
+}
+
+fn default_fallback() -> Vec<SearchMode> {
+    vec![SearchMode::Hybrid, SearchMode::Vector, SearchMode::Keyword]
+}
+
+/// Multi-vector (ColBERT-style late interaction) configuration
+///
+/// When enabled, every chunk additionally stores one vector per sliding text
+/// window, and vector candidates are re-scored with MaxSim. This multiplies
+/// embedding cost and index size by roughly `chunk_size / stride_chars`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiVectorConfig {
+    /// Enable window vectors at index time and MaxSim re-scoring at query time
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Window size in characters
+    #[serde(default = "default_window_chars")]
+    pub window_chars: usize,
+
+    /// Step between window starts in characters
+    #[serde(default = "default_stride_chars")]
+    pub stride_chars: usize,
+}
+
+impl Default for MultiVectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_chars: default_window_chars(),
+            stride_chars: default_stride_chars(),
+        }
+    }
+}
+
+fn default_window_chars() -> usize {
+    200
+}
+
+fn default_stride_chars() -> usize {
+    100
+}
+
+/// Vector index configuration
+///
+/// With `search_dimensions` set, ANN search runs on that many leading
+/// dimensions of each vector (kept in a LanceDB database of their own) and
+/// the best `rescore` candidates are re-scored on the full vectors. Only
+/// useful with Matryoshka-trained models such as `text-embedding-3-*`.
+/// Changing `search_dimensions` triggers a full rebuild on the next
+/// `oc index build`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorIndexConfig {
+    /// Leading dimensions searched; unset searches full vectors
+    #[serde(default)]
+    pub search_dimensions: Option<usize>,
+
+    /// Candidates re-scored on the full vectors
+    #[serde(default = "default_rescore")]
+    pub rescore: usize,
+}
+
+impl Default for VectorIndexConfig {
+    fn default() -> Self {
+        Self {
+            search_dimensions: None,
+            rescore: default_rescore(),
+        }
+    }
+}
+
+impl VectorIndexConfig {
+    /// Prefix length to search for vectors of `dimensions`, if shorter
+    pub fn prefix_dimensions(&self, dimensions: usize) -> Option<usize> {
+        self.search_dimensions.filter(|&d| d > 0 && d < dimensions)
+    }
+}
+
+fn default_rescore() -> usize {
+    100
+}
+
+/// Index build pipeline configuration
+///
+/// Builds run scan → chunk → embed → write. Documents are read and chunked
+/// on `chunk_workers` threads, up to `embed_concurrency` embedding requests
+/// are in flight at once, and at most `queue_depth` chunked documents wait
+/// between the stages, so memory depends on these and `embedding.batch_size`
+/// rather than on the size of the workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    /// Threads reading and chunking documents
+    #[serde(default = "default_chunk_workers")]
+    pub chunk_workers: usize,
+
+    /// Embedding batches requested concurrently
+    #[serde(default = "default_embed_concurrency")]
+    pub embed_concurrency: usize,
+
+    /// Chunked documents buffered ahead of the embedding stage
+    #[serde(default = "default_queue_depth")]
+    pub queue_depth: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            chunk_workers: default_chunk_workers(),
+            embed_concurrency: default_embed_concurrency(),
+            queue_depth: default_queue_depth(),
+        }
+    }
+}
+
+fn default_chunk_workers() -> usize {
+    4
+}
+
+fn default_embed_concurrency() -> usize {
+    4
+}
+
+fn default_queue_depth() -> usize {
+    64
+}
+
+/// Federated search over several workspaces
+///
+/// ```toml
+/// [[federation.workspaces]]
+/// name = "work"
+/// root = "/home/me/work/.opencontext"
+/// weight = 0.8
+/// ```
+///
+/// Each workspace is an OpenContext root with its own index. They are
+/// queried with this configuration's embedding settings, so every index must
+/// be built with the same embedding model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationConfig {
+    /// Label of this workspace's own results
+    #[serde(default = "default_local_name")]
+    pub local_name: String,
+
+    /// Rank weight of this workspace's own results; 0 leaves it out
+    #[serde(default = "default_workspace_weight")]
+    pub local_weight: f32,
+
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceConfig>,
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            local_name: default_local_name(),
+            local_weight: default_workspace_weight(),
+            workspaces: Vec::new(),
+        }
+    }
+}
+
+/// One workspace of a federated search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Label attached to this workspace's results
+    pub name: String,
+
+    /// OpenContext root holding the workspace's index
+    pub root: PathBuf,
+
+    /// Rank weight relative to the other workspaces
+    #[serde(default = "default_workspace_weight")]
+    pub weight: f32,
+
+    /// Collection to search (defaults to the default collection)
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+fn default_local_name() -> String {
+    "local".to_string()
+}
+
+fn default_workspace_weight() -> f32 {
+    1.0
+}
+
+/// Clipboard watcher of the desktop app
+///
+/// When enabled, text copied anywhere is captured into the day's inbox note
+/// (see [`crate::capture`]) if it passes these rules. Each text is captured
+/// once per session.
+///
+/// ```toml
+/// [clipboard]
+/// enabled = true
+/// min_length = 80
+/// apps = ["Safari", "Firefox", "Preview"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Shorter copies (passwords, single words, snippets) are ignored
+    #[serde(default = "default_clipboard_min_length")]
+    pub min_length: usize,
+
+    /// Longer copies (whole files, logs) are ignored
+    #[serde(default = "default_clipboard_max_length")]
+    pub max_length: usize,
+
+    /// Only capture text copied in these applications (case-insensitive
+    /// names). Empty allows every app. The frontmost app is only known on
+    /// macOS and X11; elsewhere a non-empty list captures nothing.
+    #[serde(default)]
+    pub apps: Vec<String>,
+
+    /// How often the clipboard is checked, in milliseconds
+    #[serde(default = "default_clipboard_poll_ms")]
+    pub poll_ms: u64,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_length: default_clipboard_min_length(),
+            max_length: default_clipboard_max_length(),
+            apps: Vec::new(),
+            poll_ms: default_clipboard_poll_ms(),
+        }
+    }
+}
+
+fn default_clipboard_min_length() -> usize {
+    40
+}
+
+fn default_clipboard_max_length() -> usize {
+    20_000
+}
+
+fn default_clipboard_poll_ms() -> u64 {
+    1000
+}
+
+/// How `oc sessions import` turns shell history into session documents
+///
+/// Secrets (passwords, tokens, keys, credentials in URLs) are always
+/// redacted; `redact` adds patterns of your own.
+///
+/// ```toml
+/// [sessions]
+/// gap_minutes = 20
+/// redact = ["corp-[0-9]{6}"]
+/// ignore_commands = ["ls", "cd", "clear"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    /// Minutes without a command that end a session
+    #[serde(default = "default_session_gap_minutes")]
+    pub gap_minutes: u64,
+
+    /// Extra regexes whose matches are replaced with `[REDACTED]`
+    #[serde(default)]
+    pub redact: Vec<String>,
+
+    /// Commands left out when their first word is one of these
+    #[serde(default = "default_ignore_commands")]
+    pub ignore_commands: Vec<String>,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            gap_minutes: default_session_gap_minutes(),
+            redact: Vec::new(),
+            ignore_commands: default_ignore_commands(),
+        }
+    }
+}
+
+fn default_session_gap_minutes() -> u64 {
+    30
+}
+
+fn default_ignore_commands() -> Vec<String> {
+    ["ls", "ll", "la", "clear", "exit", "history", "pwd"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Sources synced into the workspace (see [`crate::sources`])
+///
+/// Each source is pulled every `interval_minutes` by the scheduler of the
+/// long-running process (MCP server), or on demand with `oc sources sync`.
+///
+/// ```toml
+/// [sources]
+/// interval_minutes = 30
+///
+/// [[sources.github]]
+/// repos = ["wormuz/OpenContext"]
+/// folder = "github"
+/// discussions = true
+///
+/// [[sources.jira]]
+/// url = "https://acme.atlassian.net"
+/// email = "me@acme.com"
+///
+/// [[sources.linear]]
+/// folder = "tickets/linear"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourcesConfig {
+    /// Minutes between two syncs of the same source
+    #[serde(default = "default_sources_interval_minutes")]
+    pub interval_minutes: u64,
+
+    #[serde(default)]
+    pub github: Vec<GithubSourceConfig>,
+
+    #[serde(default)]
+    pub jira: Vec<JiraSourceConfig>,
+
+    #[serde(default)]
+    pub linear: Vec<LinearSourceConfig>,
+}
+
+impl Default for SourcesConfig {
+    fn default() -> Self {
+        Self {
+            interval_minutes: default_sources_interval_minutes(),
+            github: Vec::new(),
+            jira: Vec::new(),
+            linear: Vec::new(),
+        }
+    }
+}
+
+fn default_sources_interval_minutes() -> u64 {
+    60
+}
+
+/// GitHub repositories whose issues and discussions are mirrored, one doc
+/// per issue or discussion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubSourceConfig {
+    /// Repositories as `owner/name`
+    pub repos: Vec<String>,
+
+    /// Workspace folder the docs are written to, under `<owner>/<name>/`
+    #[serde(default = "default_github_folder")]
+    pub folder: String,
+
+    #[serde(default = "default_github_issues")]
+    pub issues: bool,
+
+    /// Needs a token, as discussions are only served by the GraphQL API
+    #[serde(default)]
+    pub discussions: bool,
+
+    /// API token (defaults to `GITHUB_TOKEN`); required for private
+    /// repositories
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl GithubSourceConfig {
+    /// Token from config or environment
+    pub fn get_token(&self) -> Option<String> {
+        self.token
+            .clone()
+            .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+            .filter(|t| !t.is_empty())
+    }
+}
+
+fn default_github_folder() -> String {
+    "github".to_string()
+}
+
+fn default_github_issues() -> bool {
+    true
+}
+
+/// A Jira site whose tickets are mirrored, one doc per ticket
+///
+/// The API token comes from `token`, `JIRA_API_TOKEN` or the system keyring
+/// entry for account `jira` (`oc sources token jira`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraSourceConfig {
+    /// Site URL, e.g. `https://acme.atlassian.net`
+    pub url: String,
+
+    /// Account the API token belongs to
+    pub email: String,
+
+    /// Tickets to mirror (default: those you are assignee, reporter or
+    /// watcher of)
+    #[serde(default = "default_jira_jql")]
+    pub jql: String,
+
+    #[serde(default = "default_jira_folder")]
+    pub folder: String,
+
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl JiraSourceConfig {
+    /// Token from config, environment or keyring
+    pub fn get_token(&self) -> Option<String> {
+        self.token
+            .clone()
+            .or_else(|| std::env::var("JIRA_API_TOKEN").ok())
+            .filter(|t| !t.is_empty())
+            .or_else(|| crate::keyring::get("jira"))
+    }
+}
+
+fn default_jira_jql() -> String {
+    "assignee = currentUser() OR reporter = currentUser() OR watcher = currentUser()".to_string()
+}
+
+fn default_jira_folder() -> String {
+    "tickets/jira".to_string()
+}
+
+/// A Linear workspace whose issues you are involved in (assignee, creator
+/// or subscriber) are mirrored, one doc per issue
+///
+/// The API key comes from `token`, `LINEAR_API_KEY` or the system keyring
+/// entry for account `linear` (`oc sources token linear`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinearSourceConfig {
+    #[serde(default = "default_linear_folder")]
+    pub folder: String,
+
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for LinearSourceConfig {
+    fn default() -> Self {
+        Self {
+            folder: default_linear_folder(),
+            token: None,
+        }
+    }
+}
+
+impl LinearSourceConfig {
+    /// Token from config, environment or keyring
+    pub fn get_token(&self) -> Option<String> {
+        self.token
+            .clone()
+            .or_else(|| std::env::var("LINEAR_API_KEY").ok())
+            .filter(|t| !t.is_empty())
+            .or_else(|| crate::keyring::get("linear"))
+    }
+}
+
+fn default_linear_folder() -> String {
+    "tickets/linear".to_string()
+}
+
+/// Trust levels used as ranking priors
+///
+/// A result's score is multiplied by the level of its folder and the level
+/// of its source type, so a clipped web page ranks below your own notes
+/// when both match equally well. Source types are the `source` and
+/// `origin` values of a result's provenance; when both are listed the
+/// origin's level applies. Folders apply to everything below them, the
+/// deepest listed folder winning. Anything unlisted has level 1.
+///
+/// ```toml
+/// [trust.folders]
+/// "projects" = 1.2
+/// "inbox" = 0.8
+///
+/// [trust.sources]
+/// web_clip = 0.5
+/// ocr = 0.7
+/// jira = 0.9
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustConfig {
+    /// Level by workspace folder
+    #[serde(default)]
+    pub folders: BTreeMap<String, f32>,
+
+    /// Level by source type (`markdown`, `capture`, `evernote`, `jira`, ...)
+    /// or origin (`web_clip`, `ocr`, `transcript`)
+    #[serde(default)]
+    pub sources: BTreeMap<String, f32>,
+}
+
+impl TrustConfig {
+    /// Whether no level is configured, leaving ranking untouched
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.sources.is_empty()
+    }
+}
+
+/// Memory budget for in-memory structures
+///
+/// A searcher kept running by `oc serve` or the MCP server caches query
+/// embeddings, the chunks of recently expanded documents and the spelling
+/// index built from the keyword index. The budget is split between them:
+/// half for the chunk cache, a quarter for the embedding cache and a
+/// quarter for the keyword index, which also caps the keyword writer's
+/// indexing buffer (15–50 MB). Caches evict their least recently used
+/// entries to stay within their share. `oc debug memory` shows what each
+/// one holds.
+///
+/// ```toml
+/// [memory]
+/// budget_mb = 64
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Total budget in MiB
+    #[serde(default = "default_memory_budget_mb")]
+    pub budget_mb: usize,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            budget_mb: default_memory_budget_mb(),
+        }
+    }
+}
+
+impl MemoryConfig {
+    pub fn budget_bytes(&self) -> usize {
+        self.budget_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+fn default_memory_budget_mb() -> usize {
+    256
+}
+
+/// Sparse embedding configuration
+///
+/// Expects a text-embeddings-inference compatible `/embed_sparse` endpoint
+/// (e.g. serving `naver/splade-v3`). Disabled by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SparseConfig {
+    /// Compute and search sparse vectors
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Base URL of the sparse embedding server
+    #[serde(default = "default_sparse_api_base")]
+    pub api_base: String,
+
+    /// Optional bearer token for the sparse embedding server
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Batch size for sparse embedding requests
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+}
+
+impl Default for SparseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_base: default_sparse_api_base(),
+            api_key: None,
+            batch_size: default_batch_size(),
+        }
+    }
+}
+
+fn default_sparse_api_base() -> String {
+    "http://localhost:8080".to_string()
+}
+
+/// Chat completion API configuration (OpenAI compatible)
+///
+/// Unset `api_key` / `api_base` fall back to the embedding settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    /// API key (defaults to the embedding API key)
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// API base URL (defaults to the embedding API base)
+    #[serde(default)]
+    pub api_base: Option<String>,
+
+    /// Model name
+    #[serde(default = "default_llm_model")]
+    pub model: String,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            api_base: None,
+            model: default_llm_model(),
+        }
+    }
+}
+
+fn default_llm_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+/// Query router configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouterConfig {
+    /// Ask the LLM to classify queries the heuristics cannot place
+    #[serde(default)]
+    pub llm: bool,
+
+    /// Expand conceptual queries with a hypothetical answer (HyDE) before embedding
+    #[serde(default)]
+    pub hyde: bool,
+}
+
+/// Cross-language retrieval configuration
+///
+/// Pair with a multilingual embedding model (e.g. `bge-m3`) so vector search
+/// matches across languages; keyword search is covered by translating the
+/// query with the `[llm]` model into each of `languages`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossLanguageConfig {
+    /// Search in every workspace language by default
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// ISO 639-1 codes of the languages the notes are written in
+    #[serde(default = "default_languages")]
+    pub languages: Vec<String>,
+
+    /// Translate the query for the keyword signal (needs `[llm]`)
+    #[serde(default = "default_translate_keyword")]
+    pub translate_keyword: bool,
+}
+
+impl Default for CrossLanguageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            languages: default_languages(),
+            translate_keyword: default_translate_keyword(),
+        }
+    }
+}
+
+fn default_languages() -> Vec<String> {
+    vec!["en".to_string(), "zh".to_string()]
+}
+
+fn default_translate_keyword() -> bool {
+    true
+}
+
+/// Tokenizer configuration for the keyword index
+///
+/// Changing any of these rebuilds the keyword index on the next open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenizerConfig {
+    /// Word splitter: `"default"` or a name passed to `register_tokenizer`
+    #[serde(default = "default_tokenizer_kind")]
+    pub kind: String,
+
+    /// Fold Traditional Chinese to Simplified in the keyword index, queries
+    /// and embedded text, so either form matches the other
+    #[serde(default)]
+    pub chinese_normalization: bool,
+
+    /// Fold accented Latin letters to ASCII in the keyword index and queries
+    /// ("café" matches "cafe")
+    #[serde(default)]
+    pub fold_diacritics: bool,
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            kind: default_tokenizer_kind(),
+            chinese_normalization: false,
+            fold_diacritics: false,
+        }
+    }
+}
+
+fn default_tokenizer_kind() -> String {
+    "default".to_string()
+}
+
+/// Contextual retrieval configuration
+///
+/// Each chunk gets a one or two sentence LLM summary of where it sits in its
+/// document, prepended to the text that is embedded. Generated contexts are
+/// cached by chunk content, so unchanged chunks never cost a second call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextualConfig {
+    /// Generate chunk contexts during indexing
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Upper bound on new LLM calls per index build; remaining chunks are embedded without context
+    #[serde(default = "default_max_contexts_per_build")]
+    pub max_chunks_per_build: usize,
+
+    /// Document text sent along with each chunk is truncated to this many characters
+    #[serde(default = "default_max_doc_chars")]
+    pub max_doc_chars: usize,
+}
+
+impl Default for ContextualConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_chunks_per_build: default_max_contexts_per_build(),
+            max_doc_chars: default_max_doc_chars(),
+        }
+    }
+}
+
+fn default_max_contexts_per_build() -> usize {
+    500
+}
+
+fn default_max_doc_chars() -> usize {
+    8000
+}
+
+/// Collection used when none is configured
+pub const DEFAULT_COLLECTION: &str = "default";
+
 /// Paths configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PathsConfig {
@@ -141,48 +1142,194 @@ pub struct PathsConfig {
     /// Index metadata path
     #[serde(default)]
     pub index_metadata_path: Option<PathBuf>,
+
+    /// Directory holding the index files; defaults to `$OPENCONTEXT_ROOT`,
+    /// then `~/.opencontext`. Explicit `lancedb_path` and
+    /// `index_metadata_path` still take precedence.
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+
+    /// Logical collection (e.g. one per project or client) to index into and
+    /// search. Collections share the LanceDB database; keyword, sparse and
+    /// metadata files are kept per collection.
+    #[serde(default)]
+    pub collection: Option<String>,
+
+    /// Workspace documents, read by filters on frontmatter fields; defaults
+    /// to `contexts` under `root`, then `$OPENCONTEXT_CONTEXTS_ROOT`, then
+    /// `contexts` under the default root
+    #[serde(default)]
+    pub contexts_root: Option<PathBuf>,
 }
 
 impl PathsConfig {
+    /// Configured collection name, or [`DEFAULT_COLLECTION`]
+    pub fn collection(&self) -> &str {
+        self.collection
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .unwrap_or(DEFAULT_COLLECTION)
+    }
+
+    /// Directory the default index paths live in
+    pub fn root_dir(&self) -> PathBuf {
+        if let Some(ref root) = self.root {
+            return root.clone();
+        }
+
+        if let Ok(root) = std::env::var("OPENCONTEXT_ROOT") {
+            return PathBuf::from(root);
+        }
+
+        dirs::home_dir()
+            .map(|h| h.join(".opencontext"))
+            .unwrap_or_else(|| PathBuf::from(".opencontext"))
+    }
+
+    /// `path` for this collection: unchanged for the default collection,
+    /// otherwise with the collection name before the extension
+    /// (`bm25-index.acme`, `sparse.acme.db`)
+    fn for_collection(&self, path: PathBuf) -> PathBuf {
+        let collection = self.collection();
+        if collection == DEFAULT_COLLECTION {
+            return path;
+        }
+        let name: String = collection
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file_name = match path.extension() {
+            Some(ext) => format!("{stem}.{name}.{}", ext.to_string_lossy()),
+            None => format!("{stem}.{name}"),
+        };
+        path.with_file_name(file_name)
+    }
+
+    /// Directory holding the workspace documents
+    pub fn get_contexts_root(&self) -> PathBuf {
+        if let Some(ref path) = self.contexts_root {
+            return path.clone();
+        }
+        if self.root.is_none() {
+            if let Ok(path) = std::env::var("OPENCONTEXT_CONTEXTS_ROOT") {
+                return PathBuf::from(path);
+            }
+        }
+        self.root_dir().join("contexts")
+    }
+
     /// Get LanceDB path, using default if not specified
     pub fn get_lancedb_path(&self) -> PathBuf {
         if let Some(ref path) = self.lancedb_path {
             return path.clone();
         }
 
-        if let Ok(root) = std::env::var("OPENCONTEXT_ROOT") {
-            return PathBuf::from(root).join("lancedb");
-        }
+        self.root_dir().join("lancedb")
+    }
 
-        dirs::home_dir()
-            .map(|h| h.join(".opencontext").join("lancedb"))
-            .unwrap_or_else(|| PathBuf::from(".opencontext/lancedb"))
+    /// Get the LanceDB path of the fast embedding tier, next to the main one
+    pub fn get_fast_lancedb_path(&self) -> PathBuf {
+        self.lancedb_sibling("fast")
+    }
+
+    /// Get the LanceDB path of the Matryoshka prefix vectors, next to the main one
+    pub fn get_prefix_lancedb_path(&self) -> PathBuf {
+        self.lancedb_sibling("mrl")
+    }
+
+    fn lancedb_sibling(&self, suffix: &str) -> PathBuf {
+        let path = self.get_lancedb_path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "lancedb".to_string());
+        path.with_file_name(format!("{name}-{suffix}"))
     }
 
     /// Get tantivy BM25 index path
     pub fn get_bm25_path(&self) -> PathBuf {
-        if let Ok(root) = std::env::var("OPENCONTEXT_ROOT") {
-            return PathBuf::from(root).join("bm25-index");
-        }
+        self.for_collection(self.default_bm25_path())
+    }
 
-        dirs::home_dir()
-            .map(|h| h.join(".opencontext").join("bm25-index"))
-            .unwrap_or_else(|| PathBuf::from(".opencontext/bm25-index"))
+    fn default_bm25_path(&self) -> PathBuf {
+        self.root_dir().join("bm25-index")
+    }
+
+    /// Get sparse vector database path
+    pub fn get_sparse_path(&self) -> PathBuf {
+        self.for_collection(self.default_sparse_path())
+    }
+
+    fn default_sparse_path(&self) -> PathBuf {
+        self.root_dir().join("sparse.db")
+    }
+
+    /// Get contextual retrieval cache path
+    pub fn get_contextual_cache_path(&self) -> PathBuf {
+        self.root_dir().join("contextual-cache.json")
+    }
+
+    /// Get folder summary cache path
+    pub fn get_folder_summary_cache_path(&self) -> PathBuf {
+        self.for_collection(self.root_dir().join("folder-summaries.json"))
+    }
+
+    /// Get slow-query log path (JSON lines)
+    pub fn get_slow_query_log_path(&self) -> PathBuf {
+        self.root_dir().join("slow-queries.jsonl")
+    }
+
+    /// Get boilerplate score file path (chunk id → score)
+    pub fn get_boilerplate_path(&self) -> PathBuf {
+        self.for_collection(self.default_boilerplate_path())
+    }
+
+    fn default_boilerplate_path(&self) -> PathBuf {
+        self.root_dir().join("boilerplate.json")
+    }
+
+    /// Get spelling index path, written at index time for searchers to map
+    pub fn get_spelling_path(&self) -> PathBuf {
+        self.for_collection(self.root_dir().join("spelling.idx"))
     }
 
     /// Get index metadata path
     pub fn get_index_metadata_path(&self) -> PathBuf {
+        self.for_collection(self.default_index_metadata_path())
+    }
+
+    fn default_index_metadata_path(&self) -> PathBuf {
         if let Some(ref path) = self.index_metadata_path {
             return path.clone();
         }
 
-        if let Ok(root) = std::env::var("OPENCONTEXT_ROOT") {
-            return PathBuf::from(root).join("index-metadata.json");
-        }
+        self.root_dir().join("index-metadata.json")
+    }
 
-        dirs::home_dir()
-            .map(|h| h.join(".opencontext").join("index-metadata.json"))
-            .unwrap_or_else(|| PathBuf::from(".opencontext/index-metadata.json"))
+    /// On-disk index artifacts, keyed by the name they carry in workspace
+    /// archives
+    pub fn index_files(&self) -> Vec<(String, PathBuf)> {
+        vec![
+            ("lancedb".into(), self.get_lancedb_path()),
+            ("lancedb-fast".into(), self.get_fast_lancedb_path()),
+            ("lancedb-mrl".into(), self.get_prefix_lancedb_path()),
+            ("bm25-index".into(), self.get_bm25_path()),
+            ("sparse.db".into(), self.get_sparse_path()),
+            ("boilerplate.json".into(), self.get_boilerplate_path()),
+            ("spelling.idx".into(), self.get_spelling_path()),
+            ("index-metadata.json".into(), self.get_index_metadata_path()),
+        ]
     }
 }
 
@@ -280,6 +1427,9 @@ impl SearchConfig {
         if let Ok(model) = std::env::var("EMBEDDING_MODEL") {
             config.embedding.model = model;
         }
+        if let Ok(collection) = std::env::var("OPENCONTEXT_COLLECTION") {
+            config.paths.collection = Some(collection);
+        }
 
         Ok(config)
     }
@@ -0,0 +1,183 @@
+//! Query-time context documents
+//!
+//! [`Searcher::search_with_context`](super::Searcher::search_with_context)
+//! takes texts that are not in the index, typically the document open in
+//! the editor, and ranks results by similarity to them as well as to the
+//! query. Each text is chunked like an indexed document and embedded for
+//! this query only. The averaged vector becomes one more ANN signal, which
+//! is fused into the query's results. Nothing is written to any store.
+
+use std::collections::HashMap;
+
+use super::chunker::Chunker;
+use super::types::{ContextDoc, MatchType, SearchHit};
+use crate::RelPath;
+
+/// Chunks embedded per context document; the rest of a long draft is ignored
+pub(super) const MAX_CHUNKS_PER_DOC: usize = 16;
+
+/// Texts to embed for `docs`: the first [`MAX_CHUNKS_PER_DOC`] chunks of each
+pub(super) fn chunk_texts(chunker: &Chunker, docs: &[ContextDoc]) -> Vec<String> {
+    docs.iter()
+        .flat_map(|doc| {
+            let path = doc.path.as_deref().unwrap_or("");
+            chunker
+                .chunk(&doc.content, path)
+                .into_iter()
+                .take(MAX_CHUNKS_PER_DOC)
+                .map(|c| c.content)
+        })
+        .collect()
+}
+
+/// Unit-length mean of `vectors`, or `None` if there are none
+pub(super) fn mean_vector(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dims = vectors.first()?.len();
+    let mut mean = vec![0.0_f32; dims];
+    for vector in vectors {
+        for (sum, v) in mean.iter_mut().zip(vector) {
+            *sum += v;
+        }
+    }
+    let norm = mean.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        mean.iter_mut().for_each(|v| *v /= norm);
+    }
+    Some(mean)
+}
+
+/// Normalized paths of the context documents that name one
+pub(super) fn own_paths(docs: &[ContextDoc]) -> Vec<String> {
+    docs.iter()
+        .filter_map(|d| d.path.as_deref())
+        .map(|p| RelPath::new(p).into_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Reciprocal-rank fusion of the query's `hits` with the context signal's
+/// `related` hits, weighted by `weight`. Query hits keep how they matched; a
+/// keyword hit also found by the context signal becomes a hybrid match.
+/// Scores are normalized to [0, 1].
+pub(super) fn fuse(
+    hits: Vec<SearchHit>,
+    related: Vec<SearchHit>,
+    weight: f32,
+    rrf_k: f32,
+    key: impl Fn(&SearchHit) -> String,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let mut fused: HashMap<String, (f32, SearchHit)> = HashMap::new();
+    for (rank, hit) in hits.into_iter().enumerate() {
+        let score = 1.0 / (rrf_k + rank as f32 + 1.0);
+        fused.entry(key(&hit)).or_insert((score, hit));
+    }
+    for (rank, hit) in related.into_iter().enumerate() {
+        let score = weight / (rrf_k + rank as f32 + 1.0);
+        fused
+            .entry(key(&hit))
+            .and_modify(|(total, existing)| {
+                *total += score;
+                if existing.matched_by == MatchType::Keyword {
+                    existing.matched_by = MatchType::Hybrid;
+                }
+            })
+            .or_insert((
+                score,
+                SearchHit {
+                    matched_by: MatchType::Vector,
+                    ..hit
+                },
+            ));
+    }
+
+    let max_score = fused.values().map(|(s, _)| *s).fold(0.0_f32, f32::max);
+    let mut results: Vec<SearchHit> = fused
+        .into_values()
+        .map(|(score, hit)| SearchHit {
+            score: if max_score > 0.0 {
+                score / max_score
+            } else {
+                0.0
+            },
+            ..hit
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    results.truncate(limit);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(id: &str, matched_by: MatchType) -> SearchHit {
+        SearchHit {
+            chunk_id: Some(id.to_string()),
+            display_name: id.to_string(),
+            ..SearchHit::fixture(&format!("{id}.md"), matched_by)
+        }
+    }
+
+    fn key(h: &SearchHit) -> String {
+        h.chunk_id.clone().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_fuse_promotes_hits_related_to_context() {
+        let hits = vec![
+            hit("a", MatchType::Keyword),
+            hit("b", MatchType::Keyword),
+            hit("c", MatchType::Vector),
+        ];
+        let related = vec![hit("c", MatchType::Vector), hit("d", MatchType::Vector)];
+        let fused = fuse(hits, related, 1.0, 60.0, key, 10);
+
+        let ids: Vec<String> = fused.iter().map(key).collect();
+        assert_eq!(ids[0], "c");
+        assert_eq!(fused[0].score, 1.0);
+        assert_eq!(fused[0].matched_by, MatchType::Vector);
+        assert!(ids.contains(&"d".to_string()));
+        let a = fused.iter().find(|h| key(h) == "a").unwrap();
+        assert_eq!(a.matched_by, MatchType::Keyword);
+
+        let fused = fuse(
+            vec![hit("a", MatchType::Keyword)],
+            vec![hit("a", MatchType::Vector)],
+            1.0,
+            60.0,
+            key,
+            10,
+        );
+        assert_eq!(fused[0].matched_by, MatchType::Hybrid);
+    }
+
+    #[test]
+    fn test_mean_vector_is_unit_length() {
+        let mean = mean_vector(&[vec![1.0, 0.0], vec![0.0, 1.0]]).unwrap();
+        assert!((mean[0] - mean[1]).abs() < 1e-6);
+        assert!((mean.iter().map(|v| v * v).sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!(mean_vector(&[]).is_none());
+    }
+
+    #[test]
+    fn test_chunk_texts_and_own_paths() {
+        let docs = vec![
+            ContextDoc {
+                path: Some(r"drafts\plan.md".to_string()),
+                content: "# Plan\n\nShip the context search feature next week.".to_string(),
+            },
+            ContextDoc {
+                path: None,
+                content: "   ".to_string(),
+            },
+        ];
+        assert_eq!(chunk_texts(&Chunker::default(), &docs).len(), 1);
+        assert_eq!(own_paths(&docs), vec!["drafts/plan.md"]);
+    }
+}
@@ -0,0 +1,116 @@
+//! Contextual retrieval: LLM-generated chunk context
+//!
+//! Before embedding, each chunk is sent to the LLM together with (a truncated
+//! copy of) its document, and the short context the model writes is prepended
+//! to the embedded text. Results are cached on disk keyed by file path and
+//! chunk content, and new calls per build are capped by
+//! `contextual.max_chunks_per_build`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use super::config::ContextualConfig;
+use super::llm::LlmClient;
+use super::types::Chunk;
+
+const CONTEXT_PROMPT: &str = "You situate a chunk of a document within the whole document \
+to improve search retrieval. Reply with one or two short sentences saying which document and \
+section the chunk comes from and what it is about. Reply with the context only.";
+
+/// Generates and caches per-chunk contexts
+pub struct ContextualEnricher {
+    llm: LlmClient,
+    config: ContextualConfig,
+    cache_path: PathBuf,
+    cache: HashMap<String, String>,
+    generated: usize,
+    dirty: bool,
+}
+
+impl ContextualEnricher {
+    /// Create an enricher, loading any cached contexts from `cache_path`
+    pub fn new(llm: LlmClient, config: ContextualConfig, cache_path: PathBuf) -> Self {
+        let cache = std::fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default();
+        Self {
+            llm,
+            config,
+            cache_path,
+            cache,
+            generated: 0,
+            dirty: false,
+        }
+    }
+
+    /// Reset the per-build call budget
+    pub fn start_build(&mut self) {
+        self.generated = 0;
+    }
+
+    fn cache_key(chunk: &Chunk) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(chunk.file_path.as_bytes());
+        hasher.update([0]);
+        hasher.update(chunk.content.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Context for a chunk: cached, freshly generated, or `None` when the
+    /// budget is exhausted or the LLM call fails.
+    pub async fn context_for(&mut self, document: &str, chunk: &Chunk) -> Option<String> {
+        let key = Self::cache_key(chunk);
+        if let Some(context) = self.cache.get(&key) {
+            return Some(context.clone());
+        }
+        if self.generated >= self.config.max_chunks_per_build {
+            return None;
+        }
+        self.generated += 1;
+
+        let document: String = document.chars().take(self.config.max_doc_chars).collect();
+        let prompt = format!(
+            "<document path=\"{}\">\n{}\n</document>\n<chunk>\n{}\n</chunk>",
+            chunk.file_path, document, chunk.content
+        );
+        match self.llm.complete(CONTEXT_PROMPT, &prompt).await {
+            Ok(context) if !context.is_empty() => {
+                self.cache.insert(key, context.clone());
+                self.dirty = true;
+                Some(context)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!(
+                    "[Contextual] Context generation failed for {}: {}",
+                    chunk.id,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Persist newly generated contexts
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Some(parent) = self.cache_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&self.cache) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(&self.cache_path, json) {
+                    log::warn!("[Contextual] Failed to write cache: {}", e);
+                } else {
+                    self.dirty = false;
+                }
+            }
+            Err(e) => log::warn!("[Contextual] Failed to serialize cache: {}", e),
+        }
+    }
+}
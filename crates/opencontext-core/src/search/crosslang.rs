@@ -0,0 +1,103 @@
+//! Cross-language retrieval
+//!
+//! Vector search is language-agnostic as long as the embedding model is
+//! multilingual. Keyword search is not, so when cross-language retrieval is on
+//! the query is translated into the workspace's other languages and the BM25
+//! results for every version are merged.
+
+use std::collections::HashMap;
+
+use super::types::SearchHit;
+
+/// Language of a query, guessed from its script
+pub fn detect_language(query: &str) -> &'static str {
+    let mut cjk = 0;
+    let mut kana = 0;
+    let mut hangul = 0;
+    let mut cyrillic = 0;
+    let mut latin = 0;
+    for c in query.chars() {
+        match c {
+            '\u{3040}'..='\u{30ff}' => kana += 1,
+            '\u{ac00}'..='\u{d7af}' => hangul += 1,
+            '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => cjk += 1,
+            '\u{0400}'..='\u{04ff}' => cyrillic += 1,
+            c if c.is_ascii_alphabetic() => latin += 1,
+            _ => {}
+        }
+    }
+    if kana > 0 {
+        "ja"
+    } else if hangul > 0 {
+        "ko"
+    } else if cjk > 0 && cjk * 2 >= latin / 3 {
+        // A handful of Latin letters (product names, acronyms) don't make it English
+        "zh"
+    } else if cyrillic > latin {
+        "ru"
+    } else {
+        "en"
+    }
+}
+
+/// Workspace languages the query should be translated into
+pub fn target_languages<'a>(query: &str, languages: &'a [String]) -> Vec<&'a str> {
+    let source = detect_language(query);
+    languages
+        .iter()
+        .map(String::as_str)
+        .filter(|l| !l.eq_ignore_ascii_case(source))
+        .collect()
+}
+
+/// System prompt for translating a search query into `language`
+pub fn translate_prompt(language: &str) -> String {
+    format!(
+        "Translate the search query into the language with ISO code '{language}'. \
+Keep code identifiers, file names and proper nouns unchanged. \
+Reply with the translated query only."
+    )
+}
+
+/// Merge keyword results from several query versions, keeping each chunk's
+/// best (per-query normalized) score
+pub fn merge_hits(lists: Vec<Vec<SearchHit>>, limit: usize) -> Vec<SearchHit> {
+    let mut best: HashMap<String, SearchHit> = HashMap::new();
+    for hit in lists.into_iter().flatten() {
+        let key = hit
+            .chunk_id
+            .clone()
+            .unwrap_or_else(|| format!("{}:{:?}", hit.file_path, hit.line_start));
+        match best.get(&key) {
+            Some(existing) if existing.score >= hit.score => {}
+            _ => {
+                best.insert(key, hit);
+            }
+        }
+    }
+    let mut merged: Vec<SearchHit> = best.into_values().collect();
+    merged.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    merged.truncate(limit);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("向量索引怎么重建"), "zh");
+        assert_eq!(detect_language("LanceDB 向量索引"), "zh");
+        assert_eq!(detect_language("rebuild the vector index"), "en");
+        assert_eq!(detect_language("インデックス"), "ja");
+
+        let languages = ["en", "zh"].map(String::from);
+        assert_eq!(target_languages("向量索引", &languages), vec!["en"]);
+        assert_eq!(target_languages("vector index", &languages), vec!["zh"]);
+    }
+}
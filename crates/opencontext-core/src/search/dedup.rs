@@ -0,0 +1,115 @@
+//! Cross-document result deduplication
+//!
+//! The same text often lives under several paths: a copy left behind by a
+//! rename, a template pasted into two projects. Without deduplication every
+//! copy takes a result slot. Before results are cut to the limit, each one is
+//! compared with the better results kept so far. If it comes from another
+//! document and has the same text (ignoring case and whitespace) or a nearly
+//! parallel chunk vector, it is folded into that result's `also_in`.
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+use super::late_interaction::cosine;
+use super::types::SearchHit;
+
+/// Hash of `text` with case and whitespace folded, `None` for blank text
+pub(super) fn content_key(text: &str) -> Option<String> {
+    let mut hasher = Sha256::new();
+    let mut empty = true;
+    for word in text.split_whitespace() {
+        if !empty {
+            hasher.update(b" ");
+        }
+        hasher.update(word.to_lowercase().as_bytes());
+        empty = false;
+    }
+    (!empty).then(|| hex::encode(&hasher.finalize()[..16]))
+}
+
+/// Merge duplicates in `hits` (best first) into the first result holding
+/// the same content. `vectors` maps chunk ids to embeddings; pairs whose
+/// cosine similarity reaches `similarity` count as duplicates too.
+pub(super) fn dedup(
+    hits: Vec<SearchHit>,
+    vectors: &HashMap<String, Vec<f32>>,
+    similarity: f32,
+) -> Vec<SearchHit> {
+    let vector = |hit: &SearchHit| hit.chunk_id.as_ref().and_then(|id| vectors.get(id));
+    let mut kept: Vec<(SearchHit, Option<String>)> = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let key = content_key(&hit.content);
+        let duplicate_of = kept.iter().position(|(best, best_key)| {
+            if best.file_path == hit.file_path {
+                return false;
+            }
+            if key.is_some() && key == *best_key {
+                return true;
+            }
+            match (vector(best), vector(&hit)) {
+                (Some(a), Some(b)) => cosine(a, b) >= similarity,
+                _ => false,
+            }
+        });
+        let Some(i) = duplicate_of else {
+            kept.push((hit, key));
+            continue;
+        };
+        let best = &mut kept[i].0;
+        for path in std::iter::once(hit.file_path).chain(hit.also_in) {
+            if path != best.file_path && !best.also_in.contains(&path) {
+                best.also_in.push(path);
+            }
+        }
+    }
+    kept.into_iter().map(|(hit, _)| hit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::MatchType;
+
+    fn hit(path: &str, chunk_id: &str, content: &str, score: f32) -> SearchHit {
+        SearchHit {
+            chunk_id: Some(chunk_id.to_string()),
+            content: content.to_string(),
+            score,
+            ..SearchHit::fixture(path, MatchType::Hybrid)
+        }
+    }
+
+    #[test]
+    fn test_dedup_merges_copies_into_best_result() {
+        let hits = vec![
+            hit("plans/q3.md", "a", "Ship the   search revamp in Q3.", 0.9),
+            hit("plans/q3.md", "b", "Hiring plan.", 0.8),
+            hit("old/q3-copy.md", "c", "ship the search revamp\nin Q3.", 0.7),
+            hit("notes/other.md", "d", "Unrelated text.", 0.6),
+            hit("archive/q3.md", "e", "Ship the search revamp in Q3!", 0.5),
+        ];
+        let vectors = HashMap::from([
+            ("a".to_string(), vec![1.0, 0.0]),
+            ("d".to_string(), vec![0.0, 1.0]),
+            ("e".to_string(), vec![0.999, 0.01]),
+        ]);
+        let deduped = dedup(hits.clone(), &vectors, 0.98);
+        let paths: Vec<&str> = deduped.iter().map(|h| h.file_path.as_str()).collect();
+        assert_eq!(paths, vec!["plans/q3.md", "plans/q3.md", "notes/other.md"]);
+        assert_eq!(deduped[0].also_in, vec!["old/q3-copy.md", "archive/q3.md"]);
+        assert!(deduped[1].also_in.is_empty());
+
+        // Without vectors only identical text is merged
+        let deduped = dedup(hits, &HashMap::new(), 0.98);
+        assert_eq!(deduped.len(), 4);
+        assert_eq!(deduped[0].also_in, vec!["old/q3-copy.md"]);
+    }
+
+    #[test]
+    fn test_content_key_folds_case_and_whitespace() {
+        assert_eq!(content_key("A  b\nC"), content_key("a b c"));
+        assert_ne!(content_key("a b c"), content_key("a bc"));
+        assert_eq!(content_key(" \n"), None);
+    }
+}
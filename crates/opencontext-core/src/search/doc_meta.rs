@@ -0,0 +1,119 @@
+//! Document stats for result cards
+//!
+//! While chunking, the indexer records each document's title, word and
+//! chunk count and its file times. They are saved in the index metadata under
+//! `docs`, next to the checksums. The searcher attaches them to document
+//! and chunk results as [`DocMeta`], so a UI can render a rich result
+//! without asking for the document. The document's
+//! [provenance](super::provenance) is recorded alongside. Documents indexed
+//! before these stats were recorded get them on their next re-index.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::config::SearchConfig;
+use super::types::DocMeta;
+
+/// Document stats by workspace path
+pub type Stats = HashMap<String, DocMeta>;
+
+/// Reading speed used for `reading_minutes`
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Words in `text`: whitespace-separated runs, with each CJK character
+/// counted on its own since those scripts don't separate words
+pub(super) fn word_count(text: &str) -> usize {
+    let mut words = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            words += 1;
+            in_word = false;
+        } else if c.is_whitespace() {
+            in_word = false;
+        } else if !in_word {
+            words += 1;
+            in_word = true;
+        }
+    }
+    words
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4dbf}'   // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}'   // CJK Unified Ideographs
+        | '\u{ac00}'..='\u{d7af}'   // Hangul syllables
+        | '\u{f900}'..='\u{faff}'   // CJK Compatibility Ideographs
+    )
+}
+
+/// Stats for the document at `rel_path` with body `content` split into
+/// `chunks` chunks
+pub(super) fn compute(rel_path: &str, content: &str, chunks: usize, abs_path: &Path) -> DocMeta {
+    let words = word_count(crate::frontmatter::body(content));
+    let file = std::fs::metadata(abs_path).ok();
+    let millis = |time: std::io::Result<SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+    };
+    let title = match crate::frontmatter::fields(content).get("title") {
+        Some(serde_json::Value::String(title)) if !title.trim().is_empty() => {
+            Some(title.trim().to_string())
+        }
+        _ => None,
+    };
+    DocMeta {
+        title,
+        words,
+        chunks,
+        reading_minutes: words.div_ceil(WORDS_PER_MINUTE).max(1),
+        modified: file.as_ref().and_then(|m| millis(m.modified())),
+        created: file.as_ref().and_then(|m| millis(m.created())),
+        provenance: Some(super::provenance::detect(rel_path, content)),
+    }
+}
+
+/// Stats by document path from the index metadata
+pub(super) fn load(config: &SearchConfig) -> Stats {
+    std::fs::read_to_string(config.paths.get_index_metadata_path())
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("docs").cloned())
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_count_counts_cjk_characters() {
+        assert_eq!(word_count("Ship the  search\nrevamp."), 4);
+        assert_eq!(word_count("搜索功能 v2"), 5);
+        assert_eq!(word_count(" \n"), 0);
+    }
+
+    #[test]
+    fn test_compute_skips_frontmatter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("plan.md");
+        let content = format!("---\ntitle: Plan\n---\n{}", "word ".repeat(450));
+        std::fs::write(&path, &content).unwrap();
+
+        let meta = compute("plan.md", &content, 3, &path);
+        assert_eq!(meta.title.as_deref(), Some("Plan"));
+        assert_eq!(meta.words, 450);
+        assert_eq!(meta.chunks, 3);
+        assert_eq!(meta.reading_minutes, 3);
+        assert!(meta.modified.is_some());
+        assert_eq!(
+            compute("gone.md", "", 0, &dir.path().join("gone.md")).modified,
+            None
+        );
+    }
+}
@@ -102,7 +102,13 @@ impl EmbeddingClient {
         let mut all_embeddings = Vec::with_capacity(texts.len());
 
         for batch in texts.chunks(self.config.batch_size) {
-            let batch_embeddings = self.embed_batch(batch.to_vec(), &api_key, &url).await?;
+            let batch_embeddings = self
+                .embed_batch(batch.to_vec(), &api_key, &url)
+                .await
+                .map_err(|e| {
+                    e.with_operation("embed")
+                        .with_provider(&self.config.api_base)
+                })?;
             all_embeddings.extend(batch_embeddings);
         }
 
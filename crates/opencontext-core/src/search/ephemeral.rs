@@ -0,0 +1,355 @@
+//! Session-scoped in-memory index
+//!
+//! An [`EphemeralIndex`] holds texts an agent wants to search for a while,
+//! such as a conversation transcript or pasted notes, without adding them
+//! to the workspace index. Texts are chunked with the workspace's chunk
+//! settings, embedded with the same client as the main index and analyzed
+//! with the keyword index's tokenizer. Queries rank chunks by vector
+//! similarity and BM25 and fuse the two with weighted RRF. Nothing is
+//! written to disk; the index lives as long as its owner.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+
+use super::chunker::Chunker;
+use super::config::SearchConfig;
+use super::embedding::EmbeddingClient;
+use super::error::SearchResult;
+use super::tokenizer;
+use super::types::{MatchType, SearchHit, TextChunk};
+
+const RRF_K: f32 = 60.0;
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+struct EphemeralChunk {
+    source: String,
+    chunk_index: usize,
+    chunk: TextChunk,
+    /// Analyzed term frequencies
+    terms: HashMap<String, u32>,
+    length: usize,
+    vector: Vec<f32>,
+}
+
+/// In-memory index of texts added during a session
+pub struct EphemeralIndex {
+    config: SearchConfig,
+    embedding_client: Arc<EmbeddingClient>,
+    chunker: Chunker,
+    analyzer: TextAnalyzer,
+    chunks: Vec<EphemeralChunk>,
+}
+
+impl EphemeralIndex {
+    /// Create an empty index with its own embedding client. Use
+    /// [`Searcher::ephemeral`](super::Searcher::ephemeral) to share the
+    /// client of an open searcher.
+    pub fn new(config: SearchConfig) -> SearchResult<Self> {
+        let client = Arc::new(EmbeddingClient::new(config.embedding.clone())?);
+        Ok(Self::with_client(config, client))
+    }
+
+    pub(super) fn with_client(
+        config: SearchConfig,
+        embedding_client: Arc<EmbeddingClient>,
+    ) -> Self {
+        Self {
+            chunker: Chunker::new(config.search.chunk_size, config.search.chunk_overlap),
+            analyzer: tokenizer::analyzer(&config.tokenizer),
+            config,
+            embedding_client,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Chunk, embed and add `content` under the name `source`, replacing
+    /// whatever was added under that name before. Returns the number of
+    /// chunks added.
+    pub async fn add(&mut self, source: &str, content: &str) -> SearchResult<usize> {
+        let chunks = self.chunker.chunk(content, source);
+        let texts = chunks
+            .iter()
+            .map(|c| tokenizer::normalize_for_embedding(&c.content, &self.config.tokenizer))
+            .collect();
+        let vectors = self.embedding_client.embed(texts).await?;
+        Ok(self.insert(source, chunks, vectors))
+    }
+
+    fn insert(&mut self, source: &str, chunks: Vec<TextChunk>, vectors: Vec<Vec<f32>>) -> usize {
+        self.remove(source);
+        let count = chunks.len();
+        for (chunk_index, (chunk, vector)) in chunks.into_iter().zip(vectors).enumerate() {
+            let terms = analyze(&self.analyzer, &chunk.content);
+            let mut frequencies: HashMap<String, u32> = HashMap::new();
+            for term in &terms {
+                *frequencies.entry(term.clone()).or_default() += 1;
+            }
+            self.chunks.push(EphemeralChunk {
+                source: source.to_string(),
+                chunk_index,
+                chunk,
+                terms: frequencies,
+                length: terms.len(),
+                vector,
+            });
+        }
+        count
+    }
+
+    /// Drop everything added under `source`; returns the number of chunks removed
+    pub fn remove(&mut self, source: &str) -> usize {
+        let before = self.chunks.len();
+        self.chunks.retain(|c| c.source != source);
+        before - self.chunks.len()
+    }
+
+    /// Drop everything
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Number of chunks held
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Names of the added texts, in the order they were added
+    pub fn sources(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.chunks
+            .iter()
+            .filter(|c| seen.insert(c.source.as_str()))
+            .map(|c| c.source.clone())
+            .collect()
+    }
+
+    /// Best `limit` chunks for `query`. Hits carry the source name as
+    /// `file_path` and `<source>#<n>` as `chunk_id`.
+    pub async fn search(&self, query: &str, limit: usize) -> SearchResult<Vec<SearchHit>> {
+        let query = query.trim();
+        if query.is_empty() || self.chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let normalized = tokenizer::normalize_for_embedding(query, &self.config.tokenizer);
+        let query_vector = self.embedding_client.embed_one(&normalized).await?;
+        Ok(self.rank(query, &query_vector, limit))
+    }
+
+    fn rank(&self, query: &str, query_vector: &[f32], limit: usize) -> Vec<SearchHit> {
+        let mut by_vector: Vec<(usize, f32)> = self
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, cosine(query_vector, &c.vector)))
+            .collect();
+        sort_desc(&mut by_vector);
+
+        let mut by_keyword = self.bm25(query);
+        sort_desc(&mut by_keyword);
+
+        let weights = &self.config.search;
+        let mut fused: HashMap<usize, (f32, HashSet<MatchType>)> = HashMap::new();
+        for (match_type, weight, ranked) in [
+            (MatchType::Vector, weights.vector_weight, &by_vector),
+            (MatchType::Keyword, weights.keyword_weight, &by_keyword),
+        ] {
+            for (rank, (i, _)) in ranked.iter().enumerate() {
+                let entry = fused.entry(*i).or_default();
+                entry.0 += weight / (RRF_K + rank as f32 + 1.0);
+                entry.1.insert(match_type);
+            }
+        }
+
+        let max_score = fused.values().map(|(s, _)| *s).fold(0.0_f32, f32::max);
+        let mut ranked: Vec<(usize, f32, HashSet<MatchType>)> = fused
+            .into_iter()
+            .map(|(i, (score, signals))| (i, score, signals))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(i, score, signals)| {
+                let matched_by = if signals.len() > 1 {
+                    MatchType::Hybrid
+                } else {
+                    signals.into_iter().next().unwrap_or(MatchType::Vector)
+                };
+                let score = if max_score > 0.0 {
+                    score / max_score
+                } else {
+                    0.0
+                };
+                self.hit(&self.chunks[i], score, matched_by)
+            })
+            .collect()
+    }
+
+    /// BM25 score of every chunk sharing a term with `query`
+    fn bm25(&self, query: &str) -> Vec<(usize, f32)> {
+        let query_terms: HashSet<String> = analyze(&self.analyzer, query).into_iter().collect();
+        let n = self.chunks.len() as f32;
+        let avg_length = (self.chunks.iter().map(|c| c.length).sum::<usize>() as f32 / n).max(1.0);
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for term in &query_terms {
+            let df = self
+                .chunks
+                .iter()
+                .filter(|c| c.terms.contains_key(term))
+                .count() as f32;
+            if df == 0.0 {
+                continue;
+            }
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            for (i, chunk) in self.chunks.iter().enumerate() {
+                let Some(&tf) = chunk.terms.get(term) else {
+                    continue;
+                };
+                let tf = tf as f32;
+                let norm = 1.0 - BM25_B + BM25_B * chunk.length as f32 / avg_length;
+                *scores.entry(i).or_default() += idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm);
+            }
+        }
+        scores.into_iter().collect()
+    }
+
+    fn hit(&self, c: &EphemeralChunk, score: f32, matched_by: MatchType) -> SearchHit {
+        SearchHit {
+            file_path: c.source.clone(),
+            chunk_id: Some(format!("{}#{}", c.source, c.chunk_index)),
+            display_name: c.source.clone(),
+            content: c.chunk.content.clone(),
+            snippet: None,
+            heading_path: (!c.chunk.heading_path.is_empty()).then(|| c.chunk.heading_path.clone()),
+            section_title: None,
+            line_start: Some(c.chunk.start_line),
+            line_end: Some(c.chunk.end_line),
+            block_id: None,
+            score,
+            matched_by,
+            hit_count: None,
+            doc_count: None,
+            folder_path: None,
+            aggregate_type: None,
+            doc_type: None,
+            entry_id: None,
+            entry_date: None,
+            entry_created_at: None,
+            idea_box: None,
+            children: vec![],
+            also_in: Vec::new(),
+            folder_summary: None,
+            doc_meta: None,
+            citation: None,
+            provenance: None,
+            trust: None,
+        }
+    }
+}
+
+/// Terms of `text` as the keyword index would store them
+fn analyze(analyzer: &TextAnalyzer, text: &str) -> Vec<String> {
+    let mut analyzer = analyzer.clone();
+    let mut stream = analyzer.token_stream(text);
+    let mut terms = Vec::new();
+    while stream.advance() {
+        terms.push(stream.token().text.clone());
+    }
+    terms
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm =
+        a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        dot / norm
+    } else {
+        0.0
+    }
+}
+
+fn sort_desc(ranked: &mut [(usize, f32)]) {
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Index whose client is never called; tests supply vectors directly
+    fn index() -> EphemeralIndex {
+        let mut config = SearchConfig::default();
+        config.embedding.api_key = Some("unused".to_string());
+        let client = Arc::new(EmbeddingClient::new(config.embedding.clone()).unwrap());
+        EphemeralIndex::with_client(config, client)
+    }
+
+    fn chunk(content: &str) -> TextChunk {
+        TextChunk {
+            content: content.to_string(),
+            heading_path: String::new(),
+            start_line: 1,
+            end_line: 1,
+        }
+    }
+
+    #[test]
+    fn test_rank_fuses_keyword_and_vector_signals() {
+        let mut index = index();
+        index.insert(
+            "transcript",
+            vec![
+                chunk("We agreed the deploy runs every Friday afternoon."),
+                chunk("Lunch options near the office were discussed."),
+            ],
+            vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+        );
+        index.insert(
+            "paste",
+            vec![chunk("Rollback steps for a failed deployment.")],
+            vec![vec![0.6, 0.8]],
+        );
+
+        // "running" and "runs" share a stem in the keyword analyzer
+        let hits = index.rank("when is the deploy running", &[1.0, 0.0], 3);
+        assert_eq!(hits[0].file_path, "transcript");
+        assert_eq!(hits[0].chunk_id.as_deref(), Some("transcript#0"));
+        assert_eq!(hits[0].matched_by, MatchType::Hybrid);
+        assert_eq!(hits[0].score, 1.0);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(
+            hits[2].content,
+            "Lunch options near the office were discussed."
+        );
+    }
+
+    #[test]
+    fn test_add_replaces_source_and_clear_empties() {
+        let mut index = index();
+        index.insert(
+            "notes",
+            vec![chunk("one"), chunk("two")],
+            vec![vec![1.0]; 2],
+        );
+        index.insert("paste", vec![chunk("three")], vec![vec![1.0]]);
+        assert_eq!(
+            index.insert("notes", vec![chunk("four")], vec![vec![1.0]]),
+            1
+        );
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.sources(), vec!["paste", "notes"]);
+
+        assert_eq!(index.remove("paste"), 1);
+        index.clear();
+        assert!(index.is_empty());
+        assert!(index.rank("four", &[1.0], 5).is_empty());
+    }
+}
@@ -1,5 +1,9 @@
 //! Search error types
 
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
 use thiserror::Error;
 
 /// Search-specific error type
@@ -11,6 +15,9 @@ pub enum SearchError {
     #[error("Embedding API error: {0}")]
     Embedding(String),
 
+    #[error("LLM API error: {0}")]
+    Llm(String),
+
     #[error("Vector store error: {0}")]
     VectorStore(String),
 
@@ -20,18 +27,26 @@ pub enum SearchError {
     #[error("Search error: {0}")]
     Search(String),
 
+    #[error("Invalid search options: {0}")]
+    InvalidOptions(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[cfg(feature = "search")]
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[cfg(feature = "search")]
     #[error("LanceDB error: {0}")]
     Lance(#[from] lancedb::Error),
 
+    #[error("Search cancelled")]
+    Cancelled,
+
     #[error("Index not built. Run 'oc index build' first.")]
     IndexNotBuilt,
 
@@ -39,7 +54,223 @@ pub enum SearchError {
         "API key not configured. Set OPENAI_API_KEY or configure in ~/.opencontext/config.toml"
     )]
     ApiKeyMissing,
+
+    /// Another error with where it happened; see [`SearchError::code`] and
+    /// [`SearchError::hint`], which look through it
+    #[error("{source} ({context})")]
+    Context {
+        source: Box<SearchError>,
+        context: ErrorContext,
+    },
+}
+
+/// Where an error happened: the operation, the file or directory involved
+/// and the remote provider, whichever are known
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ErrorContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            self.operation.clone(),
+            self.path.as_ref().map(|p| format!("path {p}")),
+            self.provider.as_ref().map(|p| format!("provider {p}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// What API error payloads carry for a failed search call
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorPayload {
+    pub error: String,
+    pub code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<ErrorContext>,
+}
+
+impl SearchError {
+    /// Stable machine-readable code (`INDEX_NOT_BUILT`, `API_KEY_MISSING`,
+    /// ...). Messages may be reworded; codes are not.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "CONFIG_ERROR",
+            Self::Embedding(_) => "EMBEDDING_ERROR",
+            Self::Llm(_) => "LLM_ERROR",
+            Self::VectorStore(_) => "VECTOR_STORE_ERROR",
+            Self::Index(_) => "INDEX_ERROR",
+            Self::Search(_) => "SEARCH_ERROR",
+            Self::InvalidOptions(_) => "INVALID_OPTIONS",
+            Self::Io(_) => "IO_ERROR",
+            #[cfg(feature = "search")]
+            Self::Http(e) if e.is_timeout() => "HTTP_TIMEOUT",
+            #[cfg(feature = "search")]
+            Self::Http(_) => "HTTP_ERROR",
+            Self::Json(_) => "JSON_ERROR",
+            #[cfg(feature = "search")]
+            Self::Lance(_) => "VECTOR_STORE_ERROR",
+            Self::Cancelled => "CANCELLED",
+            Self::IndexNotBuilt => "INDEX_NOT_BUILT",
+            Self::ApiKeyMissing => "API_KEY_MISSING",
+            Self::Context { source, .. } => source.code(),
+        }
+    }
+
+    /// What the user can do about the error, if there is anything
+    pub fn hint(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Config(_) => {
+                "Check ~/.opencontext/config.toml and the OPENCONTEXT_* environment variables"
+            }
+            Self::Embedding(_) => {
+                "Check EMBEDDING_MODEL and EMBEDDING_API_BASE with `oc config list` and that the provider is reachable"
+            }
+            Self::Llm(_) => "Check the [llm] model and API base in ~/.opencontext/config.toml",
+            Self::VectorStore(_) | Self::Index(_) => {
+                "The index may be damaged or outdated; rebuild it with `oc index build --force`"
+            }
+            #[cfg(feature = "search")]
+            Self::Lance(_) => {
+                "The index may be damaged or outdated; rebuild it with `oc index build --force`"
+            }
+            Self::InvalidOptions(_) => "See `oc search --help` for the accepted options",
+            Self::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                "Check that this user can read and write the OpenContext data directory"
+            }
+            #[cfg(feature = "search")]
+            Self::Http(e) if e.is_timeout() => {
+                "The provider did not answer in time; check the network or try again"
+            }
+            #[cfg(feature = "search")]
+            Self::Http(e) if e.is_connect() => {
+                "Could not connect; check EMBEDDING_API_BASE and the network"
+            }
+            #[cfg(feature = "search")]
+            Self::Http(e) => match e.status().map(|s| s.as_u16()) {
+                Some(401 | 403) => "The provider rejected the API key; check EMBEDDING_API_KEY",
+                Some(429) => "Rate limited by the provider; wait a moment or lower EMBEDDING_BATCH_SIZE",
+                _ => return None,
+            },
+            Self::Json(_) => {
+                "Unexpected response; check that EMBEDDING_API_BASE is an OpenAI-compatible endpoint"
+            }
+            Self::IndexNotBuilt => "Run `oc index build` first",
+            Self::ApiKeyMissing => "Run `oc config set EMBEDDING_API_KEY <key>` or set OPENAI_API_KEY",
+            Self::Context { source, .. } => return source.hint(),
+            Self::Search(_) | Self::Io(_) | Self::Cancelled => return None,
+        })
+    }
+
+    /// Where the error happened, when that was recorded
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without its context
+    pub fn root(&self) -> &SearchError {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+
+    /// Record the operation that failed (e.g. "embed", "open keyword index")
+    pub fn with_operation(self, operation: impl Into<String>) -> Self {
+        let operation = operation.into();
+        self.add_context(|c| {
+            c.operation.get_or_insert(operation);
+        })
+    }
+
+    /// Record the file or directory involved
+    pub fn with_path(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().display().to_string();
+        self.add_context(|c| {
+            c.path.get_or_insert(path);
+        })
+    }
+
+    /// Record the remote provider involved, such as an API base URL
+    pub fn with_provider(self, provider: impl Into<String>) -> Self {
+        let provider = provider.into();
+        self.add_context(|c| {
+            c.provider.get_or_insert(provider);
+        })
+    }
+
+    /// Fields already set by a more specific caller are kept
+    fn add_context(self, set: impl FnOnce(&mut ErrorContext)) -> Self {
+        match self {
+            Self::Context {
+                source,
+                mut context,
+            } => {
+                set(&mut context);
+                Self::Context { source, context }
+            }
+            other => {
+                let mut context = ErrorContext::default();
+                set(&mut context);
+                Self::Context {
+                    source: Box::new(other),
+                    context,
+                }
+            }
+        }
+    }
+
+    /// Message, code, hint and context for an API error payload
+    pub fn payload(&self) -> ErrorPayload {
+        ErrorPayload {
+            error: self.to_string(),
+            code: self.code(),
+            hint: self.hint(),
+            context: self.context().cloned(),
+        }
+    }
 }
 
 /// Result type alias for search operations
 pub type SearchResult<T> = std::result::Result<T, SearchError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_keeps_code_and_hint() {
+        let err = SearchError::Embedding("Incorrect API key provided".to_string())
+            .with_operation("embed")
+            .with_provider("https://api.openai.com/v1")
+            .with_operation("index file");
+        assert_eq!(err.code(), "EMBEDDING_ERROR");
+        assert!(err.hint().unwrap().contains("EMBEDDING_MODEL"));
+        assert!(matches!(err.root(), SearchError::Embedding(_)));
+        assert_eq!(
+            err.to_string(),
+            "Embedding API error: Incorrect API key provided \
+             (embed, provider https://api.openai.com/v1)"
+        );
+
+        let payload = serde_json::to_value(SearchError::IndexNotBuilt.payload()).unwrap();
+        assert_eq!(payload["code"], "INDEX_NOT_BUILT");
+        assert_eq!(payload["hint"], "Run `oc index build` first");
+        assert!(payload.get("context").is_none());
+        assert_eq!(SearchError::Cancelled.hint(), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Facet counts over a search candidate set
+//!
+//! Counts are per matched unit (a document, or an idea entry) rather than per
+//! chunk, so a long document matching in ten places still counts once.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::types::{FacetCount, Facets, SearchHit};
+
+/// Compute folder / doc type / tag / month facets for `hits`
+pub fn compute(hits: &[SearchHit]) -> Facets {
+    let mut folder = Counter::default();
+    let mut doc_type = Counter::default();
+    let mut tag = Counter::default();
+    let mut month = Counter::default();
+
+    for hit in hits {
+        let unit = match hit.entry_id.as_deref() {
+            Some(entry_id) => format!("{}#{}", hit.file_path, entry_id),
+            None => hit.file_path.clone(),
+        };
+
+        if let Some((parent, _)) = hit.file_path.rsplit_once('/') {
+            folder.add(parent, &unit);
+        }
+        doc_type.add(hit.doc_type.as_deref().unwrap_or("doc"), &unit);
+        for t in extract_tags(&hit.content) {
+            tag.add(&t, &unit);
+        }
+        if let Some(m) = hit.entry_date.as_deref().and_then(|d| d.get(..7)) {
+            month.add(m, &unit);
+        }
+    }
+
+    Facets {
+        folder: folder.into_counts(),
+        doc_type: doc_type.into_counts(),
+        tag: tag.into_counts(),
+        month: month.into_counts(),
+    }
+}
+
+/// Inline `#tags` in chunk text (markdown headings are not tags)
+pub(crate) fn extract_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_start();
+        if line.starts_with('#') && line.trim_start_matches('#').starts_with(' ') {
+            continue;
+        }
+        for word in line.split_whitespace() {
+            let Some(rest) = word.strip_prefix('#') else {
+                continue;
+            };
+            let tag: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                .collect();
+            let tag = tag.trim_end_matches(['-', '/']);
+            if tag.chars().any(|c| !c.is_ascii_digit()) {
+                tags.push(tag.to_lowercase());
+            }
+        }
+    }
+    tags
+}
+
+#[derive(Default)]
+struct Counter {
+    units: BTreeMap<String, HashSet<String>>,
+}
+
+impl Counter {
+    fn add(&mut self, value: &str, unit: &str) {
+        if value.is_empty() {
+            return;
+        }
+        self.units
+            .entry(value.to_string())
+            .or_default()
+            .insert(unit.to_string());
+    }
+
+    /// Counts sorted by count (desc), then value
+    fn into_counts(self) -> Vec<FacetCount> {
+        let mut counts: Vec<FacetCount> = self
+            .units
+            .into_iter()
+            .map(|(value, units)| FacetCount {
+                value,
+                count: units.len(),
+            })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::types::MatchType;
+
+    fn hit(file_path: &str, content: &str) -> SearchHit {
+        SearchHit {
+            display_name: file_path.to_string(),
+            content: content.to_string(),
+            ..SearchHit::fixture(file_path, MatchType::Keyword)
+        }
+    }
+
+    #[test]
+    fn test_counts_documents_once() {
+        let hits = vec![
+            hit("Product/a.md", "# Heading\nsee #roadmap and #Q3"),
+            hit("Product/a.md", "more #roadmap"),
+            hit("Product/b.md", "#roadmap, issue #42"),
+            hit("notes.md", "plain"),
+        ];
+        let facets = compute(&hits);
+
+        assert_eq!(facets.folder.len(), 1);
+        assert_eq!(facets.folder[0].value, "Product");
+        assert_eq!(facets.folder[0].count, 2);
+        assert_eq!(facets.doc_type[0].count, 3);
+        assert_eq!(facets.tag[0].value, "roadmap");
+        assert_eq!(facets.tag[0].count, 2);
+        assert_eq!(facets.tag.len(), 2);
+        assert!(facets.month.is_empty());
+    }
+
+    #[test]
+    fn test_idea_months() {
+        let mut a = hit(".ideas/inbox/2024/05/01.md", "idea one");
+        a.doc_type = Some("idea".to_string());
+        a.entry_id = Some("e1".to_string());
+        a.entry_date = Some("2024-05-01".to_string());
+        let mut b = a.clone();
+        b.entry_id = Some("e2".to_string());
+
+        let facets = compute(&[a, b]);
+        assert_eq!(facets.month[0].value, "2024-05");
+        assert_eq!(facets.month[0].count, 2);
+        assert_eq!(facets.doc_type[0].value, "idea");
+    }
+}
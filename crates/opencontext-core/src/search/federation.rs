@@ -0,0 +1,340 @@
+//! Federated search across workspaces
+//!
+//! A [`FederatedSearcher`] holds one [`Searcher`] per workspace listed under
+//! `[federation]`, plus one for the local workspace. A query runs against
+//! all of them at once. Each workspace's results are ranked on their own
+//! scale, so scores are not compared across workspaces. Instead each result
+//! list is merged by weighted reciprocal rank, scaled by the workspace's
+//! `weight`. Every hit is labelled with the workspace it came from.
+//!
+//! A workspace that fails to open or to answer is reported in
+//! [`FederatedResults::workspaces`] and the others still return results.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::citation::format_citation;
+use super::config::{SearchConfig, WorkspaceConfig};
+use super::error::SearchResult;
+use super::searcher::{fusion_key, Searcher, RRF_K};
+use super::types::{SearchHit, SearchOptions, SearchResults};
+
+/// A search result and the workspace it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct FederatedHit {
+    pub workspace: String,
+    #[serde(flatten)]
+    pub hit: SearchHit,
+}
+
+/// How one workspace took part in a federated query
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceStatus {
+    pub name: String,
+    pub weight: f32,
+    /// Results the workspace returned before merging
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_missing: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Merged results of a federated query
+/// Uses snake_case to match Node.js API format
+#[derive(Debug, Clone, Serialize)]
+pub struct FederatedResults {
+    pub query: String,
+    pub results: Vec<FederatedHit>,
+    pub count: usize,
+    pub workspaces: Vec<WorkspaceStatus>,
+}
+
+struct Member {
+    name: String,
+    weight: f32,
+    /// Why the workspace could not be opened
+    searcher: Result<Searcher, String>,
+}
+
+/// Searches several workspaces and merges their results
+pub struct FederatedSearcher {
+    members: Vec<Member>,
+}
+
+impl FederatedSearcher {
+    /// Open the local workspace (unless `local_weight` is 0) and every
+    /// workspace under `[federation]`
+    pub async fn new(config: SearchConfig) -> SearchResult<Self> {
+        let federation = config.federation.clone();
+        let mut members = Vec::new();
+        if federation.local_weight > 0.0 {
+            members.push(Member {
+                name: federation.local_name.clone(),
+                weight: federation.local_weight,
+                searcher: Ok(Searcher::new(config.clone()).await?),
+            });
+        }
+        for workspace in &federation.workspaces {
+            let searcher = Searcher::new(workspace_config(&config, workspace))
+                .await
+                .map_err(|e| {
+                    log::warn!(
+                        "[Federation] Could not open workspace '{}': {}",
+                        workspace.name,
+                        e
+                    );
+                    e.to_string()
+                });
+            members.push(Member {
+                name: workspace.name.clone(),
+                weight: workspace.weight,
+                searcher,
+            });
+        }
+        Ok(Self { members })
+    }
+
+    /// Names of the federated workspaces, local first
+    pub fn workspaces(&self) -> Vec<String> {
+        self.members.iter().map(|m| m.name.clone()).collect()
+    }
+
+    /// Run `options` against every workspace concurrently and merge the
+    /// results. `options.collection` is ignored; each workspace searches the
+    /// collection it is configured with.
+    pub async fn search(&self, options: SearchOptions) -> SearchResult<FederatedResults> {
+        let query = options.query.trim().to_string();
+        let limit = options.limit();
+        let options = SearchOptions {
+            collection: None,
+            ..options
+        };
+        let runs = self.members.iter().map(|member| {
+            let options = options.clone();
+            async move {
+                match &member.searcher {
+                    Ok(searcher) => searcher.search(options).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.clone()),
+                }
+            }
+        });
+        let outcomes = futures::future::join_all(runs).await;
+
+        let mut statuses = Vec::new();
+        let mut lists = Vec::new();
+        for (member, outcome) in self.members.iter().zip(outcomes) {
+            let mut status = WorkspaceStatus {
+                name: member.name.clone(),
+                weight: member.weight,
+                count: 0,
+                index_missing: None,
+                error: None,
+            };
+            match outcome {
+                Ok(results) => {
+                    status.count = results.results.len();
+                    status.index_missing = results.index_missing;
+                    status.error = results.error.clone();
+                    lists.push((member.name.as_str(), member.weight, results));
+                }
+                Err(e) => {
+                    log::warn!("[Federation] Workspace '{}' failed: {}", member.name, e);
+                    status.error = Some(e);
+                }
+            }
+            statuses.push(status);
+        }
+
+        let mut results = merge(lists, limit);
+        // Footnotes were numbered per workspace; number them in merged order
+        if let Some(style) = options.citation {
+            for (rank, result) in results.iter_mut().enumerate() {
+                result.hit.citation = Some(format_citation(&result.hit, style, rank + 1));
+            }
+        }
+        Ok(FederatedResults {
+            query,
+            count: results.len(),
+            results,
+            workspaces: statuses,
+        })
+    }
+}
+
+/// `config` pointed at `workspace`'s root: its own index paths and collection
+fn workspace_config(config: &SearchConfig, workspace: &WorkspaceConfig) -> SearchConfig {
+    let mut config = config.clone();
+    config.paths.root = Some(workspace.root.clone());
+    config.paths.lancedb_path = None;
+    config.paths.index_metadata_path = None;
+    config.paths.contexts_root = None;
+    config.paths.collection = workspace.collection.clone();
+    config.federation = Default::default();
+    config
+}
+
+/// Weighted reciprocal-rank merge of per-workspace result lists
+/// (`(workspace, weight, results)`). Scores are normalized to [0, 1].
+fn merge(lists: Vec<(&str, f32, SearchResults)>, limit: usize) -> Vec<FederatedHit> {
+    let mut fused: HashMap<(String, String), (f32, FederatedHit)> = HashMap::new();
+    for (workspace, weight, results) in lists {
+        for (rank, hit) in results.results.into_iter().enumerate() {
+            let score = weight / (RRF_K + rank as f32 + 1.0);
+            fused
+                .entry((workspace.to_string(), fusion_key(&hit)))
+                .and_modify(|(total, _)| *total += score)
+                .or_insert((
+                    score,
+                    FederatedHit {
+                        workspace: workspace.to_string(),
+                        hit,
+                    },
+                ));
+        }
+    }
+
+    let max_score = fused.values().map(|(s, _)| *s).fold(0.0_f32, f32::max);
+    let mut merged: Vec<FederatedHit> = fused
+        .into_values()
+        .map(|(score, mut hit)| {
+            hit.hit.score = if max_score > 0.0 {
+                score / max_score
+            } else {
+                0.0
+            };
+            hit
+        })
+        .collect();
+    merged.sort_by(|a, b| {
+        b.hit
+            .score
+            .partial_cmp(&a.hit.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    merged.truncate(limit);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::MatchType;
+
+    fn results(ids: &[&str]) -> SearchResults {
+        let mut results = SearchResults::empty("q".to_string());
+        results.results = ids
+            .iter()
+            .map(|id| SearchHit {
+                file_path: format!("{id}.md"),
+                chunk_id: Some(id.to_string()),
+                display_name: id.to_string(),
+                content: String::new(),
+                snippet: None,
+                heading_path: None,
+                section_title: None,
+                line_start: None,
+                line_end: None,
+                block_id: None,
+                score: 0.5,
+                matched_by: MatchType::Hybrid,
+                hit_count: None,
+                doc_count: None,
+                folder_path: None,
+                aggregate_type: None,
+                doc_type: None,
+                entry_id: None,
+                entry_date: None,
+                entry_created_at: None,
+                idea_box: None,
+                children: vec![],
+                also_in: Vec::new(),
+                folder_summary: None,
+                doc_meta: None,
+                citation: None,
+                provenance: None,
+                trust: None,
+            })
+            .collect();
+        results
+    }
+
+    #[test]
+    fn test_merge_interleaves_by_weighted_rank() {
+        let merged = merge(
+            vec![
+                ("personal", 1.0, results(&["a", "b"])),
+                ("work", 2.0, results(&["a", "c"])),
+                ("archive", 0.5, results(&["d"])),
+            ],
+            10,
+        );
+        let labels: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|h| (h.workspace.as_str(), h.hit.chunk_id.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("work", "a"),
+                ("work", "c"),
+                ("personal", "a"),
+                ("personal", "b"),
+                ("archive", "d")
+            ]
+        );
+        assert_eq!(merged[0].hit.score, 1.0);
+        assert!(merged[4].hit.score < 0.3);
+
+        assert_eq!(
+            merge(vec![("work", 1.0, results(&["a", "b", "c"]))], 2).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_workspace_config_points_paths_at_root() {
+        let mut config = SearchConfig::default();
+        config.paths.lancedb_path = Some("/local/lancedb".into());
+        config.paths.collection = Some("notes".to_string());
+        let workspace = WorkspaceConfig {
+            name: "archive".to_string(),
+            root: "/data/archive".into(),
+            weight: 0.5,
+            collection: None,
+        };
+        config.federation.workspaces.push(workspace.clone());
+
+        let config = workspace_config(&config, &workspace);
+        assert_eq!(
+            config.paths.get_lancedb_path(),
+            std::path::Path::new("/data/archive/lancedb")
+        );
+        assert_eq!(
+            config.paths.get_bm25_path(),
+            std::path::Path::new("/data/archive/bm25-index")
+        );
+        assert!(config.federation.workspaces.is_empty());
+    }
+
+    #[test]
+    fn test_federation_config_from_toml() {
+        let config: SearchConfig = toml::from_str(
+            r#"
+            [federation]
+            local_name = "personal"
+
+            [[federation.workspaces]]
+            name = "work"
+            root = "/home/me/work/.opencontext"
+            weight = 0.8
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.federation.local_name, "personal");
+        assert_eq!(config.federation.local_weight, 1.0);
+        assert_eq!(config.federation.workspaces[0].weight, 0.8);
+        assert!(config.federation.workspaces[0].collection.is_none());
+    }
+}
@@ -0,0 +1,569 @@
+//! Typed result filters
+//!
+//! A [`Filter`] restricts search results by folder, tag, document type,
+//! date or frontmatter field, and combines these with `And`, `Or` and
+//! `Not`. It can be built in code or parsed from a small query-string
+//! language:
+//!
+//! ```text
+//! tag:rust path:projects/ created:>2024-01-01
+//! (type:idea OR tag:todo) -path:archive/ status:"in progress"
+//! ```
+//!
+//! Terms separated by spaces must all match; `OR` between two terms (or
+//! parenthesized groups) matches either and binds tighter than the spaces,
+//! and `-` or `NOT` negates the term after it. The fields are `path`, `tag`, `type`, `created` and
+//! `modified`; any other `key:value` compares a frontmatter field. Dates
+//! take `YYYY-MM-DD`, `>`, `>=`, `<`, `<=` a date, or a `from..to` range.
+//!
+//! Filters are applied to the candidates of a query, after retrieval.
+//! `tag` matches inline `#tags` in the matched chunk or the document's
+//! frontmatter `tags`. `created` is the entry date of an idea, otherwise the
+//! file's creation date; both dates of a document come from the stats the
+//! indexer records (see [`DocMeta`](super::DocMeta)).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::doc_meta;
+use super::error::{SearchError, SearchResult};
+use super::facets::extract_tags;
+use super::provenance;
+use super::types::SearchHit;
+use crate::relpath::{self, RelPath};
+
+/// Values of `type:`, as set on chunks by the indexer
+const DOC_TYPES: &[&str] = &["doc", "idea", "memory", "session", "commit", "pull_request"];
+
+/// A condition on search results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    /// Every filter matches (an empty list matches everything)
+    And(Vec<Filter>),
+    /// At least one filter matches
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    /// Documents in this folder or below it
+    Path(String),
+    /// Inline `#tag` or frontmatter tag; `project` also matches `project/x`
+    Tag(String),
+    /// `"doc"` or `"idea"`
+    DocType(String),
+    /// Source type or origin of the matched chunk's provenance, e.g.
+    /// `"evernote"` or `"web_clip"`
+    Source(String),
+    /// Date within `from..=to` (`YYYY-MM-DD`, either end open)
+    DateRange {
+        field: DateField,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        from: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<String>,
+    },
+    /// Frontmatter field equal to `value` (ignoring case), or a list field
+    /// containing it
+    Metadata {
+        key: String,
+        value: String,
+    },
+}
+
+/// Date a [`Filter::DateRange`] looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DateField {
+    Created,
+    Modified,
+}
+
+impl Filter {
+    /// Parse the query-string form described in the module docs
+    pub fn parse(expr: &str) -> SearchResult<Filter> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.and_expr()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(filter),
+            Some(token) => Err(invalid(format!("unexpected '{}'", token.text()))),
+        }
+    }
+
+    /// `self` and `other`, flattening nested `And`s
+    pub fn and(self, other: Filter) -> Filter {
+        match (self, other) {
+            (Filter::And(mut a), Filter::And(b)) => {
+                a.extend(b);
+                Filter::And(a)
+            }
+            (Filter::And(mut a), f) | (f, Filter::And(mut a)) => {
+                a.push(f);
+                Filter::And(a)
+            }
+            (a, b) => Filter::And(vec![a, b]),
+        }
+    }
+
+    /// Check field values, such as dates and document types
+    pub fn validate(&self) -> SearchResult<()> {
+        match self {
+            Filter::And(filters) | Filter::Or(filters) => {
+                filters.iter().try_for_each(Filter::validate)
+            }
+            Filter::Not(filter) => filter.validate(),
+            Filter::DocType(t) if !DOC_TYPES.contains(&t.as_str()) => Err(invalid(format!(
+                "type '{t}' is not one of {}",
+                DOC_TYPES.join(", ")
+            ))),
+            Filter::DateRange { from, to, .. } => {
+                for date in [from, to].into_iter().flatten() {
+                    parse_date(date)?;
+                }
+                match (from, to) {
+                    (Some(from), Some(to)) if from > to => {
+                        Err(invalid(format!("date range {from}..{to} is empty")))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = SearchError;
+
+    fn from_str(expr: &str) -> SearchResult<Filter> {
+        Filter::parse(expr)
+    }
+}
+
+fn invalid(message: String) -> SearchError {
+    SearchError::InvalidOptions(format!("filter: {message}"))
+}
+
+fn parse_date(date: &str) -> SearchResult<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| invalid(format!("'{date}' is not a YYYY-MM-DD date")))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::Word(word) => word,
+        }
+    }
+}
+
+/// Split on whitespace and parentheses; double quotes keep spaces in a word
+fn tokenize(expr: &str) -> SearchResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut chars = expr.chars();
+    let flush = |word: &mut String, tokens: &mut Vec<Token>| {
+        if !word.is_empty() {
+            tokens.push(Token::Word(std::mem::take(word)));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => word.push(c),
+                    None => return Err(invalid("unclosed quote".to_string())),
+                }
+            },
+            '(' | ')' if word.is_empty() || word == "-" || c == ')' => {
+                flush(&mut word, &mut tokens);
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            c if c.is_whitespace() => flush(&mut word, &mut tokens),
+            c => word.push(c),
+        }
+    }
+    flush(&mut word, &mut tokens);
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Terms that must all match, up to `)` or the end
+    fn and_expr(&mut self) -> SearchResult<Filter> {
+        let mut terms = Vec::new();
+        while self.peek().is_some_and(|t| *t != Token::Close) {
+            terms.push(self.or_expr()?);
+        }
+        match terms.len() {
+            0 => Err(invalid("expected a term".to_string())),
+            1 => Ok(terms.remove(0)),
+            _ => Ok(Filter::And(terms)),
+        }
+    }
+
+    /// A term, or terms joined by `OR`
+    fn or_expr(&mut self) -> SearchResult<Filter> {
+        let mut alternatives = vec![self.unary()?];
+        while self.peek().is_some_and(|t| t.text() == "OR") {
+            self.pos += 1;
+            alternatives.push(self.unary()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Filter::Or(alternatives)
+        })
+    }
+
+    fn unary(&mut self) -> SearchResult<Filter> {
+        match self.next() {
+            Some(Token::Open) => {
+                let filter = self.and_expr()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err(invalid("missing ')'".to_string())),
+                }
+            }
+            Some(Token::Word(word)) if word == "NOT" => Ok(Filter::Not(Box::new(self.unary()?))),
+            Some(Token::Word(word)) => match word.strip_prefix('-') {
+                Some("") => Ok(Filter::Not(Box::new(self.unary()?))),
+                Some(term) => Ok(Filter::Not(Box::new(term_filter(term)?))),
+                None => term_filter(&word),
+            },
+            Some(Token::Close) => Err(invalid("unexpected ')'".to_string())),
+            None => Err(invalid("expected a term".to_string())),
+        }
+    }
+}
+
+/// One `field:value` term
+fn term_filter(term: &str) -> SearchResult<Filter> {
+    let Some((key, value)) = term
+        .split_once(':')
+        .filter(|(k, v)| !k.is_empty() && !v.is_empty())
+    else {
+        return Err(invalid(format!("'{term}' is not field:value")));
+    };
+    let filter = match key.to_ascii_lowercase().as_str() {
+        "path" => Filter::Path(value.to_string()),
+        "tag" => Filter::Tag(value.trim_start_matches('#').to_string()),
+        "type" => Filter::DocType(value.to_ascii_lowercase()),
+        "created" => date_filter(DateField::Created, value)?,
+        "modified" => date_filter(DateField::Modified, value)?,
+        _ => Filter::Metadata {
+            key: key.to_string(),
+            value: value.to_string(),
+        },
+    };
+    filter.validate()?;
+    Ok(filter)
+}
+
+fn date_filter(field: DateField, value: &str) -> SearchResult<Filter> {
+    let day = |date: &str| parse_date(date).map(|d| d.to_string());
+    let shift = |date: &str, forward: bool| {
+        let date = parse_date(date)?;
+        let shifted = if forward {
+            date.checked_add_days(Days::new(1))
+        } else {
+            date.checked_sub_days(Days::new(1))
+        };
+        shifted
+            .map(|d| d.to_string())
+            .ok_or_else(|| invalid(format!("'{date}' is out of range")))
+    };
+    let (from, to) = if let Some(date) = value.strip_prefix(">=") {
+        (Some(day(date)?), None)
+    } else if let Some(date) = value.strip_prefix("<=") {
+        (None, Some(day(date)?))
+    } else if let Some(date) = value.strip_prefix('>') {
+        (Some(shift(date, true)?), None)
+    } else if let Some(date) = value.strip_prefix('<') {
+        (None, Some(shift(date, false)?))
+    } else if let Some((from, to)) = value.split_once("..") {
+        let end = |date: &str| (!date.is_empty()).then(|| day(date)).transpose();
+        (end(from)?, end(to)?)
+    } else {
+        let date = day(value)?;
+        (Some(date.clone()), Some(date))
+    };
+    Ok(Filter::DateRange { field, from, to })
+}
+
+/// What filters look at beyond the hit itself: document stats and,
+/// read on first use, frontmatter
+pub(super) struct FilterContext<'a> {
+    stats: &'a doc_meta::Stats,
+    contexts_root: PathBuf,
+    frontmatter: RefCell<HashMap<String, Map<String, Value>>>,
+}
+
+impl<'a> FilterContext<'a> {
+    pub fn new(stats: &'a doc_meta::Stats, contexts_root: PathBuf) -> Self {
+        Self {
+            stats,
+            contexts_root,
+            frontmatter: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Frontmatter field of `rel_path`
+    fn field(&self, rel_path: &str, key: &str) -> Option<Value> {
+        let mut cache = self.frontmatter.borrow_mut();
+        let fields = cache.entry(rel_path.to_string()).or_insert_with(|| {
+            std::fs::read_to_string(RelPath::new(rel_path).to_path(&self.contexts_root))
+                .map(|content| crate::frontmatter::fields(&content))
+                .unwrap_or_default()
+        });
+        fields.get(key).cloned()
+    }
+
+    /// Date of `field` for `hit` as `YYYY-MM-DD`
+    fn date(&self, hit: &SearchHit, field: DateField) -> Option<String> {
+        if field == DateField::Created {
+            if let Some(date) = hit.entry_date.as_deref() {
+                return Some(date.to_string());
+            }
+        }
+        let meta = self.stats.get(&hit.file_path)?;
+        let millis = match field {
+            DateField::Created => meta.created,
+            DateField::Modified => meta.modified,
+        }?;
+        let date = DateTime::from_timestamp_millis(millis as i64)?.date_naive();
+        Some(date.to_string())
+    }
+
+    /// Whether `hit` passes `filter`
+    pub fn matches(&self, filter: &Filter, hit: &SearchHit) -> bool {
+        match filter {
+            Filter::And(filters) => filters.iter().all(|f| self.matches(f, hit)),
+            Filter::Or(filters) => filters.iter().any(|f| self.matches(f, hit)),
+            Filter::Not(filter) => !self.matches(filter, hit),
+            Filter::Path(prefix) => relpath::is_within(&hit.file_path, prefix),
+            Filter::DocType(doc_type) => hit.doc_type.as_deref().unwrap_or("doc") == doc_type,
+            Filter::Source(source) => self
+                .stats
+                .get(&hit.file_path)
+                .and_then(|meta| meta.provenance.as_ref())
+                .is_some_and(|doc| {
+                    let chunk =
+                        provenance::for_chunk(doc, hit.heading_path.as_deref(), &hit.content);
+                    chunk.source == *source || chunk.origin.as_deref() == Some(source)
+                }),
+            Filter::Tag(tag) => {
+                extract_tags(&hit.content)
+                    .iter()
+                    .any(|t| tag_matches(t, tag))
+                    || self
+                        .field(&hit.file_path, "tags")
+                        .is_some_and(|tags| value_items(&tags).iter().any(|t| tag_matches(t, tag)))
+            }
+            Filter::DateRange { field, from, to } => self.date(hit, *field).is_some_and(|date| {
+                from.as_deref().is_none_or(|from| date.as_str() >= from)
+                    && to.as_deref().is_none_or(|to| date.as_str() <= to)
+            }),
+            Filter::Metadata { key, value } => {
+                self.field(&hit.file_path, key).is_some_and(|field| {
+                    value_items(&field)
+                        .iter()
+                        .any(|item| item.eq_ignore_ascii_case(value))
+                })
+            }
+        }
+    }
+}
+
+/// `tag` equals `wanted` (ignoring case) or is nested under it
+fn tag_matches(tag: &str, wanted: &str) -> bool {
+    let tag = tag.trim_start_matches('#');
+    tag.eq_ignore_ascii_case(wanted)
+        || (tag.len() > wanted.len()
+            && tag.as_bytes()[wanted.len()] == b'/'
+            && tag[..wanted.len()].eq_ignore_ascii_case(wanted))
+}
+
+/// A frontmatter value as strings: list items, a comma-separated string's
+/// parts, or the scalar itself
+fn value_items(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(value_items).collect(),
+        Value::String(s) => s
+            .split(',')
+            .map(|part| part.trim().to_string())
+            .filter(|part| !part.is_empty())
+            .collect(),
+        Value::Null => Vec::new(),
+        other => vec![other.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{DocMeta, MatchType, SearchOptions};
+
+    fn hit(path: &str, content: &str, entry_date: Option<&str>) -> SearchHit {
+        SearchHit {
+            content: content.to_string(),
+            doc_type: entry_date.map(|_| "idea".to_string()),
+            entry_date: entry_date.map(str::to_string),
+            ..SearchHit::fixture(path, MatchType::Hybrid)
+        }
+    }
+
+    #[test]
+    fn test_parse_query_string() {
+        assert_eq!(
+            Filter::parse("tag:rust path:projects/ created:>2024-01-01").unwrap(),
+            Filter::And(vec![
+                Filter::Tag("rust".to_string()),
+                Filter::Path("projects/".to_string()),
+                Filter::DateRange {
+                    field: DateField::Created,
+                    from: Some("2024-01-02".to_string()),
+                    to: None,
+                },
+            ])
+        );
+        assert_eq!(
+            Filter::parse("(type:idea OR tag:todo) -path:archive/ status:\"in progress\"").unwrap(),
+            Filter::And(vec![
+                Filter::Or(vec![
+                    Filter::DocType("idea".to_string()),
+                    Filter::Tag("todo".to_string()),
+                ]),
+                Filter::Not(Box::new(Filter::Path("archive/".to_string()))),
+                Filter::Metadata {
+                    key: "status".to_string(),
+                    value: "in progress".to_string(),
+                },
+            ])
+        );
+        assert_eq!(
+            Filter::parse("modified:2024-03-01..").unwrap(),
+            Filter::DateRange {
+                field: DateField::Modified,
+                from: Some("2024-03-01".to_string()),
+                to: None,
+            }
+        );
+        assert_eq!(
+            Filter::parse("tag:a tag:b OR tag:c").unwrap(),
+            Filter::And(vec![
+                Filter::Tag("a".to_string()),
+                Filter::Or(vec![
+                    Filter::Tag("b".to_string()),
+                    Filter::Tag("c".to_string())
+                ]),
+            ])
+        );
+        for bad in [
+            "",
+            "rust",
+            "tag:",
+            "type:note",
+            "created:2024-13-01",
+            "(tag:a",
+            "a:b)",
+            "tag:a OR",
+        ] {
+            assert!(Filter::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_matches_hits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("projects")).unwrap();
+        std::fs::write(
+            dir.path().join("projects/a.md"),
+            "---\ntags: [rust, cli]\nstatus: Done\n---\n# A\n",
+        )
+        .unwrap();
+        let stats = HashMap::from([(
+            "projects/a.md".to_string(),
+            DocMeta {
+                created: Some(1_709_251_200_000), // 2024-03-01
+                ..Default::default()
+            },
+        )]);
+        let ctx = FilterContext::new(&stats, dir.path().to_path_buf());
+
+        let doc = hit("projects/a.md", "Notes on #search/ranking", None);
+        let idea = hit(".ideas/2024/inbox.md", "Try #rust", Some("2023-12-24"));
+        let matches = |expr: &str, hit: &SearchHit| ctx.matches(&Filter::parse(expr).unwrap(), hit);
+
+        assert!(matches("tag:rust path:projects/ created:>2024-01-01", &doc));
+        assert!(matches("tag:search status:done", &doc));
+        assert!(!matches("tag:cli", &idea));
+        assert!(matches("tag:rust type:idea created:<2024-01-01", &idea));
+        assert!(matches("-path:projects/ OR status:done", &idea));
+        assert!(!matches("-(type:idea OR tag:cli)", &idea));
+        assert!(!matches("modified:>2020-01-01", &doc));
+    }
+
+    #[test]
+    fn test_source_filter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let clipped = DocMeta {
+            provenance: Some(super::provenance::detect(
+                "clips/recipe.md",
+                "---\nsource: evernote\nurl: https://example.com\n---\n",
+            )),
+            ..Default::default()
+        };
+        let stats = HashMap::from([("clips/recipe.md".to_string(), clipped)]);
+        let ctx = FilterContext::new(&stats, dir.path().to_path_buf());
+
+        let clip = hit("clips/recipe.md", "Bake at 200°C", None);
+        let source = |s: &str| Filter::Source(s.to_string());
+        assert!(ctx.matches(&source("evernote"), &clip));
+        assert!(ctx.matches(&source("web_clip"), &clip));
+        assert!(!ctx.matches(&source("markdown"), &clip));
+        // Documents indexed without provenance match no source
+        assert!(!ctx.matches(&source("markdown"), &hit("notes/a.md", "", None)));
+
+        let options = SearchOptions {
+            include_sources: Some(vec!["clips".into(), "notes".into()]),
+            exclude_sources: Some(vec!["web_clip".into()]),
+            ..Default::default()
+        };
+        let sources = options.source_filter().unwrap();
+        assert!(!ctx.matches(&sources, &clip));
+        assert!(ctx.matches(&sources, &hit("notes/a.md", "", None)));
+        assert!(!ctx.matches(&sources, &hit("other/a.md", "", None)));
+        assert!(SearchOptions::default().source_filter().is_none());
+    }
+}
@@ -0,0 +1,227 @@
+//! Folder summaries for folder-level results
+//!
+//! A folder result on its own only shows the best chunk that matched in the
+//! folder. With the `folder_summary` option each one also carries a
+//! [`FolderSummary`]. Its document list comes from the index metadata, so it
+//! covers the whole folder and not just the documents the query hit. Its
+//! headings are those of the matching chunks. With
+//! `search.folder_summary_llm` the `[llm]` model adds a sentence or two on
+//! what the folder holds. Replies are cached in `folder-summaries.json`
+//! under a key built from the folder's documents and their checksums, so a
+//! folder is described again only after one of its documents changes.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use super::types::{FolderSummary, SearchHit};
+
+pub(super) const SUMMARY_PROMPT: &str = "You describe a folder of a personal knowledge base \
+so the user can decide whether to open it. Given the folder's documents, some of their headings \
+and an excerpt, write one or two short sentences on what the folder holds. Answer with the \
+description only, in the language of the notes.";
+
+/// New LLM summaries per query; further folders go without until a later query
+pub(super) const MAX_NEW_PER_QUERY: usize = 3;
+
+/// Excerpt of the best chunk sent along for the LLM summary
+const EXCERPT_CHARS: usize = 1500;
+
+/// Folder of a workspace path, `.` for the root
+pub(super) fn folder_of(rel_path: &str) -> &str {
+    rel_path.rsplit_once('/').map_or(".", |(folder, _)| folder)
+}
+
+/// Documents directly in `folder`, sorted, from the index checksums
+pub(super) fn folder_docs<'a>(
+    checksums: &'a HashMap<String, String>,
+    folder: &str,
+) -> Vec<&'a str> {
+    let mut docs: Vec<&str> = checksums
+        .keys()
+        .map(String::as_str)
+        .filter(|p| folder_of(p) == folder)
+        .collect();
+    docs.sort_unstable();
+    docs
+}
+
+/// Record `hit`'s heading path in `headings` (distinct, best match first,
+/// at most [`FolderSummary::MAX_HEADINGS`])
+pub(super) fn add_heading(headings: &mut Vec<String>, hit: &SearchHit) {
+    let Some(heading) = hit.heading_path.as_deref().filter(|h| !h.is_empty()) else {
+        return;
+    };
+    if headings.len() < FolderSummary::MAX_HEADINGS && !headings.iter().any(|h| h == heading) {
+        headings.push(heading.to_string());
+    }
+}
+
+/// Summary of `folder` without the LLM description
+pub(super) fn outline(
+    checksums: &HashMap<String, String>,
+    folder: &str,
+    headings: Vec<String>,
+) -> FolderSummary {
+    let docs = folder_docs(checksums, folder);
+    FolderSummary {
+        doc_total: docs.len(),
+        docs: docs
+            .into_iter()
+            .take(FolderSummary::MAX_DOCS)
+            .map(str::to_string)
+            .collect(),
+        headings,
+        summary: None,
+    }
+}
+
+/// Cache key for `folder`'s LLM summary: changes whenever a document in the
+/// folder is added, removed or edited
+pub(super) fn cache_key(checksums: &HashMap<String, String>, folder: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(folder.as_bytes());
+    for doc in folder_docs(checksums, folder) {
+        hasher.update([0]);
+        hasher.update(doc.as_bytes());
+        hasher.update([0]);
+        hasher.update(checksums[doc].as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// User message for the summary prompt
+pub(super) fn prompt_input(folder: &str, outline: &FolderSummary, excerpt: &str) -> String {
+    let mut input = format!("Folder: {folder}\n\nDocuments:\n");
+    for doc in &outline.docs {
+        input.push_str(&format!("- {doc}\n"));
+    }
+    if outline.doc_total > outline.docs.len() {
+        input.push_str(&format!(
+            "- ... {} more\n",
+            outline.doc_total - outline.docs.len()
+        ));
+    }
+    if !outline.headings.is_empty() {
+        input.push_str("\nHeadings:\n");
+        for heading in &outline.headings {
+            input.push_str(&format!("- {heading}\n"));
+        }
+    }
+    let excerpt: String = excerpt.chars().take(EXCERPT_CHARS).collect();
+    input.push_str(&format!("\nExcerpt:\n{excerpt}\n"));
+    input
+}
+
+/// LLM folder descriptions on disk, keyed by [`cache_key`]
+pub(super) struct SummaryCache {
+    path: PathBuf,
+    entries: HashMap<String, String>,
+    dirty: bool,
+}
+
+impl SummaryCache {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries,
+            dirty: false,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, summary: String) {
+        self.entries.insert(key, summary);
+        self.dirty = true;
+    }
+
+    /// Write the cache if anything was added; failures are logged, not returned
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let written = serde_json::to_string(&self.entries)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                if let Some(parent) = self.path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&self.path, json).map_err(|e| e.to_string())
+            });
+        match written {
+            Ok(()) => self.dirty = false,
+            Err(e) => log::warn!("[Searcher] Could not save folder summaries: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::MatchType;
+
+    fn checksums(paths: &[(&str, &str)]) -> HashMap<String, String> {
+        paths
+            .iter()
+            .map(|(p, c)| (p.to_string(), c.to_string()))
+            .collect()
+    }
+
+    fn hit(heading: Option<&str>) -> SearchHit {
+        SearchHit {
+            heading_path: heading.map(str::to_string),
+            ..SearchHit::fixture("plans/q3.md", MatchType::Hybrid)
+        }
+    }
+
+    #[test]
+    fn test_outline_lists_direct_docs_and_matched_headings() {
+        let index = checksums(&[
+            ("plans/q4.md", "1"),
+            ("plans/q3.md", "2"),
+            ("plans/old/q1.md", "3"),
+            ("readme.md", "4"),
+        ]);
+        let mut headings = Vec::new();
+        for heading in [Some("Goals"), None, Some("Goals"), Some("Goals > Hiring")] {
+            add_heading(&mut headings, &hit(heading));
+        }
+        let summary = outline(&index, "plans", headings);
+        assert_eq!(summary.docs, vec!["plans/q3.md", "plans/q4.md"]);
+        assert_eq!(summary.doc_total, 2);
+        assert_eq!(summary.headings, vec!["Goals", "Goals > Hiring"]);
+        assert_eq!(outline(&index, ".", vec![]).docs, vec!["readme.md"]);
+
+        let input = prompt_input("plans", &summary, "Ship search in Q3.");
+        assert!(input.contains("- plans/q4.md\n"));
+        assert!(input.contains("- Goals > Hiring\n"));
+    }
+
+    #[test]
+    fn test_cache_key_tracks_folder_contents() {
+        let before = checksums(&[("plans/q3.md", "1"), ("notes/a.md", "2")]);
+        let other_folder_changed = checksums(&[("plans/q3.md", "1"), ("notes/a.md", "3")]);
+        let edited = checksums(&[("plans/q3.md", "9"), ("notes/a.md", "2")]);
+        let key = cache_key(&before, "plans");
+        assert_eq!(key, cache_key(&other_folder_changed, "plans"));
+        assert_ne!(key, cache_key(&edited, "plans"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache/folder-summaries.json");
+        let mut cache = SummaryCache::load(path.clone());
+        cache.insert(key.clone(), "Quarterly plans.".to_string());
+        cache.save();
+        assert_eq!(
+            SummaryCache::load(path).get(&key).map(String::as_str),
+            Some("Quarterly plans.")
+        );
+    }
+}
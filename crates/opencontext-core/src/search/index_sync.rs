@@ -16,7 +16,9 @@ use tokio::time::{interval_at, Instant};
 use super::config::SearchConfig;
 use super::error::SearchResult;
 use super::indexer::Indexer;
-use crate::events::{DocEvent, Event, FolderEvent, SharedEventBus};
+use crate::events::{DocEvent, Event, FolderEvent, HealthEvent, SharedEventBus};
+use crate::manifest::FileChange;
+use crate::SymlinkPolicy;
 
 /// Update action for the index
 #[derive(Debug, Clone)]
@@ -29,6 +31,18 @@ enum IndexAction {
     Rename { old_path: String, new_path: String },
 }
 
+impl IndexAction {
+    fn to_wal_op(&self) -> crate::wal::WalOp {
+        match self.clone() {
+            IndexAction::Update { rel_path } => crate::wal::WalOp::Update { rel_path },
+            IndexAction::Remove { rel_path } => crate::wal::WalOp::Remove { rel_path },
+            IndexAction::Rename { old_path, new_path } => {
+                crate::wal::WalOp::Rename { old_path, new_path }
+            }
+        }
+    }
+}
+
 /// Index synchronization service
 ///
 /// Collects file change events and processes them in batches at regular intervals.
@@ -37,6 +51,8 @@ pub struct IndexSyncService {
     contexts_root: PathBuf,
     /// SQLite db path for WAL replay on startup
     db_path: Option<PathBuf>,
+    /// How the startup scan treats symlinks
+    symlinks: SymlinkPolicy,
     indexer: Arc<Mutex<Option<Indexer>>>,
     enabled: Arc<std::sync::atomic::AtomicBool>,
     /// Pending actions waiting to be processed
@@ -60,6 +76,7 @@ impl IndexSyncService {
             config,
             contexts_root,
             db_path: None,
+            symlinks: SymlinkPolicy::default(),
             indexer: Arc::new(Mutex::new(None)),
             enabled: Arc::new(std::sync::atomic::AtomicBool::new(true)),
             pending_actions: Arc::new(Mutex::new(HashMap::new())),
@@ -70,7 +87,9 @@ impl IndexSyncService {
         }
     }
 
-    /// Signal the service to stop and flush pending actions.
+    /// Signal the service to stop. Pending actions are applied before
+    /// [`start`](Self::start) returns; any that cannot be are written back to
+    /// the WAL for the next run to replay.
     pub fn stop(&self) {
         let _ = self.shutdown_tx.send(true);
     }
@@ -87,6 +106,13 @@ impl IndexSyncService {
         self
     }
 
+    /// Set how the startup scan treats symlinks; should match the workspace's
+    /// [`OpenContext::symlink_policy`](crate::OpenContext::symlink_policy)
+    pub fn with_symlink_policy(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
     /// Set external flush notify — when notified, interval loop runs immediately
     pub fn with_flush_notify(mut self, notify: Arc<Notify>) -> Self {
         self.flush_notify = Some(notify);
@@ -124,9 +150,11 @@ impl IndexSyncService {
             }
         }
 
-        // Replay any pending WAL entries from previous run
+        // Replay any pending WAL entries from previous run, then pick up
+        // file changes nobody emitted events for
         if let Some(ref db_path) = self.db_path {
             self.replay_wal(db_path.clone()).await;
+            self.replay_missed_changes(db_path.clone()).await;
         }
 
         // Spawn interval processor (every N seconds)
@@ -168,6 +196,7 @@ impl IndexSyncService {
                                 continue;
                             }
 
+                            let urgent = matches!(event, Event::Doc(DocEvent::Captured { .. }));
                             let actions = Self::event_to_actions(event);
                             let mut pending_guard = self.pending_actions.lock().await;
                             for action in actions {
@@ -189,9 +218,24 @@ impl IndexSyncService {
                             if count > 0 {
                                 log::debug!("[IndexSync] {} pending updates", count);
                             }
+                            // Captures should be searchable within seconds
+                            if urgent {
+                                if let Some(ref notify) = self.flush_notify {
+                                    notify.notify_one();
+                                }
+                            }
                         }
                         Err(broadcast::error::RecvError::Lagged(n)) => {
                             log::warn!("[IndexSync] Lagged behind by {} events", n);
+                            event_bus.emit_health(HealthEvent::WatcherOverflow {
+                                subscriber: "index_sync".to_string(),
+                                missed: n,
+                            });
+                            // The dropped events are gone; diff against the
+                            // manifest instead to find what they touched
+                            if let Some(ref db_path) = self.db_path {
+                                self.replay_missed_changes(db_path.clone()).await;
+                            }
                         }
                         Err(broadcast::error::RecvError::Closed) => {
                             log::info!("[IndexSync] Event bus closed, stopping sync service");
@@ -202,7 +246,12 @@ impl IndexSyncService {
                 _ = shutdown_rx.changed() => {
                     if *shutdown_rx.borrow() {
                         log::info!("[IndexSync] Shutdown signal received, flushing pending actions");
-                        Self::flush_pending(self.pending_actions.clone(), self.indexer.clone()).await;
+                        Self::flush_pending(
+                            self.pending_actions.clone(),
+                            self.indexer.clone(),
+                            self.db_path.clone(),
+                        )
+                        .await;
                         break;
                     }
                 }
@@ -216,15 +265,24 @@ impl IndexSyncService {
     fn event_to_actions(event: Event) -> Vec<IndexAction> {
         match event {
             Event::Doc(doc_event) => match doc_event {
-                DocEvent::Created { rel_path } | DocEvent::Updated { rel_path } => {
-                    vec![IndexAction::Update { rel_path }]
+                DocEvent::Created { rel_path }
+                | DocEvent::Updated { rel_path }
+                | DocEvent::Captured { rel_path, .. } => {
+                    vec![IndexAction::Update {
+                        rel_path: rel_path.into_string(),
+                    }]
                 }
                 DocEvent::Deleted { rel_path } => {
-                    vec![IndexAction::Remove { rel_path }]
+                    vec![IndexAction::Remove {
+                        rel_path: rel_path.into_string(),
+                    }]
                 }
                 DocEvent::Renamed { old_path, new_path }
                 | DocEvent::Moved { old_path, new_path } => {
-                    vec![IndexAction::Rename { old_path, new_path }]
+                    vec![IndexAction::Rename {
+                        old_path: old_path.into_string(),
+                        new_path: new_path.into_string(),
+                    }]
                 }
             },
             Event::Folder(folder_event) => match folder_event {
@@ -232,20 +290,29 @@ impl IndexSyncService {
                 FolderEvent::Renamed { affected_docs, .. }
                 | FolderEvent::Moved { affected_docs, .. } => affected_docs
                     .into_iter()
-                    .map(|(old_path, new_path)| IndexAction::Rename { old_path, new_path })
+                    .map(|(old_path, new_path)| IndexAction::Rename {
+                        old_path: old_path.into_string(),
+                        new_path: new_path.into_string(),
+                    })
                     .collect(),
                 FolderEvent::Deleted { removed_docs, .. } => removed_docs
                     .into_iter()
-                    .map(|rel_path| IndexAction::Remove { rel_path })
+                    .map(|rel_path| IndexAction::Remove {
+                        rel_path: rel_path.into_string(),
+                    })
                     .collect(),
             },
+            Event::Health(_) => vec![],
         }
     }
 
-    /// Flush all pending actions immediately (called on shutdown).
+    /// Flush all pending actions immediately (called on shutdown). Actions
+    /// that fail, or that have no index to go to yet, are journaled back to
+    /// the WAL so nothing is lost with the process.
     async fn flush_pending(
         pending: Arc<Mutex<HashMap<String, IndexAction>>>,
         indexer: Arc<Mutex<Option<Indexer>>>,
+        db_path: Option<PathBuf>,
     ) {
         let actions: Vec<IndexAction> = {
             let mut guard = pending.lock().await;
@@ -259,10 +326,59 @@ impl IndexSyncService {
             actions.len()
         );
         let mut guard = indexer.lock().await;
-        if let Some(ref mut idx) = *guard {
-            if idx.index_exists().await {
-                Self::apply_actions(idx, actions).await;
+        let leftover = match guard.as_mut() {
+            Some(idx) if idx.index_exists().await => {
+                let touched = Self::touched_paths(&actions);
+                let failed = Self::apply_actions(idx, actions).await;
+                let failed_paths = Self::touched_paths(&failed);
+                let applied: Vec<String> = touched
+                    .into_iter()
+                    .filter(|p| !failed_paths.contains(p))
+                    .collect();
+                if let Some(ref path) = db_path {
+                    Self::refresh_manifest(path, idx.contexts_root(), &applied);
+                }
+                failed
             }
+            _ => actions,
+        };
+        drop(guard);
+
+        if leftover.is_empty() {
+            return;
+        }
+        let Some(path) = db_path else {
+            log::warn!(
+                "[IndexSync] Dropping {} unapplied actions: no WAL configured",
+                leftover.len()
+            );
+            return;
+        };
+        let journaled = rusqlite::Connection::open(&path)
+            .map_err(crate::CoreError::from)
+            .and_then(|conn| {
+                let ts = crate::now_iso();
+                for action in &leftover {
+                    crate::wal::append(&conn, &action.to_wal_op(), &ts)?;
+                }
+                Ok(())
+            });
+        match journaled {
+            Ok(()) => log::info!(
+                "[IndexSync] Journaled {} unapplied actions for the next run",
+                leftover.len()
+            ),
+            Err(e) => log::warn!("[IndexSync] Failed to journal pending actions: {}", e),
+        }
+    }
+
+    /// Record that the index now reflects `rel_paths`
+    fn refresh_manifest(db_path: &PathBuf, contexts_root: &std::path::Path, rel_paths: &[String]) {
+        let refreshed = rusqlite::Connection::open(db_path)
+            .map_err(crate::CoreError::from)
+            .and_then(|conn| crate::manifest::refresh(&conn, contexts_root, rel_paths));
+        if let Err(e) = refreshed {
+            log::warn!("[IndexSync] Manifest refresh failed: {}", e);
         }
     }
 
@@ -365,7 +481,11 @@ impl IndexSyncService {
                     log::debug!("[IndexSync] Index not built, skipping updates");
                     continue;
                 }
+                let touched = Self::touched_paths(&actions);
                 Self::apply_actions(idx, actions).await;
+                if let Some(ref path) = db_path {
+                    Self::refresh_manifest(path, idx.contexts_root(), &touched);
+                }
             }
             drop(indexer_guard);
 
@@ -445,13 +565,80 @@ impl IndexSyncService {
         }
     }
 
-    /// Apply a batch of index actions. Shared by the interval loop and flush_pending.
-    async fn apply_actions(indexer: &mut Indexer, actions: Vec<IndexAction>) {
+    /// Compare the file manifest with the filesystem and queue the changes
+    /// made while no sync service was running (or while it lagged behind
+    /// the bus), as the events they would have produced
+    async fn replay_missed_changes(&self, db_path: PathBuf) {
+        let changes = match rusqlite::Connection::open(&db_path)
+            .map_err(crate::CoreError::from)
+            .and_then(|conn| crate::manifest::reconcile(&conn, &self.contexts_root, self.symlinks))
+        {
+            Ok(changes) => changes,
+            Err(e) => {
+                log::warn!("[IndexSync] Scan for missed changes failed: {}", e);
+                return;
+            }
+        };
+        if changes.is_empty() {
+            return;
+        }
+        log::info!("[IndexSync] Scan found {} missed change(s)", changes.len());
+
+        let mut guard = self.pending_actions.lock().await;
+        for change in changes {
+            let event = match change {
+                FileChange::Created(rel_path) => DocEvent::Created {
+                    rel_path: rel_path.into(),
+                },
+                FileChange::Updated(rel_path) => DocEvent::Updated {
+                    rel_path: rel_path.into(),
+                },
+                FileChange::Deleted(rel_path) => DocEvent::Deleted {
+                    rel_path: rel_path.into(),
+                },
+                FileChange::Renamed { old_path, new_path } => DocEvent::Renamed {
+                    old_path: old_path.into(),
+                    new_path: new_path.into(),
+                },
+            };
+            for action in Self::event_to_actions(Event::Doc(event)) {
+                match &action {
+                    IndexAction::Update { rel_path } | IndexAction::Remove { rel_path } => {
+                        guard.insert(rel_path.clone(), action);
+                    }
+                    IndexAction::Rename { old_path, new_path } => {
+                        guard.remove(old_path);
+                        guard.insert(new_path.clone(), action);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Paths whose manifest entries an action makes stale
+    fn touched_paths(actions: &[IndexAction]) -> Vec<String> {
+        actions
+            .iter()
+            .flat_map(|action| match action {
+                IndexAction::Update { rel_path } | IndexAction::Remove { rel_path } => {
+                    vec![rel_path.clone()]
+                }
+                IndexAction::Rename { old_path, new_path } => {
+                    vec![old_path.clone(), new_path.clone()]
+                }
+            })
+            .collect()
+    }
+
+    /// Apply a batch of index actions and return the ones that failed.
+    /// Shared by the interval loop and flush_pending.
+    async fn apply_actions(indexer: &mut Indexer, actions: Vec<IndexAction>) -> Vec<IndexAction> {
+        let mut failed = Vec::new();
         let mut success_count = 0;
         let mut error_count = 0;
 
         for action in actions {
-            let result = match action {
+            let result = match action.clone() {
                 IndexAction::Update { rel_path } => {
                     indexer.index_file(&rel_path).await.map(|count| {
                         log::debug!("[IndexSync] Updated: {} ({} chunks)", rel_path, count);
@@ -475,6 +662,7 @@ impl IndexSyncService {
                 Err(e) => {
                     log::warn!("[IndexSync] Error: {}", e);
                     error_count += 1;
+                    failed.push(action);
                 }
             }
         }
@@ -490,5 +678,6 @@ impl IndexSyncService {
             success_count,
             error_count
         );
+        failed
     }
 }
@@ -1,85 +1,37 @@
 //! Document indexer
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use sha2::{Digest, Sha256};
+use tokio_util::sync::CancellationToken;
 
 use super::bm25_store::Bm25Store;
+use super::boilerplate;
 use super::chunker::Chunker;
 use super::config::SearchConfig;
+use super::contextual::ContextualEnricher;
+use super::doc_meta;
 use super::embedding::EmbeddingClient;
 use super::error::{SearchError, SearchResult};
-use super::types::Chunk;
+use super::late_interaction;
+use super::llm::LlmClient;
+use super::matryoshka;
+use super::memory::Limits;
+use super::pipeline;
+use super::sparse_embedding::SparseEmbeddingClient;
+use super::sparse_store::SparseStore;
+use super::spelling::SpellIndex;
+use super::tokenizer;
+use super::types::{Chunk, ChunkWindow, DocMeta};
 use super::vector_store::VectorStore;
-
-#[derive(Clone)]
-struct IdeaEntry {
-    id: String,
-    created_at: String,
-    content: String,
-}
+use crate::embeds::Transcluder;
+use crate::RelPath;
 
 const DEFAULT_IDEA_BOX: &str = "inbox";
 
-fn parse_idea_marker(line: &str) -> Option<(String, String)> {
-    let trimmed = line.trim();
-    if !trimmed.starts_with("[//]: # (") || !trimmed.ends_with(')') {
-        return None;
-    }
-    let inner = trimmed.strip_prefix("[//]: # (")?.strip_suffix(')')?;
-    let mut id = None;
-    let mut created_at = None;
-    for part in inner.split_whitespace() {
-        if let Some(value) = part.strip_prefix("idea:id=") {
-            id = Some(value.to_string());
-        } else if let Some(value) = part.strip_prefix("created_at=") {
-            created_at = Some(value.to_string());
-        }
-    }
-    match (id, created_at) {
-        (Some(id), Some(created_at)) => Some((id, created_at)),
-        _ => None,
-    }
-}
-
-fn parse_idea_entries(content: &str) -> Vec<IdeaEntry> {
-    let mut entries = Vec::new();
-    let mut current_id: Option<String> = None;
-    let mut current_created: Option<String> = None;
-    let mut buffer: Vec<String> = Vec::new();
-
-    for line in content.lines() {
-        if let Some((id, created_at)) = parse_idea_marker(line) {
-            if let (Some(id), Some(created_at)) = (current_id.take(), current_created.take()) {
-                entries.push(IdeaEntry {
-                    id,
-                    created_at,
-                    content: buffer.join("\n").trim().to_string(),
-                });
-            }
-            buffer.clear();
-            current_id = Some(id);
-            current_created = Some(created_at);
-            continue;
-        }
-        if current_id.is_some() {
-            buffer.push(line.to_string());
-        }
-    }
-
-    if let (Some(id), Some(created_at)) = (current_id.take(), current_created.take()) {
-        entries.push(IdeaEntry {
-            id,
-            created_at,
-            content: buffer.join("\n").trim().to_string(),
-        });
-    }
-
-    entries
-}
-
-fn extract_idea_box(rel_path: &str) -> Option<String> {
+pub(super) fn extract_idea_box(rel_path: &str) -> Option<String> {
     let normalized = rel_path.trim_start_matches("./");
     if !normalized.starts_with(".ideas/") {
         return None;
@@ -109,6 +61,42 @@ pub struct IndexChanges {
     pub unchanged: usize,
 }
 
+/// Load doc checksums from metadata file
+pub(super) fn load_checksums(config: &SearchConfig) -> HashMap<String, String> {
+    let path = config.paths.get_index_metadata_path();
+    if !path.exists() {
+        return HashMap::new();
+    }
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("checksums").cloned())
+        .and_then(|v| serde_json::from_value::<HashMap<String, String>>(v).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the index on disk was embedded with different text-shaping
+/// settings (heading context, contextual retrieval, Chinese normalization),
+/// fast-tier model or prefix dimensions than the current config
+pub(super) fn embedding_settings_changed(config: &SearchConfig) -> bool {
+    let metadata = std::fs::read_to_string(config.paths.get_index_metadata_path())
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .unwrap_or_default();
+    let built_with = |key: &str| metadata.get(key).and_then(|v| v.as_bool()) == Some(true);
+    let tiers = &config.embedding.tiers;
+    let fast_model = tiers.enabled.then_some(tiers.model.as_str());
+    let prefix_dimensions = config
+        .vector_index
+        .prefix_dimensions(config.embedding.dimensions);
+    built_with("includeHeadingContext") != config.embedding.include_heading_context
+        || built_with("contextualRetrieval") != config.contextual.enabled
+        || built_with("chineseNormalization") != config.tokenizer.chinese_normalization
+        || metadata.get("fastTierModel").and_then(|v| v.as_str()) != fast_model
+        || metadata.get("searchDimensions").and_then(|v| v.as_u64())
+            != prefix_dimensions.map(|d| d as u64)
+}
+
 /// Index build statistics
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -128,6 +116,9 @@ pub struct IndexStats {
     /// Per-file change counts (populated for incremental builds)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub changes: Option<IndexChanges>,
+    /// The build was cancelled; what was written is checkpointed and the
+    /// next build picks up the rest incrementally
+    pub interrupted: bool,
 }
 
 /// Index build progress
@@ -146,16 +137,53 @@ pub struct IndexProgress {
     pub message: Option<String>,
 }
 
+/// An embedding request running alongside chunking and writing
+type EmbedTask = tokio::task::JoinHandle<SearchResult<Vec<Vec<f32>>>>;
+
+/// Chunks of consecutive documents embedded and written together
+#[derive(Default)]
+struct Batch {
+    chunks: Vec<Chunk>,
+    /// Every document in the batch, including those without chunks
+    paths: Vec<String>,
+    /// Body checksums, recorded once the batch is written
+    checksums: Vec<(String, String)>,
+    /// Document stats, recorded along with the checksums
+    metas: Vec<(String, DocMeta)>,
+    /// Stored vectors kept for chunks whose content did not change
+    reused: Reused,
+}
+
+/// Embeddings already in the index for unchanged chunks, by chunk id
+#[derive(Default)]
+struct Reused {
+    vectors: HashMap<String, Vec<f32>>,
+    windows: HashMap<String, Vec<Vec<f32>>>,
+}
+
 /// Document indexer for building search index
 pub struct Indexer {
     config: SearchConfig,
     contexts_root: PathBuf,
     vector_store: VectorStore,
     bm25_store: Bm25Store,
-    embedding_client: EmbeddingClient,
+    /// Shared with the embedding tasks of a running build
+    embedding_client: Arc<EmbeddingClient>,
     chunker: Chunker,
+    /// Sparse embedding client and store, present when `[sparse]` is enabled
+    sparse: Option<(SparseEmbeddingClient, SparseStore)>,
+    /// Fast-tier embedding client and store, present when `[embedding.tiers]` is enabled
+    fast: Option<(EmbeddingClient, VectorStore)>,
+    /// Truncated vectors, present when `[vector_index] search_dimensions` is set
+    prefix: Option<VectorStore>,
+    /// Contextual retrieval enricher, present when `[contextual]` is enabled
+    contextual: Option<ContextualEnricher>,
     /// Whether vector_store has been re-initialized with actual dimensions
     dimensions_verified: bool,
+    /// Stops a running build at the next batch boundary
+    cancel: CancellationToken,
+    /// Stats of documents written since the metadata was last saved
+    doc_meta: HashMap<String, DocMeta>,
 }
 
 impl Indexer {
@@ -165,15 +193,63 @@ impl Indexer {
         let bm25_path = config.paths.get_bm25_path();
         let dimensions = config.embedding.dimensions;
 
-        let mut vector_store = VectorStore::new(lancedb_path, dimensions);
+        let mut vector_store =
+            VectorStore::new(lancedb_path, dimensions).with_collection(config.paths.collection());
         vector_store.initialize().await?;
 
-        let bm25_store = Bm25Store::open(bm25_path)?;
+        let bm25_store = Bm25Store::open_with(bm25_path, &config.tokenizer)?
+            .with_writer_heap(Limits::new(&config.memory).writer_heap());
 
-        let embedding_client = EmbeddingClient::new(config.embedding.clone())?;
+        let embedding_client = Arc::new(EmbeddingClient::new(config.embedding.clone())?);
 
         let chunker = Chunker::new(config.search.chunk_size, config.search.chunk_overlap);
 
+        let sparse = if config.sparse.enabled {
+            Some((
+                SparseEmbeddingClient::new(config.sparse.clone())?,
+                SparseStore::open(config.paths.get_sparse_path())?,
+            ))
+        } else {
+            None
+        };
+
+        let fast = if config.embedding.tiers.enabled {
+            let mut store = VectorStore::new(
+                config.paths.get_fast_lancedb_path(),
+                config.embedding.tiers.dimensions,
+            )
+            .with_collection(config.paths.collection());
+            store.initialize().await?;
+            Some((EmbeddingClient::new(config.embedding.fast_tier())?, store))
+        } else {
+            None
+        };
+
+        let prefix = match config
+            .vector_index
+            .prefix_dimensions(config.embedding.dimensions)
+        {
+            Some(dimensions) => {
+                let mut store =
+                    VectorStore::new(config.paths.get_prefix_lancedb_path(), dimensions)
+                        .with_collection(config.paths.collection());
+                store.initialize().await?;
+                Some(store)
+            }
+            None => None,
+        };
+
+        let contextual = if config.contextual.enabled {
+            let llm = LlmClient::new(&config.llm, &config.embedding)?;
+            Some(ContextualEnricher::new(
+                llm,
+                config.contextual.clone(),
+                config.paths.get_contextual_cache_path(),
+            ))
+        } else {
+            None
+        };
+
         Ok(Self {
             config,
             contexts_root,
@@ -181,10 +257,31 @@ impl Indexer {
             bm25_store,
             embedding_client,
             chunker,
+            sparse,
+            fast,
+            prefix,
+            contextual,
             dimensions_verified: false,
+            cancel: CancellationToken::new(),
+            doc_meta: HashMap::new(),
         })
     }
 
+    pub fn contexts_root(&self) -> &Path {
+        &self.contexts_root
+    }
+
+    fn abs_path(&self, rel_path: &str) -> PathBuf {
+        RelPath::new(rel_path).to_path(&self.contexts_root)
+    }
+
+    /// Token that cancels the next builds. A cancelled build stops taking
+    /// new documents, writes the batches already being embedded, saves
+    /// checksums for what it wrote and returns with `interrupted` set.
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
     /// Verify and update vector store dimensions based on actual embedding dimensions
     async fn verify_dimensions(&mut self) -> SearchResult<()> {
         if self.dimensions_verified {
@@ -200,7 +297,8 @@ impl Indexer {
             );
 
             let lancedb_path = self.config.paths.get_lancedb_path();
-            self.vector_store = VectorStore::new(lancedb_path, actual_dim);
+            self.vector_store = VectorStore::new(lancedb_path, actual_dim)
+                .with_collection(self.config.paths.collection());
             self.vector_store.initialize().await?;
         }
 
@@ -208,6 +306,286 @@ impl Indexer {
         Ok(())
     }
 
+    /// Text sent to the embedding model for a chunk. With
+    /// `include_heading_context`, the document title and heading path are
+    /// prepended so the vector reflects where the chunk sits in the document.
+    fn embedding_text(&self, chunk: &Chunk) -> String {
+        tokenizer::normalize_for_embedding(&self.shaped_text(chunk), &self.config.tokenizer)
+    }
+
+    fn shaped_text(&self, chunk: &Chunk) -> String {
+        if !self.config.embedding.include_heading_context {
+            return chunk.content.clone();
+        }
+        let title = crate::relpath::display_name(&chunk.file_path);
+        if chunk.heading_path.is_empty() {
+            format!("{}\n\n{}", title, chunk.content)
+        } else {
+            format!("{} > {}\n\n{}", title, chunk.heading_path, chunk.content)
+        }
+    }
+
+    /// Texts to embed for a batch of chunks, with contextual-retrieval
+    /// context prepended when enabled
+    async fn embedding_texts(&mut self, chunks: &[&Chunk]) -> Vec<String> {
+        let mut texts: Vec<String> = chunks.iter().map(|c| self.embedding_text(c)).collect();
+
+        if let Some(enricher) = self.contextual.as_mut() {
+            let mut documents: HashMap<String, String> = HashMap::new();
+            for (chunk, text) in chunks.iter().zip(texts.iter_mut()) {
+                let document = documents.entry(chunk.file_path.clone()).or_insert_with(|| {
+                    std::fs::read_to_string(
+                        RelPath::new(&chunk.file_path).to_path(&self.contexts_root),
+                    )
+                    .unwrap_or_default()
+                });
+                if let Some(context) = enricher.context_for(document, chunk).await {
+                    *text = format!("{}\n\n{}", context, text);
+                }
+            }
+            enricher.save();
+        }
+
+        texts
+    }
+
+    fn embedding_settings_changed(&self) -> bool {
+        embedding_settings_changed(&self.config)
+    }
+
+    /// Recompute boilerplate scores over the whole keyword corpus. Repetition
+    /// is a cross-document property, so one changed file can change the
+    /// scores of chunks in every other file.
+    fn refresh_boilerplate(&self) {
+        let chunks = match self.bm25_store.all_contents() {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                log::warn!("[Indexer] Boilerplate scan failed: {}", e);
+                return;
+            }
+        };
+        let scores = boilerplate::scores(
+            chunks
+                .iter()
+                .map(|(id, file, content)| (id.as_str(), file.as_str(), content.as_str())),
+        );
+        if let Err(e) = boilerplate::save(&self.config.paths.get_boilerplate_path(), &scores) {
+            log::warn!("[Indexer] Failed to save boilerplate scores: {}", e);
+        }
+    }
+
+    /// Rebuild the spelling index from the keyword vocabulary and save it
+    /// for searchers to map, so none of them has to build it on startup
+    fn refresh_spelling(&self) {
+        let built = self.bm25_store.opstamp().and_then(|opstamp| {
+            let index = SpellIndex::build(self.bm25_store.vocabulary()?);
+            Ok((opstamp, index))
+        });
+        let saved = built.and_then(|(opstamp, index)| {
+            Ok(index.save(&self.config.paths.get_spelling_path(), opstamp)?)
+        });
+        if let Err(e) = saved {
+            log::warn!("[Indexer] Failed to save the spelling index: {}", e);
+        }
+    }
+
+    /// Look up the stored embeddings of `chunks`. Chunk ids are
+    /// content-addressed, so a stored vector under the same id was computed
+    /// from the same text. Must run before the chunks' file is deleted from
+    /// the index; a failed lookup just means everything is re-embedded.
+    async fn stored_vectors(&self, chunks: &[Chunk]) -> Reused {
+        let ids: Vec<String> = chunks.iter().map(|c| c.id.clone()).collect();
+        let vectors = match self.vector_store.get_vectors(&ids).await {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                log::warn!("[Indexer] Could not load stored vectors: {}", e);
+                return Reused::default();
+            }
+        };
+        let windows = if self.config.multi_vector.enabled && !vectors.is_empty() {
+            self.vector_store
+                .get_windows(&ids)
+                .await
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        if !vectors.is_empty() {
+            log::debug!(
+                "[Indexer] Reusing {}/{} stored chunk vectors",
+                vectors.len(),
+                ids.len()
+            );
+        }
+        Reused { vectors, windows }
+    }
+
+    /// Chunks that still need an embedding
+    fn unembedded<'a>(chunks: &'a [Chunk], reused: &Reused) -> Vec<&'a Chunk> {
+        chunks
+            .iter()
+            .filter(|c| !reused.vectors.contains_key(&c.id))
+            .collect()
+    }
+
+    /// Attach vectors: reused ones by id, fresh `embeddings` in order to the rest
+    fn attach_vectors(chunks: &mut [Chunk], reused: &mut Reused, embeddings: Vec<Vec<f32>>) {
+        let mut embeddings = embeddings.into_iter();
+        for chunk in chunks {
+            chunk.vector = match reused.vectors.remove(&chunk.id) {
+                Some(vector) => vector,
+                None => embeddings.next().unwrap_or_default(),
+            };
+        }
+    }
+
+    /// Embed and store per-window vectors when multi-vector mode is enabled.
+    /// Windows of reused chunks are copied over when the window layout still
+    /// matches.
+    async fn store_windows(
+        &mut self,
+        chunks: &[Chunk],
+        reused: &mut HashMap<String, Vec<Vec<f32>>>,
+    ) -> SearchResult<usize> {
+        let mv = &self.config.multi_vector;
+        if !mv.enabled {
+            return Ok(0);
+        }
+
+        let mut windows = Vec::new();
+        let mut texts = Vec::new();
+        for chunk in chunks {
+            let chunk_windows =
+                late_interaction::windows(&chunk.content, mv.window_chars, mv.stride_chars);
+            let stored = reused
+                .remove(&chunk.id)
+                .filter(|stored| stored.len() == chunk_windows.len());
+            for (window_index, text) in chunk_windows.into_iter().enumerate() {
+                let vector = match &stored {
+                    Some(stored) => stored[window_index].clone(),
+                    None => {
+                        texts.push(text);
+                        vec![]
+                    }
+                };
+                windows.push(ChunkWindow {
+                    chunk_id: chunk.id.clone(),
+                    file_path: chunk.file_path.clone(),
+                    window_index,
+                    vector,
+                });
+            }
+        }
+        if windows.is_empty() {
+            return Ok(0);
+        }
+
+        if !texts.is_empty() {
+            let mut embeddings = self.embedding_client.embed(texts).await?.into_iter();
+            for window in windows.iter_mut().filter(|w| w.vector.is_empty()) {
+                window.vector = embeddings.next().unwrap_or_default();
+            }
+        }
+        self.vector_store.upsert_windows(windows).await
+    }
+
+    /// Compute and store sparse vectors when `[sparse]` is enabled
+    async fn store_sparse(&self, chunks: &[Chunk]) -> SearchResult<usize> {
+        let Some((client, store)) = self.sparse.as_ref() else {
+            return Ok(0);
+        };
+        let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+        let vectors = client.embed(texts).await?;
+        store.upsert(chunks, &vectors)
+    }
+
+    /// Embed chunks with the fast-tier model and store them in the fast index
+    /// when `[embedding.tiers]` is enabled. Chunks are always re-embedded:
+    /// the small model is cheap and its texts skip contextual enrichment.
+    async fn store_fast(&mut self, chunks: &[Chunk]) -> SearchResult<usize> {
+        if self.fast.is_none() || chunks.is_empty() {
+            return Ok(0);
+        }
+        let texts: Vec<String> = chunks.iter().map(|c| self.embedding_text(c)).collect();
+        let path = self.config.paths.get_fast_lancedb_path();
+        let collection = self.config.paths.collection().to_string();
+        let Some((client, store)) = self.fast.as_mut() else {
+            return Ok(0);
+        };
+        let vectors = client.embed(texts).await?;
+
+        // Models that ignore the `dimensions` parameter return their own size
+        let actual_dim = vectors.first().map_or(0, Vec::len);
+        if actual_dim > 0 && actual_dim != store.dimensions() {
+            log::info!(
+                "Re-initializing fast-tier vector store with actual dimensions: {} (was {})",
+                actual_dim,
+                store.dimensions()
+            );
+            *store = VectorStore::new(path, actual_dim).with_collection(&collection);
+            store.initialize().await?;
+        }
+
+        let chunks: Vec<Chunk> = chunks
+            .iter()
+            .zip(vectors)
+            .map(|(chunk, vector)| Chunk {
+                vector,
+                ..chunk.clone()
+            })
+            .collect();
+        store.upsert(chunks).await
+    }
+
+    /// Store the leading dimensions of each chunk vector in the prefix index
+    /// when `[vector_index] search_dimensions` is set
+    async fn store_prefix(&mut self, chunks: &[Chunk]) -> SearchResult<usize> {
+        let Some(store) = self.prefix.as_mut() else {
+            return Ok(0);
+        };
+        let dimensions = store.dimensions();
+        let chunks: Vec<Chunk> = chunks
+            .iter()
+            .filter(|chunk| chunk.vector.len() > dimensions)
+            .map(|chunk| Chunk {
+                vector: matryoshka::truncate(&chunk.vector, dimensions),
+                ..chunk.clone()
+            })
+            .collect();
+        store.upsert(chunks).await
+    }
+
+    /// Drop a file's rows from the fast-tier and prefix indexes, if there are any
+    async fn delete_derived(&self, rel_path: &str) -> SearchResult<()> {
+        if let Some((_, store)) = self.fast.as_ref() {
+            store.delete_by_file(rel_path).await?;
+        }
+        if let Some(store) = self.prefix.as_ref() {
+            store.delete_by_file(rel_path).await?;
+        }
+        Ok(())
+    }
+
+    /// Empty the fast-tier and prefix indexes, if there are any
+    async fn reset_derived(&mut self) -> SearchResult<()> {
+        if let Some((_, store)) = self.fast.as_mut() {
+            store.reset().await?;
+        }
+        if let Some(store) = self.prefix.as_mut() {
+            store.reset().await?;
+            // Reopened at the configured size, which may differ from the dropped table's
+            let dimensions = self
+                .config
+                .vector_index
+                .search_dimensions
+                .unwrap_or_default();
+            *store = VectorStore::new(self.config.paths.get_prefix_lancedb_path(), dimensions)
+                .with_collection(self.config.paths.collection());
+            store.initialize().await?;
+        }
+        Ok(())
+    }
+
     /// Build index for all documents
     pub async fn build_all(&mut self, docs: Vec<crate::Doc>) -> SearchResult<IndexStats> {
         self.build_all_with_progress(docs, |_| {}).await
@@ -224,174 +602,55 @@ impl Indexer {
     {
         let start = std::time::Instant::now();
         let total_docs = docs.len();
-        let mut total_chunks = 0;
-        let mut processed_docs = 0;
-        let mut all_bm25_chunks: Vec<Chunk> = Vec::new();
 
         // Reset existing index
         self.vector_store.reset().await?;
+        self.bm25_store.index_all(&[])?;
+        if let Some(enricher) = self.contextual.as_mut() {
+            enricher.start_build();
+        }
+        if let Some((_, store)) = self.sparse.as_ref() {
+            store.reset()?;
+        }
+        self.reset_derived().await?;
 
-        // Process documents in batches
-        let batch_size = self.config.embedding.batch_size;
-        let total_batches = docs.len().div_ceil(batch_size);
+        on_progress(IndexProgress {
+            phase: "chunking".to_string(),
+            current: 0,
+            total: total_docs,
+            percent: 0,
+            message: Some(format!("正在分块处理文档 (0/{})", total_docs)),
+        });
 
-        for (batch_idx, batch) in docs.chunks(batch_size).enumerate() {
-            let mut all_chunks = Vec::new();
+        let mut checksums = HashMap::new();
+        let transcluder = Arc::new(Self::transcluder(&docs));
+        let (total_chunks, interrupted) = self
+            .run_pipeline(docs, transcluder, false, &mut checksums, &mut on_progress)
+            .await?;
+        self.refresh_boilerplate();
+        self.refresh_spelling();
 
-            // Phase 1: Chunking
-            on_progress(IndexProgress {
-                phase: "chunking".to_string(),
-                current: batch_idx + 1,
-                total: total_batches,
-                percent: ((batch_idx * 100) / total_batches.max(1)) as u8,
+        // Final progress
+        on_progress(if interrupted {
+            Self::interrupted_progress(total_docs)
+        } else {
+            IndexProgress {
+                phase: "done".to_string(),
+                current: total_docs,
+                total: total_docs,
+                percent: 100,
                 message: Some(format!(
-                    "正在分块处理文档 ({}/{})",
-                    processed_docs, total_docs
+                    "索引构建完成！共 {} 个文档，{} 个文本块",
+                    total_docs, total_chunks
                 )),
-            });
-
-            for doc in batch {
-                // Skip files that no longer exist on disk (orphaned DB records)
-                if !std::path::Path::new(&doc.abs_path).exists() {
-                    log::warn!(
-                        "Skipping missing file during indexing: {} (orphaned DB record?)",
-                        doc.rel_path
-                    );
-                    processed_docs += 1;
-                    continue;
-                }
-
-                let content = std::fs::read_to_string(&doc.abs_path)?;
-                if content.trim().is_empty() {
-                    processed_docs += 1;
-                    continue;
-                }
-
-                if doc.rel_path.starts_with(".ideas/") {
-                    let entries = parse_idea_entries(&content);
-                    let idea_box = extract_idea_box(&doc.rel_path);
-                    for (i, entry) in entries.into_iter().enumerate() {
-                        let entry_date = entry.created_at.get(0..10).unwrap_or("").to_string();
-                        let title_line = entry
-                            .content
-                            .split('\n')
-                            .next()
-                            .unwrap_or("")
-                            .trim()
-                            .to_string();
-                        let id = format!("{}#{}", doc.rel_path, entry.id);
-                        all_chunks.push(Chunk {
-                            id,
-                            file_path: doc.rel_path.clone(),
-                            content: entry.content,
-                            heading_path: String::new(),
-                            section_title: if title_line.is_empty() {
-                                None
-                            } else {
-                                Some(title_line)
-                            },
-                            doc_type: Some("idea".to_string()),
-                            entry_id: Some(entry.id),
-                            entry_date: if entry_date.is_empty() {
-                                None
-                            } else {
-                                Some(entry_date)
-                            },
-                            entry_created_at: Some(entry.created_at),
-                            idea_box: idea_box.clone(),
-                            chunk_index: i,
-                            vector: vec![], // Will be filled below
-                        });
-                    }
-                } else {
-                    let text_chunks = self.chunker.chunk(&content, &doc.rel_path);
-
-                    for (i, text_chunk) in text_chunks.into_iter().enumerate() {
-                        let id = format!("{}#{}", doc.rel_path, i);
-                        all_chunks.push(Chunk {
-                            id,
-                            file_path: doc.rel_path.clone(),
-                            content: text_chunk.content,
-                            heading_path: text_chunk.heading_path,
-                            section_title: None,
-                            doc_type: Some("doc".to_string()),
-                            entry_id: None,
-                            entry_date: None,
-                            entry_created_at: None,
-                            idea_box: None,
-                            chunk_index: i,
-                            vector: vec![], // Will be filled below
-                        });
-                    }
-                }
-                processed_docs += 1;
-            }
-
-            if all_chunks.is_empty() {
-                continue;
             }
-
-            // Phase 2: Embedding
-            on_progress(IndexProgress {
-                phase: "embedding".to_string(),
-                current: batch_idx + 1,
-                total: total_batches,
-                percent: ((batch_idx * 100 + 33) / total_batches.max(1)) as u8,
-                message: Some(format!("正在生成向量 ({} 个文本块)", all_chunks.len())),
-            });
-
-            let texts: Vec<String> = all_chunks.iter().map(|c| c.content.clone()).collect();
-            let embeddings = self.embedding_client.embed(texts).await?;
-
-            // After first embedding batch, verify dimensions match and re-init vector store if needed
-            if !self.dimensions_verified {
-                self.verify_dimensions().await?;
-            }
-
-            // Attach embeddings to chunks
-            for (chunk, embedding) in all_chunks.iter_mut().zip(embeddings) {
-                chunk.vector = embedding;
-            }
-
-            // Phase 3: Storing
-            on_progress(IndexProgress {
-                phase: "storing".to_string(),
-                current: batch_idx + 1,
-                total: total_batches,
-                percent: ((batch_idx * 100 + 66) / total_batches.max(1)) as u8,
-                message: Some("正在写入索引...".to_string()),
-            });
-
-            // Collect chunks for BM25 (no vectors needed)
-            all_bm25_chunks.extend(all_chunks.iter().map(|c| {
-                let mut c = c.clone();
-                c.vector = vec![];
-                c
-            }));
-
-            let count = self.vector_store.upsert(all_chunks).await?;
-            total_chunks += count;
-        }
-
-        // Build BM25 index from all collected chunks
-        self.bm25_store.index_all(&all_bm25_chunks)?;
-
-        // Final progress
-        on_progress(IndexProgress {
-            phase: "done".to_string(),
-            current: total_batches,
-            total: total_batches,
-            percent: 100,
-            message: Some(format!(
-                "索引构建完成！共 {} 个文档，{} 个文本块",
-                total_docs, total_chunks
-            )),
         });
 
         let elapsed_ms = start.elapsed().as_millis() as u64;
 
-        // Save empty checksums to mark that a build has run (enables incremental next time)
-        let _ = self.save_checksums(&HashMap::new());
+        // Record checksums so the next build can be incremental (and, after
+        // an interruption, only embeds the documents not written yet)
+        let _ = self.save_checksums(&checksums);
 
         Ok(IndexStats {
             total_docs,
@@ -406,9 +665,189 @@ impl Indexer {
             ),
             mode: "full".to_string(),
             changes: None,
+            interrupted,
         })
     }
 
+    fn interrupted_progress(total: usize) -> IndexProgress {
+        IndexProgress {
+            phase: "interrupted".to_string(),
+            current: 0,
+            total,
+            percent: 0,
+            message: Some("Build interrupted; written batches were kept".to_string()),
+        }
+    }
+
+    /// Expands embeds between `docs`, which should be the whole workspace
+    pub(super) fn transcluder(docs: &[crate::Doc]) -> Transcluder {
+        Transcluder::new(
+            docs.iter()
+                .map(|d| (d.rel_path.clone(), d.abs_path.clone())),
+        )
+    }
+
+    /// Feed `docs` through the chunk → embed → write stages and return the
+    /// number of chunks stored and whether the build was cancelled. With
+    /// `replace`, each document's previous chunks are removed before its new
+    /// ones are written. Body checksums of written documents that had content
+    /// are added to `checksums`.
+    async fn run_pipeline<F>(
+        &mut self,
+        docs: Vec<crate::Doc>,
+        transcluder: Arc<Transcluder>,
+        replace: bool,
+        checksums: &mut HashMap<String, String>,
+        on_progress: &mut F,
+    ) -> SearchResult<(usize, bool)>
+    where
+        F: FnMut(IndexProgress),
+    {
+        let total_docs = docs.len();
+        let batch_size = self.config.embedding.batch_size.max(1);
+        let concurrency = self.config.pipeline.embed_concurrency.max(1);
+        let mut chunked = pipeline::spawn_chunker(
+            docs.into_iter().map(|d| (d.rel_path, d.abs_path)).collect(),
+            self.chunker.clone(),
+            transcluder,
+            self.config.pipeline.chunk_workers,
+            self.config.pipeline.queue_depth,
+        );
+
+        let mut in_flight: VecDeque<(EmbedTask, Batch)> = VecDeque::new();
+        let mut batch = Batch::default();
+        let mut written_docs = 0;
+        let mut total_chunks = 0;
+        let mut cancelled = false;
+        loop {
+            // Once cancelled, documents still being chunked are dropped and
+            // only the batches already embedding get written
+            let next = tokio::select! {
+                biased;
+                _ = self.cancel.cancelled(), if !cancelled => {
+                    cancelled = true;
+                    batch = Batch::default();
+                    None
+                }
+                doc = chunked.recv(), if !cancelled => doc,
+                else => None,
+            };
+            let finished = next.is_none();
+            if let Some(doc) = next {
+                if let Some(checksum) = doc.checksum {
+                    batch.checksums.push((doc.rel_path.clone(), checksum));
+                }
+                if let Some(meta) = doc.meta {
+                    batch.metas.push((doc.rel_path.clone(), meta));
+                }
+                batch.paths.push(doc.rel_path);
+                batch.chunks.extend(doc.chunks);
+            }
+
+            if batch.chunks.len() >= batch_size || (finished && !batch.paths.is_empty()) {
+                let mut batch = std::mem::take(&mut batch);
+                if replace {
+                    batch.reused = self.stored_vectors(&batch.chunks).await;
+                }
+                let texts = self
+                    .embedding_texts(&Self::unembedded(&batch.chunks, &batch.reused))
+                    .await;
+                let client = Arc::clone(&self.embedding_client);
+                let task = tokio::spawn(async move { client.embed(texts).await });
+                in_flight.push_back((task, batch));
+            }
+
+            // Write in order once the embedding stage is full (or draining)
+            while in_flight.len() >= concurrency || (finished && !in_flight.is_empty()) {
+                let Some((task, mut batch)) = in_flight.pop_front() else {
+                    break;
+                };
+                let embeddings = task.await.map_err(|e| {
+                    SearchError::Embedding(format!("Embedding task failed: {}", e))
+                })??;
+                written_docs += batch.paths.len();
+                let written = std::mem::take(&mut batch.checksums);
+                let metas = std::mem::take(&mut batch.metas);
+                total_chunks += self.write_batch(batch, embeddings, replace).await?;
+                checksums.extend(written);
+                self.doc_meta.extend(metas);
+                on_progress(IndexProgress {
+                    phase: "embedding".to_string(),
+                    current: written_docs,
+                    total: total_docs,
+                    percent: (15 + (written_docs * 80) / total_docs.max(1)) as u8,
+                    message: Some(format!(
+                        "Embedded {}/{} documents",
+                        written_docs, total_docs
+                    )),
+                });
+            }
+            if finished {
+                break;
+            }
+        }
+        if let Some(enricher) = self.contextual.as_mut() {
+            enricher.save();
+        }
+        if cancelled {
+            log::info!(
+                "[Indexer] Build cancelled after {}/{} documents",
+                written_docs,
+                total_docs
+            );
+        }
+        Ok((total_chunks, cancelled))
+    }
+
+    /// Write stage: attach vectors and store a batch in every index
+    async fn write_batch(
+        &mut self,
+        batch: Batch,
+        embeddings: Vec<Vec<f32>>,
+        replace: bool,
+    ) -> SearchResult<usize> {
+        let Batch {
+            mut chunks,
+            paths,
+            mut reused,
+            ..
+        } = batch;
+        if replace {
+            for path in &paths {
+                self.vector_store.delete_by_file(path).await?;
+                self.delete_derived(path).await?;
+                if let Some((_, store)) = self.sparse.as_ref() {
+                    store.delete_by_file(path)?;
+                }
+            }
+        }
+
+        // After the first embedding batch, verify dimensions match and re-init vector store if needed
+        if !embeddings.is_empty() && !self.dimensions_verified {
+            self.verify_dimensions().await?;
+        }
+        Self::attach_vectors(&mut chunks, &mut reused, embeddings);
+
+        let bm25_chunks: Vec<Chunk> = chunks
+            .iter()
+            .map(|c| {
+                let mut c = c.clone();
+                c.vector = vec![];
+                c
+            })
+            .collect();
+        self.bm25_store.update(&paths, &bm25_chunks)?;
+        if chunks.is_empty() {
+            return Ok(0);
+        }
+
+        self.store_windows(&chunks, &mut reused.windows).await?;
+        self.store_sparse(&chunks).await?;
+        self.store_fast(&chunks).await?;
+        self.store_prefix(&chunks).await?;
+        self.vector_store.upsert(chunks).await
+    }
+
     /// Compute sha256 hex digest of a string
     fn sha256(content: &str) -> String {
         let mut hasher = Sha256::new();
@@ -416,22 +855,20 @@ impl Indexer {
         hex::encode(hasher.finalize())
     }
 
-    /// Load doc checksums from metadata file
+    /// Checksum of what gets indexed: frontmatter is not chunked, so editing
+    /// only frontmatter fields leaves it unchanged
+    pub(super) fn body_checksum(content: &str) -> String {
+        Self::sha256(crate::frontmatter::body(content))
+    }
+
     fn load_checksums(&self) -> HashMap<String, String> {
-        let path = self.config.paths.get_index_metadata_path();
-        if !path.exists() {
-            return HashMap::new();
-        }
-        std::fs::read_to_string(&path)
-            .ok()
-            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
-            .and_then(|v| v.get("checksums").cloned())
-            .and_then(|v| serde_json::from_value::<HashMap<String, String>>(v).ok())
-            .unwrap_or_default()
+        load_checksums(&self.config)
     }
 
-    /// Save doc checksums to metadata file (merges with existing metadata)
-    fn save_checksums(&self, checksums: &HashMap<String, String>) -> SearchResult<()> {
+    /// Save doc checksums to metadata file (merges with existing metadata).
+    /// Stats of documents written since the last save are merged into the
+    /// saved ones; stats of documents without a checksum are dropped.
+    fn save_checksums(&mut self, checksums: &HashMap<String, String>) -> SearchResult<()> {
         let path = self.config.paths.get_index_metadata_path();
 
         let mut meta: serde_json::Value = if path.exists() {
@@ -451,6 +888,24 @@ impl Indexer {
         meta["lastUpdated"] = serde_json::json!(now);
         meta["checksums"] =
             serde_json::to_value(checksums).unwrap_or_else(|_| serde_json::json!({}));
+        let mut docs: HashMap<String, DocMeta> = meta
+            .get("docs")
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        docs.extend(self.doc_meta.drain());
+        docs.retain(|path, _| checksums.contains_key(path));
+        meta["docs"] = serde_json::to_value(docs).unwrap_or_else(|_| serde_json::json!({}));
+        meta["includeHeadingContext"] =
+            serde_json::json!(self.config.embedding.include_heading_context);
+        meta["contextualRetrieval"] = serde_json::json!(self.config.contextual.enabled);
+        meta["chineseNormalization"] =
+            serde_json::json!(self.config.tokenizer.chinese_normalization);
+        meta["fastTierModel"] = match self.fast.as_ref() {
+            Some(_) => serde_json::json!(self.config.embedding.tiers.model),
+            None => serde_json::Value::Null,
+        };
+        meta["searchDimensions"] = serde_json::json!(self.prefix.as_ref().map(|s| s.dimensions()));
 
         if let Some(parent) = path.parent() {
             let _ = std::fs::create_dir_all(parent);
@@ -480,73 +935,84 @@ impl Indexer {
         // returns false for empty indices (0 docs = no LanceDB table created).
         let metadata_path = self.config.paths.get_index_metadata_path();
         let index_was_built = metadata_path.exists();
-        if force || !index_was_built {
+        if force
+            || !index_was_built
+            || self.bm25_store.was_recreated()
+            || self.embedding_settings_changed()
+        {
             return self.build_all_with_progress(docs, on_progress).await;
         }
 
         let start = std::time::Instant::now();
+        let total_docs = docs.len();
         let old_checksums = self.load_checksums();
+        if let Some(enricher) = self.contextual.as_mut() {
+            enricher.start_build();
+        }
 
         on_progress(IndexProgress {
             phase: "start".to_string(),
             current: 0,
-            total: docs.len(),
+            total: total_docs,
             percent: 0,
             message: Some("Incremental update".to_string()),
         });
 
-        // Compute new checksums and bucket docs
-        let mut new_checksums: HashMap<String, String> = HashMap::new();
-        let mut to_index: Vec<(crate::Doc, String)> = vec![];
-        let mut changes = IndexChanges::default();
-
         on_progress(IndexProgress {
             phase: "scan".to_string(),
-            current: docs.len(),
-            total: docs.len(),
+            current: total_docs,
+            total: total_docs,
             percent: 5,
-            message: Some(format!("Scanned {} documents", docs.len())),
+            message: Some(format!("Scanned {} documents", total_docs)),
         });
 
-        for doc in &docs {
-            if !std::path::Path::new(&doc.abs_path).exists() {
+        // Scan: checksum every doc without keeping its content around;
+        // changed docs are read again by the chunking stage. Checksums cover
+        // embedded sections, so editing one re-indexes the notes embedding it.
+        let transcluder = Arc::new(Self::transcluder(&docs));
+        let mut new_checksums: HashMap<String, String> = HashMap::new();
+        let mut to_index: Vec<crate::Doc> = vec![];
+        let mut changes = IndexChanges::default();
+        for doc in docs {
+            let Ok(content) = std::fs::read_to_string(&doc.abs_path) else {
                 continue;
-            }
-            let content = match std::fs::read_to_string(&doc.abs_path) {
-                Ok(c) => c,
-                Err(_) => continue,
             };
             if content.trim().is_empty() {
                 continue;
             }
-            let checksum = Self::sha256(&content);
-            new_checksums.insert(doc.rel_path.clone(), checksum.clone());
-
+            let checksum = Self::body_checksum(&transcluder.expand(&doc.rel_path, &content));
             match old_checksums.get(&doc.rel_path) {
                 None => {
                     changes.added += 1;
-                    to_index.push((doc.clone(), content));
+                    to_index.push(doc.clone());
                 }
                 Some(old) if old != &checksum => {
                     changes.modified += 1;
-                    to_index.push((doc.clone(), content));
+                    to_index.push(doc.clone());
                 }
                 _ => {
                     changes.unchanged += 1;
                 }
             }
+            new_checksums.insert(doc.rel_path, checksum);
         }
 
         // Detect deleted docs
-        let current_paths: std::collections::HashSet<_> = new_checksums.keys().cloned().collect();
-        let mut bm25_deleted: Vec<String> = Vec::new();
+        let mut deleted: Vec<String> = Vec::new();
         for old_path in old_checksums.keys() {
-            if !current_paths.contains(old_path.as_str()) {
+            if !new_checksums.contains_key(old_path) {
                 self.vector_store.delete_by_file(old_path).await?;
-                bm25_deleted.push(old_path.clone());
+                self.delete_derived(old_path).await?;
+                if let Some((_, store)) = self.sparse.as_ref() {
+                    store.delete_by_file(old_path)?;
+                }
+                deleted.push(old_path.clone());
                 changes.deleted += 1;
             }
         }
+        if !deleted.is_empty() {
+            self.bm25_store.update(&deleted, &[])?;
+        }
 
         on_progress(IndexProgress {
             phase: "detect".to_string(),
@@ -560,6 +1026,10 @@ impl Indexer {
         });
 
         if to_index.is_empty() {
+            if !deleted.is_empty() {
+                self.refresh_boilerplate();
+                self.refresh_spelling();
+            }
             self.save_checksums(&new_checksums)?;
             let elapsed_ms = start.elapsed().as_millis() as u64;
             on_progress(IndexProgress {
@@ -570,7 +1040,7 @@ impl Indexer {
                 message: Some("No changes, index is up to date".to_string()),
             });
             return Ok(IndexStats {
-                total_docs: docs.len(),
+                total_docs,
                 total_chunks: 0,
                 total_tokens: None,
                 elapsed_ms,
@@ -582,16 +1052,11 @@ impl Indexer {
                 ),
                 mode: "incremental".to_string(),
                 changes: Some(changes),
+                interrupted: false,
             });
         }
 
         let total_to_index = to_index.len();
-        let mut total_chunks = 0usize;
-        let mut bm25_added: Vec<Chunk> = Vec::new();
-
-        let batch_size = self.config.embedding.batch_size;
-        let total_batches = total_to_index.div_ceil(batch_size);
-
         on_progress(IndexProgress {
             phase: "chunk".to_string(),
             current: 0,
@@ -600,131 +1065,48 @@ impl Indexer {
             message: Some(format!("Chunking {} changed documents", total_to_index)),
         });
 
-        for (batch_idx, batch) in to_index.chunks(batch_size).enumerate() {
-            let mut all_chunks = Vec::new();
-
-            for (doc, content) in batch {
-                // Remove old chunks for this doc before re-indexing
-                self.vector_store.delete_by_file(&doc.rel_path).await?;
-
-                if doc.rel_path.starts_with(".ideas/") {
-                    let entries = parse_idea_entries(content);
-                    let idea_box = extract_idea_box(&doc.rel_path);
-                    for (i, entry) in entries.into_iter().enumerate() {
-                        let entry_date = entry.created_at.get(0..10).unwrap_or("").to_string();
-                        let title_line = entry
-                            .content
-                            .split('\n')
-                            .next()
-                            .unwrap_or("")
-                            .trim()
-                            .to_string();
-                        let id = format!("{}#{}", doc.rel_path, entry.id);
-                        all_chunks.push(Chunk {
-                            id,
-                            file_path: doc.rel_path.clone(),
-                            content: entry.content,
-                            heading_path: String::new(),
-                            section_title: if title_line.is_empty() {
-                                None
-                            } else {
-                                Some(title_line)
-                            },
-                            doc_type: Some("idea".to_string()),
-                            entry_id: Some(entry.id),
-                            entry_date: if entry_date.is_empty() {
-                                None
-                            } else {
-                                Some(entry_date)
-                            },
-                            entry_created_at: Some(entry.created_at),
-                            idea_box: idea_box.clone(),
-                            chunk_index: i,
-                            vector: vec![],
-                        });
-                    }
-                } else {
-                    let text_chunks = self.chunker.chunk(content, &doc.rel_path);
-                    for (i, text_chunk) in text_chunks.into_iter().enumerate() {
-                        let id = format!("{}#{}", doc.rel_path, i);
-                        all_chunks.push(Chunk {
-                            id,
-                            file_path: doc.rel_path.clone(),
-                            content: text_chunk.content,
-                            heading_path: text_chunk.heading_path,
-                            section_title: None,
-                            doc_type: Some("doc".to_string()),
-                            entry_id: None,
-                            entry_date: None,
-                            entry_created_at: None,
-                            idea_box: None,
-                            chunk_index: i,
-                            vector: vec![],
-                        });
-                    }
-                }
-            }
-
-            if all_chunks.is_empty() {
-                continue;
-            }
-
-            on_progress(IndexProgress {
-                phase: "embedding".to_string(),
-                current: batch_idx + 1,
-                total: total_batches,
-                percent: (15 + (batch_idx * 80) / total_batches.max(1)) as u8,
-                message: Some(format!(
-                    "Generating embeddings batch {}/{}",
-                    batch_idx + 1,
-                    total_batches
-                )),
-            });
-
-            let texts: Vec<String> = all_chunks.iter().map(|c| c.content.clone()).collect();
-            let embeddings = self.embedding_client.embed(texts).await?;
-
-            if !self.dimensions_verified {
-                self.verify_dimensions().await?;
-            }
-
-            for (chunk, embedding) in all_chunks.iter_mut().zip(embeddings) {
-                chunk.vector = embedding;
-            }
-
-            // Track changed file paths for BM25 incremental update
-            let changed_paths: Vec<String> =
-                batch.iter().map(|(d, _)| d.rel_path.clone()).collect();
-            bm25_deleted.extend(changed_paths);
-            bm25_added.extend(all_chunks.iter().map(|c| {
-                let mut c = c.clone();
-                c.vector = vec![];
-                c
-            }));
-
-            let count = self.vector_store.upsert(all_chunks).await?;
-            total_chunks += count;
+        // Changed docs keep their old checksum until they are written, so an
+        // interrupted build leaves them looking changed to the next one. A
+        // file edited since the scan gets the checksum of what was embedded.
+        for doc in &to_index {
+            match old_checksums.get(&doc.rel_path) {
+                Some(old) => new_checksums.insert(doc.rel_path.clone(), old.clone()),
+                None => new_checksums.remove(&doc.rel_path),
+            };
         }
-
-        self.bm25_store.update(&bm25_deleted, &bm25_added)?;
+        let (total_chunks, interrupted) = self
+            .run_pipeline(
+                to_index,
+                transcluder,
+                true,
+                &mut new_checksums,
+                &mut on_progress,
+            )
+            .await?;
+        self.refresh_boilerplate();
+        self.refresh_spelling();
 
         self.save_checksums(&new_checksums)?;
 
         let elapsed_ms = start.elapsed().as_millis() as u64;
 
-        on_progress(IndexProgress {
-            phase: "done".to_string(),
-            current: total_to_index,
-            total: total_to_index,
-            percent: 100,
-            message: Some(format!(
-                "Done: {} changed docs, {} chunks",
-                total_to_index, total_chunks
-            )),
+        on_progress(if interrupted {
+            Self::interrupted_progress(total_to_index)
+        } else {
+            IndexProgress {
+                phase: "done".to_string(),
+                current: total_to_index,
+                total: total_to_index,
+                percent: 100,
+                message: Some(format!(
+                    "Done: {} changed docs, {} chunks",
+                    total_to_index, total_chunks
+                )),
+            }
         });
 
         Ok(IndexStats {
-            total_docs: docs.len(),
+            total_docs,
             total_chunks,
             total_tokens: None,
             elapsed_ms,
@@ -736,101 +1118,57 @@ impl Indexer {
             ),
             mode: "incremental".to_string(),
             changes: Some(changes),
+            interrupted,
         })
     }
 
     /// Index a single file
     pub async fn index_file(&mut self, rel_path: &str) -> SearchResult<usize> {
-        let abs_path = self.contexts_root.join(rel_path);
+        let rel_path = RelPath::new(rel_path);
+        let rel_path = rel_path.as_str();
+        let abs_path = self.abs_path(rel_path);
 
         if !abs_path.exists() {
             return Err(SearchError::Index(format!("File not found: {}", rel_path)));
         }
 
-        // Remove existing chunks for this file
-        self.vector_store.delete_by_file(rel_path).await?;
-
-        // Read and chunk the document
-        let content = std::fs::read_to_string(&abs_path)?;
-        if content.trim().is_empty() {
+        // Read the document; nothing to re-embed if only frontmatter changed
+        let content = std::fs::read_to_string(&abs_path)
+            .map_err(|e| SearchError::from(e).with_path(&abs_path))?;
+        let indexed = self.expand_embeds(rel_path, &content);
+        let checksum = Self::body_checksum(&indexed);
+        let mut checksums = self.load_checksums();
+        if checksums.get(rel_path) == Some(&checksum) {
+            log::debug!("[Indexer] {} body unchanged, skipping re-embed", rel_path);
             return Ok(0);
         }
 
-        let mut chunks = Vec::new();
-
-        if rel_path.starts_with(".ideas/") {
-            let entries = parse_idea_entries(&content);
-            let idea_box = extract_idea_box(rel_path);
-            for (i, entry) in entries.into_iter().enumerate() {
-                let entry_date = entry.created_at.get(0..10).unwrap_or("").to_string();
-                let title_line = entry
-                    .content
-                    .split('\n')
-                    .next()
-                    .unwrap_or("")
-                    .trim()
-                    .to_string();
-                let id = format!("{}#{}", rel_path, entry.id);
-                chunks.push(Chunk {
-                    id,
-                    file_path: rel_path.to_string(),
-                    content: entry.content,
-                    heading_path: String::new(),
-                    section_title: if title_line.is_empty() {
-                        None
-                    } else {
-                        Some(title_line)
-                    },
-                    doc_type: Some("idea".to_string()),
-                    entry_id: Some(entry.id),
-                    entry_date: if entry_date.is_empty() {
-                        None
-                    } else {
-                        Some(entry_date)
-                    },
-                    entry_created_at: Some(entry.created_at),
-                    idea_box: idea_box.clone(),
-                    chunk_index: i,
-                    vector: vec![],
-                });
-            }
+        let mut chunks = if content.trim().is_empty() {
+            Vec::new()
         } else {
-            let text_chunks = self.chunker.chunk(&content, rel_path);
-            for (i, text_chunk) in text_chunks.into_iter().enumerate() {
-                let id = format!("{}#{}", rel_path, i);
-                chunks.push(Chunk {
-                    id,
-                    file_path: rel_path.to_string(),
-                    content: text_chunk.content,
-                    heading_path: text_chunk.heading_path,
-                    section_title: None,
-                    doc_type: Some("doc".to_string()),
-                    entry_id: None,
-                    entry_date: None,
-                    entry_created_at: None,
-                    idea_box: None,
-                    chunk_index: i,
-                    vector: vec![],
-                });
-            }
-        }
-
+            pipeline::doc_chunks(&self.chunker, rel_path, &indexed)
+        };
+        // Unchanged chunks keep their vectors; look them up before the
+        // file's rows are removed
+        let mut reused = self.stored_vectors(&chunks).await;
+        self.vector_store.delete_by_file(rel_path).await?;
+        self.delete_derived(rel_path).await?;
         if chunks.is_empty() {
             return Ok(0);
         }
 
-        // Generate embeddings
-        let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+        // Generate embeddings for new and edited chunks
+        let texts = self
+            .embedding_texts(&Self::unembedded(&chunks, &reused))
+            .await;
         let embeddings = self.embedding_client.embed(texts).await?;
 
         // Verify dimensions after getting embeddings
-        if !self.dimensions_verified {
+        if !embeddings.is_empty() && !self.dimensions_verified {
             self.verify_dimensions().await?;
         }
 
-        for (chunk, embedding) in chunks.iter_mut().zip(embeddings) {
-            chunk.vector = embedding;
-        }
+        Self::attach_vectors(&mut chunks, &mut reused, embeddings);
 
         // Store
         let bm25_chunks: Vec<Chunk> = chunks
@@ -841,16 +1179,56 @@ impl Indexer {
                 c
             })
             .collect();
+        self.store_windows(&chunks, &mut reused.windows).await?;
+        self.store_sparse(&chunks).await?;
+        self.store_fast(&chunks).await?;
+        self.store_prefix(&chunks).await?;
         let count = self.vector_store.upsert(chunks).await?;
         self.bm25_store
             .update(&[rel_path.to_string()], &bm25_chunks)?;
+        self.refresh_boilerplate();
+        self.refresh_spelling();
+        checksums.insert(rel_path.to_string(), checksum);
+        self.doc_meta.insert(
+            rel_path.to_string(),
+            doc_meta::compute(rel_path, &content, count, &abs_path),
+        );
+        self.save_checksums(&checksums)?;
         Ok(count)
     }
 
+    /// `content` of `rel_path` with embedded notes expanded. Scans the
+    /// workspace only when the document has embeds; notes embedding
+    /// `rel_path` are caught up by the next `build_smart`.
+    fn expand_embeds<'a>(&self, rel_path: &str, content: &'a str) -> std::borrow::Cow<'a, str> {
+        if !content.contains("![[") {
+            return content.into();
+        }
+        match Transcluder::scan(&self.contexts_root, crate::SymlinkPolicy::default()) {
+            Ok(transcluder) => transcluder.expand(rel_path, content),
+            Err(e) => {
+                log::warn!("[Indexer] Could not expand embeds of {}: {}", rel_path, e);
+                content.into()
+            }
+        }
+    }
+
     /// Remove a file from the index
     pub async fn remove_file(&mut self, rel_path: &str) -> SearchResult<()> {
+        let rel_path = RelPath::new(rel_path);
+        let rel_path = rel_path.as_str();
         self.vector_store.delete_by_file(rel_path).await?;
+        self.delete_derived(rel_path).await?;
+        if let Some((_, store)) = self.sparse.as_ref() {
+            store.delete_by_file(rel_path)?;
+        }
         self.bm25_store.update(&[rel_path.to_string()], &[])?;
+        self.refresh_boilerplate();
+        self.refresh_spelling();
+        let mut checksums = self.load_checksums();
+        if checksums.remove(rel_path).is_some() {
+            self.save_checksums(&checksums)?;
+        }
         Ok(())
     }
 
@@ -859,8 +1237,7 @@ impl Indexer {
         // For now, we simply remove old and re-index new
         self.remove_file(old_path).await?;
 
-        let abs_path = self.contexts_root.join(new_path);
-        if abs_path.exists() {
+        if self.abs_path(new_path).exists() {
             self.index_file(new_path).await?;
         }
 
@@ -895,13 +1272,31 @@ impl Indexer {
             last_updated,
             mode: "full".to_string(),
             changes: None,
+            interrupted: false,
         })
     }
 
-    /// Extended index info for status reporting (model, bm25 count, etc.)
+    /// Extended index info for status reporting (model, bm25 count, etc.).
+    /// Counts are for this indexer's collection; `collections` lists the
+    /// chunk count of every collection sharing the vector store.
     pub async fn get_index_info(&self) -> SearchResult<serde_json::Value> {
         let vector_count = self.vector_store.count().await?;
+        let collections: Vec<serde_json::Value> = self
+            .vector_store
+            .collections()
+            .await?
+            .into_iter()
+            .map(|(name, chunks)| serde_json::json!({ "name": name, "chunks": chunks }))
+            .collect();
         let bm25_count = self.bm25_store.count().unwrap_or(0);
+        let sparse_count = self
+            .sparse
+            .as_ref()
+            .map(|(_, store)| store.count().unwrap_or(0));
+        let fast_count = match self.fast.as_ref() {
+            Some((_, store)) => Some(store.count().await?),
+            None => None,
+        };
 
         let metadata_path = self.config.paths.get_index_metadata_path();
         let metadata: serde_json::Value = if metadata_path.exists() {
@@ -945,15 +1340,23 @@ impl Indexer {
             "available": vector_count > 0,
             "vector_chunks": vector_count,
             "bm25_docs": bm25_count,
+            "sparse_chunks": sparse_count,
+            "fast_chunks": fast_count,
             "total_docs": total_docs,
             "last_updated": last_updated,
             "embedding_model": embedding_model,
             "embedding_dimensions": embedding_dim,
+            "collection": self.vector_store.collection(),
+            "collections": collections,
         }))
     }
 
     /// Clean the index
     pub async fn clean(&mut self) -> SearchResult<()> {
+        if let Some((_, store)) = self.sparse.as_ref() {
+            store.reset()?;
+        }
+        self.reset_derived().await?;
         self.vector_store.reset().await
     }
 
@@ -0,0 +1,108 @@
+//! Multi-vector (ColBERT-style late interaction) scoring
+//!
+//! Each chunk stores one vector per sliding window of its text. At query time
+//! the candidates returned by ANN search are re-scored with MaxSim: every query
+//! vector is matched against its best window, and the matches are averaged.
+//! Windows are measured in characters, like the chunker, so CJK text behaves
+//! the same as space-separated languages.
+
+/// Split text into overlapping windows of `window_chars` characters,
+/// starting every `stride_chars` characters.
+pub fn windows(text: &str, window_chars: usize, stride_chars: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let window = window_chars.max(1);
+    let stride = stride_chars.clamp(1, window);
+
+    if chars.len() <= window {
+        let trimmed = text.trim();
+        return if trimmed.is_empty() {
+            vec![]
+        } else {
+            vec![trimmed.to_string()]
+        };
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + window).min(chars.len());
+        let piece: String = chars[start..end].iter().collect();
+        let piece = piece.trim();
+        if !piece.is_empty() {
+            result.push(piece.to_string());
+        }
+        if end == chars.len() {
+            break;
+        }
+        start += stride;
+    }
+    result
+}
+
+/// Cosine similarity of two vectors (0 when either is empty or zero)
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let mut dot = 0.0_f32;
+    let mut norm_a = 0.0_f32;
+    let mut norm_b = 0.0_f32;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// MaxSim late interaction score: mean over query vectors of the best cosine
+/// similarity against any document window. Returns `None` if either side is empty.
+pub fn max_sim(query: &[Vec<f32>], doc: &[Vec<f32>]) -> Option<f32> {
+    if query.is_empty() || doc.is_empty() {
+        return None;
+    }
+    let total: f32 = query
+        .iter()
+        .map(|q| {
+            doc.iter()
+                .map(|d| cosine(q, d))
+                .fold(f32::NEG_INFINITY, f32::max)
+        })
+        .sum();
+    Some(total / query.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_single_window() {
+        assert_eq!(windows("hello world", 200, 100), vec!["hello world"]);
+        assert!(windows("   ", 200, 100).is_empty());
+    }
+
+    #[test]
+    fn test_windows_overlap_and_cover_end() {
+        let text = "abcdefghij";
+        let w = windows(text, 4, 3);
+        assert_eq!(w, vec!["abcd", "defg", "ghij"]);
+    }
+
+    #[test]
+    fn test_windows_cjk_safe() {
+        let text = "这是一段用于测试窗口切分的中文内容";
+        for w in windows(text, 5, 2) {
+            assert!(w.chars().count() <= 5);
+        }
+    }
+
+    #[test]
+    fn test_max_sim_prefers_best_window() {
+        let query = vec![vec![1.0, 0.0]];
+        let doc = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let score = max_sim(&query, &doc).unwrap();
+        assert!((score - 1.0).abs() < 1e-6);
+        assert!(max_sim(&query, &[]).is_none());
+    }
+}
@@ -0,0 +1,123 @@
+//! Minimal OpenAI-compatible chat completion client
+//!
+//! Used by optional LLM-assisted search features (query routing, HyDE).
+//! Non-streaming; callers get the first choice's message content.
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::config::{EmbeddingConfig, LlmConfig};
+use super::error::{SearchError, SearchResult};
+
+/// Chat completion client
+pub struct LlmClient {
+    api_key: String,
+    api_base: String,
+    model: String,
+    client: Client,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    temperature: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: Option<String>,
+}
+
+impl LlmClient {
+    /// Create a client, falling back to the embedding API key and base URL
+    pub fn new(config: &LlmConfig, embedding: &EmbeddingConfig) -> SearchResult<Self> {
+        let api_key = match config.api_key.as_deref() {
+            Some(key) if !key.is_empty() => key.to_string(),
+            _ => embedding.get_api_key()?,
+        };
+        let api_base = config
+            .api_base
+            .clone()
+            .unwrap_or_else(|| embedding.api_base.clone());
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()
+            .map_err(SearchError::Http)?;
+
+        Ok(Self {
+            api_key,
+            api_base: api_base.trim_end_matches('/').to_string(),
+            model: config.model.clone(),
+            client,
+        })
+    }
+
+    /// Run a single system + user prompt and return the reply text
+    pub async fn complete(&self, system: &str, user: &str) -> SearchResult<String> {
+        self.chat(system, user)
+            .await
+            .map_err(|e| e.with_operation("complete").with_provider(&self.api_base))
+    }
+
+    async fn chat(&self, system: &str, user: &str) -> SearchResult<String> {
+        let request = ChatRequest {
+            model: &self.model,
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: system,
+                },
+                ChatMessage {
+                    role: "user",
+                    content: user,
+                },
+            ],
+            temperature: 0.0,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/chat/completions", self.api_base))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request)
+            .send()
+            .await
+            .map_err(SearchError::Http)?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(SearchError::Http)?;
+        if !status.is_success() {
+            return Err(SearchError::Llm(format!(
+                "API error ({}): {}",
+                status, body
+            )));
+        }
+
+        let response: ChatResponse = serde_json::from_str(&body)?;
+        response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .map(|c| c.trim().to_string())
+            .ok_or_else(|| SearchError::Llm("Empty completion".to_string()))
+    }
+}
@@ -0,0 +1,35 @@
+//! Matryoshka (MRL) prefix vectors
+//!
+//! Models trained with Matryoshka representation learning, such as OpenAI's
+//! `text-embedding-3-*`, pack the most information into the leading
+//! dimensions, so a prefix of the full vector is a usable embedding on its
+//! own. With `[vector_index] search_dimensions` the index keeps such prefixes
+//! next to the full vectors: ANN search runs on the short ones, and the best
+//! candidates are re-scored exactly on the full ones.
+
+/// The first `dimensions` components of `vector`, scaled back to unit
+/// length so distances between prefixes stay comparable
+pub fn truncate(vector: &[f32], dimensions: usize) -> Vec<f32> {
+    let mut prefix: Vec<f32> = vector.iter().take(dimensions).copied().collect();
+    let norm = prefix.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in &mut prefix {
+            *x /= norm;
+        }
+    }
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_a_unit_prefix() {
+        let prefix = truncate(&[3.0, 4.0, 12.0], 2);
+        assert_eq!(prefix, vec![0.6, 0.8]);
+
+        assert_eq!(truncate(&[0.0, 0.0, 1.0], 2), vec![0.0, 0.0]);
+        assert_eq!(truncate(&[2.0], 4), vec![1.0]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Memory budget of a searcher
+//!
+//! [`MemoryConfig`] sets one budget for the structures a searcher keeps
+//! between queries. [`Limits`] splits it per component; the caches are
+//! [`Lru`]s bounded by the estimated size of their values, and the
+//! spelling index is only kept while it fits the keyword share.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use super::config::MemoryConfig;
+use super::types::{ComponentMemory, SearchHit};
+
+/// Tantivy refuses a smaller indexing buffer
+const MIN_WRITER_HEAP: usize = 15_000_000;
+const MAX_WRITER_HEAP: usize = 50_000_000;
+
+/// Bytes of each component's share of the budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Limits {
+    pub chunk_cache: usize,
+    pub embedding_cache: usize,
+    pub keyword_index: usize,
+}
+
+impl Limits {
+    pub fn new(config: &MemoryConfig) -> Self {
+        let budget = config.budget_bytes();
+        Self {
+            chunk_cache: budget / 2,
+            embedding_cache: budget / 4,
+            keyword_index: budget / 4,
+        }
+    }
+
+    /// Indexing buffer of the keyword writer
+    pub fn writer_heap(&self) -> usize {
+        self.keyword_index.clamp(MIN_WRITER_HEAP, MAX_WRITER_HEAP)
+    }
+}
+
+/// Estimated size of a cached query embedding
+pub(super) fn vector_bytes(text: &str, vector: &[f32]) -> usize {
+    text.len() + std::mem::size_of_val(vector) + 64
+}
+
+/// Estimated size of cached hits, dominated by their text
+pub(super) fn hits_bytes(hits: &[SearchHit]) -> usize {
+    hits.iter()
+        .map(|hit| {
+            std::mem::size_of::<SearchHit>()
+                + hit.content.len()
+                + hit.file_path.len()
+                + hit.display_name.len()
+                + hit.heading_path.as_ref().map_or(0, String::len)
+                + hit.chunk_id.as_ref().map_or(0, String::len)
+        })
+        .sum()
+}
+
+/// Least recently used cache holding at most `limit` bytes of values, as
+/// estimated by the caller on insert
+pub(super) struct Lru<K, V> {
+    limit: usize,
+    bytes: usize,
+    /// Incremented on every use; the smallest tick is the oldest entry
+    tick: u64,
+    entries: HashMap<K, (V, usize, u64)>,
+    order: BTreeMap<u64, K>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Lru<K, V> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            bytes: 0,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let Some((value, _, used)) = self.entries.get_mut(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.order.remove(used);
+        *used = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value.clone())
+    }
+
+    /// Cache `value`, evicting the least recently used entries to make
+    /// room. A value larger than the whole limit is not cached.
+    pub fn insert(&mut self, key: K, value: V, bytes: usize) {
+        self.remove(&key);
+        if bytes > self.limit {
+            return;
+        }
+        while self.bytes + bytes > self.limit {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some((_, size, _)) = self.entries.remove(&oldest) {
+                self.bytes -= size;
+                self.evictions += 1;
+            }
+        }
+        self.tick += 1;
+        self.bytes += bytes;
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, bytes, self.tick));
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some((_, size, used)) = self.entries.remove(key) {
+            self.bytes -= size;
+            self.order.remove(&used);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+
+    pub fn usage(&self, component: &str) -> ComponentMemory {
+        ComponentMemory {
+            component: component.to_string(),
+            bytes: self.bytes,
+            limit_bytes: self.limit,
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut cache: Lru<&str, u32> = Lru::new(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 4);
+        assert_eq!(cache.get(&"a"), Some(1));
+        // "b" was used longest ago
+        cache.insert("c", 3, 4);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(3));
+        cache.insert("huge", 4, 11);
+        assert_eq!(cache.get(&"huge"), None);
+        cache.insert("a", 5, 6);
+        assert_eq!(cache.get(&"a"), Some(5));
+
+        let usage = cache.usage("test");
+        assert_eq!((usage.bytes, usage.entries), (10, 2));
+        assert_eq!((usage.hits, usage.misses, usage.evictions), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_limits_split_budget() {
+        let limits = Limits::new(&MemoryConfig { budget_mb: 64 });
+        assert_eq!(limits.chunk_cache, 32 << 20);
+        assert_eq!(limits.embedding_cache, 16 << 20);
+        assert_eq!(limits.writer_heap(), 16 << 20);
+        let tiny = Limits::new(&MemoryConfig { budget_mb: 8 });
+        assert_eq!(tiny.writer_heap(), MIN_WRITER_HEAP);
+    }
+}
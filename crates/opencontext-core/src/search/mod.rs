@@ -7,9 +7,17 @@
 //! - Vector-based semantic search using LanceDB
 //! - OpenAI Embedding API integration
 //! - Markdown-aware document chunking
-//! - Hybrid search (vector + keyword)
+//! - Hybrid search (vector + keyword, optionally + sparse SPLADE)
 //! - Event-driven index synchronization
 //!
+//! ## Cargo features
+//!
+//! `search` enables everything above. `keyword` is the subset without
+//! LanceDB, reqwest or any embedding code: the tokenizer, the BM25 store,
+//! the chunker, the config and result types, and `crate::events`. It suits
+//! small embedded builds (WASM, FFI) that only need keyword search next to
+//! the document manager; `search` implies it.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -23,27 +31,137 @@
 //! }).await?;
 //! ```
 
+mod api;
+#[cfg(feature = "search")]
+pub mod blocking;
 mod bm25_store;
+#[cfg(feature = "search")]
+mod boilerplate;
+mod canonical;
 mod chunker;
+mod citation;
+#[cfg(feature = "search")]
+mod compression;
+#[cfg(feature = "search")]
+mod confidence;
 mod config;
+#[cfg(feature = "search")]
+mod context;
+#[cfg(feature = "search")]
+mod contextual;
+#[cfg(feature = "search")]
+mod crosslang;
+#[cfg(feature = "search")]
+mod dedup;
+mod doc_meta;
+#[cfg(feature = "search")]
 mod embedding;
+#[cfg(feature = "search")]
+mod ephemeral;
 mod error;
+mod facets;
+#[cfg(feature = "search")]
+mod federation;
+mod filter;
+#[cfg(feature = "search")]
+mod folder_summary;
+#[cfg(feature = "search")]
 mod index_sync;
+#[cfg(feature = "search")]
 mod indexer;
+#[cfg(feature = "search")]
+mod late_interaction;
+#[cfg(feature = "search")]
+mod llm;
+#[cfg(feature = "search")]
+mod matryoshka;
+#[cfg(feature = "search")]
+mod memory;
+mod options;
+#[cfg(feature = "search")]
+mod pipeline;
+#[cfg(feature = "search")]
+mod plan;
+mod provenance;
+#[cfg(feature = "search")]
+mod related;
+#[cfg(feature = "search")]
+mod router;
+#[cfg(feature = "search")]
+mod schema;
+#[cfg(feature = "search")]
+mod scoped;
+#[cfg(feature = "search")]
 mod searcher;
+#[cfg(feature = "search")]
+mod slowlog;
+#[cfg(feature = "search")]
+mod sort;
+#[cfg(feature = "search")]
+mod sparse_embedding;
+#[cfg(feature = "search")]
+mod sparse_store;
+#[cfg(feature = "search")]
+mod spelling;
+mod tokenizer;
+#[cfg(feature = "search")]
+mod trust;
 mod types;
+#[cfg(feature = "search")]
 mod vector_store;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "search"))]
 mod tests;
 
+#[cfg(feature = "schema")]
+pub use api::json_schemas;
+pub use api::API_SCHEMA_VERSION;
 pub use bm25_store::Bm25Store;
 pub use chunker::Chunker;
-pub use config::{EmbeddingConfig, SearchConfig};
+pub use citation::format_citation;
+pub use config::{
+    ClipboardConfig, ContextualConfig, CrossLanguageConfig, EmbeddingConfig, FederationConfig,
+    GithubSourceConfig, JiraSourceConfig, LinearSourceConfig, LlmConfig, MultiVectorConfig,
+    PipelineConfig, RouterConfig, SearchConfig, SessionsConfig, SourcesConfig, SparseConfig,
+    TiersConfig, TokenizerConfig, VectorIndexConfig, WorkspaceConfig, DEFAULT_COLLECTION,
+};
+#[cfg(feature = "search")]
 pub use embedding::EmbeddingClient;
-pub use error::{SearchError, SearchResult};
+#[cfg(feature = "search")]
+pub use ephemeral::EphemeralIndex;
+pub use error::{ErrorContext, ErrorPayload, SearchError, SearchResult};
+#[cfg(feature = "search")]
+pub use federation::{FederatedHit, FederatedResults, FederatedSearcher, WorkspaceStatus};
+pub use filter::{DateField, Filter};
+#[cfg(feature = "search")]
 pub use index_sync::IndexSyncService;
+#[cfg(feature = "search")]
 pub use indexer::{IndexProgress, IndexStats, Indexer};
+#[cfg(feature = "search")]
+pub use llm::LlmClient;
+pub use options::SearchOptionsBuilder;
+#[cfg(feature = "search")]
+pub use plan::{
+    estimate_index_cost, plan_build, plan_files, IndexCostEstimate, IndexPlan, PlanAction,
+    PlannedFile, TokenCount, TokenizerFamily,
+};
+#[cfg(feature = "search")]
+pub use router::QueryIntent;
+#[cfg(feature = "search")]
+pub use schema::SCHEMA_VERSION;
+#[cfg(feature = "search")]
+pub use scoped::ScopedSearcher;
+#[cfg(feature = "search")]
 pub use searcher::Searcher;
+#[cfg(feature = "search")]
+pub use slowlog::{read_recent as read_slow_queries, SlowQueryEntry};
+#[cfg(feature = "search")]
+pub use sparse_embedding::SparseEmbeddingClient;
+#[cfg(feature = "search")]
+pub use sparse_store::SparseStore;
+pub use tokenizer::{register_tokenizer, DefaultTokenizer, Tokenizer, Word};
+#[cfg(feature = "search")]
+pub use tokio_util::sync::CancellationToken;
 pub use types::*;
+#[cfg(feature = "search")]
 pub use vector_store::VectorStore;
@@ -0,0 +1,318 @@
+//! Fluent construction of [`SearchOptions`]
+//!
+//! ```rust,ignore
+//! let options = SearchOptions::builder()
+//!     .query("release checklist")
+//!     .mode(SearchMode::Hybrid)
+//!     .limit(20)
+//!     .path_prefix("projects/")
+//!     .build()?;
+//! ```
+//!
+//! Setters take plain values instead of `Option`s, and [`build`] checks
+//! the combination before a query is run.
+//!
+//! [`build`]: SearchOptionsBuilder::build
+
+use chrono::NaiveDate;
+
+use super::error::{SearchError, SearchResult};
+use super::filter::Filter;
+use super::types::{
+    AggregateBy, CitationStyle, EmbeddingTier, GroupBy, SearchMode, SearchOptions, SortBy,
+    SortOrder,
+};
+
+impl SearchOptions {
+    /// Start building options; see [`SearchOptionsBuilder`]
+    pub fn builder() -> SearchOptionsBuilder {
+        SearchOptionsBuilder::default()
+    }
+
+    /// `include_sources` and `exclude_sources` as one filter, `None` when
+    /// both are unset or empty. Each value matches a folder, a document
+    /// type or a provenance source type or origin.
+    pub fn source_filter(&self) -> Option<Filter> {
+        let any = |sources: &[String]| {
+            Filter::Or(
+                sources
+                    .iter()
+                    .flat_map(|s| {
+                        [
+                            Filter::Path(s.clone()),
+                            Filter::DocType(s.clone()),
+                            Filter::Source(s.clone()),
+                        ]
+                    })
+                    .collect(),
+            )
+        };
+        let include = self.include_sources.as_deref().filter(|s| !s.is_empty());
+        let exclude = self.exclude_sources.as_deref().filter(|s| !s.is_empty());
+        let include = include.map(any);
+        let exclude = exclude.map(|s| Filter::Not(Box::new(any(s))));
+        match (include, exclude) {
+            (Some(include), Some(exclude)) => Some(include.and(exclude)),
+            (include, exclude) => include.or(exclude),
+        }
+    }
+}
+
+/// Builder for [`SearchOptions`]
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptionsBuilder {
+    options: SearchOptions,
+}
+
+impl SearchOptionsBuilder {
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.options.query = query.into();
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.options.limit = Some(limit);
+        self
+    }
+
+    pub fn mode(mut self, mode: SearchMode) -> Self {
+        self.options.mode = Some(mode);
+        self
+    }
+
+    pub fn aggregate_by(mut self, aggregate_by: AggregateBy) -> Self {
+        self.options.aggregate_by = Some(aggregate_by);
+        self
+    }
+
+    /// Only results passing `filter`, in addition to any filter set before
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.options.filter = Some(match self.options.filter.take() {
+            Some(existing) => existing.and(filter),
+            None => filter,
+        });
+        self
+    }
+
+    /// Only documents of this type: `"doc"` or `"idea"`
+    pub fn doc_type(self, doc_type: impl Into<String>) -> Self {
+        self.filter(Filter::DocType(doc_type.into()))
+    }
+
+    /// Only documents under this folder (`folder_filter`)
+    pub fn path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.options.folder_filter = Some(prefix.into());
+        self
+    }
+
+    /// Only results from these folders, document types or source types
+    pub fn include_sources<I, S>(mut self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.include_sources = Some(sources.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// No results from these folders, document types or source types
+    pub fn exclude_sources<I, S>(mut self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.exclude_sources = Some(sources.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn min_score(mut self, min_score: f32) -> Self {
+        self.options.min_score = Some(min_score);
+        self
+    }
+
+    /// Only idea entries dated on or after `date` (`YYYY-MM-DD`)
+    pub fn date_from(mut self, date: impl Into<String>) -> Self {
+        self.options.date_from = Some(date.into());
+        self
+    }
+
+    /// Only idea entries dated on or before `date` (`YYYY-MM-DD`)
+    pub fn date_to(mut self, date: impl Into<String>) -> Self {
+        self.options.date_to = Some(date.into());
+        self
+    }
+
+    pub fn include_neighbors(mut self, window: usize) -> Self {
+        self.options.include_neighbors = Some(window);
+        self
+    }
+
+    /// Group results by document with up to `children` chunks each
+    pub fn group_by_doc(mut self, children: usize) -> Self {
+        self.options.group_by = Some(GroupBy::Doc);
+        self.options.group_children = Some(children);
+        self
+    }
+
+    pub fn facets(mut self, facets: bool) -> Self {
+        self.options.facets = Some(facets);
+        self
+    }
+
+    pub fn fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.fields = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.options.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    pub fn cross_language(mut self, enabled: bool) -> Self {
+        self.options.cross_language = Some(enabled);
+        self
+    }
+
+    pub fn tier(mut self, tier: EmbeddingTier) -> Self {
+        self.options.tier = Some(tier);
+        self
+    }
+
+    pub fn collection(mut self, collection: impl Into<String>) -> Self {
+        self.options.collection = Some(collection.into());
+        self
+    }
+
+    pub fn allowed_folders<I, S>(mut self, folders: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.allowed_folders = Some(folders.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn folder_summary(mut self, enabled: bool) -> Self {
+        self.options.folder_summary = Some(enabled);
+        self
+    }
+
+    /// Order results by `by` in direction `order`
+    pub fn sort_by(mut self, by: SortBy, order: SortOrder) -> Self {
+        self.options.sort_by = Some(by);
+        self.options.sort_order = Some(order);
+        self
+    }
+
+    /// Fill each result's `citation` in `style`
+    pub fn citation(mut self, style: CitationStyle) -> Self {
+        self.options.citation = Some(style);
+        self
+    }
+
+    /// Check the options and return them
+    pub fn build(self) -> SearchResult<SearchOptions> {
+        let options = self.options;
+        let invalid = |message: String| Err(SearchError::InvalidOptions(message));
+
+        if options.query.trim().is_empty() {
+            return invalid("query is empty".to_string());
+        }
+        if options.limit == Some(0) {
+            return invalid("limit must be at least 1".to_string());
+        }
+        if let Some(min_score) = options.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return invalid(format!("min_score {min_score} is outside 0–1"));
+            }
+        }
+        if let Some(filter) = options.filter.as_ref() {
+            filter.validate()?;
+        }
+        let parse_date = |name: &str, date: Option<&str>| match date {
+            None => Ok(None),
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| {
+                    SearchError::InvalidOptions(format!("{name} '{date}' is not YYYY-MM-DD"))
+                }),
+        };
+        let from = parse_date("date_from", options.date_from.as_deref())?;
+        let to = parse_date("date_to", options.date_to.as_deref())?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return invalid(format!("date_from {from} is after date_to {to}"));
+            }
+        }
+        if options.group_by == Some(GroupBy::Doc) && options.group_children == Some(0) {
+            return invalid("group_by_doc needs at least 1 child".to_string());
+        }
+        if options.timeout_ms == Some(0) {
+            return invalid("timeout_ms must be at least 1".to_string());
+        }
+        if options.collection.as_deref().is_some_and(str::is_empty) {
+            return invalid("collection name is empty".to_string());
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_sets_options() {
+        let options = SearchOptions::builder()
+            .query("release checklist")
+            .mode(SearchMode::Hybrid)
+            .limit(20)
+            .path_prefix("projects/")
+            .group_by_doc(2)
+            .fields(["file_path", "snippet"])
+            .doc_type("doc")
+            .filter(Filter::Tag("release".to_string()))
+            .sort_by(SortBy::Modified, SortOrder::Desc)
+            .build()
+            .unwrap();
+        assert_eq!(options.query, "release checklist");
+        assert_eq!(options.limit(), 20);
+        assert_eq!(options.mode(), SearchMode::Hybrid);
+        assert_eq!(options.folder_filter.as_deref(), Some("projects/"));
+        assert_eq!(options.aggregate_by(), AggregateBy::Doc);
+        assert_eq!(options.group_children(), 2);
+        assert_eq!(options.sort_by, Some(SortBy::Modified));
+        assert_eq!(options.fields.unwrap(), vec!["file_path", "snippet"]);
+        assert_eq!(
+            options.filter,
+            Some(Filter::And(vec![
+                Filter::DocType("doc".to_string()),
+                Filter::Tag("release".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_options() {
+        let rejected = |builder: SearchOptionsBuilder| {
+            matches!(builder.build(), Err(SearchError::InvalidOptions(_)))
+        };
+        let query = || SearchOptions::builder().query("plans");
+        assert!(rejected(SearchOptions::builder().query("  ")));
+        assert!(rejected(query().limit(0)));
+        assert!(rejected(query().min_score(1.5)));
+        assert!(rejected(query().doc_type("note")));
+        assert!(rejected(query().date_from("2024-13-01")));
+        assert!(rejected(
+            query().date_from("2024-06-01").date_to("2024-01-01")
+        ));
+        assert!(!rejected(
+            query().date_from("2024-01-01").date_to("2024-06-01")
+        ));
+    }
+}
@@ -0,0 +1,383 @@
+//! Staged index build pipeline
+//!
+//! scan → chunk → embed → write. This module holds the chunking stage:
+//! documents are read and chunked on blocking worker threads and handed on
+//! in their original order through a bounded channel, so a slow embedding
+//! API stalls the readers instead of letting chunked text pile up. The
+//! embed and write stages live on [`Indexer`](super::Indexer), which owns
+//! the stores.
+
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use super::chunker::Chunker;
+use super::doc_meta;
+use super::indexer::{extract_idea_box, Indexer};
+use super::types::{Chunk, DocMeta};
+use crate::embeds::Transcluder;
+use crate::git_history::REPOS_DIR;
+use crate::ideas::parse_idea_entries;
+use crate::memory::{parse_memory_entries, MEMORY_DIR};
+use crate::sessions::SESSIONS_DIR;
+
+/// A document after the chunking stage
+pub(super) struct ChunkedDoc {
+    pub rel_path: String,
+    /// Empty for missing, unreadable or blank files
+    pub chunks: Vec<Chunk>,
+    /// Body checksum, `None` when there was nothing to index
+    pub checksum: Option<String>,
+    /// Document stats, `None` when there was nothing to index
+    pub meta: Option<DocMeta>,
+}
+
+/// Split a document into index chunks: one per entry for idea boxes,
+/// Markdown sections otherwise
+pub(super) fn doc_chunks(chunker: &Chunker, rel_path: &str, content: &str) -> Vec<Chunk> {
+    let mut chunks = split_doc(chunker, rel_path, content);
+    assign_ids(rel_path, &mut chunks);
+    chunks
+}
+
+/// Give each chunk a content-addressed id, `<rel_path>#<hash>`, where the
+/// hash covers the heading path and text. Ids don't move when lines are
+/// added elsewhere in the document, so fusion, windows and vector reuse can
+/// key on them. Repeats of an identical section get `-2`, `-3`, ... in order.
+fn assign_ids(rel_path: &str, chunks: &mut [Chunk]) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for chunk in chunks {
+        let mut hasher = Sha256::new();
+        hasher.update(rel_path.as_bytes());
+        hasher.update([0]);
+        hasher.update(chunk.heading_path.as_bytes());
+        hasher.update([0]);
+        hasher.update(chunk.content.as_bytes());
+        let digest = hex::encode(&hasher.finalize()[..8]);
+        let n = seen.entry(digest.clone()).or_insert(0);
+        *n += 1;
+        chunk.id = if *n == 1 {
+            format!("{rel_path}#{digest}")
+        } else {
+            format!("{rel_path}#{digest}-{n}")
+        };
+    }
+}
+
+fn split_doc(chunker: &Chunker, rel_path: &str, content: &str) -> Vec<Chunk> {
+    if rel_path.starts_with(".ideas/") {
+        let idea_box = extract_idea_box(rel_path);
+        return parse_idea_entries(content)
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let entry_date = entry.created_at.get(0..10).unwrap_or("").to_string();
+                let title_line = entry
+                    .content
+                    .split('\n')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+                Chunk {
+                    id: String::new(),
+                    file_path: rel_path.to_string(),
+                    content: entry.content,
+                    heading_path: String::new(),
+                    section_title: (!title_line.is_empty()).then_some(title_line),
+                    doc_type: Some("idea".to_string()),
+                    entry_id: Some(entry.id),
+                    entry_date: (!entry_date.is_empty()).then_some(entry_date),
+                    entry_created_at: Some(entry.created_at),
+                    idea_box: idea_box.clone(),
+                    chunk_index: i,
+                    vector: vec![],
+                }
+            })
+            .collect();
+    }
+    if rel_path.starts_with(MEMORY_DIR) {
+        let now = chrono::Utc::now();
+        return parse_memory_entries(content)
+            .into_iter()
+            .filter(|entry| !entry.is_expired(now))
+            .enumerate()
+            .map(|(i, entry)| {
+                let entry_date = entry.created_at.get(0..10).unwrap_or("").to_string();
+                // Tags as hashtags so `tag:` filters match memories too
+                let content = if entry.tags.is_empty() {
+                    entry.content
+                } else {
+                    let tags: Vec<String> = entry.tags.iter().map(|t| format!("#{t}")).collect();
+                    format!("{}\n\n{}", entry.content, tags.join(" "))
+                };
+                Chunk {
+                    id: String::new(),
+                    file_path: rel_path.to_string(),
+                    content,
+                    heading_path: String::new(),
+                    section_title: None,
+                    doc_type: Some("memory".to_string()),
+                    entry_id: Some(entry.id),
+                    entry_date: (!entry_date.is_empty()).then_some(entry_date),
+                    entry_created_at: Some(entry.created_at),
+                    idea_box: None,
+                    chunk_index: i,
+                    vector: vec![],
+                }
+            })
+            .collect();
+    }
+    let doc_type = if rel_path.starts_with(SESSIONS_DIR) {
+        "session"
+    } else if rel_path.starts_with(REPOS_DIR) {
+        if rel_path.contains("/prs/") {
+            "pull_request"
+        } else {
+            "commit"
+        }
+    } else {
+        "doc"
+    };
+    chunker
+        .chunk(content, rel_path)
+        .into_iter()
+        .enumerate()
+        .map(|(i, text_chunk)| Chunk {
+            id: String::new(),
+            file_path: rel_path.to_string(),
+            content: text_chunk.content,
+            heading_path: text_chunk.heading_path,
+            section_title: None,
+            doc_type: Some(doc_type.to_string()),
+            entry_id: None,
+            entry_date: None,
+            entry_created_at: None,
+            idea_box: None,
+            chunk_index: i,
+            vector: vec![],
+        })
+        .collect()
+}
+
+/// Chunks of the document at `abs_path`, with the notes it embeds expanded
+/// into its text
+fn read_and_chunk(
+    chunker: &Chunker,
+    transcluder: &Transcluder,
+    rel_path: String,
+    abs_path: PathBuf,
+) -> ChunkedDoc {
+    let content = match std::fs::read_to_string(&abs_path) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("Skipping {} during indexing: {}", rel_path, e);
+            String::new()
+        }
+    };
+    if content.trim().is_empty() {
+        return ChunkedDoc {
+            rel_path,
+            chunks: Vec::new(),
+            checksum: None,
+            meta: None,
+        };
+    }
+    let indexed = transcluder.expand(&rel_path, &content);
+    let chunks = doc_chunks(chunker, &rel_path, &indexed);
+    ChunkedDoc {
+        meta: Some(doc_meta::compute(
+            &rel_path,
+            &content,
+            chunks.len(),
+            &abs_path,
+        )),
+        checksum: Some(Indexer::body_checksum(&indexed)),
+        chunks,
+        rel_path,
+    }
+}
+
+/// Read and chunk `docs` (`(rel_path, abs_path)`) on `workers` blocking
+/// threads, expanding embeds with `transcluder`. Results arrive in input order; at most `queue_depth` wait in
+/// the channel, and dropping the receiver stops the workers.
+pub(super) fn spawn_chunker(
+    docs: Vec<(String, PathBuf)>,
+    chunker: Chunker,
+    transcluder: Arc<Transcluder>,
+    workers: usize,
+    queue_depth: usize,
+) -> mpsc::Receiver<ChunkedDoc> {
+    let (tx, rx) = mpsc::channel(queue_depth.max(1));
+    tokio::spawn(async move {
+        let mut chunked = futures::stream::iter(docs)
+            .map(|(rel_path, abs_path)| {
+                let chunker = chunker.clone();
+                let transcluder = Arc::clone(&transcluder);
+                tokio::task::spawn_blocking(move || {
+                    read_and_chunk(&chunker, &transcluder, rel_path, abs_path)
+                })
+            })
+            .buffered(workers.max(1));
+        while let Some(result) = chunked.next().await {
+            match result {
+                Ok(doc) => {
+                    if tx.send(doc).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::warn!("[Indexer] Chunking worker failed: {}", e),
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chunker_keeps_order_and_skips_blank_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut docs = Vec::new();
+        for i in 0..20 {
+            let rel_path = format!("doc{i:02}.md");
+            let content = if i == 7 {
+                "  \n".to_string()
+            } else {
+                format!("# Doc {i}\n\nBody of document {i}.\n")
+            };
+            std::fs::write(dir.path().join(&rel_path), content).unwrap();
+            docs.push((rel_path.clone(), dir.path().join(&rel_path)));
+        }
+        docs.push(("gone.md".to_string(), dir.path().join("gone.md")));
+
+        let transcluder = Arc::new(Transcluder::new(docs.clone()));
+        let mut rx = spawn_chunker(docs, Chunker::default(), transcluder, 3, 2);
+        let mut seen = Vec::new();
+        while let Some(doc) = rx.recv().await {
+            let blank = doc.rel_path == "doc07.md" || doc.rel_path == "gone.md";
+            assert_eq!(doc.chunks.is_empty(), blank, "{}", doc.rel_path);
+            assert_eq!(doc.checksum.is_none(), blank);
+            assert_eq!(
+                doc.meta.map(|m| m.chunks),
+                (!blank).then_some(doc.chunks.len())
+            );
+            seen.push(doc.rel_path);
+        }
+        assert_eq!(seen.len(), 21);
+        assert!(seen[..20].windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(seen[20], "gone.md");
+    }
+
+    #[test]
+    fn test_chunk_ids_survive_line_shifts() {
+        let chunker = Chunker::default();
+        let alpha = "## Alpha\n\nFirst section, long enough to stand as a chunk of its own.\n";
+        let beta = "## Beta\n\nSecond section, also long enough to stand on its own.\n";
+        let before = format!("# Notes\n\n{alpha}\n{beta}");
+        let after = format!(
+            "# Notes\n\nA new intro paragraph\nover two lines.\n\n{alpha}\n{}",
+            beta.replace("also", "now edited and")
+        );
+        let ids = |content: &str| -> Vec<(String, String)> {
+            doc_chunks(&chunker, "notes.md", content)
+                .into_iter()
+                .map(|c| (c.content, c.id))
+                .collect()
+        };
+        let (before, after) = (ids(&before), ids(&after));
+        let id_of = |chunks: &[(String, String)], text: &str| {
+            chunks
+                .iter()
+                .find(|(content, _)| content.contains(text))
+                .map(|(_, id)| id.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            id_of(&before, "First section"),
+            id_of(&after, "First section")
+        );
+        assert_ne!(
+            id_of(&before, "Second section"),
+            id_of(&after, "Second section")
+        );
+        assert!(id_of(&after, "First section").starts_with("notes.md#"));
+
+        let mut twins = vec![before[0].clone(), before[0].clone()]
+            .into_iter()
+            .map(|(content, _)| Chunk {
+                id: String::new(),
+                file_path: "notes.md".to_string(),
+                content,
+                heading_path: String::new(),
+                section_title: None,
+                doc_type: None,
+                entry_id: None,
+                entry_date: None,
+                entry_created_at: None,
+                idea_box: None,
+                chunk_index: 0,
+                vector: vec![],
+            })
+            .collect::<Vec<_>>();
+        assign_ids("notes.md", &mut twins);
+        assert_eq!(twins[1].id, format!("{}-2", twins[0].id));
+    }
+
+    #[test]
+    fn test_embedded_sections_are_indexed_with_the_host() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        std::fs::write(
+            path("host.md"),
+            "# Host
+
+![[glossary#Terms]]
+",
+        )
+        .unwrap();
+        std::fs::write(
+            path("glossary.md"),
+            "# Glossary
+
+## Terms
+
+A quokka is a marsupial.
+",
+        )
+        .unwrap();
+        let transcluder =
+            Transcluder::new(["host.md", "glossary.md"].map(|name| (name.to_string(), path(name))));
+
+        let host = read_and_chunk(
+            &Chunker::default(),
+            &transcluder,
+            "host.md".into(),
+            path("host.md"),
+        );
+        assert!(host.chunks.iter().any(|c| c.content.contains("quokka")));
+
+        std::fs::write(
+            path("glossary.md"),
+            "# Glossary
+
+## Terms
+
+A quokka is a small marsupial.
+",
+        )
+        .unwrap();
+        let edited = read_and_chunk(
+            &Chunker::default(),
+            &transcluder,
+            "host.md".into(),
+            path("host.md"),
+        );
+        assert_ne!(host.checksum, edited.checksum);
+    }
+}
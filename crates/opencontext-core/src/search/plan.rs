@@ -0,0 +1,437 @@
+//! Dry-run planning and cost estimates for index builds
+//!
+//! Works out what `build_smart` or a dirty-file update would chunk, embed
+//! and delete without opening the vector or keyword stores, so nothing on
+//! disk is created or changed. Token counts are estimates from per-tokenizer
+//! character ratios, not real tokenizer runs.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::chunker::Chunker;
+use super::config::SearchConfig;
+use super::indexer::{self, IndexChanges, Indexer};
+use super::late_interaction;
+use crate::ideas::parse_idea_entries;
+use crate::memory::{parse_memory_entries, MEMORY_DIR};
+use crate::RelPath;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    Add,
+    Modify,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedFile {
+    pub rel_path: String,
+    pub action: PlanAction,
+    pub chunks: usize,
+    pub tokens: usize,
+}
+
+/// Tokenizer families the supported embedding providers use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenizerFamily {
+    /// OpenAI `text-embedding-3-*` and `ada-002`
+    Cl100k,
+    /// DashScope `text-embedding-v*` and other Qwen-based models
+    Qwen,
+    /// BERT-style models served locally (bge, nomic, e5, ...)
+    WordPiece,
+}
+
+impl TokenizerFamily {
+    pub const ALL: [TokenizerFamily; 3] = [Self::Cl100k, Self::Qwen, Self::WordPiece];
+
+    /// Family the given embedding model tokenizes with
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+        if model.starts_with("text-embedding-3") || model.starts_with("text-embedding-ada") {
+            Self::Cl100k
+        } else if model.starts_with("text-embedding-v") || model.contains("qwen") {
+            Self::Qwen
+        } else {
+            Self::WordPiece
+        }
+    }
+
+    /// Estimated token count of `text`: Latin characters per token and
+    /// tokens per CJK character differ between families
+    pub fn estimate(self, text: &str) -> usize {
+        let (chars_per_token, tokens_per_cjk) = match self {
+            Self::Cl100k => (4.0, 1.0),
+            Self::Qwen => (4.0, 0.7),
+            Self::WordPiece => (3.5, 1.0),
+        };
+        let mut cjk = 0usize;
+        let mut other = 0usize;
+        for c in text.chars() {
+            if matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF)
+            {
+                cjk += 1;
+            } else {
+                other += 1;
+            }
+        }
+        (cjk as f64 * tokens_per_cjk + other as f64 / chars_per_token).ceil() as usize
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenCount {
+    pub tokenizer: TokenizerFamily,
+    pub tokens: usize,
+}
+
+/// Projected size and price of embedding a set of files
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexCostEstimate {
+    pub files: usize,
+    pub chunks: usize,
+    /// Texts sent to the embedding model: chunks plus multi-vector windows
+    pub embedding_inputs: usize,
+    /// Estimated tokens under every tokenizer family, for comparing providers
+    pub tokens: Vec<TokenCount>,
+    pub model: String,
+    /// Family of the configured model; `estimated_tokens` and the cost use it
+    pub tokenizer: TokenizerFamily,
+    pub estimated_tokens: usize,
+    /// `None` when the model's price is unknown (see
+    /// `embedding.price_per_million_tokens`)
+    pub estimated_cost_usd: Option<f64>,
+    /// LLM calls for contextual retrieval, one per chunk
+    pub llm_calls: usize,
+}
+
+/// What an index run would do
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexPlan {
+    /// "full" | "incremental" | "dirty"
+    pub mode: String,
+    /// Why a full rebuild is needed
+    pub reason: Option<String>,
+    /// Files to embed or delete; unchanged files are only counted
+    pub files: Vec<PlannedFile>,
+    pub changes: IndexChanges,
+    /// Cost of the files to embed
+    pub estimate: IndexCostEstimate,
+}
+
+struct Planner<'a> {
+    config: &'a SearchConfig,
+    chunker: Chunker,
+    /// Tokens per family, in `TokenizerFamily::ALL` order
+    tokens: [usize; 3],
+    plan: IndexPlan,
+}
+
+impl<'a> Planner<'a> {
+    fn new(config: &'a SearchConfig, mode: &str, reason: Option<String>) -> Self {
+        Self {
+            config,
+            chunker: Chunker::new(config.search.chunk_size, config.search.chunk_overlap),
+            tokens: [0; 3],
+            plan: IndexPlan {
+                mode: mode.to_string(),
+                reason,
+                files: Vec::new(),
+                changes: IndexChanges::default(),
+                estimate: IndexCostEstimate {
+                    files: 0,
+                    chunks: 0,
+                    embedding_inputs: 0,
+                    tokens: Vec::new(),
+                    model: config.embedding.model.clone(),
+                    tokenizer: TokenizerFamily::for_model(&config.embedding.model),
+                    estimated_tokens: 0,
+                    estimated_cost_usd: None,
+                    llm_calls: 0,
+                },
+            },
+        }
+    }
+
+    /// Chunk `content` the way the indexer would and count what gets embedded
+    fn embed(&mut self, rel_path: &str, content: &str, action: PlanAction) {
+        let title = crate::relpath::display_name(rel_path);
+        let chunks: Vec<(String, String)> = if rel_path.starts_with(".ideas/") {
+            parse_idea_entries(content)
+                .into_iter()
+                .map(|e| (e.content, String::new()))
+                .collect()
+        } else if rel_path.starts_with(MEMORY_DIR) {
+            parse_memory_entries(content)
+                .into_iter()
+                .map(|e| (e.content, String::new()))
+                .collect()
+        } else {
+            self.chunker
+                .chunk(content, rel_path)
+                .into_iter()
+                .map(|c| (c.content, c.heading_path))
+                .collect()
+        };
+
+        let mv = &self.config.multi_vector;
+        let estimate = &mut self.plan.estimate;
+        let mut inputs: Vec<String> = Vec::new();
+        for (text, heading_path) in chunks.iter() {
+            // Same shaping as `Indexer::shaped_text`
+            inputs.push(if !self.config.embedding.include_heading_context {
+                text.clone()
+            } else if heading_path.is_empty() {
+                format!("{title}\n\n{text}")
+            } else {
+                format!("{title} > {heading_path}\n\n{text}")
+            });
+            if mv.enabled {
+                inputs.extend(late_interaction::windows(
+                    text,
+                    mv.window_chars,
+                    mv.stride_chars,
+                ));
+            }
+        }
+        let mut tokens = 0;
+        for (i, family) in TokenizerFamily::ALL.into_iter().enumerate() {
+            let count: usize = inputs.iter().map(|t| family.estimate(t)).sum();
+            self.tokens[i] += count;
+            if family == estimate.tokenizer {
+                tokens = count;
+            }
+        }
+        if self.config.contextual.enabled {
+            estimate.llm_calls += chunks.len();
+        }
+        estimate.files += 1;
+        estimate.chunks += chunks.len();
+        estimate.embedding_inputs += inputs.len();
+        estimate.estimated_tokens += tokens;
+        match action {
+            PlanAction::Add => self.plan.changes.added += 1,
+            PlanAction::Modify => self.plan.changes.modified += 1,
+            PlanAction::Delete => {}
+        }
+        self.plan.files.push(PlannedFile {
+            rel_path: rel_path.to_string(),
+            action,
+            chunks: chunks.len(),
+            tokens,
+        });
+    }
+
+    fn delete(&mut self, rel_path: &str) {
+        self.plan.changes.deleted += 1;
+        self.plan.files.push(PlannedFile {
+            rel_path: rel_path.to_string(),
+            action: PlanAction::Delete,
+            chunks: 0,
+            tokens: 0,
+        });
+    }
+
+    fn finish(mut self) -> IndexPlan {
+        let estimate = &mut self.plan.estimate;
+        estimate.tokens = TokenizerFamily::ALL
+            .into_iter()
+            .zip(self.tokens)
+            .map(|(tokenizer, tokens)| TokenCount { tokenizer, tokens })
+            .collect();
+        estimate.estimated_cost_usd = self
+            .config
+            .embedding
+            .price_per_million_tokens()
+            .map(|price| estimate.estimated_tokens as f64 / 1_000_000.0 * price);
+        self.plan
+    }
+}
+
+/// Plan `Indexer::build_smart` over `docs`
+pub fn plan_build(config: &SearchConfig, docs: &[crate::Doc], force: bool) -> IndexPlan {
+    let reason = if force {
+        Some("--force".to_string())
+    } else if !config.paths.get_index_metadata_path().exists() {
+        Some("no index yet".to_string())
+    } else if indexer::embedding_settings_changed(config) {
+        Some("embedding settings changed since the last build".to_string())
+    } else {
+        None
+    };
+    let full = reason.is_some();
+    let old_checksums = indexer::load_checksums(config);
+    let mut planner = Planner::new(config, if full { "full" } else { "incremental" }, reason);
+
+    let transcluder = Indexer::transcluder(docs);
+    let mut current = HashSet::new();
+    for doc in docs {
+        let Ok(content) = std::fs::read_to_string(&doc.abs_path) else {
+            continue;
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        current.insert(doc.rel_path.as_str());
+        let content = transcluder.expand(&doc.rel_path, &content);
+        let action = match old_checksums.get(&doc.rel_path) {
+            None => PlanAction::Add,
+            Some(_) if full => PlanAction::Modify,
+            Some(old) if *old != Indexer::body_checksum(&content) => PlanAction::Modify,
+            Some(_) => {
+                planner.plan.changes.unchanged += 1;
+                continue;
+            }
+        };
+        planner.embed(&doc.rel_path, &content, action);
+    }
+
+    let mut gone: Vec<&String> = old_checksums
+        .keys()
+        .filter(|p| !current.contains(p.as_str()))
+        .collect();
+    gone.sort();
+    for rel_path in gone {
+        planner.delete(rel_path);
+    }
+    planner.finish()
+}
+
+/// Plan re-indexing exactly `embed` and dropping `delete`, as
+/// `oc index update --dirty` does
+pub fn plan_files(
+    config: &SearchConfig,
+    contexts_root: &Path,
+    embed: &[String],
+    delete: &[String],
+) -> IndexPlan {
+    let old_checksums = indexer::load_checksums(config);
+    let mut planner = Planner::new(config, "dirty", None);
+    for rel_path in embed {
+        let Ok(content) = std::fs::read_to_string(RelPath::new(rel_path).to_path(contexts_root))
+        else {
+            continue;
+        };
+        let action = if old_checksums.contains_key(rel_path) {
+            PlanAction::Modify
+        } else {
+            PlanAction::Add
+        };
+        planner.embed(rel_path, &content, action);
+    }
+    for rel_path in delete {
+        planner.delete(rel_path);
+    }
+    planner.finish()
+}
+
+/// Chunk, token and cost estimate for embedding `paths` (files or folders
+/// relative to `contexts_root`; an empty path means everything). Folders
+/// are scanned with the given symlink policy.
+pub fn estimate_index_cost(
+    config: &SearchConfig,
+    contexts_root: &Path,
+    symlinks: crate::SymlinkPolicy,
+    paths: &[String],
+) -> crate::CoreResult<IndexCostEstimate> {
+    let mut files = Vec::new();
+    for path in paths {
+        let rel_path = RelPath::new(path);
+        let abs_path = rel_path.to_path(contexts_root);
+        if abs_path.is_dir() {
+            crate::scan_md_files(&abs_path, contexts_root, symlinks, &mut files)?;
+        } else if abs_path.is_file() {
+            files.push(rel_path.into_string());
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    let mut planner = Planner::new(config, "estimate", None);
+    for rel_path in files {
+        if let Ok(content) = std::fs::read_to_string(RelPath::new(&rel_path).to_path(contexts_root))
+        {
+            planner.embed(&rel_path, &content, PlanAction::Add);
+        }
+    }
+    Ok(planner.finish().estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenizer_estimates() {
+        let cl100k = TokenizerFamily::Cl100k;
+        assert_eq!(cl100k.estimate(""), 0);
+        assert_eq!(cl100k.estimate("abcdefgh"), 2);
+        assert_eq!(cl100k.estimate("abcde"), 2);
+        assert_eq!(cl100k.estimate("中文ab"), 3);
+        assert_eq!(TokenizerFamily::Qwen.estimate("中文中文中文中文中文"), 7);
+        assert_eq!(
+            TokenizerFamily::for_model("text-embedding-3-small"),
+            TokenizerFamily::Cl100k
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("text-embedding-v4"),
+            TokenizerFamily::Qwen
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("nomic-embed-text"),
+            TokenizerFamily::WordPiece
+        );
+    }
+
+    #[test]
+    fn test_plan_files_counts_chunks_and_cost() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = SearchConfig::default();
+        config.paths.index_metadata_path = Some(dir.path().join("index/meta.json"));
+        config.embedding.model = "text-embedding-3-small".to_string();
+        std::fs::write(
+            dir.path().join("a.md"),
+            "# A\n\nFirst section.\n\n## B\n\nSecond section.\n",
+        )
+        .unwrap();
+
+        let plan = plan_files(
+            &config,
+            dir.path(),
+            &["a.md".to_string(), "missing.md".to_string()],
+            &["old.md".to_string()],
+        );
+        assert_eq!(plan.changes.added, 1);
+        assert_eq!(plan.changes.deleted, 1);
+        let estimate = &plan.estimate;
+        assert_eq!(plan.files[0].chunks, estimate.chunks);
+        assert!(estimate.chunks >= 1 && estimate.estimated_tokens > 0);
+        assert_eq!(estimate.embedding_inputs, estimate.chunks);
+        assert_eq!(estimate.tokens.len(), 3);
+        assert_eq!(estimate.tokens[0].tokens, estimate.estimated_tokens);
+        let cost = estimate.estimated_cost_usd.unwrap();
+        assert!((cost - estimate.estimated_tokens as f64 * 0.02 / 1e6).abs() < 1e-12);
+        assert!(!dir.path().join("index").exists());
+
+        std::fs::create_dir_all(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/b.md"), "# B\n\nMore text.\n").unwrap();
+        let all =
+            estimate_index_cost(&config, dir.path(), Default::default(), &[String::new()]).unwrap();
+        assert_eq!(all.files, 2);
+        let sub = estimate_index_cost(
+            &config,
+            dir.path(),
+            Default::default(),
+            &["sub".to_string()],
+        )
+        .unwrap();
+        assert_eq!(sub.files, 1);
+        assert!(sub.estimated_tokens < all.estimated_tokens);
+    }
+}
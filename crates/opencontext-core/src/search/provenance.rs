@@ -0,0 +1,154 @@
+//! Chunk provenance
+//!
+//! Not every indexed text was written by the user: some is clipped from the
+//! web, imported from another app, synced from a tracker or transcribed
+//! from a terminal recording. While chunking, the indexer records how each
+//! document was produced (see [`Provenance`]) next to its stats. The
+//! searcher puts it on every result, refined per chunk where one document
+//! mixes origins, so callers can label or down-weight text nobody typed.
+//!
+//! The adapter is read from the workspace layout and from the frontmatter
+//! the importers write (`source`, `tracker`, `citekey`, `repo`, `url`). A
+//! document can state its own origin with an `origin` field, e.g.
+//! `origin: ocr` for a scan run through OCR outside OpenContext.
+
+use serde_json::Value;
+
+use super::chunker::CHUNKER_VERSION;
+use super::types::Provenance;
+use crate::capture::INBOX_DIR;
+use crate::git_history::REPOS_DIR;
+use crate::ideas::IDEAS_DIR;
+use crate::memory::MEMORY_DIR;
+use crate::sessions::SESSIONS_DIR;
+
+/// Provenance of the document at workspace path `rel_path`
+pub(super) fn detect(rel_path: &str, content: &str) -> Provenance {
+    let fields = crate::frontmatter::fields(content);
+    let field = |key: &str| match fields.get(key) {
+        Some(Value::String(value)) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        _ => None,
+    };
+    let in_inbox = rel_path
+        .split_once('/')
+        .is_some_and(|(folder, _)| folder == INBOX_DIR);
+
+    let source = if rel_path.starts_with(IDEAS_DIR) {
+        "idea".to_string()
+    } else if rel_path.starts_with(MEMORY_DIR) {
+        "memory".to_string()
+    } else if rel_path.starts_with(SESSIONS_DIR) {
+        "session".to_string()
+    } else if rel_path.starts_with(REPOS_DIR) {
+        "git".to_string()
+    } else if let Some(tracker) = field("tracker") {
+        tracker
+    } else if fields.contains_key("citekey") {
+        "bibtex".to_string()
+    } else if let Some(source) = field("source") {
+        source
+    } else if fields.contains_key("repo") {
+        "github".to_string()
+    } else if in_inbox {
+        "capture".to_string()
+    } else {
+        "markdown".to_string()
+    };
+    let source_url = field("url");
+    let origin = field("origin").or_else(|| match source.as_str() {
+        "session" if content.contains(" · asciinema\n") => Some("transcript".to_string()),
+        // Notes with a source page were saved by a web clipper
+        "evernote" | "apple-notes" if source_url.is_some() => Some("web_clip".to_string()),
+        _ => None,
+    });
+    let chunker = match source.as_str() {
+        "idea" | "memory" => "entries".to_string(),
+        _ => format!("markdown/{CHUNKER_VERSION}"),
+    };
+    Provenance {
+        source,
+        origin,
+        source_url,
+        chunker,
+    }
+}
+
+/// Provenance of one chunk of a document with provenance `doc`. In inbox
+/// notes each capture is its own section, so a section headed
+/// `14:32 · url` is a web clip of the page on its `Source:` line.
+pub(super) fn for_chunk(doc: &Provenance, heading_path: Option<&str>, content: &str) -> Provenance {
+    let clipped = doc.source == "capture"
+        && heading_path
+            .and_then(|path| path.rsplit(" > ").next())
+            .is_some_and(|heading| heading.ends_with(" · url"));
+    if !clipped {
+        return doc.clone();
+    }
+    let url = content.lines().rev().find_map(|line| {
+        let url = line.trim().strip_prefix("Source: ")?;
+        Some(
+            url.trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string(),
+        )
+    });
+    Provenance {
+        origin: Some("web_clip".to_string()),
+        source_url: url.or_else(|| doc.source_url.clone()),
+        ..doc.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_provenance() {
+        let note = detect("projects/plan.md", "# Plan\n");
+        assert_eq!(note.source, "markdown");
+        assert_eq!(note.origin, None);
+        assert_eq!(note.chunker, format!("markdown/{CHUNKER_VERSION}"));
+
+        let clip = detect(
+            "imported/recipe.md",
+            "---\nsource: evernote\nurl: https://example.com/recipe\n---\n# Recipe\n",
+        );
+        assert_eq!(clip.source, "evernote");
+        assert_eq!(clip.origin.as_deref(), Some("web_clip"));
+        assert_eq!(
+            clip.source_url.as_deref(),
+            Some("https://example.com/recipe")
+        );
+
+        let ticket = detect("tickets/ENG-1.md", "---\ntracker: jira\n---\n# ENG-1\n");
+        assert_eq!(ticket.source, "jira");
+        let scan = detect("scans/receipt.md", "---\norigin: ocr\n---\nTOTAL 12.00\n");
+        assert_eq!(
+            (scan.source.as_str(), scan.origin.as_deref()),
+            ("markdown", Some("ocr"))
+        );
+        let recording = detect(
+            ".sessions/2024-03-01-deploy.md",
+            "# deploy\n\nRecorded 2024-03-01 10:00 · asciinema\n\n```text\n$ make\n```\n",
+        );
+        assert_eq!(recording.origin.as_deref(), Some("transcript"));
+        assert_eq!(detect(".ideas/inbox.md", "").chunker, "entries");
+    }
+
+    #[test]
+    fn test_capture_sections_from_urls_are_web_clips() {
+        let inbox = detect("inbox/2024-03-01.md", "## 14:32 · url\n\nText\n");
+        assert_eq!(inbox.source, "capture");
+        assert_eq!(inbox.origin, None);
+
+        let clip = for_chunk(
+            &inbox,
+            Some("14:32 · url"),
+            "Text\n\nSource: <https://example.com/page>",
+        );
+        assert_eq!(clip.origin.as_deref(), Some("web_clip"));
+        assert_eq!(clip.source_url.as_deref(), Some("https://example.com/page"));
+        assert_eq!(for_chunk(&inbox, Some("14:40 · clipboard"), "Text"), inbox);
+    }
+}
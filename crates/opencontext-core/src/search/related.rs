@@ -0,0 +1,136 @@
+//! Related-query suggestions
+//!
+//! Terms that co-occur with the query in the top results, weighted against
+//! their corpus document frequency (pseudo-relevance feedback), plus earlier
+//! queries from this session that share a word with the current one.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Remembered queries per searcher
+pub const HISTORY_LEN: usize = 200;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "this", "that", "with", "from", "they", "will", "would",
+    "there", "their", "what", "about", "which", "when", "make", "like", "time", "just", "into",
+    "than", "then", "them", "these", "some", "could", "other", "also", "only", "more", "most",
+    "such", "should", "each", "were", "been", "being", "does", "how", "why", "who", "its", "use",
+    "using", "used", "may", "via", "per", "yes", "etc",
+];
+
+/// Lowercase content words of `text`
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
+
+/// Terms appearing in at least two of `contents` but not in the query,
+/// scored by `hits_containing * ln(total_docs / (doc_freq + 1))`.
+/// `doc_freqs` maps candidate words to `(total_docs, doc_freq per word)`.
+pub fn cooccurring_terms(
+    query: &str,
+    contents: &[&str],
+    doc_freqs: impl FnOnce(&[&str]) -> Option<(u64, Vec<u64>)>,
+    limit: usize,
+) -> Vec<String> {
+    let query_words: HashSet<String> = words(query).collect();
+    let mut hits_containing: HashMap<String, u64> = HashMap::new();
+    for content in contents {
+        let unique: HashSet<String> = words(content).collect();
+        for word in unique {
+            if !query_words.contains(&word) {
+                *hits_containing.entry(word).or_default() += 1;
+            }
+        }
+    }
+
+    let candidates: Vec<(&str, u64)> = hits_containing
+        .iter()
+        .filter(|(_, n)| **n >= 2)
+        .map(|(w, n)| (w.as_str(), *n))
+        .collect();
+    if candidates.is_empty() {
+        return vec![];
+    }
+    let names: Vec<&str> = candidates.iter().map(|(w, _)| *w).collect();
+    let Some((total_docs, freqs)) = doc_freqs(&names) else {
+        return vec![];
+    };
+
+    let total = total_docs.max(1) as f64;
+    let mut scored: Vec<(f64, &str)> = candidates
+        .iter()
+        .zip(freqs)
+        .map(|((word, n), df)| {
+            let idf = (total / (df as f64 + 1.0)).ln().max(0.0);
+            (*n as f64 * idf, *word)
+        })
+        .filter(|(score, _)| *score > 0.0)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, w)| w.to_string())
+        .collect()
+}
+
+/// Most recent earlier queries sharing a word with `query`
+pub fn from_history(query: &str, history: &VecDeque<String>, limit: usize) -> Vec<String> {
+    let query_words: HashSet<String> = words(query).collect();
+    let normalized = query.trim().to_lowercase();
+    let mut seen = HashSet::new();
+    history
+        .iter()
+        .rev()
+        .filter(|past| past.trim().to_lowercase() != normalized)
+        .filter(|past| words(past).any(|w| query_words.contains(&w)))
+        .filter(|past| seen.insert(past.to_lowercase()))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooccurring_terms_prefer_rare_words() {
+        let contents = [
+            "Vector index uses LanceDB for storage",
+            "LanceDB stores the vector embeddings",
+            "the vector store is rebuilt on schema change",
+            "storage layout of the vector store",
+        ];
+        let df = |words: &[&str]| {
+            let freqs = words
+                .iter()
+                .map(|w| match *w {
+                    "lancedb" => 3,
+                    "storage" => 40,
+                    "store" => 60,
+                    _ => 100,
+                })
+                .collect();
+            Some((100, freqs))
+        };
+        let terms = cooccurring_terms("vector", &contents, df, 3);
+        assert_eq!(terms[0], "lancedb");
+        assert!(!terms.contains(&"vector".to_string()));
+        assert!(!terms.contains(&"the".to_string()));
+    }
+
+    #[test]
+    fn test_history_shares_a_word() {
+        let history: VecDeque<String> = ["index rebuild", "vector search", "Vector Search", "todo"]
+            .map(String::from)
+            .into();
+        assert_eq!(
+            from_history("vector weights", &history, 5),
+            vec!["Vector Search".to_string()]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Query intent classification for `mode = "auto"`
+//!
+//! Cheap heuristics decide most queries; anything they cannot place is either
+//! sent to the LLM (when `[router] llm = true`) or treated as a general query.
+
+use serde::Serialize;
+
+/// What the user is most likely trying to do with a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryIntent {
+    /// Looking for a specific document by name ("open the auth design doc")
+    Navigational,
+    /// An exact identifier, path, error code or quoted string
+    Identifier,
+    /// A natural-language question about a concept
+    Conceptual,
+    /// Short keyword query with no strong signal
+    General,
+}
+
+impl QueryIntent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryIntent::Navigational => "navigational",
+            QueryIntent::Identifier => "identifier",
+            QueryIntent::Conceptual => "conceptual",
+            QueryIntent::General => "general",
+        }
+    }
+
+    /// Parse an intent name, e.g. from an LLM reply
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().trim_matches(|c: char| !c.is_alphabetic());
+        match s.to_lowercase().as_str() {
+            "navigational" => Some(QueryIntent::Navigational),
+            "identifier" => Some(QueryIntent::Identifier),
+            "conceptual" => Some(QueryIntent::Conceptual),
+            "general" => Some(QueryIntent::General),
+            _ => None,
+        }
+    }
+}
+
+/// Prompt used when the heuristics are inconclusive
+pub const CLASSIFY_PROMPT: &str = "Classify the search query into exactly one word: \
+navigational (user wants a specific document by title or path), \
+identifier (exact code symbol, error code, path or literal string), \
+conceptual (natural-language question about a topic), \
+or general (anything else). Reply with the single word only.";
+
+/// Prompt for HyDE: a hypothetical answer passage embedded alongside the query
+pub const HYDE_PROMPT: &str = "Write a short passage (3-5 sentences) that answers the \
+question the way it might be written in the user's own notes. No preamble, no caveats.";
+
+const NAV_PREFIXES: &[&str] = &[
+    "open ",
+    "go to ",
+    "goto ",
+    "show me ",
+    "find the ",
+    "where is ",
+    "jump to ",
+    "打开",
+    "找到",
+];
+
+const NAV_NOUNS: &[&str] = &[
+    "doc", "docs", "document", "note", "notes", "file", "page", "spec", "文档", "笔记",
+];
+
+const QUESTION_WORDS: &[&str] = &[
+    "how",
+    "why",
+    "what",
+    "when",
+    "which",
+    "who",
+    "explain",
+    "should",
+    "can",
+    "does",
+    "is",
+    "are",
+    "compare",
+    "difference",
+];
+
+const CJK_QUESTION_MARKERS: &[&str] = &["如何", "为什么", "什么", "怎么", "怎样", "是否", "吗"];
+
+/// Classify a query with heuristics only. Returns `None` when no rule fires
+/// and the query is long enough that an LLM might do better.
+pub fn classify(query: &str) -> Option<QueryIntent> {
+    let q = query.trim();
+    let lower = q.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+
+    if lower.ends_with(".md") {
+        return Some(QueryIntent::Navigational);
+    }
+
+    if is_identifier(q) {
+        return Some(QueryIntent::Identifier);
+    }
+
+    if NAV_PREFIXES.iter().any(|p| lower.starts_with(p))
+        || (words.len() <= 5 && words.last().is_some_and(|w| NAV_NOUNS.contains(w)))
+    {
+        return Some(QueryIntent::Navigational);
+    }
+
+    if q.ends_with('?')
+        || q.ends_with('？')
+        || words
+            .first()
+            .is_some_and(|w| QUESTION_WORDS.contains(&w.trim_end_matches('\'')))
+        || CJK_QUESTION_MARKERS.iter().any(|m| q.contains(m))
+    {
+        return Some(QueryIntent::Conceptual);
+    }
+
+    if words.len() <= 3 {
+        return Some(QueryIntent::General);
+    }
+
+    None
+}
+
+/// Whether the query looks like an exact token rather than prose
+fn is_identifier(q: &str) -> bool {
+    let quoted = q.len() > 2
+        && ((q.starts_with('"') && q.ends_with('"')) || (q.starts_with('`') && q.ends_with('`')));
+    if quoted {
+        return true;
+    }
+    if q.contains(char::is_whitespace) || q.chars().count() < 3 {
+        return false;
+    }
+
+    let has_separator = ["_", "::", "->", "/", "#", "()", "."]
+        .iter()
+        .any(|s| q.contains(s));
+    let chars: Vec<char> = q.chars().collect();
+    let camel = chars
+        .windows(2)
+        .any(|w| w[0].is_lowercase() && w[1].is_uppercase());
+    let letters_and_digits =
+        chars.iter().any(|c| c.is_ascii_digit()) && chars.iter().any(|c| c.is_ascii_alphabetic());
+    let shouting = chars.len() >= 3
+        && chars
+            .iter()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || *c == '_');
+
+    has_separator || camel || letters_and_digits || shouting
+}
+
+/// Strip navigational filler ("open the ... doc") leaving the name terms
+pub fn navigational_terms(query: &str) -> Vec<String> {
+    let mut lower = query.trim().to_lowercase();
+    for prefix in NAV_PREFIXES {
+        if let Some(rest) = lower.strip_prefix(prefix) {
+            lower = rest.to_string();
+            break;
+        }
+    }
+    lower
+        .split(|c: char| c.is_whitespace() || c == '/' || c == '-' || c == '_')
+        .map(|w| w.trim_end_matches(".md"))
+        .filter(|w| {
+            !w.is_empty() && !NAV_NOUNS.contains(w) && !["the", "a", "an", "my", "our"].contains(w)
+        })
+        .map(|w| w.to_string())
+        .collect()
+}
+
+/// Remove surrounding quotes/backticks from an identifier query
+pub fn unquote(query: &str) -> &str {
+    query.trim().trim_matches(|c| c == '"' || c == '`')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifiers() {
+        for q in [
+            "parse_idea_entries",
+            "SearchError::Index",
+            "ERR_CONN_RESET",
+            "getUserById",
+            "v1.2.3",
+            "\"exact phrase here\"",
+        ] {
+            assert_eq!(classify(q), Some(QueryIntent::Identifier), "{q}");
+        }
+    }
+
+    #[test]
+    fn test_navigational() {
+        assert_eq!(
+            classify("open the auth design doc"),
+            Some(QueryIntent::Navigational)
+        );
+        assert_eq!(classify("roadmap.md"), Some(QueryIntent::Navigational));
+        assert_eq!(
+            navigational_terms("open the auth design doc"),
+            vec!["auth", "design"]
+        );
+    }
+
+    #[test]
+    fn test_conceptual_and_general() {
+        assert_eq!(
+            classify("how does index sync handle renames"),
+            Some(QueryIntent::Conceptual)
+        );
+        assert_eq!(
+            classify("为什么要用向量检索"),
+            Some(QueryIntent::Conceptual)
+        );
+        assert_eq!(classify("roadmap"), Some(QueryIntent::General));
+        assert_eq!(classify("vector store migration plan notes draft"), None);
+        assert_eq!(
+            QueryIntent::parse(" Conceptual."),
+            Some(QueryIntent::Conceptual)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Vector table schema versions
+//!
+//! Every LanceDB table records the schema version it was last upgraded to
+//! in `schema.json` inside the database directory. Opening a table applies
+//! each migration newer than that version. A migration adds columns and
+//! fills them for existing rows with a SQL expression, so indexes written by
+//! older releases are upgraded in place instead of rebuilt.
+//!
+//! Tables without a recorded version predate versioning and start at 0.
+//! Columns that are already present are skipped, so replaying migrations on
+//! such a table is harmless.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{SearchError, SearchResult};
+
+/// Schema version of the tables this build writes. Bump together with a new
+/// entry in [`MIGRATIONS`].
+pub const SCHEMA_VERSION: u32 = 3;
+
+pub(super) const CHUNKS_TABLE: &str = "chunks";
+pub(super) const WINDOWS_TABLE: &str = "chunk_windows";
+
+const MANIFEST_FILE: &str = "schema.json";
+const NULL_TEXT: &str = "CAST(NULL AS VARCHAR)";
+
+pub(super) struct Migration {
+    pub version: u32,
+    pub table: &'static str,
+    /// `(column, SQL expression filling existing rows)`
+    pub columns: &'static [(&'static str, &'static str)],
+}
+
+/// Every schema change since the first release, oldest first
+pub(super) const MIGRATIONS: &[Migration] = &[
+    // Section titles and idea-box metadata
+    Migration {
+        version: 1,
+        table: CHUNKS_TABLE,
+        columns: &[
+            ("section_title", NULL_TEXT),
+            (
+                "doc_type",
+                "CASE WHEN file_path LIKE '.ideas/%' THEN 'idea' ELSE 'doc' END",
+            ),
+            ("entry_id", NULL_TEXT),
+            ("entry_date", NULL_TEXT),
+            ("entry_created_at", NULL_TEXT),
+            ("idea_box", NULL_TEXT),
+        ],
+    },
+    // Collections; existing rows belong to the default one
+    Migration {
+        version: 2,
+        table: CHUNKS_TABLE,
+        columns: &[("collection", "'default'")],
+    },
+    Migration {
+        version: 2,
+        table: WINDOWS_TABLE,
+        columns: &[("collection", "'default'")],
+    },
+    // Compressed chunk text; existing rows keep theirs in `content`
+    Migration {
+        version: 3,
+        table: CHUNKS_TABLE,
+        columns: &[("content_zstd", "CAST(NULL AS BINARY)")],
+    },
+];
+
+/// Recorded schema version of each table
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct SchemaManifest {
+    #[serde(default)]
+    tables: BTreeMap<String, u32>,
+}
+
+impl SchemaManifest {
+    fn path(db_path: &Path) -> PathBuf {
+        db_path.join(MANIFEST_FILE)
+    }
+
+    /// Read the manifest; missing or unreadable means nothing is recorded
+    pub fn load(db_path: &Path) -> Self {
+        std::fs::read_to_string(Self::path(db_path))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, db_path: &Path) -> SearchResult<()> {
+        std::fs::create_dir_all(db_path)?;
+        std::fs::write(Self::path(db_path), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn version(&self, table: &str) -> u32 {
+        self.tables.get(table).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, table: &str, version: u32) {
+        self.tables.insert(table.to_string(), version);
+    }
+
+    pub fn remove(&mut self, table: &str) {
+        self.tables.remove(table);
+    }
+}
+
+/// Refuse tables written by a newer release rather than guess at their layout
+pub(super) fn check_supported(table: &str, version: u32) -> SearchResult<()> {
+    if version > SCHEMA_VERSION {
+        return Err(SearchError::VectorStore(format!(
+            "table '{table}' uses schema v{version}, but this version of OpenContext \
+             only supports up to v{SCHEMA_VERSION}. Upgrade OpenContext, or run \
+             `oc index build --force` to rebuild the index."
+        )));
+    }
+    Ok(())
+}
+
+/// Columns to add to bring `table` from version `from` to [`SCHEMA_VERSION`],
+/// leaving out those `has_column` reports as present
+pub(super) fn pending_columns(
+    table: &str,
+    from: u32,
+    has_column: impl Fn(&str) -> bool,
+) -> Vec<(String, String)> {
+    MIGRATIONS
+        .iter()
+        .filter(|m| m.table == table && m.version > from)
+        .flat_map(|m| m.columns.iter())
+        .filter(|(column, _)| !has_column(column))
+        .map(|(column, expr)| (column.to_string(), expr.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::DEFAULT_COLLECTION;
+
+    const FIRST_RELEASE: &[&str] = &[
+        "id",
+        "file_path",
+        "content",
+        "heading_path",
+        "chunk_index",
+        "vector",
+    ];
+
+    fn names(columns: &[(String, String)]) -> Vec<&str> {
+        columns.iter().map(|(c, _)| c.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pending_columns_upgrade_first_release_table() {
+        let pending = pending_columns(CHUNKS_TABLE, 0, |c| FIRST_RELEASE.contains(&c));
+        assert_eq!(
+            names(&pending),
+            vec![
+                "section_title",
+                "doc_type",
+                "entry_id",
+                "entry_date",
+                "entry_created_at",
+                "idea_box",
+                "collection",
+                "content_zstd"
+            ]
+        );
+        // An unversioned table that already has collections only gets the
+        // columns it lacks
+        let pending = pending_columns(CHUNKS_TABLE, 0, |c| {
+            FIRST_RELEASE.contains(&c) || c == "collection"
+        });
+        assert!(!names(&pending).contains(&"collection"));
+        assert_eq!(
+            names(&pending_columns(WINDOWS_TABLE, 1, |_| false)),
+            vec!["collection"]
+        );
+        assert!(pending_columns(CHUNKS_TABLE, SCHEMA_VERSION, |_| false).is_empty());
+    }
+
+    #[test]
+    fn test_migrations_are_ordered_and_current() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version <= w[1].version));
+        assert_eq!(MIGRATIONS.last().unwrap().version, SCHEMA_VERSION);
+        let collection_fill = format!("'{DEFAULT_COLLECTION}'");
+        assert!(MIGRATIONS
+            .iter()
+            .flat_map(|m| m.columns.iter())
+            .filter(|(c, _)| *c == "collection")
+            .all(|(_, expr)| *expr == collection_fill));
+    }
+
+    #[test]
+    fn test_manifest_round_trip_and_newer_tables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("lancedb");
+        assert_eq!(SchemaManifest::load(&db_path).version(CHUNKS_TABLE), 0);
+
+        let mut manifest = SchemaManifest::default();
+        manifest.set(CHUNKS_TABLE, SCHEMA_VERSION);
+        manifest.save(&db_path).unwrap();
+        let loaded = SchemaManifest::load(&db_path);
+        assert_eq!(loaded.version(CHUNKS_TABLE), SCHEMA_VERSION);
+        assert_eq!(loaded.version(WINDOWS_TABLE), 0);
+
+        assert!(check_supported(CHUNKS_TABLE, SCHEMA_VERSION).is_ok());
+        assert!(check_supported(CHUNKS_TABLE, SCHEMA_VERSION + 1).is_err());
+    }
+}
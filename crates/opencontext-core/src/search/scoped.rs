@@ -0,0 +1,94 @@
+//! Folder-scoped searchers
+//!
+//! An agent working on one project should only ever see that project's
+//! notes. [`Searcher::scoped`] returns a borrowed view that adds the folder
+//! to every query; the folder is pushed down into the LanceDB filter and the
+//! BM25 query, so the view costs nothing to create and returns full result
+//! pages even for small subtrees.
+
+use super::error::SearchResult;
+use super::searcher::{in_folder, Searcher};
+use super::types::{SearchOptions, SearchResults};
+use tokio_util::sync::CancellationToken;
+
+/// A view of a [`Searcher`] restricted to one folder subtree
+pub struct ScopedSearcher<'a> {
+    searcher: &'a Searcher,
+    prefix: String,
+}
+
+impl<'a> ScopedSearcher<'a> {
+    pub(crate) fn new(searcher: &'a Searcher, prefix: &str) -> Self {
+        Self {
+            searcher,
+            prefix: prefix.trim_matches('/').to_string(),
+        }
+    }
+
+    /// Folder this view is restricted to ("" for the whole workspace)
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Narrow the view to a subfolder, given relative to this view's folder
+    pub fn scoped(&self, subfolder: &str) -> ScopedSearcher<'a> {
+        ScopedSearcher::new(self.searcher, &resolve(&self.prefix, Some(subfolder)))
+    }
+
+    /// Execute a search within the folder. A `folder_filter` in `options`
+    /// narrows the scope further and is read relative to it, unless it
+    /// already names a folder inside the scope.
+    pub async fn search(&self, options: SearchOptions) -> SearchResult<SearchResults> {
+        self.searcher.search(self.restrict(options)).await
+    }
+
+    /// [`search`](Self::search) that stops as soon as `cancel` fires
+    pub async fn search_with_cancel(
+        &self,
+        options: SearchOptions,
+        cancel: &CancellationToken,
+    ) -> SearchResult<SearchResults> {
+        self.searcher
+            .search_with_cancel(self.restrict(options), cancel)
+            .await
+    }
+
+    fn restrict(&self, mut options: SearchOptions) -> SearchOptions {
+        options.folder_filter = Some(resolve(&self.prefix, options.folder_filter.as_deref()));
+        options
+    }
+}
+
+/// A caller's folder filter mapped into the scope `prefix`
+fn resolve(prefix: &str, folder: Option<&str>) -> String {
+    let folder = folder.unwrap_or("").trim_matches('/');
+    if folder.is_empty() {
+        prefix.to_string()
+    } else if prefix.is_empty() || in_folder(folder, prefix) {
+        folder.to_string()
+    } else {
+        format!("{prefix}/{folder}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_folder() {
+        assert!(in_folder("proj/a.md", "proj"));
+        assert!(in_folder("proj/sub/a.md", "proj/"));
+        assert!(in_folder("proj", "proj"));
+        assert!(!in_folder("project/a.md", "proj"));
+        assert!(in_folder("anything.md", ""));
+    }
+
+    #[test]
+    fn test_resolve_folder_filter() {
+        assert_eq!(resolve("proj", None), "proj");
+        assert_eq!(resolve("proj", Some("docs/")), "proj/docs");
+        assert_eq!(resolve("proj", Some("proj/docs")), "proj/docs");
+        assert_eq!(resolve("", Some("other")), "other");
+    }
+}
@@ -1,56 +1,423 @@
 //! Search executor
 //! Aligned with Node.js searcher.js implementation
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
+use arc_swap::ArcSwap;
+use parking_lot::Mutex;
+
+use tokio_util::sync::CancellationToken;
+
+use super::api::API_SCHEMA_VERSION;
 use super::bm25_store::Bm25Store;
+use super::boilerplate;
+use super::chunker::Chunker;
+use super::citation;
+use super::confidence;
 use super::config::SearchConfig;
+use super::context;
+use super::crosslang;
+use super::dedup;
+use super::doc_meta;
 use super::embedding::EmbeddingClient;
-use super::error::SearchResult;
-use super::types::{AggregateBy, MatchType, SearchHit, SearchMode, SearchOptions, SearchResults};
+use super::ephemeral::EphemeralIndex;
+use super::error::{SearchError, SearchResult};
+use super::facets;
+use super::filter::{Filter, FilterContext};
+use super::folder_summary::{self, SummaryCache};
+use super::indexer;
+use super::late_interaction;
+use super::llm::LlmClient;
+use super::matryoshka;
+use super::memory::{self, Limits, Lru};
+use super::provenance;
+use super::related;
+use super::router::{self, QueryIntent};
+use super::scoped::ScopedSearcher;
+use super::slowlog::{self, SlowQueryEntry, Stage, StageTimings};
+use super::sort;
+use super::sparse_embedding::SparseEmbeddingClient;
+use super::sparse_store::SparseStore;
+use super::spelling::{self, SpellIndex};
+use super::tokenizer;
+use super::trust;
+use super::types::{
+    AggregateBy, ContextDoc, EmbeddingTier, MatchType, MemoryReport, NoResultsDiagnostics,
+    Provenance, Readiness, SearchHit, SearchMode, SearchOptions, SearchResults, SortBy, Suggestion,
+};
 use super::vector_store::VectorStore;
+use crate::events::{HealthEvent, SharedEventBus};
+use crate::names;
+use crate::relpath;
+use crate::RelPath;
 
 /// RRF constant, typically 60
-const RRF_K: f32 = 60.0;
-
-/// Vector search weight in hybrid mode
-const VECTOR_WEIGHT: f32 = 0.7;
-
-/// Keyword search weight in hybrid mode  
-const KEYWORD_WEIGHT: f32 = 0.3;
+pub(super) const RRF_K: f32 = 60.0;
 
 /// Search executor
 pub struct Searcher {
-    #[allow(dead_code)]
     config: SearchConfig,
+    /// Current index snapshot, replaced by [`Searcher::refresh`]
+    indexes: ArcSwap<Indexes>,
+    /// Shared with ephemeral indexes opened from this searcher
+    embedding_client: Arc<EmbeddingClient>,
+    /// Searcher over the fast-tier index alone, for fast-tier queries; opened on first use
+    fast_view: tokio::sync::Mutex<Option<Arc<Searcher>>>,
+    /// Chat model for LLM-assisted routing / HyDE, present when enabled in `[router]`
+    llm: Option<LlmClient>,
+    /// Spelling index built from the BM25 vocabulary, keyed by the index
+    /// opstamp; kept only while it fits the keyword share
+    spell: Mutex<Lru<u64, Arc<SpellIndex>>>,
+    /// Query embeddings by tier and normalized query text
+    query_vectors: Mutex<VectorCache>,
+    /// Chunks of recently expanded documents by path, tagged with the BM25
+    /// opstamp they were read at
+    file_chunks: Mutex<(u64, ChunkCache)>,
+    /// Recent queries, oldest first, for related-query suggestions
+    history: Mutex<VecDeque<String>>,
+    /// Boilerplate scores by chunk id, tagged with the score file's mtime
+    boilerplate: Mutex<Option<(SystemTime, Arc<boilerplate::Scores>)>>,
+    /// Document stats by path, tagged with the index metadata's mtime
+    doc_meta: Mutex<Option<(SystemTime, Arc<doc_meta::Stats>)>>,
+    /// Searchers for other collections, opened on first use
+    collections: tokio::sync::Mutex<HashMap<String, Arc<Searcher>>>,
+    /// Where health events go, see [`Searcher::with_event_bus`]
+    event_bus: Option<SharedEventBus>,
+    /// When embedding last failed, while the provider counts as down
+    provider_down: Mutex<Option<Instant>>,
+}
+
+/// The open indexes of a searcher. A search loads the current snapshot once
+/// and passes it to every stage, so a refresh never blocks queries and a
+/// query running across one sees only the indexes it started with.
+struct Indexes {
     vector_store: VectorStore,
-    embedding_client: EmbeddingClient,
     bm25_store: Bm25Store,
+    /// Sparse embedding client and store, present when `[sparse]` is enabled
+    sparse: Option<(SparseEmbeddingClient, SparseStore)>,
+    /// Fast-tier embedding client and store, present when `[embedding.tiers]` is enabled
+    fast: Option<(EmbeddingClient, VectorStore)>,
+    /// Truncated vectors, present when `[vector_index] search_dimensions` is set
+    prefix: Option<VectorStore>,
 }
 
-impl Searcher {
-    /// Create a new searcher
-    pub async fn new(config: SearchConfig) -> SearchResult<Self> {
+impl Indexes {
+    async fn open(config: &SearchConfig) -> SearchResult<Self> {
         let lancedb_path = config.paths.get_lancedb_path();
         let bm25_path = config.paths.get_bm25_path();
         let dimensions = config.embedding.dimensions;
 
-        let mut vector_store = VectorStore::new(lancedb_path, dimensions);
+        let mut vector_store =
+            VectorStore::new(lancedb_path, dimensions).with_collection(config.paths.collection());
         vector_store.initialize().await?;
 
-        let embedding_client = EmbeddingClient::new(config.embedding.clone())?;
-        let bm25_store = Bm25Store::open(bm25_path)?;
+        let bm25_store = Bm25Store::open_with(bm25_path, &config.tokenizer)?;
+
+        let sparse = if config.sparse.enabled {
+            Some((
+                SparseEmbeddingClient::new(config.sparse.clone())?,
+                SparseStore::open(config.paths.get_sparse_path())?,
+            ))
+        } else {
+            None
+        };
+
+        let fast = if config.embedding.tiers.enabled {
+            let mut store = VectorStore::new(
+                config.paths.get_fast_lancedb_path(),
+                config.embedding.tiers.dimensions,
+            )
+            .with_collection(config.paths.collection());
+            store.initialize().await?;
+            Some((EmbeddingClient::new(config.embedding.fast_tier())?, store))
+        } else {
+            None
+        };
+
+        let prefix = match config
+            .vector_index
+            .prefix_dimensions(config.embedding.dimensions)
+        {
+            Some(dimensions) => {
+                let mut store =
+                    VectorStore::new(config.paths.get_prefix_lancedb_path(), dimensions)
+                        .with_collection(config.paths.collection());
+                store.initialize().await?;
+                Some(store)
+            }
+            None => None,
+        };
 
         Ok(Self {
-            config,
             vector_store,
-            embedding_client,
             bm25_store,
+            sparse,
+            fast,
+            prefix,
         })
     }
+}
+
+/// What the stages of one search share: the index snapshot it loaded and
+/// where they record their timings
+#[derive(Clone, Copy)]
+struct Run<'a> {
+    indexes: &'a Indexes,
+    timings: &'a StageTimings,
+}
+
+type VectorCache = Lru<(EmbeddingTier, String), Arc<Vec<f32>>>;
+type ChunkCache = Lru<String, Arc<Vec<SearchHit>>>;
+
+/// How long searches skip a provider that is down before trying it again
+const PROVIDER_RETRY: Duration = Duration::from_secs(30);
+
+/// What a failed search attempt rules out for the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// The embedding provider or the vector index; only keyword search works
+    Vectors,
+    /// Some other stage, such as the sparse encoder; any other mode may work
+    Stage,
+}
+
+/// The modes of `chain` to fall back to when `mode` fails: those listed
+/// after it, or all others if it is not listed
+fn fallbacks_after(chain: &[SearchMode], mode: SearchMode) -> Vec<SearchMode> {
+    match chain.iter().position(|m| *m == mode) {
+        Some(i) => chain[i + 1..].to_vec(),
+        None => chain.iter().copied().filter(|m| *m != mode).collect(),
+    }
+}
+
+/// Score `candidates` recalled from the fast-tier or prefix index by cosine
+/// similarity between `query_vector` and their full vectors and keep the
+/// best `limit`. Candidates missing from the main index are dropped.
+fn rescore(
+    candidates: Vec<SearchHit>,
+    query_vector: &[f32],
+    vectors: &HashMap<String, Vec<f32>>,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = candidates
+        .into_iter()
+        .filter_map(|mut hit| {
+            let vector = vectors.get(hit.chunk_id.as_ref()?)?;
+            hit.score = late_interaction::cosine(query_vector, vector).clamp(0.0, 1.0);
+            Some(hit)
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    hits.truncate(limit);
+    hits
+}
+
+impl Searcher {
+    /// Create a new searcher
+    pub async fn new(config: SearchConfig) -> SearchResult<Self> {
+        let indexes = Indexes::open(&config).await?;
+        let embedding_client = Arc::new(EmbeddingClient::new(config.embedding.clone())?);
+
+        let translate = config.cross_language.enabled && config.cross_language.translate_keyword;
+        let llm = if config.router.llm
+            || config.router.hyde
+            || translate
+            || config.search.folder_summary_llm
+        {
+            match LlmClient::new(&config.llm, &config.embedding) {
+                Ok(client) => Some(client),
+                Err(e) => {
+                    log::warn!("[Searcher] LLM features disabled: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let limits = Limits::new(&config.memory);
+        Ok(Self {
+            config,
+            indexes: ArcSwap::from_pointee(indexes),
+            embedding_client,
+            fast_view: tokio::sync::Mutex::new(None),
+            llm,
+            spell: Mutex::new(Lru::new(limits.keyword_index)),
+            query_vectors: Mutex::new(Lru::new(limits.embedding_cache)),
+            file_chunks: Mutex::new((0, Lru::new(limits.chunk_cache))),
+            history: Mutex::new(VecDeque::new()),
+            boilerplate: Mutex::new(None),
+            doc_meta: Mutex::new(None),
+            collections: tokio::sync::Mutex::new(HashMap::new()),
+            event_bus: None,
+            provider_down: Mutex::new(None),
+        })
+    }
+
+    /// Current index snapshot
+    fn indexes(&self) -> Arc<Indexes> {
+        self.indexes.load_full()
+    }
+
+    /// Reopen the indexes, after a rebuild or clean replaced them on disk,
+    /// and drop what was cached from the old ones. Queries keep running on
+    /// the old snapshot until the new one is open.
+    pub async fn refresh(&self) -> SearchResult<()> {
+        let indexes = Indexes::open(&self.config).await?;
+        self.indexes.store(Arc::new(indexes));
+        self.spell.lock().clear();
+        self.file_chunks.lock().1.clear();
+        *self.fast_view.lock().await = None;
+        self.collections.lock().await.clear();
+        Ok(())
+    }
+
+    /// Report health changes ([`HealthEvent`]) on `event_bus`: the embedding
+    /// provider going down or coming back, and an unreadable vector index.
+    /// Either way searches fall back to keyword-only results, marked
+    /// `degraded`.
+    pub fn with_event_bus(mut self, event_bus: SharedEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Open the vector table, touch the ANN index and keyword index so the
+    /// first real query is fast, and optionally ping the embedding endpoint.
+    pub async fn warm_up(&self, ping_embedding: bool) -> Readiness {
+        let indexes = self.indexes();
+        let started = Instant::now();
+        let mut readiness = Readiness {
+            index_exists: indexes.vector_store.exists().await,
+            ..Default::default()
+        };
+
+        if readiness.index_exists {
+            match indexes.vector_store.count().await {
+                Ok(count) => readiness.vector_chunks = count,
+                Err(e) => readiness.error = Some(e.to_string()),
+            }
+            let probe = vec![0.0; indexes.vector_store.dimensions()];
+            if let Err(e) = indexes.vector_store.search(&probe, 1).await {
+                readiness.error.get_or_insert(e.to_string());
+            }
+        } else {
+            readiness.error = Some(SearchError::IndexNotBuilt.to_string());
+        }
+
+        match indexes.bm25_store.count() {
+            Ok(count) => readiness.keyword_chunks = count,
+            Err(e) => {
+                readiness.error.get_or_insert(e.to_string());
+            }
+        }
+        // Mapped from disk when the indexer saved it, built otherwise
+        self.spell_index(&indexes);
+
+        if ping_embedding {
+            let result = self.embedding_client.embed_one("warm up").await;
+            readiness.embedding_ok = Some(result.is_ok());
+            if let Err(e) = result {
+                readiness.error.get_or_insert(e.to_string());
+            }
+        }
+
+        readiness.ready = readiness.error.is_none();
+        readiness.elapsed_ms = started.elapsed().as_millis() as u64;
+        readiness
+    }
+
+    /// A view of this searcher that only sees files under `prefix`
+    /// (e.g. "Product/opencontext"); creating one is free
+    pub fn scoped(&self, prefix: &str) -> ScopedSearcher<'_> {
+        ScopedSearcher::new(self, prefix)
+    }
+
+    /// An empty in-memory index for session-scoped texts, sharing this
+    /// searcher's embedding client and keyword analyzer settings
+    pub fn ephemeral(&self) -> EphemeralIndex {
+        EphemeralIndex::with_client(self.config.clone(), Arc::clone(&self.embedding_client))
+    }
+
+    /// Autocomplete a partially typed query from titles, headings and tags
+    pub fn suggest(&self, prefix: &str, limit: usize) -> SearchResult<Vec<Suggestion>> {
+        self.indexes().bm25_store.suggest(prefix, limit)
+    }
 
     /// Execute a search
     pub async fn search(&self, options: SearchOptions) -> SearchResult<SearchResults> {
+        self.search_with_cancel(options, &CancellationToken::new())
+            .await
+    }
+
+    /// Execute a search that stops as soon as `cancel` fires (e.g. the user
+    /// kept typing). In-flight embedding requests and vector queries are
+    /// dropped and `SearchError::Cancelled` is returned.
+    pub async fn search_with_cancel(
+        &self,
+        options: SearchOptions,
+        cancel: &CancellationToken,
+    ) -> SearchResult<SearchResults> {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(SearchError::Cancelled),
+            results = self.run_search(options, &[]) => results,
+        }
+    }
+
+    /// Execute a search that also ranks by similarity to `extra_docs`: texts
+    /// the caller has at hand but that are not in the index, such as the
+    /// unsaved document open in the editor. They are embedded for this query
+    /// only and never stored.
+    pub async fn search_with_context(
+        &self,
+        options: SearchOptions,
+        extra_docs: Vec<ContextDoc>,
+    ) -> SearchResult<SearchResults> {
+        self.run_search(options, &extra_docs).await
+    }
+
+    async fn run_search(
+        &self,
+        mut options: SearchOptions,
+        extra_docs: &[ContextDoc],
+    ) -> SearchResult<SearchResults> {
+        // Folder filters may arrive in Windows form (`notes\\2024`)
+        options.folder_filter = options
+            .folder_filter
+            .map(|f| RelPath::new(&f).into_string());
+        if let Some(allowed) = options.allowed_folders.as_mut() {
+            for folder in allowed.iter_mut() {
+                *folder = RelPath::new(folder).into_string();
+            }
+        }
+        // Every stage reads the same indexes, whatever a refresh swaps in meanwhile
+        let snapshot = self.indexes();
+        let indexes: &Indexes = &snapshot;
+        if let Some(collection) = options.collection.as_deref() {
+            if collection != self.config.paths.collection() {
+                let searcher = self.collection_searcher(collection).await?;
+                return Box::pin(searcher.run_search(options, extra_docs)).await;
+            }
+        }
+        let tier = options.tier.unwrap_or(self.config.embedding.tiers.default);
+        if tier == EmbeddingTier::Fast {
+            if let Some(searcher) = self.fast_searcher(indexes).await? {
+                return Box::pin(searcher.run_search(options, extra_docs)).await;
+            }
+        }
+
+        let started = Instant::now();
+        let timings = StageTimings::default();
+        let run = Run {
+            indexes,
+            timings: &timings,
+        };
         let query = options.query.trim();
 
         if query.is_empty() {
@@ -58,75 +425,203 @@ impl Searcher {
         }
 
         // Check if index exists
-        if !self.vector_store.exists().await {
+        if !indexes.vector_store.exists().await {
             return Ok(SearchResults::index_not_built(query.to_string()));
         }
 
         let limit = options.limit();
-        let mode = options.mode();
+        let mut mode = options.mode();
         let aggregate_by = options.aggregate_by();
 
-        // For aggregation, get more candidates
-        let search_limit = if aggregate_by == AggregateBy::Content {
-            limit
+        // For aggregation, get more candidates; one extra content hit tells us if there are more.
+        // Sorting by anything but relevance also needs a wider pool to sort.
+        let resorted = options.sort_by.is_some_and(|by| by != SortBy::Score);
+        let search_limit = if aggregate_by == AggregateBy::Content && !resorted {
+            limit + 1
         } else {
             limit * 5
         };
 
-        // Execute search based on mode
-        let mut hits = match mode {
-            SearchMode::Vector => self.vector_search(query, search_limit).await?,
-            SearchMode::Keyword => self.keyword_search(query, search_limit),
-            SearchMode::Hybrid => self.hybrid_search(query, search_limit).await?,
+        // Execute search based on mode. When it fails for a reason another
+        // mode may not share, go down `search.fallback`; when the embedding /
+        // vector stage overruns the caller's time budget, use keyword results.
+        let mut degraded = false;
+        let mut fallbacks = fallbacks_after(&self.config.search.fallback, mode).into_iter();
+        if mode != SearchMode::Keyword
+            && self.provider_offline()
+            && fallbacks.as_slice().contains(&SearchMode::Keyword)
+        {
+            degraded = true;
+            mode = SearchMode::Keyword;
+        }
+        let folder = options.folder_filter.as_deref().filter(|p| !p.is_empty());
+        let attempt = |mode: SearchMode| {
+            let (options, timings) = (&options, &timings);
+            async move {
+                let translations = self.translate_query(query, mode, options, timings).await;
+                let (hits, intent) = self
+                    .run_mode(run, query, &translations, folder, mode, search_limit)
+                    .await?;
+                let hits = self
+                    .fuse_context(run, hits, extra_docs, folder, search_limit)
+                    .await?;
+                Ok::<_, SearchError>((hits, intent))
+            }
+        };
+        let (mut hits, intent) = loop {
+            // A fallback to keyword search skips translation and context
+            // documents, which need the providers that just failed
+            if degraded && mode == SearchMode::Keyword {
+                break (self.keyword_search(run, query, folder, search_limit), None);
+            }
+            // `None` when the time budget ran out
+            let outcome = match options.timeout_ms {
+                Some(ms) if mode != SearchMode::Keyword => {
+                    tokio::time::timeout(Duration::from_millis(ms), attempt(mode))
+                        .await
+                        .ok()
+                }
+                _ => Some(attempt(mode).await),
+            };
+            match outcome {
+                Some(Ok(found)) => {
+                    if matches!(mode, SearchMode::Vector | SearchMode::Hybrid) {
+                        self.provider_up();
+                    }
+                    break found;
+                }
+                Some(Err(e)) => {
+                    let Some(failure) = self.report_failure(&e) else {
+                        return Err(e);
+                    };
+                    let next = fallbacks
+                        .by_ref()
+                        .find(|m| failure == Failure::Stage || *m == SearchMode::Keyword);
+                    let Some(next) = next else {
+                        return Err(e);
+                    };
+                    log::warn!(
+                        "[Searcher] {:?} search failed ({}), trying {:?}",
+                        mode,
+                        e,
+                        next
+                    );
+                    degraded = true;
+                    mode = next;
+                }
+                None => {
+                    log::warn!(
+                        "[Searcher] {:?} search exceeded {}ms, using keyword results",
+                        mode,
+                        options.timeout_ms.unwrap_or_default()
+                    );
+                    degraded = true;
+                    mode = SearchMode::Keyword;
+                }
+            }
         };
 
-        if let Some(filter_type) = options.doc_type.as_deref() {
-            hits.retain(|hit| match filter_type {
-                "idea" => hit.doc_type.as_deref() == Some("idea"),
-                "doc" => hit.doc_type.as_deref().unwrap_or("doc") == "doc",
-                _ => true,
-            });
+        let aggregation_started = Instant::now();
+        let retrieved = hits.len();
+        self.penalize_boilerplate(&mut hits);
+        self.apply_trust(&mut hits);
+        let filter = [options.filter.clone(), options.source_filter()]
+            .into_iter()
+            .flatten()
+            .reduce(Filter::and);
+        if let Some(filter) = filter.as_ref() {
+            let stats = self.doc_stats();
+            let ctx = FilterContext::new(&stats, self.config.paths.get_contexts_root());
+            hits.retain(|hit| ctx.matches(filter, hit));
         }
 
-        if let Some(ref prefix) = options.folder_filter {
-            let prefix = prefix.trim_end_matches('/');
-            hits.retain(|hit| {
-                hit.file_path.starts_with(prefix)
-                    && (hit.file_path.len() == prefix.len()
-                        || hit.file_path.as_bytes().get(prefix.len()) == Some(&b'/'))
-            });
+        // Vector and keyword hits are already restricted at the source; sparse ones are not
+        if let Some(prefix) = folder {
+            hits.retain(|hit| in_folder(&hit.file_path, prefix));
+        }
+
+        if let Some(allowed) = options.allowed_folders.as_deref() {
+            hits.retain(|hit| allowed.iter().any(|f| in_folder(&hit.file_path, f)));
         }
 
         if let Some(from) = options.date_from.as_deref() {
-            hits.retain(|hit| hit.entry_date.as_deref().map_or(true, |d| d >= from));
+            hits.retain(|hit| hit.entry_date.as_deref().is_none_or(|d| d >= from));
         }
 
         if let Some(to) = options.date_to.as_deref() {
-            hits.retain(|hit| hit.entry_date.as_deref().map_or(true, |d| d <= to));
+            hits.retain(|hit| hit.entry_date.as_deref().is_none_or(|d| d <= to));
         }
 
         if let Some(min_score) = options.min_score {
             hits.retain(|hit| hit.score >= min_score);
         }
 
+        let (total_candidates, total_exact) =
+            self.estimate_total(indexes, query, mode, &options, &hits);
+
+        let facets = options
+            .facets
+            .unwrap_or(false)
+            .then(|| facets::compute(&hits));
+
+        let related =
+            self.related_queries(indexes, query, &hits, options.allowed_folders.is_some());
+
         // Expand top results with neighboring chunks
         let neighbor_window = options.include_neighbors.unwrap_or(0);
         if neighbor_window > 0 && aggregate_by == AggregateBy::Content {
-            hits = self.expand_with_neighbors(hits, neighbor_window, limit);
+            hits = self.expand_with_neighbors(indexes, hits, neighbor_window, limit);
         }
 
-        // Aggregate results
-        let results = match aggregate_by {
-            AggregateBy::Content => hits.into_iter().take(limit).collect(),
-            AggregateBy::Doc => self.aggregate_by_doc(hits, limit),
-            AggregateBy::Folder => self.aggregate_by_folder(hits, limit),
+        // Aggregate results; duplicates are merged before the cut to `limit`
+        let mut results: Vec<SearchHit> = match aggregate_by {
+            AggregateBy::Content => hits,
+            AggregateBy::Doc => self.aggregate_by_doc(hits, usize::MAX, options.group_children()),
+            AggregateBy::Folder => {
+                let summarize = options.folder_summary.unwrap_or(false);
+                let checksums = summarize.then(|| indexer::load_checksums(&self.config));
+                let mut folders = self.aggregate_by_folder(hits, limit, checksums.as_ref());
+                if let Some(checksums) = checksums.as_ref() {
+                    self.describe_folders(&mut folders, checksums, &timings)
+                        .await;
+                }
+                folders
+            }
         };
+        if self.config.search.dedup && aggregate_by != AggregateBy::Folder {
+            results = self.dedup_results(indexes, results).await;
+        }
+        if let Some(by) = options.sort_by {
+            let order = options.sort_order.unwrap_or_else(|| by.default_order());
+            sort::sort(&mut results, by, order, &self.doc_stats());
+        }
+        results.truncate(limit);
+        self.attach_doc_meta(&mut results);
+        self.attach_display_names(&mut results);
+        attach_block_ids(&mut results, &query_terms(query));
+        if let Some(style) = options.citation {
+            citation::attach(&mut results, style);
+        }
+        let confidence = confidence::assess(query, &results, mode == SearchMode::Hybrid);
+        // Index size and vocabulary are workspace-wide, so a token limited to
+        // some folders gets no diagnosis
+        let diagnostics = (results.is_empty() && options.allowed_folders.is_none())
+            .then(|| self.diagnose_no_results(indexes, query, retrieved));
+
+        if let Some(fields) = options.fields.as_deref() {
+            for hit in &mut results {
+                hit.project(fields);
+            }
+        }
+
+        timings.record(Stage::Aggregation, aggregation_started.elapsed());
 
         // Convert mode and aggregate_by to strings for response
         let mode_str = match mode {
             SearchMode::Vector => "vector",
             SearchMode::Keyword => "keyword",
             SearchMode::Hybrid => "hybrid",
+            SearchMode::Auto => "auto",
         };
         let aggregate_str = match aggregate_by {
             AggregateBy::Content => "content",
@@ -134,114 +629,1176 @@ impl Searcher {
             AggregateBy::Folder => "folder",
         };
 
+        let has_more = total_candidates > results.len();
+        let total_candidates = total_candidates.max(results.len());
+
+        // Corrections come from the whole workspace's vocabulary, so a token
+        // limited to some folders gets none
+        let suggestions = match options.allowed_folders {
+            Some(_) => None,
+            None => self.did_you_mean(indexes, query),
+        };
+
+        self.log_if_slow(&options, mode_str, results.len(), started, &timings);
+
         Ok(SearchResults {
+            schema_version: API_SCHEMA_VERSION,
             query: query.to_string(),
             count: results.len(),
             results,
             mode: Some(mode_str.to_string()),
             aggregate_by: Some(aggregate_str.to_string()),
+            intent: intent.map(|i| i.as_str().to_string()),
+            facets,
+            total_candidates: Some(total_candidates),
+            total_exact: Some(total_exact),
+            has_more: Some(has_more),
+            degraded: degraded.then_some(true),
+            suggestions,
+            related,
+            confidence: Some(confidence),
+            diagnostics,
             index_missing: None,
             error: None,
         })
     }
 
-    /// Perform vector search
-    async fn vector_search(&self, query: &str, limit: usize) -> SearchResult<Vec<SearchHit>> {
-        // Generate query embedding
-        let query_vector = self.embedding_client.embed_one(query).await?;
+    /// Whether the embedding provider failed within the last [`PROVIDER_RETRY`]
+    fn provider_offline(&self) -> bool {
+        self.provider_down
+            .lock()
+            .is_some_and(|since| since.elapsed() < PROVIDER_RETRY)
+    }
+
+    /// Record a successful embedding request
+    fn provider_up(&self) {
+        if self.provider_down.lock().take().is_some() {
+            log::info!("[Searcher] Embedding provider is back");
+            self.emit_health(HealthEvent::EmbeddingProviderRecovered {
+                provider: self.config.embedding.api_base.clone(),
+            });
+        }
+    }
+
+    /// Record a failed search attempt and tell what it rules out, or
+    /// `None` if no other mode would do better (e.g. invalid options)
+    fn report_failure(&self, err: &SearchError) -> Option<Failure> {
+        match err.root() {
+            SearchError::Embedding(_) | SearchError::Http(_) | SearchError::Json(_) => {
+                let provider = &self.config.embedding.api_base;
+                let from_embedding = err
+                    .context()
+                    .and_then(|c| c.provider.as_ref())
+                    .is_some_and(|p| p == provider);
+                if !from_embedding {
+                    // Sparse encoder or LLM; a mode without them may work
+                    return Some(Failure::Stage);
+                }
+                let was_up = self.provider_down.lock().replace(Instant::now()).is_none();
+                if was_up {
+                    self.emit_health(HealthEvent::EmbeddingProviderDown {
+                        provider: provider.clone(),
+                        error: err.to_string(),
+                    });
+                }
+                Some(Failure::Vectors)
+            }
+            SearchError::VectorStore(_) | SearchError::Lance(_) => {
+                self.emit_health(HealthEvent::IndexCorruptionDetected {
+                    index: "vector".to_string(),
+                    path: self.config.paths.get_lancedb_path().display().to_string(),
+                    error: err.to_string(),
+                });
+                Some(Failure::Vectors)
+            }
+            _ => None,
+        }
+    }
+
+    fn emit_health(&self, event: HealthEvent) {
+        if let Some(bus) = &self.event_bus {
+            bus.emit_health(event);
+        }
+    }
+
+    /// Searcher for another collection in the same vector store. It shares
+    /// this searcher's configuration and keeps its own keyword index.
+    async fn collection_searcher(&self, collection: &str) -> SearchResult<Arc<Searcher>> {
+        let mut collections = self.collections.lock().await;
+        if let Some(searcher) = collections.get(collection) {
+            return Ok(searcher.clone());
+        }
+        let mut config = self.config.clone();
+        config.paths.collection = Some(collection.to_string());
+        let mut searcher = Searcher::new(config).await?;
+        if let Some(bus) = &self.event_bus {
+            searcher = searcher.with_event_bus(bus.clone());
+        }
+        let searcher = Arc::new(searcher);
+        collections.insert(collection.to_string(), searcher.clone());
+        Ok(searcher)
+    }
+
+    /// Searcher over the fast-tier index alone, as if the fast model were
+    /// the only one; `None` when tiers are disabled or the fast index is not
+    /// built yet
+    async fn fast_searcher(&self, indexes: &Indexes) -> SearchResult<Option<Arc<Searcher>>> {
+        match indexes.fast.as_ref() {
+            Some((_, store)) if store.exists().await => {}
+            _ => return Ok(None),
+        }
+        let mut view = self.fast_view.lock().await;
+        if let Some(searcher) = view.as_ref() {
+            return Ok(Some(searcher.clone()));
+        }
+        let mut config = self.config.clone();
+        config.paths.lancedb_path = Some(config.paths.get_fast_lancedb_path());
+        config.embedding = config.embedding.fast_tier();
+        config.vector_index.search_dimensions = None;
+        let mut searcher = Searcher::new(config).await?;
+        if let Some(bus) = &self.event_bus {
+            searcher = searcher.with_event_bus(bus.clone());
+        }
+        let searcher = Arc::new(searcher);
+        *view = Some(searcher.clone());
+        Ok(Some(searcher))
+    }
+
+    /// A few related queries: earlier queries sharing a word with this one,
+    /// then the query extended by the most distinctive terms co-occurring
+    /// with it in the top results. Records the query in the history.
+    ///
+    /// The history is shared by every token, so searches limited to some
+    /// folders (`scoped`) neither read nor add to it.
+    fn related_queries(
+        &self,
+        indexes: &Indexes,
+        query: &str,
+        hits: &[SearchHit],
+        scoped: bool,
+    ) -> Option<Vec<String>> {
+        const MAX_RELATED: usize = 5;
+        const FEEDBACK_HITS: usize = 20;
+
+        let mut related = Vec::new();
+        if self.config.search.related_from_history && !scoped {
+            let mut history = self.history.lock();
+            related = related::from_history(query, &history, 2);
+            if !hits.is_empty() {
+                history.retain(|q| q != query);
+                if history.len() == related::HISTORY_LEN {
+                    history.pop_front();
+                }
+                history.push_back(query.to_string());
+            }
+        }
+
+        let contents: Vec<&str> = hits
+            .iter()
+            .take(FEEDBACK_HITS)
+            .map(|h| h.content.as_str())
+            .collect();
+        let terms = related::cooccurring_terms(
+            query,
+            &contents,
+            |words| indexes.bm25_store.doc_freqs(words).ok(),
+            MAX_RELATED,
+        );
+        let base = query.trim();
+        for term in terms {
+            if related.len() >= MAX_RELATED {
+                break;
+            }
+            related.push(format!("{base} {term}"));
+        }
+
+        (!related.is_empty()).then_some(related)
+    }
+
+    /// Down-weight chunks the indexer flagged as boilerplate and re-sort
+    fn penalize_boilerplate(&self, hits: &mut [SearchHit]) {
+        let penalty = self.config.search.boilerplate_penalty;
+        if penalty <= 0.0 {
+            return;
+        }
+        let scores = self.boilerplate_scores();
+        if scores.is_empty() {
+            return;
+        }
+        for hit in hits.iter_mut() {
+            if let Some(bp) = hit.chunk_id.as_deref().and_then(|id| scores.get(id)) {
+                hit.score *= 1.0 - penalty.min(1.0) * bp;
+            }
+        }
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// Weight chunks by the configured trust level of their folder and
+    /// source type and re-sort
+    fn apply_trust(&self, hits: &mut [SearchHit]) {
+        let config = &self.config.trust;
+        if config.is_empty() {
+            return;
+        }
+        let stats = self.doc_stats();
+        for hit in hits.iter_mut() {
+            let provenance = stats
+                .get(&hit.file_path)
+                .and_then(|meta| meta.provenance.as_ref())
+                .map(|doc| provenance::for_chunk(doc, hit.heading_path.as_deref(), &hit.content));
+            let level = trust::level(config, &hit.file_path, provenance.as_ref());
+            hit.score *= level;
+            hit.trust = Some(level);
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    /// Saved boilerplate scores, reloaded when the indexer rewrites them
+    fn boilerplate_scores(&self) -> Arc<boilerplate::Scores> {
+        let path = self.config.paths.get_boilerplate_path();
+        let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+            return Arc::default();
+        };
+        let mut cached = self.boilerplate.lock();
+        if let Some((stamp, scores)) = cached.as_ref() {
+            if *stamp == modified {
+                return scores.clone();
+            }
+        }
+        let scores = Arc::new(boilerplate::load(&path));
+        *cached = Some((modified, scores.clone()));
+        scores
+    }
+
+    /// Give document and chunk results the stats and provenance of their
+    /// document
+    fn attach_doc_meta(&self, hits: &mut [SearchHit]) {
+        let stats = self.doc_stats();
+        if stats.is_empty() {
+            return;
+        }
+        let chunk_provenance = |doc: &Provenance, hit: &SearchHit| {
+            provenance::for_chunk(doc, hit.heading_path.as_deref(), &hit.content)
+        };
+        for hit in hits {
+            if hit.aggregate_type.as_deref() == Some("folder") {
+                continue;
+            }
+            let Some(mut meta) = stats.get(&hit.file_path).cloned() else {
+                continue;
+            };
+            if let Some(doc) = meta.provenance.take() {
+                hit.provenance = Some(chunk_provenance(&doc, hit));
+                for child in &mut hit.children {
+                    child.provenance = Some(chunk_provenance(&doc, child));
+                }
+            }
+            hit.doc_meta = Some(meta);
+        }
+    }
+
+    /// Name results so that no two of them look alike; see
+    /// [`names::display_names`]. Ideas keep the name of their entry.
+    fn attach_display_names(&self, hits: &mut [SearchHit]) {
+        let stats = self.doc_stats();
+        let is_folder = |hit: &SearchHit| hit.aggregate_type.as_deref() == Some("folder");
+        let docs = names::display_names(
+            hits.iter()
+                .filter(|hit| !is_folder(hit) && hit.doc_type.as_deref() != Some("idea"))
+                .map(|hit| {
+                    let title = stats.get(&hit.file_path).and_then(|m| m.title.as_deref());
+                    (hit.file_path.as_str(), title)
+                }),
+        );
+        let folders = names::display_names(
+            hits.iter()
+                .filter_map(|hit| hit.folder_path.as_deref().filter(|_| is_folder(hit)))
+                .filter(|path| *path != ".")
+                .map(|path| (path, None)),
+        );
+        let renames: Vec<Option<String>> = hits
+            .iter()
+            .map(|hit| match hit.folder_path.as_deref() {
+                Some(path) if is_folder(hit) => folders.get(path).cloned(),
+                _ => docs.get(hit.file_path.as_str()).cloned(),
+            })
+            .collect();
+        for (hit, name) in hits.iter_mut().zip(renames) {
+            let Some(name) = name else { continue };
+            for child in &mut hit.children {
+                child.display_name.clone_from(&name);
+            }
+            hit.display_name = name;
+        }
+    }
+
+    /// Saved document stats, reloaded when the indexer rewrites the metadata
+    fn doc_stats(&self) -> Arc<doc_meta::Stats> {
+        let path = self.config.paths.get_index_metadata_path();
+        let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+            return Arc::default();
+        };
+        let mut cached = self.doc_meta.lock();
+        if let Some((stamp, stats)) = cached.as_ref() {
+            if *stamp == modified {
+                return stats.clone();
+            }
+        }
+        let stats = Arc::new(doc_meta::load(&self.config));
+        *cached = Some((modified, stats.clone()));
+        stats
+    }
+
+    /// Spelling index for the current BM25 index: the one the indexer saved,
+    /// or built here when that is missing or stale
+    fn spell_index(&self, indexes: &Indexes) -> Option<Arc<SpellIndex>> {
+        let opstamp = indexes.bm25_store.opstamp().ok()?;
+        let mut cached = self.spell.lock();
+        if let Some(index) = cached.get(&opstamp) {
+            return Some(index);
+        }
+        cached.clear();
+        let path = self.config.paths.get_spelling_path();
+        let index = Arc::new(SpellIndex::open_or_build(&path, opstamp, || {
+            indexes.bm25_store.vocabulary().ok()
+        })?);
+        cached.insert(opstamp, index.clone(), index.heap_bytes());
+        Some(index)
+    }
+
+    /// Embedding of the normalized query `text` from `client`, cached per tier
+    async fn query_vector(
+        &self,
+        client: &EmbeddingClient,
+        tier: EmbeddingTier,
+        text: &str,
+    ) -> SearchResult<Arc<Vec<f32>>> {
+        let key = (tier, text.to_string());
+        if let Some(vector) = self.query_vectors.lock().get(&key) {
+            return Ok(vector);
+        }
+        let vector = Arc::new(client.embed_one(text).await?);
+        let bytes = memory::vector_bytes(text, &vector);
+        self.query_vectors.lock().insert(key, vector.clone(), bytes);
+        Ok(vector)
+    }
+
+    /// Chunks of the document at `path` in order, cached until the BM25
+    /// index changes
+    fn file_chunks(&self, indexes: &Indexes, path: &str) -> Arc<Vec<SearchHit>> {
+        let opstamp = indexes.bm25_store.opstamp().unwrap_or_default();
+        {
+            let mut cached = self.file_chunks.lock();
+            if cached.0 != opstamp {
+                cached.1.clear();
+                cached.0 = opstamp;
+            }
+            if let Some(chunks) = cached.1.get(&path.to_string()) {
+                return chunks;
+            }
+        }
+        let chunks = Arc::new(
+            indexes
+                .bm25_store
+                .get_chunks_by_file(path)
+                .unwrap_or_default(),
+        );
+        let bytes = memory::hits_bytes(&chunks);
+        let mut cached = self.file_chunks.lock();
+        if cached.0 == opstamp {
+            cached.1.insert(path.to_string(), chunks.clone(), bytes);
+        }
+        chunks
+    }
+
+    /// Memory held by the caches above against their shares of `[memory]`
+    pub fn memory_usage(&self) -> MemoryReport {
+        let components = vec![
+            self.file_chunks.lock().1.usage("chunk_cache"),
+            self.query_vectors.lock().usage("embedding_cache"),
+            self.spell.lock().usage("keyword_index"),
+        ];
+        MemoryReport {
+            budget_bytes: self.config.memory.budget_bytes(),
+            used_bytes: components.iter().map(|c| c.bytes).sum(),
+            components,
+        }
+    }
+
+    /// Up to three corrected queries when some query words never occur in the
+    /// corpus (the usual cause of empty or weak results). Unknown words are
+    /// replaced by their 1st / 2nd / 3rd closest known word.
+    fn did_you_mean(&self, indexes: &Indexes, query: &str) -> Option<Vec<String>> {
+        let spell = self.spell_index(indexes)?;
+        let words: Vec<String> = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
 
-        // Search vector store
-        let mut results = self.vector_store.search(&query_vector, limit).await?;
+        let corrections: HashMap<&str, Vec<String>> = words
+            .iter()
+            .filter(|w| spelling::is_correctable(w) && !spell.contains(w))
+            .map(|w| (w.as_str(), spell.candidates(w, 3)))
+            .filter(|(_, c)| !c.is_empty())
+            .collect();
+        if corrections.is_empty() {
+            return None;
+        }
+
+        let variants: Vec<String> = (0..3)
+            .map_while(|rank| {
+                let mut changed = false;
+                let rewritten: Vec<&str> = words
+                    .iter()
+                    .map(
+                        |w| match corrections.get(w.as_str()).and_then(|c| c.get(rank)) {
+                            Some(c) => {
+                                changed = true;
+                                c.as_str()
+                            }
+                            None => w.as_str(),
+                        },
+                    )
+                    .collect();
+                changed.then(|| rewritten.join(" "))
+            })
+            .collect();
+        (!variants.is_empty()).then_some(variants)
+    }
+
+    /// Explain an empty result list: index size, which query words the
+    /// workspace uses at all and the nearest indexed words to the others.
+    /// `retrieved` is the number of matches before filtering.
+    fn diagnose_no_results(
+        &self,
+        indexes: &Indexes,
+        query: &str,
+        retrieved: usize,
+    ) -> NoResultsDiagnostics {
+        let mut diagnostics = NoResultsDiagnostics {
+            index_exists: true,
+            chunks: indexes.bm25_store.count().unwrap_or(0),
+            filtered_out: retrieved,
+            ..Default::default()
+        };
+        let Some(spell) = self.spell_index(indexes) else {
+            return diagnostics;
+        };
+        let mut seen = HashSet::new();
+        let words = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .filter(|w| seen.insert(w.clone()));
+        for word in words {
+            if spell.contains(&word) {
+                diagnostics.known_terms.push(word);
+                continue;
+            }
+            if spelling::is_correctable(&word) {
+                let nearest = spell.candidates(&word, 3);
+                if !nearest.is_empty() {
+                    diagnostics.nearest_terms.insert(word.clone(), nearest);
+                }
+            }
+            diagnostics.absent_terms.push(word);
+        }
+        diagnostics
+    }
+
+    /// Record the query in the slow-query log if it exceeded `search.slow_query_ms`
+    fn log_if_slow(
+        &self,
+        options: &SearchOptions,
+        mode: &str,
+        result_count: usize,
+        started: Instant,
+        timings: &StageTimings,
+    ) {
+        let threshold = self.config.search.slow_query_ms;
+        let elapsed = started.elapsed();
+        if threshold == 0 || elapsed < Duration::from_millis(threshold) {
+            return;
+        }
+
+        let entry = SlowQueryEntry::new(options, mode, result_count, elapsed, timings);
+        log::warn!(
+            "[Searcher] Slow query ({:.0}ms) {:?}: {:?}",
+            entry.total_ms,
+            entry.query,
+            entry.stages
+        );
+        if let Err(e) = slowlog::append(&self.config.paths.get_slow_query_log_path(), &entry) {
+            log::warn!("[Searcher] Failed to write slow-query log: {}", e);
+        }
+    }
+
+    /// Number of matching results (at the requested aggregation level) and
+    /// whether that number is exact. Unfiltered keyword content searches count
+    /// every BM25 match; everything else counts the candidate pool, topped up
+    /// by the keyword match count where that is a meaningful lower bound.
+    fn estimate_total(
+        &self,
+        indexes: &Indexes,
+        query: &str,
+        mode: SearchMode,
+        options: &SearchOptions,
+        hits: &[SearchHit],
+    ) -> (usize, bool) {
+        let pool = match options.aggregate_by() {
+            AggregateBy::Content => hits.len(),
+            AggregateBy::Doc => hits
+                .iter()
+                .map(|h| h.file_path.as_str())
+                .collect::<HashSet<_>>()
+                .len(),
+            AggregateBy::Folder => hits
+                .iter()
+                .map(|h| h.file_path.rsplit_once('/').map_or(".", |(f, _)| f))
+                .collect::<HashSet<_>>()
+                .len(),
+        };
+
+        let filtered = options.filter.is_some()
+            || options.source_filter().is_some()
+            || options.date_from.is_some()
+            || options.date_to.is_some()
+            || options.min_score.is_some()
+            || options.allowed_folders.is_some();
+        if filtered || options.aggregate_by() != AggregateBy::Content || mode == SearchMode::Vector
+        {
+            return (pool, false);
+        }
+
+        let folder = options.folder_filter.as_deref();
+        match indexes.bm25_store.count_matches(query, folder) {
+            Ok(count) if mode == SearchMode::Keyword => (count, true),
+            Ok(count) => (pool.max(count), false),
+            Err(_) => (pool, false),
+        }
+    }
+
+    /// Dispatch to the strategy for `mode`; returns the hits and, in auto
+    /// mode, the detected intent
+    async fn run_mode(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        translations: &[String],
+        folder: Option<&str>,
+        mode: SearchMode,
+        limit: usize,
+    ) -> SearchResult<(Vec<SearchHit>, Option<QueryIntent>)> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        Ok(match mode {
+            SearchMode::Vector => (self.vector_search(run, query, folder, limit).await?, None),
+            SearchMode::Keyword => (
+                t.time(Stage::Bm25, || {
+                    keyword_hits(&indexes.bm25_store, query, translations, folder, limit)
+                }),
+                None,
+            ),
+            SearchMode::Hybrid => (
+                self.hybrid_search(run, query, query, translations, folder, limit)
+                    .await?,
+                None,
+            ),
+            SearchMode::Auto => {
+                let intent = self.detect_intent(query, t).await;
+                (
+                    self.routed_search(run, query, translations, folder, intent, limit)
+                        .await?,
+                    Some(intent),
+                )
+            }
+        })
+    }
+
+    /// Fuse the signal of query-time context documents into `hits`; see
+    /// [`context`]. Chunks of the context documents themselves are dropped.
+    async fn fuse_context(
+        &self,
+        run: Run<'_>,
+        hits: Vec<SearchHit>,
+        docs: &[ContextDoc],
+        folder: Option<&str>,
+        limit: usize,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        if docs.is_empty() {
+            return Ok(hits);
+        }
+        let chunker = Chunker::new(
+            self.config.search.chunk_size,
+            self.config.search.chunk_overlap,
+        );
+        let texts: Vec<String> = context::chunk_texts(&chunker, docs)
+            .iter()
+            .map(|text| tokenizer::normalize_for_embedding(text, &self.config.tokenizer))
+            .collect();
+
+        let started = Instant::now();
+        let vectors = self.embedding_client.embed(texts).await?;
+        t.record(Stage::Embed, started.elapsed());
+
+        let mut hits = match context::mean_vector(&vectors) {
+            Some(vector) => {
+                let started = Instant::now();
+                let related = indexes
+                    .vector_store
+                    .search_in(&vector, limit, folder)
+                    .await?;
+                t.record(Stage::Ann, started.elapsed());
+                t.time(Stage::Fusion, || {
+                    context::fuse(
+                        hits,
+                        related,
+                        self.config.search.context_weight,
+                        RRF_K,
+                        fusion_key,
+                        limit,
+                    )
+                })
+            }
+            None => hits,
+        };
+        let own = context::own_paths(docs);
+        hits.retain(|hit| !own.contains(&hit.file_path));
+        Ok(hits)
+    }
+
+    /// Perform vector search, restricted to files under `folder` if given
+    async fn vector_search(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        folder: Option<&str>,
+        limit: usize,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        // Generate query embedding
+        let started = Instant::now();
+        let normalized = tokenizer::normalize_for_embedding(query, &self.config.tokenizer);
+        let query_vector = self
+            .query_vector(&self.embedding_client, EmbeddingTier::Accurate, &normalized)
+            .await?;
+        t.record(Stage::Embed, started.elapsed());
+
+        // Search vector store, or re-score what the fast tier or the prefix
+        // index recalls
+        let candidates = match self.fast_candidates(run, &normalized, folder, limit).await {
+            Some(candidates) => Some(candidates),
+            None => {
+                self.prefix_candidates(run, &query_vector, folder, limit)
+                    .await?
+            }
+        };
+        let mut results = match candidates {
+            Some(candidates) => {
+                let ids: Vec<String> = candidates
+                    .iter()
+                    .filter_map(|h| h.chunk_id.clone())
+                    .collect();
+                let started = Instant::now();
+                let vectors = indexes.vector_store.get_vectors(&ids).await?;
+                t.record(Stage::Ann, started.elapsed());
+                rescore(candidates, &query_vector, &vectors, limit)
+            }
+            None => {
+                let started = Instant::now();
+                let results = indexes
+                    .vector_store
+                    .search_in(&query_vector, limit, folder)
+                    .await?;
+                t.record(Stage::Ann, started.elapsed());
+                results
+            }
+        };
 
         // Mark as vector match
         for hit in &mut results {
             hit.matched_by = MatchType::Vector;
         }
 
+        if self.config.multi_vector.enabled {
+            results = self
+                .late_interaction_rerank(run, query, query_vector.to_vec(), results)
+                .await?;
+        }
+
         Ok(results)
     }
 
+    /// Candidates recalled from the fast-tier index for accurate re-scoring.
+    /// `None` when tiers are disabled, the fast index is not built yet or
+    /// the fast model fails; the accurate index is searched directly then.
+    async fn fast_candidates(
+        &self,
+        run: Run<'_>,
+        normalized: &str,
+        folder: Option<&str>,
+        limit: usize,
+    ) -> Option<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        let (client, store) = indexes.fast.as_ref()?;
+        if !store.exists().await {
+            return None;
+        }
+        let started = Instant::now();
+        let recall = self.config.embedding.tiers.recall.max(limit);
+        let candidates = match self
+            .query_vector(client, EmbeddingTier::Fast, normalized)
+            .await
+        {
+            Ok(vector) => store.search_in(&vector, recall, folder).await,
+            Err(e) => Err(e),
+        };
+        t.record(Stage::Ann, started.elapsed());
+        match candidates {
+            Ok(candidates) if !candidates.is_empty() => Some(candidates),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!(
+                    "[Searcher] Fast-tier recall failed, searching accurate index: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Candidates from the prefix index for exact re-scoring on full
+    /// vectors; `None` when `[vector_index] search_dimensions` is unset or
+    /// the prefix index is not built yet
+    async fn prefix_candidates(
+        &self,
+        run: Run<'_>,
+        query_vector: &[f32],
+        folder: Option<&str>,
+        limit: usize,
+    ) -> SearchResult<Option<Vec<SearchHit>>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        let Some(store) = indexes.prefix.as_ref() else {
+            return Ok(None);
+        };
+        if !store.exists().await {
+            return Ok(None);
+        }
+        let started = Instant::now();
+        let prefix = matryoshka::truncate(query_vector, store.dimensions());
+        let recall = self.config.vector_index.rescore.max(limit);
+        let candidates = store.search_in(&prefix, recall, folder).await?;
+        t.record(Stage::Ann, started.elapsed());
+        Ok((!candidates.is_empty()).then_some(candidates))
+    }
+
+    /// Re-score ANN candidates with MaxSim over their stored window vectors.
+    /// Candidates without windows (indexed before the mode was enabled) keep their score.
+    async fn late_interaction_rerank(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        query_vector: Vec<f32>,
+        mut hits: Vec<SearchHit>,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        let mv = &self.config.multi_vector;
+        let chunk_ids: Vec<String> = hits.iter().filter_map(|h| h.chunk_id.clone()).collect();
+        let started = Instant::now();
+        let doc_windows = indexes.vector_store.get_windows(&chunk_ids).await?;
+        t.record(Stage::Ann, started.elapsed());
+        if doc_windows.is_empty() {
+            return Ok(hits);
+        }
+
+        let mut query_vectors = vec![query_vector];
+        let query_windows = late_interaction::windows(query, mv.window_chars, mv.stride_chars);
+        if query_windows.len() > 1 {
+            let started = Instant::now();
+            query_vectors.extend(self.embedding_client.embed(query_windows).await?);
+            t.record(Stage::Embed, started.elapsed());
+        }
+
+        for hit in &mut hits {
+            let windows = hit.chunk_id.as_ref().and_then(|id| doc_windows.get(id));
+            if let Some(score) = windows.and_then(|w| late_interaction::max_sim(&query_vectors, w))
+            {
+                hit.score = score.clamp(0.0, 1.0);
+            }
+        }
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Ok(hits)
+    }
+
     /// Keyword search via tantivy BM25 index
-    fn keyword_search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
-        self.bm25_store.search(query, limit).unwrap_or_default()
+    fn keyword_search(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        folder: Option<&str>,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        t.time(Stage::Bm25, || {
+            indexes
+                .bm25_store
+                .search_in(query, limit, folder)
+                .unwrap_or_default()
+        })
     }
 
-    /// Perform hybrid search using RRF (Reciprocal Rank Fusion)
-    async fn hybrid_search(&self, query: &str, limit: usize) -> SearchResult<Vec<SearchHit>> {
-        let candidate_limit = limit * 3;
+    /// Classify the query: heuristics first, then the LLM if enabled
+    async fn detect_intent(&self, query: &str, t: &StageTimings) -> QueryIntent {
+        if let Some(intent) = router::classify(query) {
+            return intent;
+        }
+        if let (true, Some(llm)) = (self.config.router.llm, self.llm.as_ref()) {
+            let started = Instant::now();
+            let reply = llm.complete(router::CLASSIFY_PROMPT, query).await;
+            t.record(Stage::Llm, started.elapsed());
+            match reply {
+                Ok(reply) => {
+                    if let Some(intent) = QueryIntent::parse(&reply) {
+                        return intent;
+                    }
+                }
+                Err(e) => log::warn!("[Searcher] LLM query classification failed: {}", e),
+            }
+        }
+        QueryIntent::General
+    }
+
+    /// Run the strategy matching a detected intent
+    async fn routed_search(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        translations: &[String],
+        folder: Option<&str>,
+        intent: QueryIntent,
+        limit: usize,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        match intent {
+            QueryIntent::Navigational => {
+                let hits = t.time(Stage::Bm25, || {
+                    self.navigational_search(indexes, query, folder, limit)
+                });
+                if hits.is_empty() {
+                    self.hybrid_search(run, query, query, translations, folder, limit)
+                        .await
+                } else {
+                    Ok(hits)
+                }
+            }
+            QueryIntent::Identifier => {
+                let exact = router::unquote(query);
+                let phrase = format!("\"{}\"", exact.replace('"', " "));
+                let hits = self.keyword_search(run, &phrase, folder, limit);
+                if hits.is_empty() {
+                    Ok(self.keyword_search(run, exact, folder, limit))
+                } else {
+                    Ok(hits)
+                }
+            }
+            QueryIntent::Conceptual => {
+                let expanded = self.hyde_expand(query, t).await;
+                self.hybrid_search(run, query, &expanded, translations, folder, limit)
+                    .await
+            }
+            QueryIntent::General => {
+                self.hybrid_search(run, query, query, translations, folder, limit)
+                    .await
+            }
+        }
+    }
+
+    /// Translations of the query into the workspace's other languages, used as
+    /// extra keyword queries when cross-language retrieval is on. Vector search
+    /// relies on the embedding model being multilingual instead.
+    async fn translate_query(
+        &self,
+        query: &str,
+        mode: SearchMode,
+        options: &SearchOptions,
+        t: &StageTimings,
+    ) -> Vec<String> {
+        let config = &self.config.cross_language;
+        let enabled = options.cross_language.unwrap_or(config.enabled);
+        let Some(llm) = self.llm.as_ref() else {
+            return vec![];
+        };
+        if !enabled || !config.translate_keyword || mode == SearchMode::Vector {
+            return vec![];
+        }
+
+        let mut translations = Vec::new();
+        for language in crosslang::target_languages(query, &config.languages) {
+            let started = Instant::now();
+            let reply = llm
+                .complete(&crosslang::translate_prompt(language), query)
+                .await;
+            t.record(Stage::Llm, started.elapsed());
+            match reply {
+                Ok(text) if !text.is_empty() && text != query => translations.push(text),
+                Ok(_) => {}
+                Err(e) => log::warn!("[Searcher] Query translation to {} failed: {}", language, e),
+            }
+        }
+        translations
+    }
+
+    /// Append a hypothetical answer to the query for embedding (HyDE), if enabled
+    async fn hyde_expand(&self, query: &str, t: &StageTimings) -> String {
+        if let (true, Some(llm)) = (self.config.router.hyde, self.llm.as_ref()) {
+            let started = Instant::now();
+            let passage = llm.complete(router::HYDE_PROMPT, query).await;
+            t.record(Stage::Llm, started.elapsed());
+            match passage {
+                Ok(passage) => return format!("{}\n\n{}", query, passage),
+                Err(e) => log::warn!("[Searcher] HyDE expansion failed: {}", e),
+            }
+        }
+        query.to_string()
+    }
+
+    /// Match query terms against file names and paths; returns each file's first chunk
+    fn navigational_search(
+        &self,
+        indexes: &Indexes,
+        query: &str,
+        folder: Option<&str>,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        let terms = router::navigational_terms(query);
+        if terms.is_empty() {
+            return vec![];
+        }
+
+        let mut scored: Vec<(f32, String)> = indexes
+            .bm25_store
+            .file_paths()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| folder.is_none_or(|f| in_folder(path, f)))
+            .filter_map(|path| {
+                let lower = path.to_lowercase();
+                let name = relpath::display_name(&lower);
+                let matched: f32 = terms
+                    .iter()
+                    .map(|t| {
+                        if name.contains(t.as_str()) {
+                            1.0
+                        } else if lower.contains(t.as_str()) {
+                            0.5
+                        } else {
+                            0.0
+                        }
+                    })
+                    .sum();
+                let score = matched / terms.len() as f32;
+                (score > 0.0).then_some((score, path))
+            })
+            .collect();
 
-        // Execute both searches
-        let vector_results = self.vector_search(query, candidate_limit).await?;
-        let keyword_results = self.keyword_search(query, candidate_limit);
+        scored.sort_by(|a, b| {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.1.len().cmp(&b.1.len()))
+        });
+        scored.truncate(limit);
+
+        scored
+            .into_iter()
+            .filter_map(|(score, path)| {
+                let mut hit = indexes
+                    .bm25_store
+                    .get_chunks_by_file(&path)
+                    .ok()?
+                    .into_iter()
+                    .next()?;
+                hit.score = score;
+                hit.matched_by = MatchType::Keyword;
+                Some(hit)
+            })
+            .collect()
+    }
+
+    /// Perform hybrid search using RRF (Reciprocal Rank Fusion).
+    /// `vector_query` is the text embedded for the vector signal (the query itself, or a HyDE expansion);
+    /// `translations` are extra keyword queries for cross-language retrieval;
+    /// `folder` restricts both the vector and keyword signals.
+    async fn hybrid_search(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        vector_query: &str,
+        translations: &[String],
+        folder: Option<&str>,
+        limit: usize,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        let candidate_limit = limit * 3;
+        let weights = &self.config.search;
+
+        // Run all enabled signals concurrently; BM25 is CPU-bound so it gets a blocking thread
+        let bm25 = indexes.bm25_store.clone();
+        let keyword_query = query.to_string();
+        let translations = translations.to_vec();
+        let keyword_folder = folder.map(str::to_string);
+        let keyword = tokio::task::spawn_blocking(move || {
+            let started = Instant::now();
+            let hits = keyword_hits(
+                &bm25,
+                &keyword_query,
+                &translations,
+                keyword_folder.as_deref(),
+                candidate_limit,
+            );
+            (hits, started.elapsed())
+        });
+        let sparse = async {
+            if indexes.sparse.is_some() {
+                self.sparse_search(run, query, candidate_limit)
+                    .await
+                    .map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+        let (vector, keyword, sparse) = tokio::join!(
+            self.vector_search(run, vector_query, folder, candidate_limit),
+            keyword,
+            sparse
+        );
+
+        let (keyword, keyword_elapsed) =
+            keyword.map_err(|e| SearchError::Search(format!("keyword search task: {e}")))?;
+        t.record(Stage::Bm25, keyword_elapsed);
+
+        let mut signals = vec![
+            (MatchType::Vector, weights.vector_weight, vector?),
+            (MatchType::Keyword, weights.keyword_weight, keyword),
+        ];
+        if let Some(sparse) = sparse? {
+            signals.push((MatchType::Sparse, weights.sparse_weight, sparse));
+        }
 
         // Use RRF to fuse results
-        let fused = self.rrf_fusion(vector_results, keyword_results, limit);
+        let fused = t.time(Stage::Fusion, || self.rrf_fusion(signals, limit));
 
         Ok(fused)
     }
 
-    /// Reciprocal Rank Fusion (RRF) — standard rank-based fusion (Cormack 2009).
-    /// Score = 1/(k+rank_vector) + 1/(k+rank_keyword).
-    /// Key = content hash so same chunk from both lists merges correctly.
+    /// Sparse (SPLADE) search: score postings, then load chunk content from the BM25 store
+    async fn sparse_search(
+        &self,
+        run: Run<'_>,
+        query: &str,
+        limit: usize,
+    ) -> SearchResult<Vec<SearchHit>> {
+        let Run {
+            indexes,
+            timings: t,
+        } = run;
+        let Some((client, store)) = indexes.sparse.as_ref() else {
+            return Ok(vec![]);
+        };
+
+        let started = Instant::now();
+        let query_vector = client.embed_one(query).await?;
+        t.record(Stage::Embed, started.elapsed());
+        let started = Instant::now();
+        let ranked = store.search(&query_vector, limit)?;
+        let ids: Vec<String> = ranked.iter().map(|(id, _)| id.clone()).collect();
+        let scores: HashMap<&str, f32> = ranked.iter().map(|(id, s)| (id.as_str(), *s)).collect();
+
+        let mut hits = indexes.bm25_store.get_chunks_by_ids(&ids)?;
+        t.record(Stage::Sparse, started.elapsed());
+        for hit in &mut hits {
+            hit.score = hit
+                .chunk_id
+                .as_deref()
+                .and_then(|id| scores.get(id))
+                .copied()
+                .unwrap_or(0.0);
+            hit.matched_by = MatchType::Sparse;
+        }
+        Ok(hits)
+    }
+
+    /// Weighted Reciprocal Rank Fusion (RRF) — rank-based fusion (Cormack 2009).
+    /// Score = Σ weight_i / (k + rank_i) over every signal that returned the chunk.
+    /// Hits are keyed by [`fusion_key`] so the same chunk from several lists merges.
     fn rrf_fusion(
         &self,
-        vector_results: Vec<SearchHit>,
-        keyword_results: Vec<SearchHit>,
+        signals: Vec<(MatchType, f32, Vec<SearchHit>)>,
         limit: usize,
     ) -> Vec<SearchHit> {
         struct FusedEntry {
             score: f32,
             hit: SearchHit,
-            has_vector: bool,
-            has_keyword: bool,
+            signals: HashSet<MatchType>,
         }
 
-        // Key: file_path + content fingerprint (first 64 chars) for stable dedup
-        let chunk_key = |h: &SearchHit| {
-            let content_prefix: String = h.content.chars().take(64).collect();
-            format!("{}|{}", h.file_path, content_prefix)
-        };
-
         let mut scores: HashMap<String, FusedEntry> = HashMap::new();
 
-        for (rank, hit) in vector_results.into_iter().enumerate() {
-            let key = chunk_key(&hit);
-            let rrf = 1.0 / (RRF_K + rank as f32 + 1.0);
-            scores
-                .entry(key)
-                .and_modify(|e| {
-                    e.score += rrf;
-                    e.has_vector = true;
-                })
-                .or_insert(FusedEntry {
-                    score: rrf,
-                    hit: SearchHit {
-                        matched_by: MatchType::Vector,
-                        ..hit
-                    },
-                    has_vector: true,
-                    has_keyword: false,
-                });
-        }
-
-        for (rank, hit) in keyword_results.into_iter().enumerate() {
-            let key = chunk_key(&hit);
-            let rrf = 1.0 / (RRF_K + rank as f32 + 1.0);
-            scores
-                .entry(key)
-                .and_modify(|e| {
-                    e.score += rrf;
-                    e.has_keyword = true;
-                })
-                .or_insert(FusedEntry {
-                    score: rrf,
-                    hit: SearchHit {
-                        matched_by: MatchType::Keyword,
-                        ..hit
-                    },
-                    has_vector: false,
-                    has_keyword: true,
-                });
+        for (match_type, weight, results) in signals {
+            for (rank, hit) in results.into_iter().enumerate() {
+                let key = fusion_key(&hit);
+                let rrf = weight / (RRF_K + rank as f32 + 1.0);
+                scores
+                    .entry(key)
+                    .and_modify(|e| {
+                        e.score += rrf;
+                        e.signals.insert(match_type);
+                    })
+                    .or_insert_with(|| FusedEntry {
+                        score: rrf,
+                        hit: SearchHit {
+                            matched_by: match_type,
+                            ..hit
+                        },
+                        signals: HashSet::from([match_type]),
+                    });
+            }
         }
 
         // Normalize scores to [0,1] and set matched_by
@@ -250,10 +1807,10 @@ impl Searcher {
         let mut results: Vec<SearchHit> = scores
             .into_values()
             .map(|entry| {
-                let matched_by = match (entry.has_vector, entry.has_keyword) {
-                    (true, true) => MatchType::Hybrid,
-                    (true, false) => MatchType::Vector,
-                    _ => MatchType::Keyword,
+                let matched_by = if entry.signals.len() > 1 {
+                    MatchType::Hybrid
+                } else {
+                    entry.hit.matched_by
                 };
                 let norm_score = if max_score > 0.0 {
                     entry.score / max_score
@@ -277,28 +1834,49 @@ impl Searcher {
         results
     }
 
+    /// Fold results that duplicate a better result from another document
+    /// into its `also_in`, comparing text and chunk vectors
+    async fn dedup_results(&self, indexes: &Indexes, results: Vec<SearchHit>) -> Vec<SearchHit> {
+        let similarity = self.config.search.dedup_similarity;
+        let vectors = if similarity <= 1.0 && results.len() > 1 {
+            let ids: Vec<String> = results.iter().filter_map(|h| h.chunk_id.clone()).collect();
+            indexes
+                .vector_store
+                .get_vectors(&ids)
+                .await
+                .unwrap_or_else(|e| {
+                    log::warn!("[Searcher] Dedup falls back to text comparison: {}", e);
+                    HashMap::new()
+                })
+        } else {
+            HashMap::new()
+        };
+        dedup::dedup(results, &vectors, similarity)
+    }
+
     /// Aggregate results by document
     /// Uses weighted score formula matching Node.js:
     /// score = topScore * 0.6 + min(hitCount/5, 1) * topScore * 0.4
-    fn aggregate_by_doc(&self, hits: Vec<SearchHit>, limit: usize) -> Vec<SearchHit> {
+    /// With `children > 0`, each document keeps its best `children` chunks nested in the result.
+    fn aggregate_by_doc(
+        &self,
+        hits: Vec<SearchHit>,
+        limit: usize,
+        children: usize,
+    ) -> Vec<SearchHit> {
         struct DocAgg {
             file_path: String,
             display_name: String,
             top_score: f32,
             hit_count: usize,
             top_chunk: SearchHit,
+            chunks: Vec<SearchHit>,
         }
 
         let mut doc_map: HashMap<String, DocAgg> = HashMap::new();
 
         for hit in hits {
-            let display_name = hit
-                .file_path
-                .split('/')
-                .next_back()
-                .unwrap_or(&hit.file_path)
-                .trim_end_matches(".md")
-                .to_string();
+            let display_name = relpath::display_name(&hit.file_path).to_string();
 
             let entry = doc_map
                 .entry(hit.file_path.clone())
@@ -308,9 +1886,13 @@ impl Searcher {
                     top_score: 0.0,
                     hit_count: 0,
                     top_chunk: hit.clone(),
+                    chunks: Vec::new(),
                 });
 
             entry.hit_count += 1;
+            if children > 0 {
+                entry.chunks.push(hit.clone());
+            }
 
             // Update with best chunk
             if hit.score > entry.top_score {
@@ -322,13 +1904,21 @@ impl Searcher {
         // Calculate aggregated scores and build results
         let mut results: Vec<SearchHit> = doc_map
             .into_values()
-            .map(|doc| {
+            .map(|mut doc| {
                 // Aggregated score: weighted combination of top score and hit count
                 // score = topScore * 0.6 + min(hitCount/5, 1) * topScore * 0.4
                 let hit_bonus = (doc.hit_count as f32 / 5.0).min(1.0);
                 let aggregated_score = doc.top_score * 0.6 + hit_bonus * doc.top_score * 0.4;
 
+                doc.chunks.sort_by(|a, b| {
+                    b.score
+                        .partial_cmp(&a.score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                doc.chunks.truncate(children);
+
                 SearchHit {
+                    chunk_id: doc.top_chunk.chunk_id,
                     file_path: doc.file_path,
                     display_name: doc.display_name,
                     content: doc.top_chunk.content,
@@ -336,6 +1926,7 @@ impl Searcher {
                     section_title: doc.top_chunk.section_title,
                     line_start: doc.top_chunk.line_start,
                     line_end: doc.top_chunk.line_end,
+                    block_id: doc.top_chunk.block_id,
                     score: aggregated_score,
                     matched_by: doc.top_chunk.matched_by,
                     hit_count: Some(doc.hit_count),
@@ -347,6 +1938,14 @@ impl Searcher {
                     entry_date: None,
                     entry_created_at: None,
                     idea_box: doc.top_chunk.idea_box,
+                    snippet: None,
+                    children: doc.chunks,
+                    also_in: Vec::new(),
+                    folder_summary: None,
+                    doc_meta: None,
+                    citation: None,
+                    provenance: None,
+                    trust: doc.top_chunk.trust,
                 }
             })
             .collect();
@@ -363,7 +1962,13 @@ impl Searcher {
     /// Aggregate results by folder
     /// Uses weighted score formula matching Node.js:
     /// score = topScore * 0.5 + min(hitCount/10, 1) * topScore * 0.3 + min(docCount/3, 1) * topScore * 0.2
-    fn aggregate_by_folder(&self, hits: Vec<SearchHit>, limit: usize) -> Vec<SearchHit> {
+    /// With `checksums` (the index's documents), each folder gets a [`FolderSummary`] outline.
+    fn aggregate_by_folder(
+        &self,
+        hits: Vec<SearchHit>,
+        limit: usize,
+        checksums: Option<&HashMap<String, String>>,
+    ) -> Vec<SearchHit> {
         struct FolderAgg {
             folder_path: String,
             display_name: String,
@@ -371,25 +1976,21 @@ impl Searcher {
             hit_count: usize,
             docs: HashSet<String>,
             top_chunk: SearchHit,
+            headings: Vec<String>,
         }
 
         let mut folder_map: HashMap<String, FolderAgg> = HashMap::new();
 
         for hit in hits {
-            let folder_path = hit
-                .file_path
-                .rsplit_once('/')
-                .map(|(folder, _)| folder.to_string())
+            let folder_path = RelPath::new(&hit.file_path)
+                .parent()
+                .map(RelPath::into_string)
                 .unwrap_or_else(|| ".".to_string());
 
             let display_name = if folder_path == "." {
                 "(root)".to_string()
             } else {
-                folder_path
-                    .split('/')
-                    .next_back()
-                    .unwrap_or(&folder_path)
-                    .to_string()
+                relpath::file_name(&folder_path).to_string()
             };
 
             let entry = folder_map
@@ -401,10 +2002,14 @@ impl Searcher {
                     hit_count: 0,
                     docs: HashSet::new(),
                     top_chunk: hit.clone(),
+                    headings: Vec::new(),
                 });
 
             entry.hit_count += 1;
             entry.docs.insert(hit.file_path.clone());
+            if checksums.is_some() {
+                folder_summary::add_heading(&mut entry.headings, &hit);
+            }
 
             if hit.score > entry.top_score {
                 entry.top_score = hit.score;
@@ -423,8 +2028,11 @@ impl Searcher {
                 let aggregated_score = folder.top_score * 0.5
                     + hit_bonus * folder.top_score * 0.3
                     + doc_bonus * folder.top_score * 0.2;
+                let summary = checksums
+                    .map(|c| folder_summary::outline(c, &folder.folder_path, folder.headings));
 
                 SearchHit {
+                    chunk_id: folder.top_chunk.chunk_id,
                     file_path: folder.top_chunk.file_path,
                     display_name: folder.display_name,
                     content: folder.top_chunk.content,
@@ -432,6 +2040,7 @@ impl Searcher {
                     section_title: folder.top_chunk.section_title,
                     line_start: folder.top_chunk.line_start,
                     line_end: folder.top_chunk.line_end,
+                    block_id: None,
                     score: aggregated_score,
                     matched_by: folder.top_chunk.matched_by,
                     hit_count: Some(folder.hit_count),
@@ -443,6 +2052,14 @@ impl Searcher {
                     entry_date: None,
                     entry_created_at: None,
                     idea_box: folder.top_chunk.idea_box,
+                    snippet: None,
+                    children: Vec::new(),
+                    also_in: Vec::new(),
+                    folder_summary: summary,
+                    doc_meta: None,
+                    citation: None,
+                    provenance: None,
+                    trust: None,
                 }
             })
             .collect();
@@ -456,25 +2073,68 @@ impl Searcher {
         results
     }
 
+    /// Add `[llm]` descriptions to the summaries of folder results when
+    /// `search.folder_summary_llm` is on, reusing cached ones
+    async fn describe_folders(
+        &self,
+        results: &mut [SearchHit],
+        checksums: &HashMap<String, String>,
+        t: &StageTimings,
+    ) {
+        let Some(llm) = self
+            .llm
+            .as_ref()
+            .filter(|_| self.config.search.folder_summary_llm)
+        else {
+            return;
+        };
+        let mut cache = SummaryCache::load(self.config.paths.get_folder_summary_cache_path());
+        let mut generated = 0;
+        for hit in results.iter_mut() {
+            let (Some(folder), Some(summary)) =
+                (hit.folder_path.as_deref(), hit.folder_summary.as_mut())
+            else {
+                continue;
+            };
+            let key = folder_summary::cache_key(checksums, folder);
+            if let Some(text) = cache.get(&key) {
+                summary.summary = Some(text.clone());
+                continue;
+            }
+            if generated == folder_summary::MAX_NEW_PER_QUERY {
+                continue;
+            }
+            generated += 1;
+            let started = Instant::now();
+            let input = folder_summary::prompt_input(folder, summary, &hit.content);
+            let reply = llm.complete(folder_summary::SUMMARY_PROMPT, &input).await;
+            t.record(Stage::Llm, started.elapsed());
+            match reply {
+                Ok(text) if !text.is_empty() => {
+                    cache.insert(key, text.clone());
+                    summary.summary = Some(text);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("[Searcher] Folder summary for {} failed: {}", folder, e),
+            }
+        }
+        cache.save();
+    }
+
     /// Expand top search hits by fetching neighboring chunks from the same file.
     /// For each unique (file_path) in top hits, fetches `window` chunks before and after
     /// the matched chunk and stitches them into the hit's content.
     fn expand_with_neighbors(
         &self,
+        indexes: &Indexes,
         hits: Vec<SearchHit>,
         window: usize,
         limit: usize,
     ) -> Vec<SearchHit> {
-        // Cache of file chunks to avoid re-fetching same file
-        let mut file_cache: HashMap<String, Vec<SearchHit>> = HashMap::new();
         let mut expanded = Vec::with_capacity(hits.len());
 
         for hit in hits.into_iter().take(limit) {
-            let file_chunks = file_cache.entry(hit.file_path.clone()).or_insert_with(|| {
-                self.bm25_store
-                    .get_chunks_by_file(&hit.file_path)
-                    .unwrap_or_default()
-            });
+            let file_chunks = self.file_chunks(indexes, &hit.file_path);
 
             if file_chunks.is_empty() {
                 expanded.push(hit);
@@ -507,6 +2167,143 @@ impl Searcher {
 
     /// Check if index is built
     pub async fn index_exists(&self) -> bool {
-        self.vector_store.exists().await
+        self.indexes().vector_store.exists().await
+    }
+}
+
+/// Key identifying a chunk across signals during fusion: its id, or for hits
+/// from indexes written before ids were stored, file_path + the first 64
+/// characters of content
+pub(super) fn fusion_key(hit: &SearchHit) -> String {
+    match &hit.chunk_id {
+        Some(id) => id.clone(),
+        None => {
+            let content_prefix: String = hit.content.chars().take(64).collect();
+            format!("{}|{}", hit.file_path, content_prefix)
+        }
+    }
+}
+
+/// Lowercased words of `query`, for matching against hit text
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|t| {
+            t.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Point each chunk hit at the `^block-id` block of its content holding the
+/// most query terms, so it can be cited and fetched with `get_block`. Hits
+/// whose matching text lies outside any block get none.
+fn attach_block_ids(hits: &mut [SearchHit], terms: &[String]) {
+    for hit in hits {
+        attach_block_ids(&mut hit.children, terms);
+        if hit.aggregate_type.as_deref() == Some("folder") || !hit.content.contains('^') {
+            continue;
+        }
+        // Reversed so the first of equally good blocks wins
+        hit.block_id = crate::blocks::parse_blocks(&hit.content)
+            .into_iter()
+            .rev()
+            .map(|block| {
+                let text = block.text.to_lowercase();
+                let matched = terms.iter().filter(|t| text.contains(t.as_str())).count();
+                (matched, block.id)
+            })
+            .filter(|(matched, _)| *matched > 0)
+            .max_by_key(|(matched, _)| *matched)
+            .map(|(_, id)| id);
+    }
+}
+
+/// BM25 hits for the query, merged with hits for its translations
+fn keyword_hits(
+    bm25: &Bm25Store,
+    query: &str,
+    translations: &[String],
+    folder: Option<&str>,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let hits = bm25.search_in(query, limit, folder).unwrap_or_default();
+    if translations.is_empty() {
+        return hits;
+    }
+    let mut lists = vec![hits];
+    lists.extend(
+        translations
+            .iter()
+            .map(|q| bm25.search_in(q, limit, folder).unwrap_or_default()),
+    );
+    crosslang::merge_hits(lists, limit)
+}
+
+pub(crate) use crate::relpath::is_within as in_folder;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallbacks_after_follow_the_chain() {
+        use SearchMode::{Auto, Hybrid, Keyword, Vector};
+        let chain = [Hybrid, Vector, Keyword];
+        assert_eq!(fallbacks_after(&chain, Hybrid), vec![Vector, Keyword]);
+        assert_eq!(fallbacks_after(&chain, Vector), vec![Keyword]);
+        assert_eq!(fallbacks_after(&chain, Keyword), vec![]);
+        assert_eq!(fallbacks_after(&chain, Auto), vec![Hybrid, Vector, Keyword]);
+        assert_eq!(fallbacks_after(&[], Hybrid), vec![]);
+    }
+
+    fn hit(chunk_id: &str, score: f32) -> SearchHit {
+        SearchHit {
+            chunk_id: Some(chunk_id.to_string()),
+            score,
+            ..SearchHit::fixture(&format!("{chunk_id}.md"), MatchType::Vector)
+        }
+    }
+
+    #[test]
+    fn test_rescore_ranks_by_accurate_vectors() {
+        // The fast tier ranked `a` first; the accurate vectors prefer `b`
+        let candidates = vec![hit("a", 0.9), hit("b", 0.8), hit("gone", 0.7)];
+        let vectors = HashMap::from([
+            ("a".to_string(), vec![0.0, 1.0]),
+            ("b".to_string(), vec![1.0, 0.1]),
+        ]);
+        let hits = rescore(candidates, &[1.0, 0.0], &vectors, 5);
+        let ids: Vec<&str> = hits.iter().filter_map(|h| h.chunk_id.as_deref()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert!(hits[0].score > 0.99 && hits[1].score == 0.0);
+
+        let vectors = HashMap::from([("a".to_string(), vec![1.0, 0.0])]);
+        assert_eq!(
+            rescore(vec![hit("a", 0.1)], &[1.0, 0.0], &vectors, 0).len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_attach_block_ids_picks_the_matching_block() {
+        let mut chunk = hit("a", 0.5);
+        chunk.content = "Intro about caching.\n\nCache keys hash the path. ^keys\n\n\
+                         Eviction is LRU by size. ^evict\n"
+            .to_string();
+        let mut other = chunk.clone();
+        other.content = "No blocks in this one about eviction".to_string();
+        let mut doc = hit("doc", 0.5);
+        doc.children = vec![chunk.clone()];
+        let mut hits = vec![chunk, other, doc];
+
+        attach_block_ids(&mut hits, &query_terms("LRU eviction?"));
+        assert_eq!(hits[0].block_id.as_deref(), Some("evict"));
+        assert_eq!(hits[1].block_id, None);
+        assert_eq!(hits[2].children[0].block_id.as_deref(), Some("evict"));
+
+        attach_block_ids(&mut hits, &query_terms("intro"));
+        assert_eq!(hits[0].block_id, None);
     }
 }
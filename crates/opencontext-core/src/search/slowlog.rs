@@ -0,0 +1,193 @@
+//! Slow-query log
+//!
+//! Searches slower than `search.slow_query_ms` are logged with a per-stage
+//! timing breakdown and appended to a JSON-lines file that `oc slowlog` reads.
+//! The file is rotated to `<name>.1` once it grows past `MAX_LOG_BYTES`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::types::SearchOptions;
+
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// A timed step of query execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Query embedding (dense and sparse)
+    Embed,
+    /// Vector ANN query and late-interaction re-scoring
+    Ann,
+    /// Tantivy BM25 query
+    Bm25,
+    /// Sparse postings lookup
+    Sparse,
+    /// LLM calls for routing / HyDE
+    Llm,
+    /// Rank fusion of hybrid signals
+    Fusion,
+    /// Filtering, neighbor expansion and aggregation
+    Aggregation,
+}
+
+impl Stage {
+    const ALL: [Stage; 7] = [
+        Stage::Embed,
+        Stage::Ann,
+        Stage::Bm25,
+        Stage::Sparse,
+        Stage::Llm,
+        Stage::Fusion,
+        Stage::Aggregation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Embed => "embed",
+            Stage::Ann => "ann",
+            Stage::Bm25 => "bm25",
+            Stage::Sparse => "sparse",
+            Stage::Llm => "llm",
+            Stage::Fusion => "fusion",
+            Stage::Aggregation => "aggregation",
+        }
+    }
+}
+
+/// Accumulated time per stage for one query. Shared by reference between
+/// concurrently running signals, hence the atomics.
+#[derive(Debug, Default)]
+pub struct StageTimings {
+    micros: [AtomicU64; 7],
+}
+
+impl StageTimings {
+    pub fn record(&self, stage: Stage, elapsed: Duration) {
+        let idx = Stage::ALL.iter().position(|s| *s == stage).unwrap_or(0);
+        self.micros[idx].fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Run `f` and add its duration to `stage`
+    pub fn time<T>(&self, stage: Stage, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let out = f();
+        self.record(stage, started.elapsed());
+        out
+    }
+
+    /// Non-zero stages in milliseconds
+    pub fn breakdown(&self) -> BTreeMap<String, f64> {
+        Stage::ALL
+            .iter()
+            .zip(&self.micros)
+            .filter_map(|(stage, micros)| {
+                let micros = micros.load(Ordering::Relaxed);
+                (micros > 0).then(|| (stage.as_str().to_string(), micros as f64 / 1000.0))
+            })
+            .collect()
+    }
+}
+
+/// One slow-query log record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowQueryEntry {
+    /// RFC 3339 timestamp
+    pub timestamp: String,
+    pub query: String,
+    /// Options the search was called with
+    pub options: serde_json::Value,
+    /// Mode actually executed
+    pub mode: String,
+    pub result_count: usize,
+    pub total_ms: f64,
+    /// Milliseconds per stage
+    pub stages: BTreeMap<String, f64>,
+}
+
+impl SlowQueryEntry {
+    pub fn new(
+        options: &SearchOptions,
+        mode: &str,
+        result_count: usize,
+        total: Duration,
+        timings: &StageTimings,
+    ) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            query: options.query.clone(),
+            options: serde_json::to_value(options).unwrap_or_default(),
+            mode: mode.to_string(),
+            result_count,
+            total_ms: total.as_secs_f64() * 1000.0,
+            stages: timings.breakdown(),
+        }
+    }
+}
+
+/// Append an entry, rotating the file when it gets large
+pub fn append(path: &Path, entry: &SlowQueryEntry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+        std::fs::rename(path, path.with_extension("jsonl.1"))?;
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Most recent entries, newest first
+pub fn read_recent(path: &Path, limit: usize) -> Vec<SlowQueryEntry> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return vec![];
+    };
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_read_recent() {
+        let tmpdir = tempfile::tempdir().expect("create tempdir");
+        let path = tmpdir.path().join("slow-queries.jsonl");
+
+        let timings = StageTimings::default();
+        timings.record(Stage::Embed, Duration::from_millis(1200));
+        timings.time(Stage::Fusion, || ());
+
+        for query in ["first", "second"] {
+            let options = SearchOptions {
+                query: query.to_string(),
+                ..Default::default()
+            };
+            let entry =
+                SlowQueryEntry::new(&options, "hybrid", 3, Duration::from_secs(2), &timings);
+            append(&path, &entry).expect("append");
+        }
+
+        let entries = read_recent(&path, 10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].query, "second");
+        assert_eq!(entries[0].stages["embed"], 1200.0);
+        assert!(!entries[0].stages.contains_key("ann"));
+        assert_eq!(entries[0].options["query"], "second");
+    }
+}
@@ -0,0 +1,109 @@
+//! Result order other than relevance
+//!
+//! Journals and meeting notes are usually wanted newest first. Sorting
+//! runs on the filtered candidates, before the cut to `limit`, so "recent
+//! matches" are the recent ones among everything the query matched rather
+//! than the top hits reshuffled. Results without the sort key (a document
+//! with no recorded times, a non-idea result under `entry_date`) go last in
+//! either direction; ties keep relevance order.
+
+use std::cmp::Ordering;
+
+use super::doc_meta::Stats;
+use super::types::{SearchHit, SortBy, SortOrder};
+
+/// Sort `hits` by `by`, with document times taken from `stats`
+pub(super) fn sort(hits: &mut [SearchHit], by: SortBy, order: SortOrder, stats: &Stats) {
+    let by_score = |a: &SearchHit, b: &SearchHit| b.score.total_cmp(&a.score);
+    let directed = |ordering: Ordering| match order {
+        SortOrder::Asc => ordering,
+        SortOrder::Desc => ordering.reverse(),
+    };
+    let time = |hit: &SearchHit| {
+        let meta = stats.get(&hit.file_path)?;
+        match by {
+            SortBy::Modified => meta.modified,
+            _ => meta.created,
+        }
+    };
+    let path = |hit: &SearchHit| -> String {
+        hit.folder_path
+            .as_deref()
+            .filter(|_| hit.aggregate_type.as_deref() == Some("folder"))
+            .unwrap_or(&hit.file_path)
+            .to_lowercase()
+    };
+    hits.sort_by(|a, b| {
+        let ordering = match by {
+            SortBy::Score => directed(by_score(b, a)),
+            SortBy::Modified | SortBy::Created => keyed(time(a), time(b), directed),
+            SortBy::Path => directed(path(a).cmp(&path(b))),
+            SortBy::EntryDate => keyed(a.entry_date.as_deref(), b.entry_date.as_deref(), directed),
+        };
+        ordering.then_with(|| by_score(a, b))
+    });
+}
+
+/// `a` against `b` in the sort direction, missing keys last
+fn keyed<K: Ord>(a: Option<K>, b: Option<K>, directed: impl Fn(Ordering) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => directed(a.cmp(&b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::types::DocMeta;
+    use crate::search::MatchType;
+
+    fn hit(path: &str, score: f32, entry_date: Option<&str>) -> SearchHit {
+        SearchHit {
+            score,
+            entry_date: entry_date.map(str::to_string),
+            ..SearchHit::fixture(path, MatchType::Keyword)
+        }
+    }
+
+    fn order(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.file_path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_time_path_and_entry_date() {
+        let modified = |ms: u64| DocMeta {
+            modified: Some(ms),
+            ..Default::default()
+        };
+        let stats = Stats::from([
+            ("b.md".to_string(), modified(300)),
+            ("c.md".to_string(), modified(100)),
+            ("a.md".to_string(), modified(200)),
+        ]);
+        let mut hits = vec![
+            hit("a.md", 0.9, None),
+            hit("c.md", 0.8, Some("2024-03-01")),
+            hit("d.md", 0.7, Some("2024-05-01")),
+            hit("b.md", 0.6, None),
+        ];
+
+        sort(&mut hits, SortBy::Modified, SortOrder::Desc, &stats);
+        assert_eq!(order(&hits), ["b.md", "a.md", "c.md", "d.md"]);
+        sort(&mut hits, SortBy::Modified, SortOrder::Asc, &stats);
+        assert_eq!(order(&hits), ["c.md", "a.md", "b.md", "d.md"]);
+        sort(&mut hits, SortBy::EntryDate, SortOrder::Desc, &stats);
+        assert_eq!(order(&hits), ["d.md", "c.md", "a.md", "b.md"]);
+        sort(
+            &mut hits,
+            SortBy::Path,
+            SortBy::Path.default_order(),
+            &stats,
+        );
+        assert_eq!(order(&hits), ["a.md", "b.md", "c.md", "d.md"]);
+        sort(&mut hits, SortBy::Score, SortOrder::Desc, &stats);
+        assert_eq!(order(&hits), ["a.md", "c.md", "d.md", "b.md"]);
+    }
+}
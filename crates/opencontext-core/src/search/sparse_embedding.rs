@@ -0,0 +1,112 @@
+//! Sparse embedding (SPLADE) client
+//!
+//! Talks to a text-embeddings-inference compatible server:
+//! `POST {api_base}/embed_sparse` with `{"inputs": [...]}` returns one
+//! list of `{index, value}` pairs per input.
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::config::SparseConfig;
+use super::error::{SearchError, SearchResult};
+use super::types::SparseVector;
+
+/// Sparse embedding API client
+pub struct SparseEmbeddingClient {
+    config: SparseConfig,
+    client: Client,
+}
+
+#[derive(Debug, Serialize)]
+struct SparseRequest<'a> {
+    inputs: &'a [String],
+    truncate: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SparseValue {
+    index: u32,
+    value: f32,
+}
+
+impl SparseEmbeddingClient {
+    /// Create a new sparse embedding client
+    pub fn new(config: SparseConfig) -> SearchResult<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .map_err(SearchError::Http)?;
+
+        Ok(Self { config, client })
+    }
+
+    /// Generate sparse vectors for multiple texts
+    pub async fn embed(&self, texts: Vec<String>) -> SearchResult<Vec<SparseVector>> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let url = format!(
+            "{}/embed_sparse",
+            self.config.api_base.trim_end_matches('/')
+        );
+        let mut all = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(self.config.batch_size.max(1)) {
+            all.extend(self.embed_batch(batch, &url).await?);
+        }
+
+        Ok(all)
+    }
+
+    /// Generate a sparse vector for a single text
+    pub async fn embed_one(&self, text: &str) -> SearchResult<SparseVector> {
+        self.embed(vec![text.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SearchError::Embedding("No sparse embedding returned".to_string()))
+    }
+
+    async fn embed_batch(&self, texts: &[String], url: &str) -> SearchResult<Vec<SparseVector>> {
+        let mut request = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .json(&SparseRequest {
+                inputs: texts,
+                truncate: true,
+            });
+        if let Some(key) = self.config.api_key.as_deref().filter(|k| !k.is_empty()) {
+            request = request.header("Authorization", format!("Bearer {}", key));
+        }
+
+        let response = request.send().await.map_err(SearchError::Http)?;
+        let status = response.status();
+        let body = response.text().await.map_err(SearchError::Http)?;
+
+        if !status.is_success() {
+            return Err(SearchError::Embedding(format!(
+                "Sparse API error ({}): {}",
+                status, body
+            )));
+        }
+
+        let data: Vec<Vec<SparseValue>> = serde_json::from_str(&body)?;
+        if data.len() != texts.len() {
+            return Err(SearchError::Embedding(format!(
+                "Sparse embedding count mismatch: sent {} texts, got {} vectors",
+                texts.len(),
+                data.len()
+            )));
+        }
+
+        Ok(data
+            .into_iter()
+            .map(|values| SparseVector {
+                indices: values.iter().map(|v| v.index).collect(),
+                values: values.iter().map(|v| v.value).collect(),
+            })
+            .collect())
+    }
+}
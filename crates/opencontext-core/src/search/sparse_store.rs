@@ -0,0 +1,228 @@
+//! SQLite-backed inverted index for sparse (SPLADE) vectors
+//!
+//! Postings live in a dedicated `sparse_postings` table keyed by term id, so
+//! scoring a query only touches the rows of the terms it actually contains.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+
+use super::error::{SearchError, SearchResult};
+use super::types::{Chunk, SparseVector};
+
+/// Persistent sparse vector store
+pub struct SparseStore {
+    conn: Mutex<Connection>,
+}
+
+fn sql_err(e: rusqlite::Error) -> SearchError {
+    SearchError::Index(format!("sparse store: {e}"))
+}
+
+impl SparseStore {
+    /// Open or create the sparse store at `path`
+    pub fn open(path: PathBuf) -> SearchResult<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(&path).map_err(sql_err)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS sparse_postings (
+                 term INTEGER NOT NULL,
+                 chunk_id TEXT NOT NULL,
+                 file_path TEXT NOT NULL,
+                 weight REAL NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_sparse_term ON sparse_postings(term);
+             CREATE INDEX IF NOT EXISTS idx_sparse_file ON sparse_postings(file_path);",
+        )
+        .map_err(sql_err)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> SearchResult<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| SearchError::Index("sparse store lock poisoned".to_string()))
+    }
+
+    /// Replace the postings of every file touched by `chunks` with the given vectors
+    pub fn upsert(&self, chunks: &[Chunk], vectors: &[SparseVector]) -> SearchResult<usize> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction().map_err(sql_err)?;
+
+        let mut seen_files = std::collections::HashSet::new();
+        for chunk in chunks {
+            if seen_files.insert(chunk.file_path.as_str()) {
+                tx.execute(
+                    "DELETE FROM sparse_postings WHERE file_path = ?1",
+                    params![chunk.file_path],
+                )
+                .map_err(sql_err)?;
+            }
+        }
+
+        let mut count = 0;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO sparse_postings (term, chunk_id, file_path, weight)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(sql_err)?;
+            for (chunk, vector) in chunks.iter().zip(vectors) {
+                for (term, weight) in vector.indices.iter().zip(&vector.values) {
+                    if *weight <= 0.0 {
+                        continue;
+                    }
+                    stmt.execute(params![term, chunk.id, chunk.file_path, weight])
+                        .map_err(sql_err)?;
+                }
+                count += 1;
+            }
+        }
+
+        tx.commit().map_err(sql_err)?;
+        Ok(count)
+    }
+
+    /// Score chunks by dot product with the query vector.
+    /// Returns `(chunk_id, score)` pairs, best first, scores normalized to [0, 1].
+    pub fn search(&self, query: &SparseVector, limit: usize) -> SearchResult<Vec<(String, f32)>> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare_cached("SELECT chunk_id, weight FROM sparse_postings WHERE term = ?1")
+            .map_err(sql_err)?;
+
+        let mut scores: HashMap<String, f32> = HashMap::new();
+        for (term, q_weight) in query.indices.iter().zip(&query.values) {
+            let rows = stmt
+                .query_map(params![term], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, f32>(1)?))
+                })
+                .map_err(sql_err)?;
+            for row in rows {
+                let (chunk_id, weight) = row.map_err(sql_err)?;
+                *scores.entry(chunk_id).or_insert(0.0) += q_weight * weight;
+            }
+        }
+
+        let mut ranked: Vec<(String, f32)> = scores.into_iter().filter(|(_, s)| *s > 0.0).collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(limit);
+
+        if let Some(max) = ranked.first().map(|(_, s)| *s) {
+            for (_, score) in &mut ranked {
+                *score /= max;
+            }
+        }
+        Ok(ranked)
+    }
+
+    /// Remove all postings of a file
+    pub fn delete_by_file(&self, file_path: &str) -> SearchResult<()> {
+        self.lock()?
+            .execute(
+                "DELETE FROM sparse_postings WHERE file_path = ?1",
+                params![file_path],
+            )
+            .map_err(sql_err)?;
+        Ok(())
+    }
+
+    /// Remove all postings
+    pub fn reset(&self) -> SearchResult<()> {
+        self.lock()?
+            .execute("DELETE FROM sparse_postings", [])
+            .map_err(sql_err)?;
+        Ok(())
+    }
+
+    /// Number of chunks with a stored sparse vector
+    pub fn count(&self) -> SearchResult<usize> {
+        let n: i64 = self
+            .lock()?
+            .query_row(
+                "SELECT COUNT(DISTINCT chunk_id) FROM sparse_postings",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(sql_err)?;
+        Ok(n as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn chunk(id: &str, file_path: &str) -> Chunk {
+        Chunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            content: String::new(),
+            heading_path: String::new(),
+            section_title: None,
+            doc_type: None,
+            entry_id: None,
+            entry_date: None,
+            entry_created_at: None,
+            idea_box: None,
+            chunk_index: 0,
+            vector: vec![],
+        }
+    }
+
+    fn sv(pairs: &[(u32, f32)]) -> SparseVector {
+        SparseVector {
+            indices: pairs.iter().map(|p| p.0).collect(),
+            values: pairs.iter().map(|p| p.1).collect(),
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_by_dot_product() {
+        let dir = TempDir::new().unwrap();
+        let store = SparseStore::open(dir.path().join("sparse.db")).unwrap();
+        store
+            .upsert(
+                &[chunk("a.md#0", "a.md"), chunk("b.md#0", "b.md")],
+                &[sv(&[(1, 0.2), (7, 0.1)]), sv(&[(7, 2.0)])],
+            )
+            .unwrap();
+
+        let results = store.search(&sv(&[(7, 1.0)]), 10).unwrap();
+        assert_eq!(results[0].0, "b.md#0");
+        assert!((results[0].1 - 1.0).abs() < 1e-6);
+        assert_eq!(results.len(), 2);
+        assert!(store.search(&sv(&[(99, 1.0)]), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_upsert_replaces_file_postings() {
+        let dir = TempDir::new().unwrap();
+        let store = SparseStore::open(dir.path().join("sparse.db")).unwrap();
+        store
+            .upsert(
+                &[chunk("a.md#0", "a.md"), chunk("a.md#1", "a.md")],
+                &[sv(&[(1, 1.0)]), sv(&[(2, 1.0)])],
+            )
+            .unwrap();
+        store
+            .upsert(&[chunk("a.md#0", "a.md")], &[sv(&[(3, 1.0)])])
+            .unwrap();
+
+        assert_eq!(store.count().unwrap(), 1);
+        assert!(store.search(&sv(&[(2, 1.0)]), 10).unwrap().is_empty());
+
+        store.delete_by_file("a.md").unwrap();
+        assert_eq!(store.count().unwrap(), 0);
+    }
+}
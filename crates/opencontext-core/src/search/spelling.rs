@@ -0,0 +1,420 @@
+//! "Did you mean" spelling correction
+//!
+//! A SymSpell-style index over the corpus vocabulary: every word is stored
+//! under all of its deletion variants (up to `MAX_EDIT_DISTANCE` deletes of
+//! its first `PREFIX_LEN` characters), so candidates for a misspelled word are
+//! found by generating the word's own deletes and looking them up, then
+//! verified with an optimal-string-alignment distance.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::ops::Deref;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+const MAX_EDIT_DISTANCE: usize = 2;
+const PREFIX_LEN: usize = 7;
+
+const MAGIC: &[u8; 8] = b"OCSPELL1";
+/// Magic, then the opstamp of the keyword index the file was built from
+const HEADER: usize = 16;
+
+/// Words shorter than this are never corrected
+pub const MIN_WORD_LEN: usize = 3;
+
+/// Vocabulary with precomputed deletes
+///
+/// Kept in the layout of its file: the sorted words with their frequencies,
+/// then the sorted delete variants, each with the indices of the words it
+/// comes from. Lookups binary-search that layout in place, so an index
+/// written at index time ([`SpellIndex::save`]) is used straight from the
+/// memory map instead of being rebuilt by every process that opens the
+/// keyword index.
+pub struct SpellIndex {
+    data: Data,
+    layout: Layout,
+}
+
+enum Data {
+    Built(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Deref for Data {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Data::Built(bytes) => bytes,
+            Data::Mapped(map) => map,
+        }
+    }
+}
+
+/// Entry counts and where each section starts. Offset tables hold one more
+/// entry than their section, the last being its length.
+struct Layout {
+    words: usize,
+    variants: usize,
+    word_offsets: usize,
+    freqs: usize,
+    variant_offsets: usize,
+    posting_offsets: usize,
+    word_bytes: usize,
+    variant_bytes: usize,
+    postings: usize,
+}
+
+impl Layout {
+    /// `None` when `data` is too short for the sections it declares
+    fn read(data: &[u8]) -> Option<Self> {
+        let words = read_u32(data, HEADER)? as usize;
+        let variants = read_u32(data, HEADER + 4)? as usize;
+        let word_offsets = HEADER + 8;
+        let freqs = word_offsets + (words + 1) * 4;
+        let variant_offsets = freqs + words * 8;
+        let posting_offsets = variant_offsets + (variants + 1) * 4;
+        let word_bytes = posting_offsets + (variants + 1) * 4;
+        let variant_bytes = word_bytes + read_u32(data, word_offsets + words * 4)? as usize;
+        let postings = variant_bytes + read_u32(data, variant_offsets + variants * 4)? as usize;
+        let end = postings + read_u32(data, posting_offsets + variants * 4)? as usize * 4;
+        (end <= data.len()).then_some(Self {
+            words,
+            variants,
+            word_offsets,
+            freqs,
+            variant_offsets,
+            posting_offsets,
+            word_bytes,
+            variant_bytes,
+            postings,
+        })
+    }
+}
+
+impl SpellIndex {
+    /// Build from `(word, frequency)` pairs; only lowercase ASCII words are kept
+    pub fn build(vocabulary: impl IntoIterator<Item = (String, u64)>) -> Self {
+        let mut words: Vec<(String, u64)> = vocabulary
+            .into_iter()
+            .filter(|(w, _)| is_correctable(w))
+            .collect();
+        words.sort_unstable();
+        words.dedup_by(|a, b| a.0 == b.0);
+
+        let mut deletes: HashMap<String, Vec<u32>> = HashMap::new();
+        for (idx, (word, _)) in words.iter().enumerate() {
+            let prefix: String = word.chars().take(PREFIX_LEN).collect();
+            for variant in edits(&prefix, MAX_EDIT_DISTANCE) {
+                deletes.entry(variant).or_default().push(idx as u32);
+            }
+        }
+        let mut deletes: Vec<(String, Vec<u32>)> = deletes.into_iter().collect();
+        deletes.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        push_u32(&mut data, words.len());
+        push_u32(&mut data, deletes.len());
+        push_offsets(&mut data, words.iter().map(|(w, _)| w.len()));
+        for (_, freq) in &words {
+            data.extend_from_slice(&freq.to_le_bytes());
+        }
+        push_offsets(&mut data, deletes.iter().map(|(v, _)| v.len()));
+        push_offsets(&mut data, deletes.iter().map(|(_, indices)| indices.len()));
+        for (word, _) in &words {
+            data.extend_from_slice(word.as_bytes());
+        }
+        for (variant, _) in &deletes {
+            data.extend_from_slice(variant.as_bytes());
+        }
+        for idx in deletes.iter().flat_map(|(_, indices)| indices) {
+            data.extend_from_slice(&idx.to_le_bytes());
+        }
+
+        let layout = Layout::read(&data).expect("layout of a freshly built index");
+        Self {
+            data: Data::Built(data),
+            layout,
+        }
+    }
+
+    /// Map the index saved at `path`, if it was built from the keyword index
+    /// at `opstamp`
+    pub fn open(path: &Path, opstamp: u64) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        // SAFETY: the file is only ever replaced by renaming a new one over
+        // it (see `save`), never modified in place
+        let map = unsafe { Mmap::map(&file) }.ok()?;
+        if map.get(..8)? != MAGIC || read_u64(&map, 8)? != opstamp {
+            return None;
+        }
+        let layout = Layout::read(&map)?;
+        Some(Self {
+            data: Data::Mapped(map),
+            layout,
+        })
+    }
+
+    /// The index saved at `path` if it was built from the keyword index at
+    /// `opstamp`, else one built from `vocabulary`: the keyword index has
+    /// changed since, or was last written by a version that saved none
+    pub fn open_or_build<V>(
+        path: &Path,
+        opstamp: u64,
+        vocabulary: impl FnOnce() -> Option<V>,
+    ) -> Option<Self>
+    where
+        V: IntoIterator<Item = (String, u64)>,
+    {
+        Self::open(path, opstamp).or_else(|| Some(Self::build(vocabulary()?)))
+    }
+
+    /// Save for searchers of the keyword index at `opstamp`. The file is
+    /// written next to `path` and renamed over it, so processes that mapped
+    /// the previous one keep reading it intact.
+    pub fn save(&self, path: &Path, opstamp: u64) -> std::io::Result<()> {
+        let partial = path.with_extension("partial");
+        let mut file = File::create(&partial)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&opstamp.to_le_bytes())?;
+        file.write_all(&self.data[HEADER..])?;
+        file.sync_all()?;
+        std::fs::rename(&partial, path)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        let layout = &self.layout;
+        find(layout.words, word.as_bytes(), |idx| {
+            self.entry(layout.word_offsets, layout.word_bytes, idx, 1)
+        })
+        .is_some()
+    }
+
+    /// Heap held by the index; a mapped one lives in the page cache instead
+    pub fn heap_bytes(&self) -> usize {
+        match &self.data {
+            Data::Built(bytes) => bytes.len(),
+            Data::Mapped(_) => 0,
+        }
+    }
+
+    /// Known words within `MAX_EDIT_DISTANCE` of `word`, closest then most frequent first
+    pub fn candidates(&self, word: &str, limit: usize) -> Vec<String> {
+        let layout = &self.layout;
+        let prefix: String = word.chars().take(PREFIX_LEN).collect();
+        let mut seen = HashSet::new();
+        let mut found: Vec<(usize, u64, &str)> = Vec::new();
+
+        for variant in edits(&prefix, MAX_EDIT_DISTANCE) {
+            let Some(postings) = find(layout.variants, variant.as_bytes(), |idx| {
+                self.entry(layout.variant_offsets, layout.variant_bytes, idx, 1)
+            })
+            .and_then(|idx| self.entry(layout.posting_offsets, layout.postings, idx, 4)) else {
+                continue;
+            };
+            for bytes in postings.chunks_exact(4) {
+                let idx = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+                if !seen.insert(idx) {
+                    continue;
+                }
+                let Some(candidate) = self
+                    .entry(layout.word_offsets, layout.word_bytes, idx, 1)
+                    .and_then(|bytes| std::str::from_utf8(bytes).ok())
+                else {
+                    continue;
+                };
+                if candidate == word {
+                    continue;
+                }
+                let distance = osa_distance(word, candidate);
+                if distance <= MAX_EDIT_DISTANCE {
+                    let freq = read_u64(&self.data, layout.freqs + idx * 8).unwrap_or(0);
+                    found.push((distance, freq, candidate));
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+        found
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, w)| w.to_string())
+            .collect()
+    }
+
+    /// Entry `idx` of the section at `section`, whose offset table (counted
+    /// in items of `width` bytes) is at `offsets`
+    fn entry(&self, offsets: usize, section: usize, idx: usize, width: usize) -> Option<&[u8]> {
+        let start = read_u32(&self.data, offsets + idx * 4)? as usize;
+        let end = read_u32(&self.data, offsets + idx * 4 + 4)? as usize;
+        self.data
+            .get(section + start * width..section + end * width)
+    }
+}
+
+/// Index of `key` among `count` sorted entries
+fn find<'a>(count: usize, key: &[u8], entry: impl Fn(usize) -> Option<&'a [u8]>) -> Option<usize> {
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match entry(mid)?.cmp(key) {
+            std::cmp::Ordering::Less => low = mid + 1,
+            std::cmp::Ordering::Greater => high = mid,
+            std::cmp::Ordering::Equal => return Some(mid),
+        }
+    }
+    None
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(pos..pos + 8)?.try_into().ok()?))
+}
+
+fn push_u32(data: &mut Vec<u8>, value: usize) {
+    data.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+/// Offset table of entries of the given lengths
+fn push_offsets(data: &mut Vec<u8>, lengths: impl Iterator<Item = usize>) {
+    let mut offset = 0;
+    push_u32(data, 0);
+    for len in lengths {
+        offset += len;
+        push_u32(data, offset);
+    }
+}
+
+/// Whether a word is a candidate for correction (and for the vocabulary)
+pub fn is_correctable(word: &str) -> bool {
+    word.len() >= MIN_WORD_LEN && word.chars().all(|c| c.is_ascii_lowercase())
+}
+
+/// The word itself plus every string reachable by up to `max` deletions
+fn edits(word: &str, max: usize) -> HashSet<String> {
+    let mut all = HashSet::from([word.to_string()]);
+    let mut frontier = vec![word.to_string()];
+    for _ in 0..max {
+        let mut next = Vec::new();
+        for w in &frontier {
+            let chars: Vec<char> = w.chars().collect();
+            if chars.len() <= 1 {
+                continue;
+            }
+            for i in 0..chars.len() {
+                let variant: String = chars[..i].iter().chain(&chars[i + 1..]).collect();
+                if all.insert(variant.clone()) {
+                    next.push(variant);
+                }
+            }
+        }
+        frontier = next;
+    }
+    all
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn osa_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SpellIndex {
+        SpellIndex::build(
+            [
+                ("embedding", 40),
+                ("embeddings", 5),
+                ("search", 90),
+                ("searcher", 10),
+                ("vector", 30),
+            ]
+            .map(|(w, f)| (w.to_string(), f)),
+        )
+    }
+
+    #[test]
+    fn test_corrects_typos() {
+        let spell = index();
+        assert_eq!(spell.candidates("embeding", 1), vec!["embedding"]);
+        assert_eq!(spell.candidates("serach", 1), vec!["search"]);
+        assert_eq!(spell.candidates("vectr", 1), vec!["vector"]);
+        assert!(spell.candidates("quantum", 1).is_empty());
+    }
+
+    #[test]
+    fn test_saved_index_is_mapped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("spelling.idx");
+        index().save(&path, 7).unwrap();
+
+        assert!(SpellIndex::open(&path, 8).is_none());
+        let spell = SpellIndex::open(&path, 7).unwrap();
+        assert_eq!(spell.heap_bytes(), 0);
+        assert!(spell.contains("searcher"));
+        assert!(!spell.contains("searchers"));
+        assert_eq!(
+            spell.candidates("embeding", 2),
+            vec!["embedding", "embeddings"]
+        );
+        drop(spell);
+
+        let truncated = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &truncated[..truncated.len() - 1]).unwrap();
+        assert!(SpellIndex::open(&path, 7).is_none());
+    }
+
+    #[test]
+    fn test_stale_saved_index_is_rebuilt() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("spelling.idx");
+        index().save(&path, 7).unwrap();
+        let vocabulary = || Some([("quantum".to_string(), 3)]);
+
+        let current = SpellIndex::open_or_build(&path, 7, vocabulary).unwrap();
+        assert_eq!(current.heap_bytes(), 0);
+        assert!(current.contains("searcher"));
+
+        let rebuilt = SpellIndex::open_or_build(&path, 8, vocabulary).unwrap();
+        assert!(rebuilt.heap_bytes() > 0);
+        assert!(rebuilt.contains("quantum"));
+        assert!(!rebuilt.contains("searcher"));
+    }
+
+    #[test]
+    fn test_osa_distance() {
+        assert_eq!(osa_distance("search", "serach"), 1);
+        assert_eq!(osa_distance("kitten", "sitting"), 3);
+        assert!(index().contains("search"));
+        assert!(!is_correctable("东西"));
+    }
+}
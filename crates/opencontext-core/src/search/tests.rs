@@ -115,6 +115,47 @@ mod tests {
             assert_eq!(opts.aggregate_by(), AggregateBy::Doc);
         }
 
+        #[test]
+        fn test_group_by_doc_options() {
+            let opts = SearchOptions {
+                aggregate_by: Some(AggregateBy::Content),
+                group_by: Some(GroupBy::Doc),
+                ..Default::default()
+            };
+            assert_eq!(opts.aggregate_by(), AggregateBy::Doc);
+            assert_eq!(opts.group_children(), 3);
+            assert_eq!(SearchOptions::default().group_children(), 0);
+        }
+
+        #[test]
+        fn test_search_hit_projection() {
+            let mut hit = SearchHit {
+                chunk_id: Some("a/b.md#0".to_string()),
+                display_name: "b".to_string(),
+                content: "word ".repeat(100),
+                heading_path: Some("Intro".to_string()),
+                line_start: Some(1),
+                line_end: Some(4),
+                score: 0.9,
+                doc_type: Some("doc".to_string()),
+                ..SearchHit::fixture("a/b.md", MatchType::Hybrid)
+            };
+            let fields = ["file_path", "score", "snippet"].map(String::from);
+            hit.project(&fields);
+
+            let json = serde_json::to_value(&hit).unwrap();
+            let keys: Vec<&str> = json
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|k| k.as_str())
+                .collect();
+            assert_eq!(keys.len(), 4, "{keys:?}");
+            let snippet = hit.snippet.unwrap();
+            assert!(snippet.ends_with('…'));
+            assert_eq!(snippet.chars().count(), 241);
+        }
+
         #[test]
         fn test_search_results_empty() {
             let results = SearchResults::empty("test query".to_string());
@@ -135,6 +176,9 @@ mod tests {
         fn test_search_results_index_not_built() {
             let results = SearchResults::index_not_built("query".to_string());
             assert!(results.index_missing.unwrap_or(false));
+            let diagnostics = results.diagnostics.unwrap();
+            assert!(!diagnostics.index_exists);
+            assert_eq!(diagnostics.chunks, 0);
         }
     }
 
@@ -155,6 +199,34 @@ mod tests {
             assert!(!config.model.is_empty());
             assert!(config.dimensions > 0);
             assert!(config.batch_size > 0);
+            assert!(!config.include_heading_context);
+        }
+
+        #[test]
+        fn test_include_heading_context_from_toml() {
+            let config: SearchConfig =
+                toml::from_str("[embedding]\ninclude_heading_context = true\n").unwrap();
+            assert!(config.embedding.include_heading_context);
+            assert_eq!(
+                config.embedding.batch_size,
+                EmbeddingConfig::default().batch_size
+            );
+        }
+
+        #[test]
+        fn test_collection_paths() {
+            let mut config: SearchConfig =
+                toml::from_str("[paths]\ncollection = \"acme corp\"\n").unwrap();
+            assert_eq!(config.paths.collection(), "acme corp");
+            let name =
+                |p: std::path::PathBuf| p.file_name().unwrap().to_string_lossy().into_owned();
+            assert_eq!(name(config.paths.get_bm25_path()), "bm25-index.acme_corp");
+            assert_eq!(name(config.paths.get_sparse_path()), "sparse.acme_corp.db");
+            assert_eq!(name(config.paths.get_lancedb_path()), "lancedb");
+
+            config.paths.collection = None;
+            assert_eq!(config.paths.collection(), DEFAULT_COLLECTION);
+            assert_eq!(name(config.paths.get_bm25_path()), "bm25-index");
         }
     }
 
@@ -0,0 +1,481 @@
+//! Text analysis for the BM25 content and title fields
+//!
+//! Word splitting is pluggable: the workspace's `[tokenizer] kind` picks a
+//! [`Tokenizer`] from the registry (`"default"` splits on characters that
+//! are neither alphanumeric nor `_`). Code-style identifiers among its words
+//! are indexed whole and as their parts (`vector_store` → `vector_store`,
+//! `vector`, `store`), and dates and numbers also get a canonical token (see
+//! `canonical`). Words then go through long-token removal, NFKC normalization, lowercasing, the optional passes
+//! from `[tokenizer]` and English stemming. Single-letter tokens ("C", "R")
+//! are kept. The registered name encodes the tokenizer and the enabled
+//! passes, so the BM25 schema changes — and the index is rebuilt — whenever
+//! they change.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use icu_normalizer::ComposingNormalizerBorrowed;
+use parking_lot::RwLock;
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, Language, LowerCaser, RemoveLongFilter, Stemmer, TextAnalyzer, Token,
+    TokenFilter, TokenStream, Tokenizer as TantivyTokenizer,
+};
+use tantivy::Index;
+
+use super::canonical;
+use super::config::TokenizerConfig;
+
+/// Traditional → Simplified character pairs (OpenCC `TSCharacters` subset
+/// covering the characters common in technical and everyday notes)
+const TRADITIONAL_SIMPLIFIED: &str = "\
+們们個个來来這这時时說说國国學学對对會会發发開开關关問问題题東东車车長长門门見见聞闻\
+書书話话語语讀读寫写記记錄录電电腦脑網网絡络線线數数據据庫库檔档資资訊讯號号碼码類类\
+別别頁页體体驗验專专業业務务員员實实現现標标準准設设計计劃划規规則则應应該该讓让還还\
+進进過过邊边當当從从後后給给將将點点麼么樣样無无與与為为層层級级並并處处導导習习慣惯\
+價价鐘钟錢钱銀银單单雙双變变換换擇择選选項项優优質质產产條条係系統统歷历歲岁萬万億亿\
+兩两氣气溫温雲云視视頻频圖图館馆藝艺術术聽听聲声樂乐歡欢愛爱戀恋戰战爭争論论議议證证\
+顯显籤签簽签鍵键盤盘擊击尋寻敗败敵敌軍军隊队團团機机構构築筑夢梦醫医療疗藥药師师範范\
+詞词彙汇義义總总結结綁绑縮缩續续繼继維维護护紀纪製制運运動动態态勢势頭头筆笔報报紙纸\
+區区縣县鄉乡鎮镇島岛灣湾臺台陸陆漢汉華华廣广亞亚歐欧韓韩羅罗馬马魚鱼鳥鸟龍龙龜龟貓猫\
+豬猪雞鸡鴨鸭蟲虫風风飛飞葉叶親亲戶户廳厅廚厨廁厕貝贝買买賣卖貨货費费購购貸贷帳账賬账\
+稅税經经濟济貿贸際际險险慶庆節节課课試试練练講讲認认識识譯译讚赞謝谢請请詢询評评訂订\
+訪访調调誤误錯错誰谁談谈諾诺謎谜龐庞廢废廟庙歸归難难離离雜杂雖虽響响頂顶順顺須须預预\
+領领顧顾飯饭飲饮餘余驅驱驚惊鬥斗鬧闹齊齐齒齿麥麦黃黄黨党鐵铁鋼钢錶表鏡镜閉闭間间閱阅\
+闆板階阶陽阳陰阴隨随靜静韻韵頓顿額额顏颜養养髮发鬆松魯鲁鮮鲜鳳凤鳴鸣麗丽齡龄組组細细\
+終终絕绝綠绿緒绪緣缘編编緩缓織织繪绘約约紅红純纯紹绍絲丝綜综緊紧績绩繩绳繳缴纖纤纜缆\
+屬属帶带幫帮幹干張张強强彈弹徑径復复徵征憂忧慮虑懷怀戲戏擁拥擔担擴扩攝摄擬拟揮挥損损\
+搖摇摺折撥拨擋挡擠挤擾扰攜携斷断晝昼暫暂極极樓楼樹树橋桥檢检權权殘残殺杀毀毁湯汤滅灭\
+滿满漁渔漲涨潔洁潛潜澤泽濃浓濕湿災灾烏乌煙烟煩烦熱热營营燈灯燒烧爐炉牆墙狀状獨独獲获\
+猶犹獎奖畫画異异疊叠盡尽監监眾众確确礎础禮礼禱祷種种積积稱称穩稳窮穷競竞簡简糧粮聖圣\
+聯联聰聪職职肅肃脅胁膚肤臉脸興兴舉举舊旧艙舱艦舰藍蓝蘭兰虛虚蝦虾衛卫衝冲補补裝装裡里\
+複复覺觉覽览觀观觸触許许診诊詩诗詳详誠诚諸诸謀谋負负財财貢贡貧贫販贩貪贪責责貴贵貼贴\
+賓宾賞赏賠赔賢贤賴赖贈赠趕赶趙赵跡迹踐践蹤踪軌轨軟软轉转輕轻載载較较輔辅輪轮輸输辦办\
+農农遠远遞递適适遺遗郵邮鄰邻醜丑釋释針针鈔钞鋒锋銷销鎖锁鏈链鑰钥閒闲闊阔陣阵陳陈隱隐\
+靈灵飄飘飢饥駕驾騎骑騙骗鹽盐麵面黴霉齋斋變变襯衬險险樁桩錨锚屆届";
+
+fn traditional_map() -> &'static HashMap<char, char> {
+    static MAP: OnceLock<HashMap<char, char>> = OnceLock::new();
+    MAP.get_or_init(|| {
+        let chars: Vec<char> = TRADITIONAL_SIMPLIFIED.chars().collect();
+        chars.chunks_exact(2).map(|p| (p[0], p[1])).collect()
+    })
+}
+
+/// Map Traditional Chinese characters to their Simplified forms
+pub fn to_simplified(text: &str) -> String {
+    let map = traditional_map();
+    text.chars().map(|c| *map.get(&c).unwrap_or(&c)).collect()
+}
+
+/// NFKC-normalize text: full-width forms become half-width, ligatures and
+/// compatibility characters are expanded, accents are composed
+pub fn nfkc(text: &str) -> String {
+    ComposingNormalizerBorrowed::new_nfkc()
+        .normalize(text)
+        .into_owned()
+}
+
+/// Apply the configured normalization passes to text that is about to be
+/// embedded, so vectors agree with the keyword index. Diacritics are left
+/// alone; embedding models handle them well and they carry meaning.
+pub fn normalize_for_embedding(text: &str, config: &TokenizerConfig) -> String {
+    let text = nfkc(text);
+    if config.chinese_normalization {
+        to_simplified(&text)
+    } else {
+        text
+    }
+}
+
+/// A word of the input text with its byte range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub text: String,
+    pub offset_from: usize,
+    pub offset_to: usize,
+}
+
+/// Splits text into words for the keyword index and keyword queries.
+///
+/// Implement this to plug in a segmenter for languages without spaces
+/// (e.g. wrap `jieba_rs::Jieba::cut_for_search` for Chinese) or a domain
+/// tokenizer, then [`register_tokenizer`] it and select it with
+/// `[tokenizer] kind = "<name>"`. Normalization and stemming are applied
+/// afterwards, so implementations only need to find word boundaries.
+pub trait Tokenizer: Send + Sync {
+    fn tokenize(&self, text: &str) -> Vec<Word>;
+}
+
+/// Splits on every character that is neither alphanumeric nor `_`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultTokenizer;
+
+impl Tokenizer for DefaultTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Word> {
+        let mut words = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices() {
+            match (c.is_alphanumeric() || c == '_', start) {
+                (true, None) => start = Some(i),
+                (false, Some(from)) => {
+                    words.push(word(text, from, i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(from) = start {
+            words.push(word(text, from, text.len()));
+        }
+        words
+    }
+}
+
+fn word(text: &str, from: usize, to: usize) -> Word {
+    Word {
+        text: text[from..to].to_string(),
+        offset_from: from,
+        offset_to: to,
+    }
+}
+
+/// Split a snake_case / camelCase / PascalCase identifier into its parts,
+/// preceded by the whole compound. Other words come back unchanged (minus
+/// any surrounding underscores).
+pub fn split_identifier(word: Word) -> Vec<Word> {
+    let chars: Vec<(usize, char)> = word.text.char_indices().collect();
+    let mut parts: Vec<(usize, usize)> = Vec::new();
+    let mut start: Option<usize> = None;
+    for (idx, &(i, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(from) = start.take() {
+                parts.push((from, i));
+            }
+            continue;
+        }
+        if let (Some(from), Some(&(_, prev))) = (start, idx.checked_sub(1).map(|p| &chars[p])) {
+            let next_lower = chars.get(idx + 1).is_some_and(|(_, n)| n.is_lowercase());
+            let boundary = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_uppercase() && next_lower));
+            if boundary {
+                parts.push((from, i));
+                start = Some(i);
+            }
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(from) = start {
+        parts.push((from, word.text.len()));
+    }
+
+    let base = word.offset_from;
+    let part = |(from, to): (usize, usize)| Word {
+        text: word.text[from..to].to_string(),
+        offset_from: base + from,
+        offset_to: base + to,
+    };
+    match parts.len() {
+        0 => vec![],
+        1 if parts[0] == (0, word.text.len()) => vec![word],
+        1 => vec![part(parts[0])],
+        _ => {
+            let (from, to) = (parts[0].0, parts[parts.len() - 1].1);
+            let mut words = vec![part((from, to))];
+            words.extend(parts.into_iter().map(part));
+            words
+        }
+    }
+}
+
+type Registry = RwLock<HashMap<String, Arc<dyn Tokenizer>>>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let default: Arc<dyn Tokenizer> = Arc::new(DefaultTokenizer);
+        RwLock::new(HashMap::from([("default".to_string(), default)]))
+    })
+}
+
+/// Make a tokenizer selectable as `[tokenizer] kind = "<name>"`. Register
+/// before opening a `Searcher` or `Indexer`; re-registering a name replaces it.
+pub fn register_tokenizer(name: &str, tokenizer: Arc<dyn Tokenizer>) {
+    registry().write().insert(name.to_string(), tokenizer);
+}
+
+/// The tokenizer for `kind`, falling back to the default for unknown names
+fn lookup(kind: &str) -> Arc<dyn Tokenizer> {
+    let registry = registry().read();
+    match registry.get(kind) {
+        Some(tokenizer) => tokenizer.clone(),
+        None => {
+            log::warn!(
+                "[Tokenizer] Unknown tokenizer '{}', using the default",
+                kind
+            );
+            registry["default"].clone()
+        }
+    }
+}
+
+/// Adapts a [`Tokenizer`] to tantivy's tokenizer interface
+#[derive(Clone)]
+struct Adapter(Arc<dyn Tokenizer>);
+
+impl TantivyTokenizer for Adapter {
+    type TokenStream<'a> = WordStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> WordStream {
+        let mut words: Vec<Word> = self
+            .0
+            .tokenize(text)
+            .into_iter()
+            .flat_map(split_identifier)
+            .collect();
+        words.extend(canonical::canonical_tokens(text));
+        words.sort_by_key(|w| w.offset_from);
+        WordStream {
+            words: words.into_iter(),
+            token: Token::default(),
+        }
+    }
+}
+
+struct WordStream {
+    words: std::vec::IntoIter<Word>,
+    token: Token,
+}
+
+impl TokenStream for WordStream {
+    fn advance(&mut self) -> bool {
+        let Some(word) = self.words.next() else {
+            return false;
+        };
+        self.token.text = word.text;
+        self.token.offset_from = word.offset_from;
+        self.token.offset_to = word.offset_to;
+        self.token.position = self.token.position.wrapping_add(1);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+/// Register the content analyzer for `config` on `index` and return its name
+pub fn register(index: &Index, config: &TokenizerConfig) -> String {
+    let name = analyzer_name(config);
+    index.tokenizers().register(&name, analyzer(config));
+    name
+}
+
+/// The content analyzer for `config`, as used by the BM25 index
+pub(super) fn analyzer(config: &TokenizerConfig) -> TextAnalyzer {
+    let mut builder = TextAnalyzer::builder(Adapter(lookup(&config.kind)))
+        .filter(RemoveLongFilter::limit(40))
+        .filter(MapText(nfkc))
+        .filter(LowerCaser)
+        .dynamic();
+    if config.chinese_normalization {
+        builder = builder.filter_dynamic(MapText(to_simplified));
+    }
+    if config.fold_diacritics {
+        builder = builder.filter_dynamic(AsciiFoldingFilter);
+    }
+    builder
+        .filter_dynamic(Stemmer::new(Language::English))
+        .build()
+}
+
+/// Tokenizer name for `config`; differs for every combination of passes
+pub fn analyzer_name(config: &TokenizerConfig) -> String {
+    let mut name = format!("oc_text:{}+ident+canon+nfkc", config.kind);
+    if config.chinese_normalization {
+        name.push_str("+zh_s");
+    }
+    if config.fold_diacritics {
+        name.push_str("+fold");
+    }
+    name
+}
+
+/// Token filter rewriting non-ASCII token text with a mapping function
+#[derive(Clone)]
+pub struct MapText(fn(&str) -> String);
+
+impl TokenFilter for MapText {
+    type Tokenizer<T: TantivyTokenizer> = MapTextFilter<T>;
+
+    fn transform<T: TantivyTokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        MapTextFilter {
+            map: self.0,
+            tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MapTextFilter<T> {
+    map: fn(&str) -> String,
+    tokenizer: T,
+}
+
+impl<T: TantivyTokenizer> TantivyTokenizer for MapTextFilter<T> {
+    type TokenStream<'a> = MapTextStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        MapTextStream {
+            map: self.map,
+            tail: self.tokenizer.token_stream(text),
+        }
+    }
+}
+
+pub struct MapTextStream<T> {
+    map: fn(&str) -> String,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for MapTextStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        if !token.text.is_ascii() {
+            token.text = (self.map)(&token.text);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_paired() {
+        assert_eq!(TRADITIONAL_SIMPLIFIED.chars().count() % 2, 0);
+        let map = traditional_map();
+        for (t, s) in map {
+            assert_ne!(t, s, "{t} maps to itself");
+            assert!(!map.contains_key(s), "{s} is both a source and a target");
+        }
+    }
+
+    #[test]
+    fn test_traditional_matches_simplified() {
+        assert_eq!(to_simplified("資料庫設計"), "资料库设计");
+        assert_eq!(to_simplified("向量检索 vector"), "向量检索 vector");
+
+        let config = TokenizerConfig {
+            chinese_normalization: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokens(&config, "網路設定 Searching"),
+            vec!["网路设定", "search"]
+        );
+    }
+
+    fn tokens(config: &TokenizerConfig, text: &str) -> Vec<String> {
+        let index = Index::create_in_ram(tantivy::schema::Schema::builder().build());
+        let name = register(&index, config);
+        let mut analyzer = index.tokenizers().get(&name).unwrap();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_nfkc_and_diacritic_folding() {
+        let plain = TokenizerConfig::default();
+        assert_eq!(
+            tokens(&plain, "ＡＰＩ ﬁle ２０２４"),
+            vec!["api", "file", "2024"]
+        );
+        assert_eq!(tokens(&plain, "café"), vec!["café"]);
+        assert_eq!(tokens(&plain, "C and R"), vec!["c", "and", "r"]);
+
+        let folding = TokenizerConfig {
+            fold_diacritics: true,
+            ..Default::default()
+        };
+        assert_eq!(tokens(&folding, "Café naïve"), vec!["cafe", "naiv"]);
+        assert_ne!(analyzer_name(&plain), analyzer_name(&folding));
+    }
+
+    #[test]
+    fn test_registered_tokenizer() {
+        struct CharTokenizer;
+        impl Tokenizer for CharTokenizer {
+            fn tokenize(&self, text: &str) -> Vec<Word> {
+                text.char_indices()
+                    .filter(|(_, c)| !c.is_whitespace())
+                    .map(|(i, c)| word(text, i, i + c.len_utf8()))
+                    .collect()
+            }
+        }
+        register_tokenizer("chars", Arc::new(CharTokenizer));
+
+        let chars = TokenizerConfig {
+            kind: "chars".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(tokens(&chars, "向量 db"), vec!["向", "量", "d", "b"]);
+        assert_ne!(
+            analyzer_name(&chars),
+            analyzer_name(&TokenizerConfig::default())
+        );
+
+        let unknown = TokenizerConfig {
+            kind: "missing".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(tokens(&unknown, "vector store"), vec!["vector", "store"]);
+    }
+
+    #[test]
+    fn test_split_identifier() {
+        let texts = |text: &str| -> Vec<String> {
+            DefaultTokenizer
+                .tokenize(text)
+                .into_iter()
+                .flat_map(split_identifier)
+                .map(|w| w.text)
+                .collect()
+        };
+        assert_eq!(
+            texts("EmbeddingClient"),
+            vec!["EmbeddingClient", "Embedding", "Client"]
+        );
+        assert_eq!(
+            texts("vector_store"),
+            vec!["vector_store", "vector", "store"]
+        );
+        assert_eq!(texts("HTTPServer"), vec!["HTTPServer", "HTTP", "Server"]);
+        assert_eq!(texts("__init__ plain URL"), vec!["init", "plain", "URL"]);
+
+        let words = DefaultTokenizer.tokenize("use vector_store");
+        let store = split_identifier(words[1].clone()).pop().unwrap();
+        assert_eq!((store.offset_from, store.offset_to), (11, 16));
+    }
+}
@@ -0,0 +1,61 @@
+//! Trust levels as ranking priors
+//!
+//! Relevance alone ranks a clipped web page that repeats the query's words
+//! above the curated note that answers it. With `[trust]` levels configured
+//! (see [`TrustConfig`]) the searcher multiplies every candidate's score
+//! by the level of its folder and source type before filtering and
+//! aggregation, and returns the level with the hit as `trust`.
+
+use super::config::TrustConfig;
+use super::types::Provenance;
+use crate::relpath::is_within;
+
+/// Level of a result at `path` whose text was produced as `provenance`
+pub(super) fn level(config: &TrustConfig, path: &str, provenance: Option<&Provenance>) -> f32 {
+    let folder = config
+        .folders
+        .iter()
+        .filter(|(folder, _)| is_within(path, folder))
+        .max_by_key(|(folder, _)| folder.trim_end_matches('/').len())
+        .map_or(1.0, |(_, level)| *level);
+    let source = provenance
+        .and_then(|p| {
+            p.origin
+                .as_ref()
+                .and_then(|origin| config.sources.get(origin))
+                .or_else(|| config.sources.get(&p.source))
+        })
+        .copied()
+        .unwrap_or(1.0);
+    (folder * source).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_trust_level() {
+        let config = TrustConfig {
+            folders: BTreeMap::from([("projects".into(), 1.2), ("projects/old/".into(), 0.5)]),
+            sources: BTreeMap::from([("capture".into(), 0.8), ("web_clip".into(), 0.5)]),
+        };
+        let provenance = |source: &str, origin: Option<&str>| Provenance {
+            source: source.into(),
+            origin: origin.map(String::from),
+            ..Default::default()
+        };
+
+        assert_eq!(level(&config, "notes/a.md", None), 1.0);
+        assert_eq!(level(&config, "projects/a.md", None), 1.2);
+        assert_eq!(level(&config, "projects-b/a.md", None), 1.0);
+        assert_eq!(level(&config, "projects/old/a.md", None), 0.5);
+        let capture = provenance("capture", None);
+        assert_eq!(level(&config, "inbox/a.md", Some(&capture)), 0.8);
+        let clip = provenance("capture", Some("web_clip"));
+        assert_eq!(level(&config, "inbox/a.md", Some(&clip)), 0.5);
+        let note = provenance("markdown", Some("ocr"));
+        assert_eq!(level(&config, "projects/a.md", Some(&note)), 1.2);
+    }
+}
@@ -1,6 +1,9 @@
 //! Common types for search module
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::filter::Filter;
 
 /// A chunk of document content with its embedding
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,8 +54,31 @@ pub struct TextChunk {
     pub end_line: usize,
 }
 
+/// One sliding-window vector of a chunk, used by multi-vector (late interaction) search
+#[derive(Debug, Clone)]
+pub struct ChunkWindow {
+    /// Id of the chunk this window belongs to
+    pub chunk_id: String,
+    /// Relative path to the source document
+    pub file_path: String,
+    /// Index of this window within the chunk
+    pub window_index: usize,
+    /// Embedding vector of the window text
+    pub vector: Vec<f32>,
+}
+
+/// Sparse (SPLADE-style) term-weight vector
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SparseVector {
+    /// Vocabulary ids of non-zero terms
+    pub indices: Vec<u32>,
+    /// Weights, parallel to `indices`
+    pub values: Vec<f32>,
+}
+
 /// Search mode
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum SearchMode {
     /// Vector search only
@@ -62,10 +88,25 @@ pub enum SearchMode {
     /// Hybrid search (vector + keyword with RRF fusion)
     #[default]
     Hybrid,
+    /// Pick a strategy per query from its detected intent
+    Auto,
+}
+
+/// Embedding tier a query is answered from, see [`super::config::TiersConfig`]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingTier {
+    /// The small model only: cheap, for interactive search
+    Fast,
+    /// Fast recall, then re-scored with the main model
+    #[default]
+    Accurate,
 }
 
 /// Aggregation level for search results
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum AggregateBy {
     /// Return individual content chunks
@@ -77,19 +118,83 @@ pub enum AggregateBy {
     Folder,
 }
 
-/// How a result was matched
+/// Order of search results
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    /// Most relevant first
+    #[default]
+    Score,
+    /// Last modification of the document, newest first
+    Modified,
+    /// Creation of the document, newest first
+    Created,
+    /// Workspace path, A to Z
+    Path,
+    /// Date of the idea entry, newest first; other results go last
+    EntryDate,
+}
+
+impl SortBy {
+    /// Direction used when none is given
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortBy::Path => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        }
+    }
+}
+
+/// Direction of [`SortBy`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// How [`SearchHit::citation`] is rendered (see [`format_citation`](super::format_citation))
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CitationStyle {
+    /// `[Title](path#L12-L30)`
+    Link,
+    /// `[^1]: [Title](path#L12-L30)`, numbered by rank
+    Footnote,
+}
+
+/// Result grouping
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Flat results (shaped by `aggregate_by`)
+    #[default]
+    None,
+    /// One parent hit per document with its best chunks nested in `children`
+    Doc,
+}
+
+/// How a result was matched
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum MatchType {
     Vector,
     Keyword,
+    Sparse,
     #[serde(rename = "vector+keyword")]
     Hybrid,
 }
 
 /// Search options
-#[derive(Debug, Clone, Default, Deserialize)]
-#[serde(rename_all = "camelCase")]
+/// Uses snake_case; the camelCase names of earlier releases are still accepted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub struct SearchOptions {
     /// The search query
     pub query: String,
@@ -98,19 +203,76 @@ pub struct SearchOptions {
     /// Search mode
     pub mode: Option<SearchMode>,
     /// Aggregation level
+    #[serde(alias = "aggregateBy")]
     pub aggregate_by: Option<AggregateBy>,
-    /// Filter by document type: "doc" | "idea"
-    pub doc_type: Option<String>,
+    /// Restrict results by path, tag, document type, date or frontmatter
+    /// field (see [`Filter`])
+    pub filter: Option<Filter>,
     /// Filter by folder path prefix (e.g. "Product/opencontext")
+    #[serde(alias = "folderFilter")]
     pub folder_filter: Option<String>,
+    /// Only results from one of these sources. A source is a folder, a
+    /// document type or a [`Provenance`] source type or origin, e.g.
+    /// `["projects", "markdown"]`.
+    #[serde(alias = "includeSources")]
+    pub include_sources: Option<Vec<String>>,
+    /// Drop results from any of these sources (same values as
+    /// `include_sources`), e.g. `["web_clip", "inbox"]`
+    #[serde(alias = "excludeSources")]
+    pub exclude_sources: Option<Vec<String>>,
     /// Minimum relevance score 0–1 (results below this are dropped)
+    #[serde(alias = "minScore")]
     pub min_score: Option<f32>,
     /// Filter by entry date >= this date (ISO date, ideas only, e.g. "2024-01-01")
+    #[serde(alias = "dateFrom")]
     pub date_from: Option<String>,
     /// Filter by entry date <= this date (ISO date, ideas only)
+    #[serde(alias = "dateTo")]
     pub date_to: Option<String>,
     /// Number of neighboring chunks to include around each top result (0 = disabled)
+    #[serde(alias = "includeNeighbors")]
     pub include_neighbors: Option<usize>,
+    /// Group results by document, keeping child chunks (overrides `aggregate_by`)
+    #[serde(alias = "groupBy")]
+    pub group_by: Option<GroupBy>,
+    /// Maximum child chunks per group (default 3)
+    #[serde(alias = "groupChildren")]
+    pub group_children: Option<usize>,
+    /// Compute facet counts over the filtered candidate set
+    pub facets: Option<bool>,
+    /// Result fields to return (e.g. `["file_path", "score", "snippet"]`).
+    /// `file_path`, `score` and a requested `citation` are always kept;
+    /// `None` returns everything.
+    pub fields: Option<Vec<String>>,
+    /// Time budget for the embedding / vector stage in milliseconds; when it
+    /// runs out the query is answered from the keyword index alone
+    #[serde(alias = "timeoutMs")]
+    pub timeout_ms: Option<u64>,
+    /// Also match keyword results in the workspace's other languages
+    /// (defaults to `[cross_language] enabled`)
+    #[serde(alias = "crossLanguage")]
+    pub cross_language: Option<bool>,
+    /// Collection to search (defaults to `[paths] collection`)
+    pub collection: Option<String>,
+    /// Folders the caller may read (see [`crate::acl`]); hits elsewhere are
+    /// dropped. `None` means unrestricted.
+    #[serde(alias = "allowedFolders")]
+    pub allowed_folders: Option<Vec<String>>,
+    /// Attach a [`FolderSummary`] to each folder result (`aggregate_by = folder`)
+    #[serde(alias = "folderSummary")]
+    pub folder_summary: Option<bool>,
+    /// Embedding tier (defaults to `[embedding.tiers] default`; ignored
+    /// unless tiers are enabled)
+    pub tier: Option<EmbeddingTier>,
+    /// Order results by something other than relevance. Applied after
+    /// filtering, to the candidates the query matched.
+    #[serde(alias = "sortBy")]
+    pub sort_by: Option<SortBy>,
+    /// Direction of `sort_by` (defaults to [`SortBy::default_order`])
+    #[serde(alias = "sortOrder")]
+    pub sort_order: Option<SortOrder>,
+    /// Fill each result's `citation` in this style
+    pub citation: Option<CitationStyle>,
 }
 
 impl SearchOptions {
@@ -123,20 +285,41 @@ impl SearchOptions {
     }
 
     pub fn aggregate_by(&self) -> AggregateBy {
+        if self.group_by == Some(GroupBy::Doc) {
+            return AggregateBy::Doc;
+        }
         self.aggregate_by.unwrap_or_default()
     }
+
+    /// Child chunks to keep per document group (0 when not grouping)
+    pub fn group_children(&self) -> usize {
+        match self.group_by {
+            Some(GroupBy::Doc) => self.group_children.unwrap_or(3),
+            _ => 0,
+        }
+    }
 }
 
 /// A single search result
 /// Uses snake_case to match Node.js API format
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub struct SearchHit {
     /// File path of the matched document
     pub file_path: String,
+    /// Id of the matched chunk (as stored in the index)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_id: Option<String>,
     /// Display name for the document
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub display_name: String,
     /// Matched content snippet
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub content: String,
+    /// Short single-line excerpt of `content` (only when requested via `fields`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
     /// Heading path within the document
     #[serde(skip_serializing_if = "Option::is_none")]
     pub heading_path: Option<String>,
@@ -149,6 +332,9 @@ pub struct SearchHit {
     /// End line number
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line_end: Option<usize>,
+    /// `^block-id` of the block in `content` the query matches best
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
     /// Relevance score (0-1)
     pub score: f32,
     /// How this result was matched
@@ -180,12 +366,276 @@ pub struct SearchHit {
     /// Ideas box name (e.g. "inbox")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub idea_box: Option<String>,
+    /// Best matching chunks of this document, best first (`group_by = "doc"` only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SearchHit>,
+    /// Other documents with the same content, merged into this result
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub also_in: Vec<String>,
+    /// Overview of the folder (folder results with `folder_summary` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder_summary: Option<FolderSummary>,
+    /// Stats of the matched document, as recorded by the indexer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_meta: Option<DocMeta>,
+    /// Ready-to-paste citation (only when requested via `citation`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub citation: Option<String>,
+    /// How the matched text was produced, as recorded by the indexer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Trust level the score was multiplied by (only when `[trust]` levels
+    /// are configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trust: Option<f32>,
+}
+
+/// Overview of a folder result, for navigating to the right document
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FolderSummary {
+    /// Indexed documents directly in the folder, at most
+    /// [`FolderSummary::MAX_DOCS`]
+    pub docs: Vec<String>,
+    /// Number of indexed documents directly in the folder
+    pub doc_total: usize,
+    /// Headings of the folder's matching chunks, best match first
+    pub headings: Vec<String>,
+    /// Short description by the `[llm]` model (`search.folder_summary_llm`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+impl FolderSummary {
+    pub const MAX_DOCS: usize = 20;
+    pub const MAX_HEADINGS: usize = 8;
+}
+
+/// Document-level stats for rendering a result card
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DocMeta {
+    /// `title` frontmatter field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Words in the document body; each CJK character counts as one
+    pub words: usize,
+    /// Chunks the document was split into
+    pub chunks: usize,
+    /// Estimated reading time in minutes, at least 1
+    pub reading_minutes: usize,
+    /// Last modification of the file (ms since epoch)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<u64>,
+    /// Creation of the file (ms since epoch), where the filesystem records it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    /// How the document was produced. Saved with the stats and moved to
+    /// [`SearchHit::provenance`] on results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// Where an indexed text came from and how it was turned into chunks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Provenance {
+    /// Adapter that produced the text: `markdown` for notes written in the
+    /// workspace, `capture`, `idea`, `memory`, `session`, `git`, an importer
+    /// (`evernote`, `apple-notes`, `confluence`, `google-docs`, `bibtex`) or
+    /// a synced source (`github`, `jira`, `linear`)
+    pub source: String,
+    /// How the text was obtained when nobody typed it: `web_clip`, `ocr`
+    /// or `transcript`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// Page or item the text was imported from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Chunking strategy and its version, e.g. `markdown/1`
+    pub chunker: String,
+}
+
+/// A text passed along with a query and used for that query only, such as
+/// the unsaved document open in the editor
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextDoc {
+    /// Workspace path of the text, if it is a document of the workspace.
+    /// Chunks of that document are left out of the results.
+    pub path: Option<String>,
+    pub content: String,
+}
+
+/// A completion for a partially typed query
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Text to show / insert
+    pub text: String,
+    /// Where it came from: 'title' | 'heading' | 'tag'
+    pub kind: String,
+    /// Number of indexed chunks carrying it
+    pub count: usize,
+}
+
+/// Result of `Searcher::warm_up`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Readiness {
+    /// Queries will be served from warm indexes
+    pub ready: bool,
+    /// Whether the vector index has been built
+    pub index_exists: bool,
+    /// Rows in the vector table
+    pub vector_chunks: usize,
+    /// Documents in the keyword index
+    pub keyword_chunks: usize,
+    /// Embedding endpoint reachable (`None` when not pinged)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_ok: Option<bool>,
+    /// Time spent warming up
+    pub elapsed_ms: u64,
+    /// First failure encountered, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One facet value and the number of matching documents / entries
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+/// Facet counts, each sorted by count descending
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Facets {
+    /// Parent folder of the matched document
+    pub folder: Vec<FacetCount>,
+    /// 'doc' | 'idea'
+    pub doc_type: Vec<FacetCount>,
+    /// Inline `#tags` found in matched chunks
+    pub tag: Vec<FacetCount>,
+    /// Entry month (YYYY-MM, ideas only)
+    pub month: Vec<FacetCount>,
+}
+
+/// Maximum characters in a projected `snippet`
+const SNIPPET_CHARS: usize = 240;
+
+impl SearchHit {
+    /// Drop every field not named in `fields`, filling `snippet` if requested
+    pub fn project(&mut self, fields: &[String]) {
+        let wants = |name: &str| fields.iter().any(|f| f == name);
+
+        if wants("snippet") {
+            self.snippet = Some(snippet(&self.content));
+        }
+        if !wants("content") {
+            self.content.clear();
+        }
+        if !wants("display_name") {
+            self.display_name.clear();
+        }
+        macro_rules! keep {
+            ($($field:ident),*) => {
+                $(if !wants(stringify!($field)) {
+                    self.$field = None;
+                })*
+            };
+        }
+        keep!(
+            chunk_id,
+            heading_path,
+            section_title,
+            line_start,
+            line_end,
+            block_id,
+            hit_count,
+            doc_count,
+            folder_path,
+            aggregate_type,
+            doc_type,
+            entry_id,
+            entry_date,
+            entry_created_at,
+            idea_box,
+            folder_summary,
+            doc_meta,
+            provenance,
+            trust
+        );
+        if wants("children") {
+            for child in &mut self.children {
+                child.project(fields);
+            }
+        } else {
+            self.children.clear();
+        }
+        if !wants("also_in") {
+            self.also_in.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+impl SearchHit {
+    /// A hit on `file_path` scoring 1.0 with every other field empty, for
+    /// tests to fill in with struct update syntax
+    pub(crate) fn fixture(file_path: &str, matched_by: MatchType) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            chunk_id: None,
+            display_name: String::new(),
+            content: String::new(),
+            snippet: None,
+            heading_path: None,
+            section_title: None,
+            line_start: None,
+            line_end: None,
+            block_id: None,
+            score: 1.0,
+            matched_by,
+            hit_count: None,
+            doc_count: None,
+            folder_path: None,
+            aggregate_type: None,
+            doc_type: None,
+            entry_id: None,
+            entry_date: None,
+            entry_created_at: None,
+            idea_box: None,
+            children: vec![],
+            also_in: vec![],
+            folder_summary: None,
+            doc_meta: None,
+            citation: None,
+            provenance: None,
+            trust: None,
+        }
+    }
+}
+
+/// Collapse whitespace and cut to `SNIPPET_CHARS`
+fn snippet(content: &str) -> String {
+    let text = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= SNIPPET_CHARS {
+        return text;
+    }
+    let mut cut: String = text.chars().take(SNIPPET_CHARS).collect();
+    cut.push('…');
+    cut
 }
 
 /// Search results response
 /// Uses snake_case to match Node.js API format
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub struct SearchResults {
+    /// Version of this JSON contract ([`API_SCHEMA_VERSION`](super::API_SCHEMA_VERSION))
+    pub schema_version: u32,
     /// The original query
     pub query: String,
     /// Search results
@@ -198,6 +648,37 @@ pub struct SearchResults {
     /// Aggregation type used
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregate_by: Option<String>,
+    /// Detected query intent (auto mode only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    /// Facet counts over the candidate set (when requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Facets>,
+    /// Number of matching results before `limit` was applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_candidates: Option<usize>,
+    /// Whether `total_candidates` is an exact count rather than an estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_exact: Option<bool>,
+    /// Whether more results exist beyond this page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
+    /// Set when the requested mode could not run and keyword results were returned instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded: Option<bool>,
+    /// "Did you mean" rewrites of the query (only when it has unknown words)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestions: Option<Vec<String>>,
+    /// Related queries: earlier queries sharing a word, then the query
+    /// expanded with terms that co-occur with it in the top results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related: Option<Vec<String>>,
+    /// How well the results cover the query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    /// Why nothing was found (only when `results` is empty)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<NoResultsDiagnostics>,
     /// Whether the index exists
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index_missing: Option<bool>,
@@ -206,14 +687,114 @@ pub struct SearchResults {
     pub error: Option<String>,
 }
 
+/// Estimate of whether the results answer the query (see
+/// `search::confidence`)
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Confidence {
+    /// 0–1, from term coverage, agreement of the retrieval signals and
+    /// how clearly the best result stands out
+    pub score: f32,
+    pub level: ConfidenceLevel,
+    /// Share of the query's content words found in the top results
+    pub term_coverage: f32,
+    /// Content words of the query none of the top results contain
+    pub missing_terms: Vec<String>,
+}
+
+/// [`Confidence::score`] in words
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ConfidenceLevel {
+    /// 0.7 and above: the results address the query
+    High,
+    /// 0.4 to 0.7: partial coverage; check the results before relying on them
+    Medium,
+    /// Below 0.4: the workspace likely doesn't cover the query
+    Low,
+}
+
+impl ConfidenceLevel {
+    pub fn from_score(score: f32) -> Self {
+        if score >= 0.7 {
+            ConfidenceLevel::High
+        } else if score >= 0.4 {
+            ConfidenceLevel::Medium
+        } else {
+            ConfidenceLevel::Low
+        }
+    }
+}
+
+/// Memory held by one component of a searcher
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ComponentMemory {
+    /// `chunk_cache`, `embedding_cache` or `keyword_index`
+    pub component: String,
+    /// Estimated size of what it holds now
+    pub bytes: usize,
+    /// Its share of the `[memory]` budget
+    pub limit_bytes: usize,
+    pub entries: usize,
+    /// Lookups served from memory, and those that were not
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to stay within the limit
+    pub evictions: u64,
+}
+
+/// Result of `Searcher::memory_usage`
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MemoryReport {
+    /// `[memory] budget_mb` in bytes
+    pub budget_bytes: usize,
+    pub used_bytes: usize,
+    pub components: Vec<ComponentMemory>,
+}
+
+/// What an empty result list is down to: a missing or empty index, words
+/// the workspace never uses, or filters that dropped every match
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NoResultsDiagnostics {
+    /// Whether the search index has been built
+    pub index_exists: bool,
+    /// Chunks in the keyword index
+    pub chunks: usize,
+    /// Matches retrieved before filters, `min_score` and folder access
+    /// dropped them all (counted up to the retrieval limit)
+    pub filtered_out: usize,
+    /// Query words that occur somewhere in the workspace
+    pub known_terms: Vec<String>,
+    /// Query words that occur nowhere in the workspace
+    pub absent_terms: Vec<String>,
+    /// Closest indexed words to each absent term, where there are any
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub nearest_terms: BTreeMap<String, Vec<String>>,
+}
+
 impl SearchResults {
     pub fn empty(query: String) -> Self {
         Self {
+            schema_version: super::API_SCHEMA_VERSION,
             query,
             results: vec![],
             count: 0,
             mode: None,
             aggregate_by: None,
+            intent: None,
+            facets: None,
+            total_candidates: None,
+            total_exact: None,
+            has_more: None,
+            degraded: None,
+            suggestions: None,
+            related: None,
+            confidence: None,
+            diagnostics: None,
             index_missing: None,
             error: None,
         }
@@ -221,11 +802,22 @@ impl SearchResults {
 
     pub fn with_error(query: String, error: String) -> Self {
         Self {
+            schema_version: super::API_SCHEMA_VERSION,
             query,
             results: vec![],
             count: 0,
             mode: None,
             aggregate_by: None,
+            intent: None,
+            facets: None,
+            total_candidates: None,
+            total_exact: None,
+            has_more: None,
+            degraded: None,
+            suggestions: None,
+            related: None,
+            confidence: None,
+            diagnostics: None,
             index_missing: None,
             error: Some(error),
         }
@@ -233,11 +825,22 @@ impl SearchResults {
 
     pub fn index_not_built(query: String) -> Self {
         Self {
+            schema_version: super::API_SCHEMA_VERSION,
             query,
             results: vec![],
             count: 0,
             mode: None,
             aggregate_by: None,
+            intent: None,
+            facets: None,
+            total_candidates: None,
+            total_exact: None,
+            has_more: None,
+            degraded: None,
+            suggestions: None,
+            related: None,
+            confidence: None,
+            diagnostics: Some(NoResultsDiagnostics::default()),
             index_missing: Some(true),
             error: None,
         }
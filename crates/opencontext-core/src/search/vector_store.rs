@@ -1,52 +1,90 @@
 //! LanceDB vector store
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use arrow_array::{
-    types::Float32Type, FixedSizeListArray, RecordBatch, RecordBatchIterator, StringArray,
-    UInt32Array,
+    types::Float32Type, Array, BinaryArray, FixedSizeListArray, RecordBatch, RecordBatchIterator,
+    StringArray, UInt32Array,
 };
 use arrow_schema::{DataType, Field, Schema};
 use futures::TryStreamExt;
-use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::table::NewColumnTransform;
 use lancedb::{connect, Connection, Table};
 
+use super::compression::ContentCodec;
+use super::config::DEFAULT_COLLECTION;
 use super::error::{SearchError, SearchResult};
-use super::types::{Chunk, MatchType, SearchHit};
-
-const TABLE_NAME: &str = "chunks";
+use super::schema::{
+    self, SchemaManifest, CHUNKS_TABLE as TABLE_NAME, SCHEMA_VERSION,
+    WINDOWS_TABLE as WINDOWS_TABLE_NAME,
+};
+use super::types::{Chunk, ChunkWindow, MatchType, SearchHit};
 
-/// LanceDB vector store for semantic search
+/// LanceDB vector store for semantic search.
+///
+/// Several logical collections can share one database: every row carries a
+/// `collection` column and each store instance reads and writes only the
+/// rows of its own collection. Collections share the embedding dimensions.
 pub struct VectorStore {
     db_path: PathBuf,
     dimensions: usize,
+    collection: String,
     db: Option<Connection>,
     table: Option<Table>,
+    /// Per-window vectors for multi-vector (late interaction) mode
+    windows_table: Option<Table>,
+    /// Compresses the `content_zstd` column
+    codec: ContentCodec,
 }
 
 impl VectorStore {
     /// Create a new vector store
     pub fn new(db_path: PathBuf, dimensions: usize) -> Self {
         Self {
+            codec: ContentCodec::new(&db_path),
             db_path,
             dimensions,
+            collection: DEFAULT_COLLECTION.to_string(),
             db: None,
             table: None,
+            windows_table: None,
         }
     }
 
+    /// Restrict the store to a named collection
+    pub fn with_collection(mut self, collection: &str) -> Self {
+        self.collection = collection.to_string();
+        self
+    }
+
+    /// Collection this store reads and writes
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    /// SQL predicate selecting this store's rows
+    fn collection_filter(&self) -> String {
+        format!("collection = '{}'", self.collection.replace('\'', "''"))
+    }
+
     /// Initialize the database connection
     pub async fn initialize(&mut self) -> SearchResult<()> {
         // Create directory if it doesn't exist
         if let Some(parent) = self.db_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            std::fs::create_dir_all(parent).map_err(|e| SearchError::from(e).with_path(parent))?;
         }
 
         let db = connect(self.db_path.to_string_lossy().as_ref())
             .execute()
             .await
-            .map_err(SearchError::Lance)?;
+            .map_err(|e| {
+                SearchError::Lance(e)
+                    .with_operation("open vector store")
+                    .with_path(&self.db_path)
+            })?;
 
         self.db = Some(db);
 
@@ -63,6 +101,7 @@ impl VectorStore {
                     .execute()
                     .await
                     .map_err(SearchError::Lance)?;
+                self.migrate(TABLE_NAME, &table).await?;
 
                 // Detect dimension mismatch — warn if existing index was built with different dims
                 if let Ok(schema) = table.schema().await {
@@ -92,11 +131,61 @@ impl VectorStore {
                     self.table = Some(table);
                 }
             }
+
+            if table_names.contains(&WINDOWS_TABLE_NAME.to_string()) {
+                let table = db
+                    .open_table(WINDOWS_TABLE_NAME)
+                    .execute()
+                    .await
+                    .map_err(SearchError::Lance)?;
+                self.migrate(WINDOWS_TABLE_NAME, &table).await?;
+                self.windows_table = Some(table);
+            }
         }
 
         Ok(())
     }
 
+    /// Bring an opened table up to [`SCHEMA_VERSION`], adding the columns
+    /// of every migration it has not seen yet
+    async fn migrate(&self, name: &str, table: &Table) -> SearchResult<()> {
+        let mut manifest = SchemaManifest::load(&self.db_path);
+        let from = manifest.version(name);
+        schema::check_supported(name, from)?;
+        if from == SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        let existing = table.schema().await.map_err(SearchError::Lance)?;
+        let columns = schema::pending_columns(name, from, |c| existing.field_with_name(c).is_ok());
+        if !columns.is_empty() {
+            log::info!(
+                "[VectorStore] Upgrading table '{}' from schema v{} to v{}: adding {}",
+                name,
+                from,
+                SCHEMA_VERSION,
+                columns
+                    .iter()
+                    .map(|(c, _)| c.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            table
+                .add_columns(NewColumnTransform::SqlExpressions(columns), None)
+                .await
+                .map_err(SearchError::Lance)?;
+        }
+        manifest.set(name, SCHEMA_VERSION);
+        manifest.save(&self.db_path)
+    }
+
+    /// Record a freshly created table at the current schema version
+    fn record_schema_version(&self, name: &str) -> SearchResult<()> {
+        let mut manifest = SchemaManifest::load(&self.db_path);
+        manifest.set(name, SCHEMA_VERSION);
+        manifest.save(&self.db_path)
+    }
+
     /// Check if index exists
     pub async fn exists(&self) -> bool {
         self.table.is_some()
@@ -124,6 +213,8 @@ impl VectorStore {
                 ),
                 false,
             ),
+            Field::new("collection", DataType::Utf8, true),
+            Field::new("content_zstd", DataType::Binary, true),
         ]))
     }
 
@@ -138,15 +229,19 @@ impl VectorStore {
             .as_ref()
             .ok_or_else(|| SearchError::VectorStore("Database not initialized".to_string()))?;
 
+        if self.table.is_none() {
+            // The dictionary has to stay as long as the table it compressed
+            let samples: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+            self.codec.train(&samples)?;
+        }
         let schema = self.create_schema();
         let batch = self.chunks_to_batch(&chunks, schema.clone())?;
         let count = batch.num_rows();
 
-        // Wrap in iterator
-        let batches = RecordBatchIterator::new(vec![Ok(batch)], schema.clone());
-
         if let Some(table) = self.table.as_ref() {
             // Add to existing table
+            let batch = aligned(table, batch).await?;
+            let batches = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
             table
                 .add(Box::new(batches))
                 .execute()
@@ -154,22 +249,228 @@ impl VectorStore {
                 .map_err(SearchError::Lance)?;
         } else {
             // Create new table
+            let batches = RecordBatchIterator::new(vec![Ok(batch)], schema);
             let table = db
                 .create_table(TABLE_NAME, Box::new(batches))
                 .execute()
                 .await
                 .map_err(SearchError::Lance)?;
+            self.record_schema_version(TABLE_NAME)?;
             self.table = Some(table);
         }
 
         Ok(count)
     }
 
+    /// Schema of the per-window vector table
+    fn create_windows_schema(&self) -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("chunk_id", DataType::Utf8, false),
+            Field::new("file_path", DataType::Utf8, false),
+            Field::new("window_index", DataType::UInt32, false),
+            Field::new(
+                "vector",
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    self.dimensions as i32,
+                ),
+                false,
+            ),
+            Field::new("collection", DataType::Utf8, true),
+        ]))
+    }
+
+    /// Insert per-window vectors for multi-vector mode
+    pub async fn upsert_windows(&mut self, windows: Vec<ChunkWindow>) -> SearchResult<usize> {
+        if windows.is_empty() {
+            return Ok(0);
+        }
+
+        let db = self
+            .db
+            .as_ref()
+            .ok_or_else(|| SearchError::VectorStore("Database not initialized".to_string()))?;
+
+        let schema = self.create_windows_schema();
+        let chunk_ids: Vec<&str> = windows.iter().map(|w| w.chunk_id.as_str()).collect();
+        let file_paths: Vec<&str> = windows.iter().map(|w| w.file_path.as_str()).collect();
+        let window_indices: Vec<u32> = windows.iter().map(|w| w.window_index as u32).collect();
+        let vectors_array = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            windows
+                .iter()
+                .map(|w| Some(w.vector.iter().copied().map(Some).collect::<Vec<_>>())),
+            self.dimensions as i32,
+        );
+
+        let collections = vec![self.collection.as_str(); windows.len()];
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(chunk_ids)),
+                Arc::new(StringArray::from(file_paths)),
+                Arc::new(UInt32Array::from(window_indices)),
+                Arc::new(vectors_array),
+                Arc::new(StringArray::from(collections)),
+            ],
+        )
+        .map_err(|e| SearchError::VectorStore(e.to_string()))?;
+        let count = batch.num_rows();
+
+        if let Some(table) = self.windows_table.as_ref() {
+            let batch = aligned(table, batch).await?;
+            let batches = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+            table
+                .add(Box::new(batches))
+                .execute()
+                .await
+                .map_err(SearchError::Lance)?;
+        } else {
+            let batches = RecordBatchIterator::new(vec![Ok(batch)], schema);
+            let table = db
+                .create_table(WINDOWS_TABLE_NAME, Box::new(batches))
+                .execute()
+                .await
+                .map_err(SearchError::Lance)?;
+            self.record_schema_version(WINDOWS_TABLE_NAME)?;
+            self.windows_table = Some(table);
+        }
+
+        Ok(count)
+    }
+
+    /// Load window vectors for the given chunk ids, ordered by window index
+    pub async fn get_windows(
+        &self,
+        chunk_ids: &[String],
+    ) -> SearchResult<HashMap<String, Vec<Vec<f32>>>> {
+        let mut result: HashMap<String, Vec<(u32, Vec<f32>)>> = HashMap::new();
+        let table = match self.windows_table.as_ref() {
+            Some(t) if !chunk_ids.is_empty() => t,
+            _ => return Ok(HashMap::new()),
+        };
+
+        let filter = chunk_ids
+            .iter()
+            .map(|id| format!("'{}'", id.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let batches = table
+            .query()
+            .only_if(format!(
+                "chunk_id IN ({}) AND {}",
+                filter,
+                self.collection_filter()
+            ))
+            .execute()
+            .await
+            .map_err(SearchError::Lance)?
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(SearchError::Lance)?;
+
+        for batch in batches {
+            let ids = batch
+                .column_by_name("chunk_id")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| SearchError::VectorStore("Missing chunk_id column".to_string()))?;
+            let indices = batch
+                .column_by_name("window_index")
+                .and_then(|c| c.as_any().downcast_ref::<UInt32Array>())
+                .ok_or_else(|| {
+                    SearchError::VectorStore("Missing window_index column".to_string())
+                })?;
+            let vectors = batch
+                .column_by_name("vector")
+                .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                .ok_or_else(|| SearchError::VectorStore("Missing vector column".to_string()))?;
+
+            for i in 0..batch.num_rows() {
+                let values = vectors.value(i);
+                let values = values
+                    .as_any()
+                    .downcast_ref::<arrow_array::Float32Array>()
+                    .ok_or_else(|| SearchError::VectorStore("Invalid vector column".to_string()))?;
+                result
+                    .entry(ids.value(i).to_string())
+                    .or_default()
+                    .push((indices.value(i), values.values().to_vec()));
+            }
+        }
+
+        Ok(result
+            .into_iter()
+            .map(|(id, mut windows)| {
+                windows.sort_by_key(|(idx, _)| *idx);
+                (id, windows.into_iter().map(|(_, v)| v).collect())
+            })
+            .collect())
+    }
+
+    /// Load stored chunk vectors by id, for reusing embeddings of chunks
+    /// whose content has not changed
+    pub async fn get_vectors(&self, ids: &[String]) -> SearchResult<HashMap<String, Vec<f32>>> {
+        let mut result = HashMap::new();
+        let table = match self.table.as_ref() {
+            Some(t) if !ids.is_empty() => t,
+            _ => return Ok(result),
+        };
+
+        let filter = ids
+            .iter()
+            .map(|id| format!("'{}'", id.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let batches = table
+            .query()
+            .only_if(format!(
+                "id IN ({}) AND {}",
+                filter,
+                self.collection_filter()
+            ))
+            .select(Select::columns(&["id", "vector"]))
+            .execute()
+            .await
+            .map_err(SearchError::Lance)?
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(SearchError::Lance)?;
+
+        for batch in batches {
+            let ids = batch
+                .column_by_name("id")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| SearchError::VectorStore("Missing id column".to_string()))?;
+            let vectors = batch
+                .column_by_name("vector")
+                .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                .ok_or_else(|| SearchError::VectorStore("Missing vector column".to_string()))?;
+
+            for i in 0..batch.num_rows() {
+                let values = vectors.value(i);
+                let values = values
+                    .as_any()
+                    .downcast_ref::<arrow_array::Float32Array>()
+                    .ok_or_else(|| SearchError::VectorStore("Invalid vector column".to_string()))?;
+                result.insert(ids.value(i).to_string(), values.values().to_vec());
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Convert chunks to Arrow RecordBatch
     fn chunks_to_batch(&self, chunks: &[Chunk], schema: Arc<Schema>) -> SearchResult<RecordBatch> {
         let ids: Vec<&str> = chunks.iter().map(|c| c.id.as_str()).collect();
         let file_paths: Vec<&str> = chunks.iter().map(|c| c.file_path.as_str()).collect();
-        let contents: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+        let compressed = self.codec.compress_all(
+            &chunks
+                .iter()
+                .map(|c| c.content.as_str())
+                .collect::<Vec<_>>(),
+        )?;
         let heading_paths: Vec<&str> = chunks.iter().map(|c| c.heading_path.as_str()).collect();
         let section_titles: Vec<&str> = chunks
             .iter()
@@ -203,13 +504,14 @@ impl VectorStore {
                 .map(|c| Some(c.vector.iter().copied().map(Some).collect::<Vec<_>>())),
             self.dimensions as i32,
         );
+        let collections = vec![self.collection.as_str(); chunks.len()];
 
         let batch = RecordBatch::try_new(
             schema,
             vec![
                 Arc::new(StringArray::from(ids)),
                 Arc::new(StringArray::from(file_paths)),
-                Arc::new(StringArray::from(contents)),
+                Arc::new(StringArray::from(vec![""; chunks.len()])),
                 Arc::new(StringArray::from(heading_paths)),
                 Arc::new(StringArray::from(section_titles)),
                 Arc::new(StringArray::from(doc_types)),
@@ -219,6 +521,10 @@ impl VectorStore {
                 Arc::new(StringArray::from(idea_boxes)),
                 Arc::new(UInt32Array::from(chunk_indices)),
                 Arc::new(vectors_array),
+                Arc::new(StringArray::from(collections)),
+                Arc::new(BinaryArray::from(
+                    compressed.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                )),
             ],
         )
         .map_err(|e| SearchError::VectorStore(e.to_string()))?;
@@ -226,14 +532,58 @@ impl VectorStore {
         Ok(batch)
     }
 
+    /// Chunk text of every row of `batch`, `None` without a content column.
+    /// Compressed rows are decompressed here, once they are results.
+    fn read_contents(&self, batch: &RecordBatch) -> SearchResult<Option<Vec<String>>> {
+        let Some(plain) = batch
+            .column_by_name("content")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        else {
+            return Ok(None);
+        };
+        let compressed = batch
+            .column_by_name("content_zstd")
+            .and_then(|c| c.as_any().downcast_ref::<BinaryArray>());
+        let mut decoder = self.codec.decoder();
+        (0..batch.num_rows())
+            .map(|i| match compressed {
+                Some(frames) if !frames.is_null(i) => decoder.decode(frames.value(i)),
+                _ => Ok(plain.value(i).to_string()),
+            })
+            .collect::<SearchResult<Vec<_>>>()
+            .map(Some)
+    }
+
     /// Search for similar vectors
     pub async fn search(&self, query_vector: &[f32], limit: usize) -> SearchResult<Vec<SearchHit>> {
+        self.search_in(query_vector, limit, None).await
+    }
+
+    /// Search for similar vectors among the chunks under `folder` (a path
+    /// prefix). The filter is applied before the nearest-neighbour search, so
+    /// a narrow folder still yields `limit` results.
+    pub async fn search_in(
+        &self,
+        query_vector: &[f32],
+        limit: usize,
+        folder: Option<&str>,
+    ) -> SearchResult<Vec<SearchHit>> {
         let table = self.table.as_ref().ok_or(SearchError::IndexNotBuilt)?;
 
-        let results = table
+        let mut filter = self.collection_filter();
+        if let Some(folder) = folder {
+            let folder = folder.trim_end_matches('/').replace('\'', "''");
+            filter.push_str(&format!(
+                " AND (file_path = '{folder}' OR starts_with(file_path, '{folder}/'))"
+            ));
+        }
+        let query = table
             .vector_search(query_vector.to_vec())
             .map_err(SearchError::Lance)?
-            .limit(limit)
+            .only_if(filter)
+            .limit(limit);
+
+        let results = query
             .execute()
             .await
             .map_err(SearchError::Lance)?
@@ -244,14 +594,17 @@ impl VectorStore {
         let mut hits = Vec::new();
 
         for batch in results {
+            let ids = batch
+                .column_by_name("id")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+
             let file_paths = batch
                 .column_by_name("file_path")
                 .and_then(|c| c.as_any().downcast_ref::<StringArray>())
                 .ok_or_else(|| SearchError::VectorStore("Missing file_path column".to_string()))?;
 
-            let contents = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            let mut contents = self
+                .read_contents(&batch)?
                 .ok_or_else(|| SearchError::VectorStore("Missing content column".to_string()))?;
 
             let heading_paths = batch
@@ -369,21 +722,9 @@ impl VectorStore {
                     section_title
                         .clone()
                         .or_else(|| heading_path.clone())
-                        .unwrap_or_else(|| {
-                            file_path
-                                .split('/')
-                                .next_back()
-                                .unwrap_or(&file_path)
-                                .trim_end_matches(".md")
-                                .to_string()
-                        })
+                        .unwrap_or_else(|| crate::relpath::display_name(&file_path).to_string())
                 } else {
-                    file_path
-                        .split('/')
-                        .next_back()
-                        .unwrap_or(&file_path)
-                        .trim_end_matches(".md")
-                        .to_string()
+                    crate::relpath::display_name(&file_path).to_string()
                 };
 
                 // Convert distance to similarity score
@@ -394,13 +735,15 @@ impl VectorStore {
                     .unwrap_or(0.5);
 
                 hits.push(SearchHit {
+                    chunk_id: ids.map(|arr| arr.value(i).to_string()),
                     file_path,
                     display_name,
-                    content: contents.value(i).to_string(),
+                    content: std::mem::take(&mut contents[i]),
                     heading_path,
                     section_title,
                     line_start,
                     line_end,
+                    block_id: None,
                     score,
                     matched_by: MatchType::Vector,
                     hit_count: None,
@@ -412,6 +755,14 @@ impl VectorStore {
                     entry_date,
                     entry_created_at,
                     idea_box,
+                    snippet: None,
+                    children: Vec::new(),
+                    also_in: Vec::new(),
+                    folder_summary: None,
+                    doc_meta: None,
+                    citation: None,
+                    provenance: None,
+                    trust: None,
                 });
             }
         }
@@ -427,22 +778,48 @@ impl VectorStore {
         };
 
         // Use delete with filter
-        table
-            .delete(&format!("file_path = '{}'", file_path.replace('\'', "''")))
-            .await
-            .map_err(SearchError::Lance)?;
+        let filter = format!(
+            "file_path = '{}' AND {}",
+            file_path.replace('\'', "''"),
+            self.collection_filter()
+        );
+        table.delete(&filter).await.map_err(SearchError::Lance)?;
+
+        if let Some(windows) = self.windows_table.as_ref() {
+            windows.delete(&filter).await.map_err(SearchError::Lance)?;
+        }
 
         // LanceDB delete doesn't return count, so we return 0
         Ok(0)
     }
 
-    /// Reset the index (delete all data)
+    /// Reset the index (delete all data of this collection). The tables are
+    /// dropped once no other collection uses them, so a rebuild can change
+    /// the embedding dimensions.
     pub async fn reset(&mut self) -> SearchResult<()> {
         let db = self
             .db
             .as_ref()
             .ok_or_else(|| SearchError::VectorStore("Database not initialized".to_string()))?;
 
+        let others = self
+            .collections()
+            .await?
+            .into_iter()
+            .any(|(name, _)| name != self.collection);
+        if others {
+            for table in [self.table.as_ref(), self.windows_table.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                table
+                    .delete(&self.collection_filter())
+                    .await
+                    .map_err(SearchError::Lance)?;
+            }
+            return Ok(());
+        }
+
         // Drop and recreate table
         if self.table.is_some() {
             db.drop_table(TABLE_NAME)
@@ -450,6 +827,17 @@ impl VectorStore {
                 .map_err(SearchError::Lance)?;
             self.table = None;
         }
+        if self.windows_table.is_some() {
+            db.drop_table(WINDOWS_TABLE_NAME)
+                .await
+                .map_err(SearchError::Lance)?;
+            self.windows_table = None;
+        }
+        let mut manifest = SchemaManifest::load(&self.db_path);
+        manifest.remove(TABLE_NAME);
+        manifest.remove(WINDOWS_TABLE_NAME);
+        manifest.save(&self.db_path)?;
+        self.codec.clear()?;
 
         Ok(())
     }
@@ -459,17 +847,59 @@ impl VectorStore {
         self.dimensions
     }
 
-    /// Get total chunk count
+    /// Get chunk count of this collection
     pub async fn count(&self) -> SearchResult<usize> {
         let table = match self.table.as_ref() {
             Some(t) => t,
             None => return Ok(0),
         };
 
-        let count = table.count_rows(None).await.map_err(SearchError::Lance)?;
+        let count = table
+            .count_rows(Some(self.collection_filter()))
+            .await
+            .map_err(SearchError::Lance)?;
         Ok(count)
     }
 
+    /// Chunk count of every collection in the database, sorted by name
+    pub async fn collections(&self) -> SearchResult<Vec<(String, usize)>> {
+        let table = match self.table.as_ref() {
+            Some(t) => t,
+            None => return Ok(vec![]),
+        };
+
+        let batches = table
+            .query()
+            .select(Select::columns(&["collection"]))
+            .execute()
+            .await
+            .map_err(SearchError::Lance)?
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(SearchError::Lance)?;
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for batch in batches {
+            let Some(names) = batch
+                .column_by_name("collection")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            else {
+                continue;
+            };
+            for i in 0..batch.num_rows() {
+                let name = if names.is_null(i) {
+                    DEFAULT_COLLECTION
+                } else {
+                    names.value(i)
+                };
+                *counts.entry(name.to_string()).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort();
+        Ok(counts)
+    }
+
     /// Get all chunks (for keyword search)
     pub async fn get_all_chunks(&self) -> SearchResult<Vec<SearchHit>> {
         let table = match self.table.as_ref() {
@@ -479,6 +909,7 @@ impl VectorStore {
 
         let results = table
             .query()
+            .only_if(self.collection_filter())
             .execute()
             .await
             .map_err(SearchError::Lance)?
@@ -497,14 +928,15 @@ impl VectorStore {
                 None => continue,
             };
 
-            let contents = match batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-            {
-                Some(arr) => arr,
+            let mut contents = match self.read_contents(&batch)? {
+                Some(contents) => contents,
                 None => continue,
             };
 
+            let ids = batch
+                .column_by_name("id")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+
             let heading_paths = batch
                 .column_by_name("heading_path")
                 .and_then(|c| c.as_any().downcast_ref::<StringArray>());
@@ -613,31 +1045,21 @@ impl VectorStore {
                     section_title
                         .clone()
                         .or_else(|| heading_path.clone())
-                        .unwrap_or_else(|| {
-                            file_path
-                                .split('/')
-                                .next_back()
-                                .unwrap_or(&file_path)
-                                .trim_end_matches(".md")
-                                .to_string()
-                        })
+                        .unwrap_or_else(|| crate::relpath::display_name(&file_path).to_string())
                 } else {
-                    file_path
-                        .split('/')
-                        .next_back()
-                        .unwrap_or(&file_path)
-                        .trim_end_matches(".md")
-                        .to_string()
+                    crate::relpath::display_name(&file_path).to_string()
                 };
 
                 hits.push(SearchHit {
+                    chunk_id: ids.map(|arr| arr.value(i).to_string()),
                     file_path,
                     display_name,
-                    content: contents.value(i).to_string(),
+                    content: std::mem::take(&mut contents[i]),
                     heading_path,
                     section_title,
                     line_start,
                     line_end,
+                    block_id: None,
                     score: 0.0,
                     matched_by: MatchType::Keyword,
                     hit_count: None,
@@ -649,6 +1071,14 @@ impl VectorStore {
                     entry_date,
                     entry_created_at,
                     idea_box,
+                    snippet: None,
+                    children: Vec::new(),
+                    also_in: Vec::new(),
+                    folder_summary: None,
+                    doc_meta: None,
+                    citation: None,
+                    provenance: None,
+                    trust: None,
                 });
             }
         }
@@ -656,3 +1086,20 @@ impl VectorStore {
         Ok(hits)
     }
 }
+
+/// Reorder `batch` to the column order of `table`. Columns added by a
+/// migration sit at the end of an upgraded table, while new batches follow
+/// the order of the current schema.
+async fn aligned(table: &Table, batch: RecordBatch) -> SearchResult<RecordBatch> {
+    let target = table.schema().await.map_err(SearchError::Lance)?;
+    let source = batch.schema();
+    let indices = target
+        .fields()
+        .iter()
+        .map(|f| source.index_of(f.name()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| SearchError::VectorStore(format!("Table schema mismatch: {}", e)))?;
+    batch
+        .project(&indices)
+        .map_err(|e| SearchError::VectorStore(e.to_string()))
+}
@@ -0,0 +1,546 @@
+//! GitHub source
+//!
+//! Mirrors the issues (REST API) and discussions (GraphQL API) of the
+//! repositories in `[[sources.github]]`, one doc per thread at
+//! `<folder>/<owner>/<name>/issues/<number>.md` or `.../discussions/...`.
+//! A doc holds the opening post and every comment; labels become frontmatter
+//! `tags`, so `tag:bug` finds labelled issues.
+//!
+//! Issues are fetched oldest change first from the cursor on, at most
+//! [`MAX_THREADS_PER_SYNC`] per sync, so a large backlog is mirrored over a
+//! few syncs. The GraphQL API cannot filter by update time: discussions are
+//! read newest change first until the cursor is reached, which means a first
+//! sync only mirrors the most recently active ones.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{day, http_client, send_json, Comment, Fetched, SourceDoc};
+use crate::frontmatter;
+use crate::git_history::{parse_github_remote, GithubRepo};
+use crate::memory::clean_tags;
+use crate::search::GithubSourceConfig;
+use crate::{CoreError, CoreResult};
+
+const API: &str = "https://api.github.com";
+
+/// Threads fetched by one sync of one source
+pub const MAX_THREADS_PER_SYNC: usize = 200;
+
+/// Minimal GitHub API client
+pub(crate) struct GithubClient {
+    client: reqwest::Client,
+    token: Option<String>,
+}
+
+impl GithubClient {
+    pub(crate) fn new(token: Option<String>) -> CoreResult<Self> {
+        Ok(Self {
+            client: http_client("GitHub")?,
+            token,
+        })
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> CoreResult<T> {
+        let mut request = request.header("Accept", "application/vnd.github+json");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        send_json(request, "GitHub").await
+    }
+
+    /// GET a REST endpoint, e.g. `/repos/o/n/issues`
+    pub(crate) async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> CoreResult<T> {
+        self.send(self.client.get(format!("{API}{path}")).query(query))
+            .await
+    }
+
+    /// Run a GraphQL query and return its `data`
+    async fn graphql<T: DeserializeOwned>(&self, query: &str, variables: Value) -> CoreResult<T> {
+        if self.token.is_none() {
+            return Err(CoreError::Message(
+                "the GitHub GraphQL API needs a token (set `token` or GITHUB_TOKEN)".into(),
+            ));
+        }
+        #[derive(Deserialize)]
+        struct Response<T> {
+            data: Option<T>,
+            #[serde(default)]
+            errors: Vec<GraphqlError>,
+        }
+        #[derive(Deserialize)]
+        struct GraphqlError {
+            message: String,
+        }
+        let request = self
+            .client
+            .post(format!("{API}/graphql"))
+            .json(&json!({ "query": query, "variables": variables }));
+        let response: Response<T> = self.send(request).await?;
+        match response.data {
+            Some(data) if response.errors.is_empty() => Ok(data),
+            _ => Err(CoreError::Message(format!(
+                "GitHub GraphQL error: {}",
+                response
+                    .errors
+                    .iter()
+                    .map(|e| e.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ))),
+        }
+    }
+}
+
+/// An issue or discussion with its comments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thread {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub author: String,
+    /// `open` or `closed`
+    pub state: String,
+    pub labels: Vec<String>,
+    /// Discussion category
+    pub category: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub url: String,
+    pub comments: Vec<Comment>,
+}
+
+/// What is mirrored from a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadKind {
+    Issues,
+    Discussions,
+}
+
+impl ThreadKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThreadKind::Issues => "issues",
+            ThreadKind::Discussions => "discussions",
+        }
+    }
+}
+
+/// One repository and kind of thread from `[[sources.github]]`
+pub struct GithubSource {
+    pub repo: GithubRepo,
+    pub kind: ThreadKind,
+    folder: String,
+    token: Option<String>,
+}
+
+/// Sources described by the `[[sources.github]]` entries
+pub fn sources(configs: &[GithubSourceConfig]) -> CoreResult<Vec<GithubSource>> {
+    let mut sources = Vec::new();
+    for config in configs {
+        let folder = config.folder.trim_matches('/').to_string();
+        let kinds = [
+            (config.issues, ThreadKind::Issues),
+            (config.discussions, ThreadKind::Discussions),
+        ];
+        for name in &config.repos {
+            let repo = parse_github_remote(&format!("https://github.com/{}", name.trim()))
+                .ok_or_else(|| {
+                    CoreError::Message(format!(
+                        "invalid GitHub repository {name:?} in [[sources.github]] (expected owner/name)"
+                    ))
+                })?;
+            for (_, kind) in kinds.iter().filter(|(enabled, _)| *enabled) {
+                sources.push(GithubSource {
+                    repo: repo.clone(),
+                    kind: *kind,
+                    folder: folder.clone(),
+                    token: config.get_token(),
+                });
+            }
+        }
+    }
+    Ok(sources)
+}
+
+impl GithubSource {
+    /// `github:<owner>/<name>/<kind>`
+    pub fn id(&self) -> String {
+        format!(
+            "github:{}/{}/{}",
+            self.repo.owner,
+            self.repo.name,
+            self.kind.as_str()
+        )
+    }
+
+    /// Threads changed since `cursor` (an `updated_at` timestamp) as docs
+    pub async fn fetch(&self, cursor: Option<&str>) -> CoreResult<Fetched> {
+        let client = GithubClient::new(self.token.clone())?;
+        let threads = match self.kind {
+            ThreadKind::Issues => fetch_issues(&client, &self.repo, cursor).await?,
+            ThreadKind::Discussions => fetch_discussions(&client, &self.repo, cursor).await?,
+        };
+        let cursor = threads.iter().map(|t| t.updated_at.clone()).max();
+        let docs = threads
+            .iter()
+            .map(|thread| {
+                Ok(SourceDoc {
+                    rel_path: format!(
+                        "{}/{}/{}/{}/{}.md",
+                        self.folder,
+                        self.repo.owner,
+                        self.repo.name,
+                        self.kind.as_str(),
+                        thread.number
+                    ),
+                    content: render_thread(&self.repo, self.kind, thread)?,
+                })
+            })
+            .collect::<CoreResult<Vec<_>>>()?;
+        Ok(Fetched { docs, cursor })
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiUser {
+    login: String,
+}
+
+fn login(user: Option<ApiUser>) -> String {
+    user.map(|u| u.login).unwrap_or_else(|| "ghost".to_string())
+}
+
+async fn fetch_issues(
+    client: &GithubClient,
+    repo: &GithubRepo,
+    since: Option<&str>,
+) -> CoreResult<Vec<Thread>> {
+    #[derive(Deserialize)]
+    struct ApiLabel {
+        name: String,
+    }
+    #[derive(Deserialize)]
+    struct ApiIssue {
+        number: u64,
+        title: String,
+        body: Option<String>,
+        user: Option<ApiUser>,
+        state: String,
+        #[serde(default)]
+        labels: Vec<ApiLabel>,
+        comments: u64,
+        created_at: String,
+        updated_at: String,
+        html_url: String,
+        /// Present when the "issue" is a pull request
+        pull_request: Option<Value>,
+    }
+    #[derive(Deserialize)]
+    struct ApiComment {
+        user: Option<ApiUser>,
+        body: Option<String>,
+        created_at: String,
+    }
+
+    let path = format!("/repos/{}/{}/issues", repo.owner, repo.name);
+    let mut threads = Vec::new();
+    'pages: for page in 1.. {
+        let mut query = vec![
+            ("state", "all".to_string()),
+            ("sort", "updated".to_string()),
+            ("direction", "asc".to_string()),
+            ("per_page", "100".to_string()),
+            ("page", page.to_string()),
+        ];
+        if let Some(since) = since {
+            query.push(("since", since.to_string()));
+        }
+        let issues: Vec<ApiIssue> = client.get(&path, &query).await?;
+        let last_page = issues.len() < 100;
+        for issue in issues {
+            if issue.pull_request.is_some() {
+                continue;
+            }
+            let mut comments = Vec::new();
+            if issue.comments > 0 {
+                let list: Vec<ApiComment> = client
+                    .get(
+                        &format!("{path}/{}/comments", issue.number),
+                        &[("per_page", "100".to_string())],
+                    )
+                    .await?;
+                comments = list
+                    .into_iter()
+                    .map(|c| Comment {
+                        author: login(c.user),
+                        body: c.body.unwrap_or_default(),
+                        created_at: c.created_at,
+                    })
+                    .collect();
+            }
+            threads.push(Thread {
+                number: issue.number,
+                title: issue.title,
+                body: issue.body.unwrap_or_default(),
+                author: login(issue.user),
+                state: issue.state,
+                labels: issue.labels.into_iter().map(|l| l.name).collect(),
+                category: None,
+                created_at: issue.created_at,
+                updated_at: issue.updated_at,
+                url: issue.html_url,
+                comments,
+            });
+            if threads.len() >= MAX_THREADS_PER_SYNC {
+                break 'pages;
+            }
+        }
+        if last_page {
+            break;
+        }
+    }
+    Ok(threads)
+}
+
+const DISCUSSIONS_QUERY: &str = "query($owner: String!, $name: String!, $after: String) {
+  repository(owner: $owner, name: $name) {
+    discussions(first: 50, after: $after, orderBy: {field: UPDATED_AT, direction: DESC}) {
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        number title body url createdAt updatedAt closed
+        author { login }
+        category { name }
+        labels(first: 20) { nodes { name } }
+        comments(first: 100) { nodes { author { login } body createdAt } }
+      }
+    }
+  }
+}";
+
+async fn fetch_discussions(
+    client: &GithubClient,
+    repo: &GithubRepo,
+    since: Option<&str>,
+) -> CoreResult<Vec<Thread>> {
+    #[derive(Deserialize)]
+    struct Nodes<T> {
+        nodes: Vec<T>,
+    }
+    #[derive(Deserialize)]
+    struct Name {
+        name: String,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PageInfo {
+        has_next_page: bool,
+        end_cursor: Option<String>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ApiComment {
+        author: Option<ApiUser>,
+        body: String,
+        created_at: String,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ApiDiscussion {
+        number: u64,
+        title: String,
+        body: String,
+        url: String,
+        created_at: String,
+        updated_at: String,
+        closed: bool,
+        author: Option<ApiUser>,
+        category: Option<Name>,
+        labels: Option<Nodes<Name>>,
+        comments: Nodes<ApiComment>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Discussions {
+        page_info: PageInfo,
+        nodes: Vec<ApiDiscussion>,
+    }
+    #[derive(Deserialize)]
+    struct Repository {
+        discussions: Discussions,
+    }
+    #[derive(Deserialize)]
+    struct Data {
+        repository: Repository,
+    }
+
+    let mut threads = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let data: Data = client
+            .graphql(
+                DISCUSSIONS_QUERY,
+                json!({ "owner": repo.owner, "name": repo.name, "after": after }),
+            )
+            .await?;
+        let discussions = data.repository.discussions;
+        for d in discussions.nodes {
+            // Newest change first: everything from here on is known
+            if since.is_some_and(|since| d.updated_at.as_str() < since) {
+                return Ok(threads);
+            }
+            threads.push(Thread {
+                number: d.number,
+                title: d.title,
+                body: d.body,
+                author: login(d.author),
+                state: if d.closed { "closed" } else { "open" }.to_string(),
+                labels: d
+                    .labels
+                    .map(|l| l.nodes.into_iter().map(|n| n.name).collect())
+                    .unwrap_or_default(),
+                category: d.category.map(|c| c.name),
+                created_at: d.created_at,
+                updated_at: d.updated_at,
+                url: d.url,
+                comments: d
+                    .comments
+                    .nodes
+                    .into_iter()
+                    .map(|c| Comment {
+                        author: login(c.author),
+                        body: c.body,
+                        created_at: c.created_at,
+                    })
+                    .collect(),
+            });
+            if threads.len() >= MAX_THREADS_PER_SYNC {
+                return Ok(threads);
+            }
+        }
+        match discussions.page_info.end_cursor {
+            Some(cursor) if discussions.page_info.has_next_page => after = Some(cursor),
+            _ => return Ok(threads),
+        }
+    }
+}
+
+/// Doc for one issue or discussion
+pub fn render_thread(repo: &GithubRepo, kind: ThreadKind, thread: &Thread) -> CoreResult<String> {
+    let mut body = format!(
+        "\n# {} (#{})\n\n{} opened on {} · {}\n",
+        thread.title,
+        thread.number,
+        thread.author,
+        day(&thread.created_at),
+        thread.state
+    );
+    if !thread.body.trim().is_empty() {
+        body.push_str(&format!("\n{}\n", thread.body.trim()));
+    }
+    if !thread.comments.is_empty() {
+        body.push_str("\n## Comments\n");
+        for comment in &thread.comments {
+            body.push_str(&format!(
+                "\n### {} · {}\n\n{}\n",
+                comment.author,
+                day(&comment.created_at),
+                comment.body.trim()
+            ));
+        }
+    }
+
+    let number_key = match kind {
+        ThreadKind::Issues => "issue",
+        ThreadKind::Discussions => "discussion",
+    };
+    let mut fields: Vec<(&str, Value)> = vec![
+        ("repo", Value::from(format!("{}/{}", repo.owner, repo.name))),
+        (number_key, Value::from(thread.number)),
+        ("state", Value::from(thread.state.as_str())),
+        ("author", Value::from(thread.author.as_str())),
+        ("created", Value::from(day(&thread.created_at))),
+        ("updated", Value::from(day(&thread.updated_at))),
+        ("url", Value::from(thread.url.as_str())),
+    ];
+    if let Some(category) = &thread.category {
+        fields.push(("category", Value::from(category.as_str())));
+    }
+    let tags = clean_tags(&thread.labels);
+    if !tags.is_empty() {
+        fields.push(("tags", Value::from(tags)));
+    }
+    fields.iter().try_fold(body, |content, (key, value)| {
+        frontmatter::set_field(&content, key, value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn github(repos: &[&str], discussions: bool) -> GithubSourceConfig {
+        GithubSourceConfig {
+            repos: repos.iter().map(|r| r.to_string()).collect(),
+            folder: "/github/".to_string(),
+            issues: true,
+            discussions,
+            token: Some("t".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_sources_from_config() {
+        let configured = sources(&[github(&["wormuz/OpenContext"], true)]).unwrap();
+        let ids: Vec<String> = configured.iter().map(|s| s.id()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "github:wormuz/OpenContext/issues",
+                "github:wormuz/OpenContext/discussions"
+            ]
+        );
+        assert_eq!(configured[0].folder, "github");
+        assert!(sources(&[github(&["not-a-repo"], false)]).is_err());
+    }
+
+    #[test]
+    fn test_render_thread() {
+        let repo = GithubRepo {
+            owner: "me".into(),
+            name: "app".into(),
+        };
+        let thread = Thread {
+            number: 12,
+            title: "Crash on start".into(),
+            body: "Stack trace below.".into(),
+            author: "ada".into(),
+            state: "open".into(),
+            labels: vec!["bug".into(), "good first issue".into()],
+            category: None,
+            created_at: "2026-10-01T08:00:00Z".into(),
+            updated_at: "2026-10-03T09:00:00Z".into(),
+            url: "https://github.com/me/app/issues/12".into(),
+            comments: vec![Comment {
+                author: "grace".into(),
+                body: "Fixed in main.\n".into(),
+                created_at: "2026-10-03T09:00:00Z".into(),
+            }],
+        };
+        let doc = render_thread(&repo, ThreadKind::Issues, &thread).unwrap();
+        let fields = frontmatter::fields(&doc);
+        assert_eq!(fields["repo"], "me/app");
+        assert_eq!(fields["issue"], 12);
+        assert_eq!(fields["updated"], "2026-10-03");
+        assert_eq!(fields["tags"], json!(["bug", "good-first-issue"]));
+        assert_eq!(
+            frontmatter::body(&doc),
+            "\n# Crash on start (#12)\n\nada opened on 2026-10-01 · open\n\nStack trace below.\n\n## Comments\n\n### grace · 2026-10-03\n\nFixed in main.\n"
+        );
+    }
+}
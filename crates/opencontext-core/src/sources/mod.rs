@@ -0,0 +1,341 @@
+//! Sources
+//!
+//! A source mirrors an outside system (GitHub issues and discussions, Jira
+//! and Linear tickets) into workspace docs. Each sync asks the system for what changed since the
+//! source's cursor and rewrites only the docs whose content differs, so
+//! upstream edits arrive without touching everything else. Cursor, time and
+//! error of the last sync of every source are kept in the `source_state`
+//! table.
+//!
+//! [`sync_sources`] runs the configured sources once (`oc sources sync`);
+//! [`SourceScheduler`] repeats that every `[sources] interval_minutes` for as
+//! long as a process like the MCP server runs.
+
+pub mod github;
+pub mod tickets;
+
+use rusqlite::{params, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+
+use crate::search::SourcesConfig;
+use crate::{now_iso, CoreError, CoreResult, OpenContext};
+
+/// A doc as a source renders it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDoc {
+    pub rel_path: String,
+    pub content: String,
+}
+
+/// What one fetch of a source returned
+#[derive(Debug, Clone, Default)]
+pub struct Fetched {
+    pub docs: Vec<SourceDoc>,
+    /// Where the next fetch continues; `None` keeps the previous cursor
+    pub cursor: Option<String>,
+}
+
+/// Stored progress of one source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceState {
+    pub source: String,
+    pub cursor: Option<String>,
+    pub synced_at: String,
+    /// Docs written by the last sync
+    pub docs: usize,
+    /// Why the last sync failed
+    pub error: Option<String>,
+}
+
+/// Outcome of syncing one source
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceReport {
+    pub source: String,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: usize,
+    pub error: Option<String>,
+}
+
+/// One comment of an issue, discussion or ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+/// `YYYY-MM-DD` part of an API timestamp
+fn day(timestamp: &str) -> &str {
+    timestamp.get(0..10).unwrap_or(timestamp)
+}
+
+pub(crate) fn http_client(system: &str) -> CoreResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent("opencontext")
+        .build()
+        .map_err(|e| CoreError::Message(format!("{system} client failed: {e}")))
+}
+
+/// Send `request` and decode its JSON response, turning HTTP errors into
+/// messages naming `system`
+pub(crate) async fn send_json<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    system: &str,
+) -> CoreResult<T> {
+    let http_error =
+        |e: reqwest::Error| CoreError::Message(format!("{system} request failed: {e}"));
+    let response = request.send().await.map_err(http_error)?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(CoreError::Message(format!(
+            "{system} returned {status}: {}",
+            text.trim()
+        )));
+    }
+    response.json().await.map_err(http_error)
+}
+
+/// A configured source of any kind
+pub enum Source {
+    Github(github::GithubSource),
+    Jira(tickets::JiraSource),
+    Linear(tickets::LinearSource),
+}
+
+impl Source {
+    /// Every source in `config`
+    pub fn configured(config: &SourcesConfig) -> CoreResult<Vec<Source>> {
+        let mut sources: Vec<Source> = github::sources(&config.github)?
+            .into_iter()
+            .map(Source::Github)
+            .collect();
+        sources.extend(
+            tickets::jira_sources(&config.jira)?
+                .into_iter()
+                .map(Source::Jira),
+        );
+        sources.extend(
+            tickets::linear_sources(&config.linear)
+                .into_iter()
+                .map(Source::Linear),
+        );
+        Ok(sources)
+    }
+
+    /// Key of the source's row in `source_state`
+    pub fn id(&self) -> String {
+        match self {
+            Source::Github(source) => source.id(),
+            Source::Jira(source) => source.id(),
+            Source::Linear(source) => source.id(),
+        }
+    }
+
+    /// What changed since `cursor`
+    pub async fn fetch(&self, cursor: Option<&str>) -> CoreResult<Fetched> {
+        match self {
+            Source::Github(source) => source.fetch(cursor).await,
+            Source::Jira(source) => source.fetch(cursor).await,
+            Source::Linear(source) => source.fetch(cursor).await,
+        }
+    }
+}
+
+impl OpenContext {
+    /// Progress of every source synced so far
+    pub fn source_states(&self) -> CoreResult<Vec<SourceState>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT source, cursor, synced_at, docs, error FROM source_state ORDER BY source",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(SourceState {
+                        source: row.get(0)?,
+                        cursor: row.get(1)?,
+                        synced_at: row.get(2)?,
+                        docs: row.get::<_, i64>(3)? as usize,
+                        error: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+    }
+
+    pub(crate) fn source_cursor(&self, source: &str) -> CoreResult<Option<String>> {
+        self.with_conn(|conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT cursor FROM source_state WHERE source = ?1",
+                    [source],
+                    |row| row.get::<_, Option<String>>(0),
+                )
+                .optional()?
+                .flatten())
+        })
+    }
+
+    /// Write the docs `fetched` from `source` and move its cursor on
+    pub fn apply_source(&self, source: &str, fetched: &Fetched) -> CoreResult<SourceReport> {
+        let mut report = SourceReport {
+            source: source.to_string(),
+            ..Default::default()
+        };
+        for doc in &fetched.docs {
+            match self.put_generated_doc(&doc.rel_path, &doc.content)? {
+                Some(true) => report.created.push(doc.rel_path.clone()),
+                Some(false) => report.updated.push(doc.rel_path.clone()),
+                None => report.unchanged += 1,
+            }
+        }
+        let written = (report.created.len() + report.updated.len()) as i64;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO source_state (source, cursor, synced_at, docs, error)
+                 VALUES (?1, ?2, ?3, ?4, NULL)
+                 ON CONFLICT(source) DO UPDATE SET
+                    cursor = COALESCE(excluded.cursor, source_state.cursor),
+                    synced_at = excluded.synced_at,
+                    docs = excluded.docs,
+                    error = NULL",
+                params![source, fetched.cursor, now_iso(), written],
+            )?;
+            Ok(())
+        })?;
+        Ok(report)
+    }
+
+    /// Remember that `source` failed, keeping its cursor
+    pub(crate) fn record_source_error(&self, source: &str, error: &str) -> CoreResult<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO source_state (source, synced_at, docs, error)
+                 VALUES (?1, ?2, 0, ?3)
+                 ON CONFLICT(source) DO UPDATE SET
+                    synced_at = excluded.synced_at,
+                    docs = 0,
+                    error = excluded.error",
+                params![source, now_iso(), error],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+/// Sync every configured source whose id starts with `only` (all if `None`),
+/// one after the other. A failing source is reported and recorded without
+/// stopping the others; the error is only returned for a bad configuration.
+pub async fn sync_sources(
+    ctx: &OpenContext,
+    config: &SourcesConfig,
+    only: Option<&str>,
+) -> CoreResult<Vec<SourceReport>> {
+    let mut reports = Vec::new();
+    for source in Source::configured(config)? {
+        let id = source.id();
+        if only.is_some_and(|only| !id.starts_with(only)) {
+            continue;
+        }
+        let cursor = ctx.source_cursor(&id)?;
+        let result = match source.fetch(cursor.as_deref()).await {
+            Ok(fetched) => ctx.apply_source(&id, &fetched),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(report) => reports.push(report),
+            Err(e) => {
+                let error = e.to_string();
+                log::warn!("[Sources] {id} failed: {error}");
+                ctx.record_source_error(&id, &error)?;
+                reports.push(SourceReport {
+                    source: id,
+                    error: Some(error),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// Runs [`sync_sources`] on an interval until stopped
+pub struct SourceScheduler {
+    config: SourcesConfig,
+    shutdown_tx: watch::Sender<bool>,
+    shutdown_rx: watch::Receiver<bool>,
+    /// Notified to sync right away instead of waiting for the interval
+    wake: Arc<Notify>,
+}
+
+impl SourceScheduler {
+    pub fn new(config: SourcesConfig) -> Self {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        Self {
+            config,
+            shutdown_tx,
+            shutdown_rx,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Whether any source is configured
+    pub fn has_sources(&self) -> bool {
+        self.config.github.iter().any(|g| !g.repos.is_empty())
+            || !self.config.jira.is_empty()
+            || !self.config.linear.is_empty()
+    }
+
+    /// Start the next sync now
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Make [`start`](Self::start) return after the sync in progress
+    pub fn stop(&self) {
+        let _ = self.shutdown_tx.send(true);
+    }
+
+    /// Sync all sources now and then every interval until [`stop`](Self::stop)
+    pub async fn start(&self, ctx: &OpenContext) {
+        if !self.has_sources() {
+            return;
+        }
+        let interval = Duration::from_secs(self.config.interval_minutes.max(1) * 60);
+        log::info!(
+            "[Sources] Syncing every {} minute(s)",
+            interval.as_secs() / 60
+        );
+        let mut shutdown_rx = self.shutdown_rx.clone();
+        loop {
+            match sync_sources(ctx, &self.config, None).await {
+                Ok(reports) => {
+                    let written: usize = reports
+                        .iter()
+                        .map(|r| r.created.len() + r.updated.len())
+                        .sum();
+                    log::info!(
+                        "[Sources] Synced {} source(s), {written} doc(s) written",
+                        reports.len()
+                    );
+                }
+                Err(e) => log::error!("[Sources] {e}"),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = self.wake.notified() => {}
+                _ = shutdown_rx.changed() => {}
+            }
+            if *shutdown_rx.borrow() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,591 @@
+//! Ticket sources
+//!
+//! Mirror the Jira and Linear tickets you are involved in, one doc per
+//! ticket at `<folder>/<KEY>.md`, so questions like "what's blocking
+//! release 2.3" are answered from the tickets themselves. Status, assignee,
+//! priority and fix versions go into frontmatter and the doc's first lines;
+//! labels become `tags`.
+//!
+//! Jira is queried with `[[sources.jira]] jql` (by default everything you are
+//! assignee, reporter or watcher of), oldest change first from the day of the
+//! cursor on. Linear is queried for issues you are assignee, creator or
+//! subscriber of that changed since the cursor, newest change first, so a
+//! first sync of a busy workspace only mirrors the most recent
+//! [`MAX_TICKETS_PER_SYNC`].
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{day, http_client, send_json, Comment, Fetched, SourceDoc};
+use crate::frontmatter;
+use crate::memory::clean_tags;
+use crate::search::{JiraSourceConfig, LinearSourceConfig};
+use crate::{CoreError, CoreResult};
+
+const LINEAR_API: &str = "https://api.linear.app/graphql";
+
+/// Tickets fetched by one sync of one source
+pub const MAX_TICKETS_PER_SYNC: usize = 200;
+
+/// A Jira issue or Linear issue with its comments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ticket {
+    /// `PROJ-123` / `ENG-42`
+    pub key: String,
+    pub title: String,
+    pub body: String,
+    pub status: String,
+    pub assignee: Option<String>,
+    pub reporter: Option<String>,
+    pub priority: Option<String>,
+    /// Issue type (Jira only)
+    pub kind: Option<String>,
+    pub project: Option<String>,
+    /// Releases the ticket is planned for
+    pub versions: Vec<String>,
+    pub labels: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub url: String,
+    pub comments: Vec<Comment>,
+}
+
+fn missing_token(account: &str, env: &str) -> CoreError {
+    CoreError::Message(format!(
+        "no {account} token: set `token`, {env} or run `oc sources token {account}`"
+    ))
+}
+
+fn ticket_docs(folder: &str, tracker: &str, tickets: &[Ticket]) -> CoreResult<Fetched> {
+    let docs = tickets
+        .iter()
+        .map(|ticket| {
+            Ok(SourceDoc {
+                rel_path: format!("{folder}/{}.md", ticket.key),
+                content: render_ticket(tracker, ticket)?,
+            })
+        })
+        .collect::<CoreResult<Vec<_>>>()?;
+    let cursor = tickets.iter().map(|t| t.updated_at.clone()).max();
+    Ok(Fetched { docs, cursor })
+}
+
+/// One `[[sources.jira]]` entry
+pub struct JiraSource {
+    url: String,
+    email: String,
+    jql: String,
+    folder: String,
+    token: Option<String>,
+}
+
+/// Sources described by the `[[sources.jira]]` entries
+pub fn jira_sources(configs: &[JiraSourceConfig]) -> CoreResult<Vec<JiraSource>> {
+    configs
+        .iter()
+        .map(|config| {
+            let url = config.url.trim().trim_end_matches('/').to_string();
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err(CoreError::Message(format!(
+                    "invalid Jira url {:?} in [[sources.jira]] (expected https://...)",
+                    config.url
+                )));
+            }
+            Ok(JiraSource {
+                url,
+                email: config.email.trim().to_string(),
+                jql: config.jql.clone(),
+                folder: config.folder.trim_matches('/').to_string(),
+                token: config.get_token(),
+            })
+        })
+        .collect()
+}
+
+impl JiraSource {
+    /// `jira:<host>/<folder>`
+    pub fn id(&self) -> String {
+        let host = self.url.split("://").nth(1).unwrap_or(&self.url);
+        format!("jira:{host}/{}", self.folder)
+    }
+
+    /// Atlassian Cloud replaced `/search` with the token-paged `/search/jql`;
+    /// Server and Data Center only have the former
+    fn is_cloud(&self) -> bool {
+        self.url.contains(".atlassian.net")
+    }
+
+    /// JQL of a sync continuing at `cursor`
+    fn query(&self, cursor: Option<&str>) -> String {
+        match cursor {
+            // Day granularity keeps the query valid in every time zone;
+            // tickets of that day come again and are left unchanged
+            Some(cursor) => format!(
+                "({}) AND updated >= \"{}\" ORDER BY updated ASC",
+                self.jql,
+                day(cursor)
+            ),
+            None => format!("({}) ORDER BY updated ASC", self.jql),
+        }
+    }
+
+    /// Tickets changed since `cursor` (an `updated` timestamp) as docs
+    pub async fn fetch(&self, cursor: Option<&str>) -> CoreResult<Fetched> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApiUser {
+            display_name: String,
+        }
+        #[derive(Deserialize)]
+        struct Named {
+            name: String,
+        }
+        #[derive(Deserialize)]
+        struct ApiComment {
+            author: Option<ApiUser>,
+            body: Option<String>,
+            created: String,
+        }
+        #[derive(Deserialize)]
+        struct ApiComments {
+            #[serde(default)]
+            comments: Vec<ApiComment>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fields {
+            summary: String,
+            description: Option<String>,
+            status: Named,
+            assignee: Option<ApiUser>,
+            reporter: Option<ApiUser>,
+            priority: Option<Named>,
+            issuetype: Option<Named>,
+            project: Option<Named>,
+            #[serde(default)]
+            fix_versions: Vec<Named>,
+            #[serde(default)]
+            labels: Vec<String>,
+            created: String,
+            updated: String,
+            comment: Option<ApiComments>,
+        }
+        #[derive(Deserialize)]
+        struct ApiIssue {
+            key: String,
+            fields: Fields,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Page {
+            issues: Vec<ApiIssue>,
+            next_page_token: Option<String>,
+            total: Option<usize>,
+        }
+
+        let token = self
+            .token
+            .as_deref()
+            .ok_or_else(|| missing_token("jira", "JIRA_API_TOKEN"))?;
+        let client = http_client("Jira")?;
+        let endpoint = if self.is_cloud() {
+            "search/jql"
+        } else {
+            "search"
+        };
+        let jql = self.query(cursor);
+        let fields = "summary,description,status,assignee,reporter,priority,issuetype,project,fixVersions,labels,created,updated,comment";
+
+        let mut tickets = Vec::new();
+        let mut next_page_token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("jql", jql.clone()),
+                ("fields", fields.to_string()),
+                ("maxResults", "50".to_string()),
+            ];
+            match &next_page_token {
+                Some(page) => query.push(("nextPageToken", page.clone())),
+                None if !self.is_cloud() => query.push(("startAt", tickets.len().to_string())),
+                None => {}
+            }
+            let mut request = client
+                .get(format!("{}/rest/api/2/{endpoint}", self.url))
+                .query(&query)
+                .header("Accept", "application/json");
+            // Cloud takes email + API token, Server / Data Center a personal
+            // access token
+            request = if self.email.is_empty() {
+                request.bearer_auth(token)
+            } else {
+                request.basic_auth(&self.email, Some(token))
+            };
+            let page: Page = send_json(request, "Jira").await?;
+            let received = page.issues.len();
+            for issue in page.issues {
+                let f = issue.fields;
+                tickets.push(Ticket {
+                    url: format!("{}/browse/{}", self.url, issue.key),
+                    key: issue.key,
+                    title: f.summary,
+                    body: f.description.unwrap_or_default(),
+                    status: f.status.name,
+                    assignee: f.assignee.map(|u| u.display_name),
+                    reporter: f.reporter.map(|u| u.display_name),
+                    priority: f.priority.map(|p| p.name),
+                    kind: f.issuetype.map(|t| t.name),
+                    project: f.project.map(|p| p.name),
+                    versions: f.fix_versions.into_iter().map(|v| v.name).collect(),
+                    labels: f.labels,
+                    created_at: f.created,
+                    updated_at: f.updated,
+                    comments: f
+                        .comment
+                        .map(|c| c.comments)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|c| Comment {
+                            author: c.author.map(|u| u.display_name).unwrap_or_default(),
+                            body: c.body.unwrap_or_default(),
+                            created_at: c.created,
+                        })
+                        .collect(),
+                });
+            }
+            if tickets.len() >= MAX_TICKETS_PER_SYNC || received == 0 {
+                break;
+            }
+            if self.is_cloud() {
+                match page.next_page_token {
+                    Some(page) => next_page_token = Some(page),
+                    None => break,
+                }
+            } else if page.total.is_none_or(|total| tickets.len() >= total) {
+                break;
+            }
+        }
+        tickets.truncate(MAX_TICKETS_PER_SYNC);
+        ticket_docs(&self.folder, "jira", &tickets)
+    }
+}
+
+/// One `[[sources.linear]]` entry
+pub struct LinearSource {
+    folder: String,
+    token: Option<String>,
+}
+
+/// Sources described by the `[[sources.linear]]` entries
+pub fn linear_sources(configs: &[LinearSourceConfig]) -> Vec<LinearSource> {
+    configs
+        .iter()
+        .map(|config| LinearSource {
+            folder: config.folder.trim_matches('/').to_string(),
+            token: config.get_token(),
+        })
+        .collect()
+}
+
+const LINEAR_ISSUES_QUERY: &str = "query($filter: IssueFilter, $after: String) {
+  issues(first: 50, after: $after, filter: $filter, orderBy: updatedAt) {
+    pageInfo { hasNextPage endCursor }
+    nodes {
+      identifier title description url createdAt updatedAt priorityLabel
+      state { name }
+      assignee { name }
+      creator { name }
+      project { name }
+      projectMilestone { name }
+      labels { nodes { name } }
+      comments(first: 100) { nodes { body createdAt user { name } } }
+    }
+  }
+}";
+
+impl LinearSource {
+    /// `linear:<folder>`
+    pub fn id(&self) -> String {
+        format!("linear:{}", self.folder)
+    }
+
+    /// Tickets changed since `cursor` (an `updatedAt` timestamp) as docs
+    pub async fn fetch(&self, cursor: Option<&str>) -> CoreResult<Fetched> {
+        #[derive(Deserialize)]
+        struct Named {
+            name: String,
+        }
+        #[derive(Deserialize)]
+        struct Nodes<T> {
+            nodes: Vec<T>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApiComment {
+            body: String,
+            created_at: String,
+            user: Option<Named>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApiIssue {
+            identifier: String,
+            title: String,
+            description: Option<String>,
+            url: String,
+            created_at: String,
+            updated_at: String,
+            priority_label: Option<String>,
+            state: Named,
+            assignee: Option<Named>,
+            creator: Option<Named>,
+            project: Option<Named>,
+            project_milestone: Option<Named>,
+            labels: Nodes<Named>,
+            comments: Nodes<ApiComment>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PageInfo {
+            has_next_page: bool,
+            end_cursor: Option<String>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Issues {
+            page_info: PageInfo,
+            nodes: Vec<ApiIssue>,
+        }
+        #[derive(Deserialize)]
+        struct Data {
+            issues: Issues,
+        }
+        #[derive(Deserialize)]
+        struct GraphqlError {
+            message: String,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            data: Option<Data>,
+            #[serde(default)]
+            errors: Vec<GraphqlError>,
+        }
+
+        let token = self
+            .token
+            .as_deref()
+            .ok_or_else(|| missing_token("linear", "LINEAR_API_KEY"))?;
+        let client = http_client("Linear")?;
+        let me = json!({ "isMe": { "eq": true } });
+        let mut filter = json!({
+            "or": [
+                { "assignee": me },
+                { "creator": me },
+                { "subscribers": { "some": me } },
+            ]
+        });
+        if let Some(cursor) = cursor {
+            filter["updatedAt"] = json!({ "gte": cursor });
+        }
+
+        let mut tickets = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let request = client
+                .post(LINEAR_API)
+                .header("Authorization", token)
+                .json(&json!({
+                    "query": LINEAR_ISSUES_QUERY,
+                    "variables": { "filter": filter, "after": after },
+                }));
+            let response: Response = send_json(request, "Linear").await?;
+            let issues = match response.data {
+                Some(data) if response.errors.is_empty() => data.issues,
+                _ => {
+                    return Err(CoreError::Message(format!(
+                        "Linear GraphQL error: {}",
+                        response
+                            .errors
+                            .iter()
+                            .map(|e| e.message.as_str())
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    )))
+                }
+            };
+            for issue in issues.nodes {
+                tickets.push(Ticket {
+                    key: issue.identifier,
+                    title: issue.title,
+                    body: issue.description.unwrap_or_default(),
+                    status: issue.state.name,
+                    assignee: issue.assignee.map(|u| u.name),
+                    reporter: issue.creator.map(|u| u.name),
+                    priority: issue.priority_label.filter(|p| p != "No priority"),
+                    kind: None,
+                    project: issue.project.map(|p| p.name),
+                    versions: issue
+                        .project_milestone
+                        .map(|m| m.name)
+                        .into_iter()
+                        .collect(),
+                    labels: issue.labels.nodes.into_iter().map(|l| l.name).collect(),
+                    created_at: issue.created_at,
+                    updated_at: issue.updated_at,
+                    url: issue.url,
+                    comments: issue
+                        .comments
+                        .nodes
+                        .into_iter()
+                        .map(|c| Comment {
+                            author: c.user.map(|u| u.name).unwrap_or_default(),
+                            body: c.body,
+                            created_at: c.created_at,
+                        })
+                        .collect(),
+                });
+                if tickets.len() >= MAX_TICKETS_PER_SYNC {
+                    return ticket_docs(&self.folder, "linear", &tickets);
+                }
+            }
+            match issues.page_info.end_cursor {
+                Some(cursor) if issues.page_info.has_next_page => after = Some(cursor),
+                _ => return ticket_docs(&self.folder, "linear", &tickets),
+            }
+        }
+    }
+}
+
+/// Doc for one ticket of `tracker` (`jira` or `linear`)
+pub fn render_ticket(tracker: &str, ticket: &Ticket) -> CoreResult<String> {
+    let mut summary = vec![ticket.status.clone()];
+    if let Some(assignee) = &ticket.assignee {
+        summary.push(format!("assigned to {assignee}"));
+    }
+    if let Some(reporter) = &ticket.reporter {
+        summary.push(format!("reported by {reporter}"));
+    }
+    if let Some(priority) = &ticket.priority {
+        summary.push(format!("{priority} priority"));
+    }
+    let mut body = format!(
+        "\n# {}: {}\n\n{}\n",
+        ticket.key,
+        ticket.title,
+        summary.join(" · ")
+    );
+    if !ticket.versions.is_empty() {
+        body.push_str(&format!("\nRelease: {}\n", ticket.versions.join(", ")));
+    }
+    if !ticket.body.trim().is_empty() {
+        body.push_str(&format!("\n{}\n", ticket.body.trim()));
+    }
+    if !ticket.comments.is_empty() {
+        body.push_str("\n## Comments\n");
+        for comment in &ticket.comments {
+            body.push_str(&format!(
+                "\n### {} · {}\n\n{}\n",
+                comment.author,
+                day(&comment.created_at),
+                comment.body.trim()
+            ));
+        }
+    }
+
+    let optional = |value: &Option<String>| value.as_deref().map(Value::from);
+    let mut fields: Vec<(&str, Option<Value>)> = vec![
+        ("tracker", Some(Value::from(tracker))),
+        ("key", Some(Value::from(ticket.key.as_str()))),
+        ("status", Some(Value::from(ticket.status.as_str()))),
+        ("assignee", optional(&ticket.assignee)),
+        ("reporter", optional(&ticket.reporter)),
+        ("priority", optional(&ticket.priority)),
+        ("type", optional(&ticket.kind)),
+        ("project", optional(&ticket.project)),
+        (
+            "versions",
+            (!ticket.versions.is_empty()).then(|| Value::from(ticket.versions.clone())),
+        ),
+        ("created", Some(Value::from(day(&ticket.created_at)))),
+        ("updated", Some(Value::from(day(&ticket.updated_at)))),
+        ("url", Some(Value::from(ticket.url.as_str()))),
+    ];
+    let tags = clean_tags(&ticket.labels);
+    if !tags.is_empty() {
+        fields.push(("tags", Some(Value::from(tags))));
+    }
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .try_fold(body, |content, (key, value)| {
+            frontmatter::set_field(&content, key, &value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jira(url: &str) -> JiraSourceConfig {
+        toml::from_str(&format!(
+            "url = {url:?}\nemail = \"me@acme.com\"\nfolder = \"/tickets/jira/\"\ntoken = \"t\""
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_jira_sources_from_config() {
+        let configured = jira_sources(&[jira("https://acme.atlassian.net/")]).unwrap();
+        let source = &configured[0];
+        assert_eq!(source.id(), "jira:acme.atlassian.net/tickets/jira");
+        assert!(source.is_cloud());
+        assert!(source.jql.contains("watcher = currentUser()"));
+        assert_eq!(
+            source.query(Some("2026-10-03T09:15:00.000+0200")),
+            format!(
+                "({}) AND updated >= \"2026-10-03\" ORDER BY updated ASC",
+                source.jql
+            )
+        );
+        assert!(jira_sources(&[jira("acme.atlassian.net")]).is_err());
+
+        let linear = linear_sources(&[LinearSourceConfig::default()]);
+        assert_eq!(linear[0].id(), "linear:tickets/linear");
+    }
+
+    #[test]
+    fn test_render_ticket() {
+        let ticket = Ticket {
+            key: "REL-7".into(),
+            title: "Signing cert expired".into(),
+            body: "Blocks the 2.3 build.".into(),
+            status: "In Progress".into(),
+            assignee: Some("Ada".into()),
+            reporter: Some("Grace".into()),
+            priority: Some("High".into()),
+            kind: Some("Bug".into()),
+            project: None,
+            versions: vec!["2.3".into()],
+            labels: vec!["Release Blocker".into()],
+            created_at: "2026-10-01T08:00:00.000+0000".into(),
+            updated_at: "2026-10-03T09:00:00.000+0000".into(),
+            url: "https://acme.atlassian.net/browse/REL-7".into(),
+            comments: vec![Comment {
+                author: "Grace".into(),
+                body: "New cert ordered.".into(),
+                created_at: "2026-10-02T10:00:00.000+0000".into(),
+            }],
+        };
+        let doc = render_ticket("jira", &ticket).unwrap();
+        let fields = frontmatter::fields(&doc);
+        assert_eq!(fields["key"], "REL-7");
+        assert_eq!(fields["status"], "In Progress");
+        assert_eq!(fields["assignee"], "Ada");
+        assert_eq!(fields["versions"], json!(["2.3"]));
+        assert_eq!(fields["tags"], json!(["Release-Blocker"]));
+        assert!(!fields.contains_key("project"));
+        assert_eq!(
+            frontmatter::body(&doc),
+            "\n# REL-7: Signing cert expired\n\nIn Progress · assigned to Ada · reported by Grace · High priority\n\nRelease: 2.3\n\nBlocks the 2.3 build.\n\n## Comments\n\n### Grace · 2026-10-02\n\nNew cert ordered.\n"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Stale-note detection
+//!
+//! Each document gets a staleness score between 0 and 1 built from four
+//! signals: how long ago it was last changed, how long since it was last
+//! opened or returned by a search (the `doc_hits` table), how many of its
+//! links point at documents that no longer exist, and whether it names a
+//! successor in a `superseded_by` frontmatter field or a "Superseded by" line.
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::Serialize;
+use std::fs;
+
+use crate::links::{parse_links, Resolution, Resolver};
+use crate::{normalize_folder_path, now_iso, CoreResult, OpenContext};
+
+const SUPERSEDED_KEYS: [&str; 3] = ["superseded_by", "superseded-by", "supersededBy"];
+
+/// The inputs of a staleness score
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StaleSignals {
+    /// Days since the doc was last modified
+    pub age_days: i64,
+    /// Days since the doc was last opened or returned by a search; `None`
+    /// if it never was
+    pub days_since_hit: Option<i64>,
+    pub broken_links: usize,
+    pub superseded: bool,
+}
+
+impl StaleSignals {
+    /// Weighted sum of the signals, each saturating: a year without edits,
+    /// half a year without hits, three broken links
+    pub fn score(&self) -> f64 {
+        let age = (self.age_days.max(0) as f64 / 365.0).min(1.0);
+        let unused = self
+            .days_since_hit
+            .map_or(1.0, |d| (d.max(0) as f64 / 180.0).min(1.0));
+        let broken = (self.broken_links as f64 / 3.0).min(1.0);
+        let superseded = if self.superseded { 1.0 } else { 0.0 };
+        0.35 * age + 0.25 * unused + 0.1 * broken + 0.3 * superseded
+    }
+
+    /// Human-readable explanation of the non-zero signals
+    pub fn reasons(&self) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if self.superseded {
+            reasons.push("superseded".to_string());
+        }
+        if self.age_days >= 30 {
+            reasons.push(format!("not updated in {} days", self.age_days));
+        }
+        match self.days_since_hit {
+            None => reasons.push("never opened or found by search".to_string()),
+            Some(days) if days >= 30 => reasons.push(format!("no hits in {days} days")),
+            Some(_) => {}
+        }
+        match self.broken_links {
+            0 => {}
+            1 => reasons.push("1 broken link".to_string()),
+            n => reasons.push(format!("{n} broken links")),
+        }
+        reasons
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleDoc {
+    pub rel_path: String,
+    pub score: f64,
+    #[serde(flatten)]
+    pub signals: StaleSignals,
+    pub updated_at: String,
+    pub last_hit_at: Option<String>,
+    pub hit_count: i64,
+    /// Link targets that do not resolve to a document
+    pub broken: Vec<String>,
+    pub superseded_by: Option<String>,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StaleQuery {
+    /// Only docs under this folder
+    pub folder: Option<String>,
+    /// Leave out docs scoring below this
+    pub min_score: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+/// Successor named by the doc: a `superseded_by` frontmatter field or the
+/// first link on a line starting with "Superseded by"
+fn superseded_by(content: &str) -> Option<String> {
+    let fields = crate::frontmatter::fields(content);
+    let field = SUPERSEDED_KEYS.iter().find_map(|k| match fields.get(*k)? {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        _ => None,
+    });
+    if field.is_some() {
+        return field;
+    }
+    crate::frontmatter::body(content).lines().find_map(|line| {
+        let text = line.trim_start_matches(['>', '*', '_', ' ', '\t']);
+        let head = text.get(..13)?;
+        if !head.eq_ignore_ascii_case("superseded by") {
+            return None;
+        }
+        parse_links(line)
+            .into_iter()
+            .next()
+            .map(|link| link.target)
+            .or_else(|| {
+                Some(
+                    text[13..]
+                        .trim_matches([':', ' ', '*', '_', '.'])
+                        .to_string(),
+                )
+            })
+            .filter(|s| !s.is_empty())
+    })
+}
+
+fn days_between(from: &str, now: DateTime<Utc>) -> Option<i64> {
+    let ts = DateTime::parse_from_rfc3339(from).ok()?;
+    Some((now - ts.with_timezone(&Utc)).num_days())
+}
+
+impl OpenContext {
+    /// Documents ranked from most to least stale
+    pub fn stale_docs(&self, query: &StaleQuery) -> CoreResult<Vec<StaleDoc>> {
+        let prefix = match query.folder.as_deref() {
+            Some(folder) => {
+                let folder = normalize_folder_path(Some(folder))?;
+                (!folder.is_empty()).then(|| format!("{folder}/"))
+            }
+            None => None,
+        };
+        let rows = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT d.rel_path, d.abs_path, d.stable_id, d.updated_at, h.last_hit_at, COALESCE(h.hit_count, 0)
+                 FROM docs d LEFT JOIN doc_hits h ON h.doc_id = d.id
+                 ORDER BY d.rel_path",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, i64>(5)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        let resolver = Resolver::new(rows.iter().map(|r| (r.0.clone(), r.2.clone())));
+        let now = Utc::now();
+        let mut docs = Vec::new();
+        for (rel_path, abs_path, _, updated_at, last_hit_at, hit_count) in rows {
+            // Idea boxes grow by appending entries; they are never "stale"
+            if rel_path.starts_with(crate::ideas::IDEAS_DIR)
+                || prefix.as_deref().is_some_and(|p| !rel_path.starts_with(p))
+            {
+                continue;
+            }
+            let content = fs::read_to_string(&abs_path).unwrap_or_default();
+            let modified = fs::metadata(&abs_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|m| (now - DateTime::<Utc>::from(m)).num_days());
+            let age_days = match (days_between(&updated_at, now), modified) {
+                (Some(a), Some(b)) => a.min(b),
+                (a, b) => a.or(b).unwrap_or(0),
+            };
+            let broken: Vec<String> = parse_links(&content)
+                .into_iter()
+                .filter(|link| matches!(resolver.resolve(&rel_path, link), Resolution::Broken(_)))
+                .map(|link| link.target)
+                .collect();
+            let superseded_by = superseded_by(&content);
+            let signals = StaleSignals {
+                age_days,
+                days_since_hit: last_hit_at.as_deref().and_then(|ts| days_between(ts, now)),
+                broken_links: broken.len(),
+                superseded: superseded_by.is_some(),
+            };
+            let score = signals.score();
+            if query.min_score.is_some_and(|min| score < min) {
+                continue;
+            }
+            docs.push(StaleDoc {
+                rel_path,
+                score,
+                reasons: signals.reasons(),
+                signals,
+                updated_at,
+                last_hit_at,
+                hit_count,
+                broken,
+                superseded_by,
+            });
+        }
+        docs.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.rel_path.cmp(&b.rel_path))
+        });
+        if let Some(limit) = query.limit {
+            docs.truncate(limit);
+        }
+        Ok(docs)
+    }
+
+    /// Note that these docs were just opened or returned by a search.
+    /// Unknown paths are ignored.
+    pub fn record_doc_hits(&self, rel_paths: &[String]) -> CoreResult<()> {
+        let ts = now_iso();
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "INSERT INTO doc_hits (doc_id, hit_count, last_hit_at)
+                 SELECT id, 1, ?2 FROM docs WHERE rel_path = ?1
+                 ON CONFLICT(doc_id) DO UPDATE SET hit_count = hit_count + 1, last_hit_at = ?2",
+            )?;
+            for rel_path in rel_paths {
+                stmt.execute(params![rel_path, ts])?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_superseded_by() {
+        let content = "---\nsuperseded_by: new/plan.md\n---\n# Plan\n";
+        assert_eq!(superseded_by(content).as_deref(), Some("new/plan.md"));
+        assert_eq!(
+            superseded_by("# Old\n\n> **Superseded by** [v2](v2.md)\n").as_deref(),
+            Some("v2.md")
+        );
+        assert_eq!(
+            superseded_by("# Old\n\nSuperseded by: the Q4 plan.\n").as_deref(),
+            Some("the Q4 plan")
+        );
+        assert_eq!(superseded_by("# Current\n"), None);
+    }
+
+    #[test]
+    fn test_score() {
+        let fresh = StaleSignals {
+            age_days: 2,
+            days_since_hit: Some(1),
+            ..Default::default()
+        };
+        let old = StaleSignals {
+            age_days: 400,
+            days_since_hit: None,
+            broken_links: 1,
+            superseded: false,
+        };
+        assert!(fresh.score() < 0.01);
+        assert!(old.score() > 0.6 && old.score() < 0.7);
+        assert_eq!(
+            old.reasons(),
+            vec![
+                "not updated in 400 days",
+                "never opened or found by search",
+                "1 broken link"
+            ]
+        );
+        let superseded = StaleSignals {
+            superseded: true,
+            ..fresh.clone()
+        };
+        assert!(superseded.score() > fresh.score() + 0.29);
+    }
+}
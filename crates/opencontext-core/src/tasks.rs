@@ -0,0 +1,258 @@
+//! Checkbox tasks
+//!
+//! `- [ ]` / `- [x]` items are collected into the `tasks` table whenever a
+//! doc's content is written or reconciled, so open action items can be
+//! listed across the workspace without opening every file. A due date can be
+//! given inline as `due:2026-10-20`, `@due(2026-10-20)` or `📅 2026-10-20`.
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::fs;
+
+use crate::{
+    like_children, normalize_doc_path, normalize_folder_path, CoreError, CoreResult, DocSaved,
+    OpenContext,
+};
+
+/// A checkbox item found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTask {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+    pub done: bool,
+    /// `YYYY-MM-DD`
+    pub due: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Task {
+    pub rel_path: String,
+    pub line: usize,
+    pub text: String,
+    pub done: bool,
+    pub due: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskQuery {
+    /// Only tasks in docs under this folder
+    pub folder: Option<String>,
+    /// `Some(false)` for open tasks, `Some(true)` for completed ones
+    pub done: Option<bool>,
+    /// Only tasks due on or before this date (`YYYY-MM-DD`)
+    pub due_by: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Checkbox items in `content`, skipping frontmatter and code fences
+pub fn parse_tasks(content: &str) -> Vec<ParsedTask> {
+    let header = &content[..content.len() - crate::frontmatter::body(content).len()];
+    let skip = header.matches('\n').count();
+    let mut fence: Option<&str> = None;
+    let mut tasks = Vec::new();
+    for (idx, line) in content.lines().enumerate().skip(skip) {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        if let Some((done, text, _)) = checkbox(line) {
+            if !text.is_empty() {
+                tasks.push(ParsedTask {
+                    line: idx + 1,
+                    text: text.to_string(),
+                    done,
+                    due: due_date(text),
+                });
+            }
+        }
+    }
+    tasks
+}
+
+/// (done, text, byte offset of the check mark) for a list item with a checkbox
+fn checkbox(line: &str) -> Option<(bool, &str, usize)> {
+    let item = line.trim_start();
+    let rest = match item.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = item.chars().take_while(char::is_ascii_digit).count();
+            if digits == 0 {
+                return None;
+            }
+            item[digits..].strip_prefix(['.', ')'])?
+        }
+    };
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    let mark = rest.trim_start().strip_prefix('[')?;
+    let done = match mark.chars().next()? {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    let tail = mark[1..].strip_prefix(']')?;
+    if !(tail.is_empty() || tail.starts_with(' ')) {
+        return None;
+    }
+    Some((done, tail.trim(), line.len() - mark.len()))
+}
+
+fn due_date(text: &str) -> Option<String> {
+    ["due:", "@due(", "📅"].into_iter().find_map(|token| {
+        let (_, after) = text.split_once(token)?;
+        let date = after.trim_start().get(..10)?;
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+        Some(date.to_string())
+    })
+}
+
+/// Replace the indexed tasks of one doc
+pub(crate) fn sync_doc_tasks(conn: &Connection, doc_id: i64, content: &str) -> CoreResult<()> {
+    conn.execute("DELETE FROM tasks WHERE doc_id = ?1", [doc_id])?;
+    let mut stmt = conn
+        .prepare("INSERT INTO tasks (doc_id, line, text, done, due) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    for task in parse_tasks(content) {
+        stmt.execute(params![
+            doc_id,
+            task.line as i64,
+            task.text,
+            task.done,
+            task.due
+        ])?;
+    }
+    Ok(())
+}
+
+/// Re-read every registered doc from disk; returns the number of tasks found
+pub(crate) fn reindex_all(conn: &Connection) -> CoreResult<usize> {
+    let docs: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, abs_path FROM docs")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        rows
+    };
+    for (doc_id, abs_path) in docs {
+        // Missing or unreadable files simply have no tasks
+        let content = fs::read_to_string(&abs_path).unwrap_or_default();
+        sync_doc_tasks(conn, doc_id, &content)?;
+    }
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM tasks", [], |row| row.get(0))?;
+    Ok(count as usize)
+}
+
+impl OpenContext {
+    /// Tasks matching `query`, soonest due first, then by document and line
+    pub fn list_tasks(&self, query: &TaskQuery) -> CoreResult<Vec<Task>> {
+        let pattern = match query.folder.as_deref() {
+            Some(folder) => {
+                let folder = normalize_folder_path(Some(folder))?;
+                (!folder.is_empty()).then(|| like_children(&folder))
+            }
+            None => None,
+        };
+        let limit = query.limit.map_or(-1, |l| l as i64);
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT d.rel_path, t.line, t.text, t.done, t.due
+                 FROM tasks t JOIN docs d ON d.id = t.doc_id
+                 WHERE (?1 IS NULL OR d.rel_path LIKE ?1 ESCAPE '\\')
+                   AND (?2 IS NULL OR t.done = ?2)
+                   AND (?3 IS NULL OR (t.due IS NOT NULL AND t.due <= ?3))
+                 ORDER BY t.due IS NULL, t.due, d.rel_path, t.line
+                 LIMIT ?4",
+            )?;
+            let rows = stmt
+                .query_map(params![pattern, query.done, query.due_by, limit], |row| {
+                    Ok(Task {
+                        rel_path: row.get(0)?,
+                        line: row.get::<_, i64>(1)? as usize,
+                        text: row.get(2)?,
+                        done: row.get(3)?,
+                        due: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+    }
+
+    /// Tick or untick the checkbox on `line` (1-based) of a doc
+    pub fn set_task_done(&self, doc_path: &str, line: usize, done: bool) -> CoreResult<DocSaved> {
+        let rel_doc_path = normalize_doc_path(Some(doc_path))?;
+        self.edit_doc_content(&rel_doc_path, |content| {
+            let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+            let not_a_task = || CoreError::Message(format!("{rel_doc_path}:{line} is not a task"));
+            let text = lines
+                .get(line.wrapping_sub(1))
+                .copied()
+                .ok_or_else(not_a_task)?;
+            let (_, _, mark) = checkbox(text).ok_or_else(not_a_task)?;
+            let updated = format!(
+                "{}{}{}",
+                &text[..mark],
+                if done { 'x' } else { ' ' },
+                &text[mark + 1..]
+            );
+            lines[line - 1] = &updated;
+            Ok(lines.concat())
+        })
+    }
+
+    /// Rebuild the task table from the files on disk, picking up edits made
+    /// outside OpenContext
+    pub fn reindex_tasks(&self) -> CoreResult<usize> {
+        self.with_conn(reindex_all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tasks() {
+        let content = "---\ntitle: x\n---\n# Plan\n\n- [ ] Ship beta due:2026-11-01\n  - [x] Write docs\n1. [ ] Call @due(2026-10-20)\n- [ ]\n- [link](x)\n```\n- [ ] not a task\n```\n* [X] Done 📅 2026-13-40\n";
+        let tasks = parse_tasks(content);
+        assert_eq!(
+            tasks,
+            vec![
+                ParsedTask {
+                    line: 6,
+                    text: "Ship beta due:2026-11-01".into(),
+                    done: false,
+                    due: Some("2026-11-01".into()),
+                },
+                ParsedTask {
+                    line: 7,
+                    text: "Write docs".into(),
+                    done: true,
+                    due: None,
+                },
+                ParsedTask {
+                    line: 8,
+                    text: "Call @due(2026-10-20)".into(),
+                    done: false,
+                    due: Some("2026-10-20".into()),
+                },
+                ParsedTask {
+                    line: 14,
+                    text: "Done 📅 2026-13-40".into(),
+                    done: true,
+                    due: None,
+                },
+            ]
+        );
+    }
+}
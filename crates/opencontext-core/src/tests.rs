@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod context_tests {
-    use crate::{EnvOverrides, OpenContext};
+    use crate::{CoreError, EnvOverrides, OpenContext};
     use tempfile::TempDir;
 
     fn create_test_context() -> (OpenContext, TempDir) {
@@ -13,6 +13,7 @@ mod context_tests {
             base_root: Some(base_path.clone()),
             contexts_root: Some(base_path.join("contexts")),
             db_path: Some(base_path.join("test.db")),
+            ..Default::default()
         })
         .expect("Failed to initialize context");
 
@@ -41,6 +42,7 @@ mod context_tests {
             base_root: Some(base_path.clone()),
             contexts_root: Some(base_path.join("contexts")),
             db_path: Some(base_path.join("test.db")),
+            ..Default::default()
         };
 
         // Initialize twice - should not fail
@@ -48,6 +50,46 @@ mod context_tests {
         let _ctx2 = OpenContext::initialize(overrides).expect("Second init failed");
     }
 
+    #[test]
+    fn test_read_only_rejects_mutations() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        let overrides = EnvOverrides {
+            base_root: Some(base_path.clone()),
+            contexts_root: Some(base_path.join("contexts")),
+            db_path: Some(base_path.join("test.db")),
+            ..Default::default()
+        };
+        let writable = OpenContext::initialize(overrides.clone()).expect("init failed");
+        writable
+            .create_folder("notes", None)
+            .expect("create folder");
+        writable
+            .create_doc("notes", "a.md", None)
+            .expect("create doc");
+
+        let ctx = OpenContext::initialize(EnvOverrides {
+            read_only: Some(true),
+            ..overrides
+        })
+        .expect("init failed");
+        assert!(ctx.is_read_only());
+        assert!(matches!(
+            ctx.create_folder("other", None),
+            Err(CoreError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            ctx.save_doc_content("notes/a.md", "changed", None),
+            Err(CoreError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            ctx.remove_doc("notes/a.md"),
+            Err(CoreError::ReadOnly(_))
+        ));
+        assert_eq!(ctx.list_docs("notes", false).expect("list docs").len(), 1);
+        assert_eq!(ctx.get_doc_content("notes/a.md").expect("content"), "");
+    }
+
     #[test]
     fn test_env_info_returns_paths() {
         let (ctx, temp_dir) = create_test_context();
@@ -72,6 +114,7 @@ mod folder_tests {
             base_root: Some(base_path.clone()),
             contexts_root: Some(base_path.join("contexts")),
             db_path: Some(base_path.join("test.db")),
+            ..Default::default()
         })
         .expect("Failed to initialize context");
 
@@ -223,6 +266,80 @@ mod folder_tests {
         assert!(docs[0].rel_path.starts_with("renamed-parent/child/"));
     }
 
+    #[test]
+    fn test_folder_ops_treat_like_wildcards_literally() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_folder("a_b", None).unwrap();
+        ctx.create_folder("axb", None).unwrap();
+        ctx.create_doc("a_b", "one.md", None).unwrap();
+        ctx.create_doc("axb", "two.md", None).unwrap();
+
+        ctx.rename_folder("a_b", "renamed").unwrap();
+        let docs = ctx.list_docs("axb", true).unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].rel_path, "axb/two.md");
+        assert_eq!(ctx.list_docs("renamed", true).unwrap().len(), 1);
+
+        ctx.create_folder("c_d", None).unwrap();
+        ctx.create_folder("cxd", None).unwrap();
+        ctx.create_doc("cxd", "three.md", None).unwrap();
+        ctx.remove_folder("c_d", true).unwrap();
+        assert_eq!(ctx.list_docs("cxd", false).unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "keyword")]
+    #[test]
+    fn test_folder_events_list_affected_docs() {
+        use crate::events::{create_event_bus, Event, FolderEvent};
+        use crate::RelPath;
+
+        let (ctx, _temp) = create_test_context();
+        let bus = create_event_bus();
+        let ctx = ctx.with_event_bus(bus.clone());
+        let mut rx = bus.subscribe();
+
+        ctx.create_folder("projects/alpha", None).unwrap();
+        ctx.create_folder("archive", None).unwrap();
+        ctx.create_doc("projects/alpha", "plan.md", None).unwrap();
+        ctx.create_doc("projects", "index.md", None).unwrap();
+        while rx.try_recv().is_ok() {}
+
+        ctx.move_folder("projects", "archive").unwrap();
+        match rx.try_recv().unwrap() {
+            Event::Folder(FolderEvent::Moved {
+                old_path,
+                new_path,
+                affected_docs,
+            }) => {
+                assert_eq!(old_path, "projects");
+                assert_eq!(new_path, "archive/projects");
+                assert_eq!(
+                    affected_docs,
+                    vec![
+                        (
+                            RelPath::new("projects/alpha/plan.md"),
+                            RelPath::new("archive/projects/alpha/plan.md")
+                        ),
+                        (
+                            RelPath::new("projects/index.md"),
+                            RelPath::new("archive/projects/index.md")
+                        ),
+                    ]
+                );
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+
+        ctx.remove_folder("archive", true).unwrap();
+        match rx.try_recv().unwrap() {
+            Event::Folder(FolderEvent::Deleted { removed_docs, .. }) => {
+                assert_eq!(removed_docs.len(), 2)
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
     #[test]
     fn test_rename_folder_target_exists() {
         let (ctx, _temp) = create_test_context();
@@ -292,11 +409,90 @@ mod folder_tests {
         let result = ctx.remove_folder("nonexistent", false);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_folder_manager_journals_affected_docs() {
+        use crate::wal::{self, WalOp};
+
+        let (ctx, temp) = create_test_context();
+        ctx.create_folder("projects/alpha", None).unwrap();
+        ctx.create_folder("archive", None).unwrap();
+        ctx.create_doc("projects/alpha", "plan.md", None).unwrap();
+        ctx.create_doc("projects", "index.md", None).unwrap();
+
+        let folders = ctx.folders();
+        folders.move_to("projects", "archive").unwrap();
+        let contexts = temp.path().join("contexts");
+        assert!(contexts.join("archive/projects/alpha/plan.md").exists());
+        assert!(!contexts.join("projects").exists());
+        let mut docs: Vec<String> = ctx
+            .list_docs("archive", true)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.rel_path)
+            .collect();
+        docs.sort();
+        assert_eq!(
+            docs,
+            [
+                "archive/projects/alpha/plan.md",
+                "archive/projects/index.md"
+            ]
+        );
+
+        folders.delete("archive", true).unwrap();
+        assert!(!contexts.join("archive").exists());
+
+        // The index sync service learns of both through the WAL
+        let conn = rusqlite::Connection::open(temp.path().join("test.db")).unwrap();
+        let journaled: Vec<String> = wal::load_pending(&conn)
+            .unwrap()
+            .into_iter()
+            .filter_map(|(_, op)| match op {
+                WalOp::Rename { old_path, new_path } => Some(format!("{old_path} -> {new_path}")),
+                WalOp::Remove { rel_path } => Some(format!("- {rel_path}")),
+                WalOp::Update { .. } => None,
+            })
+            .collect();
+        assert_eq!(
+            journaled,
+            [
+                "projects/alpha/plan.md -> archive/projects/alpha/plan.md",
+                "projects/index.md -> archive/projects/index.md",
+                "- archive/projects/alpha/plan.md",
+                "- archive/projects/index.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_folder_rename_restores_directory_on_db_failure() {
+        let (ctx, temp) = create_test_context();
+        ctx.create_folder("projects", None).unwrap();
+        ctx.create_doc("projects", "plan.md", None).unwrap();
+        ctx.with_conn(|conn| {
+            conn.execute_batch(
+                "CREATE TEMP TRIGGER fail_rekey BEFORE UPDATE ON folders
+                 BEGIN SELECT RAISE(ABORT, 'rekey failed'); END;",
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(ctx.folders().rename("projects", "renamed").is_err());
+        let contexts = temp.path().join("contexts");
+        assert!(contexts.join("projects/plan.md").exists());
+        assert!(!contexts.join("renamed").exists());
+        assert!(ctx.find_folder("projects").unwrap().is_some());
+    }
 }
 
 #[cfg(test)]
 mod doc_tests {
-    use crate::{EnvOverrides, OpenContext};
+    use crate::dates::{DateQuery, DateRange, DateSource};
+    use crate::staleness::StaleQuery;
+    use crate::tasks::TaskQuery;
+    use crate::{CoreError, EnvOverrides, OpenContext};
     use tempfile::TempDir;
 
     fn create_test_context() -> (OpenContext, TempDir) {
@@ -307,6 +503,7 @@ mod doc_tests {
             base_root: Some(base_path.clone()),
             contexts_root: Some(base_path.join("contexts")),
             db_path: Some(base_path.join("test.db")),
+            ..Default::default()
         })
         .expect("Failed to initialize context");
 
@@ -613,6 +810,47 @@ mod doc_tests {
         assert!(content.is_empty());
     }
 
+    #[test]
+    fn test_get_documents() {
+        let (ctx, _temp) = create_test_context();
+        for (name, content) in [("a.md", "12345"), ("big.md", "1234567890"), ("c.md", "123")] {
+            ctx.create_doc("test-folder", name, None).unwrap();
+            ctx.save_doc_content(&format!("test-folder/{name}"), content, None)
+                .unwrap();
+        }
+
+        let paths: Vec<String> = ["a.md", "missing.md", "big.md", "c.md", "a.md"]
+            .iter()
+            .map(|name| format!("test-folder/{name}"))
+            .collect();
+        let docs = ctx.get_documents(&paths, 9);
+        let summary: Vec<(&str, Option<&str>, bool, bool)> = docs
+            .iter()
+            .map(|d| {
+                (
+                    d.doc_path.as_str(),
+                    d.content.as_deref(),
+                    d.error.is_some(),
+                    d.over_limit,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("test-folder/a.md", Some("12345"), false, false),
+                ("test-folder/missing.md", None, true, false),
+                ("test-folder/big.md", None, true, true),
+                ("test-folder/c.md", Some("123"), false, false),
+            ]
+        );
+        assert_eq!(
+            docs[0].revision.as_deref(),
+            Some(ctx.doc_revision("test-folder/a.md").unwrap().as_str())
+        );
+        assert_eq!(docs[2].doc.as_ref().unwrap().name, "big.md");
+    }
+
     #[test]
     fn test_save_doc_content() {
         let (ctx, _temp) = create_test_context();
@@ -638,6 +876,963 @@ mod doc_tests {
         let doc = ctx.get_doc_meta("test-folder/doc.md").unwrap();
         assert_eq!(doc.description, "New desc");
     }
+
+    #[test]
+    fn test_save_doc_content_detects_conflicts() {
+        let (ctx, temp) = create_test_context();
+
+        ctx.create_doc("test-folder", "doc.md", None).unwrap();
+        let saved = ctx
+            .save_doc_content("test-folder/doc.md", "v1", None)
+            .unwrap();
+        assert_eq!(
+            saved.revision,
+            ctx.doc_revision("test-folder/doc.md").unwrap()
+        );
+
+        // Someone edits the file directly
+        std::fs::write(temp.path().join("contexts/test-folder/doc.md"), "v1 edited").unwrap();
+
+        let err = ctx
+            .save_doc_content_if_unchanged("test-folder/doc.md", "v2", None, &saved.revision)
+            .unwrap_err();
+        assert!(matches!(err, CoreError::Conflict { .. }), "{err}");
+        assert_eq!(
+            ctx.get_doc_content("test-folder/doc.md").unwrap(),
+            "v1 edited"
+        );
+
+        let current = ctx.doc_revision("test-folder/doc.md").unwrap();
+        ctx.save_doc_content_if_unchanged("test-folder/doc.md", "v2", None, &current)
+            .unwrap();
+        assert_eq!(ctx.get_doc_content("test-folder/doc.md").unwrap(), "v2");
+    }
+
+    #[test]
+    fn test_append_to_section_and_apply_patch() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_doc("test-folder", "doc.md", None).unwrap();
+        ctx.save_doc_content("test-folder/doc.md", "# Doc\n\n## Todo\n\n- a\n", None)
+            .unwrap();
+
+        let saved = ctx
+            .append_to_section("test-folder/doc.md", "Doc > Todo", "- b")
+            .unwrap();
+        let content = ctx.get_doc_content("test-folder/doc.md").unwrap();
+        assert_eq!(content, "# Doc\n\n## Todo\n\n- a\n- b\n");
+        assert_eq!(
+            saved.revision,
+            ctx.doc_revision("test-folder/doc.md").unwrap()
+        );
+
+        ctx.apply_patch(
+            "test-folder/doc.md",
+            "@@ -5,2 +5,2 @@\n-- a\n+- a (done)\n - b\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.get_doc_content("test-folder/doc.md").unwrap(),
+            "# Doc\n\n## Todo\n\n- a (done)\n- b\n"
+        );
+
+        assert!(ctx
+            .append_to_section("test-folder/doc.md", "Missing", "x")
+            .is_err());
+    }
+
+    #[test]
+    fn test_tasks_follow_doc_content() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_folder("projects/a", None).unwrap();
+        ctx.create_folder("personal", None).unwrap();
+        ctx.create_doc("projects/a", "plan.md", None).unwrap();
+        ctx.create_doc("personal", "todo.md", None).unwrap();
+        ctx.save_doc_content(
+            "projects/a/plan.md",
+            "# Plan\n\n- [ ] Ship due:2026-11-01\n- [x] Draft\n",
+            None,
+        )
+        .unwrap();
+        ctx.save_doc_content("personal/todo.md", "- [ ] Groceries\n", None)
+            .unwrap();
+
+        let open = ctx
+            .list_tasks(&TaskQuery {
+                folder: Some("projects/".into()),
+                done: Some(false),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(
+            (open[0].line, open[0].text.as_str()),
+            (3, "Ship due:2026-11-01")
+        );
+        assert_eq!(open[0].due.as_deref(), Some("2026-11-01"));
+
+        ctx.set_task_done("projects/a/plan.md", 3, true).unwrap();
+        assert!(ctx
+            .get_doc_content("projects/a/plan.md")
+            .unwrap()
+            .contains("- [x] Ship"));
+        let open = ctx
+            .list_tasks(&TaskQuery {
+                done: Some(false),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].rel_path, "personal/todo.md");
+        assert!(ctx.set_task_done("personal/todo.md", 5, true).is_err());
+
+        ctx.remove_doc("personal/todo.md").unwrap();
+        assert_eq!(ctx.list_tasks(&TaskQuery::default()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_tasks_folder_filter_is_literal() {
+        let (ctx, _temp) = create_test_context();
+        for folder in ["a_b", "axb"] {
+            ctx.create_folder(folder, None).unwrap();
+            ctx.create_doc(folder, "todo.md", None).unwrap();
+            ctx.save_doc_content(&format!("{folder}/todo.md"), "- [ ] Task\n", None)
+                .unwrap();
+        }
+
+        let tasks = ctx
+            .list_tasks(&TaskQuery {
+                folder: Some("a_b".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].rel_path, "a_b/todo.md");
+    }
+
+    #[test]
+    fn test_dates_follow_doc_content() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_folder("projects", None).unwrap();
+        ctx.create_doc("projects", "launch.md", None).unwrap();
+        ctx.save_doc_content(
+            "projects/launch.md",
+            "---\ndue: 2024-09-15\n---\n# Launch\n\nKickoff 2024-06-28, beta 2024-07-10.\n",
+            None,
+        )
+        .unwrap();
+
+        let q3 = ctx
+            .list_dates(&DateQuery {
+                range: Some(DateRange::parse("2024-Q3").unwrap()),
+                ..Default::default()
+            })
+            .unwrap();
+        let found: Vec<_> = q3
+            .iter()
+            .map(|d| (d.date.as_str(), d.source, d.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("2024-07-10", DateSource::Inline, 6),
+                ("2024-09-15", DateSource::Frontmatter, 2),
+            ]
+        );
+
+        ctx.save_doc_content(
+            "projects/launch.md",
+            "# Launch\n\nSlipped to 2025-01-20.\n",
+            None,
+        )
+        .unwrap();
+        let all = ctx.list_dates(&DateQuery::default()).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].date, "2025-01-20");
+
+        ctx.remove_doc("projects/launch.md").unwrap();
+        assert!(ctx.list_dates(&DateQuery::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dates_folder_filter_is_literal() {
+        let (ctx, _temp) = create_test_context();
+        for folder in ["a_b", "axb"] {
+            ctx.create_folder(folder, None).unwrap();
+            ctx.create_doc(folder, "plan.md", None).unwrap();
+            ctx.save_doc_content(&format!("{folder}/plan.md"), "Kickoff 2024-06-28.\n", None)
+                .unwrap();
+        }
+
+        let dates = ctx
+            .list_dates(&DateQuery {
+                folder: Some("a_b".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(dates.len(), 1);
+        assert_eq!(dates[0].rel_path, "a_b/plan.md");
+    }
+
+    #[test]
+    fn test_digest_collects_recent_activity() {
+        let (ctx, _temp) = create_test_context();
+        let since = "1h";
+
+        ctx.create_folder("projects", None).unwrap();
+        ctx.create_folder(".ideas", None).unwrap();
+        ctx.create_doc("projects", "plan.md", Some("Launch plan"))
+            .unwrap();
+        ctx.create_doc("projects", "old.md", None).unwrap();
+        ctx.create_doc(".ideas", "inbox.md", None).unwrap();
+        ctx.save_doc_content(
+            ".ideas/inbox.md",
+            "[//]: # (idea:id=a1 created_at=2020-01-01T00:00:00.000Z)\nAncient\n\n[//]: # (idea:id=b2 created_at=2999-01-01T00:00:00.000Z)\nTry a weekly review\n",
+            None,
+        )
+        .unwrap();
+        ctx.remove_doc("projects/old.md").unwrap();
+
+        let digest = ctx.collect_digest(since, Some("digests")).unwrap();
+        let created: Vec<_> = digest.created.iter().map(|d| d.rel_path.as_str()).collect();
+        assert_eq!(created, vec!["projects/plan.md"]);
+        assert_eq!(digest.ideas.len(), 1);
+        assert_eq!(digest.ideas[0].content, "Try a weekly review");
+        assert_eq!(digest.removed, vec!["projects/old.md"]);
+
+        let saved = ctx
+            .save_digest(&digest, Some("- Planning started"), "digests")
+            .unwrap();
+        let note = ctx.get_doc_content(&saved.rel_path).unwrap();
+        assert!(note.contains("## Summary\n\n- Planning started"), "{note}");
+        assert!(
+            note.contains("- [projects/plan.md](/projects/plan.md) — Launch plan"),
+            "{note}"
+        );
+
+        // The digest note itself is not activity
+        let again = ctx.collect_digest(since, Some("digests")).unwrap();
+        assert_eq!(again.created.len(), 1);
+        ctx.save_digest(&again, None, "digests").unwrap();
+    }
+
+    #[cfg(feature = "keyword")]
+    #[test]
+    fn test_capture_appends_to_dated_inbox() {
+        use crate::capture::{CaptureSource, INBOX_DIR};
+        use crate::events::{create_event_bus, DocEvent, Event};
+
+        let (ctx, _temp) = create_test_context();
+        let bus = create_event_bus();
+        let ctx = ctx.with_event_bus(bus.clone());
+        let mut rx = bus.subscribe();
+
+        assert!(ctx.capture("  ", CaptureSource::Text, None).is_err());
+        assert!(ctx.capture("Clipped", CaptureSource::Url, None).is_err());
+
+        let first = ctx
+            .capture("Call the printer guy", CaptureSource::Text, None)
+            .unwrap();
+        let second = ctx
+            .capture(
+                "Rust 2024 stabilises async closures",
+                CaptureSource::Url,
+                Some("https://blog.rust-lang.org/"),
+            )
+            .unwrap();
+        assert_eq!(first.rel_path, second.rel_path);
+        assert!(first.rel_path.starts_with(&format!("{INBOX_DIR}/")));
+
+        let note = ctx.get_doc_content(&first.rel_path).unwrap();
+        assert!(note.starts_with("# Inbox "), "{note}");
+        assert!(note.contains(" · text\n\nCall the printer guy\n"), "{note}");
+        assert!(
+            note.ends_with(" · url\n\nRust 2024 stabilises async closures\n\nSource: <https://blog.rust-lang.org/>\n"),
+            "{note}"
+        );
+
+        let captured: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                Event::Doc(DocEvent::Captured { rel_path, source }) => Some((rel_path, source)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(captured.len(), 2);
+        assert_eq!(captured[1].0, first.rel_path.as_str());
+        assert_eq!(captured[1].1, "url");
+    }
+
+    #[cfg(feature = "keyword")]
+    #[test]
+    fn test_import_sessions_writes_day_notes_once() {
+        use crate::search::SessionsConfig;
+
+        let (ctx, _temp) = create_test_context();
+        let config = SessionsConfig::default();
+        // Two days apart; `ls` is ignored, the token redacted
+        let history = ": 1700000000:0;kubectl config use-context prod\n: 1700000030:0;ls\n: 1700000060:0;# fixed: was on staging\n: 1700000090:0;export API_TOKEN=abc123\n: 1700200000:0;cargo test\n";
+
+        let import = ctx
+            .import_sessions(history, "/home/me/.zsh_history", None, &config)
+            .unwrap();
+        assert_eq!(import.created.len(), 2);
+        assert_eq!(import.commands, 3);
+        assert_eq!(import.redactions, 1);
+        let note = ctx.get_doc_content(&import.created[0]).unwrap();
+        assert!(note.starts_with("# Shell sessions "), "{note}");
+        assert!(
+            note.contains("kubectl config use-context prod\n```\n\nfixed: was on staging\n\n```sh\nexport API_TOKEN=[REDACTED]\n```"),
+            "{note}"
+        );
+        assert!(!note.contains("\nls\n"), "{note}");
+
+        let again = ctx
+            .import_sessions(history, "/home/me/.zsh_history", None, &config)
+            .unwrap();
+        assert!(again.created.is_empty() && again.updated.is_empty());
+        assert_eq!(again.unchanged, 2);
+    }
+
+    #[cfg(feature = "search")]
+    #[test]
+    fn test_apply_source_keeps_cursor_and_state() {
+        use crate::sources::{Fetched, SourceDoc};
+
+        let (ctx, _temp) = create_test_context();
+        let source = "github:me/app/issues";
+        let doc = |n: u32, state: &str| SourceDoc {
+            rel_path: format!("github/me/app/issues/{n}.md"),
+            content: format!("# Issue {n}\n\n{state}\n"),
+        };
+        let first = Fetched {
+            docs: vec![doc(1, "open"), doc(2, "open")],
+            cursor: Some("2026-10-02T00:00:00Z".into()),
+        };
+        let report = ctx.apply_source(source, &first).unwrap();
+        assert_eq!(report.created.len(), 2);
+        assert_eq!(
+            ctx.get_doc_content("github/me/app/issues/2.md").unwrap(),
+            "# Issue 2\n\nopen\n"
+        );
+
+        // Only #2 changed upstream; an empty fetch keeps the cursor
+        let second = Fetched {
+            docs: vec![doc(1, "open"), doc(2, "closed")],
+            cursor: Some("2026-10-05T00:00:00Z".into()),
+        };
+        let report = ctx.apply_source(source, &second).unwrap();
+        assert_eq!(report.updated, vec!["github/me/app/issues/2.md"]);
+        assert_eq!(report.unchanged, 1);
+        ctx.apply_source(source, &Fetched::default()).unwrap();
+        assert_eq!(
+            ctx.source_cursor(source).unwrap().as_deref(),
+            Some("2026-10-05T00:00:00Z")
+        );
+
+        ctx.record_source_error(source, "GitHub returned 403")
+            .unwrap();
+        let states = ctx.source_states().unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].error.as_deref(), Some("GitHub returned 403"));
+        assert_eq!(states[0].cursor.as_deref(), Some("2026-10-05T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_import_git_writes_dated_notes_once() {
+        use crate::frontmatter;
+        use crate::git_history::{parse_git_log, PullRequest};
+
+        let (ctx, _temp) = create_test_context();
+        let repo = std::path::Path::new("/src/My Project");
+        let commits = parse_git_log(
+            "c2\x1fAda\x1f2026-10-14T16:00:00+02:00\x1fAdd retries\x1f\x1e\n\
+             c1\x1fAda\x1f2026-10-14T09:30:00+02:00\x1fFix watcher race\x1fMissed the first event.\x1e\n\
+             c0\x1fGrace\x1f2026-10-12T11:00:00+02:00\x1fInitial commit\x1f\x1e\n",
+        );
+        let pulls = vec![PullRequest {
+            number: 7,
+            title: "Retry failed syncs".into(),
+            body: "Closes #3".into(),
+            author: "ada".into(),
+            state: "merged".into(),
+            created_at: "2026-10-13T08:00:00Z".into(),
+            merged_at: Some("2026-10-14T17:00:00Z".into()),
+            url: "https://github.com/me/my-project/pull/7".into(),
+        }];
+
+        let import = ctx.import_git(repo, &commits, &pulls).unwrap();
+        assert_eq!(import.folder, ".repos/my-project");
+        assert_eq!(
+            import.created,
+            vec![
+                ".repos/my-project/commits/2026-10-12.md",
+                ".repos/my-project/commits/2026-10-14.md",
+                ".repos/my-project/prs/7.md",
+            ]
+        );
+        let day = ctx.get_doc_content(&import.created[1]).unwrap();
+        let fields = frontmatter::fields(&day);
+        assert_eq!(fields["repo"], "/src/My Project");
+        assert_eq!(fields["date"], "2026-10-14");
+        // Oldest commit of the day first
+        let body = frontmatter::body(&day);
+        assert!(
+            body.find("## Fix watcher race").unwrap() < body.find("## Add retries").unwrap(),
+            "{day}"
+        );
+        assert!(
+            body.contains("`c1` · Ada · 09:30\n\nMissed the first event."),
+            "{day}"
+        );
+        let pr = ctx.get_doc_content(&import.created[2]).unwrap();
+        assert!(
+            pr.contains(
+                "# #7 Retry failed syncs\n\nOpened by ada on 2026-10-13 · merged 2026-10-14"
+            ),
+            "{pr}"
+        );
+
+        let again = ctx.import_git(repo, &commits, &pulls).unwrap();
+        assert!(again.created.is_empty() && again.updated.is_empty());
+        assert_eq!(again.unchanged, 3);
+    }
+
+    #[test]
+    fn test_import_wiki_nests_pages_and_links_them() {
+        use crate::frontmatter;
+        use crate::wiki_import::WikiPage;
+
+        let (ctx, _temp) = create_test_context();
+        let pages = vec![
+            WikiPage {
+                title: "Release Process".into(),
+                body: "Follow [signing](Signing_2.html#keys) first.\n".into(),
+                author: Some("Ada".into()),
+                editor: Some("Grace".into()),
+                updated: Some("2024-03-01".into()),
+                id: "Release-Process_1.html".into(),
+                ..Default::default()
+            },
+            WikiPage {
+                parents: vec!["Release Process".into()],
+                title: "Signing".into(),
+                body: "# Signing\n\nUse the HSM.\n".into(),
+                id: "Signing_2.html".into(),
+                ..Default::default()
+            },
+        ];
+
+        let import = ctx.import_wiki("confluence", "/wiki/", &pages).unwrap();
+        assert_eq!(
+            import.created,
+            vec!["wiki/release-process.md", "wiki/release-process/signing.md"]
+        );
+        let release = ctx.get_doc_content("wiki/release-process.md").unwrap();
+        let fields = frontmatter::fields(&release);
+        assert_eq!(fields["source"], "confluence");
+        assert_eq!(fields["editor"], "Grace");
+        assert_eq!(fields["updated"], "2024-03-01");
+        assert_eq!(
+            frontmatter::body(&release),
+            "\n# Release Process\n\nFollow [signing](/wiki/release-process/signing.md#keys) first.\n"
+        );
+        let signing = ctx
+            .get_doc_content("wiki/release-process/signing.md")
+            .unwrap();
+        assert_eq!(frontmatter::body(&signing), "\n# Signing\n\nUse the HSM.\n");
+
+        let again = ctx.import_wiki("confluence", "wiki", &pages).unwrap();
+        assert_eq!(again.unchanged, 2);
+    }
+
+    #[cfg(feature = "search")]
+    #[test]
+    fn test_import_notes_writes_attachments_beside_notebook() {
+        use crate::notes_import::{Attachment, ImportedNote};
+
+        let (ctx, temp) = create_test_context();
+        let note = ImportedNote {
+            notebook: "Work Log".into(),
+            title: "Standup".into(),
+            html: r#"<div>Board:</div><en-media hash="5d41402abc4b2a76b9719d911017c592"/>"#.into(),
+            created: Some("2015-02-03T09:00:00Z".into()),
+            attachments: vec![Attachment {
+                file_name: "board.png".into(),
+                mime: "image/png".into(),
+                data: b"hello".to_vec(),
+            }],
+            ..Default::default()
+        };
+        let notes = vec![note.clone(), note];
+
+        let import = ctx.import_notes("evernote", "evernote", &notes).unwrap();
+        assert_eq!(
+            import.created,
+            vec![
+                "evernote/work-log/standup.md",
+                "evernote/work-log/standup-2.md"
+            ]
+        );
+        assert_eq!(import.attachments, 2);
+        let content = ctx.get_doc_content("evernote/work-log/standup.md").unwrap();
+        assert!(content.contains("created: 2015-02-03"), "{content}");
+        assert!(
+            content.contains("![](_attachments/standup/board.png)"),
+            "{content}"
+        );
+        let file = temp
+            .path()
+            .join("contexts/evernote/work-log/_attachments/standup/board.png");
+        assert_eq!(std::fs::read(file).unwrap(), b"hello");
+
+        let again = ctx.import_notes("evernote", "evernote", &notes).unwrap();
+        assert_eq!((again.unchanged, again.attachments), (2, 0));
+    }
+
+    #[cfg(feature = "keyword")]
+    #[test]
+    fn test_publish_site_renders_only_published_docs() {
+        use crate::publish::PublishOptions;
+
+        let (ctx, temp) = create_test_context();
+        ctx.create_folder("garden", None).unwrap();
+        ctx.create_folder("private", None).unwrap();
+        ctx.create_doc("garden", "Rust.md", None).unwrap();
+        ctx.create_doc("garden", "draft.md", None).unwrap();
+        ctx.create_doc("private", "diary.md", None).unwrap();
+        ctx.create_doc("private", "talk.md", None).unwrap();
+        ctx.save_doc_content(
+            "garden/Rust.md",
+            "---\ntags: [lang]\n---\n# Rust notes\n\nSee [[talk]], [[diary]] and \
+             [the draft](draft.md).\n\n![chart](chart.png)\n",
+            None,
+        )
+        .unwrap();
+        ctx.save_doc_content("garden/draft.md", "---\npublish: false\n---\nWIP\n", None)
+            .unwrap();
+        ctx.save_doc_content("private/diary.md", "Dear diary\n", None)
+            .unwrap();
+        ctx.save_doc_content(
+            "private/talk.md",
+            "---\npublish: true\ntitle: Borrowing talk\n---\nSlides\n",
+            None,
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("contexts/garden/chart.png"), b"png").unwrap();
+
+        let out = temp.path().join("site");
+        let options = PublishOptions {
+            folders: vec!["garden".to_string()],
+            title: "Garden".to_string(),
+        };
+        let report = ctx.publish_site(&out, &options).unwrap();
+        assert_eq!(report.pages, vec!["garden/Rust.md", "private/talk.md"]);
+        assert_eq!(report.assets, vec!["garden/chart.png"]);
+        assert_eq!(report.hidden_links, 2);
+
+        let page = std::fs::read_to_string(out.join("garden/Rust.html")).unwrap();
+        assert!(
+            page.contains("<title>Rust notes · Garden</title>"),
+            "{page}"
+        );
+        assert!(page.contains("<h1 id=\"rust-notes\">"), "{page}");
+        assert!(
+            page.contains("<a href=\"../private/talk.html\">talk</a>"),
+            "{page}"
+        );
+        assert!(page.contains("diary and the draft."), "{page}");
+        assert!(page.contains("<img src=\"../garden/chart.png\""), "{page}");
+        assert!(!page.contains("tags: [lang]"), "{page}");
+        let talk = std::fs::read_to_string(out.join("private/talk.html")).unwrap();
+        assert!(talk.contains("Linked from"), "{talk}");
+        assert!(out.join("garden/chart.png").is_file());
+        assert!(!out.join("garden/draft.html").exists());
+        let index = std::fs::read_to_string(out.join("search-index.json")).unwrap();
+        assert!(index.contains("\"title\":\"Borrowing talk\""), "{index}");
+        assert!(!index.contains("Dear diary"), "{index}");
+
+        // Unpublishing removes the page; files oc did not write stay
+        std::fs::write(out.join("CNAME"), "notes.example.com").unwrap();
+        ctx.save_doc_content("private/talk.md", "Slides\n", None)
+            .unwrap();
+        let again = ctx.publish_site(&out, &options).unwrap();
+        assert_eq!(again.pages, vec!["garden/Rust.md"]);
+        assert_eq!(again.removed, 1);
+        assert!(!out.join("private").exists());
+        assert!(out.join("CNAME").is_file());
+
+        let taken = temp.path().join("taken");
+        std::fs::create_dir(&taken).unwrap();
+        std::fs::write(taken.join("keep.txt"), "mine").unwrap();
+        assert!(ctx.publish_site(&taken, &options).is_err());
+    }
+
+    #[cfg(feature = "keyword")]
+    #[test]
+    fn test_render_resolves_links_and_expands_embeds() {
+        use crate::acl::Access;
+
+        let (ctx, _temp) = create_test_context();
+        ctx.create_folder("notes", None).unwrap();
+        ctx.create_folder("secret", None).unwrap();
+        ctx.create_doc("notes", "plan.md", None).unwrap();
+        ctx.create_doc("notes", "goals.md", None).unwrap();
+        ctx.create_doc("secret", "keys.md", None).unwrap();
+        ctx.save_doc_content(
+            "notes/plan.md",
+            "# Plan\n\nSee [[keys]] and [goals](goals.md#Q3).\n\n![[goals#Q3]]\n\n\
+             ```rust\nlet x = 1;\n```\n\n<div onclick=\"x()\">raw<script>bad()</script></div>\n",
+            None,
+        )
+        .unwrap();
+        ctx.save_doc_content(
+            "notes/goals.md",
+            "# Goals\n## Q2\nold\n## Q3\nShip **v2**\n",
+            None,
+        )
+        .unwrap();
+        ctx.save_doc_content("secret/keys.md", "hunter2\n", None)
+            .unwrap();
+
+        let doc = ctx.render("notes/plan.md", &Access::All).unwrap();
+        assert_eq!(doc.title, "Plan");
+        assert_eq!(doc.links, vec!["secret/keys.md", "notes/goals.md"]);
+        assert_eq!(doc.embeds, vec!["notes/goals.md"]);
+        assert!(doc
+            .html
+            .contains("<a href=\"?doc=secret/keys.md\">keys</a>"));
+        assert!(doc
+            .html
+            .contains("<a href=\"?doc=notes/goals.md#q3\">goals</a>"));
+        assert!(
+            doc.html.contains("Ship <strong>v2</strong>"),
+            "{}",
+            doc.html
+        );
+        assert!(!doc.html.contains("old"), "{}", doc.html);
+        assert!(doc.html.contains("<span class=\"hl-keyword\">let</span>"));
+        assert!(doc.html.contains("<div>raw</div>"), "{}", doc.html);
+        assert!(!doc.html.contains("bad()"));
+
+        let team = Access::Folders(vec!["notes".to_string()]);
+        let doc = ctx.render("notes/plan.md", &team).unwrap();
+        assert_eq!(doc.broken_links, vec!["secret/keys.md"]);
+        assert!(doc.html.contains("See keys and"), "{}", doc.html);
+    }
+
+    #[test]
+    fn test_import_bibtex_keeps_reader_notes() {
+        use crate::bibtex::parse_bibtex;
+
+        let (ctx, _temp) = create_test_context();
+        let bib = "@article{lee2021, title = {Sparse Retrieval}, author = {Lee, Bob}, year = 2021}";
+        let import = ctx.import_bibtex("references", &parse_bibtex(bib)).unwrap();
+        assert_eq!(import.created, vec!["references/lee2021.md"]);
+
+        let note = ctx.get_doc_content("references/lee2021.md").unwrap();
+        assert!(note.ends_with("## Notes\n"), "{note}");
+        let with_notes = format!("{note}\nBM25 still wins on short queries.\n");
+        ctx.save_doc_content("references/lee2021.md", &with_notes, None)
+            .unwrap();
+
+        let bib = bib.replace("2021}", "2022}");
+        let again = ctx
+            .import_bibtex("references", &parse_bibtex(&bib))
+            .unwrap();
+        assert_eq!(again.updated, vec!["references/lee2021.md"]);
+        let note = ctx.get_doc_content("references/lee2021.md").unwrap();
+        assert!(note.contains("Bob Lee (2022)."), "{note}");
+        assert!(
+            note.ends_with("## Notes\n\nBM25 still wins on short queries.\n"),
+            "{note}"
+        );
+    }
+
+    #[test]
+    fn test_memories_expire_and_forget() {
+        let (ctx, _temp) = create_test_context();
+        assert!(ctx.list_memories(None).unwrap().is_empty());
+        assert!(ctx.remember("  ", &[], None).is_err());
+        assert!(ctx.remember("Short lived", &[], Some("soon")).is_err());
+
+        let tabs = ctx
+            .remember("Prefers tabs", &["#prefs".to_string()], None)
+            .unwrap();
+        let week = ctx.remember("On call", &[], Some("1w")).unwrap();
+        assert!(week.expires_at.is_some());
+        let listed: Vec<_> = ctx.list_memories(None).unwrap();
+        assert_eq!(listed, vec![tabs.clone(), week.clone()]);
+        assert_eq!(
+            ctx.list_memories(Some("prefs")).unwrap(),
+            vec![tabs.clone()]
+        );
+
+        // Entries past their expiry are hidden, then dropped from the file
+        let mut content = ctx.get_doc_content(crate::memory::MEMORY_DOC).unwrap();
+        content.push_str(
+            "\n[//]: # (memory:id=old created_at=2020-01-01T00:00:00.000Z expires_at=2020-01-02T00:00:00.000Z)\nStale\n",
+        );
+        ctx.save_doc_content(crate::memory::MEMORY_DOC, &content, None)
+            .unwrap();
+        assert_eq!(ctx.list_memories(None).unwrap().len(), 2);
+        assert_eq!(ctx.purge_expired_memories().unwrap(), 1);
+        assert_eq!(ctx.purge_expired_memories().unwrap(), 0);
+
+        assert!(ctx.forget(&tabs.id).unwrap());
+        assert!(!ctx.forget(&tabs.id).unwrap());
+        assert_eq!(ctx.list_memories(None).unwrap(), vec![week]);
+    }
+
+    #[test]
+    fn test_check_links_suggests_renamed_targets() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_folder("drafts", None).unwrap();
+        ctx.create_folder("notes", None).unwrap();
+        ctx.create_doc("drafts", "plan.md", None).unwrap();
+        ctx.create_doc("notes", "index.md", None).unwrap();
+        ctx.create_doc("notes", "lonely.md", None).unwrap();
+        ctx.save_doc_content(
+            "notes/index.md",
+            "- [Plan](../drafts/plan.md)\n- [[drafts/plan]]\n- [[Nowhere]]\n",
+            None,
+        )
+        .unwrap();
+
+        let report = ctx.check_links(None).unwrap();
+        assert!(report.broken.iter().all(|b| b.target == "Nowhere"));
+        assert_eq!(report.orphans, vec!["notes/lonely.md"]);
+
+        ctx.rename_doc("drafts/plan.md", "launch.md").unwrap();
+        ctx.rename_folder("drafts", "projects").unwrap();
+        // Renames rewrite the links, so bring back the old text as a stale copy would
+        ctx.save_doc_content(
+            "notes/index.md",
+            "- [Plan](../drafts/plan.md)\n- [[drafts/plan]]\n- [[Nowhere]]\n",
+            None,
+        )
+        .unwrap();
+        let report = ctx.check_links(Some("notes")).unwrap();
+        let broken: Vec<_> = report
+            .broken
+            .iter()
+            .map(|b| (b.line, b.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                (1, Some("projects/launch.md")),
+                (2, Some("projects/launch.md")),
+                (3, None),
+            ]
+        );
+        assert_eq!(report.orphans, vec!["notes/index.md", "notes/lonely.md"]);
+    }
+
+    #[test]
+    fn test_rename_rewrites_inbound_links() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_folder("drafts", None).unwrap();
+        ctx.create_folder("notes", None).unwrap();
+        ctx.create_folder("archive", None).unwrap();
+        ctx.create_doc("drafts", "plan.md", None).unwrap();
+        ctx.create_doc("notes", "index.md", None).unwrap();
+        ctx.save_doc_content(
+            "notes/index.md",
+            "- [Plan](../drafts/plan.md)\n- [[drafts/plan|plan]]\n- [[plan]]\n",
+            None,
+        )
+        .unwrap();
+        ctx.save_doc_content(
+            "drafts/plan.md",
+            "Back to [index](../notes/index.md)\n",
+            None,
+        )
+        .unwrap();
+
+        let preview = ctx.preview_link_rewrites("drafts", "projects/q4").unwrap();
+        assert_eq!(preview.len(), 3);
+        assert_eq!(
+            ctx.get_doc_content("notes/index.md").unwrap(),
+            "- [Plan](../drafts/plan.md)\n- [[drafts/plan|plan]]\n- [[plan]]\n"
+        );
+
+        let result = ctx.rename_doc("drafts/plan.md", "launch.md").unwrap();
+        assert_eq!(result.links_rewritten.len(), 3);
+        assert_eq!(
+            ctx.get_doc_content("notes/index.md").unwrap(),
+            "- [Plan](../drafts/launch.md)\n- [[drafts/launch|plan]]\n- [[launch]]\n"
+        );
+
+        ctx.move_folder("drafts", "archive").unwrap();
+        assert_eq!(
+            ctx.get_doc_content("notes/index.md").unwrap(),
+            "- [Plan](../archive/drafts/launch.md)\n- [[archive/drafts/launch|plan]]\n- [[launch]]\n"
+        );
+        assert_eq!(
+            ctx.get_doc_content("archive/drafts/launch.md").unwrap(),
+            "Back to [index](../../notes/index.md)\n"
+        );
+        assert!(ctx.check_links(None).unwrap().broken.is_empty());
+    }
+
+    #[test]
+    fn test_stale_docs_ranking() {
+        let (ctx, _temp) = create_test_context();
+
+        ctx.create_folder("notes", None).unwrap();
+        ctx.create_doc("notes", "current.md", None).unwrap();
+        ctx.create_doc("notes", "old.md", None).unwrap();
+        ctx.save_doc_content(
+            "notes/old.md",
+            "---\nsuperseded_by: current.md\n---\nSee [gone](gone.md) and [current](current.md).\n",
+            None,
+        )
+        .unwrap();
+        ctx.record_doc_hits(&["notes/current.md".to_string(), "missing.md".to_string()])
+            .unwrap();
+
+        let ranked = ctx.stale_docs(&StaleQuery::default()).unwrap();
+        assert_eq!(ranked[0].rel_path, "notes/old.md");
+        assert_eq!(ranked[0].broken, vec!["gone.md"]);
+        assert_eq!(ranked[0].superseded_by.as_deref(), Some("current.md"));
+        assert_eq!(ranked[1].rel_path, "notes/current.md");
+        assert_eq!(ranked[1].hit_count, 1);
+        assert!(ranked[1].score < 0.01);
+
+        let filtered = ctx
+            .stale_docs(&StaleQuery {
+                min_score: Some(0.3),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+    }
+
+    #[test]
+    fn test_random_and_resurfaced_docs() {
+        use crate::resurface::{RandomQuery, ResurfaceOptions};
+
+        let (ctx, _temp) = create_test_context();
+        ctx.create_folder("notes", None).unwrap();
+        ctx.create_folder("digests", None).unwrap();
+        for name in ["a.md", "b.md", "c.md"] {
+            ctx.create_doc("notes", name, None).unwrap();
+        }
+        ctx.create_doc("digests", "digest.md", None).unwrap();
+        ctx.save_doc_content("notes/b.md", "Reading list #books\n", None)
+            .unwrap();
+
+        let tagged = ctx
+            .random_docs(&RandomQuery {
+                tag: Some("books".into()),
+                count: Some(5),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].excerpt, "Reading list #books");
+        let all = ctx
+            .random_docs(&RandomQuery {
+                folder: Some("notes".into()),
+                count: Some(5),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(all.len(), 3);
+
+        let options = ResurfaceOptions {
+            count: Some(2),
+            min_age_days: Some(0),
+            exclude_folder: Some("digests".into()),
+            ..Default::default()
+        };
+        let peek = ctx
+            .resurface(&ResurfaceOptions {
+                dry_run: true,
+                ..options.clone()
+            })
+            .unwrap();
+        let first = ctx.resurface(&options).unwrap();
+        let paths = |docs: &[crate::resurface::PickedDoc]| -> Vec<String> {
+            docs.iter().map(|d| d.rel_path.clone()).collect()
+        };
+        assert_eq!(paths(&peek), paths(&first));
+        assert_eq!(first.len(), 2);
+        // Shown notes wait at least a day; only the third is still due
+        let second = ctx.resurface(&options).unwrap();
+        assert_eq!(second.len(), 1);
+        assert!(!paths(&first).contains(&second[0].rel_path));
+        assert!(second[0].rel_path.starts_with("notes/"));
+        assert!(ctx.resurface(&options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_flashcards_keep_schedule_across_edits() {
+        use crate::flashcards::CardQuery;
+
+        let (ctx, _temp) = create_test_context();
+        ctx.create_folder("study", None).unwrap();
+        ctx.create_doc("study", "geo.md", None).unwrap();
+        ctx.save_doc_content(
+            "study/geo.md",
+            "Capital of France :: Paris\n{{c1::Canberra}} is in Australia.\n",
+            None,
+        )
+        .unwrap();
+
+        let due = ctx.list_cards(&CardQuery::default()).unwrap();
+        assert_eq!(due.len(), 2);
+        let paris = due.iter().find(|c| c.front == "Capital of France").unwrap();
+        let reviewed = ctx.review_card(&paris.id, 4).unwrap();
+        assert_eq!(reviewed.schedule.interval_days, 1);
+        assert!(ctx.review_card(&paris.id, 6).is_err());
+        assert!(ctx.review_card("missing", 3).is_err());
+        assert_eq!(ctx.list_cards(&CardQuery::default()).unwrap().len(), 1);
+
+        // Editing the answer keeps the card's schedule; removing a card drops it
+        ctx.save_doc_content("study/geo.md", "# Geo\nCapital of France :: Paris!\n", None)
+            .unwrap();
+        let all = ctx
+            .list_cards(&CardQuery {
+                all: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!((all[0].line, all[0].back.as_str()), (2, "Paris!"));
+        assert_eq!(all[0].schedule.repetitions, 1);
+
+        let stats = ctx.card_stats().unwrap();
+        assert_eq!((stats.total, stats.due, stats.reviewed_today), (1, 0, 1));
+        assert_eq!(ctx.reindex_cards().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_flashcards_folder_filter_is_literal() {
+        use crate::flashcards::CardQuery;
+
+        let (ctx, _temp) = create_test_context();
+        for folder in ["a_b", "axb"] {
+            ctx.create_folder(folder, None).unwrap();
+            ctx.create_doc(folder, "cards.md", None).unwrap();
+            let card = format!("Capital of {folder} :: {folder}\n");
+            ctx.save_doc_content(&format!("{folder}/cards.md"), &card, None)
+                .unwrap();
+        }
+
+        let cards = ctx
+            .list_cards(&CardQuery {
+                folder: Some("a_b".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].rel_path, "a_b/cards.md");
+    }
 }
 
 #[cfg(test)]
@@ -653,6 +1848,7 @@ mod manifest_tests {
             base_root: Some(base_path.clone()),
             contexts_root: Some(base_path.join("contexts")),
             db_path: Some(base_path.join("test.db")),
+            ..Default::default()
         })
         .expect("Failed to initialize context");
 
@@ -844,4 +2040,86 @@ mod manifest_tests {
         let rel_paths: Vec<&str> = result.items.iter().map(|d| d.rel_path.as_str()).collect();
         assert_eq!(rel_paths, vec!["project/keep.md"]);
     }
+
+    #[test]
+    fn test_reconcile_picks_up_directory_moves() {
+        let (ctx, _temp) = create_test_context();
+        ctx.create_folder("notes/alpha/sub", None).unwrap();
+        ctx.create_folder("archive", None).unwrap();
+        ctx.create_folder("index", None).unwrap();
+        ctx.create_doc("notes/alpha", "plan.md", None).unwrap();
+        ctx.create_doc("notes/alpha/sub", "detail.md", None)
+            .unwrap();
+        ctx.create_doc("index", "home.md", None).unwrap();
+        ctx.save_doc_content("index/home.md", "[plan](../notes/alpha/plan.md)\n", None)
+            .unwrap();
+        let stable_id = ctx.get_doc_meta("notes/alpha/plan.md").unwrap().stable_id;
+
+        // One `mv` of the whole directory, outside OpenContext
+        let root = ctx.env_info().contexts_root;
+        std::fs::rename(root.join("notes/alpha"), root.join("archive/alpha-2024")).unwrap();
+
+        let report = ctx.reconcile_folder("archive").unwrap();
+        assert!(report.added.is_empty());
+        assert!(report.removed.is_empty());
+        assert_eq!(report.folders_moved.len(), 1);
+        assert_eq!(report.folders_moved[0].old_path, "notes/alpha");
+        assert_eq!(report.folders_moved[0].new_path, "archive/alpha-2024");
+
+        let meta = ctx.get_doc_meta("archive/alpha-2024/plan.md").unwrap();
+        assert_eq!(meta.stable_id, stable_id);
+        assert!(ctx.get_doc_meta("archive/alpha-2024/sub/detail.md").is_ok());
+        assert_eq!(
+            ctx.get_doc_content("index/home.md").unwrap(),
+            "[plan](../archive/alpha-2024/plan.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_detect_folder_moves() {
+        let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let gone = strings(&["a", "a/b", "c"]);
+        let missing = strings(&["a/b/x.md", "a/y.md", "c/z.md"]);
+        let found = strings(&["d/b/x.md", "d/y.md", "e/z.md", "f/z.md"]);
+        // `c` matches two directories, so it is left to the add/remove pass
+        assert_eq!(
+            crate::detect_folder_moves(&gone, &missing, &found),
+            vec![("a".to_string(), "d".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_index_status_tracks_changes_since_last_index() {
+        let (ctx, _temp) = create_test_context();
+        ctx.create_folder("notes", None).unwrap();
+        ctx.create_doc("notes", "a.md", None).unwrap();
+        ctx.create_doc("notes", "b.md", None).unwrap();
+
+        let status = ctx.index_status().unwrap();
+        assert!(!status.recorded);
+        assert_eq!(status.new, vec!["notes/a.md", "notes/b.md"]);
+
+        ctx.mark_indexed(None).unwrap();
+        let status = ctx.index_status().unwrap();
+        assert!(status.recorded && status.is_clean());
+        assert_eq!(status.tracked, 2);
+
+        ctx.save_doc_content("notes/a.md", "# A\n\nEdited.\n", None)
+            .unwrap();
+        ctx.create_doc("notes", "c.md", None).unwrap();
+        ctx.save_doc_content("notes/c.md", "# C\n", None).unwrap();
+        ctx.rename_doc("notes/b.md", "d.md").unwrap();
+        let status = ctx.index_status().unwrap();
+        assert_eq!(status.modified, vec!["notes/a.md"]);
+        assert_eq!(status.new, vec!["notes/c.md"]);
+        assert_eq!(status.renamed.len(), 1);
+        assert_eq!(status.renamed[0].new_path, "notes/d.md");
+
+        ctx.remove_doc("notes/d.md").unwrap();
+        ctx.mark_indexed(Some(&["notes/a.md".to_string(), "notes/b.md".to_string()]))
+            .unwrap();
+        let status = ctx.index_status().unwrap();
+        assert!(status.modified.is_empty() && status.renamed.is_empty());
+        assert_eq!(status.new, vec!["notes/c.md"]);
+    }
 }
@@ -0,0 +1,401 @@
+//! Wiki imports
+//!
+//! Brings pages that live outside markdown into the docs tree: a Confluence
+//! space exported as HTML (Space settings → Export space → HTML, unzipped)
+//! or the Google Docs of a Drive folder, exported as markdown through the
+//! Drive API. Every page becomes one doc under the import folder, nested like
+//! the page tree, with the author, last editor and update date in
+//! frontmatter. Importing the same space again rewrites only the pages that
+//! changed.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::{frontmatter, html, relpath, CoreError, CoreResult, OpenContext};
+
+/// One page read from a wiki
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WikiPage {
+    /// Titles of the enclosing pages or folders, outermost first
+    pub parents: Vec<String>,
+    pub title: String,
+    /// Markdown; links to another page's `id` are pointed at its doc
+    pub body: String,
+    pub author: Option<String>,
+    /// Who changed the page last
+    pub editor: Option<String>,
+    /// `YYYY-MM-DD` of the last change
+    pub updated: Option<String>,
+    /// Confluence export file name or Google Drive file id
+    pub id: String,
+    pub url: Option<String>,
+}
+
+/// What [`OpenContext::import_wiki`] wrote
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WikiImport {
+    /// `confluence` or `google-docs`
+    pub source: String,
+    /// Folder the pages were written to
+    pub folder: String,
+    /// Documents created
+    pub created: Vec<String>,
+    /// Existing documents that changed
+    pub updated: Vec<String>,
+    /// Documents that were already up to date
+    pub unchanged: usize,
+    pub pages: usize,
+}
+
+/// Space name and pages of an unzipped Confluence HTML export
+pub fn read_confluence_export(dir: &Path) -> CoreResult<(String, Vec<WikiPage>)> {
+    let mut files: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".html") && name != "index.html")
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(CoreError::Message(format!(
+            "{} has no Confluence pages (expected the unzipped HTML export of a space)",
+            dir.display()
+        )));
+    }
+    let mut space = String::new();
+    let mut pages = Vec::new();
+    for file in files {
+        let source = fs::read_to_string(dir.join(&file))?;
+        let (page_space, page) = parse_confluence_page(&file, &source);
+        if space.is_empty() {
+            space = page_space;
+        }
+        pages.push(page);
+    }
+    Ok((space, pages))
+}
+
+/// Space name and page of one exported page
+fn parse_confluence_page(file: &str, source: &str) -> (String, WikiPage) {
+    let full_title = html::element(source, "<title")
+        .map(html::to_text)
+        .unwrap_or_default();
+    let (space, title) = match full_title.split_once(" : ") {
+        Some((space, title)) => (space.trim().to_string(), title.trim().to_string()),
+        None => (String::new(), full_title),
+    };
+    let parents = html::element(source, r#"id="breadcrumbs""#)
+        .map(html::links)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(href, _)| href != "index.html")
+        .map(|(_, text)| text)
+        .collect();
+    let metadata = html::element(source, r#"class="page-metadata""#)
+        .map(html::to_text)
+        .unwrap_or_default();
+    let (author, editor, updated) = parse_confluence_metadata(&metadata);
+    let body = html::element(source, r#"id="main-content""#)
+        .map(html::to_markdown)
+        .unwrap_or_default();
+    let page = WikiPage {
+        parents,
+        title: if title.is_empty() {
+            file.trim_end_matches(".html").to_string()
+        } else {
+            title
+        },
+        body,
+        author,
+        editor,
+        updated,
+        id: file.to_string(),
+        url: None,
+    };
+    (space, page)
+}
+
+/// Author, editor and date of `Created by Ada, last modified by Grace on
+/// Mar 01, 2024`
+fn parse_confluence_metadata(text: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let Some(rest) = text.split("Created by ").nth(1) else {
+        return (None, None, None);
+    };
+    let (people, date) = match rest.rsplit_once(" on ") {
+        Some((people, date)) => (people, Some(date)),
+        None => (rest, None),
+    };
+    let (author, editor) = match people.split_once(", last modified by ") {
+        Some((author, editor)) => (author, Some(editor.trim().to_string())),
+        None => (people, None),
+    };
+    let updated = date
+        .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%b %d, %Y").ok())
+        .map(|d| d.format("%Y-%m-%d").to_string());
+    (Some(author.trim().to_string()), editor, updated)
+}
+
+const DRIVE_API: &str = "https://www.googleapis.com/drive/v3";
+const GOOGLE_DOC: &str = "application/vnd.google-apps.document";
+const DRIVE_FOLDER: &str = "application/vnd.google-apps.folder";
+
+/// Google Docs in the Drive folder `folder_id` and its subfolders (every
+/// doc the token can read if `None`), at most `limit`, exported as markdown.
+/// `token` is an OAuth access token with a Drive read scope.
+#[cfg(feature = "search")]
+pub async fn fetch_google_docs(
+    token: &str,
+    folder_id: Option<&str>,
+    limit: usize,
+) -> CoreResult<Vec<WikiPage>> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Person {
+        display_name: String,
+    }
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct File {
+        id: String,
+        name: String,
+        mime_type: String,
+        modified_time: Option<String>,
+        web_view_link: Option<String>,
+        #[serde(default)]
+        owners: Vec<Person>,
+        last_modifying_user: Option<Person>,
+    }
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct FileList {
+        #[serde(default)]
+        files: Vec<File>,
+        next_page_token: Option<String>,
+    }
+
+    let client = crate::sources::http_client("Google Drive")?;
+    let http_error = |e: reqwest::Error| CoreError::Message(format!("Google Drive: {e}"));
+    let mut pages = Vec::new();
+    // Folders still to list, with the titles leading to them
+    let mut pending: Vec<(Option<String>, Vec<String>)> =
+        vec![(folder_id.map(str::to_string), Vec::new())];
+    while let Some((folder, parents)) = pending.pop() {
+        let query = match &folder {
+            Some(id) => format!(
+                "'{id}' in parents and trashed = false and (mimeType = '{GOOGLE_DOC}' or mimeType = '{DRIVE_FOLDER}')"
+            ),
+            None => format!("trashed = false and mimeType = '{GOOGLE_DOC}'"),
+        };
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut params = vec![
+                ("q", query.clone()),
+                ("pageSize", "100".to_string()),
+                ("orderBy", "modifiedTime desc".to_string()),
+                (
+                    "fields",
+                    "nextPageToken,files(id,name,mimeType,modifiedTime,webViewLink,owners(displayName),lastModifyingUser(displayName))"
+                        .to_string(),
+                ),
+            ];
+            if let Some(page_token) = &page_token {
+                params.push(("pageToken", page_token.clone()));
+            }
+            let request = client
+                .get(format!("{DRIVE_API}/files"))
+                .bearer_auth(token)
+                .query(&params);
+            let list: FileList = crate::sources::send_json(request, "Google Drive").await?;
+            for file in list.files {
+                if file.mime_type == DRIVE_FOLDER {
+                    let mut path = parents.clone();
+                    path.push(file.name);
+                    pending.push((Some(file.id), path));
+                    continue;
+                }
+                let response = client
+                    .get(format!("{DRIVE_API}/files/{}/export", file.id))
+                    .bearer_auth(token)
+                    .query(&[("mimeType", "text/markdown")])
+                    .send()
+                    .await
+                    .map_err(http_error)?;
+                let status = response.status();
+                let body = response.text().await.map_err(http_error)?;
+                if !status.is_success() {
+                    return Err(CoreError::Message(format!(
+                        "Google Drive returned {status} exporting {:?}: {}",
+                        file.name,
+                        body.trim()
+                    )));
+                }
+                pages.push(WikiPage {
+                    parents: parents.clone(),
+                    title: file.name,
+                    body,
+                    author: file.owners.into_iter().next().map(|p| p.display_name),
+                    editor: file.last_modifying_user.map(|p| p.display_name),
+                    updated: file
+                        .modified_time
+                        .map(|t| t.get(0..10).unwrap_or(&t).to_string()),
+                    id: file.id,
+                    url: file.web_view_link,
+                });
+                if pages.len() >= limit {
+                    return Ok(pages);
+                }
+            }
+            match list.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+    }
+    Ok(pages)
+}
+
+/// Doc for one page
+fn render_page(source: &str, page: &WikiPage, body: &str) -> CoreResult<String> {
+    let body = body.trim();
+    let content = if body.starts_with("# ") {
+        format!("\n{body}\n")
+    } else if body.is_empty() {
+        format!("\n# {}\n", page.title)
+    } else {
+        format!("\n# {}\n\n{body}\n", page.title)
+    };
+    let optional = |value: &Option<String>| value.as_deref().map(Value::from);
+    let fields = [
+        ("title", Some(Value::from(page.title.as_str()))),
+        ("source", Some(Value::from(source))),
+        ("author", optional(&page.author)),
+        ("editor", optional(&page.editor)),
+        ("updated", optional(&page.updated)),
+        ("url", optional(&page.url)),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .try_fold(content, |content, (key, value)| {
+            frontmatter::set_field(&content, key, &value)
+        })
+}
+
+impl OpenContext {
+    /// Write `pages` read from `source` (`confluence`, `google-docs`) under
+    /// `folder`, one doc per page at `<folder>/<parent>/.../<title>.md`
+    pub fn import_wiki(
+        &self,
+        source: &str,
+        folder: &str,
+        pages: &[WikiPage],
+    ) -> CoreResult<WikiImport> {
+        let folder = folder.trim_matches('/').to_string();
+        let mut import = WikiImport {
+            source: source.to_string(),
+            folder: folder.clone(),
+            pages: pages.len(),
+            ..Default::default()
+        };
+
+        // Decide every path first so links between pages can be rewritten
+        let mut taken = HashSet::new();
+        let mut paths = Vec::with_capacity(pages.len());
+        for page in pages {
+            let mut segments: Vec<String> = page
+                .parents
+                .iter()
+                .map(|p| relpath::slug(p))
+                .filter(|s| !s.is_empty())
+                .collect();
+            segments.insert(0, folder.clone());
+            let mut name = relpath::slug(&page.title);
+            if name.is_empty() || taken.contains(&format!("{}/{name}", segments.join("/"))) {
+                name = format!("{name}-{}", relpath::slug(&page.id))
+                    .trim_start_matches('-')
+                    .to_string();
+            }
+            segments.push(name);
+            let path = segments.join("/");
+            taken.insert(path.clone());
+            paths.push(format!("{path}.md"));
+        }
+        let targets: HashMap<&str, &str> = pages
+            .iter()
+            .zip(&paths)
+            .map(|(page, path)| (page.id.as_str(), path.as_str()))
+            .collect();
+
+        for (page, rel_path) in pages.iter().zip(&paths) {
+            let body = rewrite_page_links(&page.body, &targets);
+            let content = render_page(source, page, &body)?;
+            match self.put_generated_doc(rel_path, &content)? {
+                Some(true) => import.created.push(rel_path.clone()),
+                Some(false) => import.updated.push(rel_path.clone()),
+                None => import.unchanged += 1,
+            }
+        }
+        Ok(import)
+    }
+}
+
+/// Point `](<page id>)` links at the imported docs, keeping `#anchors`
+fn rewrite_page_links(body: &str, targets: &HashMap<&str, &str>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(at) = rest.find("](") {
+        out.push_str(&rest[..at + 2]);
+        rest = &rest[at + 2..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        let (target, anchor) = rest[..end].split_at(rest[..end].find('#').unwrap_or(end));
+        if let Some(path) = targets.get(target) {
+            out.push('/');
+            out.push_str(path);
+            out.push_str(anchor);
+            rest = &rest[end..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_confluence_page() {
+        let source = r#"<html><head><title>Engineering : Release Process</title></head><body>
+            <div id="main-header"><div id="breadcrumb-section"><ol id="breadcrumbs">
+              <li class="first"><span><a href="index.html">Engineering</a></span></li>
+              <li><span><a href="Home_65538.html">Home</a></span></li>
+            </ol></div></div>
+            <div class="page-metadata">
+              Created by <span class='author'> Ada Lovelace</span>, last modified by <span class='editor'> Grace Hopper</span> on Mar 01, 2024
+            </div>
+            <div id="main-content" class="wiki-content group"><p>See <a href="Signing_65540.html#keys">signing</a>.</p></div>
+            <div class="pageSection group"><h2>Attachments:</h2></div>
+        </body></html>"#;
+        let (space, page) = parse_confluence_page("Release-Process_65539.html", source);
+        assert_eq!(space, "Engineering");
+        assert_eq!(page.title, "Release Process");
+        assert_eq!(page.parents, vec!["Home"]);
+        assert_eq!(page.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(page.editor.as_deref(), Some("Grace Hopper"));
+        assert_eq!(page.updated.as_deref(), Some("2024-03-01"));
+        assert_eq!(page.body, "See [signing](Signing_65540.html#keys).\n");
+
+        let targets = HashMap::from([("Signing_65540.html", "wiki/home/signing.md")]);
+        assert_eq!(
+            rewrite_page_links(&page.body, &targets),
+            "See [signing](/wiki/home/signing.md#keys).\n"
+        );
+        assert_eq!(
+            parse_confluence_metadata("Created by Ada on Jan 5, 2023"),
+            (Some("Ada".into()), None, Some("2023-01-05".into()))
+        );
+    }
+}
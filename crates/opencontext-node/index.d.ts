@@ -50,6 +50,98 @@ export interface SaveDocOptions {
   docPath: string
   content: string
   description?: string
+  /** Refuse with a "conflict:" error if the file is no longer at this revision */
+  expectedRevision?: string
+}
+export interface MergeDocOptions {
+  base: string
+  ours: string
+  theirs: string
+}
+export interface SetDocFieldOptions {
+  docPath: string
+  key: string
+  value: any
+}
+export interface ListTasksOptions {
+  /** Only tasks in docs under this folder */
+  folder?: string
+  /** `false` for open tasks, `true` for completed ones */
+  done?: boolean
+  /** Only tasks due on or before this date (`YYYY-MM-DD`) */
+  dueBy?: string
+  limit?: number
+}
+export interface ListDatesOptions {
+  /** `2024`, `2024-Q3`, `2024-07`, `2024-07-15` or `<from>..<to>` */
+  range?: string
+  /** Only dates in docs under this folder */
+  folder?: string
+  /** `frontmatter`, `entry` or `inline` */
+  source?: string
+  limit?: number
+}
+export interface StaleDocsOptions {
+  /** Only docs under this folder */
+  folder?: string
+  /** Leave out docs scoring below this (0-1) */
+  minScore?: number
+  limit?: number
+}
+export interface RandomDocsOptions {
+  /** Only docs under this folder */
+  folder?: string
+  /** Only docs with this tag (frontmatter `tags` or inline `#tag`) */
+  tag?: string
+  /** Docs to pick (default 1) */
+  count?: number
+}
+export interface ResurfaceOptions {
+  /** Docs to pick (default 3) */
+  count?: number
+  /** Only docs neither edited nor opened for this many days (default 90) */
+  minAgeDays?: number
+  folder?: string
+  tag?: string
+  /** Don't record the picks as shown */
+  dryRun?: boolean
+}
+export interface ListCardsOptions {
+  /** Only cards in docs under this folder */
+  folder?: string
+  /** Every card, not only those due today */
+  all?: boolean
+  limit?: number
+}
+export interface SetTaskDoneOptions {
+  docPath: string
+  /** 1-based line of the checkbox */
+  line: number
+  done: boolean
+}
+export interface CaptureOptions {
+  text: string
+  /** `text` (default), `clipboard`, `stdin`, `url` or `agent` */
+  source?: string
+  /** Page the text came from; required for `url` captures */
+  url?: string
+}
+export interface RememberOptions {
+  text: string
+  tags?: Array<string>
+  /** Expire after this long (`12h`, `30d`, `2w`) */
+  ttl?: string
+}
+export interface AppendToSectionOptions {
+  docPath: string
+  /** Heading path as in search hits, e.g. "Plan > Steps" */
+  headingPath: string
+  text: string
+}
+export interface ApplyPatchOptions {
+  docPath: string
+  /** Unified diff against the doc's current content */
+  patch: string
 }
 export interface ReconcileDocOptions {
   docPath: string
@@ -71,10 +163,96 @@ export declare function moveDoc(options: MoveDocOptions): NapiResult
 export declare function renameDoc(options: RenameDocOptions): NapiResult
 export declare function removeDoc(options: RemoveDocOptions): NapiResult
 export declare function setDocDescription(options: SetDescriptionOptions): NapiResult
+/** Whether the workspace rejects document and folder mutations */
+export declare function isReadOnly(): boolean
+/**
+ * Folders readable with `token`: `{ kind: "all" }`, `{ kind: "folders",
+ * folders: [...] }`, or `null` when the token is refused
+ */
+export declare function resolveAccess(token?: string | undefined | null): any
 export declare function getDocContent(docPath: string): NapiResult
+/**
+ * Several documents in one call, each with its content or error
+ *
+ * @param maxBytes - Limit on the contents returned together (default 256 KiB)
+ */
+export declare function getDocuments(docPaths: Array<string>, maxBytes?: number | undefined | null): NapiResult
+/**
+ * Document as sanitized HTML with links resolved and embeds expanded
+ *
+ * @param allowedFolders - Only link to and embed docs under these folders
+ */
+export declare function renderDoc(docPath: string, allowedFolders?: Array<string> | undefined | null): NapiResult
+/** Paragraph or list item marked `^blockId` in a document */
+export declare function getBlock(docPath: string, blockId: string): NapiResult
+/**
+ * Lines `line - before ..= line + after` of a document, with the heading
+ * path they sit under and the sections just above and below
+ */
+export declare function getContext(docPath: string, line: number, before?: number | undefined | null, after?: number | undefined | null): NapiResult
+/**
+ * Documents called `query` by title, alias or file name, best first
+ *
+ * @param limit - At most this many matches (default 10)
+ */
+export declare function resolveNote(query: string, limit?: number | undefined | null): NapiResult
 export declare function getDocMeta(docPath: string): NapiResult
 export declare function getDocByStableId(stableId: string): NapiResult
 export declare function saveDocContent(options: SaveDocOptions): NapiResult
+/** Revision of a doc's current content, for conditional saves */
+export declare function getDocRevision(docPath: string): string
+/** Three-way merge of two edits of the same markdown document */
+export declare function mergeDocContent(options: MergeDocOptions): NapiResult
+/** Frontmatter fields of a doc */
+export declare function getDocFields(docPath: string): any
+/** One frontmatter field of a doc (`null` if not set) */
+export declare function getDocField(docPath: string, key: string): any
+/** Set a frontmatter field in place */
+export declare function setDocField(options: SetDocFieldOptions): NapiResult
+/** Checkbox tasks across the workspace */
+export declare function listTasks(options?: ListTasksOptions | undefined | null): NapiResult
+/** Tick or untick a task's checkbox */
+export declare function setTaskDone(options: SetTaskDoneOptions): NapiResult
+/** Rebuild the task table from files on disk; returns the task count */
+export declare function reindexTasks(): number
+/** Append text to today's inbox note */
+export declare function capture(options: CaptureOptions): NapiResult
+/** Save an agent memory */
+export declare function remember(options: RememberOptions): NapiResult
+/** Live agent memories, oldest first */
+export declare function listMemories(tag?: string | undefined | null): NapiResult
+/** Delete one memory; `false` if the id is unknown */
+export declare function forgetMemory(id: string): boolean
+/** Drop expired memories; returns how many were removed */
+export declare function purgeExpiredMemories(): number
+/** Dates mentioned in docs, in chronological order */
+export declare function listDates(options?: ListDatesOptions | undefined | null): NapiResult
+/** Rebuild the date index from files on disk; returns the date count */
+export declare function reindexDates(): number
+/** Documents ranked by staleness score, most stale first */
+export declare function staleDocs(options?: StaleDocsOptions | undefined | null): NapiResult
+/** Docs picked at random */
+export declare function randomDocs(options?: RandomDocsOptions | undefined | null): NapiResult
+/** Old docs due to be shown again, recorded as shown unless `dryRun` */
+export declare function resurface(options?: ResurfaceOptions | undefined | null): NapiResult
+/** Flashcards due for review today, most overdue first */
+export declare function listCards(options?: ListCardsOptions | undefined | null): NapiResult
+/** Grade an answer to a card (0-5) and schedule its next review */
+export declare function reviewCard(cardId: string, grade: number): NapiResult
+/** Card counts for the review queue */
+export declare function cardStats(): NapiResult
+/** Rebuild the card table from files on disk; returns the card count */
+export declare function reindexCards(): number
+/** Record that docs were opened or returned by a search */
+export declare function recordDocHits(relPaths: Array<string>): void
+/** Dead links (with suggested fixes) and orphan documents */
+export declare function checkLinks(folder?: string | undefined | null): NapiResult
+/** Links a rename or move from `old_path` to `new_path` would rewrite */
+export declare function previewLinkRewrites(oldPath: string, newPath: string): NapiResult
+/** Add text at the end of a heading's section */
+export declare function appendToSection(options: AppendToSectionOptions): NapiResult
+/** Apply a unified diff to a doc */
+export declare function applyPatch(options: ApplyPatchOptions): NapiResult
 export declare function reconcileDoc(options: ReconcileDocOptions): NapiResult
 export declare function generateManifest(options: ManifestOptions): NapiResult
 export interface SuggestFoldersOptions {
@@ -85,21 +263,217 @@ export interface ReconcileOptions {
   folderPath: string
 }
 export declare function reconcileFolder(options: ReconcileOptions): NapiResult
+/** Files that changed since the search index last saw them */
+export declare function indexStatus(): NapiResult
+export interface ExportWorkspaceOptions {
+  path: string
+  /** Also bundle the search index so the target need not re-embed */
+  includeIndex?: boolean
+}
+export declare function exportWorkspace(options: ExportWorkspaceOptions): NapiResult
+export interface ImportWorkspaceOptions {
+  path: string
+  /** Replace local files and index data that the archive also contains */
+  overwrite?: boolean
+  /** Restore the archived search index if present (default true) */
+  includeIndex?: boolean
+}
+export interface ImportSessionsOptions {
+  /** Shell history file or asciinema `.cast` recording */
+  path: string
+  /** `zsh`, `bash`, `fish`, `asciinema` or `plain`; detected when omitted */
+  format?: string
+}
+export declare function importWorkspace(options: ImportWorkspaceOptions): NapiResult
+export interface ExportAnkiOptions {
+  /** `.apkg` file to write */
+  path: string
+  /** Only cards from docs with this tag */
+  tag?: string
+  /** Only cards from docs under this folder */
+  folder?: string
+  /** Deck name (default "OpenContext") */
+  deck?: string
+}
+/** Write flashcards to an Anki package; re-exporting updates the same notes */
+export declare function exportAnki(options: ExportAnkiOptions): NapiResult
+/** Import shell history or a terminal recording as session documents */
+export declare function importSessions(options: ImportSessionsOptions): NapiResult
+export interface ImportGitOptions {
+  /** Any path inside the repository */
+  repo: string
+  /** `commits` and/or `prs` (default commits only) */
+  what?: Array<string>
+  /** Only commits after this (anything `git log --since` takes) */
+  since?: string
+  /** GitHub token for pull requests (default `$GITHUB_TOKEN`) */
+  token?: string
+  /** Most pull requests to fetch, most recently updated first (default 200) */
+  limit?: number
+}
+/** Import commit messages and GitHub pull requests of a git repository */
+export declare function importGit(options: ImportGitOptions): Promise<any>
+/**
+ * Import every entry of a `.bib` file as a reference note
+ *
+ * @param folder - Where the notes go (default `references`)
+ */
+export declare function importBibtex(file: string, folder?: string | undefined | null): NapiResult
+/**
+ * Import Evernote `.enex` exports, one notebook folder per file
+ *
+ * @param folder - Where the notebooks go (default `evernote`)
+ */
+export declare function importEnex(files: Array<string>, folder?: string | undefined | null): NapiResult
+/**
+ * Import every note of Apple Notes (macOS only)
+ *
+ * @param folder - Where the Notes folders go (default `apple-notes`)
+ */
+export declare function importAppleNotes(folder?: string | undefined | null): NapiResult
+/**
+ * Import an unzipped Confluence HTML space export
+ *
+ * @param folder - Where the pages go (default `confluence/<space>`)
+ */
+export declare function importConfluence(dir: string, folder?: string | undefined | null): NapiResult
+export interface ImportGoogleDocsOptions {
+  /**
+   * Drive folder to import with its subfolders (default every doc the
+   * token can read)
+   */
+  driveFolder?: string
+  /** Where the docs go (default `google-docs`) */
+  folder?: string
+  /**
+   * OAuth access token with a Drive read scope (default
+   * `$GOOGLE_ACCESS_TOKEN`, then the `google` keyring entry)
+   */
+  token?: string
+  /** Most docs to import, most recently modified first (default 500) */
+  limit?: number
+}
+/** Import Google Docs, exported as markdown through the Drive API */
+export declare function importGoogleDocs(options: ImportGoogleDocsOptions): Promise<any>
+export interface PublishSiteOptions {
+  /**
+   * Folders published whole, minus docs with `publish: false` (default
+   * none: only docs with `publish: true`)
+   */
+  folders?: Array<string>
+  /** Site name (default `Notes`) */
+  title?: string
+}
+/**
+ * Render the published documents to a static HTML site with client-side
+ * search
+ */
+export declare function publishSite(outDir: string, options?: PublishSiteOptions | undefined | null): NapiResult
 /** Search options passed from JavaScript */
 export interface SearchOptions {
   query: string
   limit?: number
   mode?: string
   aggregateBy?: string
+  /** "doc" | "idea"; shorthand for a `type:` filter */
   docType?: string
+  /** Filter expression, e.g. `tag:rust path:projects/ created:>2024-01-01` */
+  filter?: string
   folderFilter?: string
+  /** Only results from these folders, doc types or provenance sources */
+  includeSources?: Array<string>
+  /** No results from these folders, doc types or provenance sources */
+  excludeSources?: Array<string>
   minScore?: number
   dateFrom?: string
   dateTo?: string
   includeNeighbors?: number
+  /** "doc" to nest each document's best chunks under one parent hit */
+  groupBy?: string
+  groupChildren?: number
+  /** Return folder / doc type / tag / month facet counts */
+  facets?: boolean
+  /** Result fields to return, e.g. ["file_path", "score", "snippet"] */
+  fields?: Array<string>
+  /** Embedding / vector time budget; falls back to keyword results when exceeded */
+  timeoutMs?: number
+  /** Translate the query into the workspace's other languages for keyword matching */
+  crossLanguage?: boolean
+  /** Collection to search instead of the configured one */
+  collection?: string
+  /** Folders the caller may read; hits elsewhere are dropped */
+  allowedFolders?: Array<string>
+  /**
+   * Attach a document list, matched headings and (optionally) an LLM
+   * description to each folder result
+   */
+  folderSummary?: boolean
+  /** "fast" | "accurate"; only used when embedding tiers are enabled */
+  tier?: string
+  /** "score" | "modified" | "created" | "path" | "entry_date" */
+  sortBy?: string
+  /** "asc" | "desc"; defaults to A to Z for paths, newest first for dates */
+  sortOrder?: string
+  /** "link" | "footnote": fill each result's `citation` */
+  citation?: string
+}
+/** Text used for one query only, e.g. the unsaved document in the editor */
+export interface ContextDoc {
+  /** Workspace path, if the text is (a draft of) a workspace document */
+  path?: string
+  content: string
+}
+export interface PlanIndexOptions {
+  /** Plan a full rebuild */
+  force?: boolean
+  /** Only docs in this folder */
+  folder?: string
+  /** Plan `update --dirty` instead of a build */
+  dirty?: boolean
 }
+/**
+ * What an index build or dirty update would chunk, embed and delete,
+ * without touching the index
+ */
+export declare function planIndex(options: PlanIndexOptions): any
+/**
+ * Chunk, token and cost estimate for embedding `paths` (files or folders;
+ * none means the whole workspace)
+ */
+export declare function estimateIndexCost(paths?: Array<string> | undefined | null): any
 /** Load search config */
 export declare function loadSearchConfig(): any
+/**
+ * JSON Schema of the search options, search results and event messages,
+ * keyed by type name
+ */
+export declare function apiSchema(): any
+/** Read the most recent slow-query log entries, newest first */
+export declare function readSlowQueryLog(limit?: number | undefined | null): any
+export interface DigestOptions {
+  /** Window start: `7d`, `12h`, `2w`, `YYYY-MM-DD` or an RFC 3339 timestamp */
+  since: string
+  /** Folder the note is saved in and left out of the digest (default `digests`) */
+  folder?: string
+  /** Add a summary written by the `[llm]` model */
+  summarize?: boolean
+  /** Save the note into the workspace (default true) */
+  write?: boolean
+  /** Add this many old notes due for resurfacing */
+  resurface?: number
+  /** Only resurface notes untouched for this many days (default 90) */
+  resurfaceDays?: number
+}
+/** Collect recent activity into a digest note */
+export declare function createDigest(options: DigestOptions): Promise<any>
+/**
+ * Call `callback` with every event on the bus (document, folder and
+ * search health events) as `{ schema_version, scope, event }`
+ *
+ * The subscription lasts for the life of the process and does not keep it
+ * alive.
+ */
+export declare function onEvent(callback: (message: any) => void): void
 /**
  * Start the index sync service
  *
@@ -110,6 +484,16 @@ export declare function loadSearchConfig(): any
  * @returns true if started, false if already running
  */
 export declare function startIndexSync(intervalSecs?: number | undefined | null): Promise<boolean>
+/**
+ * Stop the index sync service, flushing pending updates first
+ *
+ * Updates that cannot be applied are journaled to the WAL and replayed by
+ * the next `start_index_sync`.
+ *
+ * @param timeout_ms - How long to wait for the flush (default: 10000)
+ * @returns true once the service has stopped, false on timeout
+ */
+export declare function stopIndexSync(timeoutMs?: number | undefined | null): Promise<boolean>
 /** Check if index sync service is running */
 export declare function isIndexSyncRunning(): boolean
 /** Flush pending index updates immediately (wake up interval loop) */
@@ -119,7 +503,33 @@ export declare function flushIndexSync(): boolean
  * Note: This is approximate as the service runs in background
  */
 export declare function getIndexSyncStatus(): any
-/** Searcher - async search executor */
+/**
+ * Sync the sources configured under `[sources]` once
+ *
+ * @param only - Only sources whose id starts with this, e.g. `github:owner/name`
+ */
+export declare function syncSources(only?: string | undefined | null): Promise<any>
+/** Cursor, time and error of the last sync of every source */
+export declare function sourceStates(): NapiResult
+/**
+ * Store a source token in the system keyring
+ *
+ * @param account - What the token is for, e.g. `jira` or `linear`
+ */
+export declare function setKeyringToken(account: string, token: string): void
+/**
+ * Sync the configured sources now and then every `[sources] interval_minutes`
+ *
+ * @returns true if started, false if already running or no source is configured
+ */
+export declare function startSourceSync(): Promise<boolean>
+/**
+ * Stop the source scheduler once the sync in progress (if any) is done
+ *
+ * @returns false if it was not running
+ */
+export declare function stopSourceSync(): boolean
+/** Searcher - async search executor, shared by concurrent queries */
 export declare class Searcher {
   /**
    * Create a new Searcher instance
@@ -128,6 +538,58 @@ export declare class Searcher {
   static create(): Promise<Searcher>
   /** Execute a search query */
   search(options: SearchOptions): Promise<any>
+  /**
+   * Execute a search query, also ranking by similarity to `context_docs`,
+   * which are embedded for this query and not stored
+   */
+  searchWithContext(options: SearchOptions, contextDocs: Array<ContextDoc>): Promise<any>
+  /**
+   * Open an empty in-memory index for session-scoped texts, sharing this
+   * searcher's embedding client
+   */
+  ephemeral(): Promise<EphemeralIndex>
+  /** Autocomplete a partially typed query from indexed titles, headings and tags */
+  suggest(prefix: string, limit?: number | undefined | null): Promise<any>
+  /** Preload indexes (and optionally ping the embedding API); returns a readiness report */
+  warmUp(pingEmbedding?: boolean | undefined | null): Promise<any>
+  /** Memory held by the searcher's caches, per component of the `[memory]` budget */
+  memoryUsage(): Promise<any>
+  /** Reopen the indexes after a rebuild or clean; searches keep running meanwhile */
+  refresh(): Promise<void>
+}
+/**
+ * EphemeralIndex - in-memory index of texts added during a session
+ * (a transcript, pasted notes); nothing is written to disk
+ */
+export declare class EphemeralIndex {
+  /**
+   * Chunk, embed and add `content` as `source`, replacing any earlier
+   * text with that name; returns the number of chunks added
+   */
+  add(source: string, content: string): Promise<number>
+  /** Best chunks for `query` across the added texts */
+  search(query: string, limit?: number | undefined | null): Promise<any>
+  /** Drop the text added as `source`; returns the number of chunks removed */
+  remove(source: string): Promise<number>
+  /** Drop every added text */
+  clear(): Promise<void>
+  /** Names of the added texts, oldest first */
+  sources(): Promise<Array<string>>
+}
+/**
+ * FederatedSearcher - one query over the local workspace and every
+ * workspace under `[federation]`, with results labelled by workspace
+ */
+export declare class FederatedSearcher {
+  /**
+   * Open every configured workspace; ones that fail to open are reported
+   * in each result's `workspaces` instead of failing here
+   */
+  static create(): Promise<FederatedSearcher>
+  /** Search all workspaces concurrently and merge by weighted rank */
+  search(options: SearchOptions): Promise<any>
+  /** Names of the federated workspaces, local first */
+  workspaces(): Array<string>
 }
 /** Indexer - async index builder */
 export declare class Indexer {
@@ -136,6 +598,11 @@ export declare class Indexer {
    * Uses contexts_root from OpenContext environment
    */
   static create(): Promise<Indexer>
+  /**
+   * Stop the build in progress after its current batch. Written batches
+   * are kept and checkpointed, so the next build resumes from there.
+   */
+  cancel(): void
   /**
    * Build index with real-time progress callback.
    * Returns a Promise. Callback receives IndexProgress as a JS object.
@@ -152,6 +619,11 @@ export declare class Indexer {
   indexFile(relPath: string): Promise<number>
   /** Remove a file from the index */
   removeFile(relPath: string): Promise<void>
+  /**
+   * Re-index only the files `index_status` reports as new, modified,
+   * deleted or renamed
+   */
+  updateDirty(): Promise<any>
   /** Check if index exists */
   indexExists(): Promise<boolean>
   /** Get index statistics */
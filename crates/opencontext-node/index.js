@@ -310,7 +310,7 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { initEnvironment, listFolders, createFolder, renameFolder, moveFolder, removeFolder, listDocs, createDoc, moveDoc, renameDoc, removeDoc, setDocDescription, getDocContent, getDocMeta, getDocByStableId, saveDocContent, reconcileDoc, generateManifest, suggestFolders, reconcileFolder, Searcher, Indexer, loadSearchConfig, startIndexSync, isIndexSyncRunning, flushIndexSync, getIndexSyncStatus } = nativeBinding
+const { initEnvironment, listFolders, createFolder, renameFolder, moveFolder, removeFolder, listDocs, createDoc, moveDoc, renameDoc, removeDoc, setDocDescription, isReadOnly, resolveAccess, getDocContent, getDocuments, renderDoc, getBlock, getContext, resolveNote, getDocMeta, getDocByStableId, saveDocContent, getDocRevision, mergeDocContent, getDocFields, getDocField, setDocField, listTasks, setTaskDone, reindexTasks, capture, remember, listMemories, forgetMemory, purgeExpiredMemories, listDates, reindexDates, staleDocs, randomDocs, resurface, listCards, reviewCard, cardStats, reindexCards, recordDocHits, checkLinks, previewLinkRewrites, appendToSection, applyPatch, reconcileDoc, generateManifest, suggestFolders, reconcileFolder, indexStatus, exportWorkspace, importWorkspace, exportAnki, importSessions, importGit, importBibtex, importEnex, importAppleNotes, importConfluence, importGoogleDocs, publishSite, Searcher, FederatedSearcher, Indexer, planIndex, estimateIndexCost, loadSearchConfig, apiSchema, createDigest, onEvent, startIndexSync, stopIndexSync, isIndexSyncRunning, flushIndexSync, getIndexSyncStatus, syncSources, sourceStates, setKeyringToken, startSourceSync, stopSourceSync } = nativeBinding
 
 module.exports.initEnvironment = initEnvironment
 module.exports.listFolders = listFolders
@@ -324,18 +324,76 @@ module.exports.moveDoc = moveDoc
 module.exports.renameDoc = renameDoc
 module.exports.removeDoc = removeDoc
 module.exports.setDocDescription = setDocDescription
+module.exports.isReadOnly = isReadOnly
+module.exports.resolveAccess = resolveAccess
 module.exports.getDocContent = getDocContent
+module.exports.getDocuments = getDocuments
+module.exports.renderDoc = renderDoc
+module.exports.getBlock = getBlock
+module.exports.getContext = getContext
+module.exports.resolveNote = resolveNote
 module.exports.getDocMeta = getDocMeta
 module.exports.getDocByStableId = getDocByStableId
 module.exports.saveDocContent = saveDocContent
+module.exports.getDocRevision = getDocRevision
+module.exports.mergeDocContent = mergeDocContent
+module.exports.getDocFields = getDocFields
+module.exports.getDocField = getDocField
+module.exports.setDocField = setDocField
+module.exports.listTasks = listTasks
+module.exports.setTaskDone = setTaskDone
+module.exports.reindexTasks = reindexTasks
+module.exports.capture = capture
+module.exports.remember = remember
+module.exports.listMemories = listMemories
+module.exports.forgetMemory = forgetMemory
+module.exports.purgeExpiredMemories = purgeExpiredMemories
+module.exports.listDates = listDates
+module.exports.reindexDates = reindexDates
+module.exports.staleDocs = staleDocs
+module.exports.randomDocs = randomDocs
+module.exports.resurface = resurface
+module.exports.listCards = listCards
+module.exports.reviewCard = reviewCard
+module.exports.cardStats = cardStats
+module.exports.reindexCards = reindexCards
+module.exports.recordDocHits = recordDocHits
+module.exports.checkLinks = checkLinks
+module.exports.previewLinkRewrites = previewLinkRewrites
+module.exports.appendToSection = appendToSection
+module.exports.applyPatch = applyPatch
 module.exports.reconcileDoc = reconcileDoc
 module.exports.generateManifest = generateManifest
 module.exports.suggestFolders = suggestFolders
 module.exports.reconcileFolder = reconcileFolder
+module.exports.indexStatus = indexStatus
+module.exports.exportWorkspace = exportWorkspace
+module.exports.importWorkspace = importWorkspace
+module.exports.exportAnki = exportAnki
+module.exports.importSessions = importSessions
+module.exports.importGit = importGit
+module.exports.importBibtex = importBibtex
+module.exports.importEnex = importEnex
+module.exports.importAppleNotes = importAppleNotes
+module.exports.importConfluence = importConfluence
+module.exports.importGoogleDocs = importGoogleDocs
+module.exports.publishSite = publishSite
 module.exports.Searcher = Searcher
+module.exports.FederatedSearcher = FederatedSearcher
 module.exports.Indexer = Indexer
+module.exports.planIndex = planIndex
+module.exports.estimateIndexCost = estimateIndexCost
 module.exports.loadSearchConfig = loadSearchConfig
+module.exports.apiSchema = apiSchema
+module.exports.createDigest = createDigest
+module.exports.onEvent = onEvent
 module.exports.startIndexSync = startIndexSync
+module.exports.stopIndexSync = stopIndexSync
 module.exports.isIndexSyncRunning = isIndexSyncRunning
 module.exports.flushIndexSync = flushIndexSync
 module.exports.getIndexSyncStatus = getIndexSyncStatus
+module.exports.syncSources = syncSources
+module.exports.sourceStates = sourceStates
+module.exports.setKeyringToken = setKeyringToken
+module.exports.startSourceSync = startSourceSync
+module.exports.stopSourceSync = stopSourceSync
@@ -12,9 +12,11 @@ use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
 use opencontext_core::events::{create_event_bus, SharedEventBus};
 use opencontext_core::search::{
-    IndexSyncService, Indexer as RustIndexer, SearchConfig, SearchOptions as RustSearchOptions,
-    Searcher as RustSearcher,
+    CancellationToken, ContextDoc as RustContextDoc, EphemeralIndex as RustEphemeralIndex,
+    FederatedSearcher as RustFederatedSearcher, IndexSyncService, Indexer as RustIndexer,
+    SearchConfig, SearchOptions as RustSearchOptions, Searcher as RustSearcher,
 };
+use opencontext_core::sources::SourceScheduler;
 use opencontext_core::{CoreError, EnvOverrides, OpenContext};
 use serde::Serialize;
 use tokio::sync::{Mutex, Notify};
@@ -28,6 +30,10 @@ static INDEX_SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
 // Flush signal — notified to wake up the interval loop immediately
 static FLUSH_NOTIFY: Lazy<Arc<Notify>> = Lazy::new(|| Arc::new(Notify::new()));
 
+// Handle to the running IndexSyncService, kept so it can be stopped on shutdown
+static SYNC_SERVICE: Lazy<std::sync::Mutex<Option<Arc<IndexSyncService>>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
 static CONTEXT: OnceCell<OpenContext> = OnceCell::new();
 
 fn ctx() -> NapiResult<&'static OpenContext> {
@@ -112,6 +118,124 @@ pub struct SaveDocOptions {
     pub doc_path: String,
     pub content: String,
     pub description: Option<String>,
+    /// Refuse with a "conflict:" error if the file is no longer at this revision
+    pub expected_revision: Option<String>,
+}
+
+#[napi(object)]
+pub struct MergeDocOptions {
+    pub base: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+#[napi(object)]
+pub struct SetDocFieldOptions {
+    pub doc_path: String,
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+#[napi(object)]
+pub struct ListTasksOptions {
+    /// Only tasks in docs under this folder
+    pub folder: Option<String>,
+    /// `false` for open tasks, `true` for completed ones
+    pub done: Option<bool>,
+    /// Only tasks due on or before this date (`YYYY-MM-DD`)
+    pub due_by: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[napi(object)]
+pub struct ListDatesOptions {
+    /// `2024`, `2024-Q3`, `2024-07`, `2024-07-15` or `<from>..<to>`
+    pub range: Option<String>,
+    /// Only dates in docs under this folder
+    pub folder: Option<String>,
+    /// `frontmatter`, `entry` or `inline`
+    pub source: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[napi(object)]
+pub struct StaleDocsOptions {
+    /// Only docs under this folder
+    pub folder: Option<String>,
+    /// Leave out docs scoring below this (0-1)
+    pub min_score: Option<f64>,
+    pub limit: Option<u32>,
+}
+
+#[napi(object)]
+pub struct RandomDocsOptions {
+    /// Only docs under this folder
+    pub folder: Option<String>,
+    /// Only docs with this tag (frontmatter `tags` or inline `#tag`)
+    pub tag: Option<String>,
+    /// Docs to pick (default 1)
+    pub count: Option<u32>,
+}
+
+#[napi(object)]
+pub struct ResurfaceOptions {
+    /// Docs to pick (default 3)
+    pub count: Option<u32>,
+    /// Only docs neither edited nor opened for this many days (default 90)
+    pub min_age_days: Option<u32>,
+    pub folder: Option<String>,
+    pub tag: Option<String>,
+    /// Don't record the picks as shown
+    pub dry_run: Option<bool>,
+}
+
+#[napi(object)]
+pub struct ListCardsOptions {
+    /// Only cards in docs under this folder
+    pub folder: Option<String>,
+    /// Every card, not only those due today
+    pub all: Option<bool>,
+    pub limit: Option<u32>,
+}
+
+#[napi(object)]
+pub struct SetTaskDoneOptions {
+    pub doc_path: String,
+    /// 1-based line of the checkbox
+    pub line: u32,
+    pub done: bool,
+}
+
+#[napi(object)]
+pub struct CaptureOptions {
+    pub text: String,
+    /// `text` (default), `clipboard`, `stdin`, `url` or `agent`
+    pub source: Option<String>,
+    /// Page the text came from; required for `url` captures
+    pub url: Option<String>,
+}
+
+#[napi(object)]
+pub struct RememberOptions {
+    pub text: String,
+    pub tags: Option<Vec<String>>,
+    /// Expire after this long (`12h`, `30d`, `2w`)
+    pub ttl: Option<String>,
+}
+
+#[napi(object)]
+pub struct AppendToSectionOptions {
+    pub doc_path: String,
+    /// Heading path as in search hits, e.g. "Plan > Steps"
+    pub heading_path: String,
+    pub text: String,
+}
+
+#[napi(object)]
+pub struct ApplyPatchOptions {
+    pub doc_path: String,
+    /// Unified diff against the doc's current content
+    pub patch: String,
 }
 
 #[napi(object)]
@@ -213,6 +337,22 @@ pub fn set_doc_description(env: Env, options: SetDescriptionOptions) -> NapiResu
     to_js(env, &result)
 }
 
+/// Whether the workspace rejects document and folder mutations
+#[napi]
+pub fn is_read_only() -> NapiResult<bool> {
+    let ctx = ctx()?;
+    Ok(ctx.is_read_only())
+}
+
+/// Folders readable with `token`: `{ kind: "all" }`, `{ kind: "folders",
+/// folders: [...] }`, or `null` when the token is refused
+#[napi]
+pub fn resolve_access(token: Option<String>) -> NapiResult<serde_json::Value> {
+    let ctx = ctx()?;
+    serde_json::to_value(ctx.access_for(token.as_deref()))
+        .map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
 #[napi]
 pub fn get_doc_content(doc_path: String) -> NapiResult<String> {
     let ctx = ctx()?;
@@ -220,6 +360,71 @@ pub fn get_doc_content(doc_path: String) -> NapiResult<String> {
     Ok(content)
 }
 
+/// Several documents in one call, each with its content or error
+///
+/// @param maxBytes - Limit on the contents returned together (default 256 KiB)
+#[napi]
+pub fn get_documents(
+    env: Env,
+    doc_paths: Vec<String>,
+    max_bytes: Option<u32>,
+) -> NapiResult<JsUnknown> {
+    let max_bytes = max_bytes.map_or(opencontext_core::DEFAULT_BATCH_BYTES, |b| b as usize);
+    let docs = ctx()?.get_documents(&doc_paths, max_bytes);
+    to_js(env, &docs)
+}
+
+/// Document as sanitized HTML with links resolved and embeds expanded
+///
+/// @param allowedFolders - Only link to and embed docs under these folders
+#[napi]
+pub fn render_doc(
+    env: Env,
+    doc_path: String,
+    allowed_folders: Option<Vec<String>>,
+) -> NapiResult<JsUnknown> {
+    use opencontext_core::acl::Access;
+
+    let access = allowed_folders.map_or(Access::All, Access::Folders);
+    let doc = convert(ctx()?.render(&doc_path, &access))?;
+    to_js(env, &doc)
+}
+
+/// Paragraph or list item marked `^blockId` in a document
+#[napi]
+pub fn get_block(env: Env, doc_path: String, block_id: String) -> NapiResult<JsUnknown> {
+    let block = convert(ctx()?.get_block(&doc_path, &block_id))?;
+    to_js(env, &block)
+}
+
+/// Lines `line - before ..= line + after` of a document, with the heading
+/// path they sit under and the sections just above and below
+#[napi]
+pub fn get_context(
+    env: Env,
+    doc_path: String,
+    line: u32,
+    before: Option<u32>,
+    after: Option<u32>,
+) -> NapiResult<JsUnknown> {
+    let context = convert(ctx()?.get_context(
+        &doc_path,
+        line as usize,
+        before.unwrap_or(0) as usize,
+        after.unwrap_or(0) as usize,
+    ))?;
+    to_js(env, &context)
+}
+
+/// Documents called `query` by title, alias or file name, best first
+///
+/// @param limit - At most this many matches (default 10)
+#[napi]
+pub fn resolve_note(env: Env, query: String, limit: Option<u32>) -> NapiResult<JsUnknown> {
+    let matches = convert(ctx()?.resolve_note(&query, limit.unwrap_or(10) as usize))?;
+    to_js(env, &matches)
+}
+
 #[napi]
 pub fn get_doc_meta(env: Env, doc_path: String) -> NapiResult<JsUnknown> {
     let ctx = ctx()?;
@@ -235,53 +440,645 @@ pub fn get_doc_by_stable_id(env: Env, stable_id: String) -> NapiResult<JsUnknown
 }
 
 #[napi]
-pub fn save_doc_content(env: Env, options: SaveDocOptions) -> NapiResult<JsUnknown> {
-    let ctx = ctx()?;
-    let result = convert(ctx.save_doc_content(
-        &options.doc_path,
-        &options.content,
-        options.description.as_deref(),
-    ))?;
-    to_js(env, &result)
-}
+pub fn save_doc_content(env: Env, options: SaveDocOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let result = match options.expected_revision.as_deref() {
+        Some(expected) => convert(ctx.save_doc_content_if_unchanged(
+            &options.doc_path,
+            &options.content,
+            options.description.as_deref(),
+            expected,
+        ))?,
+        None => convert(ctx.save_doc_content(
+            &options.doc_path,
+            &options.content,
+            options.description.as_deref(),
+        ))?,
+    };
+    to_js(env, &result)
+}
+
+/// Revision of a doc's current content, for conditional saves
+#[napi]
+pub fn get_doc_revision(doc_path: String) -> NapiResult<String> {
+    let ctx = ctx()?;
+    convert(ctx.doc_revision(&doc_path))
+}
+
+/// Three-way merge of two edits of the same markdown document
+#[napi]
+pub fn merge_doc_content(env: Env, options: MergeDocOptions) -> NapiResult<JsUnknown> {
+    let result = opencontext_core::merge::merge3(&options.base, &options.ours, &options.theirs);
+    to_js(env, &result)
+}
+
+/// Frontmatter fields of a doc
+#[napi]
+pub fn get_doc_fields(doc_path: String) -> NapiResult<serde_json::Value> {
+    let ctx = ctx()?;
+    convert(ctx.get_doc_fields(&doc_path)).map(serde_json::Value::Object)
+}
+
+/// One frontmatter field of a doc (`null` if not set)
+#[napi]
+pub fn get_doc_field(doc_path: String, key: String) -> NapiResult<serde_json::Value> {
+    let ctx = ctx()?;
+    convert(ctx.get_doc_field(&doc_path, &key)).map(Option::unwrap_or_default)
+}
+
+/// Set a frontmatter field in place
+#[napi]
+pub fn set_doc_field(env: Env, options: SetDocFieldOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let result = convert(ctx.set_doc_field(&options.doc_path, &options.key, &options.value))?;
+    to_js(env, &result)
+}
+
+/// Checkbox tasks across the workspace
+#[napi]
+pub fn list_tasks(env: Env, options: Option<ListTasksOptions>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let query = options.map_or_else(Default::default, |o| opencontext_core::tasks::TaskQuery {
+        folder: o.folder,
+        done: o.done,
+        due_by: o.due_by,
+        limit: o.limit.map(|l| l as usize),
+    });
+    let tasks = convert(ctx.list_tasks(&query))?;
+    to_js(env, &tasks)
+}
+
+/// Tick or untick a task's checkbox
+#[napi]
+pub fn set_task_done(env: Env, options: SetTaskDoneOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let result =
+        convert(ctx.set_task_done(&options.doc_path, options.line as usize, options.done))?;
+    to_js(env, &result)
+}
+
+/// Rebuild the task table from files on disk; returns the task count
+#[napi]
+pub fn reindex_tasks() -> NapiResult<u32> {
+    let ctx = ctx()?;
+    convert(ctx.reindex_tasks()).map(|n| n as u32)
+}
+
+/// Append text to today's inbox note
+#[napi]
+pub fn capture(env: Env, options: CaptureOptions) -> NapiResult<JsUnknown> {
+    use opencontext_core::capture::CaptureSource;
+
+    let ctx = ctx()?;
+    let source = match options.source.as_deref() {
+        Some(s) => CaptureSource::parse(s)
+            .ok_or_else(|| napi::Error::from_reason(format!("unknown capture source: {s}")))?,
+        None => CaptureSource::default(),
+    };
+    let captured = convert(ctx.capture(&options.text, source, options.url.as_deref()))?;
+    to_js(env, &captured)
+}
+
+/// Save an agent memory
+#[napi]
+pub fn remember(env: Env, options: RememberOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let tags = options.tags.unwrap_or_default();
+    let entry = convert(ctx.remember(&options.text, &tags, options.ttl.as_deref()))?;
+    to_js(env, &entry)
+}
+
+/// Live agent memories, oldest first
+#[napi]
+pub fn list_memories(env: Env, tag: Option<String>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let entries = convert(ctx.list_memories(tag.as_deref()))?;
+    to_js(env, &entries)
+}
+
+/// Delete one memory; `false` if the id is unknown
+#[napi]
+pub fn forget_memory(id: String) -> NapiResult<bool> {
+    let ctx = ctx()?;
+    convert(ctx.forget(&id))
+}
+
+/// Drop expired memories; returns how many were removed
+#[napi]
+pub fn purge_expired_memories() -> NapiResult<u32> {
+    let ctx = ctx()?;
+    convert(ctx.purge_expired_memories()).map(|n| n as u32)
+}
+
+/// Dates mentioned in docs, in chronological order
+#[napi]
+pub fn list_dates(env: Env, options: Option<ListDatesOptions>) -> NapiResult<JsUnknown> {
+    use opencontext_core::dates::{DateQuery, DateRange, DateSource};
+
+    let ctx = ctx()?;
+    let query = match options {
+        Some(o) => DateQuery {
+            range: o
+                .range
+                .as_deref()
+                .map(DateRange::parse)
+                .transpose()
+                .map_err(to_napi_error)?,
+            folder: o.folder,
+            source: match o.source.as_deref() {
+                Some(s) => Some(DateSource::parse(s).ok_or_else(|| {
+                    napi::Error::from_reason(format!("unknown date source: {s}"))
+                })?),
+                None => None,
+            },
+            limit: o.limit.map(|l| l as usize),
+        },
+        None => DateQuery::default(),
+    };
+    let dates = convert(ctx.list_dates(&query))?;
+    to_js(env, &dates)
+}
+
+/// Rebuild the date index from files on disk; returns the date count
+#[napi]
+pub fn reindex_dates() -> NapiResult<u32> {
+    let ctx = ctx()?;
+    convert(ctx.reindex_dates()).map(|n| n as u32)
+}
+
+/// Documents ranked by staleness score, most stale first
+#[napi]
+pub fn stale_docs(env: Env, options: Option<StaleDocsOptions>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let query = options.map_or_else(Default::default, |o| {
+        opencontext_core::staleness::StaleQuery {
+            folder: o.folder,
+            min_score: o.min_score,
+            limit: o.limit.map(|l| l as usize),
+        }
+    });
+    let docs = convert(ctx.stale_docs(&query))?;
+    to_js(env, &docs)
+}
+
+/// Docs picked at random
+#[napi]
+pub fn random_docs(env: Env, options: Option<RandomDocsOptions>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let query = options.map_or_else(Default::default, |o| {
+        opencontext_core::resurface::RandomQuery {
+            folder: o.folder,
+            tag: o.tag,
+            count: o.count.map(|c| c as usize),
+        }
+    });
+    let docs = convert(ctx.random_docs(&query))?;
+    to_js(env, &docs)
+}
+
+/// Old docs due to be shown again, recorded as shown unless `dryRun`
+#[napi]
+pub fn resurface(env: Env, options: Option<ResurfaceOptions>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let options = options.map_or_else(Default::default, |o| {
+        opencontext_core::resurface::ResurfaceOptions {
+            count: o.count.map(|c| c as usize),
+            min_age_days: o.min_age_days.map(i64::from),
+            folder: o.folder,
+            exclude_folder: None,
+            tag: o.tag,
+            dry_run: o.dry_run.unwrap_or(false),
+        }
+    });
+    let docs = convert(ctx.resurface(&options))?;
+    to_js(env, &docs)
+}
+
+/// Flashcards due for review today, most overdue first
+#[napi]
+pub fn list_cards(env: Env, options: Option<ListCardsOptions>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let query = options.map_or_else(Default::default, |o| {
+        opencontext_core::flashcards::CardQuery {
+            folder: o.folder,
+            all: o.all.unwrap_or(false),
+            limit: o.limit.map(|l| l as usize),
+        }
+    });
+    let cards = convert(ctx.list_cards(&query))?;
+    to_js(env, &cards)
+}
+
+/// Grade an answer to a card (0-5) and schedule its next review
+#[napi]
+pub fn review_card(env: Env, card_id: String, grade: u32) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let grade = u8::try_from(grade).unwrap_or(u8::MAX);
+    let card = convert(ctx.review_card(&card_id, grade))?;
+    to_js(env, &card)
+}
+
+/// Card counts for the review queue
+#[napi]
+pub fn card_stats(env: Env) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let stats = convert(ctx.card_stats())?;
+    to_js(env, &stats)
+}
+
+/// Rebuild the card table from files on disk; returns the card count
+#[napi]
+pub fn reindex_cards() -> NapiResult<u32> {
+    let ctx = ctx()?;
+    convert(ctx.reindex_cards()).map(|n| n as u32)
+}
+
+/// Record that docs were opened or returned by a search
+#[napi]
+pub fn record_doc_hits(rel_paths: Vec<String>) -> NapiResult<()> {
+    let ctx = ctx()?;
+    convert(ctx.record_doc_hits(&rel_paths))
+}
+
+/// Dead links (with suggested fixes) and orphan documents
+#[napi]
+pub fn check_links(env: Env, folder: Option<String>) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let report = convert(ctx.check_links(folder.as_deref()))?;
+    to_js(env, &report)
+}
+
+/// Links a rename or move from `old_path` to `new_path` would rewrite
+#[napi]
+pub fn preview_link_rewrites(
+    env: Env,
+    old_path: String,
+    new_path: String,
+) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let rewrites = convert(ctx.preview_link_rewrites(&old_path, &new_path))?;
+    to_js(env, &rewrites)
+}
+
+/// Add text at the end of a heading's section
+#[napi]
+pub fn append_to_section(env: Env, options: AppendToSectionOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let result =
+        convert(ctx.append_to_section(&options.doc_path, &options.heading_path, &options.text))?;
+    to_js(env, &result)
+}
+
+/// Apply a unified diff to a doc
+#[napi]
+pub fn apply_patch(env: Env, options: ApplyPatchOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let result = convert(ctx.apply_patch(&options.doc_path, &options.patch))?;
+    to_js(env, &result)
+}
+
+#[napi]
+pub fn reconcile_doc(env: Env, options: ReconcileDocOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let result = convert(ctx.reconcile_doc(&options.doc_path, options.description.as_deref()))?;
+    to_js(env, &result)
+}
+
+#[napi]
+pub fn generate_manifest(env: Env, options: ManifestOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let manifest = convert(
+        ctx.generate_manifest_full(&options.folder_path, options.limit.map(|v| v as usize)),
+    )?;
+    to_js(env, &manifest)
+}
+
+#[napi(object)]
+pub struct SuggestFoldersOptions {
+    pub query: String,
+}
+
+#[napi]
+pub fn suggest_folders(env: Env, options: SuggestFoldersOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let suggestions = convert(ctx.suggest_folders(&options.query))?;
+    to_js(env, &suggestions)
+}
+
+#[napi(object)]
+pub struct ReconcileOptions {
+    pub folder_path: String,
+}
+
+#[napi]
+pub fn reconcile_folder(env: Env, options: ReconcileOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let report = convert(ctx.reconcile_folder(&options.folder_path))?;
+    to_js(env, &report)
+}
+
+/// Files that changed since the search index last saw them
+#[napi]
+pub fn index_status(env: Env) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let status = convert(ctx.index_status())?;
+    to_js(env, &status)
+}
+
+#[napi(object)]
+pub struct ExportWorkspaceOptions {
+    pub path: String,
+    /// Also bundle the search index so the target need not re-embed
+    pub include_index: Option<bool>,
+}
+
+#[napi]
+pub fn export_workspace(env: Env, options: ExportWorkspaceOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let index_files = if options.include_index.unwrap_or(false) {
+        SearchConfig::load()
+            .map_err(search_error_to_napi)?
+            .paths
+            .index_files()
+    } else {
+        Vec::new()
+    };
+    let manifest = convert(ctx.export_workspace(
+        &PathBuf::from(options.path),
+        &opencontext_core::archive::ExportOptions { index_files },
+    ))?;
+    to_js(env, &manifest)
+}
+
+#[napi(object)]
+pub struct ImportWorkspaceOptions {
+    pub path: String,
+    /// Replace local files and index data that the archive also contains
+    pub overwrite: Option<bool>,
+    /// Restore the archived search index if present (default true)
+    pub include_index: Option<bool>,
+}
+
+#[napi]
+pub fn import_workspace(env: Env, options: ImportWorkspaceOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let index_files = if options.include_index.unwrap_or(true) {
+        SearchConfig::load()
+            .map_err(search_error_to_napi)?
+            .paths
+            .index_files()
+    } else {
+        Vec::new()
+    };
+    let report = convert(ctx.import_workspace(
+        &PathBuf::from(options.path),
+        &opencontext_core::archive::ImportOptions {
+            overwrite: options.overwrite.unwrap_or(false),
+            index_files,
+        },
+    ))?;
+    to_js(env, &report)
+}
+
+#[napi(object)]
+pub struct ExportAnkiOptions {
+    /// `.apkg` file to write
+    pub path: String,
+    /// Only cards from docs with this tag
+    pub tag: Option<String>,
+    /// Only cards from docs under this folder
+    pub folder: Option<String>,
+    /// Deck name (default "OpenContext")
+    pub deck: Option<String>,
+}
+
+/// Write flashcards to an Anki package; re-exporting updates the same notes
+#[napi]
+pub fn export_anki(env: Env, options: ExportAnkiOptions) -> NapiResult<JsUnknown> {
+    let ctx = ctx()?;
+    let export = convert(ctx.export_anki(
+        &PathBuf::from(options.path),
+        &opencontext_core::anki::AnkiExportOptions {
+            tag: options.tag,
+            folder: options.folder,
+            deck: options.deck,
+        },
+    ))?;
+    to_js(env, &export)
+}
+
+#[napi(object)]
+pub struct ImportSessionsOptions {
+    /// Shell history file or asciinema `.cast` recording
+    pub path: String,
+    /// `zsh`, `bash`, `fish`, `asciinema` or `plain`; detected when omitted
+    pub format: Option<String>,
+}
+
+/// Import shell history or a terminal recording as session documents
+#[napi]
+pub fn import_sessions(env: Env, options: ImportSessionsOptions) -> NapiResult<JsUnknown> {
+    use opencontext_core::sessions::HistoryFormat;
+
+    let ctx = ctx()?;
+    let format = match options.format.as_deref() {
+        Some(f) => Some(
+            HistoryFormat::parse(f)
+                .ok_or_else(|| napi::Error::from_reason(format!("unknown history format: {f}")))?,
+        ),
+        None => None,
+    };
+    let raw = std::fs::read(&options.path)
+        .map_err(|e| napi::Error::from_reason(format!("{}: {e}", options.path)))?;
+    // Shell histories are not always valid UTF-8 (zsh metafies some bytes)
+    let content = String::from_utf8_lossy(&raw);
+    let config = SearchConfig::load().map_err(search_error_to_napi)?.sessions;
+    let report = convert(ctx.import_sessions(&content, &options.path, format, &config))?;
+    to_js(env, &report)
+}
+
+#[napi(object)]
+pub struct ImportGitOptions {
+    /// Any path inside the repository
+    pub repo: String,
+    /// `commits` and/or `prs` (default commits only)
+    pub what: Option<Vec<String>>,
+    /// Only commits after this (anything `git log --since` takes)
+    pub since: Option<String>,
+    /// GitHub token for pull requests (default `$GITHUB_TOKEN`)
+    pub token: Option<String>,
+    /// Most pull requests to fetch, most recently updated first (default 200)
+    pub limit: Option<u32>,
+}
+
+/// Import commit messages and GitHub pull requests of a git repository
+#[napi]
+pub async fn import_git(options: ImportGitOptions) -> Result<serde_json::Value> {
+    use opencontext_core::git_history;
+
+    let what = options.what.unwrap_or_else(|| vec!["commits".to_string()]);
+    if let Some(other) = what
+        .iter()
+        .find(|w| !matches!(w.as_str(), "commits" | "prs"))
+    {
+        return Err(napi::Error::from_reason(format!(
+            "cannot import {other:?} (expected commits or prs)"
+        )));
+    }
+    let repo = convert(git_history::repo_root(&PathBuf::from(&options.repo)))?;
+    let commits = if what.iter().any(|w| w == "commits") {
+        convert(git_history::read_commits(&repo, options.since.as_deref()))?
+    } else {
+        Vec::new()
+    };
+    let pulls = if what.iter().any(|w| w == "prs") {
+        let github = convert(git_history::github_repo(&repo))?.ok_or_else(|| {
+            napi::Error::from_reason(format!(
+                "{} has no GitHub `origin` remote to fetch pull requests from",
+                repo.display()
+            ))
+        })?;
+        let token = options
+            .token
+            .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+            .filter(|t| !t.is_empty());
+        let limit = options.limit.unwrap_or(200) as usize;
+        convert(git_history::fetch_pull_requests(&github, token.as_deref(), limit).await)?
+    } else {
+        Vec::new()
+    };
+
+    let report = convert(ctx()?.import_git(&repo, &commits, &pulls))?;
+    serde_json::to_value(&report).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// Import every entry of a `.bib` file as a reference note
+///
+/// @param folder - Where the notes go (default `references`)
+#[napi]
+pub fn import_bibtex(env: Env, file: String, folder: Option<String>) -> NapiResult<JsUnknown> {
+    use opencontext_core::bibtex;
+
+    let source = std::fs::read_to_string(&file)
+        .map_err(|e| napi::Error::from_reason(format!("cannot read {file}: {e}")))?;
+    let entries = bibtex::parse_bibtex(&source);
+    let folder = folder.unwrap_or_else(|| "references".to_string());
+    let report = convert(ctx()?.import_bibtex(&folder, &entries))?;
+    to_js(env, &report)
+}
+
+/// Import Evernote `.enex` exports, one notebook folder per file
+///
+/// @param folder - Where the notebooks go (default `evernote`)
+#[napi]
+pub fn import_enex(env: Env, files: Vec<String>, folder: Option<String>) -> NapiResult<JsUnknown> {
+    use opencontext_core::notes_import;
+
+    let mut notes = Vec::new();
+    for file in &files {
+        notes.extend(convert(notes_import::read_enex(&PathBuf::from(file)))?);
+    }
+    let folder = folder.unwrap_or_else(|| "evernote".to_string());
+    let report = convert(ctx()?.import_notes("evernote", &folder, &notes))?;
+    to_js(env, &report)
+}
+
+/// Import every note of Apple Notes (macOS only)
+///
+/// @param folder - Where the Notes folders go (default `apple-notes`)
+#[napi]
+pub fn import_apple_notes(env: Env, folder: Option<String>) -> NapiResult<JsUnknown> {
+    use opencontext_core::notes_import;
 
-#[napi]
-pub fn reconcile_doc(env: Env, options: ReconcileDocOptions) -> NapiResult<JsUnknown> {
-    let ctx = ctx()?;
-    let result = convert(ctx.reconcile_doc(&options.doc_path, options.description.as_deref()))?;
-    to_js(env, &result)
+    let notes = convert(notes_import::read_apple_notes())?;
+    let folder = folder.unwrap_or_else(|| "apple-notes".to_string());
+    let report = convert(ctx()?.import_notes("apple-notes", &folder, &notes))?;
+    to_js(env, &report)
 }
 
+/// Import an unzipped Confluence HTML space export
+///
+/// @param folder - Where the pages go (default `confluence/<space>`)
 #[napi]
-pub fn generate_manifest(env: Env, options: ManifestOptions) -> NapiResult<JsUnknown> {
-    let ctx = ctx()?;
-    let manifest = convert(
-        ctx.generate_manifest_full(&options.folder_path, options.limit.map(|v| v as usize)),
-    )?;
-    to_js(env, &manifest)
+pub fn import_confluence(env: Env, dir: String, folder: Option<String>) -> NapiResult<JsUnknown> {
+    use opencontext_core::wiki_import;
+
+    let (space, pages) = convert(wiki_import::read_confluence_export(&PathBuf::from(&dir)))?;
+    let folder = folder.unwrap_or_else(|| {
+        let space = opencontext_core::relpath::slug(&space);
+        if space.is_empty() {
+            "confluence".to_string()
+        } else {
+            format!("confluence/{space}")
+        }
+    });
+    let report = convert(ctx()?.import_wiki("confluence", &folder, &pages))?;
+    to_js(env, &report)
 }
 
 #[napi(object)]
-pub struct SuggestFoldersOptions {
-    pub query: String,
+pub struct ImportGoogleDocsOptions {
+    /// Drive folder to import with its subfolders (default every doc the
+    /// token can read)
+    pub drive_folder: Option<String>,
+    /// Where the docs go (default `google-docs`)
+    pub folder: Option<String>,
+    /// OAuth access token with a Drive read scope (default
+    /// `$GOOGLE_ACCESS_TOKEN`, then the `google` keyring entry)
+    pub token: Option<String>,
+    /// Most docs to import, most recently modified first (default 500)
+    pub limit: Option<u32>,
 }
 
+/// Import Google Docs, exported as markdown through the Drive API
 #[napi]
-pub fn suggest_folders(env: Env, options: SuggestFoldersOptions) -> NapiResult<JsUnknown> {
-    let ctx = ctx()?;
-    let suggestions = convert(ctx.suggest_folders(&options.query))?;
-    to_js(env, &suggestions)
+pub async fn import_google_docs(options: ImportGoogleDocsOptions) -> Result<serde_json::Value> {
+    use opencontext_core::wiki_import;
+
+    let token = options
+        .token
+        .or_else(|| std::env::var("GOOGLE_ACCESS_TOKEN").ok())
+        .filter(|t| !t.is_empty())
+        .or_else(|| opencontext_core::keyring::get("google"))
+        .ok_or_else(|| {
+            napi::Error::from_reason(
+                "no Google access token: pass one, set GOOGLE_ACCESS_TOKEN or run `oc sources token google`",
+            )
+        })?;
+    let limit = options.limit.unwrap_or(500) as usize;
+    let pages = convert(
+        wiki_import::fetch_google_docs(&token, options.drive_folder.as_deref(), limit).await,
+    )?;
+    let folder = options.folder.unwrap_or_else(|| "google-docs".to_string());
+    let report = convert(ctx()?.import_wiki("google-docs", &folder, &pages))?;
+    serde_json::to_value(&report).map_err(|e| napi::Error::from_reason(e.to_string()))
 }
 
 #[napi(object)]
-pub struct ReconcileOptions {
-    pub folder_path: String,
+pub struct PublishSiteOptions {
+    /// Folders published whole, minus docs with `publish: false` (default
+    /// none: only docs with `publish: true`)
+    pub folders: Option<Vec<String>>,
+    /// Site name (default `Notes`)
+    pub title: Option<String>,
 }
 
+/// Render the published documents to a static HTML site with client-side
+/// search
 #[napi]
-pub fn reconcile_folder(env: Env, options: ReconcileOptions) -> NapiResult<JsUnknown> {
-    let ctx = ctx()?;
-    let report = convert(ctx.reconcile_folder(&options.folder_path))?;
+pub fn publish_site(
+    env: Env,
+    out_dir: String,
+    options: Option<PublishSiteOptions>,
+) -> NapiResult<JsUnknown> {
+    use opencontext_core::publish::PublishOptions;
+
+    let (folders, title) = options.map_or((None, None), |o| (o.folders, o.title));
+    let options = PublishOptions {
+        folders: folders.unwrap_or_default(),
+        title: title.unwrap_or_else(|| "Notes".to_string()),
+    };
+    let report = convert(ctx()?.publish_site(&PathBuf::from(out_dir), &options))?;
     to_js(env, &report)
 }
 
@@ -295,8 +1092,15 @@ fn convert<T>(value: opencontext_core::CoreResult<T>) -> NapiResult<T> {
 
 // ==================== Search Module ====================
 
+/// Carries the error code and hint in the message as `[CODE] message` and
+/// a trailing `\nHint: ...` line; `native-adapter.js` splits them back out
 fn search_error_to_napi(err: opencontext_core::search::SearchError) -> napi::Error {
-    napi::Error::from_reason(err.to_string())
+    let mut reason = format!("[{}] {}", err.code(), err);
+    if let Some(hint) = err.hint() {
+        reason.push_str("\nHint: ");
+        reason.push_str(hint);
+    }
+    napi::Error::from_reason(reason)
 }
 
 /// Search options passed from JavaScript
@@ -306,21 +1110,60 @@ pub struct SearchOptions {
     pub limit: Option<u32>,
     pub mode: Option<String>,
     pub aggregate_by: Option<String>,
+    /// "doc" | "idea"; shorthand for a `type:` filter
     pub doc_type: Option<String>,
+    /// Filter expression, e.g. `tag:rust path:projects/ created:>2024-01-01`
+    pub filter: Option<String>,
     pub folder_filter: Option<String>,
+    /// Only results from these folders, doc types or provenance sources
+    pub include_sources: Option<Vec<String>>,
+    /// No results from these folders, doc types or provenance sources
+    pub exclude_sources: Option<Vec<String>>,
     pub min_score: Option<f64>,
     pub date_from: Option<String>,
     pub date_to: Option<String>,
     pub include_neighbors: Option<u32>,
+    /// "doc" to nest each document's best chunks under one parent hit
+    pub group_by: Option<String>,
+    pub group_children: Option<u32>,
+    /// Return folder / doc type / tag / month facet counts
+    pub facets: Option<bool>,
+    /// Result fields to return, e.g. ["file_path", "score", "snippet"]
+    pub fields: Option<Vec<String>>,
+    /// Embedding / vector time budget; falls back to keyword results when exceeded
+    pub timeout_ms: Option<u32>,
+    /// Translate the query into the workspace's other languages for keyword matching
+    pub cross_language: Option<bool>,
+    /// Collection to search instead of the configured one
+    pub collection: Option<String>,
+    /// Folders the caller may read; hits elsewhere are dropped
+    pub allowed_folders: Option<Vec<String>>,
+    /// Attach a document list, matched headings and (optionally) an LLM
+    /// description to each folder result
+    pub folder_summary: Option<bool>,
+    /// "fast" | "accurate"; only used when embedding tiers are enabled
+    pub tier: Option<String>,
+    /// "score" | "modified" | "created" | "path" | "entry_date"
+    pub sort_by: Option<String>,
+    /// "asc" | "desc"; defaults to A to Z for paths, newest first for dates
+    pub sort_order: Option<String>,
+    /// "link" | "footnote": fill each result's `citation`
+    pub citation: Option<String>,
 }
 
-impl From<SearchOptions> for RustSearchOptions {
-    fn from(opts: SearchOptions) -> Self {
-        use opencontext_core::search::{AggregateBy, SearchMode};
+impl TryFrom<SearchOptions> for RustSearchOptions {
+    type Error = napi::Error;
+
+    fn try_from(opts: SearchOptions) -> Result<Self> {
+        use opencontext_core::search::{
+            AggregateBy, CitationStyle, EmbeddingTier, Filter, GroupBy, SearchMode, SortBy,
+            SortOrder,
+        };
 
         let mode = opts.mode.as_deref().map(|s| match s {
             "vector" => SearchMode::Vector,
             "keyword" => SearchMode::Keyword,
+            "auto" => SearchMode::Auto,
             _ => SearchMode::Hybrid,
         });
 
@@ -330,25 +1173,91 @@ impl From<SearchOptions> for RustSearchOptions {
             _ => AggregateBy::Doc,
         });
 
-        RustSearchOptions {
+        let tier = opts.tier.as_deref().map(|s| match s {
+            "fast" => EmbeddingTier::Fast,
+            _ => EmbeddingTier::Accurate,
+        });
+
+        let group_by = opts.group_by.as_deref().map(|s| match s {
+            "doc" => GroupBy::Doc,
+            _ => GroupBy::None,
+        });
+
+        let sort_by = opts.sort_by.as_deref().map(|s| match s {
+            "modified" => SortBy::Modified,
+            "created" => SortBy::Created,
+            "path" => SortBy::Path,
+            "entry_date" | "entryDate" => SortBy::EntryDate,
+            _ => SortBy::Score,
+        });
+
+        let sort_order = opts.sort_order.as_deref().map(|s| match s {
+            "asc" => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        });
+
+        let citation = opts.citation.as_deref().map(|s| match s {
+            "footnote" => CitationStyle::Footnote,
+            _ => CitationStyle::Link,
+        });
+
+        let mut filter = opts
+            .filter
+            .as_deref()
+            .filter(|f| !f.trim().is_empty())
+            .map(Filter::parse)
+            .transpose()
+            .map_err(search_error_to_napi)?;
+        if let Some(doc_type) = opts.doc_type {
+            let by_type = Filter::DocType(doc_type);
+            filter = Some(match filter {
+                Some(filter) => filter.and(by_type),
+                None => by_type,
+            });
+        }
+
+        Ok(RustSearchOptions {
             query: opts.query,
             limit: opts.limit.map(|v| v as usize),
             mode,
             aggregate_by,
-            doc_type: opts.doc_type,
+            filter,
             folder_filter: opts.folder_filter,
+            include_sources: opts.include_sources,
+            exclude_sources: opts.exclude_sources,
             min_score: opts.min_score.map(|v| v as f32),
             date_from: opts.date_from,
             date_to: opts.date_to,
             include_neighbors: opts.include_neighbors.map(|v| v as usize),
-        }
+            group_by,
+            group_children: opts.group_children.map(|v| v as usize),
+            facets: opts.facets,
+            fields: opts.fields,
+            timeout_ms: opts.timeout_ms.map(u64::from),
+            cross_language: opts.cross_language,
+            collection: opts.collection,
+            allowed_folders: opts.allowed_folders,
+            folder_summary: opts.folder_summary,
+            tier,
+            sort_by,
+            sort_order,
+            citation,
+        })
     }
 }
 
-/// Searcher - async search executor
+/// Text used for one query only, e.g. the unsaved document in the editor
+#[napi(object)]
+pub struct ContextDoc {
+    /// Workspace path, if the text is (a draft of) a workspace document
+    pub path: Option<String>,
+    pub content: String,
+}
+
+/// Searcher - async search executor, shared by concurrent queries
 #[napi]
 pub struct Searcher {
-    inner: Arc<Mutex<RustSearcher>>,
+    inner: Arc<RustSearcher>,
 }
 
 #[napi]
@@ -357,20 +1266,27 @@ impl Searcher {
     /// contexts_root is optional - if not provided, uses default from environment
     #[napi(factory)]
     pub async fn create() -> Result<Searcher> {
-        let config = SearchConfig::load().map_err(search_error_to_napi)?;
+        let mut config = SearchConfig::load().map_err(search_error_to_napi)?;
+        if let Ok(oc_ctx) = ctx() {
+            config
+                .paths
+                .contexts_root
+                .get_or_insert_with(|| PathBuf::from(&oc_ctx.env_info().contexts_root));
+        }
         let searcher = RustSearcher::new(config)
             .await
-            .map_err(search_error_to_napi)?;
+            .map_err(search_error_to_napi)?
+            .with_event_bus(EVENT_BUS.clone());
         Ok(Searcher {
-            inner: Arc::new(Mutex::new(searcher)),
+            inner: Arc::new(searcher),
         })
     }
 
     /// Execute a search query
     #[napi]
     pub async fn search(&self, options: SearchOptions) -> Result<serde_json::Value> {
-        let rust_options: RustSearchOptions = options.into();
-        let searcher = self.inner.lock().await;
+        let rust_options = RustSearchOptions::try_from(options)?;
+        let searcher = &self.inner;
         let results = searcher
             .search(rust_options)
             .await
@@ -378,12 +1294,178 @@ impl Searcher {
 
         serde_json::to_value(&results).map_err(|e| napi::Error::from_reason(e.to_string()))
     }
+
+    /// Execute a search query, also ranking by similarity to `context_docs`,
+    /// which are embedded for this query and not stored
+    #[napi]
+    pub async fn search_with_context(
+        &self,
+        options: SearchOptions,
+        context_docs: Vec<ContextDoc>,
+    ) -> Result<serde_json::Value> {
+        let rust_options = RustSearchOptions::try_from(options)?;
+        let docs = context_docs
+            .into_iter()
+            .map(|d| RustContextDoc {
+                path: d.path,
+                content: d.content,
+            })
+            .collect();
+        let searcher = &self.inner;
+        let results = searcher
+            .search_with_context(rust_options, docs)
+            .await
+            .map_err(search_error_to_napi)?;
+
+        serde_json::to_value(&results).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    /// Open an empty in-memory index for session-scoped texts, sharing this
+    /// searcher's embedding client
+    #[napi]
+    pub async fn ephemeral(&self) -> Result<EphemeralIndex> {
+        let searcher = &self.inner;
+        Ok(EphemeralIndex {
+            inner: Arc::new(Mutex::new(searcher.ephemeral())),
+        })
+    }
+
+    /// Autocomplete a partially typed query from indexed titles, headings and tags
+    #[napi]
+    pub async fn suggest(&self, prefix: String, limit: Option<u32>) -> Result<serde_json::Value> {
+        let searcher = &self.inner;
+        let suggestions = searcher
+            .suggest(&prefix, limit.unwrap_or(10) as usize)
+            .map_err(search_error_to_napi)?;
+
+        serde_json::to_value(&suggestions).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    /// Preload indexes (and optionally ping the embedding API); returns a readiness report
+    #[napi]
+    pub async fn warm_up(&self, ping_embedding: Option<bool>) -> Result<serde_json::Value> {
+        let searcher = &self.inner;
+        let readiness = searcher.warm_up(ping_embedding.unwrap_or(false)).await;
+
+        serde_json::to_value(&readiness).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    /// Memory held by the searcher's caches, per component of the `[memory]` budget
+    #[napi]
+    pub async fn memory_usage(&self) -> Result<serde_json::Value> {
+        let searcher = &self.inner;
+        let report = searcher.memory_usage();
+
+        serde_json::to_value(&report).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    /// Reopen the indexes after a rebuild or clean; searches keep running meanwhile
+    #[napi]
+    pub async fn refresh(&self) -> Result<()> {
+        self.inner.refresh().await.map_err(search_error_to_napi)
+    }
+}
+
+/// EphemeralIndex - in-memory index of texts added during a session
+/// (a transcript, pasted notes); nothing is written to disk
+#[napi]
+pub struct EphemeralIndex {
+    inner: Arc<Mutex<RustEphemeralIndex>>,
+}
+
+#[napi]
+impl EphemeralIndex {
+    /// Chunk, embed and add `content` as `source`, replacing any earlier
+    /// text with that name; returns the number of chunks added
+    #[napi]
+    pub async fn add(&self, source: String, content: String) -> Result<u32> {
+        let mut index = self.inner.lock().await;
+        let added = index
+            .add(&source, &content)
+            .await
+            .map_err(search_error_to_napi)?;
+        Ok(added as u32)
+    }
+
+    /// Best chunks for `query` across the added texts
+    #[napi]
+    pub async fn search(&self, query: String, limit: Option<u32>) -> Result<serde_json::Value> {
+        let index = self.inner.lock().await;
+        let hits = index
+            .search(&query, limit.unwrap_or(5) as usize)
+            .await
+            .map_err(search_error_to_napi)?;
+
+        serde_json::to_value(&hits).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    /// Drop the text added as `source`; returns the number of chunks removed
+    #[napi]
+    pub async fn remove(&self, source: String) -> u32 {
+        self.inner.lock().await.remove(&source) as u32
+    }
+
+    /// Drop every added text
+    #[napi]
+    pub async fn clear(&self) {
+        self.inner.lock().await.clear();
+    }
+
+    /// Names of the added texts, oldest first
+    #[napi]
+    pub async fn sources(&self) -> Vec<String> {
+        self.inner.lock().await.sources()
+    }
+}
+
+/// FederatedSearcher - one query over the local workspace and every
+/// workspace under `[federation]`, with results labelled by workspace
+#[napi]
+pub struct FederatedSearcher {
+    inner: Arc<RustFederatedSearcher>,
+}
+
+#[napi]
+impl FederatedSearcher {
+    /// Open every configured workspace; ones that fail to open are reported
+    /// in each result's `workspaces` instead of failing here
+    #[napi(factory)]
+    pub async fn create() -> Result<FederatedSearcher> {
+        let config = SearchConfig::load().map_err(search_error_to_napi)?;
+        let searcher = RustFederatedSearcher::new(config)
+            .await
+            .map_err(search_error_to_napi)?;
+        Ok(FederatedSearcher {
+            inner: Arc::new(searcher),
+        })
+    }
+
+    /// Search all workspaces concurrently and merge by weighted rank
+    #[napi]
+    pub async fn search(&self, options: SearchOptions) -> Result<serde_json::Value> {
+        let results = self
+            .inner
+            .search(RustSearchOptions::try_from(options)?)
+            .await
+            .map_err(search_error_to_napi)?;
+
+        serde_json::to_value(&results).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    /// Names of the federated workspaces, local first
+    #[napi]
+    pub fn workspaces(&self) -> Vec<String> {
+        self.inner.workspaces()
+    }
 }
 
 /// Indexer - async index builder
 #[napi]
 pub struct Indexer {
     inner: Arc<Mutex<RustIndexer>>,
+    // Token for the build in progress; kept outside `inner` so cancel()
+    // doesn't wait on the lock the build holds
+    cancel: Arc<std::sync::Mutex<CancellationToken>>,
 }
 
 #[napi]
@@ -402,9 +1484,27 @@ impl Indexer {
             .map_err(search_error_to_napi)?;
         Ok(Indexer {
             inner: Arc::new(Mutex::new(indexer)),
+            cancel: Arc::new(std::sync::Mutex::new(CancellationToken::new())),
         })
     }
 
+    /// Stop the build in progress after its current batch. Written batches
+    /// are kept and checkpointed, so the next build resumes from there.
+    #[napi]
+    pub fn cancel(&self) {
+        if let Ok(token) = self.cancel.lock() {
+            token.cancel();
+        }
+    }
+
+    fn fresh_cancellation(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Ok(mut current) = self.cancel.lock() {
+            *current = token.clone();
+        }
+        token
+    }
+
     /// Build index with real-time progress callback.
     /// Returns a Promise. Callback receives IndexProgress as a JS object.
     #[napi]
@@ -426,6 +1526,7 @@ impl Indexer {
             })?;
 
         let inner = self.inner.clone();
+        let cancel = self.fresh_cancellation();
 
         env.execute_tokio_future(
             async move {
@@ -440,6 +1541,7 @@ impl Indexer {
                 }
 
                 let mut indexer = inner.lock().await;
+                indexer.set_cancellation(cancel);
                 let stats = indexer
                     .build_smart(all_docs, force.unwrap_or(false), move |progress| {
                         if let Ok(value) = serde_json::to_value(&progress) {
@@ -448,6 +1550,9 @@ impl Indexer {
                     })
                     .await
                     .map_err(search_error_to_napi)?;
+                if !stats.interrupted {
+                    oc_ctx.mark_indexed(None).map_err(to_napi_error)?;
+                }
 
                 serde_json::to_value(&stats).map_err(|e| napi::Error::from_reason(e.to_string()))
             },
@@ -470,11 +1575,16 @@ impl Indexer {
             all_docs.extend(docs);
         }
 
+        let cancel = self.fresh_cancellation();
         let mut indexer = self.inner.lock().await;
+        indexer.set_cancellation(cancel);
         let stats = indexer
             .build_smart(all_docs, force.unwrap_or(false), |_| {})
             .await
             .map_err(search_error_to_napi)?;
+        if !stats.interrupted {
+            oc_ctx.mark_indexed(None).map_err(to_napi_error)?;
+        }
 
         serde_json::to_value(&stats).map_err(|e| napi::Error::from_reason(e.to_string()))
     }
@@ -488,12 +1598,20 @@ impl Indexer {
     ) -> Result<serde_json::Value> {
         let oc_ctx = ctx()?;
         let docs = oc_ctx.list_docs(&folder, true).map_err(to_napi_error)?;
+        let rel_paths: Vec<String> = docs.iter().map(|d| d.rel_path.clone()).collect();
 
+        let cancel = self.fresh_cancellation();
         let mut indexer = self.inner.lock().await;
+        indexer.set_cancellation(cancel);
         let stats = indexer
             .build_smart(docs, force.unwrap_or(false), |_| {})
             .await
             .map_err(search_error_to_napi)?;
+        if !stats.interrupted {
+            oc_ctx
+                .mark_indexed(Some(&rel_paths))
+                .map_err(to_napi_error)?;
+        }
 
         serde_json::to_value(&stats).map_err(|e| napi::Error::from_reason(e.to_string()))
     }
@@ -520,6 +1638,58 @@ impl Indexer {
         Ok(())
     }
 
+    /// Re-index only the files `index_status` reports as new, modified,
+    /// deleted or renamed
+    #[napi]
+    pub async fn update_dirty(&self) -> Result<serde_json::Value> {
+        let oc_ctx = ctx()?;
+        let status = oc_ctx.index_status().map_err(to_napi_error)?;
+
+        let mut indexer = self.inner.lock().await;
+        let mut touched = Vec::new();
+        let mut chunks = 0;
+        for rel_path in status.new.iter().chain(&status.modified) {
+            chunks += indexer
+                .index_file(rel_path)
+                .await
+                .map_err(search_error_to_napi)?;
+            touched.push(rel_path.clone());
+        }
+        for rel_path in &status.deleted {
+            indexer
+                .remove_file(rel_path)
+                .await
+                .map_err(search_error_to_napi)?;
+            touched.push(rel_path.clone());
+        }
+        for renamed in &status.renamed {
+            indexer
+                .update_file_path(&renamed.old_path, &renamed.new_path)
+                .await
+                .map_err(search_error_to_napi)?;
+            touched.push(renamed.old_path.clone());
+            touched.push(renamed.new_path.clone());
+        }
+        if !touched.is_empty() {
+            indexer
+                .update_metadata()
+                .await
+                .map_err(search_error_to_napi)?;
+        }
+        if status.recorded {
+            oc_ctx.mark_indexed(Some(&touched)).map_err(to_napi_error)?;
+        } else {
+            oc_ctx.mark_indexed(None).map_err(to_napi_error)?;
+        }
+
+        Ok(serde_json::json!({
+            "indexed": status.new.len() + status.modified.len(),
+            "removed": status.deleted.len(),
+            "renamed": status.renamed.len(),
+            "chunks": chunks,
+        }))
+    }
+
     /// Check if index exists
     #[napi]
     pub async fn index_exists(&self) -> Result<bool> {
@@ -552,6 +1722,73 @@ impl Indexer {
     }
 }
 
+#[napi(object)]
+pub struct PlanIndexOptions {
+    /// Plan a full rebuild
+    pub force: Option<bool>,
+    /// Only docs in this folder
+    pub folder: Option<String>,
+    /// Plan `update --dirty` instead of a build
+    pub dirty: Option<bool>,
+}
+
+/// What an index build or dirty update would chunk, embed and delete,
+/// without touching the index
+#[napi]
+pub fn plan_index(options: PlanIndexOptions) -> Result<serde_json::Value> {
+    use opencontext_core::search::{plan_build, plan_files};
+
+    let oc_ctx = ctx()?;
+    let config = SearchConfig::load().map_err(search_error_to_napi)?;
+    let plan = if options.dirty.unwrap_or(false) {
+        let status = oc_ctx.index_status().map_err(to_napi_error)?;
+        let mut embed = status.new;
+        embed.extend(status.modified);
+        embed.extend(status.renamed.iter().map(|r| r.new_path.clone()));
+        let mut delete = status.deleted;
+        delete.extend(status.renamed.into_iter().map(|r| r.old_path));
+        let contexts_root = PathBuf::from(&oc_ctx.env_info().contexts_root);
+        plan_files(&config, &contexts_root, &embed, &delete)
+    } else {
+        let docs = match options.folder.as_deref() {
+            Some(folder) => oc_ctx.list_docs(folder, true).map_err(to_napi_error)?,
+            None => {
+                let mut all_docs = Vec::new();
+                for folder in oc_ctx.list_folders(true).map_err(to_napi_error)? {
+                    all_docs.extend(
+                        oc_ctx
+                            .list_docs(&folder.rel_path, true)
+                            .map_err(to_napi_error)?,
+                    );
+                }
+                all_docs
+            }
+        };
+        plan_build(&config, &docs, options.force.unwrap_or(false))
+    };
+
+    serde_json::to_value(&plan).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// Chunk, token and cost estimate for embedding `paths` (files or folders;
+/// none means the whole workspace)
+#[napi]
+pub fn estimate_index_cost(paths: Option<Vec<String>>) -> Result<serde_json::Value> {
+    let oc_ctx = ctx()?;
+    let config = SearchConfig::load().map_err(search_error_to_napi)?;
+    let contexts_root = PathBuf::from(&oc_ctx.env_info().contexts_root);
+    let paths = paths.unwrap_or_else(|| vec![String::new()]);
+    let estimate = opencontext_core::search::estimate_index_cost(
+        &config,
+        &contexts_root,
+        oc_ctx.symlink_policy(),
+        &paths,
+    )
+    .map_err(to_napi_error)?;
+
+    serde_json::to_value(&estimate).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
 /// Load search config
 #[napi]
 pub fn load_search_config() -> Result<serde_json::Value> {
@@ -560,8 +1797,132 @@ pub fn load_search_config() -> Result<serde_json::Value> {
     serde_json::to_value(&config).map_err(|e| napi::Error::from_reason(e.to_string()))
 }
 
+/// JSON Schema of the search options, search results and event messages,
+/// keyed by type name
+#[napi]
+pub fn api_schema() -> serde_json::Value {
+    serde_json::to_value(opencontext_core::search::json_schemas()).unwrap_or_default()
+}
+
+/// Read the most recent slow-query log entries, newest first
+#[napi]
+pub fn read_slow_query_log(limit: Option<u32>) -> Result<serde_json::Value> {
+    let config = SearchConfig::load().map_err(search_error_to_napi)?;
+    let entries = opencontext_core::search::read_slow_queries(
+        &config.paths.get_slow_query_log_path(),
+        limit.unwrap_or(20) as usize,
+    );
+
+    serde_json::to_value(&entries).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+#[napi(object)]
+pub struct DigestOptions {
+    /// Window start: `7d`, `12h`, `2w`, `YYYY-MM-DD` or an RFC 3339 timestamp
+    pub since: String,
+    /// Folder the note is saved in and left out of the digest (default `digests`)
+    pub folder: Option<String>,
+    /// Add a summary written by the `[llm]` model
+    pub summarize: Option<bool>,
+    /// Save the note into the workspace (default true)
+    pub write: Option<bool>,
+    /// Add this many old notes due for resurfacing
+    pub resurface: Option<u32>,
+    /// Only resurface notes untouched for this many days (default 90)
+    pub resurface_days: Option<u32>,
+}
+
+/// Collect recent activity into a digest note
+#[napi]
+pub async fn create_digest(options: DigestOptions) -> Result<serde_json::Value> {
+    use opencontext_core::digest;
+
+    let ctx = ctx()?;
+    let folder = options.folder.as_deref().unwrap_or("digests");
+    let mut digest = convert(ctx.collect_digest(&options.since, Some(folder)))?;
+
+    let summary = if options.summarize.unwrap_or(false) && !digest.is_empty() {
+        let config = SearchConfig::load().map_err(search_error_to_napi)?;
+        Some(
+            digest::summarize(&digest, &config)
+                .await
+                .map_err(search_error_to_napi)?,
+        )
+    } else {
+        None
+    };
+    if let Some(count) = options.resurface.filter(|c| *c > 0) {
+        let write = options.write.unwrap_or(true);
+        digest.resurfaced = convert(ctx.resurface(
+            &opencontext_core::resurface::ResurfaceOptions {
+                count: Some(count as usize),
+                min_age_days: options.resurface_days.map(i64::from),
+                exclude_folder: Some(folder.to_string()),
+                // A preview shouldn't use up today's picks
+                dry_run: !write,
+                ..Default::default()
+            },
+        ))?;
+    }
+    let saved = if options.write.unwrap_or(true) {
+        Some(convert(ctx.save_digest(
+            &digest,
+            summary.as_deref(),
+            folder,
+        ))?)
+    } else {
+        None
+    };
+
+    Ok(serde_json::json!({
+        "markdown": digest.to_markdown(summary.as_deref()),
+        "digest": digest,
+        "summary": summary,
+        "saved": saved,
+    }))
+}
+
 // ==================== Index Sync Service ====================
 
+/// Call `callback` with every event on the bus (document, folder and
+/// search health events) as `{ schema_version, scope, event }`
+///
+/// The subscription lasts for the life of the process and does not keep it
+/// alive.
+#[napi(ts_args_type = "callback: (message: any) => void")]
+pub fn on_event(env: Env, callback: napi::JsFunction) -> Result<()> {
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use opencontext_core::events::EventMessage;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut tsfn: ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal> = callback
+        .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<serde_json::Value>| {
+            let js_val = ctx.env.to_js_value(&ctx.value)?;
+            Ok(vec![js_val])
+        })?;
+    tsfn.unref(&env)?;
+
+    let mut receiver = EVENT_BUS.subscribe();
+    napi::bindgen_prelude::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Ok(value) = serde_json::to_value(EventMessage::from(event)) {
+                        tsfn.call(value, ThreadsafeFunctionCallMode::NonBlocking);
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    log::warn!("[Events] JS listener missed {} events", n);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(())
+}
+
 /// Start the index sync service
 ///
 /// This service listens to document/folder events and automatically
@@ -612,10 +1973,16 @@ pub async fn start_index_sync(interval_secs: Option<u32>) -> Result<bool> {
 
     let interval = interval_secs.unwrap_or(300) as u64;
     let flush_notify = FLUSH_NOTIFY.clone();
-    let sync_service = IndexSyncService::new(config, contexts_root)
-        .with_interval(interval)
-        .with_db_path(db_path)
-        .with_flush_notify(flush_notify);
+    let sync_service = Arc::new(
+        IndexSyncService::new(config, contexts_root)
+            .with_interval(interval)
+            .with_db_path(db_path)
+            .with_symlink_policy(oc_ctx.symlink_policy())
+            .with_flush_notify(flush_notify),
+    );
+    if let Ok(mut slot) = SYNC_SERVICE.lock() {
+        *slot = Some(sync_service.clone());
+    }
 
     let event_bus = EVENT_BUS.clone();
 
@@ -624,6 +1991,9 @@ pub async fn start_index_sync(interval_secs: Option<u32>) -> Result<bool> {
         if let Err(e) = sync_service.start(event_bus).await {
             log::error!("[IndexSync] Service error: {}", e);
         }
+        if let Ok(mut slot) = SYNC_SERVICE.lock() {
+            *slot = None;
+        }
         INDEX_SYNC_RUNNING.store(false, Ordering::SeqCst);
         let _ = std::fs::remove_file(&lock_path);
     });
@@ -631,6 +2001,32 @@ pub async fn start_index_sync(interval_secs: Option<u32>) -> Result<bool> {
     Ok(true)
 }
 
+/// Stop the index sync service, flushing pending updates first
+///
+/// Updates that cannot be applied are journaled to the WAL and replayed by
+/// the next `start_index_sync`.
+///
+/// @param timeout_ms - How long to wait for the flush (default: 10000)
+/// @returns true once the service has stopped, false on timeout
+#[napi]
+pub async fn stop_index_sync(timeout_ms: Option<u32>) -> Result<bool> {
+    let service = SYNC_SERVICE.lock().ok().and_then(|slot| slot.clone());
+    let Some(service) = service else {
+        return Ok(!INDEX_SYNC_RUNNING.load(Ordering::SeqCst));
+    };
+    service.stop();
+
+    let deadline = std::time::Instant::now()
+        + std::time::Duration::from_millis(timeout_ms.unwrap_or(10_000) as u64);
+    while INDEX_SYNC_RUNNING.load(Ordering::SeqCst) {
+        if std::time::Instant::now() >= deadline {
+            return Ok(false);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    Ok(true)
+}
+
 /// Check if index sync service is running
 #[napi]
 pub fn is_index_sync_running() -> bool {
@@ -655,3 +2051,79 @@ pub fn get_index_sync_status() -> serde_json::Value {
         "running": INDEX_SYNC_RUNNING.load(Ordering::SeqCst),
     })
 }
+
+// ==================== Sources ====================
+
+// Running source scheduler, kept so it can be stopped on shutdown
+static SOURCE_SCHEDULER: Lazy<std::sync::Mutex<Option<Arc<SourceScheduler>>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+/// Sync the sources configured under `[sources]` once
+///
+/// @param only - Only sources whose id starts with this, e.g. `github:owner/name`
+#[napi]
+pub async fn sync_sources(only: Option<String>) -> Result<serde_json::Value> {
+    let config = SearchConfig::load().map_err(search_error_to_napi)?.sources;
+    let reports =
+        convert(opencontext_core::sources::sync_sources(ctx()?, &config, only.as_deref()).await)?;
+    serde_json::to_value(&reports).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// Cursor, time and error of the last sync of every source
+#[napi]
+pub fn source_states(env: Env) -> NapiResult<JsUnknown> {
+    let states = convert(ctx()?.source_states())?;
+    to_js(env, &states)
+}
+
+/// Store a source token in the system keyring
+///
+/// @param account - What the token is for, e.g. `jira` or `linear`
+#[napi]
+pub fn set_keyring_token(account: String, token: String) -> NapiResult<()> {
+    convert(opencontext_core::keyring::set(&account, &token))
+}
+
+/// Sync the configured sources now and then every `[sources] interval_minutes`
+///
+/// @returns true if started, false if already running or no source is configured
+#[napi]
+pub async fn start_source_sync() -> Result<bool> {
+    let config = SearchConfig::load().map_err(search_error_to_napi)?.sources;
+    let scheduler = Arc::new(SourceScheduler::new(config));
+    if !scheduler.has_sources() {
+        return Ok(false);
+    }
+    let ctx = ctx()?;
+    {
+        let mut slot = SOURCE_SCHEDULER
+            .lock()
+            .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        if slot.is_some() {
+            return Ok(false);
+        }
+        *slot = Some(scheduler.clone());
+    }
+    tokio::spawn(async move {
+        scheduler.start(ctx).await;
+        if let Ok(mut slot) = SOURCE_SCHEDULER.lock() {
+            *slot = None;
+        }
+    });
+    Ok(true)
+}
+
+/// Stop the source scheduler once the sync in progress (if any) is done
+///
+/// @returns false if it was not running
+#[napi]
+pub fn stop_source_sync() -> bool {
+    let scheduler = SOURCE_SCHEDULER.lock().ok().and_then(|slot| slot.clone());
+    match scheduler {
+        Some(scheduler) => {
+            scheduler.stop();
+            true
+        }
+        None => false,
+    }
+}
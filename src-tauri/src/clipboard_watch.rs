@@ -0,0 +1,74 @@
+//! Opt-in clipboard watcher (`[clipboard]` in config.toml): copied text that
+//! passes the configured rules is captured into the day's inbox note.
+
+use crate::utils::map_err;
+use crate::AppState;
+use opencontext_core::capture::{CaptureSource, ClipboardFilter};
+use opencontext_core::search::ClipboardConfig;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Poll the clipboard on a background thread for the lifetime of the app
+pub(crate) fn spawn<R: Runtime>(app: AppHandle<R>, config: ClipboardConfig) {
+    std::thread::spawn(move || {
+        let mut filter = ClipboardFilter::new(config.min_length, config.max_length, &config.apps);
+        // Whatever was copied before the app started is not part of this session
+        if let Ok(text) = app.clipboard().read_text() {
+            filter.skip(&text);
+        }
+        let interval = Duration::from_millis(config.poll_ms.max(100));
+        log::info!("[Clipboard] Watching the clipboard every {:?}", interval);
+
+        loop {
+            std::thread::sleep(interval);
+            let Ok(text) = app.clipboard().read_text() else {
+                continue;
+            };
+            // Only ask for the frontmost app (a process spawn) when the text changed
+            if !filter.is_new(&text) {
+                continue;
+            }
+            let app_name = if filter.checks_app() {
+                frontmost_app()
+            } else {
+                None
+            };
+            if !filter.accept(&text, app_name.as_deref()) {
+                continue;
+            }
+            let state = app.state::<AppState>();
+            let captured = state.ctx.lock().map_err(map_err).and_then(|ctx| {
+                ctx.capture(&text, CaptureSource::Clipboard, None)
+                    .map_err(map_err)
+            });
+            match captured {
+                Ok(captured) => log::info!("[Clipboard] Captured into {}", captured.rel_path),
+                Err(e) => log::warn!("[Clipboard] Capture failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Name of the application in front, which is where the copy most likely
+/// happened; `None` where it cannot be found out
+fn frontmost_app() -> Option<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args([
+                "-e",
+                "tell application \"System Events\" to get name of first application process whose frontmost is true",
+            ])
+            .output()
+    } else if cfg!(target_os = "linux") {
+        Command::new("xdotool")
+            .args(["getactivewindow", "getwindowclassname"])
+            .output()
+    } else {
+        return None;
+    };
+    let output = output.ok().filter(|o| o.status.success())?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
@@ -222,6 +222,7 @@ pub(crate) struct SaveDocOptions {
     path: String,
     content: String,
     description: Option<String>,
+    expected_revision: Option<String>,
 }
 
 #[tauri::command]
@@ -230,13 +231,20 @@ pub(crate) fn save_doc_content(
     options: SaveDocOptions,
 ) -> CmdResult<serde_json::Value> {
     let ctx = state.ctx.lock().map_err(map_err)?;
-    let doc = ctx
-        .save_doc_content(
+    let doc = match options.expected_revision.as_deref() {
+        Some(expected) => ctx.save_doc_content_if_unchanged(
             &options.path,
             &options.content,
             options.description.as_deref(),
-        )
-        .map_err(map_err)?;
+            expected,
+        ),
+        None => ctx.save_doc_content(
+            &options.path,
+            &options.content,
+            options.description.as_deref(),
+        ),
+    }
+    .map_err(map_err)?;
     serde_json::to_value(&doc).map_err(map_err)
 }
 
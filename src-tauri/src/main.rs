@@ -3,6 +3,7 @@
 
 mod agent_rpc;
 mod chat;
+mod clipboard_watch;
 mod commands;
 mod terminal_session;
 mod utils;
@@ -22,7 +23,7 @@ use tauri::image::Image;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
 use tauri::{Manager, RunEvent, WindowEvent};
-use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::{Mutex as AsyncMutex, Notify};
 
 struct AppState {
     ctx: Mutex<OpenContext>,
@@ -72,6 +73,7 @@ fn main() {
     let sync_event_bus = event_bus.clone();
     let sync_config = search_config.clone();
     let sync_contexts_root = contexts_root.clone();
+    let clipboard_config = search_config.clipboard.clone();
 
     let allow_close = Arc::new(AtomicBool::new(false));
     let allow_close_for_setup = allow_close.clone();
@@ -254,12 +256,18 @@ fn main() {
             // Start index sync service in background
             // Use tauri::async_runtime::spawn which works with Tauri's runtime management
             tauri::async_runtime::spawn(async move {
-                let sync_service = IndexSyncService::new(sync_config, sync_contexts_root);
+                // The notify lets captures skip the batch interval
+                let sync_service = IndexSyncService::new(sync_config, sync_contexts_root)
+                    .with_flush_notify(Arc::new(Notify::new()));
                 if let Err(e) = sync_service.start(sync_event_bus).await {
                     log::error!("[IndexSync] Service error: {}", e);
                 }
             });
 
+            if clipboard_config.enabled {
+                clipboard_watch::spawn(app_handle.clone(), clipboard_config);
+            }
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -0,0 +1,99 @@
+/**
+ * Folder access control for shared deployments.
+ *
+ * Rules live in acl.json (see crates/opencontext-core/src/acl.rs). The native
+ * core resolves a token to the folders it may read; the helpers here apply
+ * that to listings and document reads. Search hits are filtered natively via
+ * the `allowedFolders` search option.
+ */
+
+const native = require('./native');
+
+const FULL_ACCESS = { kind: 'all' };
+
+/** Raised when a token may not read a path; surfaced as "not found". */
+class AccessDeniedError extends Error {
+  constructor(relPath) {
+    super(`Document or folder not found: ${relPath}`);
+    this.name = 'AccessDeniedError';
+    this.code = 'ACCESS_DENIED';
+  }
+}
+
+/**
+ * Resolve the folders a token may read
+ * @param {string|undefined} token
+ * @returns {{ kind: 'all' } | { kind: 'folders', folders: string[] } | null} null if refused
+ */
+function resolveAccess(token) {
+  const binding = native.get();
+  if (typeof binding.resolveAccess !== 'function') return FULL_ACCESS;
+  return binding.resolveAccess(token || null);
+}
+
+/**
+ * Normalize a request path the way the core's RelPath does (`\` to `/`,
+ * empty and `.` segments dropped). `null` if it has a `..` segment, which
+ * could climb out of an allowed folder.
+ */
+function normalizePath(p) {
+  const segments = String(p || '')
+    .trim()
+    .split(/[\\/]/)
+    .filter((s) => s !== '' && s !== '.');
+  return segments.includes('..') ? null : segments.join('/');
+}
+
+function within(p, folder) {
+  return p === folder || p.startsWith(`${folder}/`);
+}
+
+/** Whether the caller is unrestricted, as workspace settings and index maintenance require */
+function isFullAccess(access) {
+  return !access || access.kind === 'all';
+}
+
+/** Whether a doc or folder path is readable */
+function allows(access, relPath) {
+  const p = normalizePath(relPath);
+  if (p === null) return false;
+  if (!access || access.kind === 'all') return true;
+  return access.folders.some((f) => within(p, f));
+}
+
+/** Whether a folder is readable or leads to a readable folder */
+function showsFolder(access, relPath) {
+  const p = normalizePath(relPath);
+  if (p === null) return false;
+  if (!access || access.kind === 'all') return true;
+  return p === '' || access.folders.some((f) => within(p, f) || within(f, p));
+}
+
+function assertAllowed(access, relPath) {
+  if (!allows(access, relPath)) throw new AccessDeniedError(relPath);
+}
+
+function filterFolders(access, folders) {
+  return folders.filter((f) => showsFolder(access, f.rel_path));
+}
+
+function filterDocs(access, docs) {
+  return docs.filter((d) => allows(access, d.rel_path));
+}
+
+/** `allowedFolders` search option for this access (undefined = unrestricted) */
+function searchFolders(access) {
+  return access && access.kind === 'folders' ? access.folders : undefined;
+}
+
+module.exports = {
+  AccessDeniedError,
+  resolveAccess,
+  isFullAccess,
+  allows,
+  showsFolder,
+  assertAllowed,
+  filterFolders,
+  filterDocs,
+  searchFolders,
+};
@@ -57,6 +57,16 @@ const CONFIG_KEYS = {
     envVar: 'AI_MODEL',
     default: 'gpt-4o'
   },
+  REMOTE_URL: {
+    description: 'URL of another OpenContext instance (oc ui) to send searches to, e.g. http://homeserver:3222',
+    sensitive: false,
+    envVar: 'OPENCONTEXT_REMOTE_URL'
+  },
+  REMOTE_TOKEN: {
+    description: 'API token for REMOTE_URL',
+    sensitive: true,
+    envVar: 'OPENCONTEXT_REMOTE_TOKEN'
+  },
   AI_PROMPT: {
     description: 'Custom system prompt for AI reflections',
     sensitive: false,
@@ -11,6 +11,15 @@ const store = require('./store-native');
 
 const CACHE_TTL_MS = 30_000; // 30 s
 
+/** Thrown for any mutation while the workspace is in read-only mode. */
+class ReadOnlyError extends Error {
+  constructor(action) {
+    super(`workspace is read-only: cannot ${action}`);
+    this.name = 'ReadOnlyError';
+    this.code = 'READ_ONLY';
+  }
+}
+
 class DataService {
   constructor() {
     /** @type {Map<string, { value: any, expiresAt: number }>} */
@@ -38,6 +47,17 @@ class DataService {
     this._cache.clear();
   }
 
+  /** Reject a write before it reaches the native store. */
+  _assertWritable(action) {
+    if (this.isReadOnly()) throw new ReadOnlyError(action);
+  }
+
+  /** Read-only mode is fixed at startup (OPENCONTEXT_READ_ONLY). */
+  isReadOnly() {
+    if (this._readOnly === undefined) this._readOnly = store.isReadOnly();
+    return this._readOnly;
+  }
+
   // ── Environment ───────────────────────────────────────────────────────────
 
   initEnvironment() {
@@ -56,24 +76,28 @@ class DataService {
   }
 
   createFolder(options) {
+    this._assertWritable('create folders');
     const result = store.createFolder(options);
     this._invalidate();
     return result;
   }
 
   renameFolder(options) {
+    this._assertWritable('rename folders');
     const result = store.renameFolder(options);
     this._invalidate();
     return result;
   }
 
   moveFolder(options) {
+    this._assertWritable('move folders');
     const result = store.moveFolder(options);
     this._invalidate();
     return result;
   }
 
   removeFolder(options) {
+    this._assertWritable('remove folders');
     const result = store.removeFolder(options);
     this._invalidate();
     return result;
@@ -91,30 +115,35 @@ class DataService {
   }
 
   createDoc(options) {
+    this._assertWritable('create documents');
     const result = store.createDoc(options);
     this._invalidate();
     return result;
   }
 
   moveDoc(options) {
+    this._assertWritable('move documents');
     const result = store.moveDoc(options);
     this._invalidate();
     return result;
   }
 
   renameDoc(options) {
+    this._assertWritable('rename documents');
     const result = store.renameDoc(options);
     this._invalidate();
     return result;
   }
 
   removeDoc(options) {
+    this._assertWritable('remove documents');
     const result = store.removeDoc(options);
     this._invalidate();
     return result;
   }
 
   setDocDescription(options) {
+    this._assertWritable('edit document descriptions');
     const result = store.setDocDescription(options);
     this._invalidate();
     return result;
@@ -132,13 +161,178 @@ class DataService {
     return store.getDocContent(docPath);
   }
 
+  getDocuments(docPaths, maxBytes) {
+    return store.getDocuments(docPaths, maxBytes);
+  }
+
+  getBlock(docPath, blockId) {
+    return store.getBlock(docPath, blockId);
+  }
+
+  getContext(docPath, line, before, after) {
+    return store.getContext(docPath, line, before, after);
+  }
+
+  resolveNote(query, limit) {
+    return store.resolveNote(query, limit);
+  }
+
   saveDocContent(options) {
+    this._assertWritable('save documents');
     const result = store.saveDocContent(options);
     this._invalidate();
     return result;
   }
 
+  getDocRevision(docPath) {
+    return store.getDocRevision(docPath);
+  }
+
+  mergeDocContent(options) {
+    return store.mergeDocContent(options);
+  }
+
+  getDocFields(docPath) {
+    return store.getDocFields(docPath);
+  }
+
+  getDocField(docPath, key) {
+    return store.getDocField(docPath, key);
+  }
+
+  setDocField(options) {
+    this._assertWritable('save documents');
+    const result = store.setDocField(options);
+    this._invalidate();
+    return result;
+  }
+
+  listTasks(options) {
+    return store.listTasks(options);
+  }
+
+  setTaskDone(options) {
+    this._assertWritable('save documents');
+    const result = store.setTaskDone(options);
+    this._invalidate();
+    return result;
+  }
+
+  reindexTasks() {
+    return store.reindexTasks();
+  }
+
+  capture(options) {
+    this._assertWritable('capture notes');
+    const result = store.capture(options);
+    this._invalidate();
+    return result;
+  }
+
+  remember(options) {
+    this._assertWritable('save memories');
+    const result = store.remember(options);
+    this._invalidate();
+    return result;
+  }
+
+  listMemories(tag) {
+    return store.listMemories(tag);
+  }
+
+  forgetMemory(id) {
+    this._assertWritable('save memories');
+    const result = store.forgetMemory(id);
+    this._invalidate();
+    return result;
+  }
+
+  purgeExpiredMemories() {
+    this._assertWritable('save memories');
+    const removed = store.purgeExpiredMemories();
+    if (removed) this._invalidate();
+    return removed;
+  }
+
+  listDates(options) {
+    return store.listDates(options);
+  }
+
+  reindexDates() {
+    return store.reindexDates();
+  }
+
+  staleDocs(options) {
+    return store.staleDocs(options);
+  }
+
+  randomDocs(options) {
+    return store.randomDocs(options);
+  }
+
+  resurface(options) {
+    return store.resurface(options);
+  }
+
+  listCards(options) {
+    return store.listCards(options);
+  }
+
+  reviewCard(cardId, grade) {
+    this._assertWritable('review flashcards');
+    return store.reviewCard(cardId, grade);
+  }
+
+  cardStats() {
+    return store.cardStats();
+  }
+
+  reindexCards() {
+    return store.reindexCards();
+  }
+
+  recordDocHits(relPaths) {
+    const unique = [...new Set((relPaths || []).filter(Boolean))];
+    if (unique.length === 0) return;
+    // Usage tracking only; a locked or read-only database must not fail the read
+    try {
+      store.recordDocHits(unique);
+    } catch (err) {
+      if (process.env.OC_STORE_DEBUG) console.warn('[oc store] recordDocHits failed:', err.message);
+    }
+  }
+
+  checkLinks(options) {
+    return store.checkLinks(options);
+  }
+
+  previewLinkRewrites(options) {
+    return store.previewLinkRewrites(options);
+  }
+
+  async createDigest(options) {
+    if (options.write !== false) this._assertWritable('save documents');
+    const result = await store.createDigest(options);
+    if (result.saved) this._invalidate();
+    return result;
+  }
+
+  appendToSection(options) {
+    this._assertWritable('save documents');
+    const result = store.appendToSection(options);
+    this._invalidate();
+    return result;
+  }
+
+  applyPatch(options) {
+    this._assertWritable('save documents');
+    const result = store.applyPatch(options);
+    this._invalidate();
+    return result;
+  }
+
   reconcileDoc(options) {
+    this._assertWritable('reconcile documents');
     const result = store.reconcileDoc(options);
     this._invalidate();
     return result;
@@ -155,11 +349,16 @@ class DataService {
   }
 
   reconcileFolder(options) {
+    this._assertWritable('reconcile folders');
     const result = store.reconcileFolder(options);
     this._invalidate();
     return result;
   }
 
+  indexStatus() {
+    return store.indexStatus();
+  }
+
   suggestFolders(options) {
     return store.suggestFolders(options);
   }
@@ -177,3 +376,4 @@ class DataService {
 
 // Export a singleton — all clients share the same cache
 module.exports = new DataService();
+module.exports.ReadOnlyError = ReadOnlyError;
@@ -0,0 +1,81 @@
+/**
+ * Expiry of agent memories in long-running processes.
+ *
+ * The index only drops an expired memory when the memory doc is reindexed,
+ * so a server running past a memory's expiry would keep returning it from
+ * search. This purges expired memories on a timer set for the nearest
+ * expiry and reindexes the doc straight away. The timer is re-armed at least
+ * every few minutes to pick up memories saved by other processes.
+ */
+
+const store = require('./store/index.js');
+
+const MEMORY_DOC = '.memory/memories.md';
+/** Longest wait before looking at the memory doc again */
+const RECHECK_MS = 5 * 60 * 1000;
+
+let timer = null;
+let onPurged = null;
+
+/** Epoch ms of the soonest expiry among live memories, or null */
+function nextExpiry() {
+  const times = store.listMemories()
+    .map((m) => Date.parse(m.expires_at))
+    .filter(Number.isFinite);
+  return times.length ? Math.min(...times) : null;
+}
+
+/**
+ * Drop expired memories and reindex the memory doc if any were removed
+ * @returns {Promise<number>} memories removed
+ */
+async function purge() {
+  let removed = 0;
+  try {
+    removed = store.purgeExpiredMemories();
+  } catch {
+    // Read-only workspace: expired memories stay hidden from oc_recall only
+    return 0;
+  }
+  if (removed) {
+    const { Indexer } = require('./search/index.js');
+    await new Indexer().indexFile(MEMORY_DOC).catch(() => {});
+    await onPurged?.();
+  }
+  return removed;
+}
+
+/** Arm the timer for the nearest expiry; call again after saving a memory */
+function schedule() {
+  clearTimeout(timer);
+  let at = null;
+  try {
+    at = nextExpiry();
+  } catch {
+    // Unreadable memory doc: look again later
+  }
+  const delay = at === null ? RECHECK_MS : Math.min(Math.max(at - Date.now(), 0), RECHECK_MS);
+  timer = setTimeout(() => {
+    purge().catch(() => {}).finally(schedule);
+  }, delay);
+  timer.unref();
+}
+
+/**
+ * Purge now and keep purging as memories expire
+ * @param {Object} [options]
+ * @param {Function} [options.onPurged] - Called after a purge reindexed the doc,
+ *   e.g. to refresh open searchers
+ */
+async function start(options = {}) {
+  onPurged = options.onPurged ?? null;
+  await purge();
+  schedule();
+}
+
+function stop() {
+  clearTimeout(timer);
+  timer = null;
+}
+
+module.exports = { start, stop, schedule, purge };
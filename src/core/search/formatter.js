@@ -15,10 +15,12 @@ function normalizeResult(result) {
     score: result.score,
     file_path: result.file_path || result.filePath,
     content: result.content,
+    snippet: result.snippet,
     heading_path: result.heading_path || result.headingPath,
     section_title: result.section_title || result.sectionTitle,
     line_start: result.line_start || result.lineStart,
     line_end: result.line_end || result.lineEnd,
+    block_id: result.block_id || result.blockId,
     matched_by: result.matched_by || result.matchedBy,
     hit_count: result.hit_count || result.hitCount,
     doc_count: result.doc_count || result.docCount,
@@ -29,6 +31,14 @@ function normalizeResult(result) {
     entry_date: result.entry_date || result.entryDate,
     entry_created_at: result.entry_created_at || result.entryCreatedAt,
     idea_box: result.idea_box || result.ideaBox,
+    workspace: result.workspace,
+    also_in: result.also_in || result.alsoIn,
+    folder_summary: result.folder_summary || result.folderSummary,
+    doc_meta: result.doc_meta || result.docMeta,
+    citation: result.citation,
+    provenance: result.provenance,
+    trust: result.trust,
+    children: result.children ? result.children.map(normalizeResult) : undefined,
   };
 }
 
@@ -41,6 +51,19 @@ function normalizeResults(results) {
   return (results || []).map(normalizeResult);
 }
 
+/**
+ * `{ total_candidates, total_exact, has_more }` of a search response, or
+ * null if it carries no counts
+ */
+function totalsOf(response) {
+  if (response.total_candidates === undefined) return null;
+  return {
+    total_candidates: response.total_candidates,
+    total_exact: response.total_exact ?? false,
+    has_more: response.has_more ?? false,
+  };
+}
+
 /**
  * Format search results to plain text
  * @param {string} query - Original query
@@ -48,17 +71,32 @@ function normalizeResults(results) {
  * @param {Object} options - Format options
  * @param {string} options.mode - Search mode
  * @param {string} options.aggregateBy - Aggregation type
+ * @param {Object} [options.confidence] - How well the results cover the query
+ * @param {Object} [options.diagnostics] - Why the query found nothing
+ * @param {boolean} [options.degraded] - The requested mode fell back to keyword results
+ * @param {Object} [options.facets] - Match counts by folder, doc type, tag and month
+ * @param {Object} [options.totals] - `{ total_candidates, total_exact, has_more }`
+ * @param {string[]} [options.suggestions] - "Did you mean" rewrites of the query
+ * @param {string[]} [options.related] - Related queries to try next
  * @returns {string} Formatted results
  */
 function formatPlain(query, results, options = {}) {
-  const { mode = 'hybrid', aggregateBy = 'content' } = options;
+  const {
+    mode = 'hybrid', aggregateBy = 'content', confidence, diagnostics, degraded, facets, totals, suggestions,
+    related,
+  } = options;
 
   if (!results || results.length === 0) {
-    return `🔍 Search: "${query}"\nNo results found. Try different keywords or run "oc index build" first.`;
+    return `🔍 Search: "${query}"\n` + (diagnostics
+      ? formatDiagnostics(diagnostics)
+      : 'No results found. Try different keywords or run "oc index build" first.') +
+      suggestionLine(suggestions, '\n');
   }
 
-  const modeLabel = { hybrid: 'Hybrid', vector: 'Vector', keyword: 'Keyword' }[mode] || mode;
-  let output = `🔍 ${modeLabel} Search: "${query}"\nFound ${results.length} results:\n\n`;
+  const modeLabel = { hybrid: 'Hybrid', vector: 'Vector', keyword: 'Keyword', auto: 'Auto' }[mode] || mode;
+  let output = `🔍 ${modeLabel} Search: "${query}"\nFound ${foundCount(results, totals)}:\n` +
+    (degraded ? '⚠️  Semantic search unavailable: showing keyword results only\n' : '') +
+    confidenceLine(confidence) + suggestionLine(suggestions) + '\n';
 
   results.forEach((result, i) => {
     const r = normalizeResult(result);
@@ -73,25 +111,126 @@ function formatPlain(query, results, options = {}) {
     }
   });
 
-  return output;
+  return output + facetLines(facets) + relatedLine(related);
+}
+
+/** "5 results", or "5 of ~40 results (raise --limit for more)" when the limit cut results off */
+function foundCount(results, totals) {
+  if (!totals?.has_more) return `${results.length} results`;
+  const approx = totals.total_exact ? '' : '~';
+  return `${results.length} of ${approx}${totals.total_candidates} results (raise --limit for more)`;
+}
+
+/** Facet counts, one line per non-empty facet */
+function facetLines(facets) {
+  if (!facets) return '';
+  const lines = Object.entries(facets)
+    .filter(([, counts]) => counts?.length)
+    .map(([name, counts]) => `   ${name}: ${counts.map((c) => `${c.value} (${c.count})`).join(', ')}`);
+  return lines.length ? `Facets:\n${lines.join('\n')}\n` : '';
+}
+
+/** Why a query found nothing, most likely cause first */
+function formatDiagnostics(d) {
+  if (!d.index_exists) return 'No results: the search index is not built. Run "oc index build".';
+  if (d.chunks === 0) return 'No results: the search index is empty. Add documents and run "oc index build".';
+  let output = 'No results found.\n';
+  if (d.filtered_out > 0) {
+    output += `   ${d.filtered_out} matches were removed by filters, min score or folder access.\n`;
+  }
+  if (d.absent_terms?.length) {
+    output += `   Not in any document: ${d.absent_terms.join(', ')}\n`;
+  }
+  for (const [term, nearest] of Object.entries(d.nearest_terms || {})) {
+    output += `   "${term}" → did you mean ${nearest.join(', ')}?\n`;
+  }
+  if (d.known_terms?.length) {
+    output += `   Found in documents: ${d.known_terms.join(', ')}\n`;
+  }
+  return output.trimEnd();
+}
+
+/** "Did you mean" line, empty without suggestions */
+function suggestionLine(suggestions, prefix = '') {
+  if (!suggestions?.length) return '';
+  return `${prefix}Did you mean: ${suggestions.map((s) => `"${s}"`).join(', ')}?\n`;
+}
+
+/** "Related" line, empty without related queries */
+function relatedLine(related) {
+  if (!related?.length) return '';
+  return `Related: ${related.map((q) => `"${q}"`).join(', ')}\n`;
+}
+
+/** Warning when the results likely don't answer the query */
+function confidenceLine(confidence) {
+  if (!confidence || confidence.level === 'high') return '';
+  const missing = confidence.missing_terms?.length
+    ? ` (not found: ${confidence.missing_terms.join(', ')})`
+    : '';
+  return confidence.level === 'low'
+    ? `⚠️  Low confidence: your notes may not cover this${missing}\n`
+    : `Partial match${missing}\n`;
 }
 
 function formatMatchLabel(matchedBy) {
   if (matchedBy === 'vector+keyword') return '[vector+keyword]';
   if (matchedBy === 'vector') return '[vector]';
+  if (matchedBy === 'sparse') return '[sparse]';
   return '[keyword]';
 }
 
+/** `workspace: ` prefix for federated results */
+function workspacePrefix(result) {
+  return result.workspace ? `${result.workspace}: ` : '';
+}
+
 function formatFolderResult(index, result, matchLabel) {
   return `[${index + 1}] Score: ${result.score.toFixed(4)} ${matchLabel}\n` +
-    `📁 ${result.folder_path || result.file_path}\n` +
-    `   ${result.doc_count || 0} documents, ${result.hit_count || 0} matches\n\n`;
+    `📁 ${workspacePrefix(result)}${result.folder_path || result.file_path}\n` +
+    `   ${result.doc_count || 0} documents, ${result.hit_count || 0} matches\n` +
+    formatFolderSummary(result.folder_summary) +
+    '\n';
+}
+
+/** Description, matched headings and documents of a folder result */
+function formatFolderSummary(summary) {
+  if (!summary) return '';
+  let output = summary.summary ? `   ${summary.summary.replace(/\s*\n\s*/g, ' ')}\n` : '';
+  if (summary.headings?.length) {
+    output += `   Headings: ${summary.headings.join('; ')}\n`;
+  }
+  if (summary.docs?.length) {
+    const more = summary.doc_total > summary.docs.length ? `, +${summary.doc_total - summary.docs.length} more` : '';
+    output += `   Docs: ${summary.docs.join(', ')}${more}\n`;
+  }
+  return output;
+}
+
+/** Line listing other documents with the same content, if any */
+function alsoInLine(result) {
+  return result.also_in?.length ? `   also in: ${result.also_in.join(', ')}\n` : '';
+}
+
+/** Line labeling text nobody typed (web clip, OCR, transcript), if any */
+function originLine(result) {
+  const origin = result.provenance?.origin;
+  if (!origin) return '';
+  const url = result.provenance.source_url ? ` of ${result.provenance.source_url}` : '';
+  return `   ${origin.replace(/_/g, ' ')}${url}\n`;
+}
+
+/** `, N words, M min read` from the document stats, if any */
+function docStats(meta) {
+  return meta ? `, ${meta.words} words, ${meta.reading_minutes} min read` : '';
 }
 
 function formatDocResult(index, result, matchLabel) {
   return `[${index + 1}] Score: ${result.score.toFixed(4)} ${matchLabel}\n` +
-    `📄 ${result.file_path}\n` +
-    `   ${result.hit_count || 0} matches\n\n`;
+    `📄 ${workspacePrefix(result)}${result.file_path}\n` +
+    alsoInLine(result) +
+    originLine(result) +
+    `   ${result.hit_count || 0} matches${docStats(result.doc_meta)}\n\n`;
 }
 
 function formatContentResult(index, result, matchLabel) {
@@ -99,12 +238,15 @@ function formatContentResult(index, result, matchLabel) {
   const lineInfo = result.line_start && result.line_end 
     ? ` (lines ${result.line_start}-${result.line_end})` 
     : '';
+  const blockInfo = result.block_id ? ` ^${result.block_id}` : '';
   const separator = '─'.repeat(40);
   const content = result.content || '';
   const truncated = content.length > 300 ? content.slice(0, 300) + '...' : content;
 
   return `[${index + 1}] Score: ${result.score.toFixed(4)} ${matchLabel}\n` +
-    `📄 ${result.file_path}${headingPath}${lineInfo}\n` +
+    `📄 ${workspacePrefix(result)}${result.file_path}${headingPath}${lineInfo}${blockInfo}\n` +
+    alsoInLine(result) +
+    originLine(result) +
     `${separator}\n${truncated}\n${separator}\n\n`;
 }
 
@@ -113,23 +255,41 @@ function formatContentResult(index, result, matchLabel) {
  * @param {string} query - Original query
  * @param {Array} results - Search results
  * @param {Object} options - Format options
+ * @param {Object} [options.confidence] - How well the results cover the query
+ * @param {Object} [options.diagnostics] - Why the query found nothing
+ * @param {boolean} [options.degraded] - The requested mode fell back to keyword results
+ * @param {Object} [options.facets] - Match counts by folder, doc type, tag and month
+ * @param {Object} [options.totals] - `{ total_candidates, total_exact, has_more }`
+ * @param {string[]} [options.suggestions] - "Did you mean" rewrites of the query
+ * @param {string[]} [options.related] - Related queries to try next
  * @returns {Object} JSON formatted results
  */
 function formatJson(query, results, options = {}) {
-  const { mode = 'hybrid', aggregateBy = 'content' } = options;
+  const {
+    mode = 'hybrid', aggregateBy = 'content', confidence, diagnostics, degraded, facets, totals, suggestions,
+    related,
+  } = options;
 
   return {
     query,
     mode,
     aggregate_by: aggregateBy,
     count: results.length,
+    ...totals,
     results: normalizeResults(results),
+    ...(confidence && { confidence }),
+    ...(diagnostics && results.length === 0 && { diagnostics }),
+    ...(degraded && { degraded }),
+    ...(facets && { facets }),
+    ...(suggestions?.length && { suggestions }),
+    ...(related?.length && { related }),
   };
 }
 
 module.exports = {
   normalizeResult,
   normalizeResults,
+  totalsOf,
   formatPlain,
   formatJson,
 };
@@ -5,7 +5,9 @@
  */
 
 const native = require('../native');
-const { NativeSearcher, NativeIndexer } = require('./native-adapter');
+const { NativeSearcher, NativeIndexer, NativeFederatedSearcher } = require('./native-adapter');
+const { RemoteSearcher } = require('./remote');
+const config = require('../config');
 
 // Require native bindings at load time
 native.require();
@@ -18,11 +20,28 @@ if (process.env.OC_SEARCH_DEBUG) {
 // Export native implementations as primary classes
 const Searcher = NativeSearcher;
 const Indexer = NativeIndexer;
+const FederatedSearcher = NativeFederatedSearcher;
+
+/**
+ * Searcher for the configured index: a RemoteSearcher when REMOTE_URL is
+ * set, the local native Searcher otherwise
+ * @param {Object} [options] - Passed to the local Searcher
+ */
+function createSearcher(options = {}) {
+  const url = config.get('REMOTE_URL');
+  if (url) {
+    return new RemoteSearcher({ url, token: config.get('REMOTE_TOKEN') });
+  }
+  return new Searcher(options);
+}
 
 module.exports = {
   // Main exports
   Searcher,
   Indexer,
+  FederatedSearcher,
+  RemoteSearcher,
+  createSearcher,
   
   // Implementation info
   isNativeAvailable: native.isAvailable,
@@ -24,6 +24,19 @@ async function start(options = {}) {
   return await native.get().startIndexSync(intervalSecs);
 }
 
+/**
+ * Stop the index sync service, flushing pending updates first. Updates that
+ * cannot be applied are journaled and replayed on the next start.
+ *
+ * @param {Object} options
+ * @param {number} options.timeoutMs - How long to wait for the flush (default: 10000)
+ * @returns {Promise<boolean>} true once stopped, false on timeout
+ */
+async function stop(options = {}) {
+  if (!native.isAvailable()) return true;
+  return await native.get().stopIndexSync(options.timeoutMs ?? null);
+}
+
 /**
  * Check if the index sync service is running
  * @returns {boolean}
@@ -47,16 +60,14 @@ function getStatus() {
 // Singleton-like interface for backward compatibility
 const indexSync = {
   start,
+  stop,
   isRunning,
   getStatus,
   
   // Compatibility methods (no-op in native version)
-  stop: () => {
-    console.log('[IndexSync] Note: Native service cannot be stopped once started');
-  },
   setEnabled: (enabled) => {
     console.log('[IndexSync] Note: Use native service control instead');
   },
 };
 
-module.exports = { indexSync, start, isRunning, getStatus };
+module.exports = { indexSync, start, stop, isRunning, getStatus };
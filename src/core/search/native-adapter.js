@@ -6,14 +6,73 @@
  */
 
 const native = require('../native');
-const { normalizeResults, formatPlain, formatJson } = require('./formatter');
+const { normalizeResults, totalsOf, formatPlain, formatJson } = require('./formatter');
 
 // Re-export availability checks
 const isNativeAvailable = native.isAvailable;
 const getNativeError = native.getError;
 
 /**
- * Native Searcher wrapper - matches JS Searcher API
+ * Split the `[CODE] message\nHint: ...` form native search errors arrive in
+ * into `err.code`, `err.hint` and a plain `err.message`
+ */
+function decodeNativeError(err) {
+  const match = /^\[([A-Z_]+)\] ([\s\S]*?)(?:\nHint: ([\s\S]*))?$/.exec(err?.message || '');
+  if (match) {
+    err.code = match[1];
+    err.message = match[2];
+    if (match[3]) err.hint = match[3];
+  }
+  return err;
+}
+
+function rethrow(err) {
+  throw decodeNativeError(err);
+}
+
+/**
+ * Map JS search options to the native SearchOptions object
+ */
+function toNativeOptions(query, options) {
+  const { limit = 5, mode = 'hybrid', aggregateBy = 'content' } = options;
+  return {
+    query,
+    limit,
+    mode,
+    aggregateBy,
+    docType: options.docType,
+    filter: options.filter,
+    folderFilter: options.folderFilter,
+    includeSources: options.includeSources,
+    excludeSources: options.excludeSources,
+    minScore: options.minScore,
+    dateFrom: options.dateFrom,
+    dateTo: options.dateTo,
+    includeNeighbors: options.includeNeighbors,
+    groupBy: options.groupBy,
+    groupChildren: options.groupChildren,
+    facets: options.facets,
+    fields: options.fields,
+    timeoutMs: options.timeoutMs,
+    crossLanguage: options.crossLanguage,
+    collection: options.collection,
+    allowedFolders: options.allowedFolders,
+    folderSummary: options.folderSummary,
+    tier: options.tier,
+    sortBy: options.sortBy,
+    sortOrder: options.sortOrder,
+    citation: options.citation,
+  };
+}
+
+/**
+ * Native Searcher wrapper - matches JS Searcher API. The estimate of how
+ * well the last query's results cover it is kept in `lastConfidence`, why
+ * it found nothing, if so, in `lastDiagnostics`, whether it fell back to
+ * keyword results in `lastDegraded`, its facet counts (when requested) in
+ * `lastFacets`, how many results matched before `limit` in `lastTotals`,
+ * "did you mean" rewrites in `lastSuggestions`, and related queries in
+ * `lastRelated`.
  */
 class NativeSearcher {
   constructor(options = {}) {
@@ -21,6 +80,13 @@ class NativeSearcher {
     this.keywordWeight = options.keywordWeight ?? 0.3;
     this.initialized = false;
     this._searcher = null;
+    this.lastConfidence = null;
+    this.lastDiagnostics = null;
+    this.lastDegraded = false;
+    this.lastFacets = null;
+    this.lastTotals = null;
+    this.lastSuggestions = null;
+    this.lastRelated = null;
   }
 
   /**
@@ -30,7 +96,7 @@ class NativeSearcher {
   async initialize(forceReinit = false) {
     if (this.initialized && !forceReinit) return;
     
-    this._searcher = await native.get().Searcher.create();
+    this._searcher = await native.get().Searcher.create().catch(rethrow);
     this.initialized = true;
   }
 
@@ -39,8 +105,30 @@ class NativeSearcher {
    * @param {string} query - Search query
    * @param {Object} options
    * @param {number} options.limit - Number of results to return
-   * @param {string} options.mode - Search mode: 'hybrid' | 'vector' | 'keyword'
+   * @param {string} options.mode - Search mode: 'hybrid' | 'vector' | 'keyword' | 'auto'
    * @param {string} options.aggregateBy - Aggregation type: 'content' | 'doc' | 'folder'
+   * @param {string} [options.filter] - Filter expression, e.g. `tag:rust path:projects/ created:>2024-01-01`
+   * @param {string} [options.groupBy] - 'doc' to nest each document's top chunks in `children`
+   * @param {number} [options.groupChildren] - Max children per document group (default 3)
+   * @param {boolean} [options.facets] - Also count matches by folder, doc type, tag and month
+   *   (kept in `lastFacets`)
+   * @param {string[]} [options.fields] - Result fields to return (file_path and score are always kept)
+   * @param {number} [options.timeoutMs] - Embedding time budget before falling back to keyword results
+   * @param {boolean} [options.crossLanguage] - Also match keywords in the workspace's other languages
+   * @param {string} [options.collection] - Collection to search instead of the configured one
+   * @param {string[]} [options.includeSources] - Only results from these folders, doc types or
+   *   provenance sources (e.g. 'markdown', 'evernote', 'web_clip')
+   * @param {string[]} [options.excludeSources] - No results from these (same values as includeSources)
+   * @param {string[]} [options.allowedFolders] - Folders the caller may read (ACL); other hits are dropped
+   * @param {boolean} [options.folderSummary] - With aggregateBy 'folder', attach each folder's
+   *   documents, matched headings and (with search.folder_summary_llm) an LLM description
+   * @param {string} [options.tier] - 'fast' | 'accurate' embedding tier (with embedding.tiers enabled)
+   * @param {string} [options.sortBy] - 'score' (default) | 'modified' | 'created' | 'path' | 'entry_date'
+   * @param {string} [options.sortOrder] - 'asc' | 'desc' (default: A to Z for paths, newest first for dates)
+   * @param {string} [options.citation] - 'link' | 'footnote': add a ready-to-paste `citation` to each
+   *   result, `[Title](path#L12-L30)` or `[^1]: [Title](path#L12-L30)`
+   * @param {Array<{path?: string, content: string}>} [options.contextDocs] - Unindexed texts (e.g. the
+   *   document being edited) to rank by as well; embedded for this query only, never stored
    * @returns {Promise<Array>} Search results array with snake_case fields
    */
   async search(query, options = {}) {
@@ -48,29 +136,176 @@ class NativeSearcher {
       await this.initialize();
     }
 
-    const { limit = 5, mode = 'hybrid', aggregateBy = 'content' } = options;
-
-    const response = await this._searcher.search({
-      query,
-      limit,
-      mode,
-      aggregateBy,
-      docType: options.docType,
-      folderFilter: options.folderFilter,
-      minScore: options.minScore,
-      dateFrom: options.dateFrom,
-      dateTo: options.dateTo,
-      includeNeighbors: options.includeNeighbors,
-    });
+    const searchOptions = toNativeOptions(query, options);
+    const contextDocs = options.contextDocs?.filter((doc) => doc?.content?.trim());
+    const response = contextDocs?.length
+      ? await this._searcher.searchWithContext(searchOptions, contextDocs).catch(rethrow)
+      : await this._searcher.search(searchOptions).catch(rethrow);
 
     // Native returns { results: [...], count: N, ... }
     // JS API expects just the array, normalized to snake_case
+    this.lastConfidence = response.confidence || null;
+    this.lastDiagnostics = response.diagnostics || null;
+    this.lastDegraded = response.degraded === true;
+    this.lastFacets = response.facets || null;
+    this.lastTotals = totalsOf(response);
+    this.lastSuggestions = response.suggestions || null;
+    this.lastRelated = response.related || null;
     return normalizeResults(response.results);
   }
 
+  /**
+   * Another searcher over the same open indexes, with its own `last*`
+   * query details, for callers serving queries
+   * concurrently
+   * @returns {Promise<NativeSearcher>}
+   */
+  async share() {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    const searcher = new NativeSearcher({
+      vectorWeight: this.vectorWeight,
+      keywordWeight: this.keywordWeight,
+    });
+    searcher._searcher = this._searcher;
+    searcher.initialized = true;
+    return searcher;
+  }
+
+  /**
+   * Open an in-memory index for texts that should be searchable only for
+   * this session (a transcript, pasted notes). Shares this searcher's
+   * embedding client; nothing is written to the workspace index.
+   * @returns {Promise<NativeEphemeralIndex>}
+   */
+  async ephemeral() {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    return new NativeEphemeralIndex(await this._searcher.ephemeral());
+  }
+
+  /**
+   * Autocomplete a partially typed query from indexed titles, headings and tags
+   * @param {string} prefix - Text typed so far
+   * @param {number} [limit=10] - Maximum suggestions
+   * @returns {Promise<Array<{text: string, kind: string, count: number}>>}
+   */
+  async suggest(prefix, limit = 10) {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    return await this._searcher.suggest(prefix, limit);
+  }
+
+  /**
+   * Preload indexes so the first query is fast
+   * @param {Object} options
+   * @param {boolean} [options.pingEmbedding] - Also check the embedding endpoint
+   * @returns {Promise<Object>} Readiness report ({ ready, index_exists, ... })
+   */
+  async warmUp(options = {}) {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    return await this._searcher.warmUp(options.pingEmbedding ?? false);
+  }
+
+  /**
+   * Memory held by this process's search caches
+   * @returns {Promise<Object>} ({ budget_bytes, used_bytes, components: [...] })
+   */
+  async memoryUsage() {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    return await this._searcher.memoryUsage();
+  }
+
+  /**
+   * Reopen the indexes after a rebuild or clean; searches running meanwhile
+   * are served from the old ones
+   */
+  async refresh() {
+    if (!this.initialized) {
+      await this.initialize();
+      return;
+    }
+    await this._searcher.refresh().catch(rethrow);
+  }
+
+  /** Format options with the last query's details (see the `last*` fields) */
+  _lastMeta(options) {
+    return {
+      confidence: this.lastConfidence,
+      diagnostics: this.lastDiagnostics,
+      degraded: this.lastDegraded,
+      facets: this.lastFacets,
+      totals: this.lastTotals,
+      suggestions: this.lastSuggestions,
+      related: this.lastRelated,
+      ...options,
+    };
+  }
+
   /** @see formatPlain */
   formatResults(query, results, options = {}) {
-    return formatPlain(query, results, options);
+    return formatPlain(query, results, this._lastMeta(options));
+  }
+
+  /** @see formatPlain */
+  formatResultsPlain(query, results, options = {}) {
+    return formatPlain(query, results, this._lastMeta(options));
+  }
+
+  /** @see formatJson */
+  formatResultsJson(query, results, options = {}) {
+    return formatJson(query, results, this._lastMeta(options));
+  }
+}
+
+/**
+ * Native FederatedSearcher wrapper: searches the local workspace and every
+ * workspace under [federation] in config.toml. Results carry a `workspace`
+ * label; per-workspace counts and errors of the last query are kept in
+ * `lastWorkspaces`.
+ */
+class NativeFederatedSearcher {
+  constructor() {
+    this.initialized = false;
+    this._searcher = null;
+    this.lastWorkspaces = [];
+  }
+
+  async initialize(forceReinit = false) {
+    if (this.initialized && !forceReinit) return;
+
+    this._searcher = await native.get().FederatedSearcher.create().catch(rethrow);
+    this.initialized = true;
+  }
+
+  /**
+   * @param {string} query
+   * @param {Object} options - Same as NativeSearcher.search; `collection` is
+   *   ignored since each workspace searches its configured collection
+   * @returns {Promise<Array>} Merged results, each with a `workspace` label
+   */
+  async search(query, options = {}) {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    const response = await this._searcher.search(toNativeOptions(query, options)).catch(rethrow);
+    this.lastWorkspaces = response.workspaces || [];
+    return normalizeResults(response.results);
+  }
+
+  /** @returns {Promise<string[]>} Workspace names, local first */
+  async workspaces() {
+    if (!this.initialized) {
+      await this.initialize();
+    }
+    return this._searcher.workspaces();
   }
 
   /** @see formatPlain */
@@ -80,7 +315,50 @@ class NativeSearcher {
 
   /** @see formatJson */
   formatResultsJson(query, results, options = {}) {
-    return formatJson(query, results, options);
+    return { ...formatJson(query, results, options), workspaces: this.lastWorkspaces };
+  }
+}
+
+/**
+ * Native EphemeralIndex wrapper; results use the same shape as search()
+ */
+class NativeEphemeralIndex {
+  constructor(inner) {
+    this._index = inner;
+  }
+
+  /**
+   * Add a text, replacing any earlier text with the same name
+   * @param {string} source - Name the text is found under (e.g. "transcript")
+   * @param {string} content
+   * @returns {Promise<number>} Chunks added
+   */
+  async add(source, content) {
+    return await this._index.add(source, content);
+  }
+
+  /**
+   * @param {string} query
+   * @param {Object} [options]
+   * @param {number} [options.limit=5]
+   * @returns {Promise<Array>} Hits with file_path set to the source name
+   */
+  async search(query, options = {}) {
+    return normalizeResults(await this._index.search(query, options.limit ?? 5));
+  }
+
+  /** @returns {Promise<number>} Chunks removed */
+  async remove(source) {
+    return await this._index.remove(source);
+  }
+
+  async clear() {
+    await this._index.clear();
+  }
+
+  /** @returns {Promise<string[]>} Names of the added texts, oldest first */
+  async sources() {
+    return await this._index.sources();
   }
 }
 
@@ -99,7 +377,7 @@ class NativeIndexer {
   async initialize() {
     if (this.initialized) return;
 
-    this._indexer = await native.get().Indexer.create();
+    this._indexer = await native.get().Indexer.create().catch(rethrow);
     this.initialized = true;
   }
 
@@ -126,11 +404,11 @@ class NativeIndexer {
 
     let stats;
     if (options.folder) {
-      stats = await this._indexer.buildFolder(options.folder, options.force ?? false);
+      stats = await this._indexer.buildFolder(options.folder, options.force ?? false).catch(rethrow);
     } else if (wrapProgress) {
-      stats = await this._indexer.buildAllWithProgress(options.force ?? false, wrapProgress);
+      stats = await this._indexer.buildAllWithProgress(options.force ?? false, wrapProgress).catch(rethrow);
     } else {
-      stats = await this._indexer.buildAll(options.force ?? false);
+      stats = await this._indexer.buildAll(options.force ?? false).catch(rethrow);
     }
 
     return {
@@ -139,9 +417,18 @@ class NativeIndexer {
       mode: stats.mode ?? 'full',
       elapsedMs: stats.elapsedMs,
       changes: stats.changes ?? null,
+      interrupted: stats.interrupted ?? false,
     };
   }
 
+  /**
+   * Stop a running buildIndex after its current batch. The build resolves
+   * with `interrupted: true`; the next build picks up where it stopped.
+   */
+  cancel() {
+    if (this._indexer) this._indexer.cancel();
+  }
+
   /**
    * Index a single file
    * @param {string} relPath - Relative path to the file
@@ -149,7 +436,7 @@ class NativeIndexer {
    */
   async indexFile(relPath) {
     await this.initialize();
-    return await this._indexer.indexFile(relPath);
+    return await this._indexer.indexFile(relPath).catch(rethrow);
   }
 
   /**
@@ -161,6 +448,40 @@ class NativeIndexer {
     await this._indexer.removeFile(relPath);
   }
 
+  /**
+   * Report what a build (or `updateDirty` with `dirty: true`) would chunk,
+   * embed and delete, with token and cost estimates. Writes nothing.
+   * @param {{ force?: boolean, folder?: string, dirty?: boolean }} options
+   * @returns {Object} IndexPlan
+   */
+  planIndex(options = {}) {
+    return native.get().planIndex({
+      force: options.force ?? false,
+      folder: options.folder,
+      dirty: options.dirty ?? false,
+    });
+  }
+
+  /**
+   * Chunk, token and cost estimate for embedding files or folders, for
+   * asking before a large build. Writes nothing.
+   * @param {string[]} [paths] - Paths relative to the contexts root; all when omitted
+   * @returns {Object} IndexCostEstimate
+   */
+  estimateIndexCost(paths) {
+    return native.get().estimateIndexCost(paths);
+  }
+
+  /**
+   * Re-index only the files that changed since the last build
+   * (see `indexStatus`)
+   * @returns {Promise<{ indexed: number, removed: number, renamed: number, chunks: number }>}
+   */
+  async updateDirty() {
+    await this.initialize();
+    return await this._indexer.updateDirty();
+  }
+
   /**
    * Check if index exists
    * @returns {Promise<boolean>}
@@ -198,4 +519,7 @@ module.exports = {
   getNativeError,
   NativeSearcher,
   NativeIndexer,
+  NativeEphemeralIndex,
+  NativeFederatedSearcher,
+  decodeNativeError,
 };
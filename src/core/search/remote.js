@@ -0,0 +1,183 @@
+/**
+ * Remote Searcher
+ *
+ * Talks to another OpenContext instance over the HTTP API served by
+ * `oc ui`, and exposes the same methods as NativeSearcher so the CLI can
+ * query an index living on another machine. Configure it with REMOTE_URL
+ * (and REMOTE_TOKEN when the server has API tokens) via `oc config set` or
+ * OPENCONTEXT_REMOTE_URL / OPENCONTEXT_REMOTE_TOKEN.
+ */
+
+const { normalizeResults, totalsOf, formatPlain, formatJson } = require('./formatter');
+
+const DEFAULT_TIMEOUT_MS = 30000;
+
+class RemoteSearcher {
+  /**
+   * @param {Object} options
+   * @param {string} options.url - Base URL of the remote instance (e.g. http://homeserver:3222)
+   * @param {string} [options.token] - API token, sent as `Authorization: Bearer <token>`
+   * @param {number} [options.timeoutMs=30000] - Per-request timeout
+   */
+  constructor(options = {}) {
+    if (!options.url) {
+      throw new Error('RemoteSearcher requires a url');
+    }
+    this.url = options.url.replace(/\/+$/, '');
+    this.token = options.token;
+    this.timeoutMs = options.timeoutMs ?? DEFAULT_TIMEOUT_MS;
+    this.initialized = true;
+    this.lastConfidence = null;
+    this.lastDiagnostics = null;
+    this.lastDegraded = false;
+    this.lastFacets = null;
+    this.lastTotals = null;
+    this.lastSuggestions = null;
+    this.lastRelated = null;
+  }
+
+  /** Nothing to open locally; kept for API parity with NativeSearcher */
+  async initialize() {}
+
+  async _get(pathname, params = {}, { allowStatus = [] } = {}) {
+    const url = new URL(this.url + pathname);
+    for (const [key, value] of Object.entries(params)) {
+      if (value !== undefined && value !== null && value !== '') {
+        url.searchParams.set(key, String(value));
+      }
+    }
+    const headers = { Accept: 'application/json' };
+    if (this.token) {
+      headers.Authorization = `Bearer ${this.token}`;
+    }
+
+    let res;
+    try {
+      res = await fetch(url, { headers, signal: AbortSignal.timeout(this.timeoutMs) });
+    } catch (err) {
+      const reason = err.name === 'TimeoutError' ? `timed out after ${this.timeoutMs}ms` : err.message;
+      throw new Error(`Remote OpenContext at ${this.url} unreachable: ${reason}`);
+    }
+    const body = await res.json().catch(() => ({}));
+    if (!res.ok && !allowStatus.includes(res.status)) {
+      const err = new Error(`Remote OpenContext: ${body.error || `HTTP ${res.status}`}`);
+      err.code = body.code;
+      err.hint = body.hint;
+      err.status = res.status;
+      throw err;
+    }
+    return body;
+  }
+
+  /**
+   * Execute search on the remote index
+   * @param {string} query - Search query
+   * @param {Object} options - Same as NativeSearcher.search, except that
+   *   `contextDocs` and `allowedFolders` are not sent (the remote applies the
+   *   token's own folder access)
+   * @returns {Promise<Array>} Search results array with snake_case fields
+   */
+  async search(query, options = {}) {
+    const { limit = 5, mode = 'hybrid', aggregateBy = 'content' } = options;
+    const body = await this._get('/api/semantic-search', {
+      q: query,
+      limit,
+      mode,
+      aggregateBy,
+      docType: options.docType,
+      filter: options.filter,
+      folderFilter: options.folderFilter,
+      includeSources: options.includeSources?.join(','),
+      excludeSources: options.excludeSources?.join(','),
+      minScore: options.minScore,
+      crossLanguage: options.crossLanguage,
+      collection: options.collection,
+      folderSummary: options.folderSummary,
+      tier: options.tier,
+      sortBy: options.sortBy,
+      sortOrder: options.sortOrder,
+      citation: options.citation,
+      facets: options.facets,
+    });
+    if (body.error && !body.indexMissing) {
+      throw new Error(`Remote OpenContext: ${body.error}`);
+    }
+    this.lastConfidence = body.confidence || null;
+    this.lastDiagnostics = body.diagnostics || null;
+    this.lastDegraded = body.degraded === true;
+    this.lastFacets = body.facets || null;
+    this.lastTotals = totalsOf(body);
+    this.lastSuggestions = body.suggestions || null;
+    this.lastRelated = body.related || null;
+    return normalizeResults(body.results);
+  }
+
+  /**
+   * Autocomplete a partially typed query from the remote index
+   * @returns {Promise<Array<{text: string, kind: string, count: number}>>}
+   */
+  async suggest(prefix, limit = 10) {
+    const body = await this._get('/api/search/suggest', { q: prefix, limit });
+    return body.suggestions || [];
+  }
+
+  /**
+   * Readiness report of the remote search engine
+   * @returns {Promise<Object>} ({ ready, index_exists, ... })
+   */
+  async warmUp() {
+    return await this._get('/readyz', {}, { allowStatus: [503] });
+  }
+
+  /**
+   * Memory held by the remote server's search caches
+   * @returns {Promise<Object>} ({ budget_bytes, used_bytes, components: [...] })
+   */
+  async memoryUsage() {
+    return await this._get('/api/debug/memory');
+  }
+
+  /**
+   * Content of a document in the remote workspace
+   * @param {string} docPath - Path relative to contexts/
+   * @returns {Promise<string>}
+   */
+  async getDocContent(docPath) {
+    const body = await this._get('/api/docs/content', { path: docPath });
+    return body.content;
+  }
+
+  /** Format options with the last query's details (see the `last*` fields) */
+  _lastMeta(options) {
+    return {
+      confidence: this.lastConfidence,
+      diagnostics: this.lastDiagnostics,
+      degraded: this.lastDegraded,
+      facets: this.lastFacets,
+      totals: this.lastTotals,
+      suggestions: this.lastSuggestions,
+      related: this.lastRelated,
+      ...options,
+    };
+  }
+
+  /** @see formatPlain */
+  formatResults(query, results, options = {}) {
+    return formatPlain(query, results, this._lastMeta(options));
+  }
+
+  /** @see formatPlain */
+  formatResultsPlain(query, results, options = {}) {
+    return formatPlain(query, results, this._lastMeta(options));
+  }
+
+  /** @see formatJson */
+  formatResultsJson(query, results, options = {}) {
+    const json = formatJson(query, results, this._lastMeta(options));
+    return { ...json, remote: this.url };
+  }
+}
+
+module.exports = {
+  RemoteSearcher,
+};
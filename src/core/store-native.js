@@ -25,6 +25,15 @@ function handleResult(result) {
 
 // ==================== Store API ====================
 
+/**
+ * Whether the workspace rejects document and folder mutations
+ * @returns {boolean}
+ */
+function isReadOnly() {
+  const binding = native.get();
+  return typeof binding.isReadOnly === 'function' ? binding.isReadOnly() : false;
+}
+
 /**
  * Initialize environment and return paths
  * @returns {{ contextsRoot: string, dbPath: string }}
@@ -61,7 +70,7 @@ function createFolder(options) {
 /**
  * Rename a folder
  * @param {{ path: string, newName: string }} options
- * @returns {{ old_path: string, new_path: string }}
+ * @returns {{ old_path: string, new_path: string, links_rewritten: Array<LinkRewrite> }}
  */
 function renameFolder(options) {
   return handleResult(native.get().renameFolder({
@@ -73,7 +82,7 @@ function renameFolder(options) {
 /**
  * Move a folder
  * @param {{ path: string, destFolderPath: string }} options
- * @returns {{ old_path: string, new_path: string }}
+ * @returns {{ old_path: string, new_path: string, links_rewritten: Array<LinkRewrite> }}
  */
 function moveFolder(options) {
   return handleResult(native.get().moveFolder({
@@ -123,7 +132,7 @@ function createDoc(options) {
 /**
  * Move a document
  * @param {{ docPath: string, destFolderPath: string }} options
- * @returns {{ old_path: string, new_path: string }}
+ * @returns {{ old_path: string, new_path: string, links_rewritten: Array<LinkRewrite> }}
  */
 function moveDoc(options) {
   return handleResult(native.get().moveDoc({
@@ -135,7 +144,7 @@ function moveDoc(options) {
 /**
  * Rename a document
  * @param {{ docPath: string, newName: string }} options
- * @returns {{ old_path: string, new_path: string }}
+ * @returns {{ old_path: string, new_path: string, links_rewritten: Array<LinkRewrite> }}
  */
 function renameDoc(options) {
   return handleResult(native.get().renameDoc({
@@ -195,16 +204,388 @@ function getDocContent(docPath) {
   return handleResult(native.get().getDocContent(docPath));
 }
 
+/**
+ * Several documents in one call. A document that can't be read carries its
+ * `error` instead of `content`; one that would push the batch past
+ * `maxBytes` comes back without content and with `over_limit: true`.
+ * @param {string[]} docPaths
+ * @param {number} [maxBytes] - Default 256 KiB
+ * @returns {Array<{ doc_path: string, doc: object|null, content: string|null, revision: string|null, error: string|null, over_limit: boolean }>}
+ */
+function getDocuments(docPaths, maxBytes) {
+  return handleResult(native.get().getDocuments(docPaths, maxBytes));
+}
+
+/**
+ * Paragraph or list item marked `^blockId` in a document
+ * @param {string} docPath
+ * @param {string} blockId - With or without the leading `^`
+ * @returns {{ id: string, text: string, line_start: number, line_end: number }}
+ */
+function getBlock(docPath, blockId) {
+  return handleResult(native.get().getBlock(docPath, blockId));
+}
+
+/**
+ * Lines around `line` of a document, with their heading path and the
+ * sections just above and below
+ * @param {string} docPath
+ * @param {number} line - 1-based
+ * @param {number} [before] - Lines before `line` (default 0)
+ * @param {number} [after] - Lines after `line` (default 0)
+ * @returns {{ text: string, line_start: number, line_end: number, heading_path: string, previous: object|null, next: object|null, line_count: number }}
+ */
+function getContext(docPath, line, before, after) {
+  return handleResult(native.get().getContext(docPath, line, before, after));
+}
+
+/**
+ * Documents called `query` by title, alias or file name, best first
+ * @param {string} query
+ * @param {number} [limit=10]
+ * @returns {Array<{ rel_path: string, name: string, kind: 'title'|'alias'|'file', score: number }>}
+ */
+function resolveNote(query, limit) {
+  return handleResult(native.get().resolveNote(query, limit ?? null));
+}
+
 /**
  * Save document content
- * @param {{ docPath: string, content: string, description?: string }} options
- * @returns {{ rel_path: string, abs_path: string }}
+ *
+ * With `expectedRevision`, the save is refused (error `code: 'CONFLICT'`)
+ * if the file changed since that revision; merge with `mergeDocContent`
+ * and retry against the current revision.
+ *
+ * @param {{ docPath: string, content: string, description?: string, expectedRevision?: string }} options
+ * @returns {{ rel_path: string, abs_path: string, revision: string }}
  */
 function saveDocContent(options) {
-  return handleResult(native.get().saveDocContent({
+  return withConflictCode(() => native.get().saveDocContent({
     docPath: options.docPath,
     content: options.content,
     description: options.description,
+    expectedRevision: options.expectedRevision,
+  }));
+}
+
+/** Tag native conflict errors with `code: 'CONFLICT'` */
+function withConflictCode(call) {
+  try {
+    return handleResult(call());
+  } catch (err) {
+    if (err && /^conflict:/.test(err.message)) err.code = 'CONFLICT';
+    throw err;
+  }
+}
+
+/**
+ * Revision of a document's current content
+ * @param {string} docPath
+ * @returns {string}
+ */
+function getDocRevision(docPath) {
+  return handleResult(native.get().getDocRevision(docPath));
+}
+
+/**
+ * Three-way merge of two edits of the same document
+ * @param {{ base: string, ours: string, theirs: string }} options
+ * @returns {{ merged: string, conflicts: number }}
+ */
+function mergeDocContent(options) {
+  return handleResult(native.get().mergeDocContent(options));
+}
+
+/**
+ * Frontmatter fields of a document (`{}` when it has none)
+ * @param {string} docPath
+ * @returns {Record<string, any>}
+ */
+function getDocFields(docPath) {
+  return handleResult(native.get().getDocFields(docPath));
+}
+
+/**
+ * One frontmatter field (`null` if not set)
+ * @param {string} docPath
+ * @param {string} key
+ * @returns {any}
+ */
+function getDocField(docPath, key) {
+  return handleResult(native.get().getDocField(docPath, key));
+}
+
+/**
+ * Set a frontmatter field in place, e.g. `setDocField({ docPath, key: 'status', value: 'done' })`.
+ * Other lines and comments in the block are kept as written.
+ *
+ * @param {{ docPath: string, key: string, value: any }} options
+ * @returns {{ rel_path: string, abs_path: string, revision: string }}
+ */
+function setDocField(options) {
+  return withConflictCode(() => native.get().setDocField({
+    docPath: options.docPath,
+    key: options.key,
+    value: options.value ?? null,
+  }));
+}
+
+/**
+ * Checkbox tasks across the workspace, soonest due first
+ * @param {{ folder?: string, done?: boolean, dueBy?: string, limit?: number }} [options]
+ * @returns {Array<{ rel_path: string, line: number, text: string, done: boolean, due: string|null }>}
+ */
+function listTasks(options = {}) {
+  return handleResult(native.get().listTasks({
+    folder: options.folder,
+    done: options.done,
+    dueBy: options.dueBy,
+    limit: options.limit,
+  }));
+}
+
+/**
+ * Tick or untick the checkbox on a line of a document
+ * @param {{ docPath: string, line: number, done: boolean }} options
+ * @returns {{ rel_path: string, abs_path: string, revision: string }}
+ */
+function setTaskDone(options) {
+  return withConflictCode(() => native.get().setTaskDone({
+    docPath: options.docPath,
+    line: options.line,
+    done: options.done,
+  }));
+}
+
+/**
+ * Rebuild the task table from the files on disk (picks up direct edits)
+ * @returns {number} task count
+ */
+function reindexTasks() {
+  return handleResult(native.get().reindexTasks());
+}
+
+/**
+ * Append text to today's inbox note (`inbox/YYYY-MM-DD.md`)
+ * @param {{ text: string, source?: 'text'|'clipboard'|'stdin'|'url'|'agent', url?: string }} options
+ * @returns {{ rel_path: string, created_at: string, source: string, url: string|null }}
+ */
+function capture(options) {
+  return handleResult(native.get().capture({
+    text: options.text,
+    source: options.source || undefined,
+    url: options.url || undefined,
+  }));
+}
+
+/**
+ * Save an agent memory in `.memory/memories.md`
+ * @param {{ text: string, tags?: string[], ttl?: string }} options  ttl like `12h`, `30d`, `2w`
+ * @returns {{ id: string, created_at: string, expires_at: string|null, tags: string[], content: string }}
+ */
+function remember(options) {
+  return handleResult(native.get().remember({
+    text: options.text,
+    tags: options.tags || undefined,
+    ttl: options.ttl || undefined,
+  }));
+}
+
+/**
+ * Live agent memories, oldest first
+ * @param {string} [tag]
+ */
+function listMemories(tag) {
+  return handleResult(native.get().listMemories(tag || undefined));
+}
+
+/** @returns {boolean} false if no memory has that id */
+function forgetMemory(id) {
+  return native.get().forgetMemory(id);
+}
+
+/** @returns {number} expired memories removed */
+function purgeExpiredMemories() {
+  return native.get().purgeExpiredMemories();
+}
+
+/**
+ * Dates mentioned in docs (frontmatter fields, idea entries, inline dates),
+ * in chronological order
+ * @param {{ range?: string, folder?: string, source?: 'frontmatter'|'entry'|'inline', limit?: number }} [options]
+ *   range is `2024`, `2024-Q3`, `2024-07`, `2024-07-15` or `<from>..<to>`
+ * @returns {Array<{ rel_path: string, date: string, source: string, line: number, context: string }>}
+ */
+function listDates(options = {}) {
+  return handleResult(native.get().listDates({
+    range: options.range,
+    folder: options.folder,
+    source: options.source,
+    limit: options.limit,
+  }));
+}
+
+/**
+ * Rebuild the date index from the files on disk
+ * @returns {number} date count
+ */
+function reindexDates() {
+  return handleResult(native.get().reindexDates());
+}
+
+/**
+ * Documents ranked by staleness (age, no recent hits, broken links,
+ * superseded), most stale first
+ * @param {{ folder?: string, minScore?: number, limit?: number }} [options]
+ * @returns {Array<{ rel_path: string, score: number, age_days: number, days_since_hit: number|null, broken_links: number, superseded: boolean, broken: string[], superseded_by: string|null, reasons: string[] }>}
+ */
+function staleDocs(options = {}) {
+  return handleResult(native.get().staleDocs({
+    folder: options.folder,
+    minScore: options.minScore,
+    limit: options.limit,
+  }));
+}
+
+/**
+ * Docs picked at random, optionally from one folder or tag
+ * @param {{ folder?: string, tag?: string, count?: number }} [options]
+ * @returns {Array<{ rel_path: string, description: string, updated_at: string, idle_days: number, times_shown: number, last_shown_at: string|null, excerpt: string }>}
+ */
+function randomDocs(options = {}) {
+  return handleResult(native.get().randomDocs({
+    folder: options.folder,
+    tag: options.tag,
+    count: options.count,
+  }));
+}
+
+/**
+ * Old docs, untouched for minAgeDays (default 90), due to be shown again.
+ * The picks are recorded so they come back after a doubling interval;
+ * dryRun leaves them due.
+ * @param {{ count?: number, minAgeDays?: number, folder?: string, tag?: string, dryRun?: boolean }} [options]
+ * @returns {Array<{ rel_path: string, description: string, updated_at: string, idle_days: number, times_shown: number, last_shown_at: string|null, excerpt: string }>}
+ */
+function resurface(options = {}) {
+  return handleResult(native.get().resurface({
+    count: options.count,
+    minAgeDays: options.minAgeDays,
+    folder: options.folder,
+    tag: options.tag,
+    dryRun: options.dryRun,
+  }));
+}
+
+/**
+ * Flashcards due today or earlier (every card with all), most overdue first
+ * @param {{ folder?: string, all?: boolean, limit?: number }} [options]
+ * @returns {Array<{ id: string, rel_path: string, line: number, kind: 'basic'|'cloze', front: string, back: string, ease: number, interval_days: number, repetitions: number, due: string }>}
+ */
+function listCards(options = {}) {
+  return handleResult(native.get().listCards({
+    folder: options.folder,
+    all: options.all,
+    limit: options.limit,
+  }));
+}
+
+/**
+ * Grade an answer to a card, 0 (forgot) to 5 (perfect), and schedule the
+ * next review with SM-2. Returns the card with its new schedule.
+ * @param {string} cardId
+ * @param {number} grade
+ */
+function reviewCard(cardId, grade) {
+  return handleResult(native.get().reviewCard(cardId, grade));
+}
+
+/**
+ * @returns {{ total: number, due: number, new: number, reviewed_today: number }}
+ */
+function cardStats() {
+  return handleResult(native.get().cardStats());
+}
+
+function reindexCards() {
+  return handleResult(native.get().reindexCards());
+}
+
+/**
+ * Record that docs were opened or returned by a search (feeds staleness)
+ * @param {string[]} relPaths
+ */
+function recordDocHits(relPaths) {
+  return handleResult(native.get().recordDocHits(relPaths));
+}
+
+/**
+ * Dead links and orphan documents
+ * @param {{ folder?: string }} [options] Only report docs under this folder
+ * @returns {{ docs_checked: number, links_checked: number, broken: Array<{ rel_path: string, line: number, target: string, kind: 'wiki'|'markdown'|'doc_id', suggestion: string|null }>, orphans: string[] }}
+ */
+function checkLinks(options = {}) {
+  return handleResult(native.get().checkLinks(options.folder));
+}
+
+/**
+ * @typedef {{ rel_path: string, line: number, kind: 'wiki'|'markdown'|'doc_id', old_target: string, new_target: string }} LinkRewrite
+ */
+
+/**
+ * Links that moving a doc or folder would rewrite, without changing anything.
+ * Renames and moves apply the same rewrites and return them as `links_rewritten`.
+ * @param {{ oldPath: string, newPath: string }} options
+ * @returns {Array<LinkRewrite>}
+ */
+function previewLinkRewrites(options) {
+  return handleResult(native.get().previewLinkRewrites(options.oldPath, options.newPath));
+}
+
+/**
+ * Collect docs created/updated, idea entries and removals since a point in
+ * time into a digest note
+ * @param {{ since: string, folder?: string, summarize?: boolean, write?: boolean, resurface?: number, resurfaceDays?: number }} options
+ *   since is `7d`, `12h`, `2w`, `YYYY-MM-DD` or an ISO timestamp; resurface adds that many
+ *   old notes (untouched for resurfaceDays, default 90)
+ * @returns {Promise<{ digest: object, summary: string|null, markdown: string, saved: { rel_path: string, abs_path: string, revision: string }|null }>}
+ */
+async function createDigest(options) {
+  return handleResult(await native.get().createDigest({
+    since: options.since,
+    folder: options.folder,
+    summarize: options.summarize,
+    write: options.write,
+    resurface: options.resurface,
+    resurfaceDays: options.resurfaceDays,
+  }));
+}
+
+/**
+ * Add text at the end of a heading's section (e.g. a bullet under
+ * "Plan > Steps"). Fails with `code: 'CONFLICT'` if the file changes
+ * while it is being edited.
+ *
+ * @param {{ docPath: string, headingPath: string, text: string }} options
+ * @returns {{ rel_path: string, abs_path: string, revision: string }}
+ */
+function appendToSection(options) {
+  return withConflictCode(() => native.get().appendToSection({
+    docPath: options.docPath,
+    headingPath: options.headingPath,
+    text: options.text,
+  }));
+}
+
+/**
+ * Apply a unified diff to a document
+ * @param {{ docPath: string, patch: string }} options
+ * @returns {{ rel_path: string, abs_path: string, revision: string }}
+ */
+function applyPatch(options) {
+  return withConflictCode(() => native.get().applyPatch({
+    docPath: options.docPath,
+    patch: options.patch,
   }));
 }
 
@@ -251,9 +632,10 @@ function generateManifest(options) {
 /**
  * Reconcile a folder: scan filesystem under `folderPath` and INSERT a
  * `docs` row for every `*.md` that has none. Also DELETE docs rows whose
- * file is no longer on disk. Does NOT rebuild embeddings.
+ * file is no longer on disk. Folders moved on disk as a whole are re-keyed
+ * first (workspace-wide). Does NOT rebuild embeddings.
  * @param {{ folderPath: string }} options
- * @returns {{ added: string[], removed: string[] }}
+ * @returns {{ added: string[], removed: string[], folders_moved: Array<{ old_path: string, new_path: string, links_rewritten: Array<LinkRewrite> }> }}
  */
 function reconcileFolder(options) {
   return handleResult(native.get().reconcileFolder({
@@ -261,6 +643,16 @@ function reconcileFolder(options) {
   }));
 }
 
+/**
+ * Files that changed since the search index last saw them, like
+ * `git status` for the index. `recorded` is false until the first build;
+ * until then every file is listed as new.
+ * @returns {{ recorded: boolean, tracked: number, new: string[], modified: string[], deleted: string[], renamed: Array<{ old_path: string, new_path: string }> }}
+ */
+function indexStatus() {
+  return handleResult(native.get().indexStatus());
+}
+
 /**
  * Suggest folders similar to the given query (normalized _ ↔ - + partial match).
  * @param {{ query: string }} options
@@ -278,6 +670,7 @@ module.exports = {
   getNativeError,
   
   // Store API
+  isReadOnly,
   initEnvironment,
   listFolders,
   createFolder,
@@ -293,9 +686,42 @@ module.exports = {
   getDocMeta,
   getDocByStableId,
   getDocContent,
+  getDocuments,
+  getBlock,
+  getContext,
+  resolveNote,
   saveDocContent,
+  getDocRevision,
+  mergeDocContent,
+  getDocFields,
+  getDocField,
+  setDocField,
+  listTasks,
+  setTaskDone,
+  reindexTasks,
+  capture,
+  remember,
+  listMemories,
+  forgetMemory,
+  purgeExpiredMemories,
+  listDates,
+  reindexDates,
+  createDigest,
+  staleDocs,
+  randomDocs,
+  resurface,
+  listCards,
+  reviewCard,
+  cardStats,
+  reindexCards,
+  recordDocHits,
+  checkLinks,
+  previewLinkRewrites,
+  appendToSection,
+  applyPatch,
   reconcileDoc,
   generateManifest,
   reconcileFolder,
+  indexStatus,
   suggestFolders,
 };
@@ -41,11 +41,46 @@ module.exports = {
   getDocMeta: (o) => dataService.getDocMeta(o),
   getDocByStableId: (id) => dataService.getDocByStableId(id),
   getDocContent: (p) => dataService.getDocContent(p),
+  getDocuments: (paths, maxBytes) => dataService.getDocuments(paths, maxBytes),
+  getBlock: (p, id) => dataService.getBlock(p, id),
+  getContext: (p, line, before, after) => dataService.getContext(p, line, before, after),
+  resolveNote: (q, limit) => dataService.resolveNote(q, limit),
   saveDocContent: (o) => dataService.saveDocContent(o),
+  getDocRevision: (p) => dataService.getDocRevision(p),
+  mergeDocContent: (o) => dataService.mergeDocContent(o),
+  getDocFields: (p) => dataService.getDocFields(p),
+  getDocField: (p, k) => dataService.getDocField(p, k),
+  setDocField: (o) => dataService.setDocField(o),
+  listTasks: (o) => dataService.listTasks(o),
+  setTaskDone: (o) => dataService.setTaskDone(o),
+  reindexTasks: () => dataService.reindexTasks(),
+  capture: (o) => dataService.capture(o),
+  remember: (o) => dataService.remember(o),
+  listMemories: (t) => dataService.listMemories(t),
+  forgetMemory: (id) => dataService.forgetMemory(id),
+  purgeExpiredMemories: () => dataService.purgeExpiredMemories(),
+  listDates: (o) => dataService.listDates(o),
+  reindexDates: () => dataService.reindexDates(),
+  createDigest: (o) => dataService.createDigest(o),
+  staleDocs: (o) => dataService.staleDocs(o),
+  randomDocs: (o) => dataService.randomDocs(o),
+  resurface: (o) => dataService.resurface(o),
+  listCards: (o) => dataService.listCards(o),
+  reviewCard: (id, grade) => dataService.reviewCard(id, grade),
+  cardStats: () => dataService.cardStats(),
+  reindexCards: () => dataService.reindexCards(),
+  recordDocHits: (p) => dataService.recordDocHits(p),
+  checkLinks: (o) => dataService.checkLinks(o),
+  previewLinkRewrites: (o) => dataService.previewLinkRewrites(o),
+  appendToSection: (o) => dataService.appendToSection(o),
+  applyPatch: (o) => dataService.applyPatch(o),
   reconcileDoc: (o) => dataService.reconcileDoc(o),
   generateManifest: (o) => dataService.generateManifest(o),
   reconcileFolder: (o) => dataService.reconcileFolder(o),
+  indexStatus: () => dataService.indexStatus(),
   suggestFolders: (o) => dataService.suggestFolders(o),
+  isReadOnly: () => dataService.isReadOnly(),
+  ReadOnlyError: dataService.ReadOnlyError,
 
   // Constants
   DEFAULT_BASE_ROOT,
@@ -6,6 +6,8 @@ const { StdioServerTransport } = require('@modelcontextprotocol/sdk/server/stdio
 const z = require('zod');
 const store = require('../core/store/index.js');
 const { Searcher, Indexer } = require('../core/search/index.js');
+const acl = require('../core/acl');
+const memoryExpiry = require('../core/memory-expiry');
 
 const server = new McpServer({
   name: 'opencontext-mcp',
@@ -26,6 +28,63 @@ function toToolResponse(data) {
   };
 }
 
+let access;
+let searcher;
+
+/**
+ * A searcher over this server's one set of open indexes and caches; each
+ * call gets its own wrapper so concurrent queries keep their own confidence
+ */
+async function getSearcher() {
+  if (!searcher) {
+    const shared = new Searcher();
+    searcher = shared.initialize().then(() => shared, (err) => {
+      searcher = undefined;
+      throw err;
+    });
+  }
+  return (await searcher).share();
+}
+
+/** Search, reopening the indexes once if another process replaced them */
+async function search(query, options) {
+  const engine = await getSearcher();
+  try {
+    return { engine, results: await engine.search(query, options) };
+  } catch (err) {
+    if (!err.message?.includes('lance error')) throw err;
+    await engine.refresh();
+    return { engine, results: await engine.search(query, options) };
+  }
+}
+
+/** Folders this server's token (OPENCONTEXT_TOKEN) may read */
+function getAccess() {
+  if (access === undefined) access = acl.resolveAccess(process.env.OPENCONTEXT_TOKEN);
+  if (!access) throw new Error('OPENCONTEXT_TOKEN is missing or not permitted by acl.json');
+  return access;
+}
+
+/**
+ * Wrap a mutating tool so read-only workspaces answer with a typed tool
+ * error (`code: "READ_ONLY"`) instead of an opaque exception. Paths hidden
+ * from this server's token cannot be written either.
+ */
+function writeTool(handler) {
+  return async (args) => {
+    const target = args.doc_path ?? args.folder_path;
+    if (target !== undefined) acl.assertAllowed(getAccess(), target);
+    try {
+      return await handler(args);
+    } catch (err) {
+      if (err && err.code === 'READ_ONLY') {
+        return { ...toToolResponse({ error: err.message, code: err.code }), isError: true };
+      }
+      throw err;
+    }
+  };
+}
+
 server.registerTool(
   'oc_list_folders',
   {
@@ -36,7 +95,7 @@ server.registerTool(
   },
   async ({ scope }) => {
     const folders = store.listFolders({ all: scope === 'all' });
-    return toToolResponse(folders);
+    return toToolResponse(acl.filterFolders(getAccess(), folders));
   }
 );
 
@@ -50,8 +109,11 @@ server.registerTool(
     })
   },
   async ({ folder_path, recursive }) => {
+    if (!acl.showsFolder(getAccess(), folder_path)) {
+      throw new Error(`Folder not found: ${folder_path}`);
+    }
     const docs = store.listDocs({ folderPath: folder_path, recursive: Boolean(recursive) });
-    return toToolResponse(docs);
+    return toToolResponse(acl.filterDocs(getAccess(), docs));
   }
 );
 
@@ -66,10 +128,10 @@ server.registerTool(
       description: z.string().optional().describe('Document description (1-2 sentences: what is inside, why it exists, when to use it)')
     })
   },
-  async ({ folder_path, doc_name, description }) => {
+  writeTool(async ({ folder_path, doc_name, description }) => {
     const result = store.createDoc({ folderPath: folder_path, name: doc_name, description: description || '' });
     return toToolResponse(result);
-  }
+  })
 );
 
 server.registerTool(
@@ -82,10 +144,10 @@ server.registerTool(
       description: z.string().describe('New description text')
     })
   },
-  async ({ doc_path, description }) => {
+  writeTool(async ({ doc_path, description }) => {
     const result = store.setDocDescription({ docPath: doc_path, description });
     return toToolResponse(result);
-  }
+  })
 );
 
 server.registerTool(
@@ -100,19 +162,243 @@ server.registerTool(
     inputSchema: z.object({
       doc_path: z.string().min(1).describe('Document path relative to contexts/, e.g. "project-a/plan.md"'),
       content: z.string().describe('Full new file content (replaces existing body). Hard cap ≈25 KB — for larger files use oc_reconcile_doc after disk edit.'),
-      description: z.string().optional().describe('Optional new description; leave empty to keep current')
+      description: z.string().optional().describe('Optional new description; leave empty to keep current'),
+      expected_revision: z.string().optional().describe('Revision returned by oc_get_context when you read the doc. If the file changed since, the save is refused with error CONFLICT instead of overwriting someone else\'s edit.')
     })
   },
-  async ({ doc_path, content, description }) => {
-    const result = store.saveDocContent({
-      docPath: doc_path,
-      content,
-      description,
+  writeTool(async ({ doc_path, content, description, expected_revision }) => {
+    try {
+      const result = store.saveDocContent({
+        docPath: doc_path,
+        content,
+        description,
+        expectedRevision: expected_revision,
+      });
+      return toToolResponse(result);
+    } catch (err) {
+      if (err && err.code === 'CONFLICT') {
+        return {
+          ...toToolResponse({
+            error: 'CONFLICT',
+            message: `${err.message}. Re-read the doc with oc_get_context, apply your change to the current content, and save again with the new revision.`,
+            revision: store.getDocRevision(doc_path),
+          }),
+          isError: true,
+        };
+      }
+      throw err;
+    }
+  })
+);
+
+server.registerTool(
+  'oc_list_tasks',
+  {
+    description:
+      'List checkbox tasks (`- [ ]` / `- [x]` lines) collected from all documents, soonest due date first. Each task has rel_path + line so you can open the doc or tick it with oc_set_task_done. Due dates come from inline `due:YYYY-MM-DD`, `@due(YYYY-MM-DD)` or `📅 YYYY-MM-DD` in the task text.',
+    inputSchema: z.object({
+      status: z.enum(['open', 'done', 'all']).optional().describe('Default "open"'),
+      folder_path: z.string().optional().describe('Only tasks in docs under this folder, e.g. "projects"'),
+      due_by: z.string().optional().describe('Only tasks due on or before this date (YYYY-MM-DD)'),
+      limit: z.number().int().positive().optional().describe('Maximum number of tasks (default 100)')
+    })
+  },
+  async ({ status, folder_path, due_by, limit }) => {
+    const tasks = store.listTasks({
+      folder: folder_path,
+      done: status === 'all' ? undefined : status === 'done',
+      dueBy: due_by,
+      limit: limit ?? 100,
     });
+    return toToolResponse(acl.filterDocs(getAccess(), tasks));
+  }
+);
+
+server.registerTool(
+  'oc_set_task_done',
+  {
+    description:
+      'Tick (done=true) or untick (done=false) the checkbox of one task, identified by doc_path and line from oc_list_tasks. Only the `[ ]`/`[x]` mark changes. Fails if that line is not a task (the doc changed) — list tasks again and retry.',
+    inputSchema: z.object({
+      doc_path: z.string().min(1).describe('Document path relative to contexts/'),
+      line: z.number().int().positive().describe('1-based line number from oc_list_tasks'),
+      done: z.boolean().optional().describe('Default true')
+    })
+  },
+  writeTool(async ({ doc_path, line, done }) => {
+    const result = store.setTaskDone({ docPath: doc_path, line, done: done ?? true });
     return toToolResponse(result);
+  })
+);
+
+server.registerTool(
+  'oc_capture',
+  {
+    description:
+      'Jot something down without deciding where it belongs: the text is appended to today\'s inbox note (inbox/YYYY-MM-DD.md) under a timestamped heading and is searchable within seconds. Use it for loose findings, links and to-dos the user wants kept; use oc_save_doc or oc_append_to_section when you know the right document.',
+    inputSchema: z.object({
+      text: z.string().min(1).describe('Markdown to capture'),
+      url: z.string().optional().describe('Page the text came from, recorded as its source')
+    })
+  },
+  writeTool(async ({ text, url }) => {
+    acl.assertAllowed(getAccess(), 'inbox');
+    return toToolResponse(store.capture({ text, source: url ? 'url' : 'agent', url }));
+  })
+);
+
+const MEMORY_DOC = '.memory/memories.md';
+
+server.registerTool(
+  'oc_remember',
+  {
+    description:
+      'Save a fact or preference worth keeping across conversations (e.g. "user prefers British spelling", "staging deploys need the VPN"). Memories are stored in .memory/memories.md, indexed as doc type "memory" and found again with oc_recall or oc_search (filter "type:memory"). Give a ttl for things that stop being true, such as "on call this week".',
+    inputSchema: z.object({
+      text: z.string().min(1).describe('The fact to remember, as one self-contained statement'),
+      tags: z.array(z.string()).optional().describe('Tags for filtering, e.g. ["prefs", "deploy"]'),
+      ttl: z.string().optional().describe('Forget after this long: "12h", "30d", "2w". Default: never')
+    })
+  },
+  writeTool(async ({ text, tags, ttl }) => {
+    acl.assertAllowed(getAccess(), MEMORY_DOC);
+    const memory = store.remember({ text, tags, ttl });
+    if (memory.expires_at) memoryExpiry.schedule();
+    return toToolResponse(memory);
+  })
+);
+
+server.registerTool(
+  'oc_recall',
+  {
+    description:
+      'Find saved memories relevant to a query, best match first. Expired memories are never returned. Call it at the start of a task to pick up user preferences and facts saved with oc_remember. Without a query, lists all live memories.',
+    inputSchema: z.object({
+      query: z.string().optional().describe('What you want to know, e.g. "deploy steps" or "writing style"'),
+      tag: z.string().optional().describe('Only memories with this tag'),
+      limit: z.number().int().positive().optional().describe('Maximum number of memories (default 10)')
+    })
+  },
+  async ({ query, tag, limit }) => {
+    acl.assertAllowed(getAccess(), MEMORY_DOC);
+    const max = limit ?? 10;
+    const live = store.listMemories(tag);
+    if (!query) return toToolResponse({ memories: live.slice(-max).reverse() });
+
+    const byId = new Map(live.map((m) => [m.id, m]));
+    const memories = [];
+    try {
+      const { results: hits } = await search(query, {
+        limit: max,
+        filter: 'type:memory',
+        allowedFolders: acl.searchFolders(getAccess()),
+      });
+      for (const hit of hits) {
+        const memory = byId.get(hit.entry_id);
+        if (!memory) continue;
+        byId.delete(memory.id);
+        memories.push({ ...memory, score: hit.score });
+      }
+    } catch {
+      // No index yet: fall back to word matching below
+    }
+    // Memories saved since the last index update are not searchable yet
+    const words = query.toLowerCase().split(/\s+/).filter(Boolean);
+    for (const memory of byId.values()) {
+      if (memories.length >= max) break;
+      const text = `${memory.content} ${memory.tags.join(' ')}`.toLowerCase();
+      if (words.some((w) => text.includes(w))) memories.push(memory);
+    }
+    return toToolResponse({ query, memories });
   }
 );
 
+server.registerTool(
+  'oc_forget',
+  {
+    description: 'Delete one saved memory by the id returned from oc_remember or oc_recall, e.g. when the user says a preference changed.',
+    inputSchema: z.object({
+      id: z.string().min(1).describe('Memory id')
+    })
+  },
+  writeTool(async ({ id }) => {
+    acl.assertAllowed(getAccess(), MEMORY_DOC);
+    return toToolResponse({ id, forgotten: store.forgetMemory(id) });
+  })
+);
+
+server.registerTool(
+  'oc_timeline',
+  {
+    description:
+      'List every date mentioned in documents within a period, in chronological order: date-valued frontmatter fields (source "frontmatter", context is the field name), idea entry timestamps ("entry") and YYYY-MM-DD dates written in the text ("inline", context is the line). Use it for questions like "what happened in 2024-Q3" or "what is scheduled for next month".',
+    inputSchema: z.object({
+      range: z.string().min(1).describe('"2024", "2024-Q3", "2024-07", "2024-07-15", or "<from>..<to>" combining any of those, e.g. "2024-06..2024-Q3"'),
+      folder_path: z.string().optional().describe('Only docs under this folder, e.g. "projects"'),
+      source: z.enum(['frontmatter', 'entry', 'inline']).optional().describe('Only dates of this kind'),
+      limit: z.number().int().positive().optional().describe('Maximum number of dates (default 200)')
+    })
+  },
+  async ({ range, folder_path, source, limit }) => {
+    const dates = store.listDates({ range, folder: folder_path, source, limit: limit ?? 200 });
+    return toToolResponse(acl.filterDocs(getAccess(), dates));
+  }
+);
+
+server.registerTool(
+  'oc_set_doc_field',
+  {
+    description:
+      'Set one frontmatter field of a document (the YAML block between --- lines at the top), e.g. status: done. Only that field\'s line changes; other fields, comments and the body are kept exactly. A frontmatter block is added if the doc has none. Use this instead of oc_save_doc to flip a status or owner — it is cheaper and does not re-embed the document.',
+    inputSchema: z.object({
+      doc_path: z.string().min(1).describe('Document path relative to contexts/, e.g. "project-a/plan.md"'),
+      key: z.string().min(1).describe('Top-level field name, e.g. "status"'),
+      value: z.union([z.string(), z.number(), z.boolean(), z.array(z.union([z.string(), z.number(), z.boolean()])), z.null()]).describe('New value; strings are quoted only when YAML needs it')
+    })
+  },
+  writeTool(async ({ doc_path, key, value }) => {
+    const result = store.setDocField({ docPath: doc_path, key, value });
+    return toToolResponse({ ...result, fields: store.getDocFields(doc_path) });
+  })
+);
+
+server.registerTool(
+  'oc_append_to_section',
+  {
+    description:
+      'Add text at the end of one section of a document, e.g. a new bullet under "## Decisions", without resending the rest of the file. The section runs until the next heading of the same or higher level, so text lands after any subsections. Keeps SQLite and search embeddings in sync like oc_save_doc.',
+    inputSchema: z.object({
+      doc_path: z.string().min(1).describe('Document path relative to contexts/, e.g. "project-a/plan.md"'),
+      heading_path: z.string().min(1).describe('Heading to append under, as in search results: "Plan > Decisions", or just "Decisions" when unambiguous. Case-insensitive.'),
+      text: z.string().min(1).describe('Markdown to insert, e.g. "- Switched to Postgres (2026-10-16)"')
+    })
+  },
+  writeTool(async ({ doc_path, heading_path, text }) => {
+    const result = store.appendToSection({
+      docPath: doc_path,
+      headingPath: heading_path,
+      text,
+    });
+    return toToolResponse(result);
+  })
+);
+
+server.registerTool(
+  'oc_apply_patch',
+  {
+    description:
+      'Apply a unified diff (`diff -u` / `git diff` format, @@ hunks with context lines) to a document. Cheaper than oc_save_doc for small edits to large files. Hunks are located by their context, so line numbers may be approximate; if a hunk\'s context is not found the whole patch is rejected and the doc is left unchanged — re-read it with oc_get_context and rebuild the diff.',
+    inputSchema: z.object({
+      doc_path: z.string().min(1).describe('Document path relative to contexts/, e.g. "project-a/plan.md"'),
+      patch: z.string().min(1).describe('Unified diff against the current content; file header lines (---/+++) are optional')
+    })
+  },
+  writeTool(async ({ doc_path, patch }) => {
+    const result = store.applyPatch({ docPath: doc_path, patch });
+    return toToolResponse(result);
+  })
+);
+
 server.registerTool(
   'oc_reconcile_doc',
   {
@@ -125,13 +411,13 @@ server.registerTool(
       description: z.string().optional().describe('Optional new description; omit to keep current')
     })
   },
-  async ({ doc_path, description }) => {
+  writeTool(async ({ doc_path, description }) => {
     const result = store.reconcileDoc({
       docPath: doc_path,
       description,
     });
     return toToolResponse(result);
-  }
+  })
 );
 
 server.registerTool(
@@ -143,10 +429,10 @@ server.registerTool(
       doc_path: z.string().min(1).describe('Document path relative to contexts/, e.g. "project-a/plan.md"')
     })
   },
-  async ({ doc_path }) => {
+  writeTool(async ({ doc_path }) => {
     const result = store.removeDoc({ docPath: doc_path });
     return toToolResponse(result);
-  }
+  })
 );
 
 server.registerTool(
@@ -165,12 +451,17 @@ server.registerTool(
     })
   },
   async ({ folder_path, limit }) => {
+    if (!acl.showsFolder(getAccess(), folder_path)) {
+      throw new Error(`Folder "${folder_path}" not found.`);
+    }
     let result;
     try {
       result = store.generateManifest({ folderPath: folder_path, limit });
     } catch (err) {
       if (err && err.message && err.message.includes('does not exist')) {
-        const suggestions = store.suggestFolders({ query: folder_path });
+        const suggestions = store
+          .suggestFolders({ query: folder_path })
+          .filter((s) => acl.showsFolder(getAccess(), s));
         if (suggestions && suggestions.length > 0) {
           const list = suggestions.map(s => `"${s}"`).join(', ');
           throw new Error(
@@ -181,9 +472,11 @@ server.registerTool(
       }
       throw err;
     }
-    const unindexed = result.unindexed_files || [];
+    const unindexed = (result.unindexed_files || []).filter((f) =>
+      acl.allows(getAccess(), path.posix.join(folder_path, f))
+    );
     const payload = {
-      items: result.items,
+      items: acl.filterDocs(getAccess(), result.items),
       unindexed_files: unindexed,
     };
     if (unindexed.length > 0) {
@@ -208,21 +501,27 @@ server.registerTool(
   'oc_reconcile_folder',
   {
     description:
-      'Two-way reconcile of `*.md` files under ~/.opencontext/contexts/<folder_path> with the SQLite index. (a) INSERTs docs rows for files present on disk but missing from the index — e.g. created via Write/Edit, or pulled from git. (b) DELETEs docs rows whose file no longer exists on disk — e.g. removed via plain `rm`. Fast — does NOT recompute embeddings (run `oc index build` for that). Returns { added, removed, count }. Use after oc_manifest reports `unindexed_files`, after pulling docs from git, or after rm-ing files outside of oc_delete_doc.',
+      'Two-way reconcile of `*.md` files under ~/.opencontext/contexts/<folder_path> with the SQLite index. (a) INSERTs docs rows for files present on disk but missing from the index — e.g. created via Write/Edit, or pulled from git. (b) DELETEs docs rows whose file no longer exists on disk — e.g. removed via plain `rm`. A folder moved or renamed on disk as a whole (plain `mv`) is detected and re-keyed in place, keeping stable ids and rewriting links to it. Fast — does NOT recompute embeddings (run `oc index build` for that). Returns { added, removed, folders_moved, count }. Use after oc_manifest reports `unindexed_files`, after pulling docs from git, or after rm-ing files outside of oc_delete_doc.',
     inputSchema: z.object({
       folder_path: z.string().min(1).describe('Folder path relative to contexts/')
     })
   },
-  async ({ folder_path }) => {
+  writeTool(async ({ folder_path }) => {
     const report = store.reconcileFolder({ folderPath: folder_path });
     const added = report.added || [];
     const removed = report.removed || [];
+    const foldersMoved = (report.folders_moved || []).map((m) => ({
+      old_path: m.old_path,
+      new_path: m.new_path,
+      links_rewritten: m.links_rewritten.length,
+    }));
     const total = added.length + removed.length;
     let hint;
-    if (total === 0) {
+    if (total === 0 && foldersMoved.length === 0) {
       hint = 'No drift detected.';
     } else {
       const parts = [];
+      if (foldersMoved.length > 0) parts.push(`${foldersMoved.length} folder(s) moved on disk were re-keyed in place (stable ids kept).`);
       if (added.length > 0) parts.push('Run `oc index build` to compute embeddings for the new docs.');
       if (removed.length > 0) parts.push(`${removed.length} stale index entry/entries pruned (file(s) gone from disk).`);
       hint = parts.join(' ');
@@ -230,38 +529,51 @@ server.registerTool(
     const payload = {
       added,
       removed,
+      folders_moved: foldersMoved,
       count: total,
       hint,
     };
     return toToolResponse(payload);
-  }
+  })
 );
 
 // ===== P0: oc_search =====
 server.registerTool(
   'oc_search',
   {
-    description: 'Search OpenContext documents by CONTENT using hybrid semantic + keyword search (BM25 + vector embeddings, RRF fusion). Understands natural language queries — not just exact keywords. Does NOT match folder names or file names — to browse a known project use oc_manifest({ folder_path: "project-name" }) instead. Returns matching content/docs/folders with file paths and stable_ids for citation. Default mode is "hybrid" (recommended); use "vector" for pure semantic similarity, "keyword" for exact BM25 only.',
+    description: 'Search OpenContext documents by CONTENT using hybrid semantic + keyword search (BM25 + vector embeddings, RRF fusion). Understands natural language queries — not just exact keywords. Does NOT match folder names or file names — to browse a known project use oc_manifest({ folder_path: "project-name" }) instead. Returns matching content/docs/folders with file paths and stable_ids for citation. Default mode is "hybrid" (recommended); use "vector" for pure semantic similarity, "keyword" for exact BM25 only, "auto" to route by query intent (file names → path match, identifiers → exact keyword, questions → hybrid). The response\'s "confidence" (level high | medium | low, with missing_terms) tells whether the notes cover the query at all: on "low", say the notes don\'t cover it instead of answering from weak hits. "degraded": true means semantic search was unavailable and only keyword results were returned. "has_more": true means more than "count" results matched ("total_candidates"); raise limit to see them. "suggestions" are spelling corrections of the query ("did you mean"); retry with one when the query has a typo. "related" lists follow-up queries (earlier searches and co-occurring terms) worth running to explore further.',
     inputSchema: z.object({
       query: z.string().min(1).describe('Search query (keywords or natural language)'),
       limit: z.number().int().positive().optional().describe('Number of results (default 5)'),
-      mode: z.enum(['hybrid', 'vector', 'keyword']).optional().describe('Search mode (default hybrid)'),
+      mode: z.enum(['hybrid', 'vector', 'keyword', 'auto']).optional().describe('Search mode (default hybrid)'),
       type: z.enum(['content', 'doc', 'folder']).optional().describe('Aggregation type (default content)'),
       folder_filter: z.string().optional().describe('Restrict search to this folder prefix, e.g. "Product/opencontext". Keeps results scoped to one project.'),
+      include_sources: z.array(z.string()).optional().describe('Only use results from these sources: folders (e.g. "projects"), doc types (doc, idea, memory, ...) or provenance source types and origins (markdown for notes written in the workspace, capture, evernote, confluence, github, jira, web_clip, ocr, transcript, ...). E.g. ["markdown"] for curated notes only.'),
+      exclude_sources: z.array(z.string()).optional().describe('Never use results from these sources (same values as include_sources), e.g. ["web_clip", "inbox"].'),
       min_score: z.number().min(0).max(1).optional().describe('Minimum relevance score 0–1. Results below this are dropped. Recommended: 0.3–0.5 to cut noise.'),
       date_from: z.string().optional().describe('Filter idea entries on or after this date (YYYY-MM-DD). Only affects idea/journal docs.'),
       date_to: z.string().optional().describe('Filter idea entries on or before this date (YYYY-MM-DD). Only affects idea/journal docs.'),
-      include_neighbors: z.number().int().min(0).max(3).optional().describe('Include N neighboring chunks around each top match for richer context (0=disabled, 1=recommended). Stitches surrounding paragraphs into the result content.')
+      include_neighbors: z.number().int().min(0).max(3).optional().describe('Include N neighboring chunks around each top match for richer context (0=disabled, 1=recommended). Stitches surrounding paragraphs into the result content.'),
+      filter: z.string().optional().describe('Filter expression: space-separated field:value terms that must all match, e.g. "tag:rust path:projects/ created:>2024-01-01". Fields: path, tag, type (doc|idea|memory|session|commit|pull_request), created, modified (YYYY-MM-DD, >, <, from..to), or any frontmatter key. Use OR between terms, -term to exclude.'),
+      fields: z.array(z.string()).optional().describe('Only return these result fields, e.g. ["file_path", "score", "snippet"]. "snippet" is a short excerpt; omit "content" to skip full chunk text when you will fetch docs with oc_get_context anyway.'),
+      sort_by: z.enum(['score', 'modified', 'created', 'path', 'entry_date']).optional().describe('Order results by relevance (default), file modification or creation time, path, or idea entry date. Use "modified" for "most recent notes about X".'),
+      sort_order: z.enum(['asc', 'desc']).optional().describe('Sort direction. Default: newest first for dates, A to Z for path.'),
+      citation: z.enum(['link', 'footnote']).optional().describe('Add a ready-to-paste "citation" to each result: "link" gives [Title](path#L12-L30), "footnote" gives [^1]: [Title](path#L12-L30) numbered by rank. Use it to cite retrieved context in documents you write.'),
+      facets: z.boolean().optional().describe('Also return "facets": match counts by folder, doc_type, tag and month, to see where the matches cluster before narrowing with folder_filter or filter.')
     }),
     outputSchema: z.object({
       query: z.string(),
       count: z.number(),
+      total_candidates: z.number().optional(),
+      total_exact: z.boolean().optional(),
+      has_more: z.boolean().optional(),
       mode: z.string().optional(),
       aggregate_by: z.string().optional(),
       results: z.array(z.object({
         file_path: z.string(),
-        display_name: z.string(),
-        content: z.string(),
+        display_name: z.string().optional(),
+        content: z.string().optional(),
+        snippet: z.string().optional(),
         score: z.number(),
         matched_by: z.string(),
         heading_path: z.string().optional(),
@@ -270,26 +582,64 @@ server.registerTool(
         entry_date: z.string().optional(),
         entry_id: z.string().optional(),
         hit_count: z.number().optional(),
-        folder_path: z.string().optional()
+        folder_path: z.string().optional(),
+        citation: z.string().optional(),
+        provenance: z.object({
+          source: z.string(),
+          origin: z.string().optional(),
+          source_url: z.string().optional(),
+          chunker: z.string()
+        }).optional(),
+        trust: z.number().optional()
       })),
+      confidence: z.object({
+        score: z.number(),
+        level: z.enum(['high', 'medium', 'low']),
+        term_coverage: z.number(),
+        missing_terms: z.array(z.string())
+      }).optional(),
+      diagnostics: z.object({
+        index_exists: z.boolean(),
+        chunks: z.number(),
+        filtered_out: z.number(),
+        known_terms: z.array(z.string()),
+        absent_terms: z.array(z.string()),
+        nearest_terms: z.record(z.string(), z.array(z.string())).optional()
+      }).optional(),
+      degraded: z.boolean().optional(),
+      facets: z.record(z.string(), z.array(z.object({
+        value: z.string(),
+        count: z.number()
+      }))).optional(),
+      suggestions: z.array(z.string()).optional(),
+      related: z.array(z.string()).optional(),
       index_missing: z.boolean().optional(),
       error: z.string().optional()
     })
   },
-  async ({ query, limit, mode, type, folder_filter, min_score, date_from, date_to, include_neighbors }) => {
+  async ({ query, limit, mode, type, folder_filter, include_sources, exclude_sources, min_score, date_from, date_to, include_neighbors, filter, fields, sort_by, sort_order, citation, facets }) => {
     try {
-      const searcher = new Searcher();
-      const results = await searcher.search(query, {
+      const { engine, results } = await search(query, {
         limit: limit ?? 5,
         mode: mode ?? 'hybrid',
         aggregateBy: type ?? 'content',
         folderFilter: folder_filter,
+        includeSources: include_sources,
+        excludeSources: exclude_sources,
         minScore: min_score,
         dateFrom: date_from,
         dateTo: date_to,
         includeNeighbors: include_neighbors,
+        filter,
+        fields,
+        sortBy: sort_by,
+        sortOrder: sort_order,
+        citation,
+        facets,
+        allowedFolders: acl.searchFolders(getAccess()),
       });
-      const jsonOutput = searcher.formatResultsJson(query, results, {
+      store.recordDocHits(results.map((r) => r.file_path || r.filePath));
+      const jsonOutput = engine.formatResultsJson(query, results, {
         mode: mode ?? 'hybrid',
         aggregateBy: type ?? 'content'
       });
@@ -307,6 +657,26 @@ server.registerTool(
   }
 );
 
+// ===== P1: oc_suggest =====
+server.registerTool(
+  'oc_suggest',
+  {
+    description: 'Complete a partial query from indexed document titles, headings and #tags (prefix match, most frequent first). Cheap — no embedding call. Use it to discover the exact names used in the corpus before running oc_search.',
+    inputSchema: z.object({
+      prefix: z.string().min(1).describe('Partial query, e.g. "auth des"'),
+      limit: z.number().int().positive().optional().describe('Number of suggestions (default 10)')
+    })
+  },
+  async ({ prefix, limit }) => {
+    // Suggestions come from titles across the whole index
+    if (acl.searchFolders(getAccess())) {
+      return toToolResponse({ prefix, suggestions: [] });
+    }
+    const suggestions = await (await getSearcher()).suggest(prefix, limit ?? 10);
+    return toToolResponse({ prefix, suggestions });
+  }
+);
+
 // ===== P1: oc_resolve =====
 server.registerTool(
   'oc_resolve',
@@ -318,6 +688,7 @@ server.registerTool(
   },
   async ({ stable_id }) => {
     const doc = store.getDocByStableId(stable_id);
+    acl.assertAllowed(getAccess(), doc.rel_path);
     return toToolResponse({
       stable_id: doc.stable_id,
       rel_path: doc.rel_path,
@@ -339,6 +710,7 @@ server.registerTool(
     })
   },
   async ({ doc_path, label }) => {
+    acl.assertAllowed(getAccess(), doc_path);
     const doc = store.getDocMeta({ docPath: doc_path });
     if (!doc.stable_id) {
       throw new Error('stable_id not found. Run `oc init` to ensure schema migration has completed.');
@@ -365,14 +737,14 @@ server.registerTool(
       description: z.string().optional().describe('Optional folder description')
     })
   },
-  async ({ folder_path, description }) => {
+  writeTool(async ({ folder_path, description }) => {
     const result = store.createFolder({ path: folder_path, description: description || '' });
     return toToolResponse({
       rel_path: result.rel_path,
       abs_path: result.abs_path,
       description: result.description || ''
     });
-  }
+  })
 );
 
 // ===== P2: oc_index_status =====
@@ -418,13 +790,16 @@ server.registerTool(
 server.registerTool(
   'oc_get_context',
   {
-    description: 'Fetch full document content by stable_id or doc_path. Standard two-step workflow: oc_search → pick relevant docs → oc_get_context to read full text. Avoids token bloat from passing full content through oc_search results. Prefer stable_id (from oc://doc/<id> links or search results) over doc_path — stable_id survives renames.',
+    description: 'Fetch full document content by stable_id or doc_path. Standard two-step workflow: oc_search → pick relevant docs → oc_get_context to read full text. Avoids token bloat from passing full content through oc_search results. Prefer stable_id (from oc://doc/<id> links or search results) over doc_path — stable_id survives renames. Pass `line` (e.g. a hit\'s line_start) to get only the lines from line - before to line + after, with the heading path they sit under and the text just above (previous) and below (next) up to the nearest heading; to read further, call again with before/after widened to previous.line_start or next.line_end.',
     inputSchema: z.object({
       stable_id: z.string().uuid().optional().describe('Document stable_id (UUID) from oc_search result or oc://doc/<id> link'),
-      doc_path: z.string().optional().describe('Document path relative to contexts/, e.g. "Product/opencontext/guide"')
+      doc_path: z.string().optional().describe('Document path relative to contexts/, e.g. "Product/opencontext/guide"'),
+      line: z.number().int().min(1).optional().describe('Return only the lines around this 1-based line instead of the full document'),
+      before: z.number().int().min(0).optional().describe('Lines to include before `line` (default 0)'),
+      after: z.number().int().min(0).optional().describe('Lines to include after `line` (default 0)')
     })
   },
-  async ({ stable_id, doc_path }) => {
+  async ({ stable_id, doc_path, line, before, after }) => {
     if (!stable_id && !doc_path) {
       throw new Error('Provide either stable_id or doc_path');
     }
@@ -434,6 +809,16 @@ server.registerTool(
     } else {
       meta = store.getDocMeta({ docPath: doc_path });
     }
+    acl.assertAllowed(getAccess(), meta.rel_path);
+    store.recordDocHits([meta.rel_path]);
+    if (line !== undefined) {
+      return toToolResponse({
+        stable_id: meta.stable_id,
+        rel_path: meta.rel_path,
+        revision: store.getDocRevision(meta.rel_path),
+        ...store.getContext(meta.rel_path, line, before ?? 0, after ?? 0)
+      });
+    }
     const content = store.getDocContent(meta.abs_path);
     return toToolResponse({
       stable_id: meta.stable_id,
@@ -441,11 +826,80 @@ server.registerTool(
       abs_path: meta.abs_path,
       description: meta.description || '',
       updated_at: meta.updated_at,
+      revision: store.getDocRevision(meta.rel_path),
       content
     });
   }
 );
 
+server.registerTool(
+  'oc_get_documents',
+  {
+    description: 'Fetch several documents by doc_path in one call, e.g. the sources of the hits you picked from oc_search, instead of calling oc_get_context once per document. Each entry carries either content (with its revision) or an error, so one missing path does not fail the others. Contents are capped in total at max_bytes: a document that would go over comes back with over_limit: true and no content — fetch it alone with oc_get_context.',
+    inputSchema: z.object({
+      doc_paths: z.array(z.string().min(1)).min(1).max(50).describe('Document paths relative to contexts/, in the order to return them'),
+      max_bytes: z.number().int().min(1).optional().describe('Limit on the total size of the contents returned (default 262144)')
+    })
+  },
+  async ({ doc_paths, max_bytes }) => {
+    const access = getAccess();
+    const allowed = doc_paths.filter((p) => acl.allows(access, p));
+    const fetched = new Map(store.getDocuments(allowed, max_bytes).map((d) => [d.doc_path, d]));
+    const documents = [...new Set(doc_paths)].map(
+      (doc_path) =>
+        fetched.get(doc_path) || {
+          doc_path,
+          doc: null,
+          content: null,
+          revision: null,
+          // Same answer as for a missing doc, so access rules don't leak which exist
+          error: `Document "${doc_path}" not found.`,
+          over_limit: false
+        }
+    );
+    const read = documents.filter((d) => d.content !== null).map((d) => d.doc.rel_path);
+    if (read.length > 0) {
+      store.recordDocHits(read);
+    }
+    return toToolResponse({ documents });
+  }
+);
+
+server.registerTool(
+  'oc_get_block',
+  {
+    description: 'Fetch one paragraph or list item of a document by its block id (the `^block-id` marker at the end of the block; search hits that match inside a block carry it as block_id). Use it to quote or cite exact text: unlike line numbers, a block id stays with its text as the document is edited.',
+    inputSchema: z.object({
+      doc_path: z.string().min(1).describe('Document path relative to contexts/, e.g. "project-a/plan.md"'),
+      block_id: z.string().min(1).describe('Block id, with or without the leading "^"')
+    })
+  },
+  async ({ doc_path, block_id }) => {
+    acl.assertAllowed(getAccess(), doc_path);
+    const block = store.getBlock(doc_path, block_id);
+    store.recordDocHits([doc_path]);
+    return toToolResponse({ doc_path, ...block });
+  }
+);
+
+server.registerTool(
+  'oc_resolve_note',
+  {
+    description: 'Find documents by the name people call them: title (frontmatter title or # heading), frontmatter aliases, or file name, tolerating typos and partial names. Use it when the user refers to a note by name ("the Q3 roadmap", "[[Launch Plan]]") and you need its doc_path; use oc_search to find notes by what they say.',
+    inputSchema: z.object({
+      name: z.string().min(1).describe('Name as the user wrote it, e.g. "q3 roadmap"'),
+      limit: z.number().int().min(1).max(50).optional().describe('Maximum matches (default 5)')
+    })
+  },
+  async ({ name, limit }) => {
+    const matches = store
+      .resolveNote(name, 50)
+      .filter((m) => acl.allows(getAccess(), m.rel_path))
+      .slice(0, limit ?? 5);
+    return toToolResponse({ name, matches });
+  }
+);
+
 // ===== P2: oc_index_flush =====
 server.registerTool(
   'oc_index_flush',
@@ -472,6 +926,66 @@ server.registerTool(
   }
 );
 
+// ===== P2: scratch index (session-scoped) =====
+let scratch;
+
+/** In-memory index for this server process; dropped when the session ends */
+async function getScratch() {
+  if (!scratch) scratch = await (await getSearcher()).ephemeral();
+  return scratch;
+}
+
+server.registerTool(
+  'oc_scratch_add',
+  {
+    description: 'Add a text (conversation transcript, pasted logs, notes the user shared) to a scratch index that lives only for this session and is never written to the workspace. Re-adding under the same name replaces it. Search it with oc_scratch_search.',
+    inputSchema: z.object({
+      name: z.string().min(1).describe('Name to find the text under, e.g. "transcript"'),
+      content: z.string().min(1).describe('Text to index')
+    })
+  },
+  async ({ name, content }) => {
+    const index = await getScratch();
+    const chunks = await index.add(name, content);
+    return toToolResponse({ name, chunks, sources: await index.sources() });
+  }
+);
+
+server.registerTool(
+  'oc_scratch_search',
+  {
+    description: 'Search the texts added with oc_scratch_add (hybrid semantic + keyword). Results name the text in file_path.',
+    inputSchema: z.object({
+      query: z.string().min(1).describe('Search query'),
+      limit: z.number().int().positive().optional().describe('Number of results (default 5)')
+    })
+  },
+  async ({ query, limit }) => {
+    const index = await getScratch();
+    const results = await index.search(query, { limit: limit ?? 5 });
+    return toToolResponse({ query, count: results.length, results });
+  }
+);
+
+server.registerTool(
+  'oc_scratch_clear',
+  {
+    description: 'Remove one text from the scratch index, or everything when no name is given.',
+    inputSchema: z.object({
+      name: z.string().optional().describe('Text to remove (default: all)')
+    })
+  },
+  async ({ name }) => {
+    const index = await getScratch();
+    if (name === undefined) {
+      await index.clear();
+    } else {
+      await index.remove(name);
+    }
+    return toToolResponse({ sources: await index.sources() });
+  }
+);
+
 async function startServer(options = {}) {
   store.initEnvironment();
   const transport = new StdioServerTransport();
@@ -483,7 +997,26 @@ async function startServer(options = {}) {
   if (!options.autoExit && process.env.OPENCONTEXT_MCP_TEST !== '1') {
     const native = require('../core/native');
     if (native.isAvailable()) {
+      // Purge expired memories now and whenever one expires later on
+      memoryExpiry.start({
+        onPurged: () => searcher?.then((s) => s.refresh()).catch(() => {}),
+      }).catch(() => {});
       native.get().startIndexSync(null).catch(() => {});
+      // Keep [sources] mirrored while the server runs; no-op without sources
+      native.get().startSourceSync().catch(() => {});
+      // Flush (or journal) pending index updates before exiting
+      let stopping = false;
+      const shutdown = () => {
+        if (stopping) process.exit(1);
+        stopping = true;
+        memoryExpiry.stop();
+        native.get().stopSourceSync();
+        native.get().stopIndexSync(null)
+          .catch(() => {})
+          .finally(() => process.exit(0));
+      };
+      process.on('SIGINT', shutdown);
+      process.on('SIGTERM', shutdown);
     }
   }
 
@@ -17,13 +17,41 @@ const {
   removeDoc,
   setDocDescription,
   getDocContent,
-  saveDocContent
+  getBlock,
+  saveDocContent,
+  getDocRevision,
+  mergeDocContent,
+  getDocFields,
+  setDocField,
+  listTasks,
+  setTaskDone,
+  capture,
+  listDates,
+  staleDocs,
+  recordDocHits,
+  previewLinkRewrites,
+  isReadOnly
 } = require('../core/store/index.js');
 const { Searcher, Indexer } = require('../core/search/index.js');
 const { indexSync } = require('../core/search/indexSync');
+const memoryExpiry = require('../core/memory-expiry');
+const native = require('../core/native');
 const config = require('../core/config');
+const acl = require('../core/acl');
 
-async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
+/** API token from `Authorization: Bearer <token>` or `X-OpenContext-Token` */
+function requestToken(req) {
+  const auth = req.get('authorization') || '';
+  const match = auth.match(/^Bearer\s+(.+)$/i);
+  return match ? match[1].trim() : req.get('x-opencontext-token');
+}
+
+/**
+ * HTTP API with a web UI at `/`: `ui` is 'app' for the bundled React app in
+ * dist/ui, 'embedded' for the single-file UI next to this file, or false
+ * for the API alone
+ */
+async function createUiServer({ host = '127.0.0.1', port = 3222, ui = 'app' }) {
   const app = express();
   app.use(cors());
   app.use(express.json({ limit: '25mb' }));
@@ -44,11 +72,50 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
     console.warn('[oc ui] Failed to start index sync:', err.message);
   }
 
+  // Drop memories as they expire, so search stops returning them
+  memoryExpiry.start({ onPurged: refreshSearcher }).catch(() => {});
+
+  // Resolve the caller's folder access once per request
+  app.use('/api', (req, res, next) => {
+    req.access = acl.resolveAccess(requestToken(req));
+    if (!req.access) {
+      return res.status(401).json({ error: 'invalid or missing API token', code: 'UNAUTHORIZED' });
+    }
+    next();
+  });
+
+  // Settings, AI and index maintenance act on the whole workspace: a
+  // folder-scoped token could point the embedding or chat endpoint at a
+  // server of its own and receive everyone's text, or wipe the index
+  const fullAccessRoutes = ['/api/env', '/api/config', '/api/ai/config', '/api/ai/chat', '/api/index/build', '/api/index/clean'];
+  app.use(fullAccessRoutes, (req, res, next) => {
+    if (acl.isFullAccess(req.access)) return next();
+    res.status(403).json({ error: 'this token is limited to some folders', code: 'FORBIDDEN' });
+  });
+
+  const mutatingRoutes = ['/api/folders', '/api/folders/*', '/api/docs', '/api/docs/*', '/api/tasks/*', '/api/capture'];
+
+  // Hidden folders behave as if they did not exist, for writes too
+  app.post(mutatingRoutes, (req, res, next) => {
+    const { path: target, doc_path, folder_path, dest_folder_path } = req.body || {};
+    const denied = [target, doc_path, folder_path, dest_folder_path].find(
+      (p) => typeof p === 'string' && !acl.allows(req.access, p)
+    );
+    if (denied === undefined) return next();
+    res.status(404).json({ error: `Document or folder not found: ${denied}` });
+  });
+
+  // Read-only workspaces reject every folder/doc mutation up front
+  app.post(mutatingRoutes, (req, res, next) => {
+    if (!isReadOnly()) return next();
+    res.status(403).json({ error: 'workspace is read-only', code: 'READ_ONLY' });
+  });
+
   // Folders
   app.get('/api/folders', (req, res) => {
     try {
       const folders = listFolders({ all: req.query.all === 'true' });
-      res.json(folders);
+      res.json(acl.filterFolders(req.access, folders));
     } catch (error) {
       res.status(500).json({ error: error.message });
     }
@@ -98,8 +165,11 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
   app.get('/api/docs', (req, res) => {
     try {
       const folderPath = req.query.folder || '';
+      if (!acl.showsFolder(req.access, folderPath)) {
+        return res.status(404).json({ error: `Folder not found: ${folderPath}` });
+      }
       const docs = listDocs({ folderPath, recursive: req.query.recursive === 'true' });
-      res.json(docs);
+      res.json(acl.filterDocs(req.access, docs));
     } catch (error) {
       res.status(400).json({ error: error.message });
     }
@@ -111,6 +181,7 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
     try {
       const { stableId } = req.params || {};
       const doc = getDocByStableId(stableId);
+      acl.assertAllowed(req.access, doc.rel_path);
       res.json({
         stable_id: doc.stable_id,
         rel_path: doc.rel_path,
@@ -132,6 +203,7 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
       if (!docPath) {
         return res.status(400).json({ error: 'Missing "path" query parameter' });
       }
+      acl.assertAllowed(req.access, docPath);
       const doc = getDocMeta({ docPath });
       res.json({
         stable_id: doc.stable_id,
@@ -193,8 +265,152 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
       if (!docPath) {
         return res.status(400).json({ error: 'Missing "path" query parameter' });
       }
+      acl.assertAllowed(req.access, docPath);
       const content = getDocContent(docPath);
-      res.json({ content });
+      recordDocHits([docPath]);
+      res.json({ content, revision: getDocRevision(docPath) });
+    } catch (error) {
+      if (error.code === 'ACCESS_DENIED') return res.status(404).json({ error: error.message });
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  // Sanitized HTML for previews; links lead to `?doc=<path>`
+  app.get('/api/docs/render', (req, res) => {
+    try {
+      const docPath = req.query.path;
+      if (!docPath) {
+        return res.status(400).json({ error: 'Missing "path" query parameter' });
+      }
+      acl.assertAllowed(req.access, docPath);
+      const rendered = native.get().renderDoc(docPath, acl.searchFolders(req.access) || null);
+      recordDocHits([docPath]);
+      res.json(rendered);
+    } catch (error) {
+      if (error.code === 'ACCESS_DENIED') return res.status(404).json({ error: error.message });
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  app.get('/api/docs/block', (req, res) => {
+    try {
+      const { path: docPath, id } = req.query;
+      if (!docPath || !id) {
+        return res.status(400).json({ error: 'Missing "path" or "id" query parameter' });
+      }
+      acl.assertAllowed(req.access, docPath);
+      res.json(getBlock(docPath, id));
+    } catch (error) {
+      if (error.code === 'ACCESS_DENIED') return res.status(404).json({ error: error.message });
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  app.get('/api/docs/fields', (req, res) => {
+    try {
+      const docPath = req.query.path;
+      if (!docPath) {
+        return res.status(400).json({ error: 'Missing "path" query parameter' });
+      }
+      acl.assertAllowed(req.access, docPath);
+      res.json({ fields: getDocFields(docPath) });
+    } catch (error) {
+      if (error.code === 'ACCESS_DENIED') return res.status(404).json({ error: error.message });
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  app.post('/api/docs/field', (req, res) => {
+    try {
+      const { path: docPath, key, value } = req.body || {};
+      if (!docPath || !key) {
+        return res.status(400).json({ error: 'Missing path or key' });
+      }
+      res.json(setDocField({ docPath, key, value }));
+    } catch (error) {
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  // Checkbox tasks; ?status=open|done|all&folder=&due_by=&limit=
+  app.get('/api/tasks', (req, res) => {
+    try {
+      const { status = 'open', folder, due_by: dueBy, limit } = req.query;
+      const tasks = listTasks({
+        folder: folder || undefined,
+        done: status === 'all' ? undefined : status === 'done',
+        dueBy: dueBy || undefined,
+        limit: limit ? Number(limit) : undefined,
+      });
+      res.json({ tasks: acl.filterDocs(req.access, tasks) });
+    } catch (error) {
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  app.post('/api/tasks/done', (req, res) => {
+    try {
+      const { path: docPath, line, done = true } = req.body || {};
+      if (!docPath || !Number.isInteger(line)) {
+        return res.status(400).json({ error: 'Missing path or line' });
+      }
+      res.json(setTaskDone({ docPath, line, done: Boolean(done) }));
+    } catch (error) {
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  // Quick capture into today's inbox note; { text, source?, url? }
+  app.post('/api/capture', (req, res) => {
+    try {
+      const { text, source, url } = req.body || {};
+      if (!text) return res.status(400).json({ error: 'Missing text' });
+      if (!acl.allows(req.access, 'inbox')) {
+        return res.status(404).json({ error: 'Document or folder not found: inbox' });
+      }
+      res.json(capture({ text, source, url }));
+    } catch (error) {
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  // Links a rename/move would rewrite, for confirming before restructuring
+  app.get('/api/links/rewrites', (req, res) => {
+    try {
+      const { old_path: oldPath, new_path: newPath } = req.query;
+      const rewrites = previewLinkRewrites({ oldPath, newPath });
+      res.json({ rewrites: acl.filterDocs(req.access, rewrites) });
+    } catch (error) {
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  // Documents ranked by staleness; ?folder=&min_score=&limit=
+  app.get('/api/review/stale', (req, res) => {
+    try {
+      const { folder, min_score: minScore, limit } = req.query;
+      const docs = staleDocs({
+        folder: folder || undefined,
+        minScore: minScore ? Number(minScore) : undefined,
+        limit: limit ? Number(limit) : undefined,
+      });
+      res.json({ docs: acl.filterDocs(req.access, docs) });
+    } catch (error) {
+      res.status(400).json({ error: error.message });
+    }
+  });
+
+  // Dates mentioned in docs; ?range=2024-Q3&folder=&source=&limit=
+  app.get('/api/timeline', (req, res) => {
+    try {
+      const { range, folder, source, limit } = req.query;
+      const dates = listDates({
+        range: range || undefined,
+        folder: folder || undefined,
+        source: source || undefined,
+        limit: limit ? Number(limit) : undefined,
+      });
+      res.json({ dates: acl.filterDocs(req.access, dates) });
     } catch (error) {
       res.status(400).json({ error: error.message });
     }
@@ -202,13 +418,29 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
 
   app.post('/api/docs/save', (req, res) => {
     try {
-      const { path: docPath, content, description } = req.body || {};
+      const { path: docPath, content, description, revision } = req.body || {};
       if (!docPath || typeof content !== 'string') {
         return res.status(400).json({ error: 'Missing path or content' });
       }
-      const result = saveDocContent({ docPath, content, description });
+      const result = saveDocContent({ docPath, content, description, expectedRevision: revision });
       res.json(result);
     } catch (error) {
+      if (error.code === 'CONFLICT') {
+        // Hand back the current file, plus a merge proposal when the client sent its base
+        const { path: docPath, content, base } = req.body;
+        const current = getDocContent(docPath);
+        const merge = typeof base === 'string'
+          ? mergeDocContent({ base, ours: content, theirs: current })
+          : null;
+        return res.status(409).json({
+          error: error.message,
+          code: 'CONFLICT',
+          revision: getDocRevision(docPath),
+          content: current,
+          merged: merge?.merged,
+          conflicts: merge?.conflicts,
+        });
+      }
       res.status(400).json({ error: error.message });
     }
   });
@@ -250,7 +482,8 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
         api_key_masked: apiKeyMasked,
         has_api_key: !!apiKey && apiKey.length > 0,
         config_path: config.getConfigPath(),
-        dimensions: 1536
+        dimensions: 1536,
+        read_only: isReadOnly()
       });
     } catch (error) {
       res.status(500).json({ error: error.message });
@@ -529,16 +762,28 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
     try {
       const indexer = await getIndexer();
       const result = await indexer.buildIndex({ force: req.body?.force });
+      await refreshSearcher();
       res.json(result);
     } catch (error) {
       res.status(500).json({ error: error.message });
     }
   });
 
+  // Lets the UI show the projected cost and ask before a build
+  app.get('/api/index/estimate', (req, res) => {
+    try {
+      const paths = req.query.paths ? [].concat(req.query.paths) : undefined;
+      res.json(new Indexer().estimateIndexCost(paths));
+    } catch (error) {
+      res.status(500).json({ error: error.message });
+    }
+  });
+
   app.post('/api/index/clean', async (req, res) => {
     try {
       const indexer = await getIndexer();
       await indexer.clean();
+      await refreshSearcher();
       res.json({ success: true });
     } catch (error) {
       res.status(500).json({ error: error.message });
@@ -574,6 +819,80 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
     return searcherInitPromise;
   }
 
+  // Point an open searcher at indexes the indexer replaced; queries keep
+  // being served while it reopens them
+  async function refreshSearcher() {
+    if (!searcher?.initialized) return;
+    try {
+      await searcher.refresh();
+    } catch (err) {
+      console.warn('[oc ui] Search index refresh failed:', err.message);
+    }
+  }
+
+  // Readiness: warm the search indexes in the background so the first query is fast
+  let searchReadiness = { ready: false, warming: true };
+
+  (async () => {
+    try {
+      const engine = await getSearcher();
+      searchReadiness = await engine.warmUp({ pingEmbedding: true });
+    } catch (err) {
+      searchReadiness = { ready: false, error: err.message };
+    }
+  })();
+
+  // Health events from the search engine, shown on /readyz so the UI can
+  // put up a degraded-mode banner. A broken index stays reported until restart.
+  const searchHealth = {};
+  if (native.isAvailable()) {
+    native.get().onEvent?.((message) => {
+      if (message.scope !== 'health') return;
+      const { type, ...details } = message.event;
+      if (type === 'embedding_provider_recovered') {
+        delete searchHealth.embedding_provider_down;
+      } else if (type === 'watcher_overflow') {
+        console.warn(`[oc ui] ${details.subscriber} missed ${details.missed} events, rescanning`);
+      } else {
+        searchHealth[type] = { ...details, at: new Date().toISOString() };
+      }
+    });
+  }
+
+  app.get('/healthz', (req, res) => {
+    res.json({ status: 'ok' });
+  });
+
+  app.get('/readyz', (req, res) => {
+    const degraded = Object.keys(searchHealth).length > 0;
+    res.status(searchReadiness.ready ? 200 : 503).json({ ...searchReadiness, degraded, health: searchHealth });
+  });
+
+  // Cache sizes against the [memory] budget; no document content
+  app.get('/api/debug/memory', async (req, res) => {
+    try {
+      const engine = await getSearcher();
+      res.json(await engine.memoryUsage());
+    } catch (error) {
+      res.status(500).json({ error: error.message });
+    }
+  });
+
+  app.get('/api/search/suggest', async (req, res) => {
+    const prefix = req.query.q || '';
+    const limit = Number(req.query.limit) || 10;
+    // Suggestions come from titles across the whole index
+    if (!prefix.trim() || acl.searchFolders(req.access)) {
+      return res.json({ suggestions: [] });
+    }
+    try {
+      const engine = await getSearcher();
+      res.json({ suggestions: await engine.suggest(prefix, limit) });
+    } catch (error) {
+      res.json({ suggestions: [], error: error.message });
+    }
+  });
+
   app.get('/api/semantic-search', async (req, res) => {
     try {
       const query = req.query.q || '';
@@ -581,34 +900,62 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
       const mode = req.query.mode || 'hybrid'; // hybrid | vector | keyword
       const aggregateBy = req.query.aggregateBy || 'doc'; // content | doc | folder
       const docType = req.query.docType || req.query.doc_type || undefined;
+      const allowedFolders = acl.searchFolders(req.access);
+      const sourceList = (value) => value
+        ? String(value).split(',').map((s) => s.trim()).filter(Boolean)
+        : undefined;
+      const searchOptions = {
+        limit,
+        mode,
+        aggregateBy,
+        docType,
+        filter: req.query.filter || undefined,
+        folderFilter: req.query.folderFilter || undefined,
+        includeSources: sourceList(req.query.includeSources),
+        excludeSources: sourceList(req.query.excludeSources),
+        minScore: req.query.minScore ? Number(req.query.minScore) : undefined,
+        crossLanguage: req.query.crossLanguage ? req.query.crossLanguage === 'true' : undefined,
+        collection: req.query.collection || undefined,
+        folderSummary: req.query.folderSummary === 'true' || undefined,
+        tier: req.query.tier || undefined,
+        sortBy: req.query.sortBy || undefined,
+        sortOrder: req.query.sortOrder || undefined,
+        citation: req.query.citation || undefined,
+        facets: req.query.facets === 'true' || undefined,
+        allowedFolders,
+      };
 
       if (!query.trim()) {
         return res.json({ results: [], query, mode, aggregate_by: aggregateBy });
       }
 
+      // A wrapper of its own, so concurrent queries keep their own confidence
       let searchEngine;
       try {
-        searchEngine = await getSearcher();
+        searchEngine = await (await getSearcher()).share();
       } catch (initErr) {
+        // Coded errors other than a missing index (e.g. API_KEY_MISSING) are reported as they are
+        if (initErr.code && initErr.code !== 'INDEX_NOT_BUILT') throw initErr;
         // If init fails, return with indexMissing hint
         return res.json({ 
           results: [], 
           query,
           error: 'Search index not built. Run "oc index build" first.',
+          code: 'INDEX_NOT_BUILT',
           indexMissing: true
         });
       }
 
       let results;
       try {
-        results = await searchEngine.search(query, { limit, mode, aggregateBy, docType });
+        results = await searchEngine.search(query, searchOptions);
       } catch (searchErr) {
-        // If search fails (e.g., stale connection), try reinitializing once
+        // If search fails (e.g., stale connection), reopen the indexes and retry once
         if (searchErr.message && searchErr.message.includes('lance error')) {
-          console.log('[oc ui] Search error, trying to reinitialize...');
+          console.log('[oc ui] Search error, refreshing the indexes...');
           try {
-            searchEngine = await getSearcher(true); // Force reinit
-            results = await searchEngine.search(query, { limit, mode, aggregateBy, docType });
+            await searchEngine.refresh();
+            results = await searchEngine.search(query, searchOptions);
           } catch (retryErr) {
             throw retryErr;
           }
@@ -617,57 +964,78 @@ async function createUiServer({ host = '127.0.0.1', port = 3222 }) {
         }
       }
       
+      recordDocHits(results.map((r) => r.file_path || r.filePath));
       res.json({
         query,
         mode,
         aggregate_by: aggregateBy,
         count: results.length,
+        ...searchEngine.lastTotals,
+        confidence: searchEngine.lastConfidence || undefined,
+        diagnostics: searchEngine.lastDiagnostics || undefined,
+        degraded: searchEngine.lastDegraded || undefined,
+        facets: searchEngine.lastFacets || undefined,
+        suggestions: searchEngine.lastSuggestions || undefined,
+        related: searchEngine.lastRelated || undefined,
         results: results.map(r => ({
           score: r.score,
-          file_path: r.filePath,
-          heading_path: r.headingPath || '',
-          section_title: r.sectionTitle || '',
-          line_start: r.lineStart,
-          line_end: r.lineEnd,
+          file_path: r.file_path || r.filePath,
+          heading_path: r.heading_path || r.headingPath || '',
+          section_title: r.section_title || r.sectionTitle || '',
+          line_start: r.line_start || r.lineStart,
+          line_end: r.line_end || r.lineEnd,
+          block_id: r.block_id || r.blockId,
           content: r.content,
-          matched_by: r.matchedBy || r.source,
-          hit_count: r.hitCount,
-          doc_count: r.docCount,
-          display_name: r.displayName,
-          folder_path: r.folderPath,
+          matched_by: r.matched_by || r.matchedBy || r.source,
+          hit_count: r.hit_count || r.hitCount,
+          doc_count: r.doc_count || r.docCount,
+          display_name: r.display_name || r.displayName,
+          folder_path: r.folder_path || r.folderPath,
           doc_type: r.docType || r.doc_type,
           entry_id: r.entryId || r.entry_id,
           entry_date: r.entryDate || r.entry_date,
           entry_created_at: r.entryCreatedAt || r.entry_created_at,
-          idea_box: r.ideaBox || r.idea_box
+          idea_box: r.ideaBox || r.idea_box,
+          also_in: r.also_in,
+          folder_summary: r.folder_summary,
+          doc_meta: r.doc_meta,
+          provenance: r.provenance,
+          trust: r.trust
         }))
       });
     } catch (error) {
       const msg = error.message || 'Search failed';
       // If index not found, return empty results with hint
-      if (msg.includes('index not found') || msg.includes('not found')) {
+      if (error.code === 'INDEX_NOT_BUILT' || (!error.code && msg.includes('not found'))) {
         return res.json({ 
           results: [], 
           query: req.query.q || '',
           error: 'Search index not built. Run "oc index build" first.',
+          code: 'INDEX_NOT_BUILT',
           indexMissing: true
         });
       }
-      res.status(500).json({ error: msg });
+      const status = error.code === 'INVALID_OPTIONS' ? 400 : 500;
+      res.status(status).json({ error: msg, code: error.code, hint: error.hint });
     }
   });
 
-  const distPath = path.resolve(__dirname, '../../dist/ui');
-  if (!fs.existsSync(distPath)) {
-    console.warn('[oc ui] UI assets not found. Have you run "npm run ui:build"?');
-  }
-  app.use(express.static(distPath));
-  app.use((req, res, next) => {
-    if (req.method !== 'GET' || req.path.startsWith('/api/')) {
-      return next();
+  if (ui === 'embedded') {
+    const page = path.join(__dirname, 'embedded.html');
+    app.get(['/', '/index.html'], (req, res) => res.sendFile(page));
+  } else if (ui === 'app') {
+    const distPath = path.resolve(__dirname, '../../dist/ui');
+    if (!fs.existsSync(distPath)) {
+      console.warn('[oc ui] UI assets not found. Have you run "npm run ui:build"?');
     }
-    res.sendFile(path.join(distPath, 'index.html'));
-  });
+    app.use(express.static(distPath));
+    app.use((req, res, next) => {
+      if (req.method !== 'GET' || req.path.startsWith('/api/')) {
+        return next();
+      }
+      res.sendFile(path.join(distPath, 'index.html'));
+    });
+  }
 
   return new Promise((resolve, reject) => {
     const server = app.listen(port, host, () => {
@@ -941,16 +941,17 @@ export async function respondAcpPermission(options) {
  * @param {number} options.limit - Max results (default 10)
  * @param {string} options.mode - Search mode: 'hybrid' | 'vector' | 'keyword' (default 'hybrid')
  * @param {string} options.aggregateBy - Aggregation: 'content' | 'doc' | 'folder' (default 'doc')
+ * @param {string} [options.tier] - Embedding tier: 'fast' | 'accurate' (only with embedding.tiers enabled)
  * @returns {Promise<{query: string, results: Array, count: number, error?: string, indexMissing?: boolean}>}
  */
 export async function semanticSearch(query, options = {}) {
-  const { limit = 10, mode = 'hybrid', aggregateBy = 'doc', docType } = options;
+  const { limit = 10, mode = 'hybrid', aggregateBy = 'doc', docType, tier } = options;
   
   const invoke = await getInvoke();
   if (invoke) {
     try {
       return await invoke('semantic_search', { 
-        options: { query, limit, mode, aggregateBy, docType } 
+        options: { query, limit, mode, aggregateBy, docType, tier } 
       });
     } catch (e) {
       console.warn('semantic_search not available in Tauri, falling back to HTTP:', e);
@@ -964,5 +965,6 @@ export async function semanticSearch(query, options = {}) {
     aggregateBy
   });
   if (docType) params.set('docType', String(docType));
+  if (tier) params.set('tier', String(tier));
   return fetchJSON(`${API_BASE}/api/semantic-search?${params}`);
 }
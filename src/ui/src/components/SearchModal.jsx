@@ -243,6 +243,7 @@ export function SearchModal({ isOpen, onClose, onSelectDoc, onSelectIdea }) {
             mode: 'hybrid',
             aggregateBy: 'doc',
             docType: 'doc',
+            tier: 'fast',
           });
         }
 
@@ -252,6 +253,7 @@ export function SearchModal({ isOpen, onClose, onSelectDoc, onSelectIdea }) {
             mode: 'hybrid',
             aggregateBy: 'content',
             docType: 'idea',
+            tier: 'fast',
           });
         }
 
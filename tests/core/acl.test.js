@@ -0,0 +1,23 @@
+/**
+ * ACL Module Tests
+ */
+
+const { describe, it, assert } = require('../helpers');
+const acl = require('../../src/core/acl');
+
+describe('ACL Module', async () => {
+  const scoped = { kind: 'folders', folders: ['allowed'] };
+
+  it('should allow paths inside a scoped folder', () => {
+    assert.strictEqual(acl.allows(scoped, 'allowed/x'), true);
+    assert.strictEqual(acl.allows(scoped, '/allowed/./x'), true);
+    assert.strictEqual(acl.allows(scoped, 'allowed-old/x'), false);
+  });
+
+  it('should refuse a scoped token a path that climbs out of its folder', () => {
+    assert.strictEqual(acl.allows(scoped, 'allowed/../denied/x'), false);
+    assert.strictEqual(acl.allows(scoped, 'allowed\\..\\denied\\x'), false);
+    assert.strictEqual(acl.showsFolder(scoped, 'allowed/../denied'), false);
+    assert.throws(() => acl.assertAllowed(scoped, 'allowed/../denied/x'), acl.AccessDeniedError);
+  });
+});
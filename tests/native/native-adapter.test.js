@@ -9,7 +9,8 @@ const {
   isNativeAvailable, 
   getNativeError, 
   NativeSearcher, 
-  NativeIndexer 
+  NativeIndexer,
+  decodeNativeError
 } = require('../../src/core/search/native-adapter');
 
 // Check if API key is available (needed for search/index tests)
@@ -34,6 +35,21 @@ describe('Native Adapter', async () => {
     });
   });
 
+  describe('decodeNativeError()', () => {
+    it('should split out the error code and hint', () => {
+      const err = decodeNativeError(new Error(
+        '[API_KEY_MISSING] API key not configured.\nHint: Run `oc config set EMBEDDING_API_KEY <key>`'
+      ));
+      assert.strictEqual(err.code, 'API_KEY_MISSING');
+      assert.strictEqual(err.message, 'API key not configured.');
+      assert.strictEqual(err.hint, 'Run `oc config set EMBEDDING_API_KEY <key>`');
+
+      const plain = decodeNativeError(new Error('something else'));
+      assert.strictEqual(plain.message, 'something else');
+      assert.strictEqual(plain.hint, undefined);
+    });
+  });
+
   // Skip native tests if native bindings are not available
   const nativeAvailable = isNativeAvailable();
   // Skip tests that require API key
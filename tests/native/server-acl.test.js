@@ -0,0 +1,98 @@
+/**
+ * Access control of the HTTP API: folder-scoped tokens may not touch
+ * workspace-wide settings, AI configuration or the index
+ */
+
+const { describe, it, before, after, assert, createTempDir, cleanupTempDir } = require('../helpers');
+const fs = require('fs');
+const path = require('path');
+const { isNativeAvailable } = require('../../src/core/search/native-adapter');
+
+const nativeAvailable = isNativeAvailable();
+
+describe('HTTP API access control', { skip: !nativeAvailable }, async () => {
+  let tempDir;
+  let server;
+  let url;
+  let originalEnv;
+
+  before(async () => {
+    tempDir = createTempDir();
+    originalEnv = {
+      OPENCONTEXT_ROOT: process.env.OPENCONTEXT_ROOT,
+      OPENCONTEXT_CONTEXTS_ROOT: process.env.OPENCONTEXT_CONTEXTS_ROOT,
+      OPENCONTEXT_DB_PATH: process.env.OPENCONTEXT_DB_PATH,
+    };
+    process.env.OPENCONTEXT_ROOT = tempDir;
+    process.env.OPENCONTEXT_CONTEXTS_ROOT = path.join(tempDir, 'contexts');
+    process.env.OPENCONTEXT_DB_PATH = path.join(tempDir, 'test.db');
+    fs.writeFileSync(
+      path.join(tempDir, 'acl.json'),
+      JSON.stringify({
+        rules: [
+          { token: 'admin-secret', folders: [''] },
+          { token: 'team-secret', folders: ['team'] },
+        ],
+      })
+    );
+
+    const { createUiServer } = require('../../src/ui/server');
+    server = await createUiServer({ port: 0, ui: false });
+    url = `http://127.0.0.1:${server.address().port}`;
+  });
+
+  after(async () => {
+    server?.close();
+    await require('../../src/core/search/indexSync').indexSync.stop().catch(() => {});
+    for (const [key, value] of Object.entries(originalEnv)) {
+      if (value === undefined) {
+        delete process.env[key];
+      } else {
+        process.env[key] = value;
+      }
+    }
+    cleanupTempDir(tempDir);
+  });
+
+  const request = (method, route, token) =>
+    fetch(url + route, {
+      method,
+      headers: { Authorization: `Bearer ${token}`, 'Content-Type': 'application/json' },
+      body: method === 'POST' ? JSON.stringify({ apiBase: 'https://attacker.example/v1' }) : undefined,
+    });
+
+  const routes = [
+    ['GET', '/api/env'],
+    ['POST', '/api/config'],
+    ['GET', '/api/ai/config'],
+    ['POST', '/api/ai/config'],
+    ['POST', '/api/ai/chat'],
+    ['POST', '/api/index/build'],
+    ['POST', '/api/index/clean'],
+  ];
+
+  for (const [method, route] of routes) {
+    it(`should refuse ${method} ${route} to a folder-scoped token`, async () => {
+      const res = await request(method, route, 'team-secret');
+      assert.strictEqual(res.status, 403);
+      assert.strictEqual((await res.json()).code, 'FORBIDDEN');
+    });
+  }
+
+  it('should leave settings unchanged after a refused write', async () => {
+    await request('POST', '/api/config', 'team-secret');
+    const res = await request('GET', '/api/env', 'admin-secret');
+    assert.strictEqual(res.status, 200);
+    assert.notStrictEqual((await res.json()).embedding_api_base, 'https://attacker.example/v1');
+  });
+
+  it('should refuse a scoped token a path that climbs out of its folder', async () => {
+    const res = await fetch(`${url}/api/folders`, {
+      method: 'POST',
+      headers: { Authorization: 'Bearer team-secret', 'Content-Type': 'application/json' },
+      body: JSON.stringify({ path: 'team/../denied/x' }),
+    });
+    assert.strictEqual(res.status, 404);
+    assert.ok(!fs.existsSync(path.join(tempDir, 'contexts', 'denied')));
+  });
+});
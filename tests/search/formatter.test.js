@@ -3,7 +3,7 @@
  */
 
 const { describe, it, assert } = require('../helpers');
-const { normalizeResult, normalizeResults, formatPlain, formatJson } = require('../../src/core/search/formatter');
+const { normalizeResult, normalizeResults, totalsOf, formatPlain, formatJson } = require('../../src/core/search/formatter');
 
 describe('Search Formatter', async () => {
   
@@ -123,6 +123,22 @@ describe('Search Formatter', async () => {
       assert.ok(output.includes('[vector]'));
     });
 
+    it('should show document stats on doc-level results', () => {
+      const results = [
+        {
+          score: 0.8,
+          file_path: 'project/spec.md',
+          hit_count: 2,
+          matched_by: 'vector',
+          doc_meta: { words: 1240, chunks: 9, reading_minutes: 7, modified: 1760000000000 },
+        }
+      ];
+
+      const output = formatPlain('spec', results, { aggregateBy: 'doc' });
+
+      assert.ok(output.includes('2 matches, 1240 words, 7 min read'));
+    });
+
     it('should format folder-level results', () => {
       const results = [
         {
@@ -142,6 +158,50 @@ describe('Search Formatter', async () => {
       assert.ok(output.includes('[keyword]'));
     });
 
+    it('should show folder summaries', () => {
+      const results = [
+        {
+          score: 0.6,
+          folder_path: 'plans',
+          doc_count: 2,
+          hit_count: 3,
+          matched_by: 'vector',
+          folder_summary: {
+            docs: ['plans/q3.md', 'plans/q4.md'],
+            doc_total: 5,
+            headings: ['Goals', 'Goals > Hiring'],
+            summary: 'Quarterly plans.',
+          },
+        }
+      ];
+
+      const output = formatPlain('plans', results, { aggregateBy: 'folder' });
+
+      assert.ok(output.includes('   Quarterly plans.\n'));
+      assert.ok(output.includes('Headings: Goals; Goals > Hiring'));
+      assert.ok(output.includes('Docs: plans/q3.md, plans/q4.md, +3 more'));
+    });
+
+    it('should label federated results with their workspace', () => {
+      const results = [
+        { score: 1, file_path: 'plans/q3.md', content: 'Q3 plan', matched_by: 'vector', workspace: 'work' }
+      ];
+
+      const output = formatPlain('plan', results, { aggregateBy: 'content' });
+
+      assert.ok(output.includes('📄 work: plans/q3.md'));
+    });
+
+    it('should list other paths of merged duplicates', () => {
+      const results = [
+        { score: 0.8, file_path: 'plans/q3.md', hit_count: 2, matched_by: 'vector', also_in: ['old/q3-copy.md'] }
+      ];
+
+      const output = formatPlain('q3', results, { aggregateBy: 'doc' });
+
+      assert.ok(output.includes('also in: old/q3-copy.md'));
+    });
+
     it('should truncate long content', () => {
       const longContent = 'x'.repeat(500);
       const results = [
@@ -180,6 +240,50 @@ describe('Search Formatter', async () => {
       assert.strictEqual(output.results[0].file_path, 'test.md');
       assert.strictEqual(output.results[0].matched_by, 'vector');
     });
+
+    it('should flag a keyword fallback', () => {
+      const results = [{ score: 0.5, file_path: 'test.md', matched_by: 'keyword' }];
+
+      assert.strictEqual(formatJson('q', results, { degraded: true }).degraded, true);
+      assert.ok(!('degraded' in formatJson('q', results, { degraded: false })));
+      assert.ok(formatPlain('q', results, { degraded: true }).includes('keyword results only'));
+    });
+
+    it('should include facet counts', () => {
+      const results = [{ score: 0.5, file_path: 'projects/a.md', matched_by: 'keyword' }];
+      const facets = { folder: [{ value: 'projects', count: 3 }], doc_type: [], tag: [], month: [] };
+
+      assert.deepStrictEqual(formatJson('q', results, { facets }).facets, facets);
+      assert.ok(formatPlain('q', results, { facets }).includes('folder: projects (3)'));
+      assert.ok(!formatPlain('q', results, { facets }).includes('tag:'));
+    });
+
+    it('should report totals when the limit cut results off', () => {
+      const results = [{ score: 0.5, file_path: 'test.md', matched_by: 'keyword' }];
+      const totals = totalsOf({ total_candidates: 40, total_exact: false, has_more: true });
+
+      const output = formatJson('q', results, { totals });
+      assert.strictEqual(output.total_candidates, 40);
+      assert.strictEqual(output.has_more, true);
+      assert.ok(formatPlain('q', results, { totals }).includes('Found 1 of ~40 results'));
+      assert.strictEqual(totalsOf({ results: [] }), null);
+    });
+
+    it('should offer spelling suggestions', () => {
+      const suggestions = ['kubernetes'];
+
+      assert.deepStrictEqual(formatJson('kubernets', [], { suggestions }).suggestions, suggestions);
+      assert.ok(!('suggestions' in formatJson('q', [], { suggestions: [] })));
+      assert.ok(formatPlain('kubernets', [], { suggestions }).includes('Did you mean: "kubernetes"?'));
+    });
+
+    it('should list related queries', () => {
+      const results = [{ score: 0.5, file_path: 'test.md', matched_by: 'keyword' }];
+      const related = ['auth tokens', 'auth oauth'];
+
+      assert.deepStrictEqual(formatJson('auth', results, { related }).related, related);
+      assert.ok(formatPlain('auth', results, { related }).includes('Related: "auth tokens", "auth oauth"'));
+    });
   });
 });
 
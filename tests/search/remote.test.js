@@ -0,0 +1,82 @@
+/**
+ * RemoteSearcher Tests
+ */
+
+const { describe, it, before, after, assert } = require('../helpers');
+const http = require('http');
+const { RemoteSearcher } = require('../../src/core/search/remote');
+
+describe('RemoteSearcher', async () => {
+  let server;
+  let url;
+  const requests = [];
+
+  before(async () => {
+    server = http.createServer((req, res) => {
+      const { pathname, searchParams } = new URL(req.url, 'http://localhost');
+      requests.push({ pathname, params: Object.fromEntries(searchParams), auth: req.headers.authorization });
+      res.setHeader('Content-Type', 'application/json');
+      if (req.headers.authorization !== 'Bearer secret') {
+        res.statusCode = 401;
+        return res.end(JSON.stringify({ error: 'invalid or missing API token', code: 'UNAUTHORIZED' }));
+      }
+      if (pathname === '/api/semantic-search') {
+        return res.end(JSON.stringify({
+          query: searchParams.get('q'),
+          results: [{ score: 0.9, file_path: 'notes/home.md', content: 'Home server setup', matched_by: 'vector' }],
+        }));
+      }
+      if (pathname === '/api/search/suggest') {
+        return res.end(JSON.stringify({ suggestions: [{ text: 'home server', kind: 'title', count: 1 }] }));
+      }
+      if (pathname === '/readyz') {
+        res.statusCode = 503;
+        return res.end(JSON.stringify({ ready: false, index_exists: false }));
+      }
+      res.statusCode = 404;
+      res.end(JSON.stringify({ error: 'Document or folder not found: missing.md' }));
+    });
+    await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve));
+    url = `http://127.0.0.1:${server.address().port}/`;
+  });
+
+  after(() => {
+    server.close();
+  });
+
+  it('should search with the token and normalize results', async () => {
+    const searcher = new RemoteSearcher({ url, token: 'secret' });
+    const results = await searcher.search('home', { limit: 3, folderFilter: 'notes' });
+
+    assert.strictEqual(results.length, 1);
+    assert.strictEqual(results[0].file_path, 'notes/home.md');
+    const request = requests.at(-1);
+    assert.strictEqual(request.pathname, '/api/semantic-search');
+    assert.deepStrictEqual(
+      { q: request.params.q, limit: request.params.limit, folderFilter: request.params.folderFilter },
+      { q: 'home', limit: '3', folderFilter: 'notes' }
+    );
+    assert.ok(!('docType' in request.params), 'unset options are not sent');
+  });
+
+  it('should return suggestions and readiness', async () => {
+    const searcher = new RemoteSearcher({ url, token: 'secret' });
+    assert.strictEqual((await searcher.suggest('ho'))[0].text, 'home server');
+    assert.strictEqual((await searcher.warmUp()).ready, false);
+  });
+
+  it('should surface server errors', async () => {
+    await assert.rejects(
+      new RemoteSearcher({ url }).search('home'),
+      /invalid or missing API token/
+    );
+    await assert.rejects(
+      new RemoteSearcher({ url, token: 'secret' }).getDocContent('missing.md'),
+      /not found: missing\.md/
+    );
+    await assert.rejects(
+      new RemoteSearcher({ url: 'http://127.0.0.1:1' }).search('home'),
+      /unreachable/
+    );
+  });
+});